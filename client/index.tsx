@@ -14,6 +14,11 @@ function App() {
   // ref 用于保存Treemap实例
   const treeMapRef = useRef(null);
   const [chartData, setChartData] = useState('');
+  // treemap data generated by mako, one group per chunk
+  const [treemapData, setTreemapData] = useState(null);
+  const [sizeType, setSizeType] = useState<'stat' | 'parsed' | 'gzip'>(
+    'stat',
+  );
 
   // toolTip展示使用
   const [tooltipContent, setToolTipContent] = useState('');
@@ -68,12 +73,13 @@ function App() {
       return filteredModules;
     }, []);
   };
-  const renderModuleSize = (module, sizeType) => {
-    const sizeProp = `${sizeType}Size`;
+  const sizeLabels = { stat: 'Stat', parsed: 'Parsed', gzip: 'Gzipped' };
+  const renderModuleSize = (module, type) => {
+    const sizeProp = `${type}Size`;
     const size = module[sizeProp];
-    const sizeLabel = 'Size';
+    const sizeLabel = sizeLabels[type];
     return typeof size === 'number' ? (
-      <div className={s.activeSize}>
+      <div className={type === sizeType ? s.activeSize : undefined}>
         {sizeLabel} size: <strong>{filesize(size)}</strong>
       </div>
     ) : null;
@@ -90,6 +96,8 @@ function App() {
         <br />
 
         {renderModuleSize(module, 'stat')}
+        {renderModuleSize(module, 'parsed')}
+        {renderModuleSize(module, 'gzip')}
         {module.path && (
           <div>
             Path: <strong>{module.path}</strong>
@@ -107,8 +115,8 @@ function App() {
     );
   };
   const createFoamTree = (chartData: any) => {
-    const formatData = format(chartData?.chunkModules || []);
-    const resData = filterModulesForSize(formatData, 'statSize');
+    const formatData = treemapData || format(chartData?.chunkModules || []);
+    const resData = filterModulesForSize(formatData, `${sizeType}Size`);
     return new FoamTree({
       element: chartRef.current,
       layout: 'squarified',
@@ -160,6 +168,7 @@ function App() {
   };
   useEffect(() => {
     window.addEventListener('load', () => {
+      setTreemapData(window?.treemapData || null);
       setChartData(window?.chartData);
     });
     window.addEventListener('resize', resize);
@@ -172,10 +181,25 @@ function App() {
       console.warn('数据未初始化!!');
       return;
     }
+    treeMapRef.current?.dispose?.();
     treeMapRef.current = createFoamTree(chartData);
-  }, [chartData]);
+  }, [chartData, treemapData, sizeType]);
   return (
     <>
+      {treemapData && (
+        <div style={{ position: 'fixed', top: 8, right: 8, zIndex: 1 }}>
+          <select
+            value={sizeType}
+            onChange={(e) => setSizeType(e.target.value as any)}
+          >
+            {Object.entries(sizeLabels).map(([type, label]) => (
+              <option key={type} value={type}>
+                {label}
+              </option>
+            ))}
+          </select>
+        </div>
+      )}
       <div style={{ width: '100vw', height: '100vh' }} ref={chartRef}></div>
       <Tooltip visible={visible} content={tooltipContent} />
     </>
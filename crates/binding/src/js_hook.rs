@@ -5,12 +5,26 @@ use serde_json::Value;
 
 use crate::threadsafe_function::ThreadsafeFunction;
 
+/// A pattern of the include and exclude filters of plugins, either a glob of the strings or the
+/// source of a RegExp
+#[napi(object)]
+pub struct JsFilterPattern {
+    pub glob: Option<String>,
+    pub regex: Option<String>,
+}
+
 #[napi(object)]
 pub struct JsHooks {
     pub name: Option<String>,
+    #[napi(ts_type = "'pre' | 'post'")]
     pub enforce: Option<String>,
+    pub include: Option<Vec<JsFilterPattern>>,
+    pub exclude: Option<Vec<JsFilterPattern>>,
+    /// The files read by the whole build, e.g. the tailwind config, all the modules are rebuilt
+    /// when they change in watch mode
+    pub build_dependencies: Option<Vec<String>>,
     #[napi(
-        ts_type = "(filePath: string) => Promise<{ content: string, type: 'css'|'js' } | void> | void;"
+        ts_type = "(filePath: string) => Promise<{ content: string, type: 'css'|'js', dependencies?: string[], contextDependencies?: string[] } | void> | void;"
     )]
     pub load: Option<JsFunction>,
     #[napi(ts_type = "(filePath: string) => Promise<bool> | bool;")]
@@ -59,6 +73,32 @@ pub struct JsHooks {
     pub generate_end: Option<JsFunction>,
     #[napi(ts_type = "() => Promise<void>;")]
     pub write_bundle: Option<JsFunction>,
+    #[napi(
+        ts_type = "(stats: { startTime: number; endTime: number; [key: string]: any }) => Promise<void> | void;"
+    )]
+    pub done: Option<JsFunction>,
+    #[napi(
+        ts_type = "(sourceMaps: { chunk: string; sourceMap: string; debugId?: string }[]) => Promise<void>;"
+    )]
+    pub source_maps_emitted: Option<JsFunction>,
+    #[napi(ts_type = r#"(delta: {
+    changedFiles: string[];
+    addedModules: string[];
+    removedModules: string[];
+    modifiedModules: string[];
+    updatedChunks: string[];
+    time: number;
+    errors: string[];
+  }) => Promise<void> | void;"#)]
+    pub rebuild_end: Option<JsFunction>,
+    #[napi(ts_type = r#"(module: {
+    path: string;
+    sourceSize: number;
+    size: number;
+    durations: { load: number; parse: number; transform: number; analyzeDeps: number };
+    dependencies: number;
+  }) => Promise<void> | void;"#)]
+    pub module_built: Option<JsFunction>,
     #[napi(
         ts_type = "(id: string, change: { event: 'create' | 'delete' | 'update' }) => Promise<void> | void;"
     )]
@@ -74,7 +114,7 @@ pub struct JsHooks {
     )]
     pub resolve_id: Option<JsFunction>,
     #[napi(
-        ts_type = "(content: { content: string, type: 'css' | 'js' }, path: string) => Promise<{ content: string, type: 'css' | 'js' } | void> | void;"
+        ts_type = "(content: { content: string, type: 'css' | 'js' }, path: string) => Promise<{ content: string, type: 'css' | 'js', dependencies?: string[], contextDependencies?: string[] } | void> | void;"
     )]
     pub transform: Option<JsFunction>,
     #[napi(ts_type = "(filePath: string) => Promise<bool> | bool;")]
@@ -85,6 +125,10 @@ pub struct TsFnHooks {
     pub build_start: Option<ThreadsafeFunction<(), ()>>,
     pub build_end: Option<ThreadsafeFunction<(), ()>>,
     pub write_bundle: Option<ThreadsafeFunction<(), ()>>,
+    pub done: Option<ThreadsafeFunction<Value, ()>>,
+    pub source_maps_emitted: Option<ThreadsafeFunction<Value, ()>>,
+    pub rebuild_end: Option<ThreadsafeFunction<Value, ()>>,
+    pub module_built: Option<ThreadsafeFunction<Value, ()>>,
     pub generate_end: Option<ThreadsafeFunction<Value, ()>>,
     pub load: Option<ThreadsafeFunction<String, Option<LoadResult>>>,
     pub load_include: Option<ThreadsafeFunction<String, Option<bool>>>,
@@ -108,6 +152,18 @@ impl TsFnHooks {
             write_bundle: hooks.write_bundle.as_ref().map(|hook| unsafe {
                 ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
             }),
+            done: hooks.done.as_ref().map(|hook| unsafe {
+                ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
+            }),
+            source_maps_emitted: hooks.source_maps_emitted.as_ref().map(|hook| unsafe {
+                ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
+            }),
+            rebuild_end: hooks.rebuild_end.as_ref().map(|hook| unsafe {
+                ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
+            }),
+            module_built: hooks.module_built.as_ref().map(|hook| unsafe {
+                ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
+            }),
             generate_end: hooks.generate_end.as_ref().map(|hook| unsafe {
                 ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
             }),
@@ -148,6 +204,10 @@ pub struct LoadResult {
     pub content: String,
     #[napi(js_name = "type")]
     pub content_type: String,
+    /// the files read by the loader, e.g. the sass partials
+    pub dependencies: Option<Vec<String>>,
+    /// the dirs scanned by the loader, e.g. the dirs of the glob imports
+    pub context_dependencies: Option<Vec<String>>,
 }
 
 #[napi(object, use_nullable = true)]
@@ -171,4 +231,6 @@ pub struct TransformResult {
     pub content: String,
     #[napi(js_name = "type")]
     pub content_type: String,
+    pub dependencies: Option<Vec<String>>,
+    pub context_dependencies: Option<Vec<String>>,
 }
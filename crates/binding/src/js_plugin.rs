@@ -1,11 +1,15 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
 use mako::ast::file::{Content, JsContent};
 use mako::compiler::Context;
-use mako::plugin::{Plugin, PluginGenerateEndParams, PluginLoadParam, PluginResolveIdParams};
+use mako::plugin::{
+    Plugin, PluginContext, PluginEmittedSourceMap, PluginFilter, PluginGenerateEndParams,
+    PluginLoadParam, PluginModuleBuiltParams, PluginRebuildEndParams, PluginResolveIdParams,
+};
 use mako::resolve::{ExternalResource, Resolution, ResolvedResource, ResolverResource};
+use mako::stats::StatsJsonMap;
 
 use crate::js_hook::{
     LoadResult, ResolveIdParams, ResolveIdResult, TransformResult, TsFnHooks, WatchChangesParams,
@@ -27,10 +31,35 @@ fn content_from_result(result: TransformResult) -> Result<Content> {
     }
 }
 
+// the paths are relative to the root or absolute
+fn dependency_paths(dependencies: Option<Vec<String>>, context: &Arc<Context>) -> Vec<PathBuf> {
+    dependencies
+        .unwrap_or_default()
+        .into_iter()
+        .map(|path| context.root.join(path))
+        .collect()
+}
+
+fn add_watch_paths(
+    plugin_context: &PluginContext,
+    dependencies: Option<Vec<String>>,
+    context_dependencies: Option<Vec<String>>,
+    context: &Arc<Context>,
+) {
+    for dependency in dependency_paths(dependencies, context) {
+        plugin_context.add_watch_file(dependency);
+    }
+    for dependency in dependency_paths(context_dependencies, context) {
+        plugin_context.add_watch_dir(dependency);
+    }
+}
+
 pub struct JsPlugin {
     pub hooks: TsFnHooks,
     pub name: Option<String>,
     pub enforce: Option<String>,
+    pub filter: Option<PluginFilter>,
+    pub build_dependencies: Option<Vec<String>>,
 }
 
 impl Plugin for JsPlugin {
@@ -42,14 +71,22 @@ impl Plugin for JsPlugin {
         self.enforce.as_deref()
     }
 
-    fn build_start(&self, _context: &Arc<Context>) -> Result<()> {
+    fn filter(&self) -> Option<&PluginFilter> {
+        self.filter.as_ref()
+    }
+
+    fn build_start(&self, context: &Arc<Context>) -> Result<()> {
+        let plugin_context = PluginContext::new(self.name(), context);
+        for dependency in dependency_paths(self.build_dependencies.clone(), context) {
+            plugin_context.add_watch_file(dependency);
+        }
         if let Some(hook) = &self.hooks.build_start {
             hook.call(())?
         }
         Ok(())
     }
 
-    fn load(&self, param: &PluginLoadParam, _context: &Arc<Context>) -> Result<Option<Content>> {
+    fn load(&self, param: &PluginLoadParam, context: &Arc<Context>) -> Result<Option<Content>> {
         if let Some(hook) = &self.hooks.load {
             if self.hooks.load_include.is_some()
                 && self
@@ -64,9 +101,19 @@ impl Plugin for JsPlugin {
             }
             let x: Option<LoadResult> = hook.call(param.file.path.to_string_lossy().to_string())?;
             if let Some(x) = x {
+                let plugin_context =
+                    PluginContext::new(self.name(), context).with_module(&param.file.path);
+                add_watch_paths(
+                    &plugin_context,
+                    x.dependencies,
+                    x.context_dependencies,
+                    context,
+                );
                 return content_from_result(TransformResult {
                     content: x.content,
                     content_type: x.content_type,
+                    dependencies: None,
+                    context_dependencies: None,
                 })
                 .map(Some);
             }
@@ -141,6 +188,42 @@ impl Plugin for JsPlugin {
         Ok(())
     }
 
+    fn done(&self, stats: &StatsJsonMap, _context: &Arc<Context>) -> Result<()> {
+        if let Some(hook) = &self.hooks.done {
+            hook.call(serde_json::to_value(stats)?)?
+        }
+        Ok(())
+    }
+
+    fn source_maps_emitted(
+        &self,
+        source_maps: &[PluginEmittedSourceMap],
+        _context: &Arc<Context>,
+    ) -> Result<()> {
+        if let Some(hook) = &self.hooks.source_maps_emitted {
+            hook.call(serde_json::to_value(source_maps)?)?
+        }
+        Ok(())
+    }
+
+    fn module_built(
+        &self,
+        params: &PluginModuleBuiltParams,
+        _context: &Arc<Context>,
+    ) -> Result<()> {
+        if let Some(hook) = &self.hooks.module_built {
+            hook.call(serde_json::to_value(params)?)?
+        }
+        Ok(())
+    }
+
+    fn rebuild_end(&self, params: &PluginRebuildEndParams, _context: &Arc<Context>) -> Result<()> {
+        if let Some(hook) = &self.hooks.rebuild_end {
+            hook.call(serde_json::to_value(params)?)?
+        }
+        Ok(())
+    }
+
     fn before_write_fs(&self, path: &std::path::Path, content: &[u8]) -> Result<()> {
         if let Some(hook) = &self.hooks._on_generate_file {
             hook.call(WriteFile {
@@ -155,7 +238,7 @@ impl Plugin for JsPlugin {
         &self,
         content: &mut Content,
         path: &str,
-        _context: &Arc<Context>,
+        context: &Arc<Context>,
     ) -> Result<Option<Content>> {
         if let Some(hook) = &self.hooks.transform_include {
             if hook.call(path.to_string())? == Some(false) {
@@ -172,7 +255,13 @@ impl Plugin for JsPlugin {
 
             let result: Option<TransformResult> = hook.call((content_str, path.to_string()))?;
 
-            if let Some(result) = result {
+            if let Some(mut result) = result {
+                add_watch_paths(
+                    &PluginContext::new(self.name(), context).with_module(Path::new(path)),
+                    result.dependencies.take(),
+                    result.context_dependencies.take(),
+                    context,
+                );
                 return content_from_result(result).map(Some);
             }
         }
@@ -1,13 +1,15 @@
 #![deny(clippy::all)]
 
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::{Arc, Once};
 
-use js_hook::{JsHooks, TsFnHooks};
+use js_hook::{JsFilterPattern, JsHooks, TsFnHooks};
 use js_plugin::JsPlugin;
 use mako::compiler::{Args, Compiler};
 use mako::config::Config;
-use mako::dev::DevServer;
-use mako::plugin::Plugin;
+use mako::dev::{DevServer, DevService};
+use mako::plugin::{FilterPattern, Plugin, PluginFilter};
 use mako::utils::logger::init_logger;
 use mako::utils::thread_pool;
 use napi::bindgen_prelude::*;
@@ -42,15 +44,39 @@ pub struct BuildParams {
     output?: {
         path: string;
         mode: "bundle" | "bundless" ;
-        esVersion?: string;
+        format?: "iife" | "esm";
+        esVersion?:
+            | "es3"
+            | "es5"
+            | "es2015"
+            | "es2016"
+            | "es2017"
+            | "es2018"
+            | "es2019"
+            | "es2020"
+            | "es2021"
+            | "es2022"
+            | "esnext";
         meta?: boolean;
         preserveModules?: boolean;
         preserveModulesRoot?: string;
         skipWrite?: boolean;
+        compress?: false | { gzip?: boolean; brotli?: boolean; threshold?: number };
+        chunkLoadRetry?:
+            | false
+            | { retries?: number; delay?: number; fallbackPublicPaths?: string[] };
+        comments?: "none" | "license" | "all";
+        asciiOnly?: boolean;
+        runtimeGlobal?: string;
+        cspNonce?: string;
+        workerChunkLoading?: "importScripts" | "import";
+        hashFunction?: "md5" | "xxhash64" | "sha256";
+        hashDigestLength?: number;
     };
     resolve?: {
        alias?: Array<[string, string]>;
        extensions?: string[];
+       conditions?: string[];
     };
     manifest?: false | {
         fileName: string;
@@ -63,6 +89,7 @@ pub struct BuildParams {
         string,
         string | {
             root: string;
+            type?: "commonjs" | "module";
             script?: string;
             subpath?: {
                 exclude?: string[];
@@ -102,14 +129,24 @@ pub struct BuildParams {
             }[];
           }
         };
-    providers?: Record<string, string[]>;
+    providers?: Record<string, string | string[]>;
     publicPath?: string;
     inlineLimit?: number;
+    assetFingerprint?: "filename" | "query";
+    assetRules?: { mime: string; filename?: string; inlineLimit?: number }[];
     inlineExcludesExtensions?: string[];
     targets?: Record<string, number>;
     platform?: "node" | "browser";
     hmr?: false | {};
-    devServer?: false | { host?: string; port?: number };
+    devServer?: false | {
+        host?: string;
+        port?: number;
+        https?: false | { cert?: string; key?: string };
+        http2?: boolean;
+        strictPort?: boolean;
+        open?: boolean | string;
+        onDemandEntries?: boolean;
+    };
     px2rem?: false | {
         root?: number;
         propBlackList?: string[];
@@ -126,12 +163,35 @@ pub struct BuildParams {
     dynamicImportToRequire?: boolean;
     umd?: false | string;
     cjs?: boolean;
+    singleFile?: boolean;
+    mpa?: false | { template?: string; sharedVendors?: boolean; inlineLimit?: number };
     writeToDisk?: boolean;
     transformImport?: { libraryName: string; libraryDirectory?: string; style?: boolean | string }[];
     clean?: boolean;
     nodePolyfill?: boolean;
+    node?: false | {
+        __dirname?: boolean;
+        __filename?: boolean;
+        global?: boolean;
+        process?: boolean;
+    };
     ignores?: string[];
-    moduleIdStrategy?: "hashed" | "named";
+    noParse?: string[];
+    ignoreModules?: { resourceRegExp: string; contextRegExp?: string }[];
+    legacy?: false | { targets?: Record<string, number>; polyfills?: boolean };
+    lint?: false | { command?: string; failOnError?: boolean };
+    outputs?: {
+        path: string;
+        format?: "iife" | "esm";
+        platform?: "browser" | "node";
+        cjs?: boolean;
+        umd?: string;
+        targets?: Record<string, number>;
+        externals?: Record<string, string>;
+    }[];
+    localeFilter?: false | { locales: string[] };
+    memoryLimit?: number;
+    moduleIdStrategy?: "hashed" | "named" | "numeric" | "deterministic";
     minify?: boolean;
     _minifish?: false | {
         mapping: Record<string, string>;
@@ -146,6 +206,9 @@ pub struct BuildParams {
     };
     optimization?: false | {
         skipModules?: boolean;
+        concatenateModules?: boolean;
+        mangleExports?: false | { reserved?: string[] };
+        hoistSharedCss?: boolean;
     };
     react?: {
         runtime?: "automatic" | "classic";
@@ -153,15 +216,75 @@ pub struct BuildParams {
         importSource?: string;
         pragmaFrag?: string;
     };
+    typescript?: {
+        verbatimModuleSyntax?: boolean;
+        importsNotUsedAsValues?: "remove" | "preserve";
+        useDefineForClassFields?: boolean;
+    };
+    compat?: {
+        classPropertiesLoose?: boolean;
+        privateMethodsLoose?: boolean;
+        optionalChainingLoose?: boolean;
+    };
+    performance?: false | {
+        budgets?: {
+            maxEntrySize?: number;
+            maxAssetSize?: number;
+            maxTotalSize?: number;
+        };
+        hints?: "warning" | "error";
+    };
     emitAssets?: boolean;
     cssModulesExportOnlyLocales?: boolean;
     inlineCSS?: false | {};
+    lossyUtf8?: boolean;
+    envPrefix?: string;
+    purgeCSS?: false | {
+        safelist?: string[];
+        content?: string[];
+    };
+    fsRoutes?: false | { dir?: string };
+    fontSubset?: false | { content?: string[]; command?: string };
+    chunkHints?: false | { prefetch?: string[]; preload?: string[] };
+    chunkNaming?: "id" | "route";
+    typeCheck?: false | { command?: string; tsconfig?: string };
+    remoteModules?:
+        | false
+        | { cacheDir?: string; lockfile?: string; frozenLockfile?: boolean };
+    sizeReport?: false | { topModules?: number };
+    metafile?: false | { fileName?: string };
+    styleInjection?: false | { insert?: string; constructable?: boolean };
+    shadowDom?: boolean;
+    conditionalCompilation?: boolean;
+    microFrontend?: false | { name?: string };
+    resolveCache?: false | { cacheDir?: string };
+    duplicateModules?: false | { hoist?: boolean; minChunks?: number };
+    serviceWorker?: false | {
+        entry: string;
+        filename?: string;
+        register?: boolean;
+        scope?: string;
+        exclude?: string[];
+    };
+    sourceMap?: { debugIds?: boolean; sourcesContent?: boolean };
+    sri?: false | {
+        algorithm?: "sha256" | "sha384" | "sha512";
+    };
+    ssr?: false | {
+        entry?: Record<string, string>;
+        outputPath?: string;
+        manifest?: string;
+    };
     rscServer?: false | {
         "emitCSS": boolean;
         "clientComponentTpl": string;
     };
     rscClient?: false | {
         "logServerComponent": "error" | "ignore";
+        "clientReferences"?: string[];
+        "manifest"?: string;
+        "serverActionTpl"?: string;
+        "serverActionsManifest"?: string;
     };
     experimental?: {
         webpackSyntaxValidate?: string[];
@@ -176,43 +299,89 @@ pub struct BuildParams {
     pub watch: bool,
 }
 
-#[napi(ts_return_type = r#"Promise<void>"#)]
-pub fn build(env: Env, build_params: BuildParams) -> napi::Result<JsObject> {
+fn filter_patterns(patterns: Option<&[JsFilterPattern]>) -> napi::Result<Vec<FilterPattern>> {
+    patterns
+        .unwrap_or_default()
+        .iter()
+        .map(|pattern| match (&pattern.glob, &pattern.regex) {
+            (Some(glob), None) => Ok(FilterPattern::glob(glob)),
+            (None, Some(regex)) => FilterPattern::regex(regex)
+                .map_err(|e| napi::Error::new(Status::InvalidArg, e.to_string())),
+            _ => Err(napi::Error::new(
+                Status::InvalidArg,
+                "Invalid plugin filter, only strings and RegExps are supported".to_string(),
+            )),
+        })
+        .collect()
+}
+
+fn prepare(
+    env: Env,
+    build_params: &BuildParams,
+) -> napi::Result<(PathBuf, Config, Vec<Arc<dyn Plugin>>)> {
     LOG_INIT.call_once(|| {
         init_logger();
     });
 
     let mut plugins: Vec<Arc<dyn Plugin>> = vec![];
     for hooks in build_params.plugins.iter() {
+        if let Some(enforce) = hooks.enforce.as_deref() {
+            if enforce != "pre" && enforce != "post" {
+                return Err(napi::Error::new(
+                    Status::InvalidArg,
+                    format!(
+                        "Invalid enforce \"{}\" of plugin {}, should be \"pre\" or \"post\"",
+                        enforce,
+                        hooks.name.as_deref().unwrap_or("unknown")
+                    ),
+                ));
+            }
+        }
+        let filter = if hooks.include.is_some() || hooks.exclude.is_some() {
+            Some(PluginFilter::new(
+                filter_patterns(hooks.include.as_deref())?,
+                filter_patterns(hooks.exclude.as_deref())?,
+            ))
+        } else {
+            None
+        };
         let tsfn_hooks = TsFnHooks::new(env, hooks);
         let plugin = JsPlugin {
             name: hooks.name.clone(),
             hooks: tsfn_hooks,
             enforce: hooks.enforce.clone(),
+            filter,
+            build_dependencies: hooks.build_dependencies.clone(),
         };
         plugins.push(Arc::new(plugin));
     }
 
-    // sort with enforce: pre / post
-    plugins.sort_by_key(|plugin| match plugin.enforce() {
-        Some("pre") => 0,
-        Some("post") => 2,
-        _ => 1,
-    });
-
-    let root = std::path::PathBuf::from(&build_params.root);
+    let root = PathBuf::from(&build_params.root);
     let default_config = serde_json::to_string(&build_params.config).unwrap();
     let config = Config::new(&root, Some(&default_config), None).map_err(|e| {
         napi::Error::new(Status::GenericFailure, format!("Load config failed: {}", e))
     })?;
+    Ok((root, config, plugins))
+}
+
+#[napi(ts_return_type = r#"Promise<void>"#)]
+pub fn build(env: Env, build_params: BuildParams) -> napi::Result<JsObject> {
+    let (root, config, plugins) = prepare(env, &build_params)?;
 
     if build_params.watch {
         let (deferred, promise) = env.create_deferred()?;
         env.execute_tokio_future(
             async move {
-                let compiler =
-                    Compiler::new(config, root.clone(), Args { watch: true }, Some(plugins))
-                        .map_err(|e| napi::Error::new(Status::GenericFailure, format!("{}", e)));
+                let compiler = Compiler::new(
+                    config,
+                    root.clone(),
+                    Args {
+                        watch: true,
+                        ..Default::default()
+                    },
+                    Some(plugins),
+                )
+                .map_err(|e| napi::Error::new(Status::GenericFailure, format!("{}", e)));
                 if let Err(e) = compiler {
                     deferred.reject(e);
                     return Ok(());
@@ -237,9 +406,16 @@ pub fn build(env: Env, build_params: BuildParams) -> napi::Result<JsObject> {
     } else {
         let (deferred, promise) = env.create_deferred()?;
         thread_pool::spawn(move || {
-            let compiler =
-                Compiler::new(config, root.clone(), Args { watch: false }, Some(plugins))
-                    .map_err(|e| napi::Error::new(Status::GenericFailure, format!("{}", e)));
+            let compiler = Compiler::new(
+                config,
+                root.clone(),
+                Args {
+                    watch: false,
+                    ..Default::default()
+                },
+                Some(plugins),
+            )
+            .map_err(|e| napi::Error::new(Status::GenericFailure, format!("{}", e)));
             let compiler = match compiler {
                 Ok(c) => c,
                 Err(e) => {
@@ -259,3 +435,91 @@ pub fn build(env: Env, build_params: BuildParams) -> napi::Result<JsObject> {
         Ok(promise)
     }
 }
+
+#[napi(object)]
+pub struct DevMiddlewareResponse {
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    pub body: Buffer,
+}
+
+/// The dev server without listening on a port, for the frameworks with their own servers
+#[napi]
+pub struct DevMiddleware {
+    service: DevService,
+    hmr_port: u16,
+}
+
+#[napi]
+impl DevMiddleware {
+    /// The local port which the hmr websocket connections should be proxied to
+    #[napi(getter)]
+    pub fn hmr_port(&self) -> u16 {
+        self.hmr_port
+    }
+
+    /// Resolves null if the request is not served by mako
+    #[napi]
+    pub async fn handle(
+        &self,
+        method: String,
+        url: String,
+        headers: HashMap<String, String>,
+    ) -> napi::Result<Option<DevMiddlewareResponse>> {
+        let headers = headers.into_iter().collect::<Vec<_>>();
+        let res = self
+            .service
+            .request(&method, &url, &headers)
+            .await
+            .map_err(|e| napi::Error::new(Status::GenericFailure, format!("{}", e)))?;
+        Ok(res.map(|res| DevMiddlewareResponse {
+            status: res.status,
+            headers: res.headers.into_iter().collect(),
+            body: res.body.into(),
+        }))
+    }
+}
+
+#[napi(ts_return_type = r#"Promise<DevMiddleware>"#)]
+pub fn create_dev_middleware(env: Env, build_params: BuildParams) -> napi::Result<JsObject> {
+    let (root, config, plugins) = prepare(env, &build_params)?;
+
+    let (deferred, promise) = env.create_deferred()?;
+    env.execute_tokio_future(
+        async move {
+            let compiler = Compiler::new(
+                config,
+                root.clone(),
+                Args {
+                    watch: true,
+                    ..Default::default()
+                },
+                Some(plugins),
+            )
+            .and_then(|compiler| {
+                compiler.compile()?;
+                Ok(compiler)
+            })
+            .map_err(|e| napi::Error::new(Status::GenericFailure, format!("{}", e)));
+            let compiler = match compiler {
+                Ok(compiler) => compiler,
+                Err(e) => {
+                    deferred.reject(e);
+                    return Ok(());
+                }
+            };
+            let service = DevServer::new(root, Arc::new(compiler)).middleware();
+            match service.listen_hmr() {
+                Ok(hmr_port) => {
+                    deferred.resolve(move |_env| Ok(DevMiddleware { service, hmr_port }));
+                }
+                Err(e) => {
+                    deferred.reject(napi::Error::new(Status::GenericFailure, format!("{}", e)));
+                }
+            }
+            Ok(())
+        },
+        move |&mut _, _res| Ok(()),
+    )?;
+    Ok(promise)
+}
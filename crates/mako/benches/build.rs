@@ -0,0 +1,183 @@
+//! The benchmarks of the cold build, the warm rebuild and the codegen over the synthetic
+//! projects of different sizes, run with `just bench-rs`, the projects are generated in the
+//! target dir
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use mako::compiler::{Args, Compiler};
+use mako::config::Config;
+
+/// The synthetic projects with the numbers of their modules
+const PROJECTS: [(&str, usize); 3] = [("small", 100), ("medium", 1000), ("large", 5000)];
+
+/// Generate a project of a tree of the ts modules, each module imports its two children and
+/// the shared utils, every tenth module imports a css file and every fiftieth module is
+/// imported dynamically to split the chunks
+fn setup_project(name: &str, modules: usize) -> PathBuf {
+    let root = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("bench")
+        .join(name);
+    if root.exists() {
+        fs::remove_dir_all(&root).unwrap();
+    }
+    let src = root.join("src");
+    fs::create_dir_all(&src).unwrap();
+    fs::write(
+        root.join("mako.config.json"),
+        r#"{ "entry": { "index": "src/index.ts" } }"#,
+    )
+    .unwrap();
+    fs::write(
+        src.join("index.ts"),
+        "import { m0 } from './m0';\n\nconsole.log(m0(0));\n",
+    )
+    .unwrap();
+    fs::write(
+        src.join("utils.ts"),
+        "export const format = (id: number, ...tags: string[]) => `${id}:${tags.join(',')}`;\n",
+    )
+    .unwrap();
+    for i in 0..modules {
+        fs::write(src.join(format!("m{}.ts", i)), module_source(i, modules)).unwrap();
+        if i % 10 == 0 {
+            fs::write(
+                src.join(format!("m{}.css", i)),
+                format!(
+                    ".m{} {{\n  display: flex;\n  padding: {}px;\n}}\n",
+                    i,
+                    i % 16
+                ),
+            )
+            .unwrap();
+        }
+    }
+    root
+}
+
+fn module_source(i: usize, modules: usize) -> String {
+    let mut imports = vec!["import { format } from './utils';".to_string()];
+    let mut calls = vec![];
+    for child in [i * 2 + 1, i * 2 + 2] {
+        if child >= modules {
+            continue;
+        }
+        if child % 50 == 0 {
+            calls.push(format!(
+                "import('./m{child}').then((m) => m.m{child}(depth + 1));"
+            ));
+        } else {
+            imports.push(format!("import {{ m{child} }} from './m{child}';"));
+            calls.push(format!("m{child}(depth + 1);"));
+        }
+    }
+    if i % 10 == 0 {
+        imports.push(format!("import './m{i}.css';"));
+    }
+    format!(
+        r#"{imports}
+
+interface Props {{
+  id: number;
+  tags: string[];
+}}
+
+export function m{i}(depth: number): string {{
+  const props: Props = {{ id: {i}, tags: ['a', 'b'].map((tag) => `${{tag}}-{i}`) }};
+  {calls}
+  return format(props.id, ...props.tags);
+}}
+"#,
+        imports = imports.join("\n"),
+        calls = calls.join("\n  "),
+    )
+}
+
+fn create_compiler(root: &Path, mode: &str, watch: bool) -> Compiler {
+    let cli_config = format!(r#"{{ "mode": "{}" }}"#, mode);
+    let config = Config::new(root, None, Some(&cli_config)).unwrap();
+    Compiler::new(
+        config,
+        root.to_path_buf(),
+        Args {
+            watch,
+            ..Default::default()
+        },
+        None,
+    )
+    .unwrap()
+}
+
+fn cold_build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cold_build");
+    group.sample_size(10);
+    for (name, modules) in PROJECTS {
+        let root = setup_project(name, modules);
+        group.bench_with_input(BenchmarkId::from_parameter(name), &root, |b, root| {
+            b.iter_batched(
+                || create_compiler(root, "production", false),
+                |compiler| compiler.compile().unwrap(),
+                BatchSize::PerIteration,
+            )
+        });
+    }
+    group.finish();
+}
+
+/// Change a leaf module and rebuild it with the hot update chunks, as the watch mode does
+fn warm_rebuild(c: &mut Criterion) {
+    let mut group = c.benchmark_group("warm_rebuild");
+    group.sample_size(10);
+    for (name, modules) in PROJECTS {
+        let root = setup_project(name, modules);
+        let compiler = create_compiler(&root, "development", true);
+        compiler.compile().unwrap();
+        let leaf = root.join("src").join(format!("m{}.ts", modules - 1));
+        let source = fs::read_to_string(&leaf).unwrap();
+        let mut snapshot_hash = compiler.full_hash();
+        let mut hmr_hash = snapshot_hash;
+        let mut version = 0;
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| {
+                version += 1;
+                fs::write(
+                    &leaf,
+                    format!("{}\nexport const version = {};\n", source, version),
+                )
+                .unwrap();
+                let result = compiler.update(vec![leaf.clone()]).unwrap();
+                let (next_snapshot_hash, next_hmr_hash, _) = compiler
+                    .generate_hot_update_chunks(result, snapshot_hash, hmr_hash)
+                    .unwrap();
+                snapshot_hash = next_snapshot_hash;
+                hmr_hash = next_hmr_hash;
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Generate the chunks from the built module graph, without building the modules
+fn codegen(c: &mut Criterion) {
+    let mut group = c.benchmark_group("codegen");
+    group.sample_size(10);
+    for (name, modules) in PROJECTS {
+        let root = setup_project(name, modules);
+        group.bench_with_input(BenchmarkId::from_parameter(name), &root, |b, root| {
+            b.iter_batched(
+                || {
+                    let compiler = create_compiler(root, "production", false);
+                    compiler.build_entries().unwrap();
+                    compiler
+                },
+                |compiler| compiler.generate().unwrap(),
+                BatchSize::PerIteration,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, cold_build, warm_rebuild, codegen);
+criterion_main!(benches);
@@ -1,8 +1,10 @@
 use swc_core::common;
-use swc_core::common::comments::{Comment, Comments as CommentsTrait};
+use swc_core::common::comments::{Comment, CommentKind, Comments as CommentsTrait};
 use swc_core::common::{BytePos, Span};
 use swc_node_comments::SwcComments;
 
+use crate::config::OutputComments;
+
 #[derive(Default)]
 pub struct Comments(SwcComments);
 
@@ -84,3 +86,131 @@ impl Comments {
         found
     }
 }
+
+/// The comments passed to the codegen by `output.comments`, which only exposes the license
+/// comments in the license mode
+pub struct EmitComments<'a> {
+    comments: &'a SwcComments,
+    license_only: bool,
+}
+
+impl<'a> EmitComments<'a> {
+    pub fn new(comments: &'a SwcComments, option: OutputComments) -> Option<Self> {
+        match option {
+            OutputComments::None => None,
+            OutputComments::License => Some(Self {
+                comments,
+                license_only: true,
+            }),
+            OutputComments::All => Some(Self {
+                comments,
+                license_only: false,
+            }),
+        }
+    }
+
+    fn filter(&self, comments: Option<Vec<Comment>>) -> Option<Vec<Comment>> {
+        if !self.license_only {
+            return comments;
+        }
+        let comments = comments?
+            .into_iter()
+            .filter(is_license_comment)
+            .collect::<Vec<_>>();
+        (!comments.is_empty()).then_some(comments)
+    }
+}
+
+/// `/*! ... */` or the block comments with `@license` or `@preserve`
+pub fn is_license_comment(comment: &Comment) -> bool {
+    comment.kind == CommentKind::Block
+        && (comment.text.starts_with('!')
+            || comment.text.contains("@license")
+            || comment.text.contains("@preserve"))
+}
+
+impl CommentsTrait for EmitComments<'_> {
+    fn add_leading(&self, pos: BytePos, cmt: Comment) {
+        self.comments.add_leading(pos, cmt)
+    }
+
+    fn add_leading_comments(&self, pos: BytePos, comments: Vec<Comment>) {
+        self.comments.add_leading_comments(pos, comments)
+    }
+
+    fn has_leading(&self, pos: BytePos) -> bool {
+        self.get_leading(pos).is_some()
+    }
+
+    fn move_leading(&self, from: BytePos, to: BytePos) {
+        self.comments.move_leading(from, to)
+    }
+
+    fn take_leading(&self, pos: BytePos) -> Option<Vec<Comment>> {
+        self.filter(self.comments.take_leading(pos))
+    }
+
+    fn get_leading(&self, pos: BytePos) -> Option<Vec<Comment>> {
+        self.filter(self.comments.get_leading(pos))
+    }
+
+    fn add_trailing(&self, pos: BytePos, cmt: Comment) {
+        self.comments.add_trailing(pos, cmt)
+    }
+
+    fn add_trailing_comments(&self, pos: BytePos, comments: Vec<Comment>) {
+        self.comments.add_trailing_comments(pos, comments)
+    }
+
+    fn has_trailing(&self, pos: BytePos) -> bool {
+        self.get_trailing(pos).is_some()
+    }
+
+    fn move_trailing(&self, from: BytePos, to: BytePos) {
+        self.comments.move_trailing(from, to)
+    }
+
+    fn take_trailing(&self, pos: BytePos) -> Option<Vec<Comment>> {
+        self.filter(self.comments.take_trailing(pos))
+    }
+
+    fn get_trailing(&self, pos: BytePos) -> Option<Vec<Comment>> {
+        self.filter(self.comments.get_trailing(pos))
+    }
+
+    fn add_pure_comment(&self, pos: BytePos) {
+        self.comments.add_pure_comment(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::common::DUMMY_SP;
+
+    use super::*;
+
+    fn block(text: &str) -> Comment {
+        Comment {
+            kind: CommentKind::Block,
+            span: DUMMY_SP,
+            text: text.into(),
+        }
+    }
+
+    #[test]
+    fn test_license_comments() {
+        let comments = SwcComments::default();
+        comments.add_leading(BytePos(1), block("! MIT"));
+        comments.add_leading(BytePos(1), block(" normal "));
+        comments.add_leading(BytePos(2), block("* @license Apache-2.0 "));
+        comments.add_leading(BytePos(3), block(" normal "));
+        let emit_comments = EmitComments::new(&comments, OutputComments::License).unwrap();
+        assert_eq!(
+            emit_comments.get_leading(BytePos(1)),
+            Some(vec![block("! MIT")])
+        );
+        assert!(emit_comments.has_leading(BytePos(2)));
+        assert!(!emit_comments.has_leading(BytePos(3)));
+        assert!(EmitComments::new(&comments, OutputComments::None).is_none());
+    }
+}
@@ -94,8 +94,8 @@ impl CssAst {
         )
     }
 
-    pub fn analyze_deps(&self) -> Vec<Dependency> {
-        let mut visitor = CSSDepAnalyzer::new();
+    pub fn analyze_deps(&self, layer: Option<String>) -> Vec<Dependency> {
+        let mut visitor = CSSDepAnalyzer::new(layer);
         self.ast.visit_with(&mut visitor);
         visitor.dependencies
     }
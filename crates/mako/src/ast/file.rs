@@ -10,9 +10,10 @@ use regex::Regex;
 use thiserror::Error;
 use twox_hash::XxHash64;
 use url::Url;
-use {md5, mime_guess};
 
 use crate::compiler::Context;
+use crate::config::OutputConfig;
+use crate::utils::hash::ContentHasher;
 use crate::utils::{base64_decode, base64_encode};
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -130,11 +131,17 @@ impl File {
             // TODO: remove this specific logic
             params.iter().any(|(k, _)| k == "asmodule");
         let is_under_node_modules = path.to_string_lossy().contains("node_modules");
-        let extname = pathname
-            .clone()
-            .extension()
-            .map(|ext| ext.to_string_lossy().to_string())
-            .unwrap_or_default();
+        // sub-modules like `a.vue?vue&type=script&lang.ts` are treated as the type of `lang`
+        let extname = params
+            .iter()
+            .find_map(|(k, _)| k.strip_prefix("lang.").map(|ext| ext.to_string()))
+            .unwrap_or_else(|| {
+                pathname
+                    .clone()
+                    .extension()
+                    .map(|ext| ext.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            });
         if is_virtual {
             File {
                 path: path.clone(),
@@ -242,28 +249,25 @@ impl File {
         }
     }
 
-    pub fn get_content_hash(&self) -> Result<String> {
+    pub fn get_content_hash(&self, output: &OutputConfig) -> Result<String> {
         let file = std::fs::File::open(&self.pathname)?;
         let len = file.metadata()?.len();
         // Decide on a reasonable buffer size (1MB in this case, fastest will depend on hardware)
         let buf_len = len.min(1_000_000) as usize;
         let mut buf = BufReader::with_capacity(buf_len, file);
-        // webpack use md4
-        let mut context = md5::Context::new();
+        let mut hasher = ContentHasher::new(output.hash_function);
         loop {
             // Get a chunk of the file
             let part = buf.fill_buf()?;
             if part.is_empty() {
                 break;
             }
-            context.consume(part);
+            hasher.update(part);
             // Tell the buffer that the chunk is consumed
             let part_len = part.len();
             buf.consume(part_len);
         }
-        let digest = context.compute();
-        let hash = format!("{:x}", digest);
-        Ok(hash[0..8].to_string())
+        Ok(hasher.finish(output.hash_length()))
     }
 
     pub fn is_content_jsx(&self) -> bool {
@@ -404,6 +408,16 @@ mod tests {
         assert_eq!(f.path(), Some("/root/d.js".to_string()));
     }
 
+    #[test]
+    fn test_extname_from_lang_param() {
+        let f = File::new(
+            "/a/b.vue?vue&type=script&lang.ts".to_string(),
+            Arc::new(Context::default()),
+        );
+        assert_eq!(f.extname, "ts");
+        assert_eq!(f.pathname, PathBuf::from("/a/b.vue"));
+    }
+
     #[test]
     fn test_parse_path_support_windows() {
         let path = "C:\\a\\b\\c?foo";
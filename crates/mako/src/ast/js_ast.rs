@@ -2,6 +2,7 @@ use std::fmt;
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
+use swc_core::common::comments::Comments;
 use swc_core::common::util::take::Take;
 use swc_core::common::{FileName, Mark, Spanned, GLOBALS};
 use swc_core::ecma::ast::{EsVersion, Module};
@@ -15,6 +16,7 @@ use swc_core::ecma::utils::contains_top_level_await;
 use swc_core::ecma::visit;
 use swc_core::ecma::visit::{VisitMutWith, VisitWith};
 
+use crate::ast::comments::EmitComments;
 use crate::ast::file::{Content, File, JsContent};
 use crate::ast::sourcemap::build_source_map_to_buf;
 use crate::ast::{error, utils};
@@ -61,6 +63,7 @@ impl JsAst {
                 jsx,
                 decorators: true,
                 decorators_before_export: true,
+                import_attributes: true,
                 ..Default::default()
             })
         };
@@ -191,10 +194,14 @@ impl JsAst {
             let is_prod = matches!(context.config.mode, Mode::Production);
             let minify = context.config.minify && is_prod;
             let ascii_only = if context.config.output.mode == OutputMode::Bundless {
-                false
+                context.config.output.ascii_only.unwrap_or(false)
             } else {
-                minify
+                context.config.output.ascii_only_or_default(minify)
             };
+            let emit_comments = EmitComments::new(
+                swc_comments,
+                context.config.output.comments_or_default(minify),
+            );
             let mut emitter = Emitter {
                 cfg: JsCodegenConfig::default()
                     .with_minify(minify)
@@ -202,7 +209,7 @@ impl JsAst {
                     .with_ascii_only(ascii_only)
                     .with_omit_last_semi(true),
                 cm: cm.clone(),
-                comments: if minify { None } else { Some(swc_comments) },
+                comments: emit_comments.as_ref().map(|c| c as &dyn Comments),
                 wr: Box::new(JsWriter::new(
                     cm.clone(),
                     "\n",
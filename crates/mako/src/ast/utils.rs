@@ -69,6 +69,58 @@ pub fn is_ident_undefined(ident: &Ident, sym: &str, unresolved_mark: &Mark) -> b
     ident.sym == *sym && ident.ctxt.outer() == *unresolved_mark
 }
 
+/// `Worker` or `SharedWorker`, which are compiled as the worker entries when constructed with
+/// `new URL('a', import.meta.url)`
+pub fn is_worker_constructor(ident: &Ident, unresolved_mark: &Mark) -> bool {
+    is_ident_undefined(ident, "Worker", unresolved_mark)
+        || is_ident_undefined(ident, "SharedWorker", unresolved_mark)
+}
+
+/// navigator.serviceWorker.register()
+pub fn is_service_worker_register(call_expr: &CallExpr, unresolved_mark: &Mark) -> bool {
+    if let Callee::Expr(box Expr::Member(MemberExpr {
+        obj:
+            box Expr::Member(MemberExpr {
+                obj: box Expr::Ident(navigator),
+                prop: MemberProp::Ident(service_worker),
+                ..
+            }),
+        prop: MemberProp::Ident(register),
+        ..
+    })) = &call_expr.callee
+    {
+        is_ident_undefined(navigator, "navigator", unresolved_mark)
+            && service_worker.sym == "serviceWorker"
+            && register.sym == "register"
+    } else {
+        false
+    }
+}
+
+/// audioContext.audioWorklet.addModule() or CSS.paintWorklet.addModule()
+pub fn is_worklet_add_module(call_expr: &CallExpr, unresolved_mark: &Mark) -> bool {
+    if let Callee::Expr(box Expr::Member(MemberExpr {
+        obj:
+            box Expr::Member(MemberExpr {
+                obj,
+                prop: MemberProp::Ident(worklet),
+                ..
+            }),
+        prop: MemberProp::Ident(add_module),
+        ..
+    })) = &call_expr.callee
+    {
+        let is_css = match obj {
+            box Expr::Ident(ident) => is_ident_undefined(ident, "CSS", unresolved_mark),
+            _ => false,
+        };
+        add_module.sym == "addModule"
+            && (worklet.sym == "audioWorklet" || (worklet.sym == "paintWorklet" && is_css))
+    } else {
+        false
+    }
+}
+
 pub fn get_first_str_arg(call_expr: &CallExpr) -> Option<String> {
     if let Some(arg) = call_expr.args.first() {
         if let box Expr::Lit(Lit::Str(str_)) = &arg.expr {
@@ -4,19 +4,21 @@ pub(crate) mod parse;
 pub(crate) mod targets;
 pub(crate) mod transform;
 
-use std::collections::HashSet;
-use std::sync::mpsc::channel;
+use std::collections::{HashSet, VecDeque};
+use std::sync::mpsc::sync_channel;
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::Result;
 use colored::Colorize;
 use thiserror::Error;
 
 use crate::ast::file::{Content, File, JsContent};
+use crate::build::analyze_deps::AnalyzeDepsResult;
 use crate::compiler::{Compiler, Context};
 use crate::generate::chunk_pot::util::hash_hashmap;
 use crate::module::{Module, ModuleAst, ModuleId, ModuleInfo};
-use crate::plugin::NextBuildParam;
+use crate::plugin::{NextBuildParam, PluginModuleBuiltDurations, PluginModuleBuiltParams};
 use crate::resolve::ResolverResource;
 use crate::utils::thread_pool;
 
@@ -26,6 +28,8 @@ pub enum BuildError {
         "{:}\n{:}", "Build failed.".to_string().red().to_string(), errors.iter().map(| e | e.to_string()).collect::< Vec < _ >> ().join("\n")
     )]
     BuildTasksError { errors: Vec<anyhow::Error> },
+    #[error("Build cancelled.")]
+    Cancelled,
 }
 
 impl Compiler {
@@ -34,37 +38,63 @@ impl Compiler {
             return Ok(HashSet::new());
         }
 
-        let (rs, rr) = channel::<Result<Module>>();
+        // Bound both the number of queued tasks in the pool and the number of
+        // pending results, so that large projects don't pile up thousands of
+        // closures (each holding a cloned File) in memory at the same time.
+        let max_in_flight = thread_pool::current_num_threads() * 2;
+        let (rs, rr) = sync_channel::<Result<Module>>(max_in_flight);
 
+        let cancellation = self.context.cancellation.clone();
         let build_with_pool = |file: File, parent_resource: Option<ResolverResource>| {
             let rs = rs.clone();
             let context = self.context.clone();
+            let cancellation = cancellation.clone();
             thread_pool::spawn(move || {
-                let result = Self::build_module(&file, parent_resource, context.clone());
-                let result = Self::handle_build_result(result, &file, context);
+                let result = cancellation
+                    .check()
+                    .and_then(|_| Self::build_module(&file, parent_resource, context.clone()));
+                // the cancelled modules are not turned into the error modules in watch mode
+                let result = if cancellation.is_cancelled() {
+                    Err(anyhow::anyhow!(BuildError::Cancelled))
+                } else {
+                    Self::handle_build_result(result, &file, context)
+                };
                 rs.send(result).unwrap();
             });
         };
-        let mut count = 0;
-        for file in files {
-            count += 1;
-            build_with_pool(file, None);
-        }
+
+        let mut pending: VecDeque<(File, Option<ResolverResource>)> =
+            files.into_iter().map(|file| (file, None)).collect();
+        let mut in_flight = 0;
 
         let mut errors = vec![];
         let mut module_ids = HashSet::new();
 
-        for build_result in rr {
-            count -= 1;
+        loop {
+            // stop scheduling and wait for the in-flight tasks to bail out
+            if cancellation.is_cancelled() {
+                pending.clear();
+            }
+            while in_flight < max_in_flight
+                && let Some((file, parent_resource)) = pending.pop_front()
+            {
+                in_flight += 1;
+                build_with_pool(file, parent_resource);
+            }
+            if in_flight == 0 {
+                break;
+            }
+
+            let build_result = rr.recv().unwrap();
+            in_flight -= 1;
+            if cancellation.is_cancelled() {
+                continue;
+            }
 
             // handle build_module error
             if build_result.is_err() {
                 errors.push(build_result.err().unwrap());
-                if count == 0 {
-                    break;
-                } else {
-                    continue;
-                }
+                continue;
             }
             let module = build_result.unwrap();
             let module_id = module.id.clone();
@@ -76,13 +106,13 @@ impl Compiler {
                         .modules_with_missing_deps
                         .write()
                         .unwrap()
-                        .retain(|id| id != &module_id.id);
+                        .retain(|id| id != module_id.as_str());
                 } else {
                     self.context
                         .modules_with_missing_deps
                         .write()
                         .unwrap()
-                        .push(module_id.id.clone());
+                        .push(module_id.id.to_string());
                 }
             }
 
@@ -113,8 +143,7 @@ impl Compiler {
                                 next_file: &file,
                                 resource: &dep.resolver_resource,
                             }) {
-                                count += 1;
-                                build_with_pool(file, Some(dep.resolver_resource.clone()));
+                                pending.push_back((file, Some(dep.resolver_resource.clone())));
                             }
 
                             Self::create_empty_module(&dep_module_id)
@@ -135,12 +164,10 @@ impl Compiler {
                 }
                 module_graph.add_dependency(&module_id, &dep_module_id, dep.dependency);
             }
-            if count == 0 {
-                break;
-            }
         }
         drop(rs);
 
+        cancellation.check()?;
         if !errors.is_empty() {
             return Err(anyhow::anyhow!(BuildError::BuildTasksError { errors }));
         }
@@ -157,7 +184,8 @@ impl Compiler {
             // safe
             .unwrap();
         let external_script = resolved_resource.get_script();
-        let is_async = external_script.is_some();
+        // es module externals are dynamically imported, see get_external_target
+        let is_async = external_script.is_some() || external_name.starts_with("import(");
         let origin_path = resolved_resource.get_resolved_path();
         let path = format!("virtual:external_{}", origin_path);
         let mut file = File::new(path.clone(), context.clone());
@@ -269,27 +297,71 @@ __mako_require__.loadScript('{}', (e) => e.type === 'load' ? resolve() : reject(
         parent_resource: Option<ResolverResource>,
         context: Arc<Context>,
     ) -> Result<Module> {
+        let cancellation = &context.cancellation;
+        // the file dependencies and the emitted assets are registered again by the loaders
+        let module_id = ModuleId::new(file.path.to_string_lossy().to_string());
+        context.clear_file_dependencies(&module_id);
+        context.clear_emitted_assets(&module_id);
+
         // 1. load
+        let start = Instant::now();
         let mut file = file.clone();
         let mut content = load::Load::load(&file, context.clone())?;
+        let source_size = content_size(&content);
         let content = context.plugin_driver.load_transform(
             &mut content,
             &file.path.to_string_lossy(),
             &context,
         )?;
+        let size = content_size(&content);
         file.set_content(content);
+        let load_time = elapsed_ms(start);
 
         // 2. parse
+        cancellation.check()?;
+        let start = Instant::now();
         let mut ast = parse::Parse::parse(&file, context.clone())?;
+        let parse_time = elapsed_ms(start);
+
+        // the prebuilt scripts matched by no_parse are wrapped as is,
+        // they should not have any import or require
+        let no_parse = matches!(ast, ModuleAst::Script(_)) && context.is_no_parse(&file);
 
         // 3. transform
-        transform::Transform::transform(&mut ast, &file, context.clone())?;
+        cancellation.check()?;
+        let start = Instant::now();
+        if !no_parse {
+            transform::Transform::transform(&mut ast, &file, context.clone())?;
+        }
+        let transform_time = elapsed_ms(start);
 
         // 4. analyze deps + resolve
-        let deps = analyze_deps::AnalyzeDeps::analyze_deps(&ast, &file, context.clone())?;
+        cancellation.check()?;
+        let start = Instant::now();
+        let deps = if no_parse {
+            AnalyzeDepsResult::default()
+        } else {
+            analyze_deps::AnalyzeDeps::analyze_deps(&ast, &file, context.clone())?
+        };
+        let analyze_deps_time = elapsed_ms(start);
 
         // 5. create module
         let path = file.path.to_string_lossy().to_string();
+        context.plugin_driver.module_built(
+            &PluginModuleBuiltParams {
+                path: path.clone(),
+                source_size,
+                size,
+                durations: PluginModuleBuiltDurations {
+                    load: load_time,
+                    parse: parse_time,
+                    transform: transform_time,
+                    analyze_deps: analyze_deps_time,
+                },
+                dependencies: deps.resolved_deps.len(),
+            },
+            &context,
+        )?;
         let module_id = ModuleId::new(path.clone());
         let raw = file.get_content_raw();
         let is_entry = file.is_entry;
@@ -325,3 +397,56 @@ __mako_require__.loadScript('{}', (e) => e.type === 'load' ? resolve() : reject(
         Ok(module)
     }
 }
+
+fn content_size(content: &Content) -> usize {
+    match content {
+        Content::Js(JsContent { content, .. }) | Content::Css(content) => content.len(),
+        Content::Assets(asset) => asset.content.len(),
+    }
+}
+
+fn elapsed_ms(start: Instant) -> f64 {
+    start.elapsed().as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_helper::{build_snapshot, setup_compiler};
+
+    #[test]
+    fn test_build_cancelled() {
+        let compiler = setup_compiler("test/build/side-effects-flag", false);
+        compiler.cancel();
+        let err = compiler.build_entries().unwrap_err();
+        assert_eq!(err.to_string(), "Build cancelled.");
+        assert!(compiler
+            .context
+            .module_graph
+            .read()
+            .unwrap()
+            .modules()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_build_module_graph_sorted() {
+        let module_ids = || {
+            let compiler = setup_compiler("test/build/side-effects-flag", false);
+            compiler.build_entries().unwrap();
+            let module_graph = compiler.context.module_graph.read().unwrap();
+            module_graph.get_module_ids()
+        };
+        let first = module_ids();
+        let mut sorted = first.clone();
+        sorted.sort();
+        assert_eq!(first, sorted);
+        assert_eq!(first, module_ids());
+    }
+
+    #[test]
+    fn test_build_snapshots() {
+        insta::glob!("../test/snapshot", "*/mako.config.json", |config| {
+            insta::assert_snapshot!(build_snapshot(config.parent().unwrap()));
+        });
+    }
+}
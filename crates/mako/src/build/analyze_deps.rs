@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
@@ -7,7 +8,8 @@ use thiserror::Error;
 use crate::ast::error;
 use crate::ast::file::File;
 use crate::compiler::Context;
-use crate::module::{Dependency, ModuleAst};
+use crate::config::ChunkNaming;
+use crate::module::{Dependency, ModuleAst, ResolveType};
 use crate::resolve::{resolve, ResolverResource};
 
 #[derive(Debug, Error)]
@@ -41,7 +43,7 @@ impl AnalyzeDeps {
         crate::mako_profile_function!();
         let mut deps = match ast {
             ModuleAst::Script(ast) => ast.analyze_deps(context.clone()),
-            ModuleAst::Css(ast) => ast.analyze_deps(),
+            ModuleAst::Css(ast) => ast.analyze_deps(file.param("layer")),
             _ => vec![],
         };
         context.plugin_driver.before_resolve(&mut deps, &context)?;
@@ -50,7 +52,7 @@ impl AnalyzeDeps {
         let mut resolved_deps = vec![];
         let mut missing_deps = HashMap::new();
 
-        for dep in deps {
+        for mut dep in deps {
             let result = resolve(
                 &file.resolve_from(&context),
                 &dep,
@@ -59,6 +61,9 @@ impl AnalyzeDeps {
             );
             match result {
                 Ok(resolver_resource) => {
+                    if context.config.chunk_naming == ChunkNaming::Route {
+                        Self::name_route_chunk(&mut dep, &resolver_resource, &context);
+                    }
                     resolved_deps.push(ResolvedDep {
                         resolver_resource,
                         dependency: dep,
@@ -91,6 +96,27 @@ impl AnalyzeDeps {
         })
     }
 
+    /// Name the chunks of the dynamically imported pages after the page paths, e.g.
+    /// src/pages/user/settings.tsx => pages_user_settings, the magic comments take precedence
+    fn name_route_chunk(
+        dep: &mut Dependency,
+        resolver_resource: &ResolverResource,
+        context: &Arc<Context>,
+    ) {
+        let ResolveType::DynamicImport(import_options) = &mut dep.resolve_type else {
+            return;
+        };
+        if import_options.chunk_name.is_some() {
+            return;
+        }
+        let pages_dir = match &context.config.fs_routes {
+            Some(fs_routes) => context.root.join(&fs_routes.dir),
+            None => context.root.join("src/pages"),
+        };
+        let path = resolver_resource.get_resolved_path();
+        import_options.chunk_name = route_chunk_name(Path::new(&path), &pages_dir);
+    }
+
     fn check_deps(deps: &Vec<Dependency>, file: &File) -> Result<()> {
         for dep in deps {
             // webpack loader syntax is not supported
@@ -117,3 +143,54 @@ impl AnalyzeDeps {
         }
     }
 }
+
+fn route_chunk_name(path: &Path, pages_dir: &Path) -> Option<String> {
+    let relative = path.strip_prefix(pages_dir).ok()?.with_extension("");
+    let mut segments = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    // src/pages/user/index.tsx => pages_user
+    if segments.len() > 1 && segments.last().is_some_and(|s| s == "index") {
+        segments.pop();
+    }
+    let dir_name = pages_dir.file_name()?.to_string_lossy().to_string();
+    let name = std::iter::once(dir_name)
+        .chain(segments)
+        .collect::<Vec<_>>()
+        .join("_");
+    Some(name.replace(
+        |c: char| !c.is_ascii_alphanumeric() && c != '_' && c != '-',
+        "_",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::route_chunk_name;
+
+    #[test]
+    fn test_route_chunk_name() {
+        let pages_dir = Path::new("/root/src/pages");
+        let name = |path: &str| route_chunk_name(Path::new(path), pages_dir);
+        assert_eq!(
+            name("/root/src/pages/user/settings.tsx"),
+            Some("pages_user_settings".to_string())
+        );
+        assert_eq!(
+            name("/root/src/pages/user/index.tsx"),
+            Some("pages_user".to_string())
+        );
+        assert_eq!(
+            name("/root/src/pages/index.tsx"),
+            Some("pages_index".to_string())
+        );
+        assert_eq!(
+            name("/root/src/pages/user/[id].tsx"),
+            Some("pages_user__id_".to_string())
+        );
+        assert_eq!(name("/root/src/components/a.tsx"), None);
+    }
+}
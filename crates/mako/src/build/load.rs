@@ -1,21 +1,24 @@
-use std::io::Read;
+use std::io::{Cursor, Read};
 use std::path::Path;
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
+use image::ImageOutputFormat;
 use mdxjs::{compile, Options as MdxOptions};
 use regex::Regex;
 use serde_xml_rs::from_str as from_xml_str;
 use serde_yaml::{from_str as from_yaml_str, Value as YamlValue};
 use thiserror::Error;
 use toml::{from_str as from_toml_str, Value as TomlValue};
-use tracing::debug;
+use tracing::{debug, warn};
 
 use crate::ast::file::{Content, File, JsContent};
 use crate::compiler::Context;
-use crate::config::Mode;
+use crate::config::{AssetFingerprint, AssetRuleConfig, Mode, OutputConfig, Platform};
 use crate::plugin::PluginLoadParam;
-use crate::utils::create_cached_regex;
+use crate::utils::{base64_encode, create_cached_regex};
+use crate::visitors::css_scoped::{scoped_css_exports, SCOPED_CSS_PARAM};
+use crate::visitors::import_attributes::JSON_MODULE_PARAM;
 
 #[derive(Debug, Error)]
 enum LoadError {
@@ -29,6 +32,16 @@ enum LoadError {
     ToSvgrError { path: String, reason: String },
     #[error("Compile md error: {path:?}, reason: {reason:?}")]
     CompileMdError { path: String, reason: String },
+    #[error("Invalid json: {path:?}, reason: {reason:?}")]
+    InvalidJson { path: String, reason: String },
+    #[error("Decode image error: {path:?}, reason: {reason:?}")]
+    DecodeImageError { path: String, reason: String },
+    #[error("Invalid UTF-8 in {path}:{line}:{column}, please convert the file to UTF-8, or enable `lossyUtf8` to replace the invalid bytes")]
+    InvalidUtf8 {
+        path: String,
+        line: usize,
+        column: usize,
+    },
 }
 
 pub const JS_EXTENSIONS: [&str; 6] = ["js", "jsx", "ts", "tsx", "cjs", "mjs"];
@@ -37,12 +50,17 @@ const JSON_EXTENSIONS: [&str; 2] = ["json", "json5"];
 const YAML_EXTENSIONS: [&str; 2] = ["yaml", "yml"];
 const XML_EXTENSIONS: [&str; 1] = ["xml"];
 const WASM_EXTENSIONS: [&str; 1] = ["wasm"];
+const NODE_ADDON_EXTENSIONS: [&str; 1] = ["node"];
 const TOML_EXTENSIONS: [&str; 1] = ["toml"];
 const SVG_EXTENSIONS: [&str; 1] = ["svg"];
 const MD_EXTENSIONS: [&str; 2] = ["md", "mdx"];
+/// the images which could be imported with `?meta` and `?placeholder`
+const IMAGE_INFO_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "gif", "webp"];
 const UNSUPPORTED_EXTENSIONS: [&str; 2] = ["sass", "stylus"];
 
 const SVGR_NAMED_EXPORT: &str = r#"ReactComponent"#;
+/// the max width and height of the placeholder images
+const PLACEHOLDER_SIZE: u32 = 16;
 
 pub struct Load {}
 
@@ -65,10 +83,23 @@ impl Load {
             return Ok(Content::Js(JsContent {
                 content: r#"
 export function moduleToDom(css) {
+    if (__mako_require__.resolveCssUrls) {
+        css = __mako_require__.resolveCssUrls(css);
+    }
+    if (__mako_require__.addStyleSheet) {
+        return __mako_require__.addStyleSheet(css);
+    }
     var styleElement = document.createElement("style");
     styleElement.type = "text/css";
+    if (__mako_require__.nonce && __mako_require__.nonce()) {
+        styleElement.nonce = __mako_require__.nonce();
+    }
     styleElement.appendChild(document.createTextNode(css))
-    document.head.appendChild(styleElement);
+    if (__mako_require__.insertStyle) {
+        __mako_require__.insertStyle(styleElement);
+    } else {
+        document.head.appendChild(styleElement);
+    }
 }
                                 "#
                 .to_string(),
@@ -93,7 +124,7 @@ export function moduleToDom(css) {
 
         // ?raw
         if file.has_param("raw") {
-            let content = FileSystem::read_file(&file.pathname)?;
+            let content = FileSystem::read_file(&file.pathname, context.config.lossy_utf8)?;
             let content = serde_json::to_string(&content)?;
             return Ok(Content::Js(JsContent {
                 content: format!("module.exports = {}", content),
@@ -101,6 +132,40 @@ export function moduleToDom(css) {
             }));
         }
 
+        // ?meta, ?placeholder
+        if (file.has_param("meta") || file.has_param("placeholder"))
+            && IMAGE_INFO_EXTENSIONS.contains(&file.extname.as_str())
+        {
+            let asset_path = Self::handle_asset(file, true, true, context.clone())?;
+            return Ok(Content::Js(JsContent {
+                content: image_info_module(file, &asset_path)?,
+                ..Default::default()
+            }));
+        }
+
+        // ?json, import with { type: 'json' }
+        if file.has_param(JSON_MODULE_PARAM) {
+            let content = FileSystem::read_file(&file.pathname, context.config.lossy_utf8)?;
+            serde_json::from_str::<serde_json::Value>(&content).map_err(|e| {
+                anyhow!(LoadError::InvalidJson {
+                    path: file.path.to_string_lossy().to_string(),
+                    reason: e.to_string(),
+                })
+            })?;
+            return Ok(Content::Js(JsContent {
+                content: format!("module.exports = {}", content),
+                ..Default::default()
+            }));
+        }
+
+        // ?scoped, the scope attribute of the css is exported
+        if file.has_param(SCOPED_CSS_PARAM) {
+            return Ok(Content::Js(JsContent {
+                content: scoped_css_exports(&file.pathname, &context.root),
+                ..Default::default()
+            }));
+        }
+
         // js
         if JS_EXTENSIONS.contains(&file.extname.as_str()) {
             // entry with ?hmr
@@ -113,19 +178,20 @@ export function moduleToDom(css) {
                 );
                 return Ok(Content::Js(JsContent { content, is_jsx }));
             }
-            let content = FileSystem::read_file(&file.pathname)?;
+            let content = FileSystem::read_file(&file.pathname, context.config.lossy_utf8)?;
+            let content = strip_shebang(content);
             return Ok(Content::Js(JsContent { content, is_jsx }));
         }
 
         // css
         if CSS_EXTENSIONS.contains(&file.extname.as_str()) {
-            let content = FileSystem::read_file(&file.pathname)?;
+            let content = FileSystem::read_file(&file.pathname, context.config.lossy_utf8)?;
             return Ok(Content::Css(content));
         }
 
         // md & mdx
         if MD_EXTENSIONS.contains(&file.extname.as_str()) {
-            let content = FileSystem::read_file(&file.pathname)?;
+            let content = FileSystem::read_file(&file.pathname, context.config.lossy_utf8)?;
             let options = MdxOptions {
                 development: matches!(context.config.mode, Mode::Development),
                 ..Default::default()
@@ -146,7 +212,7 @@ export function moduleToDom(css) {
         // svg
         // TODO: Not all svg files need to be converted to React Component, unnecessary performance consumption here
         if SVG_EXTENSIONS.contains(&file.extname.as_str()) {
-            let content = FileSystem::read_file(&file.pathname)?;
+            let content = FileSystem::read_file(&file.pathname, context.config.lossy_utf8)?;
             let svgr_transformed = svgr_rs::transform(
                 content,
                 svgr_rs::Config {
@@ -171,7 +237,7 @@ export function moduleToDom(css) {
 
         // toml
         if TOML_EXTENSIONS.contains(&file.extname.as_str()) {
-            let content = FileSystem::read_file(&file.pathname)?;
+            let content = FileSystem::read_file(&file.pathname, context.config.lossy_utf8)?;
             let content = from_toml_str::<TomlValue>(&content)?;
             let content = serde_json::to_string(&content)?;
             return Ok(Content::Js(JsContent {
@@ -185,7 +251,7 @@ export function moduleToDom(css) {
             let final_file_name = format!(
                 "{}.{}.{}",
                 file.get_file_stem(),
-                file.get_content_hash()?,
+                file.get_content_hash(&context.config.output)?,
                 file.extname
             );
             context.emit_assets(
@@ -201,9 +267,23 @@ export function moduleToDom(css) {
             }));
         }
 
+        // node addons, required at runtime from the output dir
+        if NODE_ADDON_EXTENSIONS.contains(&file.extname.as_str())
+            && matches!(context.config.platform, Platform::Node)
+        {
+            let final_file_name = Self::emit_asset(file, context.clone());
+            return Ok(Content::Js(JsContent {
+                content: format!(
+                    "module.exports = require._requireNodeAddon(\"{}\")",
+                    final_file_name
+                ),
+                ..Default::default()
+            }));
+        }
+
         // xml
         if XML_EXTENSIONS.contains(&file.extname.as_str()) {
-            let content = FileSystem::read_file(&file.pathname)?;
+            let content = FileSystem::read_file(&file.pathname, context.config.lossy_utf8)?;
             let content = from_xml_str::<serde_json::Value>(&content)?;
             let content = serde_json::to_string(&content)?;
             return Ok(Content::Js(JsContent {
@@ -214,7 +294,7 @@ export function moduleToDom(css) {
 
         // yaml
         if YAML_EXTENSIONS.contains(&file.extname.as_str()) {
-            let content = FileSystem::read_file(&file.pathname)?;
+            let content = FileSystem::read_file(&file.pathname, context.config.lossy_utf8)?;
             let content = from_yaml_str::<YamlValue>(&content)?;
             let content = serde_json::to_string(&content)?;
             return Ok(Content::Js(JsContent {
@@ -225,7 +305,7 @@ export function moduleToDom(css) {
 
         // json
         if JSON_EXTENSIONS.contains(&file.extname.as_str()) {
-            let content = FileSystem::read_file(&file.pathname)?;
+            let content = FileSystem::read_file(&file.pathname, context.config.lossy_utf8)?;
             return Ok(Content::Js(JsContent {
                 content: format!("module.exports = {}", content),
                 ..Default::default()
@@ -246,13 +326,22 @@ export function moduleToDom(css) {
         limit: bool,
         context: Arc<Context>,
     ) -> Result<String> {
+        let transformed = context.plugin_driver.transform_asset(file, &context)?;
+        let file = transformed.as_ref().unwrap_or(file);
         let file_size = file
             .get_file_size()
             .map_err(|_| LoadError::ReadFileSizeError {
                 path: file.path.to_string_lossy().to_string(),
             })?;
         let emit_assets = || -> Result<String> {
-            let final_file_name = Self::emit_asset(file, context.clone());
+            let mut final_file_name = Self::emit_asset(file, context.clone());
+            if context.config.asset_fingerprint == AssetFingerprint::Query {
+                final_file_name = format!(
+                    "{}?v={}",
+                    final_file_name,
+                    file.get_content_hash(&context.config.output)?
+                );
+            }
             if inject_public_path {
                 Ok(format!("`${{require.publicPath}}{}`", final_file_name))
             } else {
@@ -269,10 +358,10 @@ export function moduleToDom(css) {
         let should_not_transform_base64 = inline_excludes_extensions
             .iter()
             .any(|regex| regex.is_match(&file.extname));
-        if !limit
-            || file_size > context.config.inline_limit.try_into().unwrap()
-            || should_not_transform_base64
-        {
+        let inline_limit = Self::asset_rule(file, &context)
+            .and_then(|rule| rule.inline_limit)
+            .unwrap_or(context.config.inline_limit);
+        if !limit || file_size > inline_limit.try_into().unwrap() || should_not_transform_base64 {
             emit_assets()
         } else {
             let base64_result = file.get_base64();
@@ -291,24 +380,295 @@ export function moduleToDom(css) {
 
     pub fn emit_asset(file: &File, context: Arc<Context>) -> String {
         let path = file.pathname.to_string_lossy().to_string();
-        let final_file_name = format!(
-            "{}.{}.{}",
-            file.get_file_stem(),
-            file.get_content_hash().unwrap(),
-            file.extname
-        );
+        let template = Self::asset_rule(file, &context)
+            .and_then(|rule| rule.filename.as_deref())
+            .unwrap_or(match context.config.asset_fingerprint {
+                AssetFingerprint::Filename => "[name].[hash].[ext]",
+                // the hash is added to the references by handle_asset
+                AssetFingerprint::Query => "[name].[ext]",
+            });
+        let final_file_name = render_asset_filename(template, file, &context.config.output);
         context.emit_assets(path, final_file_name.clone());
         final_file_name
     }
+
+    /// The first rule of config.asset_rules matching the mime type of the asset, the assets
+    /// referenced from the scripts and the styles share the rules
+    fn asset_rule<'a>(file: &File, context: &'a Arc<Context>) -> Option<&'a AssetRuleConfig> {
+        if context.config.asset_rules.is_empty() {
+            return None;
+        }
+        let mime = mime_guess::from_path(&file.pathname).first()?;
+        context
+            .config
+            .asset_rules
+            .iter()
+            .find(|rule| rule.matches(mime.essence_str()))
+    }
+}
+
+/// Export `{ src, width, height }` of the image, with `format` for `?meta` and `placeholder`
+/// for `?placeholder`, both of them for `?meta&placeholder`
+fn image_info_module(file: &File, asset_path: &str) -> Result<String> {
+    let (width, height, format) = image_meta(&file.pathname)?;
+    let mut fields = vec![
+        format!("src: {}", asset_path),
+        format!("width: {}", width),
+        format!("height: {}", height),
+    ];
+    if file.has_param("meta") {
+        fields.push(format!("format: {}", serde_json::to_string(&format)?));
+    }
+    if file.has_param("placeholder") {
+        let placeholder = image_placeholder(&file.pathname)?;
+        fields.push(format!(
+            "placeholder: {}",
+            serde_json::to_string(&placeholder)?
+        ));
+    }
+    Ok(format!("module.exports = {{ {} }};", fields.join(", ")))
+}
+
+fn decode_image_error(path: &Path, reason: String) -> anyhow::Error {
+    anyhow!(LoadError::DecodeImageError {
+        path: path.to_string_lossy().to_string(),
+        reason,
+    })
+}
+
+/// Decode the image and return a tiny blurred png of it as a data url,
+/// which could be shown before the image is loaded
+fn image_placeholder(path: &Path) -> Result<String> {
+    let image = image::open(path).map_err(|e| decode_image_error(path, e.to_string()))?;
+    let thumbnail = image
+        .thumbnail(PLACEHOLDER_SIZE, PLACEHOLDER_SIZE)
+        .blur(1.0);
+    let mut buf = vec![];
+    thumbnail
+        .write_to(&mut Cursor::new(&mut buf), ImageOutputFormat::Png)
+        .map_err(|e| decode_image_error(path, e.to_string()))?;
+    Ok(format!("data:image/png;base64,{}", base64_encode(buf)))
+}
+
+/// Read the dimensions and the format of the image from its header,
+/// without decoding the pixels
+fn image_meta(path: &Path) -> Result<(u32, u32, String)> {
+    let to_error = |reason: String| decode_image_error(path, reason);
+    let reader = image::io::Reader::open(path)
+        .and_then(|reader| reader.with_guessed_format())
+        .map_err(|e| to_error(e.to_string()))?;
+    let format = reader
+        .format()
+        .and_then(|format| format.extensions_str().first())
+        .ok_or_else(|| to_error("unknown image format".to_string()))?
+        .to_string();
+    let (width, height) = reader
+        .into_dimensions()
+        .map_err(|e| to_error(e.to_string()))?;
+    Ok((width, height, format))
 }
 
 pub struct FileSystem {}
 
 impl FileSystem {
-    pub fn read_file<P: AsRef<Path>>(path: P) -> Result<String> {
+    /// Read file as utf-8 text with the BOM stripped, invalid utf-8 bytes are
+    /// replaced with U+FFFD with a warning when `lossy` is true, otherwise an error is returned
+    pub fn read_file<P: AsRef<Path>>(path: P, lossy: bool) -> Result<String> {
         let mut file = std::fs::File::open(path.as_ref())?;
         let mut buf = vec![];
         file.read_to_end(&mut buf)?;
-        Ok(String::from_utf8_lossy(&buf).to_string())
+        let path = path.as_ref().to_string_lossy().to_string();
+        match decode_text(&buf, false) {
+            Ok(text) => Ok(text),
+            Err((line, column)) if lossy => {
+                warn!(
+                    "Invalid UTF-8 in {}:{}:{}, the invalid bytes are replaced with U+FFFD",
+                    path, line, column
+                );
+                Ok(decode_text(&buf, true).unwrap())
+            }
+            Err((line, column)) => Err(anyhow!(LoadError::InvalidUtf8 { path, line, column })),
+        }
+    }
+}
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Returns the 1-based line and column of the first invalid byte on error
+fn decode_text(buf: &[u8], lossy: bool) -> std::result::Result<String, (usize, usize)> {
+    let buf = buf.strip_prefix(UTF8_BOM).unwrap_or(buf);
+    match std::str::from_utf8(buf) {
+        Ok(text) => Ok(text.to_string()),
+        Err(_) if lossy => Ok(String::from_utf8_lossy(buf).to_string()),
+        Err(e) => {
+            let valid = &buf[..e.valid_up_to()];
+            let line = valid.iter().filter(|b| **b == b'\n').count() + 1;
+            let line_start = valid.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+            let column = String::from_utf8_lossy(&valid[line_start..])
+                .chars()
+                .count()
+                + 1;
+            Err((line, column))
+        }
+    }
+}
+
+/// Replace the shebang line of cli scripts with an empty line, so that they can be
+/// bundled as normal modules, the line numbers are kept for source maps
+fn strip_shebang(content: String) -> String {
+    if content.starts_with("#!") {
+        let end = content.find('\n').unwrap_or(content.len());
+        content[end..].to_string()
+    } else {
+        content
+    }
+}
+
+fn render_asset_filename(template: &str, file: &File, output: &OutputConfig) -> String {
+    let mut filename = template
+        .replace("[name]", &file.get_file_stem())
+        .replace("[ext]", &file.extname);
+    if filename.contains("[hash]") {
+        filename = filename.replace("[hash]", &file.get_content_hash(output).unwrap());
+    }
+    filename
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use super::{
+        decode_text, image_info_module, image_meta, image_placeholder, strip_shebang, FileSystem,
+        Load,
+    };
+    use crate::ast::file::{Content, File};
+    use crate::compiler::Context;
+    use crate::config::{AssetRuleConfig, Config, Platform};
+
+    #[test]
+    fn test_decode_text_strips_bom() {
+        assert_eq!(
+            decode_text(b"\xEF\xBB\xBFconst a = 1;", false).unwrap(),
+            "const a = 1;"
+        );
+    }
+
+    #[test]
+    fn test_decode_text_invalid_utf8() {
+        let buf = b"const a = 1;\nconst b = '\xFF';";
+        assert_eq!(decode_text(buf, false), Err((2, 12)));
+        assert_eq!(
+            decode_text(buf, true).unwrap(),
+            "const a = 1;\nconst b = '\u{FFFD}';"
+        );
+    }
+
+    #[test]
+    fn test_asset_rule_matches() {
+        let rule = |mime: &str| AssetRuleConfig {
+            mime: mime.to_string(),
+            filename: None,
+            inline_limit: None,
+        };
+        assert!(rule("font/*").matches("font/woff2"));
+        assert!(!rule("font/*").matches("image/png"));
+        assert!(rule("image/svg+xml").matches("image/svg+xml"));
+        assert!(!rule("image/svg+xml").matches("image/png"));
+    }
+
+    #[test]
+    fn test_image_placeholder() {
+        let path = Path::new("test/build/placeholder/index.png");
+        let placeholder = image_placeholder(path).unwrap();
+        assert!(placeholder.starts_with("data:image/png;base64,"));
+    }
+
+    #[test]
+    fn test_image_meta() {
+        let path = Path::new("test/build/placeholder/index.png");
+        let (width, height, format) = image_meta(path).unwrap();
+        assert_eq!((width, height), (32, 24));
+        assert_eq!(format, "png");
+    }
+
+    #[test]
+    fn test_image_info_module() {
+        let path = std::env::current_dir()
+            .unwrap()
+            .join("test/build/placeholder/index.png");
+        let file = File::new(
+            format!("{}?meta&placeholder", path.to_string_lossy()),
+            Arc::new(Context::default()),
+        );
+        let content = image_info_module(&file, "\"index.png\"").unwrap();
+        assert!(content.starts_with(r#"module.exports = { src: "index.png", width: 32, "#));
+        assert!(content.contains(r#"height: 24, "#));
+        assert!(content.contains(r#"format: "png", "#));
+        assert!(content.contains(r#"placeholder: "data:image/png;base64,"#));
+    }
+
+    #[test]
+    fn test_load_node_addon() {
+        let context = Arc::new(Context {
+            config: Config {
+                platform: Platform::Node,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let path = std::env::current_dir()
+            .unwrap()
+            .join("test/build/node-addon/addon.node");
+        let file = File::new(path.to_string_lossy().to_string(), context.clone());
+        let Content::Js(js) = Load::load(&file, context.clone()).unwrap() else {
+            panic!("the node addon should be loaded as js");
+        };
+        let assets_info = context.assets_info.lock().unwrap();
+        let file_name = assets_info
+            .get(&path.to_string_lossy().to_string())
+            .unwrap();
+        assert!(file_name.ends_with(".node"));
+        assert_eq!(
+            js.content,
+            format!(
+                "module.exports = require._requireNodeAddon(\"{}\")",
+                file_name
+            )
+        );
+    }
+
+    #[test]
+    fn test_read_file_non_utf8() {
+        let path = Path::new("test/build/non-utf8/gbk.js");
+        assert_eq!(
+            FileSystem::read_file(path, true).unwrap(),
+            "console.log('\u{FFFD}\u{FFFD}');\n"
+        );
+        let err = FileSystem::read_file(path, false).unwrap_err();
+        assert!(err.to_string().contains("gbk.js:1:14"));
+    }
+
+    #[test]
+    fn test_load_non_utf8_by_default() {
+        let context = Arc::new(Context::default());
+        let path = std::env::current_dir()
+            .unwrap()
+            .join("test/build/non-utf8/gbk.js");
+        let file = File::new(path.to_string_lossy().to_string(), context.clone());
+        let Content::Js(js) = Load::load(&file, context).unwrap() else {
+            panic!("the js file should be loaded as js");
+        };
+        assert!(js.content.contains('\u{FFFD}'));
+    }
+
+    #[test]
+    fn test_strip_shebang() {
+        assert_eq!(
+            strip_shebang("#!/usr/bin/env node\nrequire('./cli');".to_string()),
+            "\nrequire('./cli');"
+        );
+        assert_eq!(strip_shebang("let a = '#!';".to_string()), "let a = '#!';");
     }
 }
@@ -33,6 +33,10 @@ pub enum ParseError {
     InvalidExpression { message: String, path: String },
 }
 
+/// `import sheet from "./a.css?sheet"`, the css is exported as a `CSSStyleSheet` for the
+/// `adoptedStyleSheets` of the web components instead of being injected
+pub const CSS_SHEET_PARAM: &str = "sheet";
+
 pub struct Parse {}
 
 impl Parse {
@@ -63,6 +67,10 @@ impl Parse {
             let is_modules = file.has_param("modules");
             let is_asmodule = file.has_param("asmodule");
             let css_modules = is_modules || is_asmodule;
+            // ?sheet
+            if file.has_param(CSS_SHEET_PARAM) {
+                return Self::parse_css_sheet(file, context);
+            }
             // ?asmodule
             if is_asmodule {
                 let mut ast = CssAst::new(file, context.clone(), css_modules)?;
@@ -146,4 +154,62 @@ moduleToDom(`
             path: file.path.to_string_lossy().to_string(),
         }))
     }
+
+    // the css goes through the css-related process like the inline css, and the css of its
+    // @imports are imported with ?sheet too and prepended, since the @import rules are not
+    // allowed in the constructable stylesheets
+    fn parse_css_sheet(file: &File, context: Arc<Context>) -> Result<ModuleAst> {
+        let mut ast = ModuleAst::Css(CssAst::new(file, context.clone(), false)?);
+        Transform::transform(&mut ast, file, context.clone())?;
+        let deps = AnalyzeDeps::analyze_deps(&ast, file, context.clone())?;
+        if !deps.missing_deps.is_empty() {
+            return Err(anyhow!(ParseError::InlineCSSMissingDeps {
+                path: file.path.to_string_lossy().to_string(),
+            }));
+        }
+        let imports = deps
+            .resolved_deps
+            .iter()
+            .enumerate()
+            .map(|(i, dep)| {
+                let path = dep.resolver_resource.get_resolved_path();
+                let separator = if path.contains('?') { "&" } else { "?" };
+                format!(
+                    "import {{ cssText as cssText{} }} from '{}{}{}';",
+                    i, path, separator, CSS_SHEET_PARAM
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        let css_texts = (0..deps.resolved_deps.len())
+            .map(|i| format!("cssText{} + ", i))
+            .collect::<String>();
+        let ast = ast.as_css_mut();
+        // transform (remove @imports)
+        ast.ast.visit_mut_with(&mut CSSImports {});
+        let code = ast.generate(context.clone())?.code;
+        let mut file = file.clone();
+        file.set_content(Content::Js(JsContent {
+            content: format!(
+                r#"
+{}
+export var cssText = {}{};
+var sheet = null;
+if (typeof CSSStyleSheet !== 'undefined') {{
+    sheet = new CSSStyleSheet();
+    sheet.replaceSync(
+        __mako_require__.resolveCssUrls ? __mako_require__.resolveCssUrls(cssText) : cssText
+    );
+}}
+export default sheet;
+                "#,
+                imports,
+                css_texts,
+                serde_json::to_string(&code)?
+            ),
+            ..Default::default()
+        }));
+        let ast = JsAst::new(&file, context)?;
+        Ok(ModuleAst::Script(ast))
+    }
 }
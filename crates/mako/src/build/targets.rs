@@ -1,9 +1,296 @@
 use std::collections::HashMap;
 
+use swc_core::css::compat::feature::Features;
+use swc_core::ecma::ast::EsVersion;
 use swc_core::ecma::preset_env::Targets as SwcPresetEnvTargets;
 
+use crate::config::Platform;
+
 pub fn swc_preset_env_targets_from_map(map: HashMap<String, f32>) -> SwcPresetEnvTargets {
     let serialized_str = serde_json::to_string(&map).unwrap();
     let targets: SwcPresetEnvTargets = serde_json::from_str(&serialized_str).unwrap();
     targets
 }
+
+/// The first versions supporting all the syntax of the es version, ref: https://compat-table.github.io
+fn es_version_targets(es_version: EsVersion) -> &'static [(&'static str, f32)] {
+    match es_version {
+        EsVersion::Es3 | EsVersion::Es5 => &[("ie", 11.0), ("node", 0.12)],
+        EsVersion::Es2015 => &[
+            ("chrome", 51.0),
+            ("edge", 15.0),
+            ("firefox", 54.0),
+            ("safari", 10.0),
+            ("ios", 10.0),
+            ("node", 6.5),
+        ],
+        EsVersion::Es2016 => &[
+            ("chrome", 52.0),
+            ("edge", 15.0),
+            ("firefox", 54.0),
+            ("safari", 10.1),
+            ("ios", 10.3),
+            ("node", 7.0),
+        ],
+        EsVersion::Es2017 => &[
+            ("chrome", 58.0),
+            ("edge", 16.0),
+            ("firefox", 54.0),
+            ("safari", 11.0),
+            ("ios", 11.0),
+            ("node", 8.0),
+        ],
+        EsVersion::Es2018 => &[
+            ("chrome", 64.0),
+            ("edge", 79.0),
+            ("firefox", 78.0),
+            ("safari", 12.0),
+            ("ios", 12.0),
+            ("node", 10.0),
+        ],
+        EsVersion::Es2019 => &[
+            ("chrome", 73.0),
+            ("edge", 79.0),
+            ("firefox", 78.0),
+            ("safari", 12.1),
+            ("ios", 12.2),
+            ("node", 12.0),
+        ],
+        EsVersion::Es2020 => &[
+            ("chrome", 80.0),
+            ("edge", 80.0),
+            ("firefox", 80.0),
+            ("safari", 14.1),
+            ("ios", 14.5),
+            ("node", 14.0),
+        ],
+        EsVersion::Es2021 => &[
+            ("chrome", 85.0),
+            ("edge", 85.0),
+            ("firefox", 80.0),
+            ("safari", 14.1),
+            ("ios", 14.5),
+            ("node", 15.0),
+        ],
+        EsVersion::Es2022 => &[
+            ("chrome", 94.0),
+            ("edge", 94.0),
+            ("firefox", 93.0),
+            ("safari", 16.4),
+            ("ios", 16.4),
+            ("node", 16.11),
+        ],
+        _ => &[],
+    }
+}
+
+/// The targets of the js syntax lowering, the engines of the `targets` are capped to the
+/// versions supporting `output.esVersion`, so the syntax newer than it is lowered as well,
+/// the engines not listed are not added except ie for es5 which none of the others can express
+pub fn js_targets(
+    targets: &HashMap<String, f32>,
+    es_version: EsVersion,
+    platform: &Platform,
+) -> HashMap<String, f32> {
+    let mut targets = targets.clone();
+    let is_es5 = matches!(es_version, EsVersion::Es3 | EsVersion::Es5);
+    es_version_targets(es_version)
+        .iter()
+        .filter(|(engine, _)| (*engine == "node") == matches!(platform, Platform::Node))
+        .for_each(|(engine, version)| match targets.get_mut(*engine) {
+            Some(v) => *v = v.min(*version),
+            None if is_es5 => {
+                targets.insert(engine.to_string(), *version);
+            }
+            None => {}
+        });
+    targets
+}
+
+/// The modern css features which are lowered when any of the targets doesn't support them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CssFeature {
+    Nesting,
+    CustomMedia,
+    MediaQueryRanges,
+    ColorHexAlpha,
+    ColorHwb,
+    ColorMix,
+    /// the physical longhands like margin-inline-start and inline-size
+    LogicalPropertiesLonghands,
+    /// the shorthands like margin-inline and the inset properties
+    LogicalProperties,
+}
+
+impl CssFeature {
+    /// The first browser versions supporting the feature, ref: https://caniuse.com
+    /// the browsers not listed are treated as unsupported
+    fn supported_versions(&self) -> &'static [(&'static str, f32)] {
+        match self {
+            CssFeature::Nesting => &[
+                ("chrome", 120.0),
+                ("edge", 120.0),
+                ("firefox", 117.0),
+                ("safari", 17.2),
+                ("ios", 17.2),
+                ("opera", 106.0),
+            ],
+            // not supported by any browser yet
+            CssFeature::CustomMedia => &[],
+            CssFeature::MediaQueryRanges => &[
+                ("chrome", 104.0),
+                ("edge", 104.0),
+                ("firefox", 63.0),
+                ("safari", 16.4),
+                ("ios", 16.4),
+                ("opera", 91.0),
+            ],
+            CssFeature::ColorHexAlpha => &[
+                ("chrome", 62.0),
+                ("edge", 79.0),
+                ("firefox", 49.0),
+                ("safari", 10.0),
+                ("ios", 10.0),
+                ("opera", 49.0),
+            ],
+            CssFeature::ColorHwb => &[
+                ("chrome", 101.0),
+                ("edge", 101.0),
+                ("firefox", 96.0),
+                ("safari", 15.0),
+                ("ios", 15.0),
+                ("opera", 87.0),
+            ],
+            CssFeature::ColorMix => &[
+                ("chrome", 111.0),
+                ("edge", 111.0),
+                ("firefox", 113.0),
+                ("safari", 16.2),
+                ("ios", 16.2),
+                ("opera", 97.0),
+            ],
+            CssFeature::LogicalPropertiesLonghands => &[
+                ("chrome", 69.0),
+                ("edge", 79.0),
+                ("firefox", 41.0),
+                ("safari", 12.1),
+                ("ios", 12.2),
+                ("opera", 56.0),
+            ],
+            CssFeature::LogicalProperties => &[
+                ("chrome", 87.0),
+                ("edge", 87.0),
+                ("firefox", 66.0),
+                ("safari", 14.1),
+                ("ios", 14.5),
+                ("opera", 73.0),
+            ],
+        }
+    }
+
+    pub fn should_lower(&self, targets: &HashMap<String, f32>) -> bool {
+        let supported_versions = self.supported_versions();
+        targets
+            .iter()
+            // node doesn't run css
+            .filter(|(browser, _)| browser.as_str() != "node")
+            .any(|(browser, version)| {
+                match supported_versions.iter().find(|(name, _)| name == browser) {
+                    Some((_, supported)) => version < supported,
+                    None => true,
+                }
+            })
+    }
+}
+
+/// The features lowered by the css compat compiler of swc
+pub fn swc_css_compat_features(targets: &HashMap<String, f32>) -> Features {
+    let mut features = Features::empty();
+    for (feature, flag) in [
+        (CssFeature::Nesting, Features::NESTING),
+        (CssFeature::CustomMedia, Features::CUSTOM_MEDIA),
+        (CssFeature::MediaQueryRanges, Features::MEDIA_QUERY_RANGES),
+        (CssFeature::ColorHexAlpha, Features::COLOR_HEX_ALPHA),
+        (CssFeature::ColorHwb, Features::COLOR_HWB),
+    ] {
+        if feature.should_lower(targets) {
+            features |= flag;
+        }
+    }
+    features
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use swc_core::css::compat::feature::Features;
+    use swc_core::ecma::ast::EsVersion;
+
+    use super::{js_targets, swc_css_compat_features, CssFeature};
+    use crate::config::Platform;
+
+    fn targets(items: &[(&str, f32)]) -> HashMap<String, f32> {
+        items
+            .iter()
+            .map(|(browser, version)| (browser.to_string(), *version))
+            .collect()
+    }
+
+    #[test]
+    fn test_should_lower() {
+        let chrome_80 = targets(&[("chrome", 80.0)]);
+        assert!(CssFeature::Nesting.should_lower(&chrome_80));
+        assert!(CssFeature::LogicalProperties.should_lower(&chrome_80));
+        assert!(!CssFeature::LogicalPropertiesLonghands.should_lower(&chrome_80));
+        assert!(!CssFeature::ColorHexAlpha.should_lower(&chrome_80));
+        let modern = targets(&[("chrome", 120.0), ("safari", 17.2)]);
+        assert!(!CssFeature::Nesting.should_lower(&modern));
+        assert!(CssFeature::CustomMedia.should_lower(&modern));
+        // unknown browsers are treated as unsupported
+        assert!(CssFeature::ColorMix.should_lower(&targets(&[("ie", 11.0)])));
+        assert!(!CssFeature::ColorMix.should_lower(&targets(&[("node", 14.0)])));
+    }
+
+    #[test]
+    fn test_swc_css_compat_features() {
+        let features = swc_css_compat_features(&targets(&[("chrome", 80.0)]));
+        assert!(features.contains(Features::NESTING));
+        assert!(features.contains(Features::CUSTOM_MEDIA));
+        assert!(!features.contains(Features::COLOR_HEX_ALPHA));
+    }
+
+    #[test]
+    fn test_js_targets() {
+        let js = js_targets(
+            &targets(&[("chrome", 120.0), ("firefox", 50.0)]),
+            EsVersion::Es2017,
+            &Platform::Browser,
+        );
+        assert_eq!(js.get("chrome"), Some(&58.0));
+        assert_eq!(js.get("firefox"), Some(&50.0));
+        // the engines not listed in the targets are not added
+        assert_eq!(js.get("safari"), None);
+        assert_eq!(js.get("node"), None);
+        let js = js_targets(
+            &targets(&[("chrome", 120.0)]),
+            EsVersion::Es2022,
+            &Platform::Browser,
+        );
+        assert_eq!(js, targets(&[("chrome", 94.0)]));
+        let js = js_targets(
+            &targets(&[("chrome", 120.0)]),
+            EsVersion::Es5,
+            &Platform::Browser,
+        );
+        assert_eq!(js, targets(&[("chrome", 120.0), ("ie", 11.0)]));
+        let js = js_targets(&targets(&[("node", 14.0)]), EsVersion::Es5, &Platform::Node);
+        assert_eq!(js, targets(&[("node", 0.12)]));
+        let js = js_targets(
+            &targets(&[("chrome", 80.0)]),
+            EsVersion::EsNext,
+            &Platform::Browser,
+        );
+        assert_eq!(js, targets(&[("chrome", 80.0)]));
+    }
+}
@@ -6,7 +6,7 @@ use swc_core::common::errors::HANDLER;
 use swc_core::common::GLOBALS;
 use swc_core::css::ast::{AtRule, AtRulePrelude, ImportHref, Rule, Str, Stylesheet, UrlValue};
 use swc_core::css::compat::compiler::{self, Compiler};
-use swc_core::css::{compat as swc_css_compat, prefixer, visit as swc_css_visit};
+use swc_core::css::{prefixer, visit as swc_css_visit};
 use swc_core::ecma::preset_env::{self as swc_preset_env};
 use swc_core::ecma::transforms::base::feature::FeatureFlag;
 use swc_core::ecma::transforms::base::fixer::paren_remover;
@@ -21,22 +21,27 @@ use swc_core::ecma::visit::{Fold, VisitMut};
 use crate::ast::css_ast::CssAst;
 use crate::ast::file::File;
 use crate::build::targets;
-use crate::build::targets::swc_preset_env_targets_from_map;
+use crate::build::targets::{swc_css_compat_features, swc_preset_env_targets_from_map, CssFeature};
 use crate::compiler::Context;
-use crate::config::Mode;
+use crate::config::{LogicalPropertiesDir, Mode};
 use crate::features;
 use crate::module::ModuleAst;
 use crate::plugin::PluginTransformJsParam;
 use crate::plugins::context_module::ContextModuleVisitor;
 use crate::visitors::amd_define_overrides::amd_define_overrides;
 use crate::visitors::css_assets::CSSAssets;
+use crate::visitors::css_color_mix::CSSColorMix;
 use crate::visitors::css_flexbugs::CSSFlexbugs;
+use crate::visitors::css_layers::wrap_in_layer;
+use crate::visitors::css_logical_properties::CSSLogicalProperties;
 use crate::visitors::css_px2rem::Px2Rem;
+use crate::visitors::css_scoped::{scope_id, CSSScoped, SCOPED_STYLE_PARAM};
 use crate::visitors::default_export_namer::DefaultExportNamer;
 use crate::visitors::dynamic_import_to_require::DynamicImportToRequire;
 use crate::visitors::env_replacer::{build_env_map, EnvReplacer};
 use crate::visitors::fix_helper_inject_position::FixHelperInjectPosition;
 use crate::visitors::fix_symbol_conflict::FixSymbolConflict;
+use crate::visitors::import_attributes::ImportAttributes;
 use crate::visitors::import_meta_env_replacer::ImportMetaEnvReplacer;
 use crate::visitors::import_template_to_string_literal::ImportTemplateToStringLiteral;
 use crate::visitors::new_url_assets::NewUrlAssets;
@@ -106,8 +111,11 @@ impl Transform {
                                 // since when use this in js, it will remove all unused imports
                                 // which is not expected as what webpack does
                                 if is_ts {
-                                    visitors
-                                        .push(Box::new(ts_strip(unresolved_mark, top_level_mark)));
+                                    visitors.push(Box::new(ts_strip(
+                                        context.config.typescript.clone(),
+                                        unresolved_mark,
+                                        top_level_mark,
+                                    )));
                                 }
                                 // named default export
                                 if context.args.watch && !file.is_under_node_modules && is_jsx {
@@ -135,6 +143,10 @@ impl Transform {
                                 }
                                 {
                                     let mut define = context.config.define.clone();
+                                    let env = context.env.read().unwrap().clone();
+                                    for (k, v) in features::dotenv::Dotenv::define(&env) {
+                                        define.entry(k).or_insert(v);
+                                    }
                                     let mode = context.config.mode.to_string();
                                     define
                                         .entry("process.env.NODE_ENV".to_string())
@@ -142,7 +154,7 @@ impl Transform {
                                     let env_map = build_env_map(define, &context)?;
                                     visitors
                                         .push(Box::new(EnvReplacer::new(env_map, unresolved_mark)));
-                                    visitors.push(Box::new(ImportMetaEnvReplacer::new(mode)));
+                                    visitors.push(Box::new(ImportMetaEnvReplacer::new(mode, env)));
                                 }
                                 visitors.push(Box::new(TryResolve {
                                     path: file.path.to_string_lossy().to_string(),
@@ -156,6 +168,9 @@ impl Transform {
                                     unresolved_mark,
                                     top_level_mark,
                                 )));
+                                // ImportAttributes must be before VirtualCSSModules
+                                // since the css module scripts are not css modules
+                                visitors.push(Box::new(ImportAttributes {}));
                                 visitors.push(Box::new(VirtualCSSModules {
                                     auto_css_modules: context.config.auto_css_modules,
                                     unresolved_mark,
@@ -170,15 +185,13 @@ impl Transform {
                                         unresolved_mark,
                                     )));
                                 }
-                                if matches!(context.config.platform, crate::config::Platform::Node)
-                                {
-                                    visitors.push(Box::new(
-                                        features::node::MockFilenameAndDirname {
-                                            unresolved_mark,
-                                            current_path: file.path.clone(),
-                                            context: context.clone(),
-                                        },
-                                    ));
+                                let node_shims = features::node::NodeShims::new(
+                                    unresolved_mark,
+                                    file.path.clone(),
+                                    context.clone(),
+                                );
+                                if node_shims.is_enabled() {
+                                    visitors.push(Box::new(node_shims));
                                 }
 
                                 // folders
@@ -225,7 +238,11 @@ impl Transform {
                                         swc_preset_env::Config {
                                             mode: Some(swc_preset_env::Mode::Entry),
                                             targets: Some(swc_preset_env_targets_from_map(
-                                                context.config.targets.clone(),
+                                                targets::js_targets(
+                                                    &context.config.targets,
+                                                    context.config.output.es_version,
+                                                    &context.config.platform,
+                                                ),
                                             )),
                                             ..Default::default()
                                         },
@@ -265,8 +282,24 @@ impl Transform {
                 // replace @import url() to @import before CSSUrlReplacer
                 import_url_to_href(&mut ast.ast);
                 let mut visitors: Vec<Box<dyn swc_css_visit::VisitMut>> = vec![];
+                // lower the modern css features which the targets don't support,
+                // color-mix() goes first since the mixed colors may be lowered by the compat compiler
+                let targets = &context.config.targets;
+                if CssFeature::ColorMix.should_lower(targets) {
+                    visitors.push(Box::new(CSSColorMix {}));
+                }
+                if let Some(dir) = context.config.logical_properties_dir {
+                    let logical_properties = CSSLogicalProperties {
+                        longhands: CssFeature::LogicalPropertiesLonghands.should_lower(targets),
+                        shorthands: CssFeature::LogicalProperties.should_lower(targets),
+                        rtl: dir == LogicalPropertiesDir::Rtl,
+                    };
+                    if logical_properties.longhands || logical_properties.shorthands {
+                        visitors.push(Box::new(logical_properties));
+                    }
+                }
                 visitors.push(Box::new(Compiler::new(compiler::Config {
-                    process: swc_css_compat::feature::Features::NESTING,
+                    process: swc_css_compat_features(targets),
                 })));
                 let path = file.path.to_string_lossy().to_string();
                 visitors.push(Box::new(CSSAssets {
@@ -283,6 +316,12 @@ impl Transform {
                         context.config.px2rem.as_ref().unwrap().clone(),
                     )));
                 }
+                // import scope from "./a.css?scoped"
+                if file.has_param(SCOPED_STYLE_PARAM) {
+                    visitors.push(Box::new(CSSScoped {
+                        scope_id: scope_id(&file.pathname, &context.root),
+                    }));
+                }
                 // prefixer
                 visitors.push(Box::new(prefixer::prefixer(prefixer::options::Options {
                     env: Some(targets::swc_preset_env_targets_from_map(
@@ -297,6 +336,11 @@ impl Transform {
                     CssAst::compile_css_modules(file.pathname.to_str().unwrap(), &mut ast.ast);
                 }
 
+                // the css imported with layer(), e.g. a.css?layer=base
+                if let Some(layer) = file.param("layer") {
+                    wrap_in_layer(&mut ast.ast, &layer);
+                }
+
                 Ok(())
             }
             ModuleAst::None => Ok(()),
@@ -308,10 +352,26 @@ impl Context {
     pub fn assumptions_for(&self, file: &File) -> Assumptions {
         let is_ts = file.extname == "ts" || file.extname == "tsx";
 
+        let use_define_for_class_fields = if is_ts {
+            self.config
+                .typescript
+                .use_define_for_class_fields
+                .unwrap_or(self.config.use_define_for_class_fields)
+        } else {
+            self.config.use_define_for_class_fields
+        };
+        let compat = &self.config.compat;
+
         let mut assumptions = Assumptions::default();
-        assumptions.set_public_class_fields |= !self.config.use_define_for_class_fields;
+        assumptions.set_public_class_fields |=
+            !use_define_for_class_fields || compat.class_properties_loose;
         if is_ts {
-            assumptions.set_class_methods |= !self.config.use_define_for_class_fields;
+            assumptions.set_class_methods |= !use_define_for_class_fields;
+        }
+        assumptions.private_fields_as_properties |= compat.private_methods_loose;
+        if compat.optional_chaining_loose {
+            assumptions.no_document_all = true;
+            assumptions.pure_getters = true;
         }
         assumptions
     }
@@ -347,3 +407,26 @@ fn import_url_to_href(ast: &mut Stylesheet) {
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assumptions_for() {
+        let mut context = Context::default();
+        context.config.typescript.use_define_for_class_fields = Some(false);
+        context.config.compat.private_methods_loose = true;
+        let context = Arc::new(context);
+        let js = File::new("/a/index.js".to_string(), context.clone());
+        let ts = File::new("/a/index.ts".to_string(), context.clone());
+
+        let assumptions = context.assumptions_for(&js);
+        assert!(!assumptions.set_public_class_fields);
+        assert!(assumptions.private_fields_as_properties);
+        assert!(!assumptions.no_document_all);
+        let assumptions = context.assumptions_for(&ts);
+        assert!(assumptions.set_public_class_fields);
+        assert!(assumptions.set_class_methods);
+    }
+}
@@ -2,24 +2,96 @@ use std::path::PathBuf;
 
 use clap;
 use clap::builder::TypedValueParser;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
+use crate::compiler::GraphDumpFormat;
 use crate::config::Mode;
+use crate::utils::logger::{DebugScope, LogFormat, LogLevel};
 
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
     #[arg(short, long)]
     pub watch: bool,
+    #[arg(required = true)]
+    pub root: Option<PathBuf>,
+    #[arg(long, default_value_t = Mode::Development, value_parser = mode_parser())]
+    pub mode: Mode,
+    /// Max number of worker threads used to build modules, defaults to the
+    /// number of logical CPUs
+    #[arg(long)]
+    pub max_workers: Option<usize>,
+    /// Generate an html treemap report of the chunks and modules
+    #[arg(long)]
+    pub analyze: bool,
+    /// Dump the module graph to the output directory after compiling
+    #[arg(long, value_enum)]
+    pub dump_graph: Option<GraphDumpFormat>,
+    /// Only dump modules whose path relative to root starts with this prefix
+    #[arg(long, requires = "dump_graph")]
+    pub dump_graph_filter: Option<String>,
+    /// Only dump modules at most this many imports away from the entries
+    #[arg(long, requires = "dump_graph")]
+    pub dump_graph_depth: Option<usize>,
+    /// Open the browser at the path after the dev server starts, defaults to `/`
+    #[arg(long, num_args = 0..=1, default_missing_value = "/", requires = "watch")]
+    pub open: Option<String>,
+    /// Log level of mako, defaults to MAKO_LOG_LEVEL or info, RUST_LOG takes precedence
+    #[arg(long, value_enum)]
+    pub log_level: Option<LogLevel>,
+    /// Log format, defaults to MAKO_LOG_FORMAT or text
+    #[arg(long, value_enum)]
+    pub log_format: Option<LogFormat>,
+    /// Enable the debug logs of the scopes, e.g. `--debug resolve,graph`
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub debug: Vec<DebugScope>,
+    /// Build in this process even if a daemon is running for the root
+    #[arg(long)]
+    pub no_daemon: bool,
+    /// Compare the stats of the build with the stats.json of another build, see `mako diff`
+    #[arg(long, conflicts_with = "watch")]
+    pub compare_with: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Keep the compiler resident for the root, `mako <root>` builds are run by the daemon
+    /// with the modules built by the last build
+    Daemon(DaemonArgs),
+    /// Report the modules added or removed, the size changes of the chunks and the new duplicate
+    /// packages between the stats.json of two builds
+    Diff(DiffArgs),
+}
+
+#[derive(clap::Args)]
+pub struct DaemonArgs {
     pub root: PathBuf,
-    #[arg(long, default_value_t = Mode::Development,
-        value_parser = clap::builder::PossibleValuesParser::new(["production", "prod", "p", "development","dev"])
-                .map(|s|{
-                    match s.as_str() {
-                        "production" | "prod" | "p" => Mode::Production,
-                        "development" | "dev" => Mode::Development,
-                        _ => unreachable!()
-                    }
-                })
-    )]
+    #[arg(long, default_value_t = Mode::Development, value_parser = mode_parser())]
     pub mode: Mode,
+    /// Max number of worker threads used to build modules, defaults to the
+    /// number of logical CPUs
+    #[arg(long)]
+    pub max_workers: Option<usize>,
+    /// Stop the daemon running for the root
+    #[arg(long)]
+    pub stop: bool,
+}
+
+#[derive(clap::Args)]
+pub struct DiffArgs {
+    /// The stats.json of the base build
+    pub before: PathBuf,
+    /// The stats.json of the build to compare
+    pub after: PathBuf,
+}
+
+fn mode_parser() -> impl TypedValueParser<Value = Mode> {
+    clap::builder::PossibleValuesParser::new(["production", "prod", "p", "development", "dev"])
+        .map(|s| match s.as_str() {
+            "production" | "prod" | "p" => Mode::Production,
+            "development" | "dev" => Mode::Development,
+            _ => unreachable!(),
+        })
 }
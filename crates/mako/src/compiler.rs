@@ -1,7 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex, RwLock};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex, RwLock};
 use std::time::Instant;
 
 use anyhow::{anyhow, Error, Result};
@@ -14,23 +14,42 @@ use swc_node_comments::SwcComments;
 use tracing::debug;
 
 use crate::ast::comments::Comments;
-use crate::ast::file::win_path;
-use crate::config::{Config, ModuleIdStrategy, OutputMode};
+use crate::ast::file::{win_path, File};
+use crate::config::{CjsNamedExportsCheck, Config, ModuleIdStrategy, OutputMode};
+use crate::dev::on_demand_entries::OnDemandEntries;
+use crate::features::dotenv::Dotenv;
 use crate::generate::chunk_graph::ChunkGraph;
+use crate::generate::generate_chunks::ChunkFilesCache;
 use crate::generate::optimize_chunk::OptimizeChunksInfo;
+use crate::module::{relative_to_root, ModuleId};
 use crate::module_graph::ModuleGraph;
-use crate::plugin::{Plugin, PluginDriver, PluginGenerateEndParams};
+pub use crate::module_graph::{GraphDumpFormat, GraphDumpOptions};
+use crate::plugin::{Plugin, PluginDriver, PluginGenerateEndParams, PluginRebuildEndParams};
 use crate::plugins;
-use crate::resolve::{get_resolvers, Resolvers};
+use crate::plugins::type_check::TypeDiagnostic;
+use crate::resolve::{get_resolvers, ResolveCache, Resolvers};
 use crate::share::helpers::SWC_HELPERS;
 use crate::stats::StatsInfo;
-use crate::utils::id_helper::{assign_numeric_ids, compare_modules_by_incoming_edges};
+use crate::utils::cancellation::CancellationToken;
+use crate::utils::id_helper::{
+    assign_deterministic_ids, assign_numeric_ids, incoming_edges_sort_key,
+};
 use crate::utils::{thread_pool, ParseRegex};
 
+pub struct EmittedAsset {
+    pub content: Vec<u8>,
+    /// the module being built when the asset is emitted, the assets emitted by the build wide
+    /// hooks are kept until they're emitted again
+    pub module_id: Option<ModuleId>,
+}
+
 pub struct Context {
     pub module_graph: RwLock<ModuleGraph>,
     pub chunk_graph: RwLock<ChunkGraph>,
     pub assets_info: Mutex<HashMap<String, String>>,
+    /// the assets emitted by the plugins by the file names, which are written to the output
+    /// with the assets of the modules
+    pub emitted_assets: Mutex<HashMap<String, EmittedAsset>>,
     pub modules_with_missing_deps: RwLock<Vec<String>>,
     pub config: Config,
     pub numeric_ids_map: RwLock<HashMap<String, usize>>,
@@ -38,10 +57,46 @@ pub struct Context {
     pub root: PathBuf,
     pub meta: Meta,
     pub plugin_driver: PluginDriver,
+    /// the plugins passed to the compiler, e.g. the js plugins, which are passed on to the
+    /// compilers created by the builtin plugins like the ones of config.outputs
+    pub extra_plugins: Vec<Arc<dyn Plugin>>,
     pub stats_info: StatsInfo,
     pub resolvers: Resolvers,
+    pub resolve_cache: ResolveCache,
     pub static_cache: RwLock<MemoryChunkFileCache>,
     pub optimize_infos: Mutex<Option<Vec<OptimizeChunksInfo>>>,
+    /// variables loaded from the .env files, which are reloaded when the files change in watch mode
+    pub env: RwLock<HashMap<String, String>>,
+    /// the entries of config, the glob entries are added or removed as the files change in watch mode
+    pub entries: RwLock<HashMap<String, PathBuf>>,
+    /// modules marked dirty by the plugins, which are rebuilt by the next rebuild in watch mode
+    pub invalidated_modules: Mutex<HashSet<ModuleId>>,
+    /// the regexes of config.no_parse, the matched scripts are not transformed or analyzed
+    pub no_parse: Vec<Regex>,
+    /// the normal chunk files of the last generate, which are reused in watch mode
+    pub chunk_files_cache: ChunkFilesCache,
+    /// the modules whose asts are dropped as the memory usage exceeds config.memory_limit
+    pub dropped_asts: Mutex<HashSet<ModuleId>>,
+    /// cancels the in-flight build, e.g. when the files change again in watch mode
+    pub cancellation: CancellationToken,
+    /// the files which are not modules but read by the loaders of the modules, e.g. the sass
+    /// partials, the modules are rebuilt when the files change in watch mode
+    pub file_dependencies: RwLock<HashMap<PathBuf, HashSet<ModuleId>>>,
+    /// the dirs which are scanned by the loaders of the modules, e.g. the dirs of
+    /// `require.context`, the modules are rebuilt when the files are added to or removed from
+    /// the dirs in watch mode
+    pub dir_dependencies: RwLock<HashMap<PathBuf, HashSet<ModuleId>>>,
+    /// the files which are read by the whole build, e.g. the tailwind config, all the modules
+    /// are rebuilt when the files change in watch mode
+    pub build_dependencies: RwLock<HashSet<PathBuf>>,
+    /// the chunk files served by the dev server when config.dev_server.on_demand_entries is
+    /// enabled
+    pub on_demand_entries: OnDemandEntries,
+    /// the diagnostics of the last type check when config.type_check is enabled
+    pub type_diagnostics: RwLock<Vec<TypeDiagnostic>>,
+    pub(crate) invalidate_listener: Mutex<Option<Box<dyn Fn() + Send>>>,
+    pub(crate) diagnostics_listener: Mutex<Option<Box<dyn Fn() + Send>>>,
+    pub(crate) rebuild_listeners: Mutex<Vec<mpsc::Sender<PluginRebuildEndParams>>>,
 }
 
 #[derive(Default)]
@@ -73,6 +128,13 @@ impl MemoryChunkFileCache {
         Ok(())
     }
 
+    pub fn size(&self) -> usize {
+        self.content_map
+            .values()
+            .map(|(content, _)| content.len())
+            .sum()
+    }
+
     pub fn read<T: AsRef<str>>(&self, path: T) -> Option<Vec<u8>> {
         self.content_map
             .get(path.as_ref())
@@ -91,6 +153,7 @@ impl MemoryChunkFileCache {
 #[derive(Default)]
 pub struct Args {
     pub watch: bool,
+    pub max_workers: Option<usize>,
 }
 
 impl Context {
@@ -120,19 +183,40 @@ impl Default for Context {
         let resolvers = get_resolvers(&config);
         Self {
             config,
-            args: Args { watch: false },
+            args: Args {
+                watch: false,
+                ..Default::default()
+            },
             root: PathBuf::from(""),
             module_graph: RwLock::new(ModuleGraph::new()),
             chunk_graph: RwLock::new(ChunkGraph::new()),
             assets_info: Mutex::new(HashMap::new()),
+            emitted_assets: Default::default(),
             modules_with_missing_deps: RwLock::new(Vec::new()),
             meta: Meta::new(),
             plugin_driver: Default::default(),
+            extra_plugins: vec![],
             stats_info: StatsInfo::new(),
             resolvers,
+            resolve_cache: Default::default(),
             optimize_infos: Mutex::new(None),
             static_cache: Default::default(),
             numeric_ids_map: RwLock::new(numeric_ids_map),
+            env: Default::default(),
+            entries: Default::default(),
+            invalidated_modules: Default::default(),
+            invalidate_listener: Default::default(),
+            type_diagnostics: Default::default(),
+            diagnostics_listener: Default::default(),
+            rebuild_listeners: Default::default(),
+            no_parse: vec![],
+            chunk_files_cache: Default::default(),
+            dropped_asts: Default::default(),
+            cancellation: Default::default(),
+            file_dependencies: Default::default(),
+            dir_dependencies: Default::default(),
+            on_demand_entries: Default::default(),
+            build_dependencies: Default::default(),
         }
     }
 }
@@ -212,6 +296,113 @@ impl Context {
         let mut assets_info = self.assets_info.lock().unwrap();
         assets_info.insert(origin_path, output_path);
     }
+
+    pub(crate) fn write_emitted_assets(&self) -> Result<()> {
+        let emitted_assets = self.emitted_assets.lock().unwrap();
+        for (file_name, asset) in emitted_assets.iter() {
+            let output_path = self.config.output.path.join(file_name);
+            if let Some(parent) = output_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(output_path, &asset.content)?;
+        }
+        Ok(())
+    }
+
+    /// Remove the assets emitted while building the module, they're emitted again when the
+    /// module is rebuilt, and dropped with the module when it's removed in watch mode
+    pub(crate) fn clear_emitted_assets(&self, module_id: &ModuleId) {
+        let mut emitted_assets = self.emitted_assets.lock().unwrap();
+        if emitted_assets.is_empty() {
+            return;
+        }
+        emitted_assets.retain(|_, asset| asset.module_id.as_ref() != Some(module_id));
+    }
+
+    /// Mark the module dirty from the external events, e.g. a cms webhook or a changed
+    /// graphql schema, a rebuild is triggered in watch mode to reprocess it, and the
+    /// hmr updates propagate as the module file is changed
+    pub fn invalidate(&self, module_id: &ModuleId) {
+        self.invalidated_modules
+            .lock()
+            .unwrap()
+            .insert(module_id.clone());
+        if let Some(listener) = self.invalidate_listener.lock().unwrap().as_ref() {
+            listener();
+        }
+    }
+
+    pub(crate) fn on_invalidate(&self, listener: Box<dyn Fn() + Send>) {
+        *self.invalidate_listener.lock().unwrap() = Some(listener);
+    }
+
+    /// Register a file read by the loaders of the module, the module is rebuilt when the file
+    /// changes in watch mode, the files of a module are cleared as the module is rebuilt
+    pub fn add_file_dependency(&self, module_path: &Path, path: PathBuf) {
+        self.file_dependencies
+            .write()
+            .unwrap()
+            .entry(path)
+            .or_default()
+            .insert(ModuleId::new(module_path.to_string_lossy().to_string()));
+    }
+
+    /// Register a dir scanned by the loaders of the module, the module is rebuilt when the files
+    /// are added to or removed from the dir (recursively) in watch mode
+    pub fn add_dir_dependency(&self, module_path: &Path, dir: PathBuf) {
+        self.dir_dependencies
+            .write()
+            .unwrap()
+            .entry(dir)
+            .or_default()
+            .insert(ModuleId::new(module_path.to_string_lossy().to_string()));
+    }
+
+    /// Register a file read by the whole build, all the modules are rebuilt when the file
+    /// changes in watch mode
+    pub fn add_build_dependency(&self, path: PathBuf) {
+        self.build_dependencies.write().unwrap().insert(path);
+    }
+
+    pub(crate) fn clear_file_dependencies(&self, module_id: &ModuleId) {
+        for dependencies in [&self.file_dependencies, &self.dir_dependencies] {
+            let mut dependencies = dependencies.write().unwrap();
+            if dependencies.is_empty() {
+                continue;
+            }
+            dependencies.retain(|_, modules| {
+                modules.remove(module_id);
+                !modules.is_empty()
+            });
+        }
+    }
+
+    /// Replace the diagnostics with the ones of a finished type check, the dev server is
+    /// notified to push them to the error overlay of the browsers
+    pub(crate) fn set_type_diagnostics(&self, diagnostics: Vec<TypeDiagnostic>) {
+        *self.type_diagnostics.write().unwrap() = diagnostics;
+        if let Some(listener) = self.diagnostics_listener.lock().unwrap().as_ref() {
+            listener();
+        }
+    }
+
+    pub(crate) fn on_diagnostics(&self, listener: Box<dyn Fn() + Send>) {
+        *self.diagnostics_listener.lock().unwrap() = Some(listener);
+    }
+
+    /// Send the delta of a rebuild to the receivers of `Compiler::watch`, the dropped receivers
+    /// are unsubscribed
+    pub(crate) fn emit_rebuild(&self, params: &PluginRebuildEndParams) {
+        self.rebuild_listeners
+            .lock()
+            .unwrap()
+            .retain(|listener| listener.send(params.clone()).is_ok());
+    }
+
+    pub(crate) fn is_no_parse(&self, file: &File) -> bool {
+        let path = file.path.to_string_lossy();
+        self.no_parse.iter().any(|regex| regex.is_match(&path))
+    }
 }
 
 pub struct Compiler {
@@ -231,24 +422,37 @@ impl Compiler {
 
         let root = PathBuf::from(win_path(root.to_str().unwrap()));
 
+        thread_pool::init(args.max_workers);
+
         // why add plugins before builtin plugins?
         // because plugins like less-loader need to be added before assets plugin
-        // TODO: support plugin orders
+        // the plugins with `enforce: "pre"` are added before all the builtin plugins, even the
+        // ones inserted ahead later, and the ones with `enforce: "post"` after all of them
         let mut plugins: Vec<Arc<dyn Plugin>> = vec![];
-        if let Some(extra_plugins) = extra_plugins {
-            plugins.extend(extra_plugins);
+        let mut pre_plugins: Vec<Arc<dyn Plugin>> = vec![];
+        let mut post_plugins: Vec<Arc<dyn Plugin>> = vec![];
+        let extra_plugins = extra_plugins.unwrap_or_default();
+        for plugin in extra_plugins.iter().cloned() {
+            match plugin.enforce() {
+                Some("pre") => pre_plugins.push(plugin),
+                Some("post") => post_plugins.push(plugin),
+                _ => plugins.push(plugin),
+            }
         }
         let builtin_plugins: Vec<Arc<dyn Plugin>> = vec![
             // features
             Arc::new(plugins::manifest::ManifestPlugin {}),
             Arc::new(plugins::copy::CopyPlugin {}),
             Arc::new(plugins::import::ImportPlugin {}),
+            Arc::new(plugins::data_uri::DataUriPlugin::default()),
+            Arc::new(plugins::graphql::GraphqlPlugin {}),
             // file types
             Arc::new(plugins::context_module::ContextModulePlugin {}),
             Arc::new(plugins::runtime::MakoRuntime {}),
             Arc::new(plugins::invalid_webpack_syntax::InvalidWebpackSyntaxPlugin {}),
             Arc::new(plugins::hmr_runtime::HMRRuntimePlugin {}),
             Arc::new(plugins::wasm_runtime::WasmRuntimePlugin {}),
+            Arc::new(plugins::node_addon_runtime::NodeAddonRuntimePlugin {}),
             Arc::new(plugins::async_runtime::AsyncRuntimePlugin {}),
             Arc::new(plugins::emotion::EmotionPlugin {}),
             Arc::new(plugins::tree_shaking::FarmTreeShake {}),
@@ -270,6 +474,14 @@ impl Compiler {
             )));
         }
 
+        if config.cjs_named_exports_check != CjsNamedExportsCheck::Off {
+            plugins.push(Arc::new(
+                plugins::cjs_named_exports::CjsNamedExportsChecker {
+                    emit_error: config.cjs_named_exports_check == CjsNamedExportsCheck::Error,
+                },
+            ));
+        }
+
         if let Some(duplicate_package_checker) = &config.check_duplicate_package {
             plugins.push(Arc::new(
                 plugins::duplicate_package_checker::DuplicatePackageCheckerPlugin::new()
@@ -279,6 +491,106 @@ impl Compiler {
             ));
         }
 
+        if config.performance.is_some() {
+            plugins.push(Arc::new(
+                plugins::performance_budgets::PerformanceBudgetsPlugin {},
+            ));
+        }
+
+        if let Some(purge_css) = &config.purge_css {
+            plugins.push(Arc::new(plugins::purge_css::PurgeCssPlugin::new(
+                purge_css,
+            )?));
+        }
+
+        if let Some(font_subset) = &config.font_subset {
+            plugins.push(Arc::new(plugins::font_subset::FontSubsetPlugin::new(
+                font_subset,
+            )));
+        }
+
+        if let Some(lint) = &config.lint {
+            plugins.push(Arc::new(plugins::lint::LintPlugin::new(lint)));
+        }
+
+        if let Some(type_check) = &config.type_check {
+            plugins.push(Arc::new(plugins::type_check::TypeCheckPlugin::new(
+                type_check,
+            )));
+        }
+
+        if let Some(legacy) = &config.legacy {
+            plugins.push(Arc::new(plugins::legacy::LegacyPlugin::new(legacy)));
+        }
+
+        if !config.outputs.is_empty() {
+            plugins.push(Arc::new(plugins::extra_outputs::ExtraOutputsPlugin::new(
+                &config.outputs,
+            )));
+        }
+
+        if let Some(service_worker) = &config.service_worker {
+            plugins.push(Arc::new(plugins::service_worker::ServiceWorkerPlugin::new(
+                service_worker,
+            )?));
+        }
+
+        if let Some(fs_routes) = &config.fs_routes {
+            plugins.push(Arc::new(plugins::fs_routes::FsRoutesPlugin::new(
+                fs_routes, &root,
+            )));
+        }
+
+        if let Some(remote_modules) = &config.remote_modules {
+            plugins.push(Arc::new(plugins::remote_modules::RemoteModulesPlugin::new(
+                remote_modules,
+                &root,
+            )?));
+        }
+
+        if let Some(duplicate_modules) = &config.duplicate_modules {
+            plugins.push(Arc::new(
+                plugins::duplicate_modules::DuplicateModulesPlugin::new(duplicate_modules),
+            ));
+        }
+
+        if let Some(size_report) = &config.size_report {
+            plugins.push(Arc::new(plugins::size_report::SizeReportPlugin::new(
+                size_report,
+            )));
+        }
+
+        if let Some(metafile) = &config.metafile {
+            plugins.push(Arc::new(plugins::metafile::MetafilePlugin::new(metafile)));
+        }
+
+        if let Some(resolve_cache) = &config.resolve_cache {
+            plugins.push(Arc::new(plugins::resolve_cache::ResolveCachePlugin::new(
+                resolve_cache,
+                &root,
+            )));
+        }
+
+        if let Some(mpa) = &config.mpa {
+            plugins.push(Arc::new(plugins::mpa::MpaPlugin::new(mpa)));
+        }
+
+        if let Some(ssr) = &config.ssr {
+            plugins.push(Arc::new(plugins::ssr::SsrPlugin::new(ssr)));
+        }
+
+        if config.conditional_compilation {
+            plugins.push(Arc::new(
+                plugins::conditional_compilation::ConditionalCompilationPlugin {},
+            ));
+        }
+
+        if let Some(rsc_client) = &config.rsc_client {
+            plugins.push(Arc::new(plugins::rsc_client::RscClientPlugin::new(
+                rsc_client,
+            )));
+        }
+
         if config.experimental.require_context {
             plugins.push(Arc::new(plugins::require_context::RequireContextPlugin {}))
         }
@@ -338,11 +650,33 @@ impl Compiler {
             .collect::<Result<Vec<Regex>>>()?;
         plugins.push(Arc::new(plugins::ignore::IgnorePlugin { ignores }));
 
+        if !config.ignore_modules.is_empty() || config.locale_filter.is_some() {
+            // ahead of the resolve_id hooks of the other plugins
+            plugins.insert(
+                0,
+                Arc::new(plugins::ignore::IgnoreModulesPlugin::new(&config)?),
+            );
+        }
+
+        plugins.splice(0..0, pre_plugins);
+        plugins.extend(post_plugins);
+
         let plugin_driver = PluginDriver::new(plugins);
 
         plugin_driver.modify_config(&mut config, &root, &args)?;
 
+        let no_parse = config
+            .no_parse
+            .iter()
+            .map(|no_parse| {
+                Regex::new(no_parse)
+                    .map_err(|_| anyhow!("Config Error invalid regex: {}", no_parse))
+            })
+            .collect::<Result<Vec<Regex>>>()?;
         let resolvers = get_resolvers(&config);
+        let env = Dotenv::load(&root, &config)?;
+        let entries = config.entry.clone();
+        let resolve_cache = ResolveCache::new(&root);
         let mut numeric_ids_map = HashMap::new();
         SWC_HELPERS.iter().enumerate().for_each(|(i, item)| {
             numeric_ids_map.insert(item.to_string(), i);
@@ -360,18 +694,62 @@ impl Compiler {
                 module_graph: RwLock::new(ModuleGraph::new()),
                 chunk_graph: RwLock::new(ChunkGraph::new()),
                 assets_info: Mutex::new(HashMap::new()),
+                emitted_assets: Default::default(),
                 modules_with_missing_deps: RwLock::new(Vec::new()),
                 meta: Meta::new(),
                 plugin_driver,
+                extra_plugins,
                 numeric_ids_map: RwLock::new(numeric_ids_map),
                 stats_info: StatsInfo::new(),
                 resolvers,
+                resolve_cache,
                 optimize_infos: Mutex::new(None),
+                env: RwLock::new(env),
+                entries: RwLock::new(entries),
+                invalidated_modules: Default::default(),
+                invalidate_listener: Default::default(),
+                type_diagnostics: Default::default(),
+                diagnostics_listener: Default::default(),
+                rebuild_listeners: Default::default(),
+                no_parse,
+                chunk_files_cache: Default::default(),
+                dropped_asts: Default::default(),
+                cancellation: Default::default(),
+                file_dependencies: Default::default(),
+                dir_dependencies: Default::default(),
+                on_demand_entries: Default::default(),
+                build_dependencies: Default::default(),
             }),
         })
     }
 
+    pub(crate) fn entry_file(&self, entry: &Path) -> crate::ast::file::File {
+        let mut entry = entry.to_string_lossy().to_string();
+        let is_browser = matches!(
+            self.context.config.platform,
+            crate::config::Platform::Browser
+        );
+        let watch = self.context.args.watch;
+        let hmr = self.context.config.hmr.is_some();
+        if is_browser && watch && hmr {
+            entry = format!("{}?hmr", entry);
+        }
+        crate::ast::file::File::new_entry(entry, self.context.clone())
+    }
+
+    /// Aborts the in-flight build, which fails with `BuildError::Cancelled`
+    pub fn cancel(&self) {
+        self.context.cancellation.cancel();
+    }
+
+    /// Cancels the in-flight build and blocks until it has returned, e.g. before exiting on Ctrl-C
+    pub fn cancel_and_wait(&self) {
+        self.cancel();
+        self.context.cancellation.wait_idle();
+    }
+
     pub fn compile(&self) -> Result<()> {
+        let _running = self.context.cancellation.start();
         // 先清空 dist 目录
         if self.context.config.clean {
             self.clean_dist()?;
@@ -386,49 +764,77 @@ impl Compiler {
         )
         .green();
         println!("{}", building_with_message);
-        {
-            crate::mako_profile_scope!("Build Stage");
-            let files = self
-                .context
-                .config
-                .entry
-                .values()
-                .map(|entry| {
-                    let mut entry = entry.to_string_lossy().to_string();
-                    let is_browser = matches!(
-                        self.context.config.platform,
-                        crate::config::Platform::Browser
-                    );
-                    let watch = self.context.args.watch;
-                    let hmr = self.context.config.hmr.is_some();
-                    if is_browser && watch && hmr {
-                        entry = format!("{}?hmr", entry);
-                    }
-                    crate::ast::file::File::new_entry(entry, self.context.clone())
-                })
-                .collect();
-            self.context.plugin_driver.build_start(&self.context)?;
+        self.build_entries()?;
+        self.generate_and_emit(t_compiler, start_time)
+    }
+
+    /// Build the module graph from the entries, it's public with the `bench` feature so that the
+    /// codegen benchmarks build the graph without generating the chunks
+    #[cfg(feature = "bench")]
+    pub fn build_entries(&self) -> Result<()> {
+        self.build_module_graph()
+    }
 
-            self.build(files)?;
+    /// Build the module graph from the entries
+    #[cfg(not(feature = "bench"))]
+    pub(crate) fn build_entries(&self) -> Result<()> {
+        self.build_module_graph()
+    }
 
-            debug!("start after build");
+    fn build_module_graph(&self) -> Result<()> {
+        crate::mako_profile_scope!("Build Stage");
+        let files = self
+            .context
+            .entries
+            .read()
+            .unwrap()
+            .values()
+            .map(|entry| self.entry_file(entry))
+            .collect();
+        self.context.plugin_driver.build_start(&self.context)?;
 
-            self.context
-                .plugin_driver
-                .after_build(&self.context, self)?;
-        }
+        self.build(files)?;
+        // independent of the completion order of the build tasks, so that the outputs are
+        // reproducible, the modules added by the later updates in watch mode are only appended
+        self.context.module_graph.write().unwrap().sort();
+
+        debug!("start after build");
 
+        self.context
+            .plugin_driver
+            .after_build(&self.context, self)?;
+        Ok(())
+    }
+
+    /// Generate the chunks from the built module graph and emit them
+    pub(crate) fn generate_and_emit(&self, t_compiler: Instant, start_time: i64) -> Result<()> {
         self.context.plugin_driver.before_generate(&self.context)?;
 
+        if let ModuleIdStrategy::Deterministic = self.context.config.module_id_strategy {
+            let module_graph = self.context.module_graph.read().unwrap();
+            let mut numeric_ids_map = self.context.numeric_ids_map.write().unwrap();
+            // the ids assigned by the previous builds of the compiler, e.g. in the daemon, are kept
+            let assigned_ids = numeric_ids_map.clone();
+            assign_deterministic_ids(
+                module_graph.modules(),
+                |module| relative_to_root(&module.id.id, &self.context.root),
+                |module| assigned_ids.get(&module.id.id)?.checked_sub(10),
+                |module, id| {
+                    // reserved ten indexes for swc helper and others runtime module
+                    numeric_ids_map.insert(module.id.id.to_string(), id + 10);
+                },
+            )
+        }
+
         if let ModuleIdStrategy::Numeric = self.context.config.module_id_strategy {
             let module_graph = self.context.module_graph.read().unwrap();
             assign_numeric_ids(
                 module_graph.modules(),
-                |a, b| compare_modules_by_incoming_edges(&module_graph, &a.id, &b.id),
+                |module| incoming_edges_sort_key(&module_graph, &module.id, &self.context.root),
                 |module, id| {
                     let mut numeric_ids_map = self.context.numeric_ids_map.write().unwrap();
                     // reserved ten indexes for swc helper and others runtime module
-                    numeric_ids_map.insert(module.id.id.clone(), id + 10);
+                    numeric_ids_map.insert(module.id.id.to_string(), id + 10);
                 },
             )
         }
@@ -464,12 +870,28 @@ impl Compiler {
                     .plugin_driver
                     .generate_end(&params, &self.context)?;
                 self.context.plugin_driver.write_bundle(&self.context)?;
+                self.context
+                    .plugin_driver
+                    .done(&params.stats, &self.context)?;
+                if self.context.args.watch {
+                    self.drop_asts_over_limit();
+                }
                 Ok(())
             }
             Err(e) => Err(e),
         }
     }
 
+    /// Subscribe the structured deltas of the rebuilds in watch mode, i.e. the changed files,
+    /// the added, removed and modified modules, the updated chunks, the time and the errors,
+    /// e.g. for the IDEs and frameworks which drive their UIs off the incremental builds; drop
+    /// the receiver to unsubscribe
+    pub fn watch(&self) -> mpsc::Receiver<PluginRebuildEndParams> {
+        let (tx, rx) = mpsc::channel();
+        self.context.rebuild_listeners.lock().unwrap().push(tx);
+        rx
+    }
+
     pub fn full_hash(&self) -> u64 {
         crate::mako_profile_function!();
         let cg = self.context.chunk_graph.read().unwrap();
@@ -477,7 +899,13 @@ impl Compiler {
         cg.full_hash(&mg)
     }
 
-    fn clean_dist(&self) -> Result<()> {
+    /// Dumps the module graph as DOT or JSON for external visualization
+    pub fn dump_module_graph(&self, options: &GraphDumpOptions) -> String {
+        let mg = self.context.module_graph.read().unwrap();
+        mg.dump(options, &self.context.root)
+    }
+
+    pub(crate) fn clean_dist(&self) -> Result<()> {
         // compiler 前清除 dist，如果后续 dev 环境不在 output_path 里，需要再补上 dev 的逻辑
         let output_path = &self.context.config.output.path;
         if fs::metadata(output_path).is_ok() {
@@ -486,3 +914,317 @@ impl Compiler {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::path::{Path, PathBuf};
+    use std::sync::{mpsc, Arc, Mutex};
+
+    use anyhow::Result;
+
+    use super::{Args, Compiler, Context};
+    use crate::ast::file::{Content, File, JsContent};
+    use crate::config::Config;
+    use crate::module::ModuleId;
+    use crate::plugin::{Plugin, PluginLoadParam, PluginRebuildEndParams};
+    use crate::stats::StatsJsonMap;
+
+    #[test]
+    fn test_file_dependencies() {
+        let context = Context::default();
+        let partial = PathBuf::from("/src/_vars.scss");
+        context.add_file_dependency(Path::new("/src/a.scss"), partial.clone());
+        context.add_file_dependency(Path::new("/src/b.scss"), partial.clone());
+
+        context.clear_file_dependencies(&ModuleId::new("/src/a.scss".to_string()));
+        assert_eq!(
+            context.file_dependencies.read().unwrap()[&partial],
+            HashSet::from([ModuleId::new("/src/b.scss".to_string())])
+        );
+
+        context.clear_file_dependencies(&ModuleId::new("/src/b.scss".to_string()));
+        assert!(context.file_dependencies.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_dir_dependencies() {
+        let context = Context::default();
+        let dir = PathBuf::from("/src/locales");
+        context.add_dir_dependency(Path::new("/src/i18n.ts"), dir.clone());
+        context.add_file_dependency(Path::new("/src/i18n.ts"), dir.join("en.json"));
+        assert_eq!(
+            context.dir_dependencies.read().unwrap()[&dir],
+            HashSet::from([ModuleId::new("/src/i18n.ts".to_string())])
+        );
+
+        context.clear_file_dependencies(&ModuleId::new("/src/i18n.ts".to_string()));
+        assert!(context.dir_dependencies.read().unwrap().is_empty());
+        assert!(context.file_dependencies.read().unwrap().is_empty());
+    }
+
+    struct LoadPlugin {
+        name: &'static str,
+        enforce: Option<&'static str>,
+    }
+
+    impl Plugin for LoadPlugin {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn enforce(&self) -> Option<&str> {
+            self.enforce
+        }
+
+        fn load(
+            &self,
+            _param: &PluginLoadParam,
+            _context: &Arc<Context>,
+        ) -> Result<Option<Content>> {
+            Ok(Some(Content::Js(JsContent {
+                content: self.name.to_string(),
+                ..Default::default()
+            })))
+        }
+    }
+
+    fn loaded_by(plugins: Vec<(&'static str, Option<&'static str>)>, path: &str) -> String {
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/plugin-order");
+        let plugins = plugins
+            .into_iter()
+            .map(|(name, enforce)| Arc::new(LoadPlugin { name, enforce }) as Arc<dyn Plugin>)
+            .collect();
+        let compiler = Compiler::new(
+            Config::default(),
+            root.clone(),
+            Args::default(),
+            Some(plugins),
+        )
+        .unwrap();
+        let context = compiler.context.clone();
+        let file = File::new(
+            root.join(path).to_string_lossy().to_string(),
+            context.clone(),
+        );
+        match context
+            .plugin_driver
+            .load(&PluginLoadParam { file: &file }, &context)
+            .unwrap()
+        {
+            Some(Content::Js(js)) => js.content,
+            _ => panic!("not loaded by the plugins"),
+        }
+    }
+
+    #[test]
+    fn test_plugin_enforce_order() {
+        assert_eq!(
+            loaded_by(
+                vec![
+                    ("normal", None),
+                    ("post", Some("post")),
+                    ("pre", Some("pre"))
+                ],
+                "index.js"
+            ),
+            "pre"
+        );
+        assert_eq!(
+            loaded_by(vec![("post", Some("post")), ("normal", None)], "index.js"),
+            "normal"
+        );
+        // the post plugins are after the builtin loaders, e.g. the graphql one
+        assert_ne!(
+            loaded_by(vec![("post", Some("post"))], "query.graphql"),
+            "post"
+        );
+        assert_eq!(loaded_by(vec![("post", Some("post"))], "index.js"), "post");
+    }
+
+    #[derive(Default)]
+    struct HooksPlugin {
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl Plugin for HooksPlugin {
+        fn name(&self) -> &str {
+            "hooks"
+        }
+
+        fn write_bundle(&self, _context: &Arc<Context>) -> Result<()> {
+            self.calls.lock().unwrap().push("write_bundle".to_string());
+            Ok(())
+        }
+
+        fn done(&self, stats: &StatsJsonMap, _context: &Arc<Context>) -> Result<()> {
+            assert!(stats.end_time >= stats.start_time);
+            self.calls.lock().unwrap().push("done".to_string());
+            Ok(())
+        }
+
+        fn watch_changes(&self, id: &str, event: &str, _context: &Arc<Context>) -> Result<()> {
+            let file_name = Path::new(id).file_name().unwrap().to_string_lossy();
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("watch_changes {} {}", event, file_name));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_lifecycle_hooks() {
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/lifecycle-hooks");
+        let plugin = Arc::new(HooksPlugin::default());
+        let config = Config::new(&root, None, None).unwrap();
+        let plugins = vec![plugin.clone() as Arc<dyn Plugin>];
+        let compiler = Compiler::new(config, root.clone(), Args::default(), Some(plugins)).unwrap();
+        compiler.compile().unwrap();
+        compiler.update(vec![root.join("index.ts")]).unwrap();
+        assert_eq!(
+            *plugin.calls.lock().unwrap(),
+            vec!["write_bundle", "done", "watch_changes update index.ts"]
+        );
+    }
+
+    /// Loads the data module from an external source, which is changed out of the files
+    struct DataPlugin {
+        data: Mutex<String>,
+    }
+
+    impl Plugin for DataPlugin {
+        fn name(&self) -> &str {
+            "data"
+        }
+
+        fn load(
+            &self,
+            param: &PluginLoadParam,
+            _context: &Arc<Context>,
+        ) -> Result<Option<Content>> {
+            if !param.file.path.ends_with("data.ts") {
+                return Ok(None);
+            }
+            Ok(Some(Content::Js(JsContent {
+                content: self.data.lock().unwrap().clone(),
+                ..Default::default()
+            })))
+        }
+    }
+
+    #[test]
+    fn test_invalidate() {
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/invalidate");
+        let plugin = Arc::new(DataPlugin {
+            data: Mutex::new(r#"export default "a";"#.to_string()),
+        });
+        let config = Config::new(&root, None, None).unwrap();
+        let plugins = vec![plugin.clone() as Arc<dyn Plugin>];
+        let compiler = Compiler::new(config, root.clone(), Args::default(), Some(plugins)).unwrap();
+        compiler.compile().unwrap();
+        let (tx, rx) = mpsc::channel();
+        compiler
+            .context
+            .on_invalidate(Box::new(move || tx.send(()).unwrap()));
+
+        *plugin.data.lock().unwrap() = r#"export default "b";"#.to_string();
+        let data = ModuleId::from(root.join("data.ts"));
+        compiler.context.invalidate(&data);
+        // the listener triggers the rebuild in watch mode
+        rx.try_recv().unwrap();
+
+        let result = compiler.update(vec![]).unwrap();
+        assert!(result.modified.contains(&data));
+        assert!(compiler
+            .context
+            .invalidated_modules
+            .lock()
+            .unwrap()
+            .is_empty());
+        let module_graph = compiler.context.module_graph.read().unwrap();
+        let module = module_graph.get_module(&data).unwrap();
+        assert!(module.info.as_ref().unwrap().raw.contains(r#""b""#));
+    }
+
+    /// Holds the build in the load of the data module until it's cancelled
+    struct BlockingPlugin {
+        started: Mutex<mpsc::Sender<()>>,
+    }
+
+    impl Plugin for BlockingPlugin {
+        fn name(&self) -> &str {
+            "blocking"
+        }
+
+        fn load(&self, param: &PluginLoadParam, context: &Arc<Context>) -> Result<Option<Content>> {
+            if param.file.path.ends_with("data.ts") {
+                self.started.lock().unwrap().send(()).unwrap();
+                while !context.cancellation.is_cancelled() {
+                    std::thread::sleep(std::time::Duration::from_millis(1));
+                }
+            }
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_cancel_and_wait() {
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/invalidate");
+        let (started_tx, started_rx) = mpsc::channel();
+        let plugin: Arc<dyn Plugin> = Arc::new(BlockingPlugin {
+            started: Mutex::new(started_tx),
+        });
+        let config = Config::new(&root, None, None).unwrap();
+        let compiler =
+            Arc::new(Compiler::new(config, root, Args::default(), Some(vec![plugin])).unwrap());
+        let (result_tx, result_rx) = mpsc::channel();
+        let building = compiler.clone();
+        std::thread::spawn(move || {
+            let result = building.compile();
+            result_tx.send(result.map_err(|e| e.to_string())).unwrap();
+        });
+        started_rx.recv().unwrap();
+        compiler.cancel_and_wait();
+        // the build has returned by the time the wait is over
+        let result = result_rx
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(result.unwrap_err(), "Build cancelled.");
+    }
+
+    #[test]
+    fn test_watch() {
+        let compiler = Compiler {
+            context: Arc::new(Context::default()),
+        };
+        let events = compiler.watch();
+        let params = PluginRebuildEndParams {
+            changed_files: vec!["/src/a.ts".to_string()],
+            ..Default::default()
+        };
+        compiler.context.emit_rebuild(&params);
+        assert_eq!(
+            events.try_recv().unwrap().changed_files,
+            params.changed_files
+        );
+
+        // the dropped receivers are unsubscribed
+        drop(events);
+        compiler.context.emit_rebuild(&params);
+        assert!(compiler
+            .context
+            .rebuild_listeners
+            .lock()
+            .unwrap()
+            .is_empty());
+    }
+}
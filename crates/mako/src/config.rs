@@ -1,30 +1,62 @@
 mod analyze;
+mod asset_fingerprint;
+mod asset_rules;
+mod chunk_hints;
+mod chunk_naming;
+mod cjs_named_exports;
 mod code_splitting;
+mod compat;
 mod dev_server;
 mod devtool;
+mod duplicate_modules;
 mod duplicate_package_checker;
+mod entry;
 mod experimental;
 mod external;
+mod font_subset;
+mod fs_routes;
 mod generic_usize;
 mod hmr;
+mod ignore_module;
 mod inline_css;
+mod legacy;
+mod lint;
+mod locale_filter;
+mod logical_properties_dir;
 mod macros;
 mod manifest;
+mod metafile;
+mod micro_frontend;
 mod minifish;
 mod mode;
 mod module_id_strategy;
+mod mpa;
+mod node;
 mod optimization;
 mod output;
+mod outputs;
+mod performance;
 mod progress;
 mod provider;
+mod purge_css;
 mod px2rem;
 mod react;
+mod remote_modules;
 mod resolve;
+mod resolve_cache;
 mod rsc_client;
 mod rsc_server;
+mod service_worker;
+mod size_report;
+mod source_map;
+mod sri;
+mod ssr;
 mod stats;
+mod style_injection;
 mod transform_import;
 mod tree_shaking;
+mod type_check;
+mod typescript;
 mod umd;
 mod watch;
 
@@ -34,47 +66,86 @@ use std::path::{Path, PathBuf};
 
 pub use analyze::AnalyzeConfig;
 use anyhow::{anyhow, Result};
+pub use asset_fingerprint::AssetFingerprint;
+pub use asset_rules::AssetRuleConfig;
+pub use chunk_hints::{deserialize_chunk_hints, ChunkHintsConfig};
+pub use chunk_naming::ChunkNaming;
+pub use cjs_named_exports::CjsNamedExportsCheck;
 pub use code_splitting::*;
 use colored::Colorize;
+pub use compat::CompatConfig;
 use config;
-pub use dev_server::{deserialize_dev_server, DevServerConfig};
+pub use dev_server::{deserialize_dev_server, DevServerConfig, HttpsConfig};
 pub use devtool::{deserialize_devtool, DevtoolConfig};
+pub use duplicate_modules::{deserialize_duplicate_modules, DuplicateModulesConfig};
 pub use duplicate_package_checker::{
     deserialize_check_duplicate_package, DuplicatePackageCheckerConfig,
 };
+pub use entry::{get_entries, glob_entry_name, is_glob_entry};
 use experimental::ExperimentalConfig;
 pub use external::{
     ExternalAdvanced, ExternalAdvancedSubpath, ExternalAdvancedSubpathConverter,
     ExternalAdvancedSubpathRule, ExternalAdvancedSubpathTarget, ExternalConfig,
 };
+pub use font_subset::{deserialize_font_subset, FontSubsetConfig};
+pub use fs_routes::{deserialize_fs_routes, FsRoutesConfig};
 pub use generic_usize::GenericUsizeDefault;
 pub use hmr::{deserialize_hmr, HmrConfig};
+pub use ignore_module::IgnoreModuleConfig;
 pub use inline_css::{deserialize_inline_css, InlineCssConfig};
+pub use legacy::{deserialize_legacy, LegacyConfig};
+pub use lint::{deserialize_lint, LintConfig};
+pub use locale_filter::{deserialize_locale_filter, LocaleFilterConfig};
+pub use logical_properties_dir::LogicalPropertiesDir;
 pub use manifest::{deserialize_manifest, ManifestConfig};
+pub use metafile::{deserialize_metafile, MetafileConfig};
+pub use micro_frontend::{deserialize_micro_frontend, MicroFrontendConfig};
 use miette::{miette, ByteOffset, Diagnostic, NamedSource, SourceOffset, SourceSpan};
 pub use minifish::{deserialize_minifish, MinifishConfig};
 pub use mode::Mode;
 pub use module_id_strategy::ModuleIdStrategy;
-pub use optimization::{deserialize_optimization, OptimizationConfig};
+pub use mpa::{deserialize_mpa, mpa_code_splitting, MpaConfig};
+pub use node::{deserialize_node, NodeConfig};
+pub use optimization::{deserialize_optimization, MangleExportsConfig, OptimizationConfig};
 use output::get_default_chunk_loading_global;
-pub use output::{CrossOriginLoading, OutputConfig, OutputMode};
+pub use output::{
+    CompressConfig, CrossOriginLoading, HashFunction, OutputComments, OutputConfig, OutputFormat,
+    OutputMode, WorkerChunkLoading,
+};
+pub use outputs::ExtraOutputConfig;
+pub use performance::{
+    deserialize_performance, PerformanceBudgets, PerformanceConfig, PerformanceHints,
+};
 pub use progress::{deserialize_progress, ProgressConfig};
-pub use provider::Providers;
+pub use provider::{Provider, Providers};
+pub use purge_css::{deserialize_purge_css, PurgeCssConfig};
 pub use px2rem::{deserialize_px2rem, Px2RemConfig};
 pub use react::{ReactConfig, ReactRuntimeConfig};
+pub use remote_modules::{deserialize_remote_modules, RemoteModulesConfig};
 pub use resolve::ResolveConfig;
+pub use resolve_cache::{deserialize_resolve_cache, ResolveCacheConfig};
 pub use rsc_client::{deserialize_rsc_client, LogServerComponent, RscClientConfig};
 pub use rsc_server::{deserialize_rsc_server, RscServerConfig};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+pub use service_worker::{deserialize_service_worker, ServiceWorkerConfig};
+pub use size_report::{deserialize_size_report, SizeReportConfig};
+pub use source_map::SourceMapConfig;
+pub use sri::{deserialize_sri, SriAlgorithm, SriConfig};
+pub use ssr::{deserialize_ssr, SsrConfig};
 pub use stats::{deserialize_stats, StatsConfig};
+pub use style_injection::{deserialize_style_injection, StyleInjectionConfig};
 use thiserror::Error;
 pub use transform_import::{TransformImportConfig, TransformImportStyle};
 pub use tree_shaking::{deserialize_tree_shaking, TreeShakingStrategy};
+pub use type_check::{deserialize_type_check, TypeCheckConfig};
+pub use typescript::{ImportsNotUsedAsValues, TypescriptConfig};
 pub use umd::{deserialize_umd, Umd};
 pub use watch::WatchConfig;
 
 use crate::features::node::Node;
+use crate::utils::get_pkg_name;
+use crate::utils::logger::print_warning;
 
 #[derive(Debug, Diagnostic)]
 #[diagnostic(code("mako.config.json parsed failed"))]
@@ -115,7 +186,7 @@ fn validate_mako_config(abs_config_file: String) -> miette::Result<()> {
     Ok(())
 }
 
-#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Platform {
     #[serde(rename = "browser")]
     Browser,
@@ -123,10 +194,13 @@ pub enum Platform {
     Node,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
     pub entry: HashMap<String, PathBuf>,
+    /// the glob entries, which are kept to add or remove the entries in watch mode
+    #[serde(skip)]
+    pub entry_globs: HashMap<String, String>,
     pub output: OutputConfig,
     pub resolve: ResolveConfig,
     #[serde(deserialize_with = "deserialize_manifest", default)]
@@ -141,6 +215,9 @@ pub struct Config {
     pub public_path: String,
     pub inline_limit: usize,
     pub inline_excludes_extensions: Vec<String>,
+    #[serde(default)]
+    pub asset_fingerprint: AssetFingerprint,
+    pub asset_rules: Vec<AssetRuleConfig>,
     pub targets: HashMap<String, f32>,
     pub platform: Platform,
     pub module_id_strategy: ModuleIdStrategy,
@@ -154,6 +231,10 @@ pub struct Config {
     pub dev_server: Option<DevServerConfig>,
     #[serde(deserialize_with = "deserialize_code_splitting", default)]
     pub code_splitting: Option<CodeSplitting>,
+    #[serde(deserialize_with = "deserialize_chunk_hints", default)]
+    pub chunk_hints: Option<ChunkHintsConfig>,
+    #[serde(default)]
+    pub chunk_naming: ChunkNaming,
     #[serde(deserialize_with = "deserialize_px2rem", default)]
     pub px2rem: Option<Px2RemConfig>,
     #[serde(deserialize_with = "deserialize_progress", default)]
@@ -169,12 +250,21 @@ pub struct Config {
     #[serde(deserialize_with = "deserialize_umd", default)]
     pub umd: Option<Umd>,
     pub cjs: bool,
+    pub single_file: bool,
+    #[serde(deserialize_with = "deserialize_mpa", default)]
+    pub mpa: Option<MpaConfig>,
     pub write_to_disk: bool,
     pub transform_import: Vec<TransformImportConfig>,
     pub chunk_parallel: bool,
     pub clean: bool,
     pub node_polyfill: bool,
+    #[serde(deserialize_with = "deserialize_node", default)]
+    pub node: Option<NodeConfig>,
     pub ignores: Vec<String>,
+    pub no_parse: Vec<String>,
+    pub ignore_modules: Vec<IgnoreModuleConfig>,
+    #[serde(deserialize_with = "deserialize_locale_filter", default)]
+    pub locale_filter: Option<LocaleFilterConfig>,
     #[serde(
         rename = "_minifish",
         deserialize_with = "deserialize_minifish",
@@ -185,9 +275,16 @@ pub struct Config {
     pub optimize_package_imports: bool,
     pub emotion: bool,
     pub flex_bugs: bool,
+    /// the logical properties are lowered with the direction when the targets don't support
+    /// them, they are kept as is if it's not set since the direction can't be known
+    #[serde(default)]
+    pub logical_properties_dir: Option<LogicalPropertiesDir>,
     #[serde(deserialize_with = "deserialize_optimization")]
     pub optimization: Option<OptimizationConfig>,
     pub react: ReactConfig,
+    pub typescript: TypescriptConfig,
+    #[serde(deserialize_with = "deserialize_type_check", default)]
+    pub type_check: Option<TypeCheckConfig>,
     pub emit_assets: bool,
     #[serde(rename = "cssModulesExportOnlyLocales")]
     pub css_modules_export_only_locales: bool,
@@ -211,14 +308,68 @@ pub struct Config {
     pub rsc_client: Option<RscClientConfig>,
     pub experimental: ExperimentalConfig,
     pub watch: WatchConfig,
+    /// the memory limit in MB, the asts are dropped in watch mode when it's exceeded
+    pub memory_limit: Option<usize>,
     pub use_define_for_class_fields: bool,
     pub emit_decorator_metadata: bool,
+    pub compat: CompatConfig,
     #[serde(
         rename = "duplicatePackageChecker",
         deserialize_with = "deserialize_check_duplicate_package",
         default
     )]
     pub check_duplicate_package: Option<DuplicatePackageCheckerConfig>,
+    #[serde(deserialize_with = "deserialize_duplicate_modules", default)]
+    pub duplicate_modules: Option<DuplicateModulesConfig>,
+    #[serde(deserialize_with = "deserialize_performance", default)]
+    pub performance: Option<PerformanceConfig>,
+    #[serde(
+        rename = "purgeCSS",
+        deserialize_with = "deserialize_purge_css",
+        default
+    )]
+    pub purge_css: Option<PurgeCssConfig>,
+    #[serde(deserialize_with = "deserialize_service_worker", default)]
+    pub service_worker: Option<ServiceWorkerConfig>,
+    #[serde(deserialize_with = "deserialize_font_subset", default)]
+    pub font_subset: Option<FontSubsetConfig>,
+    #[serde(deserialize_with = "deserialize_fs_routes", default)]
+    pub fs_routes: Option<FsRoutesConfig>,
+    #[serde(deserialize_with = "deserialize_remote_modules", default)]
+    pub remote_modules: Option<RemoteModulesConfig>,
+    #[serde(deserialize_with = "deserialize_size_report", default)]
+    pub size_report: Option<SizeReportConfig>,
+    #[serde(deserialize_with = "deserialize_metafile", default)]
+    pub metafile: Option<MetafileConfig>,
+    #[serde(deserialize_with = "deserialize_style_injection", default)]
+    pub style_injection: Option<StyleInjectionConfig>,
+    /// the runtime works in the shadow roots and the micro-frontend containers, the styles are
+    /// injected into the root of `__mako_container__` instead of document.head
+    #[serde(default)]
+    pub shadow_dom: bool,
+    /// keep the code between `// #if <condition>` and `// #endif` only when the condition is
+    /// active, node_modules are skipped
+    #[serde(default)]
+    pub conditional_compilation: bool,
+    #[serde(deserialize_with = "deserialize_micro_frontend", default)]
+    pub micro_frontend: Option<MicroFrontendConfig>,
+    #[serde(deserialize_with = "deserialize_resolve_cache", default)]
+    pub resolve_cache: Option<ResolveCacheConfig>,
+    pub source_map: SourceMapConfig,
+    #[serde(deserialize_with = "deserialize_sri", default)]
+    pub sri: Option<SriConfig>,
+    #[serde(deserialize_with = "deserialize_legacy", default)]
+    pub legacy: Option<LegacyConfig>,
+    #[serde(deserialize_with = "deserialize_lint", default)]
+    pub lint: Option<LintConfig>,
+    /// the extra outputs built from the same module graph, e.g. the esm, cjs and umd outputs
+    #[serde(default)]
+    pub outputs: Vec<ExtraOutputConfig>,
+    #[serde(deserialize_with = "deserialize_ssr", default)]
+    pub ssr: Option<SsrConfig>,
+    pub lossy_utf8: bool,
+    pub cjs_named_exports_check: CjsNamedExportsCheck,
+    pub env_prefix: String,
 }
 
 const CONFIG_FILE: &str = "mako.config.json";
@@ -270,6 +421,29 @@ impl Config {
                 config.output.path = root.join(config.output.path.to_string_lossy().to_string());
             }
 
+            // the preset of the qiankun and single-spa apps, the lifecycles of the entry are
+            // exposed by umd, and the styles go to the container of the app
+            if let Some(micro_frontend) = &config.micro_frontend {
+                if config.platform != Platform::Browser
+                    || config.output.mode == OutputMode::Bundless
+                    || config.cjs
+                {
+                    return Err(anyhow!(
+                        "microFrontend can only be used with browser platform and bundle mode"
+                    ));
+                }
+                let name = micro_frontend
+                    .name
+                    .clone()
+                    .or(config.umd.clone())
+                    .or_else(|| get_pkg_name(root))
+                    .ok_or_else(|| {
+                        anyhow!("microFrontend.name is required without package.json")
+                    })?;
+                config.umd = Some(name);
+                config.shadow_dom = true;
+            }
+
             if config.output.chunk_loading_global.is_empty() {
                 config.output.chunk_loading_global =
                     get_default_chunk_loading_global(config.umd.clone(), root);
@@ -278,14 +452,10 @@ impl Config {
             let node_env_config_opt = config.define.get("NODE_ENV");
             if let Some(node_env_config) = node_env_config_opt {
                 if node_env_config.as_str() != Some(config.mode.to_string().as_str()) {
-                    let warn_message = format!(
-                        "{}: The configuration of {} conflicts with current {} and will be overwritten as {} ",
-                        "warning".to_string().yellow(),
-                        "NODE_ENV".to_string().yellow(),
-                        "mode".to_string().yellow(),
-                        config.mode.to_string().red()
-                    );
-                    println!("{}", warn_message);
+                    print_warning(&format!(
+                        "The configuration of NODE_ENV conflicts with current mode and will be overwritten as {}",
+                        config.mode
+                    ));
                 }
             }
 
@@ -297,10 +467,107 @@ impl Config {
                 return Err(anyhow!("hmr can only be used with devServer",));
             }
 
-            if config.inline_css.is_some() && config.umd.is_none() {
+            if let Some(dev_server) = &config.dev_server {
+                if dev_server.http2 && dev_server.https.is_none() {
+                    return Err(anyhow!(
+                        "devServer.http2 can only be used with devServer.https"
+                    ));
+                }
+                if let Some(https) = &dev_server.https
+                    && https.cert.is_some() != https.key.is_some()
+                {
+                    return Err(anyhow!(
+                        "devServer.https.cert and devServer.https.key must be specified together"
+                    ));
+                }
+            }
+
+            if config.legacy.is_some()
+                && (config.platform != Platform::Browser
+                    || config.output.mode == OutputMode::Bundless)
+            {
+                return Err(anyhow!(
+                    "legacy can only be used with browser platform and bundle mode"
+                ));
+            }
+
+            if !config.outputs.is_empty() && config.output.mode == OutputMode::Bundless {
+                return Err(anyhow!("outputs can only be used with bundle mode"));
+            }
+            for output in &config.outputs {
+                if output.format.as_ref().unwrap_or(&config.output.format) == &OutputFormat::Esm
+                    && (output.umd.is_some() || output.cjs)
+                {
+                    return Err(anyhow!(
+                        "the esm format of outputs cannot be used with umd or cjs"
+                    ));
+                }
+            }
+
+            if config.output.format == OutputFormat::Esm
+                && (config.output.mode == OutputMode::Bundless
+                    || config.umd.is_some()
+                    || config.cjs)
+            {
+                return Err(anyhow!(
+                    "output.format esm can only be used with bundle mode, and cannot be used with umd or cjs"
+                ));
+            }
+            // the es module chunks can't be loaded by importScripts()
+            if config.output.format == OutputFormat::Esm
+                && config.output.worker_chunk_loading == Some(WorkerChunkLoading::ImportScripts)
+            {
+                return Err(anyhow!(
+                    "output.format esm cannot be used with output.workerChunkLoading importScripts"
+                ));
+            }
+
+            if config.ssr.is_some()
+                && (config.platform != Platform::Browser
+                    || config.output.mode == OutputMode::Bundless)
+            {
+                return Err(anyhow!(
+                    "ssr can only be used with browser platform and bundle mode"
+                ));
+            }
+
+            // inline the async chunks, css and assets into the entry chunks
+            if config.single_file {
+                if config.output.mode == OutputMode::Bundless {
+                    return Err(anyhow!("singleFile can only be used with bundle mode"));
+                }
+                config.dynamic_import_to_require = true;
+                config.code_splitting = None;
+                config.inline_limit = usize::MAX;
+                if config.inline_css.is_none() {
+                    config.inline_css = Some(InlineCssConfig {});
+                }
+            }
+
+            // emit a html for each entry, with the chunks split by the pages
+            if let Some(mpa) = &config.mpa {
+                if config.platform != Platform::Browser
+                    || config.output.mode == OutputMode::Bundless
+                {
+                    return Err(anyhow!(
+                        "mpa can only be used with browser platform and bundle mode"
+                    ));
+                }
+                if config.code_splitting.is_none() && !config.single_file {
+                    config.code_splitting = Some(mpa_code_splitting(mpa));
+                }
+            }
+
+            if config.inline_css.is_some() && config.umd.is_none() && !config.single_file {
                 return Err(anyhow!("inlineCSS can only be used with umd",));
             }
 
+            // the shorter hashes collide easily and can't be told from the other hex words in
+            // the file names, e.g. by the manifest
+            if config.output.hash_digest_length < 4 {
+                return Err(anyhow!("output.hashDigestLength must be at least 4"));
+            }
+
             let mode = format!("\"{}\"", config.mode);
             config
                 .define
@@ -344,20 +611,25 @@ impl Config {
                 }
             }
 
-            // normalize entry
-            let entry_tuples = config
+            // normalize entry, the glob entries are expanded
+            config.entry_globs = config
                 .entry
-                .clone()
-                .into_iter()
-                .map(|(k, v)| {
-                    if let Ok(entry_path) = root.join(v).canonicalize() {
-                        Ok((k, entry_path))
-                    } else {
-                        Err(anyhow!("entry:{} not found", k,))
-                    }
-                })
-                .collect::<Result<Vec<_>>>()?;
-            config.entry = entry_tuples.into_iter().collect();
+                .iter()
+                .map(|(k, v)| (k.clone(), v.to_string_lossy().to_string()))
+                .filter(|(_, v)| is_glob_entry(v))
+                .collect();
+            config.entry = get_entries(root, &config.entry)?;
+            if config.entry.is_empty() && !config.entry_globs.is_empty() {
+                return Err(anyhow!(
+                    "Entry is empty, no files match {}",
+                    config
+                        .entry_globs
+                        .values()
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
 
             // support relative alias
             config.resolve.alias = config
@@ -375,6 +647,11 @@ impl Config {
                 })
                 .collect();
 
+            // the integrity check of cross-origin resources requires cors
+            if config.sri.is_some() && config.output.cross_origin_loading.is_none() {
+                config.output.cross_origin_loading = Some(CrossOriginLoading::Anonymous);
+            }
+
             // dev 环境下不产生 hash, prod 环境下根据用户配置
             if config.mode == Mode::Development {
                 config.hash = false;
@@ -407,7 +684,7 @@ pub enum ConfigError {
 
 #[cfg(test)]
 mod tests {
-    use crate::config::{Config, Mode, Platform};
+    use crate::config::{Config, Mode, OutputFormat, Platform};
 
     #[test]
     fn test_config() {
@@ -470,6 +747,81 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    #[should_panic(expected = "output.hashDigestLength must be at least 4")]
+    fn test_config_invalid_hash_digest_length() {
+        let current_dir = std::env::current_dir().unwrap();
+        Config::new(
+            &current_dir.join("test/config/normal"),
+            None,
+            Some(r#"{"output":{"hashDigestLength":2}}"#),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_micro_frontend() {
+        let current_dir = std::env::current_dir().unwrap();
+        let config = Config::new(
+            &current_dir.join("test/config/normal"),
+            None,
+            Some(r#"{"platform":"browser","microFrontend":{"name":"app1"}}"#),
+        )
+        .unwrap();
+        assert_eq!(config.umd, Some("app1".to_string()));
+        assert_eq!(config.output.chunk_loading_global, "makoChunk_app1");
+        assert!(config.shadow_dom);
+    }
+
+    #[test]
+    fn test_esm_format_with_node_platform() {
+        let current_dir = std::env::current_dir().unwrap();
+        let config = Config::new(
+            &current_dir.join("test/config/normal"),
+            None,
+            Some(r#"{"output":{"format":"esm"}}"#),
+        )
+        .unwrap();
+        assert_eq!(config.platform, Platform::Node);
+        assert_eq!(config.output.format, OutputFormat::Esm);
+    }
+
+    #[test]
+    #[should_panic(expected = "output.format esm can only be used with bundle mode")]
+    fn test_esm_format_with_cjs() {
+        let current_dir = std::env::current_dir().unwrap();
+        Config::new(
+            &current_dir.join("test/config/normal"),
+            None,
+            Some(r#"{"output":{"format":"esm"},"cjs":true}"#),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "output.format esm cannot be used with output.workerChunkLoading")]
+    fn test_esm_format_with_import_scripts() {
+        let current_dir = std::env::current_dir().unwrap();
+        Config::new(
+            &current_dir.join("test/config/normal"),
+            None,
+            Some(r#"{"output":{"format":"esm","workerChunkLoading":"importScripts"}}"#),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "mpa can only be used with browser platform and bundle mode")]
+    fn test_mpa_with_node_platform() {
+        let current_dir = std::env::current_dir().unwrap();
+        Config::new(
+            &current_dir.join("test/config/node-platform"),
+            None,
+            Some(r#"{"mpa":{}}"#),
+        )
+        .unwrap();
+    }
+
     #[test]
     fn test_node_platform() {
         let current_dir = std::env::current_dir().unwrap();
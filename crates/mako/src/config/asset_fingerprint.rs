@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// How the content hashes of the emitted assets are added for the cache busting
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AssetFingerprint {
+    /// e.g. `logo.1a2b3c4d.png`
+    #[serde(rename = "filename")]
+    #[default]
+    Filename,
+    /// keep the original filenames and add the hashes to the references, e.g. `logo.png?v=1a2b3c4d`
+    #[serde(rename = "query")]
+    Query,
+}
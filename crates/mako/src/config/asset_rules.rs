@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetRuleConfig {
+    /// the mime type of the assets, e.g. `image/svg+xml`, or all the subtypes with `font/*`
+    pub mime: String,
+    /// the output filename template, e.g. `fonts/[name].[hash].[ext]`
+    pub filename: Option<String>,
+    /// overrides inlineLimit for the assets, 0 to never inline them
+    pub inline_limit: Option<usize>,
+}
+
+impl AssetRuleConfig {
+    pub fn matches(&self, mime: &str) -> bool {
+        match self.mime.strip_suffix("/*") {
+            Some(top_level) => mime.split('/').next() == Some(top_level),
+            None => self.mime == mime,
+        }
+    }
+}
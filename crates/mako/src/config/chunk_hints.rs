@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct ChunkHintsConfig {
+    /// the regexes of the dynamically imported modules whose chunks are prefetched
+    #[serde(default)]
+    pub prefetch: Vec<String>,
+    /// the regexes of the dynamically imported modules whose chunks are preloaded
+    #[serde(default)]
+    pub preload: Vec<String>,
+}
+
+create_deserialize_fn!(deserialize_chunk_hints, ChunkHintsConfig);
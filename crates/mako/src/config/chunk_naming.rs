@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// How the async chunks are named
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChunkNaming {
+    /// named after the ids of the imported modules, e.g. `a1b2c3d4-async.js`
+    #[serde(rename = "id")]
+    #[default]
+    Id,
+    /// the chunks of the pages are named after the page paths, e.g. `pages_user_settings-async.js`
+    #[serde(rename = "route")]
+    Route,
+}
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// How to report the named imports which can't be found in the exports of the commonjs
+/// modules they're imported from
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum CjsNamedExportsCheck {
+    Off,
+    #[default]
+    Warn,
+    /// fail the build, e.g. in ci
+    Error,
+}
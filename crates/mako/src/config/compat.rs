@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// The loose modes of the syntax lowering, same as the `loose` options of the babel plugins,
+/// the outputs are smaller and faster but less spec compliant
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CompatConfig {
+    /// assign the class properties instead of `Object.defineProperty`,
+    /// same as `@babel/plugin-transform-class-properties` with `loose: true`
+    #[serde(default)]
+    pub class_properties_loose: bool,
+    /// define the private methods and fields as properties with unique names instead of
+    /// `WeakMap`s and `WeakSet`s, same as `@babel/plugin-transform-private-methods` with
+    /// `loose: true`
+    #[serde(default)]
+    pub private_methods_loose: bool,
+    /// compare with `== null` instead of checking both `null` and `undefined`, same as
+    /// `@babel/plugin-transform-optional-chaining` with `loose: true`
+    #[serde(default)]
+    pub optional_chaining_loose: bool,
+}
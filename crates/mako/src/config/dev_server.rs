@@ -2,11 +2,80 @@ use serde::{Deserialize, Serialize};
 
 use crate::create_deserialize_fn;
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct DevServerConfig {
     pub host: String,
     pub port: u16,
+    #[serde(deserialize_with = "deserialize_https", default)]
+    pub https: Option<HttpsConfig>,
+    /// negotiated with alpn, so it requires https
+    #[serde(default)]
+    pub http2: bool,
+    /// exit instead of trying the next port when the port is in use
+    #[serde(default)]
+    pub strict_port: bool,
+    /// the path to open in the browser after the server starts, `Some(None)` if it's disabled,
+    /// the root path is opened by the mako cli if it's not specified
+    #[serde(
+        deserialize_with = "deserialize_open",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub open: Option<Option<String>>,
+    /// emit the chunks of the entries served to the browsers first after the changes, and
+    /// defer the chunks of the other entries after the hmr message is sent
+    #[serde(default)]
+    pub on_demand_entries: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct HttpsConfig {
+    /// pem files of the certificate and the private key, relative to root, a self-signed
+    /// certificate of localhost is generated if they are not specified
+    pub cert: Option<String>,
+    pub key: Option<String>,
+}
+
+// `true` opens the root path, `null` is the same as not specified
+fn deserialize_open<'de, D>(deserializer: D) -> Result<Option<Option<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value: serde_json::Value = serde_json::Value::deserialize(deserializer)?;
+    match value {
+        serde_json::Value::Null => Ok(None),
+        serde_json::Value::Bool(false) => Ok(Some(None)),
+        serde_json::Value::Bool(true) => Ok(Some(Some("/".to_string()))),
+        serde_json::Value::String(path) => Ok(Some(Some(path))),
+        _ => Err(serde::de::Error::custom(format!(
+            "invalid `open` value: {}",
+            value
+        ))),
+    }
 }
 
 create_deserialize_fn!(deserialize_dev_server, DevServerConfig);
+create_deserialize_fn!(deserialize_https, HttpsConfig);
+
+#[cfg(test)]
+mod tests {
+    use super::DevServerConfig;
+
+    #[test]
+    fn test_open() {
+        let open = |value: &str| {
+            serde_json::from_str::<DevServerConfig>(&format!(
+                r#"{{"host":"127.0.0.1","port":3000{}}}"#,
+                value
+            ))
+            .unwrap()
+            .open
+        };
+        assert_eq!(open(""), None);
+        assert_eq!(open(r#","open":null"#), None);
+        assert_eq!(open(r#","open":false"#), Some(None));
+        assert_eq!(open(r#","open":true"#), Some(Some("/".to_string())));
+        assert_eq!(open(r#","open":"/docs""#), Some(Some("/docs".to_string())));
+    }
+}
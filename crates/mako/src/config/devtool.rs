@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::create_deserialize_fn;
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub enum DevtoolConfig {
     /// Generate separate sourcemap file
     #[serde(rename = "source-map")]
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateModulesConfig {
+    /// print the modules emitted into multiple chunks with the wasted bytes
+    #[serde(default)]
+    pub report: bool,
+    /// hoist the modules duplicated in the async chunks into shared chunks
+    #[serde(default)]
+    pub hoist: bool,
+    /// the min number of the chunks a module is duplicated in to be hoisted
+    #[serde(default = "default_min_chunks")]
+    pub min_chunks: usize,
+}
+
+fn default_min_chunks() -> usize {
+    2
+}
+
+create_deserialize_fn!(deserialize_duplicate_modules, DuplicateModulesConfig);
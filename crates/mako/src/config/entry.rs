@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use glob::{glob, MatchOptions, Pattern};
+use pathdiff::diff_paths;
+
+use crate::ast::file::win_path;
+
+pub fn is_glob_entry(entry: &str) -> bool {
+    entry.contains(['*', '?', '['])
+}
+
+/// Resolve the entries to absolute paths, the glob entries like `"pages": "src/pages/**/index.tsx"`
+/// are expanded to one entry per matched file, see [glob_entry_name] for the names
+pub fn get_entries(
+    root: &Path,
+    entry: &HashMap<String, PathBuf>,
+) -> Result<HashMap<String, PathBuf>> {
+    let mut entries = HashMap::new();
+    for (name, path) in entry {
+        let pattern = path.to_string_lossy();
+        if !is_glob_entry(&pattern) {
+            let entry_path = root
+                .join(path)
+                .canonicalize()
+                .map_err(|_| anyhow!("entry:{} not found", name))?;
+            entries.insert(name.clone(), entry_path);
+            continue;
+        }
+        // the root may contain the special characters like `[` which are not a part of the pattern
+        let root_pattern = PathBuf::from(Pattern::escape(&root.to_string_lossy()));
+        let paths = glob(&root_pattern.join(path).to_string_lossy())
+            .map_err(|e| anyhow!("entry:{} is not a valid glob pattern, {}", name, e))?;
+        for path in paths.flatten().filter(|path| path.is_file()) {
+            if let Some(entry_name) = glob_entry_name(root, name, &pattern, &path) {
+                entries.insert(entry_name, path.canonicalize()?);
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// The name of the entry expanded from a glob entry, which is the key joined with the path
+/// relative to the static part of the pattern, without the extension, e.g.
+/// pages + src/pages/**/index.tsx + src/pages/home/index.tsx => pages/home/index
+/// returns None if the path doesn't match the pattern
+pub fn glob_entry_name(root: &Path, name: &str, pattern: &str, path: &Path) -> Option<String> {
+    let pattern = pattern.trim_start_matches("./");
+    let relative_path = diff_paths(path, root)?;
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..Default::default()
+    };
+    if !Pattern::new(pattern)
+        .ok()?
+        .matches_path_with(&relative_path, options)
+    {
+        return None;
+    }
+    let base = Path::new(pattern)
+        .components()
+        .take_while(|c| !is_glob_entry(&c.as_os_str().to_string_lossy()))
+        .collect::<PathBuf>();
+    let relative_to_base = relative_path.strip_prefix(base).ok()?.with_extension("");
+    Some(format!(
+        "{}/{}",
+        name,
+        win_path(&relative_to_base.to_string_lossy())
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+
+    use super::{get_entries, glob_entry_name, is_glob_entry};
+
+    #[test]
+    fn test_is_glob_entry() {
+        assert!(is_glob_entry("src/pages/**/index.tsx"));
+        assert!(is_glob_entry("src/pages/?.tsx"));
+        assert!(!is_glob_entry("src/index.tsx"));
+    }
+
+    #[test]
+    fn test_glob_entry_name() {
+        let root = Path::new("/root");
+        assert_eq!(
+            glob_entry_name(
+                root,
+                "pages",
+                "src/pages/**/index.tsx",
+                Path::new("/root/src/pages/home/index.tsx")
+            ),
+            Some("pages/home/index".to_string())
+        );
+        assert_eq!(
+            glob_entry_name(
+                root,
+                "pages",
+                "./src/pages/*.ts",
+                Path::new("/root/src/pages/about.ts")
+            ),
+            Some("pages/about".to_string())
+        );
+        assert_eq!(
+            glob_entry_name(
+                root,
+                "pages",
+                "src/pages/*.ts",
+                Path::new("/root/src/pages/nested/about.ts")
+            ),
+            None
+        );
+        assert_eq!(
+            glob_entry_name(
+                root,
+                "pages",
+                "src/pages/**/index.tsx",
+                Path::new("/root/src/utils.tsx")
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_get_entries_with_special_root() {
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("test/config/entry-glob/[root]")
+            .canonicalize()
+            .unwrap();
+        let entry = HashMap::from([
+            ("index".to_string(), PathBuf::from("src/index.ts")),
+            ("pages".to_string(), PathBuf::from("src/pages/**/index.ts")),
+        ]);
+        let entries = get_entries(&root, &entry).unwrap();
+        let mut names = entries.keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["index", "pages/about/index", "pages/home/index"]
+        );
+        assert_eq!(
+            entries.get("pages/home/index"),
+            Some(&root.join("src/pages/home/index.ts"))
+        );
+    }
+}
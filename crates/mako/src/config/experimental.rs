@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::create_deserialize_fn;
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ExperimentalConfig {
     pub webpack_syntax_validate: Vec<String>,
@@ -12,7 +12,7 @@ pub struct ExperimentalConfig {
     pub detect_circular_dependence: Option<DetectCircularDependence>,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct DetectCircularDependence {
     pub ignores: Vec<String>,
@@ -1,19 +1,19 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Debug, Hash)]
+#[derive(Deserialize, Serialize, Debug, Hash, Clone)]
 #[serde(untagged)]
 pub enum ExternalConfig {
     Basic(String),
     Advanced(ExternalAdvanced),
 }
 
-#[derive(Deserialize, Serialize, Debug, Hash)]
+#[derive(Deserialize, Serialize, Debug, Hash, Clone)]
 pub struct ExternalAdvancedSubpath {
     pub exclude: Option<Vec<String>>,
     pub rules: Vec<ExternalAdvancedSubpathRule>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Hash)]
+#[derive(Deserialize, Serialize, Debug, Hash, Clone)]
 pub struct ExternalAdvanced {
     pub root: String,
     #[serde(rename = "type")]
@@ -22,7 +22,7 @@ pub struct ExternalAdvanced {
     pub subpath: Option<ExternalAdvancedSubpath>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Hash)]
+#[derive(Deserialize, Serialize, Debug, Hash, Clone)]
 pub struct ExternalAdvancedSubpathRule {
     pub regex: String,
     #[serde(with = "external_target_format")]
@@ -31,12 +31,12 @@ pub struct ExternalAdvancedSubpathRule {
     pub target_converter: Option<ExternalAdvancedSubpathConverter>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Hash)]
+#[derive(Deserialize, Serialize, Debug, Hash, Clone)]
 pub enum ExternalAdvancedSubpathConverter {
     PascalCase,
 }
 
-#[derive(Deserialize, Serialize, Debug, Hash)]
+#[derive(Deserialize, Serialize, Debug, Hash, Clone)]
 #[serde(untagged)]
 pub enum ExternalAdvancedSubpathTarget {
     Empty,
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct FontSubsetConfig {
+    /// the globs of the files relative to root whose characters are kept in the fonts
+    #[serde(default = "default_content")]
+    pub content: Vec<String>,
+    /// the command of the subsetter, which accepts the options of pyftsubset
+    #[serde(default = "default_command")]
+    pub command: String,
+}
+
+fn default_content() -> Vec<String> {
+    vec!["src/**/*".to_string()]
+}
+
+fn default_command() -> String {
+    "pyftsubset".to_string()
+}
+
+create_deserialize_fn!(deserialize_font_subset, FontSubsetConfig);
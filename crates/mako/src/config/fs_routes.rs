@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct FsRoutesConfig {
+    /// the pages dir to generate the routes from, relative to root
+    #[serde(default = "default_dir")]
+    pub dir: String,
+}
+
+fn default_dir() -> String {
+    "src/pages".to_string()
+}
+
+create_deserialize_fn!(deserialize_fs_routes, FsRoutesConfig);
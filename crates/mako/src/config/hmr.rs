@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::create_deserialize_fn;
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct HmrConfig {}
 
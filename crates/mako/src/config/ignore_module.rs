@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IgnoreModuleConfig {
+    /// regex pattern of the request, e.g. `^\./locale$`
+    pub resource_reg_exp: String,
+    /// regex pattern of the directory of the importer, e.g. `moment$`
+    pub context_reg_exp: Option<String>,
+}
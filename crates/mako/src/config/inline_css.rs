@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::create_deserialize_fn;
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct InlineCssConfig {}
 
 create_deserialize_fn!(deserialize_inline_css, InlineCssConfig);
@@ -0,0 +1,25 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct LegacyConfig {
+    /// browser targets of the legacy output
+    #[serde(default = "default_targets")]
+    pub targets: HashMap<String, f32>,
+    /// build the core-js polyfills of the targets into `polyfills.js` of the legacy output
+    #[serde(default = "default_polyfills")]
+    pub polyfills: bool,
+}
+
+fn default_polyfills() -> bool {
+    true
+}
+
+fn default_targets() -> HashMap<String, f32> {
+    HashMap::from([("ie".to_string(), 11.0)])
+}
+
+create_deserialize_fn!(deserialize_legacy, LegacyConfig);
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LintConfig {
+    /// the command of the linter, e.g. `eslint`, defaults to the oxlint or eslint of the project
+    pub command: Option<String>,
+    /// whether the build fails when there are lint errors, only works in build mode
+    #[serde(default = "default_fail_on_error")]
+    pub fail_on_error: bool,
+}
+
+fn default_fail_on_error() -> bool {
+    true
+}
+
+create_deserialize_fn!(deserialize_lint, LintConfig);
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct LocaleFilterConfig {
+    /// locales of moment and dayjs which should be kept, e.g. `zh-cn`
+    #[serde(default)]
+    pub locales: Vec<String>,
+}
+
+create_deserialize_fn!(deserialize_locale_filter, LocaleFilterConfig);
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+/// The direction which the logical properties are lowered to the physical ones with
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum LogicalPropertiesDir {
+    Ltr,
+    Rtl,
+}
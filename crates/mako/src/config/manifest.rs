@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{create_deserialize_fn, plugins};
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ManifestConfig {
     #[serde(
         rename(deserialize = "fileName"),
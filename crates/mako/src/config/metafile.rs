@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MetafileConfig {
+    /// the file name of the metafile in the output dir
+    #[serde(default = "default_file_name")]
+    pub file_name: String,
+}
+
+fn default_file_name() -> String {
+    "metafile.json".to_string()
+}
+
+create_deserialize_fn!(deserialize_metafile, MetafileConfig);
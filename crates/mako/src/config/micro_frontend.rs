@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct MicroFrontendConfig {
+    /// the name of the app, which is the umd name the lifecycles are exposed as, the package
+    /// name by default
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+create_deserialize_fn!(deserialize_micro_frontend, MicroFrontendConfig);
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::create_deserialize_fn;
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct MinifishConfig {
     pub mapping: HashMap<String, String>,
@@ -13,7 +13,7 @@ pub struct MinifishConfig {
     pub inject: Option<HashMap<String, InjectItem>>,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct InjectItem {
     pub from: String,
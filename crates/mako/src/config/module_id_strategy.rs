@@ -8,4 +8,8 @@ pub enum ModuleIdStrategy {
     Named,
     #[serde(rename = "numeric")]
     Numeric,
+    /// the numeric ids derived from the hashes of the relative paths, which don't change when
+    /// the other modules are added, removed or renamed
+    #[serde(rename = "deterministic")]
+    Deterministic,
 }
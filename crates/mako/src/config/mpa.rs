@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    AllowChunks, ChunkGroup, ChunkNameSuffixStrategy, CodeSplitting, CodeSplittingAdvancedOptions,
+    CodeSplittingStrategy, CodeSplittingStrategyOptions,
+};
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MpaConfig {
+    /// the html template of the pages relative to root, the tags of the chunks are injected
+    /// before `</head>` and `</body>`
+    pub template: Option<String>,
+    /// put the node_modules of all pages into one vendors chunk, or split them by the pages
+    /// using them if false
+    #[serde(default = "default_true")]
+    pub shared_vendors: bool,
+    /// the scripts not larger than the limit in bytes are inlined into the html, which inlines
+    /// the runtime of the entry chunk to save a request on first paint, 0 to disable
+    #[serde(default)]
+    pub inline_limit: usize,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// The code splitting of the pages, which is used when `codeSplitting` is not configured
+pub fn mpa_code_splitting(config: &MpaConfig) -> CodeSplitting {
+    CodeSplitting {
+        strategy: CodeSplittingStrategy::Advanced,
+        options: Some(CodeSplittingStrategyOptions::Advanced(
+            CodeSplittingAdvancedOptions {
+                groups: vec![
+                    ChunkGroup {
+                        name: "vendors".to_string(),
+                        name_suffix: (!config.shared_vendors)
+                            .then_some(ChunkNameSuffixStrategy::DependentsHash),
+                        allow_chunks: AllowChunks::All,
+                        test: Some(r"[/\\]node_modules[/\\]".to_string()),
+                        priority: -10,
+                        ..Default::default()
+                    },
+                    ChunkGroup {
+                        name: "common".to_string(),
+                        name_suffix: Some(ChunkNameSuffixStrategy::DependentsHash),
+                        allow_chunks: AllowChunks::All,
+                        min_chunks: 2,
+                        // always split, to avoid multi-instance risk
+                        min_size: 1,
+                        priority: -20,
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+        )),
+    }
+}
+
+create_deserialize_fn!(deserialize_mpa, MpaConfig);
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+/// Shims of node globals for non-node platforms
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct NodeConfig {
+    /// replace `__dirname` with the dir of the module relative to root
+    #[serde(rename = "__dirname", default = "default_true")]
+    pub dirname: bool,
+    /// replace `__filename` with the path of the module relative to root
+    #[serde(rename = "__filename", default = "default_true")]
+    pub filename: bool,
+    /// replace `global` with `globalThis`
+    #[serde(default = "default_true")]
+    pub global: bool,
+    /// polyfill `process` with the `process` package
+    #[serde(default = "default_true")]
+    pub process: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+create_deserialize_fn!(deserialize_node, NodeConfig);
@@ -2,11 +2,23 @@ use serde::{Deserialize, Serialize};
 
 use crate::create_deserialize_fn;
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct OptimizationConfig {
     pub skip_modules: Option<bool>,
     pub concatenate_modules: Option<bool>,
+    #[serde(deserialize_with = "deserialize_mangle_exports", default)]
+    pub mangle_exports: Option<MangleExportsConfig>,
+    /// move the css modules shared by several async chunks into a shared chunk
+    pub hoist_shared_css: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct MangleExportsConfig {
+    /// the export names which are never mangled
+    #[serde(default)]
+    pub reserved: Vec<String>,
 }
 
 create_deserialize_fn!(deserialize_optimization, OptimizationConfig);
+create_deserialize_fn!(deserialize_mangle_exports, MangleExportsConfig);
@@ -8,11 +8,12 @@ use swc_core::ecma::ast::EsVersion;
 use crate::create_deserialize_fn;
 use crate::utils::get_pkg_name;
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct OutputConfig {
     pub path: PathBuf,
     pub mode: OutputMode,
+    pub format: OutputFormat,
     pub es_version: EsVersion,
     pub meta: bool,
     pub chunk_loading_global: String,
@@ -22,6 +23,62 @@ pub struct OutputConfig {
     #[serde(deserialize_with = "deserialize_cross_origin_loading")]
     pub cross_origin_loading: Option<CrossOriginLoading>,
     pub global_module_registry: bool,
+    #[serde(deserialize_with = "deserialize_compress", default)]
+    pub compress: Option<CompressConfig>,
+    #[serde(deserialize_with = "deserialize_chunk_load_retry", default)]
+    pub chunk_load_retry: Option<ChunkLoadRetryConfig>,
+    /// the comments kept in the js outputs, all by default and none when minified
+    #[serde(default)]
+    pub comments: Option<OutputComments>,
+    /// escape the non-ascii characters in the js outputs, enabled by default when minified
+    #[serde(default)]
+    pub ascii_only: Option<bool>,
+    /// the global name the runtime api is exposed as, for the bundles built by others to
+    /// register and require the modules at runtime
+    #[serde(default)]
+    pub runtime_global: Option<String>,
+    /// the nonce attribute of the tags in the html, usually a placeholder replaced by the server
+    #[serde(default)]
+    pub csp_nonce: Option<String>,
+    /// how the worker chunks load their async chunks, by the output format by default
+    #[serde(default)]
+    pub worker_chunk_loading: Option<WorkerChunkLoading>,
+    /// the hash function of the content hashes in the file names and the hashed module ids
+    #[serde(default)]
+    pub hash_function: HashFunction,
+    #[serde(default = "default_hash_digest_length")]
+    pub hash_digest_length: usize,
+}
+
+fn default_hash_digest_length() -> usize {
+    8
+}
+
+impl OutputConfig {
+    pub fn comments_or_default(&self, minify: bool) -> OutputComments {
+        self.comments.unwrap_or(if minify {
+            OutputComments::None
+        } else {
+            OutputComments::All
+        })
+    }
+
+    pub fn ascii_only_or_default(&self, minify: bool) -> bool {
+        self.ascii_only.unwrap_or(minify)
+    }
+
+    /// the length of the content hashes, no longer than the digest of the hash function
+    pub fn hash_length(&self) -> usize {
+        self.hash_digest_length
+            .min(self.hash_function.max_digest_length())
+    }
+
+    pub fn worker_chunk_loading_or_default(&self) -> WorkerChunkLoading {
+        self.worker_chunk_loading.unwrap_or(match self.format {
+            OutputFormat::Esm => WorkerChunkLoading::Import,
+            _ => WorkerChunkLoading::ImportScripts,
+        })
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug, PartialEq, Eq, ValueEnum, Clone)]
@@ -32,6 +89,56 @@ pub enum OutputMode {
     Bundless,
 }
 
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+pub enum OutputFormat {
+    /// chunks are classic scripts loaded by `<script>` tags
+    #[serde(rename = "iife")]
+    Iife,
+    /// chunks are es modules loaded by `import()` and the entry imports its shared chunks
+    #[serde(rename = "esm")]
+    Esm,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputComments {
+    None,
+    /// `/*! ... */` and the comments with `@license` or `@preserve`
+    License,
+    All,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum HashFunction {
+    #[default]
+    Md5,
+    /// the fastest, for the large projects
+    Xxhash64,
+    /// for the pipelines which only allow the sha-2 family
+    Sha256,
+}
+
+impl HashFunction {
+    pub fn max_digest_length(&self) -> usize {
+        match self {
+            HashFunction::Md5 => 32,
+            HashFunction::Xxhash64 => 16,
+            HashFunction::Sha256 => 64,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WorkerChunkLoading {
+    /// classic workers, the chunks are loaded synchronously by `importScripts()`
+    #[serde(rename = "importScripts")]
+    ImportScripts,
+    /// module workers, created with `{ type: "module" }`, the chunks are loaded by `import()`
+    #[serde(rename = "import")]
+    Import,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub enum CrossOriginLoading {
     #[serde(rename = "anonymous")]
@@ -55,4 +162,48 @@ pub fn get_default_chunk_loading_global(umd: Option<String>, root: &Path) -> Str
     format!("makoChunk_{}", unique_name)
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CompressConfig {
+    #[serde(default = "default_true")]
+    pub gzip: bool,
+    #[serde(default = "default_true")]
+    pub brotli: bool,
+    /// only files larger than this size (in bytes) will be compressed
+    #[serde(default = "default_compress_threshold")]
+    pub threshold: usize,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkLoadRetryConfig {
+    /// how many times a failed chunk is reloaded before giving up
+    #[serde(default = "default_chunk_load_retries")]
+    pub retries: usize,
+    /// the delay (in ms) before the first retry, doubled for each of the next retries
+    #[serde(default = "default_chunk_load_retry_delay")]
+    pub delay: usize,
+    /// the public paths tried in turn by the retries after the configured public path
+    #[serde(default)]
+    pub fallback_public_paths: Vec<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_compress_threshold() -> usize {
+    1024
+}
+
+fn default_chunk_load_retries() -> usize {
+    3
+}
+
+fn default_chunk_load_retry_delay() -> usize {
+    1000
+}
+
 create_deserialize_fn!(deserialize_cross_origin_loading, CrossOriginLoading);
+create_deserialize_fn!(deserialize_compress, CompressConfig);
+create_deserialize_fn!(deserialize_chunk_load_retry, ChunkLoadRetryConfig);
@@ -0,0 +1,26 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::{OutputFormat, Platform, Umd};
+
+/// An extra output built from the module graph of the main output, the options not set are the
+/// same as the main output, except cjs and umd which are not inherited
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtraOutputConfig {
+    /// the output directory relative to root
+    pub path: PathBuf,
+    pub format: Option<OutputFormat>,
+    pub platform: Option<Platform>,
+    #[serde(default)]
+    pub cjs: bool,
+    pub umd: Option<Umd>,
+    /// the scripts are lowered further to the targets, the build stage follows the main targets
+    pub targets: Option<HashMap<String, f32>>,
+    /// the dependencies externalized in addition to the main externals, e.g. `"react": "React"`
+    /// or `"react": "commonjs react"`
+    #[serde(default)]
+    pub externals: HashMap<String, String>,
+}
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PerformanceConfig {
+    #[serde(default)]
+    pub budgets: PerformanceBudgets,
+    #[serde(default)]
+    pub hints: PerformanceHints,
+}
+
+/// All sizes are in bytes, source maps are not counted.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PerformanceBudgets {
+    pub max_entry_size: Option<u64>,
+    pub max_asset_size: Option<u64>,
+    pub max_total_size: Option<u64>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PerformanceHints {
+    #[serde(rename = "warning")]
+    #[default]
+    Warning,
+    #[serde(rename = "error")]
+    Error,
+}
+
+create_deserialize_fn!(deserialize_performance, PerformanceConfig);
@@ -1,7 +1,45 @@
 use std::collections::HashMap;
 
-// format: HashMap<identifier, (import_source, specifier)>
+use serde::{Deserialize, Serialize};
+
+// format: HashMap<identifier, Provider>
 // e.g.
-// { "process": ("process", "") }
-// { "Buffer": ("buffer", "Buffer") }
-pub type Providers = HashMap<String, (String, String)>;
+// { "$": "jquery" }
+// { "process": ["process", ""] }
+// { "Buffer": ["buffer", "Buffer"] }
+// { "Fragment": ["react", "default", "Fragment"] }
+pub type Providers = HashMap<String, Provider>;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Provider {
+    /// the whole module
+    Module(String),
+    /// the module and the path of properties to access
+    Member(Vec<String>),
+}
+
+impl Provider {
+    pub fn new(source: &str, props: &[&str]) -> Self {
+        let mut path = vec![source.to_string()];
+        path.extend(props.iter().map(|prop| prop.to_string()));
+        Provider::Member(path)
+    }
+
+    /// Returns the import source and the properties to access, empty properties
+    /// are ignored, e.g. `["process", ""]` is the same as `"process"`
+    pub fn parse(&self) -> Option<(&str, Vec<&str>)> {
+        match self {
+            Provider::Module(source) => Some((source.as_str(), vec![])),
+            Provider::Member(path) => {
+                let (source, props) = path.split_first()?;
+                let props = props
+                    .iter()
+                    .map(|prop| prop.as_str())
+                    .filter(|prop| !prop.is_empty())
+                    .collect();
+                Some((source.as_str(), props))
+            }
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct PurgeCssConfig {
+    /// regex patterns of class names which should always be kept
+    #[serde(default)]
+    pub safelist: Vec<String>,
+    /// extra files (globs relative to root) to scan for used class names, e.g. html templates
+    #[serde(default)]
+    pub content: Vec<String>,
+}
+
+create_deserialize_fn!(deserialize_purge_css, PurgeCssConfig);
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteModulesConfig {
+    /// the dir to store the downloaded modules, relative to root
+    #[serde(default = "default_cache_dir")]
+    pub cache_dir: String,
+    /// the lockfile with the integrities of the remote modules, relative to root
+    #[serde(default = "default_lockfile")]
+    pub lockfile: String,
+    /// fail the build when a remote module is not in the lockfile instead of adding it,
+    /// e.g. in the CI
+    #[serde(default)]
+    pub frozen_lockfile: bool,
+}
+
+fn default_cache_dir() -> String {
+    "node_modules/.cache_mako/remote".to_string()
+}
+
+fn default_lockfile() -> String {
+    "mako.lock.json".to_string()
+}
+
+create_deserialize_fn!(deserialize_remote_modules, RemoteModulesConfig);
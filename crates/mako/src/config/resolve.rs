@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ResolveConfig {
     pub alias: Vec<(String, String)>,
     pub extensions: Vec<String>,
+    /// the custom conditions of the package exports, also the conditions of `#if`
+    #[serde(default)]
+    pub conditions: Vec<String>,
 }
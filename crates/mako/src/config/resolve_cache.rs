@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveCacheConfig {
+    /// the dir to store the resolutions of the last build, relative to root
+    #[serde(default = "default_cache_dir")]
+    pub cache_dir: String,
+}
+
+fn default_cache_dir() -> String {
+    "node_modules/.cache_mako".to_string()
+}
+
+create_deserialize_fn!(deserialize_resolve_cache, ResolveCacheConfig);
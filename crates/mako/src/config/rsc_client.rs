@@ -3,10 +3,30 @@ use serde::{Deserialize, Serialize};
 
 use crate::create_deserialize_fn;
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct RscClientConfig {
     pub log_server_component: LogServerComponent,
+    /// the globs of the client components which are built into their own async chunks
+    #[serde(default)]
+    pub client_references: Vec<String>,
+    /// the file name of the client reference manifest
+    #[serde(default = "default_client_manifest")]
+    pub manifest: String,
+    /// the stub of the server actions, with `{{id}}`, `{{name}}` and `{{path}}`
+    #[serde(default)]
+    pub server_action_tpl: Option<String>,
+    /// the file name of the server actions manifest
+    #[serde(default = "default_server_actions_manifest")]
+    pub server_actions_manifest: String,
+}
+
+fn default_client_manifest() -> String {
+    "react-client-manifest.json".to_string()
+}
+
+fn default_server_actions_manifest() -> String {
+    "server-actions-manifest.json".to_string()
 }
 
 #[derive(Deserialize, Serialize, Debug, PartialEq, Eq, ValueEnum, Clone)]
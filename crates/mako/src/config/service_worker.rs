@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceWorkerConfig {
+    /// the source file of the service worker, relative to root
+    pub entry: String,
+    /// the output filename of the service worker
+    #[serde(default = "default_filename")]
+    pub filename: String,
+    /// register the service worker in the entries
+    #[serde(default = "default_true")]
+    pub register: bool,
+    pub scope: Option<String>,
+    /// regex patterns of the emitted files which should not be precached
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+fn default_filename() -> String {
+    "sw.js".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+create_deserialize_fn!(deserialize_service_worker, ServiceWorkerConfig);
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeReportConfig {
+    /// how many of the largest modules to list under each chunk file
+    #[serde(default = "default_top_modules")]
+    pub top_modules: usize,
+}
+
+fn default_top_modules() -> usize {
+    3
+}
+
+create_deserialize_fn!(deserialize_size_report, SizeReportConfig);
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceMapConfig {
+    /// inject the debug ids into the js chunks and their source maps
+    pub debug_ids: bool,
+    /// whether to keep the original sources in the source maps
+    pub sources_content: bool,
+}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SriAlgorithm {
+    Sha256,
+    #[default]
+    Sha384,
+    Sha512,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SriConfig {
+    #[serde(default)]
+    pub algorithm: SriAlgorithm,
+}
+
+create_deserialize_fn!(deserialize_sri, SriConfig);
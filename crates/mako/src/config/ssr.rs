@@ -0,0 +1,29 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SsrConfig {
+    /// entries of the server bundle relative to root, the client entries are used if empty
+    #[serde(default)]
+    pub entry: HashMap<String, String>,
+    /// output directory of the server bundle, relative to output.path
+    #[serde(default = "default_output_path")]
+    pub output_path: String,
+    /// filename of the ssr manifest emitted to output.path
+    #[serde(default = "default_manifest")]
+    pub manifest: String,
+}
+
+fn default_output_path() -> String {
+    "server".to_string()
+}
+
+fn default_manifest() -> String {
+    "ssr-manifest.json".to_string()
+}
+
+create_deserialize_fn!(deserialize_ssr, SsrConfig);
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct StyleInjectionConfig {
+    /// the selector of the element the styles are inserted into, the shadow root of the
+    /// element if it has one, document.head by default
+    #[serde(default)]
+    pub insert: Option<String>,
+    /// the styles are loaded as the constructable stylesheets instead of the style and link
+    /// tags, which are adopted by the shadow roots with `__mako_require__.adoptStyles()`
+    #[serde(default)]
+    pub constructable: bool,
+}
+
+create_deserialize_fn!(deserialize_style_injection, StyleInjectionConfig);
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct TypeCheckConfig {
+    /// the command of the type checker, e.g. `tsgo`, defaults to the tsc of the project
+    pub command: Option<String>,
+    /// the tsconfig file passed to the type checker with `-p`
+    pub tsconfig: Option<String>,
+}
+
+create_deserialize_fn!(deserialize_type_check, TypeCheckConfig);
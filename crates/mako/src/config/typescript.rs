@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TypescriptConfig {
+    /// Same as `verbatimModuleSyntax` of tsconfig, only the imports and exports
+    /// marked with `type` are removed, the others are kept as is even if unused
+    pub verbatim_module_syntax: bool,
+    pub imports_not_used_as_values: ImportsNotUsedAsValues,
+    /// Same as `useDefineForClassFields` of tsconfig, overrides the top level
+    /// `useDefineForClassFields` for the TypeScript files when set
+    #[serde(default)]
+    pub use_define_for_class_fields: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportsNotUsedAsValues {
+    /// drop the imports which are only used as types
+    #[serde(rename = "remove")]
+    Remove,
+    /// keep the imports for side effects, e.g. `import { T } from './a'` becomes `import './a'`
+    #[serde(rename = "preserve")]
+    Preserve,
+}
@@ -0,0 +1,447 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Mode;
+
+const SOCKET_FILE: &str = "node_modules/.cache_mako/daemon.sock";
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum DaemonRequest {
+    Build {
+        mode: Mode,
+        /// `--max-workers` of the client, the daemon's own is used if it's not set
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_workers: Option<usize>,
+    },
+    Stop,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum DaemonResponse {
+    Built {
+        time: u64,
+    },
+    Failed {
+        error: String,
+    },
+    /// the daemon is started for another mode, the build should be run by the client
+    ModeMismatch {
+        mode: Mode,
+    },
+    /// the thread pool of the daemon has another size, the build should be run by the client
+    WorkersMismatch {
+        max_workers: Option<usize>,
+    },
+    Stopped,
+}
+
+/// The unix socket the daemon of the root listens on
+pub fn socket_path(root: &Path) -> PathBuf {
+    root.join(SOCKET_FILE)
+}
+
+/// Send the request to the daemon running for the root, returns `None` if there is no daemon
+#[cfg(unix)]
+pub fn request(root: &Path, request: &DaemonRequest) -> Result<Option<DaemonResponse>> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let Ok(mut stream) = UnixStream::connect(socket_path(root)) else {
+        return Ok(None);
+    };
+    writeln!(stream, "{}", serde_json::to_string(request)?)?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    Ok(Some(serde_json::from_str(&line)?))
+}
+
+#[cfg(not(unix))]
+pub fn request(_root: &Path, _request: &DaemonRequest) -> Result<Option<DaemonResponse>> {
+    Ok(None)
+}
+
+#[cfg(unix)]
+pub use server::Daemon;
+
+#[cfg(unix)]
+mod server {
+    use std::collections::{HashSet, VecDeque};
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::sync::mpsc::{channel, Receiver, Sender};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+    use std::{fs, thread};
+
+    use anyhow::{anyhow, Result};
+    use notify::{RecommendedWatcher, Watcher as NotifyWatcher};
+    use notify_debouncer_full::{new_debouncer, DebounceEventResult, Debouncer, FileIdMap};
+    use tracing::debug;
+
+    use super::{socket_path, DaemonRequest, DaemonResponse};
+    use crate::compiler::{Args, Compiler};
+    use crate::config::{Config, Mode};
+    use crate::dev::watch::Watcher;
+    use crate::module_graph::ModuleGraph;
+
+    /// The files read once when the compiler is created, the changes of them start over, so do
+    /// the changes of the `.env*` files
+    const RESTART_FILES: [&str; 3] = ["mako.config.json", "package.json", "tsconfig.json"];
+
+    const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(10);
+
+    /// The events of the files written right before a build request are delivered once the
+    /// debounce timeout passes, the build waits until no event comes for this long
+    const SETTLE_TIMEOUT: Duration = Duration::from_millis(50);
+
+    /// The watcher events and the client requests are handled in the order they arrive through
+    /// one channel, so a build never misses the changes reported before its request
+    enum Message {
+        Changed(Vec<PathBuf>),
+        Request(UnixStream),
+    }
+
+    struct DaemonState {
+        compiler: Arc<Compiler>,
+        /// the module graph after the build stage, the generate stage transforms and shakes the
+        /// modules in place, so every build starts from it with the changed modules rebuilt
+        module_graph: ModuleGraph,
+        _debouncer: Debouncer<RecommendedWatcher, FileIdMap>,
+    }
+
+    /**
+     * Keep the compiler resident for the builds of the root, the modules are built once and only
+     * the files changed since the last build are rebuilt, so the builds run by `mako <root>` skip
+     * the cold start of loading the config, resolving and parsing all the modules
+     */
+    pub struct Daemon {
+        root: PathBuf,
+        mode: Mode,
+        max_workers: Option<usize>,
+        state: Option<DaemonState>,
+        changed: HashSet<PathBuf>,
+        sender: Sender<Message>,
+        receiver: Receiver<Message>,
+        /// the requests received while waiting for the changes before a build
+        pending: VecDeque<UnixStream>,
+    }
+
+    impl Daemon {
+        pub fn new(root: PathBuf, mode: Mode, max_workers: Option<usize>) -> Self {
+            let (sender, receiver) = channel();
+            Self {
+                root,
+                mode,
+                max_workers,
+                state: None,
+                changed: Default::default(),
+                sender,
+                receiver,
+                pending: Default::default(),
+            }
+        }
+
+        pub fn serve(mut self) -> Result<()> {
+            let socket = socket_path(&self.root);
+            if socket.exists() {
+                if UnixStream::connect(&socket).is_ok() {
+                    return Err(anyhow!(
+                        "A mako daemon is already running for {}",
+                        self.root.display()
+                    ));
+                }
+                // left by a daemon which is not stopped normally
+                fs::remove_file(&socket)?;
+            }
+            fs::create_dir_all(socket.parent().unwrap())?;
+            let listener = UnixListener::bind(&socket)?;
+            println!(
+                "Mako daemon is running for {} in {} mode",
+                self.root.display(),
+                self.mode
+            );
+
+            let sender = self.sender.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => {
+                            if sender.send(Message::Request(stream)).is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => eprintln!("Error accepting daemon connection: {:?}", e),
+                    }
+                }
+            });
+
+            loop {
+                let message = match self.pending.pop_front() {
+                    Some(stream) => Message::Request(stream),
+                    None => match self.receiver.recv() {
+                        Ok(message) => message,
+                        Err(_) => break,
+                    },
+                };
+                match message {
+                    Message::Changed(paths) => self.changed.extend(paths),
+                    Message::Request(stream) => match self.handle(stream) {
+                        Ok(true) => break,
+                        Ok(false) => {}
+                        Err(e) => eprintln!("Error handling daemon request: {:?}", e),
+                    },
+                }
+            }
+            fs::remove_file(&socket)?;
+            println!("Mako daemon is stopped");
+            Ok(())
+        }
+
+        /// Handle the request from the client, returns whether the daemon should stop
+        fn handle(&mut self, mut stream: UnixStream) -> Result<bool> {
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line)?;
+            let request: DaemonRequest = serde_json::from_str(&line)?;
+            debug!("daemon request: {:?}", request);
+            let response = match request {
+                DaemonRequest::Build { mode, .. } if mode != self.mode => {
+                    DaemonResponse::ModeMismatch {
+                        mode: self.mode.clone(),
+                    }
+                }
+                DaemonRequest::Build {
+                    max_workers: Some(max_workers),
+                    ..
+                } if Some(max_workers) != self.max_workers => DaemonResponse::WorkersMismatch {
+                    max_workers: self.max_workers,
+                },
+                DaemonRequest::Build { .. } => {
+                    let t_build = Instant::now();
+                    match self.build() {
+                        Ok(()) => DaemonResponse::Built {
+                            time: t_build.elapsed().as_millis() as u64,
+                        },
+                        Err(e) => DaemonResponse::Failed {
+                            error: e.to_string(),
+                        },
+                    }
+                }
+                DaemonRequest::Stop => DaemonResponse::Stopped,
+            };
+            writeln!(stream, "{}", serde_json::to_string(&response)?)?;
+            Ok(response == DaemonResponse::Stopped)
+        }
+
+        /// Take the watcher events until no event comes for the settle timeout, the requests
+        /// received meanwhile are handled after the build in order
+        fn settle(&mut self) {
+            while let Ok(message) = self.receiver.recv_timeout(SETTLE_TIMEOUT) {
+                match message {
+                    Message::Changed(paths) => self.changed.extend(paths),
+                    Message::Request(stream) => self.pending.push_back(stream),
+                }
+            }
+        }
+
+        fn build(&mut self) -> Result<()> {
+            if self.state.is_some() {
+                self.settle();
+            }
+            let changed = self.changed.drain().collect::<Vec<_>>();
+            debug!("daemon changed files: {:?}", changed);
+            if changed.iter().any(|path| is_restart_file(&self.root, path)) {
+                self.state = None;
+            }
+
+            let t_compiler = Instant::now();
+            let start_time = chrono::Local::now().timestamp_millis();
+            // the state is dropped if the update fails, since the module graph may be updated
+            // partially, and the next build starts over
+            let state = match self.state.take() {
+                Some(mut state) => {
+                    let compiler = &state.compiler;
+                    // the snapshot is moved into the compiler and taken again after the update,
+                    // so the module graph is cloned once per build
+                    *compiler.context.module_graph.write().unwrap() =
+                        std::mem::take(&mut state.module_graph);
+                    if !changed.is_empty() {
+                        compiler.update(changed)?;
+                    }
+                    state.module_graph = compiler.context.module_graph.read().unwrap().clone();
+                    state
+                }
+                None => self.start()?,
+            };
+            let compiler = state.compiler.clone();
+            self.state = Some(state);
+
+            if compiler.context.config.clean {
+                compiler.clean_dist()?;
+            }
+            compiler.context.stats_info.clear_assets();
+            compiler.generate_and_emit(t_compiler, start_time)
+        }
+
+        /// Create the compiler, build the entries and watch the files of the modules
+        fn start(&self) -> Result<DaemonState> {
+            let cli_args = format!(r#"{{ "mode": "{}" }}"#, self.mode);
+            let mut config = Config::new(&self.root, None, Some(cli_args.as_str()))
+                .map_err(|e| anyhow!(format!("Load config failed: {}", e)))?;
+            config.mode = self.mode.clone();
+            let compiler = Arc::new(Compiler::new(
+                config,
+                self.root.clone(),
+                Args {
+                    watch: false,
+                    max_workers: self.max_workers,
+                },
+                None,
+            )?);
+            compiler.build_entries()?;
+
+            let sender = self.sender.clone();
+            let mut debouncer = new_debouncer(
+                DEBOUNCE_TIMEOUT,
+                None,
+                move |result: DebounceEventResult| match result {
+                    Ok(events) => {
+                        let _ = sender.send(Message::Changed(Watcher::normalize_events(events)));
+                    }
+                    Err(e) => eprintln!("Error watching files: {:?}", e),
+                },
+            )?;
+            let mut watcher = Watcher::new(&self.root, debouncer.watcher(), &compiler);
+            watcher.watch()?;
+            // the restart files may be created after the start, e.g. `.env.local`
+            debouncer
+                .watcher()
+                .watch(&self.root, notify::RecursiveMode::NonRecursive)?;
+
+            let module_graph = compiler.context.module_graph.read().unwrap().clone();
+            Ok(DaemonState {
+                compiler,
+                module_graph,
+                _debouncer: debouncer,
+            })
+        }
+    }
+
+    pub(super) fn is_restart_file(root: &Path, path: &Path) -> bool {
+        path.parent() == Some(root)
+            && path.file_name().is_some_and(|name| {
+                let name = name.to_string_lossy();
+                RESTART_FILES.contains(&name.as_ref()) || name.starts_with(".env")
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daemon_messages() {
+        let request = DaemonRequest::Build {
+            mode: Mode::Production,
+            max_workers: None,
+        };
+        let json = serde_json::to_string(&request).unwrap();
+        assert_eq!(json, r#"{"type":"build","mode":"production"}"#);
+        assert_eq!(
+            serde_json::from_str::<DaemonRequest>(&json).unwrap(),
+            request
+        );
+        assert_eq!(
+            serde_json::from_str::<DaemonResponse>(r#"{"type":"built","time":12}"#).unwrap(),
+            DaemonResponse::Built { time: 12 }
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_request_without_daemon() {
+        let root = std::env::temp_dir().join("mako-daemon-test-no-daemon");
+        assert_eq!(request(&root, &DaemonRequest::Stop).unwrap(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_is_restart_file() {
+        use super::server::is_restart_file;
+
+        let root = Path::new("/app");
+        assert!(is_restart_file(root, Path::new("/app/mako.config.json")));
+        assert!(is_restart_file(root, Path::new("/app/tsconfig.json")));
+        assert!(is_restart_file(root, Path::new("/app/.env")));
+        assert!(is_restart_file(root, Path::new("/app/.env.local")));
+        assert!(!is_restart_file(root, Path::new("/app/src/.env")));
+        assert!(!is_restart_file(root, Path::new("/app/src/index.ts")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_daemon_build() {
+        use std::time::Duration;
+        use std::{fs, thread};
+
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/tmp/daemon");
+        if root.exists() {
+            fs::remove_dir_all(&root).unwrap();
+        }
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("mako.config.json"), r#"{ "minify": false }"#).unwrap();
+        fs::write(root.join("src/index.ts"), "console.log('first');").unwrap();
+
+        let daemon = Daemon::new(root.clone(), Mode::Production, None);
+        let handle = thread::spawn(move || daemon.serve());
+        while !socket_path(&root).exists() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let build = |mode, max_workers| {
+            request(&root, &DaemonRequest::Build { mode, max_workers })
+                .unwrap()
+                .unwrap()
+        };
+        let output = || fs::read_to_string(root.join("dist/index.js")).unwrap();
+
+        assert!(matches!(
+            build(Mode::Production, None),
+            DaemonResponse::Built { .. }
+        ));
+        assert!(output().contains("first"));
+
+        // the changed module is rebuilt by the next build, the response is sent after the
+        // build handles the watcher events of the write
+        fs::write(root.join("src/index.ts"), "console.log('second');").unwrap();
+        assert!(matches!(
+            build(Mode::Production, None),
+            DaemonResponse::Built { .. }
+        ));
+        assert!(output().contains("second"));
+
+        assert_eq!(
+            build(Mode::Production, Some(1)),
+            DaemonResponse::WorkersMismatch { max_workers: None }
+        );
+        assert_eq!(
+            build(Mode::Development, None),
+            DaemonResponse::ModeMismatch {
+                mode: Mode::Production,
+            }
+        );
+
+        assert_eq!(
+            request(&root, &DaemonRequest::Stop).unwrap(),
+            Some(DaemonResponse::Stopped)
+        );
+        handle.join().unwrap().unwrap();
+        assert!(!socket_path(&root).exists());
+    }
+}
@@ -1,9 +1,16 @@
+pub(crate) mod on_demand_entries;
+mod tls;
 pub(crate) mod update;
-mod watch;
+pub(crate) mod watch;
 
-use std::net::{SocketAddr, TcpListener};
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::future::Future;
+use std::net::{IpAddr, Ipv4Addr, TcpListener};
 use std::path::PathBuf;
+use std::pin::Pin;
 use std::sync::{mpsc, Arc};
+use std::task::{self, Poll};
 use std::time::{Duration, Instant};
 
 use anyhow::{self, Result};
@@ -11,16 +18,20 @@ use colored::Colorize;
 use futures::{SinkExt, StreamExt};
 use get_if_addrs::get_if_addrs;
 use hyper::header::CONTENT_TYPE;
-use hyper::service::{make_service_fn, service_fn};
+use hyper::server::conn::Http;
+use hyper::service::{make_service_fn, Service};
 use hyper::{Body, Request, Server};
-use notify_debouncer_full::new_debouncer;
+use notify_debouncer_full::{new_debouncer, DebounceEventResult};
 use tokio::sync::broadcast;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
 use tracing::debug;
 use tungstenite::Message;
 use {hyper, hyper_staticfile, hyper_tungstenite, open};
 
 use crate::compiler::{Compiler, Context};
-use crate::plugin::PluginGenerateEndParams;
+use crate::module::ModuleId;
+use crate::plugin::{PluginGenerateEndParams, PluginRebuildEndParams};
 use crate::utils::{process_req_url, tokio_runtime};
 
 pub struct DevServer {
@@ -52,33 +63,66 @@ impl DevServer {
         }
 
         // server
-        if self.compiler.context.config.dev_server.is_some() {
-            let config_port = self
-                .compiler
-                .context
-                .config
-                .dev_server
-                .as_ref()
-                .unwrap()
-                .port;
-            let port = Self::find_available_port("127.0.0.1".to_string(), config_port);
-            let addr: SocketAddr = ([127, 0, 0, 1], port).into();
-            let context = self.compiler.context.clone();
-            let txws = txws.clone();
-            let make_svc = make_service_fn(move |_conn| {
-                let context = context.clone();
-                let txws = txws.clone();
-                async move {
-                    Ok::<_, hyper::Error>(service_fn(move |req| {
-                        let context = context.clone();
-                        let txws = txws.clone();
-                        let staticfile =
-                            hyper_staticfile::Static::new(context.config.output.path.clone());
-                        async move { Self::handle_requests(req, context, staticfile, txws).await }
-                    }))
+        if let Some(dev_server) = &self.compiler.context.config.dev_server {
+            let config_port = dev_server.port;
+            let ip = match dev_server.host.as_str() {
+                "localhost" => IpAddr::V4(Ipv4Addr::LOCALHOST),
+                host => match host.parse::<IpAddr>() {
+                    Ok(ip) => ip,
+                    Err(_) => {
+                        eprintln!("Invalid devServer.host: {}", host);
+                        return;
+                    }
+                },
+            };
+            // the listener is kept and served, so that the port can't be taken meanwhile
+            let listener = if dev_server.strict_port {
+                match TcpListener::bind((ip, config_port)) {
+                    Ok(listener) => listener,
+                    Err(_) => {
+                        eprintln!("Port {} is in use.", config_port);
+                        return;
+                    }
                 }
-            });
-            let server = Server::bind(&addr).serve(make_svc);
+            } else {
+                Self::bind_available_port(ip, config_port)
+            };
+            let addr = match listener
+                .set_nonblocking(true)
+                .and_then(|_| listener.local_addr())
+            {
+                Ok(addr) => addr,
+                Err(e) => {
+                    eprintln!("Error starting server: {:?}", e);
+                    return;
+                }
+            };
+            let port = addr.port();
+            let service = DevService {
+                context: self.compiler.context.clone(),
+                txws: txws.clone(),
+            };
+            let tls_config = match &dev_server.https {
+                Some(https) => match tls::server_config(https, dev_server.http2, &self.root) {
+                    Ok(tls_config) => Some(tls_config),
+                    Err(e) => {
+                        eprintln!("Error creating tls config: {:?}", e);
+                        return;
+                    }
+                },
+                None => None,
+            };
+            let protocol = if tls_config.is_some() {
+                "https"
+            } else {
+                "http"
+            };
+            // the host which can be visited in the browser
+            let local_host = if ip.is_unspecified() || ip.is_loopback() {
+                "localhost".to_string()
+            } else {
+                ip.to_string()
+            };
             // TODO: print when mako is run standalone
             if std::env::var("MAKO_CLI").is_ok() {
                 println!();
@@ -92,29 +136,125 @@ impl DevServer {
                 }
                 println!(
                     "Local:   {}",
-                    format!("http://localhost:{}/", port).to_string().cyan()
+                    format!("{}://{}:{}/", protocol, local_host, port)
+                        .to_string()
+                        .cyan()
                 );
-                let ips = Self::get_ips();
-                let ips = ips
-                    .iter()
-                    .filter(|ip| !ip.starts_with("127."))
-                    .collect::<Vec<_>>();
-                for ip in ips {
+                // all the interfaces are reachable when listening on the unspecified address,
+                // and only the host itself when listening on a specific one
+                let network_ips = if ip.is_unspecified() {
+                    Self::get_ips()
+                        .into_iter()
+                        .filter(|ip| !ip.starts_with("127."))
+                        .collect::<Vec<_>>()
+                } else if ip.is_loopback() {
+                    vec![]
+                } else {
+                    vec![ip.to_string()]
+                };
+                if network_ips.is_empty() {
+                    println!(
+                        "Network: {}",
+                        "use devServer.host 0.0.0.0 to expose".to_string().dimmed()
+                    );
+                }
+                for ip in network_ips {
                     println!(
                         "Network: {}",
-                        format!("http://{}:{}/", ip, port).to_string().cyan()
+                        format!("{}://{}:{}/", protocol, ip, port)
+                            .to_string()
+                            .cyan()
                     );
                 }
                 println!();
-                open::that(format!("http://localhost:{}/", port)).unwrap();
             }
-            debug!("Listening on http://{:?}", addr);
-            if let Err(e) = server.await {
+            // the root path is opened by default when run by the mako cli
+            let open = match &dev_server.open {
+                Some(open) => open.clone(),
+                None => std::env::var("MAKO_CLI").is_ok().then(|| "/".to_string()),
+            };
+            if let Some(path) = &open {
+                let url = format!(
+                    "{}://{}:{}/{}",
+                    protocol,
+                    local_host,
+                    port,
+                    path.trim_start_matches('/')
+                );
+                if let Err(e) = open::that(&url) {
+                    eprintln!("Error opening {}: {:?}", url, e);
+                }
+            }
+            debug!("Listening on {}://{:?}", protocol, addr);
+            let result = match tls_config {
+                Some(tls_config) => Self::serve_tls(listener, tls_config, service).await,
+                None => {
+                    let make_svc = make_service_fn(move |_conn| {
+                        let service = service.clone();
+                        async move { Ok::<_, hyper::Error>(service) }
+                    });
+                    match Server::from_tcp(listener) {
+                        Ok(server) => server.serve(make_svc).await.map_err(anyhow::Error::from),
+                        Err(e) => Err(anyhow::Error::from(e)),
+                    }
+                }
+            };
+            if let Err(e) = result {
                 eprintln!("Error starting server: {:?}", e);
             }
         }
     }
 
+    async fn serve_tls(
+        listener: TcpListener,
+        tls_config: ServerConfig,
+        service: DevService,
+    ) -> Result<()> {
+        let http2 = tls_config.alpn_protocols.iter().any(|p| p == b"h2");
+        let acceptor = TlsAcceptor::from(Arc::new(tls_config));
+        let listener = tokio::net::TcpListener::from_std(listener)?;
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let acceptor = acceptor.clone();
+            let service = service.clone();
+            tokio::spawn(async move {
+                let stream = match acceptor.accept(stream).await {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        debug!("tls handshake failed: {:?}", e);
+                        return;
+                    }
+                };
+                let mut http = Http::new();
+                if !http2 {
+                    http.http1_only(true);
+                }
+                // upgrades are required by the hmr websocket
+                if let Err(e) = http.serve_connection(stream, service).with_upgrades().await {
+                    debug!("connection error: {:?}", e);
+                }
+            });
+        }
+    }
+
+    /// Watch for changes and return the request handler without listening on a port,
+    /// so that it can be mounted to the servers of frameworks
+    pub fn middleware(&self) -> DevService {
+        let (txws, _) = broadcast::channel::<WsMessage>(256);
+        let root = self.root.clone();
+        let compiler = self.compiler.clone();
+        let txws_watch = txws.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = Self::watch_for_changes(root, compiler, txws_watch) {
+                eprintln!("Error watching files: {:?}", e);
+            }
+        });
+        DevService {
+            context: self.compiler.context.clone(),
+            txws,
+        }
+    }
+
     async fn handle_requests(
         req: Request<Body>,
         context: Arc<Context>,
@@ -151,7 +291,9 @@ impl DevServer {
                     let txws = txws.clone();
                     tokio_runtime::spawn(async move {
                         let receiver = txws.subscribe();
-                        Self::handle_websocket(websocket, receiver).await.unwrap();
+                        Self::handle_websocket(websocket, receiver, context)
+                            .await
+                            .unwrap();
                     });
                     Ok(response)
                 } else {
@@ -170,6 +312,15 @@ impl DevServer {
                     Some(_) => "text/plain; charset=utf-8",
                 };
 
+                if context
+                    .config
+                    .dev_server
+                    .as_ref()
+                    .is_some_and(|dev_server| dev_server.on_demand_entries)
+                {
+                    context.on_demand_entries.record(path_without_slash_start);
+                }
+
                 // staticfile has 302 problems when modify tooooo fast in 1 second
                 // it will response 302 and we will get the old file
                 // TODO: fix the 302 problem?
@@ -199,13 +350,12 @@ impl DevServer {
                     });
                 }
 
-                // for hmr files
+                // for hmr files, the method and headers are kept for the conditional and
+                // range requests
                 debug!("< static file serve: {}", path);
-                let req = hyper::Request::builder()
-                    .uri(path)
-                    .body(hyper::Body::empty())
-                    .unwrap();
-                let res = staticfile.serve(req).await;
+                let (mut parts, body) = req.into_parts();
+                parts.uri = path.parse()?;
+                let res = staticfile.serve(Request::from_parts(parts, body)).await;
                 res.map_err(anyhow::Error::from)
             }
         }
@@ -227,31 +377,29 @@ impl DevServer {
         ips
     }
 
-    fn find_available_port(host: String, port: u16) -> u16 {
-        let mut port = port;
-        if TcpListener::bind((host.clone(), port)).is_ok() {
-            port
-        } else {
-            port += 1;
-            Self::find_available_port(host, port)
+    fn bind_available_port(host: IpAddr, port: u16) -> TcpListener {
+        match TcpListener::bind((host, port)) {
+            Ok(listener) => listener,
+            Err(_) => Self::bind_available_port(host, port + 1),
         }
     }
 
-    // TODO: refact socket message data structure
     async fn handle_websocket(
         websocket: hyper_tungstenite::HyperWebsocket,
         mut receiver: broadcast::Receiver<WsMessage>,
+        context: Arc<Context>,
     ) -> Result<()> {
         let websocket = websocket.await?;
         let (mut sender, mut ws_recv) = websocket.split();
+        // the clients connected after a type check get its diagnostics on connect
+        if !context.type_diagnostics.read().unwrap().is_empty() {
+            let msg = WsMessage::diagnostics(&context);
+            sender.send(Message::text(msg.to_text())).await?;
+        }
         let task = tokio_runtime::spawn(async move {
             loop {
                 if let Ok(msg) = receiver.recv().await {
-                    if sender
-                        .send(Message::text(format!(r#"{{"hash":"{}"}}"#, msg.hash)))
-                        .await
-                        .is_err()
-                    {
+                    if sender.send(Message::text(msg.to_text())).await.is_err() {
                         break;
                     }
                 }
@@ -273,41 +421,119 @@ impl DevServer {
         txws: broadcast::Sender<WsMessage>,
     ) -> Result<()> {
         let (tx, rx) = mpsc::channel();
+        let fs_tx = tx.clone();
+        // the new changes abort the in-flight rebuild, which is retried along with them
+        let fs_cancellation = compiler.context.cancellation.clone();
         // let mut watcher = RecommendedWatcher::new(tx, notify::Config::default())?;
-        let mut debouncer = new_debouncer(Duration::from_millis(10), None, tx).unwrap();
+        let mut debouncer = new_debouncer(
+            Duration::from_millis(10),
+            None,
+            move |result: DebounceEventResult| {
+                if let Ok(events) = &result
+                    && !watch::Watcher::normalize_events(events.clone()).is_empty()
+                {
+                    fs_cancellation.cancel();
+                }
+                let _ = fs_tx.send(WatchEvent::Fs(result));
+            },
+        )
+        .unwrap();
+        let invalidate_cancellation = compiler.context.cancellation.clone();
+        compiler.context.on_invalidate(Box::new(move || {
+            invalidate_cancellation.cancel();
+            let _ = tx.send(WatchEvent::Invalidated);
+        }));
+        let diagnostics_context = compiler.context.clone();
+        let diagnostics_txws = txws.clone();
+        compiler.context.on_diagnostics(Box::new(move || {
+            let _ = diagnostics_txws.send(WsMessage::diagnostics(&diagnostics_context));
+        }));
         let mut watcher = watch::Watcher::new(&root, debouncer.watcher(), &compiler);
         watcher.watch()?;
 
         let initial_hash = compiler.full_hash();
         let mut snapshot_hash = Box::new(initial_hash);
         let mut hmr_hash = Box::new(initial_hash);
+        let mut cancelled_paths: Vec<PathBuf> = vec![];
 
-        for result in rx {
-            if result.is_err() {
-                eprintln!("Error watching files: {:?}", result.err().unwrap());
-                continue;
+        for event in rx {
+            let mut paths = match event {
+                WatchEvent::Fs(Ok(events)) => watch::Watcher::normalize_events(events),
+                WatchEvent::Fs(Err(e)) => {
+                    eprintln!("Error watching files: {:?}", e);
+                    continue;
+                }
+                WatchEvent::Invalidated => vec![],
+            };
+            for path in cancelled_paths.drain(..) {
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
             }
-            let paths = watch::Watcher::normalize_events(result.unwrap());
-            if !paths.is_empty() {
-                let compiler = compiler.clone();
-                let txws = txws.clone();
-                if let Err(e) =
-                    Self::rebuild(paths, compiler, txws, &mut snapshot_hash, &mut hmr_hash)
-                {
+            let is_invalidated = !compiler
+                .context
+                .invalidated_modules
+                .lock()
+                .unwrap()
+                .is_empty();
+            if !paths.is_empty() || is_invalidated {
+                compiler.context.cancellation.reset();
+                let t_rebuild = Instant::now();
+                let mut params = PluginRebuildEndParams {
+                    changed_files: paths
+                        .iter()
+                        .map(|path| path.to_string_lossy().to_string())
+                        .collect(),
+                    ..Default::default()
+                };
+                if let Err(e) = Self::rebuild(
+                    paths.clone(),
+                    compiler.clone(),
+                    txws.clone(),
+                    &mut snapshot_hash,
+                    &mut hmr_hash,
+                    &mut params,
+                ) {
+                    if compiler.context.cancellation.is_cancelled() {
+                        println!("Rebuild cancelled by the new changes");
+                        cancelled_paths = paths;
+                        continue;
+                    }
                     eprintln!("Error rebuilding: {:?}", e);
+                    params.errors.push(e.to_string());
                 }
+                params.time = t_rebuild.elapsed().as_millis() as i64;
+                Self::rebuild_end(&compiler, &params);
+                if let Err(e) = watcher.watch_file_dependencies() {
+                    eprintln!("Error watching file dependencies: {:?}", e);
+                }
+                compiler.drop_asts_over_limit();
             }
         }
         Ok(())
     }
 
+    /// Report the delta of a rebuild to the plugins and the receivers of `Compiler::watch`
+    fn rebuild_end(compiler: &Compiler, params: &PluginRebuildEndParams) {
+        if let Err(e) = compiler
+            .context
+            .plugin_driver
+            .rebuild_end(params, &compiler.context)
+        {
+            eprintln!("Error in rebuild end: {:?}", e);
+        }
+        compiler.context.emit_rebuild(params);
+    }
+
     fn rebuild(
         paths: Vec<PathBuf>,
         compiler: Arc<Compiler>,
         txws: broadcast::Sender<WsMessage>,
         last_snapshot_hash: &mut Box<u64>,
         hmr_hash: &mut Box<u64>,
+        params: &mut PluginRebuildEndParams,
     ) -> Result<()> {
+        let _running = compiler.context.cancellation.start();
         debug!("watch paths detected: {:?}", paths);
         debug!("checking update status...");
         println!("Checking...");
@@ -325,13 +551,26 @@ impl DevServer {
         debug!("checking update status... done");
 
         if let Err(e) = update_result {
+            if compiler.context.cancellation.is_cancelled() {
+                return Err(e);
+            }
             debug!("checking update status... failed");
             eprintln!("{}", e);
+            params.errors.push(e.to_string());
             // do not return error, since it's already printed
             return Ok(());
         }
 
         let res = update_result.unwrap();
+        params.added_modules = sorted_ids(&res.added);
+        params.removed_modules = sorted_ids(&res.removed);
+        params.modified_modules = sorted_ids(&res.modified);
+        let updated_modules = res
+            .added
+            .iter()
+            .chain(res.modified.iter())
+            .cloned()
+            .collect::<Vec<_>>();
         let is_updated = res.is_updated();
         debug!("update status is ok, is_updated: {}", is_updated);
         if !is_updated {
@@ -351,6 +590,17 @@ impl DevServer {
             return Err(e);
         }
         let (next_snapshot_hash, next_hmr_hash, current_hmr_hash) = next_hash.unwrap();
+        params.updated_chunks = {
+            let chunk_graph = compiler.context.chunk_graph.read().unwrap();
+            let mut chunks = chunk_graph
+                .get_chunks()
+                .into_iter()
+                .filter(|chunk| updated_modules.iter().any(|id| chunk.has_module(id)))
+                .map(|chunk| chunk.filename())
+                .collect::<Vec<_>>();
+            chunks.sort();
+            chunks
+        };
         debug!(
             "hash info, next: {:?}, last: {:?}, is_equal: {}",
             next_snapshot_hash,
@@ -367,10 +617,39 @@ impl DevServer {
 
         debug!("full rebuild...");
 
+        compiler.restore_dropped_asts_of_rendered_chunks()?;
         compiler.context.stats_info.clear_assets();
 
+        // the chunks of the entries open in the browsers are emitted first, and the clients are
+        // notified before the chunks of the other entries are emitted
+        let active_chunks = if compiler
+            .context
+            .config
+            .dev_server
+            .as_ref()
+            .is_some_and(|dev_server| dev_server.on_demand_entries)
+            && !compiler.context.config.hash
+        {
+            let chunk_graph = compiler.context.chunk_graph.read().unwrap();
+            compiler
+                .context
+                .on_demand_entries
+                .active_chunks(&chunk_graph)
+        } else {
+            HashSet::new()
+        };
+        let notified = Cell::new(false);
+        let notify = || {
+            debug!("active chunks emitted in {}ms", t_compiler.elapsed().as_millis());
+            if txws.receiver_count() > 0 {
+                txws.send(WsMessage::Hash(next_hmr_hash)).unwrap();
+            }
+            notified.set(true);
+        };
+        let on_demand = (!active_chunks.is_empty())
+            .then_some((&active_chunks, &notify as &dyn Fn()));
         let mut stats = compiler
-            .emit_dev_chunks(next_hmr_hash, current_hmr_hash)
+            .emit_dev_chunks(next_hmr_hash, current_hmr_hash, on_demand)
             .map_err(|e| {
                 debug!("  > build failed: {:?}", e);
                 e
@@ -406,12 +685,20 @@ impl DevServer {
                     debug!("write bundle failed: {:?}", e);
                     e
                 })?;
+            compiler
+                .context
+                .plugin_driver
+                .done(&params.stats, &compiler.context)
+                .map_err(|e| {
+                    debug!("done hook failed: {:?}", e);
+                    e
+                })?;
         }
 
         let receiver_count = txws.receiver_count();
         debug!("receiver count: {}", receiver_count);
-        if receiver_count > 0 {
-            txws.send(WsMessage { hash: **hmr_hash }).unwrap();
+        if receiver_count > 0 && !notified.get() {
+            txws.send(WsMessage::Hash(**hmr_hash)).unwrap();
             debug!("send message to clients");
         }
 
@@ -419,7 +706,223 @@ impl DevServer {
     }
 }
 
+fn sorted_ids(ids: &HashSet<ModuleId>) -> Vec<String> {
+    let mut ids = ids.iter().map(|id| id.id.to_string()).collect::<Vec<_>>();
+    ids.sort();
+    ids
+}
+
+enum WatchEvent {
+    Fs(DebounceEventResult),
+    /// modules are invalidated by the plugins with `Context::invalidate`
+    Invalidated,
+}
+
+/// The request handler of the dev server, which serves the outputs and the hmr websocket
+#[derive(Clone)]
+pub struct DevService {
+    context: Arc<Context>,
+    txws: broadcast::Sender<WsMessage>,
+}
+
+pub struct DevResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl DevService {
+    pub async fn handle(&self, req: Request<Body>) -> Result<hyper::Response<Body>> {
+        let staticfile = hyper_staticfile::Static::new(self.context.config.output.path.clone());
+        DevServer::handle_requests(req, self.context.clone(), staticfile, self.txws.clone()).await
+    }
+
+    /// Handle a request without body for the bindings, returns None if it's not
+    /// served by mako, so that it can be passed to the next middleware; the headers are
+    /// passed through for the conditional and range requests
+    pub async fn request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(String, String)],
+    ) -> Result<Option<DevResponse>> {
+        let mut req = Request::builder().method(method).uri(url);
+        for (name, value) in headers {
+            req = req.header(name, value);
+        }
+        let req = req.body(Body::empty())?;
+        let res = self.handle(req).await?;
+        // bad request is responded for the paths out of the public path
+        if matches!(
+            res.status(),
+            hyper::StatusCode::NOT_FOUND | hyper::StatusCode::BAD_REQUEST
+        ) {
+            return Ok(None);
+        }
+        let status = res.status().as_u16();
+        let headers = res
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| {
+                value
+                    .to_str()
+                    .ok()
+                    .map(|value| (name.to_string(), value.to_string()))
+            })
+            .collect();
+        let body = hyper::body::to_bytes(res.into_body()).await?.to_vec();
+        Ok(Some(DevResponse {
+            status,
+            headers,
+            body,
+        }))
+    }
+
+    /// Listen on a random port of localhost for the hmr websocket, since the upgraded
+    /// connections can't be passed through the bindings, they are proxied to the port
+    pub fn listen_hmr(&self) -> Result<u16> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let port = listener.local_addr()?.port();
+        let service = self.clone();
+        tokio_runtime::spawn(async move {
+            let make_svc = make_service_fn(move |_conn| {
+                let service = service.clone();
+                async move { Ok::<_, hyper::Error>(service) }
+            });
+            match Server::from_tcp(listener) {
+                Ok(server) => {
+                    if let Err(e) = server.serve(make_svc).await {
+                        eprintln!("Error starting hmr server: {:?}", e);
+                    }
+                }
+                Err(e) => eprintln!("Error starting hmr server: {:?}", e),
+            }
+        });
+        Ok(port)
+    }
+}
+
+impl Service<Request<Body>> for DevService {
+    type Response = hyper::Response<Body>;
+    type Error = anyhow::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut task::Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let service = self.clone();
+        Box::pin(async move { service.handle(req).await })
+    }
+}
+
 #[derive(Clone, Debug)]
-struct WsMessage {
-    hash: u64,
+enum WsMessage {
+    Hash(u64),
+    /// the serialized diagnostics of the last type check
+    Diagnostics(String),
+}
+
+impl WsMessage {
+    fn diagnostics(context: &Context) -> Self {
+        let diagnostics = context.type_diagnostics.read().unwrap();
+        WsMessage::Diagnostics(serde_json::to_string(&*diagnostics).unwrap())
+    }
+
+    fn to_text(&self) -> String {
+        match self {
+            WsMessage::Hash(hash) => format!(r#"{{"hash":"{}"}}"#, hash),
+            WsMessage::Diagnostics(diagnostics) => {
+                format!(r#"{{"type":"diagnostics","diagnostics":{}}}"#, diagnostics)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr, TcpListener};
+    use std::sync::Arc;
+
+    use tokio::sync::broadcast;
+
+    use super::{DevServer, DevService, WsMessage};
+    use crate::plugin::PluginRebuildEndParams;
+    use crate::utils::test_helper::setup_compiler;
+    use crate::utils::tokio_runtime;
+
+    #[test]
+    fn test_dev_service() {
+        let compiler = setup_compiler("test/build/dev-service", false);
+        compiler.compile().unwrap();
+        let (txws, _) = broadcast::channel::<WsMessage>(1);
+        let service = DevService {
+            context: compiler.context.clone(),
+            txws,
+        };
+        let request = |url: &str, headers: &[(String, String)]| {
+            tokio_runtime::block_on(service.request("GET", url, headers)).unwrap()
+        };
+
+        let res = request("/index.js", &[]).unwrap();
+        assert_eq!(res.status, 200);
+        assert!(String::from_utf8_lossy(&res.body).contains("dev service"));
+        assert!(res
+            .headers
+            .iter()
+            .any(|(name, value)| name == "content-type" && value.contains("javascript")));
+
+        // passed to the next middleware
+        assert!(request("/missing.js", &[]).is_none());
+
+        // the headers are passed through
+        let res = request("/index.js", &[("range".to_string(), "bytes=0-3".to_string())]).unwrap();
+        assert_eq!(res.status, 206);
+        assert_eq!(res.body.len(), 4);
+    }
+
+    #[test]
+    fn test_bind_available_port() {
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let taken = TcpListener::bind((ip, 0)).unwrap();
+        let port = taken.local_addr().unwrap().port();
+        let listener = DevServer::bind_available_port(ip, port);
+        assert!(listener.local_addr().unwrap().port() > port);
+    }
+
+    #[test]
+    fn test_rebuild_delta() {
+        let compiler = Arc::new(setup_compiler("test/build/rebuild-delta", false));
+        compiler.compile().unwrap();
+        let events = compiler.watch();
+        let root = compiler.context.root.clone();
+        let changed = root.join("a.ts");
+        let hash = compiler.full_hash();
+        let (txws, _) = broadcast::channel::<WsMessage>(1);
+        let mut params = PluginRebuildEndParams {
+            changed_files: vec![changed.to_string_lossy().to_string()],
+            ..Default::default()
+        };
+        DevServer::rebuild(
+            vec![changed.clone()],
+            compiler.clone(),
+            txws,
+            &mut Box::new(hash),
+            &mut Box::new(hash),
+            &mut params,
+        )
+        .unwrap();
+        DevServer::rebuild_end(&compiler, &params);
+
+        let delta = events.try_recv().unwrap();
+        assert_eq!(delta.changed_files, params.changed_files);
+        assert!(delta.added_modules.is_empty());
+        assert!(delta.removed_modules.is_empty());
+        assert_eq!(delta.modified_modules, vec![changed.to_string_lossy().to_string()]);
+        assert_eq!(delta.updated_chunks, vec!["index.js"]);
+        assert!(delta.errors.is_empty());
+        assert!(events.try_recv().is_err());
+    }
 }
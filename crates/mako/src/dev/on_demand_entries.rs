@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::generate::chunk::{ChunkId, ChunkType};
+use crate::generate::chunk_graph::ChunkGraph;
+
+/// The chunk files served by the dev server, the chunks of the entries which are open in the
+/// browsers are emitted first after the changes with devServer.onDemandEntries, and the
+/// chunks of the other entries are deferred after the hmr message is sent
+#[derive(Default)]
+pub struct OnDemandEntries {
+    served_files: Mutex<HashSet<String>>,
+}
+
+impl OnDemandEntries {
+    pub fn record(&self, path: &str) {
+        if !path.ends_with(".js") && !path.ends_with(".css") {
+            return;
+        }
+        let mut served_files = self.served_files.lock().unwrap();
+        if !served_files.contains(path) {
+            served_files.insert(path.to_string());
+        }
+    }
+
+    /// The served chunks, their entries and all the chunks loaded by the entries
+    pub fn active_chunks(&self, chunk_graph: &ChunkGraph) -> HashSet<ChunkId> {
+        let served_files = self.served_files.lock().unwrap();
+        if served_files.is_empty() {
+            return HashSet::new();
+        }
+        // a.js and a.css are served for the same chunk
+        let served_names = served_files
+            .iter()
+            .map(|file| chunk_name(file))
+            .collect::<HashSet<_>>();
+        let mut active_chunks = HashSet::new();
+        for chunk in chunk_graph.get_chunks() {
+            if !served_names.contains(&chunk_name(&chunk.filename())) {
+                continue;
+            }
+            active_chunks.insert(chunk.id.clone());
+            let entries = if matches!(chunk.chunk_type, ChunkType::Entry(..)) {
+                vec![chunk.id.clone()]
+            } else {
+                chunk_graph.entry_ancestors_chunk(&chunk.id)
+            };
+            for entry in entries {
+                active_chunks.extend(chunk_graph.installable_descendants_chunk(&entry));
+                active_chunks.extend(chunk_graph.entry_dependencies_chunk(&entry));
+                active_chunks.insert(entry);
+            }
+        }
+        active_chunks
+    }
+}
+
+fn chunk_name(file: &str) -> String {
+    Path::new(file)
+        .with_extension("")
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::OnDemandEntries;
+    use crate::generate::chunk::{Chunk, ChunkType};
+    use crate::generate::chunk_graph::ChunkGraph;
+    use crate::module::ModuleId;
+
+    fn chunk(id: &str, chunk_type: ChunkType) -> Chunk {
+        let mut chunk = Chunk::new(ModuleId::new(id.to_string()), chunk_type);
+        chunk.add_module(ModuleId::new(id.to_string()));
+        chunk
+    }
+
+    fn entry(id: &str) -> ChunkType {
+        ChunkType::Entry(ModuleId::new(id.to_string()), id.to_string(), false)
+    }
+
+    #[test]
+    fn test_active_chunks() {
+        let mut chunk_graph = ChunkGraph::new();
+        chunk_graph.add_chunk(chunk("a", entry("a")));
+        chunk_graph.add_chunk(chunk("b", entry("b")));
+        chunk_graph.add_chunk(chunk("lazy.ts", ChunkType::Async));
+        chunk_graph.add_edge(
+            &ModuleId::new("a".to_string()),
+            &ModuleId::new("lazy.ts".to_string()),
+        );
+
+        let on_demand_entries = OnDemandEntries::default();
+        assert!(on_demand_entries.active_chunks(&chunk_graph).is_empty());
+
+        on_demand_entries.record("a.css");
+        on_demand_entries.record("index.html");
+        assert_eq!(
+            on_demand_entries.active_chunks(&chunk_graph),
+            HashSet::from([
+                ModuleId::new("a".to_string()),
+                ModuleId::new("lazy.ts".to_string())
+            ])
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use rustls_pemfile::Item;
+use tokio_rustls::rustls::{Certificate, PrivateKey, ServerConfig};
+
+use crate::config::HttpsConfig;
+
+pub(crate) fn server_config(https: &HttpsConfig, http2: bool, root: &Path) -> Result<ServerConfig> {
+    let (certs, key) = match (&https.cert, &https.key) {
+        (Some(cert), Some(key)) => (read_certs(&root.join(cert))?, read_key(&root.join(key))?),
+        _ => self_signed()?,
+    };
+    let mut config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)?;
+    // browsers only support http2 over tls, which is negotiated with alpn
+    config.alpn_protocols = if http2 {
+        vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+    } else {
+        vec![b"http/1.1".to_vec()]
+    };
+    Ok(config)
+}
+
+fn read_certs(path: &Path) -> Result<Vec<Certificate>> {
+    let mut reader = BufReader::new(
+        File::open(path).map_err(|e| anyhow!("Read {} failed: {}", path.display(), e))?,
+    );
+    let certs = rustls_pemfile::certs(&mut reader)?;
+    if certs.is_empty() {
+        return Err(anyhow!("No certificate found in {}", path.display()));
+    }
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+fn read_key(path: &Path) -> Result<PrivateKey> {
+    let mut reader = BufReader::new(
+        File::open(path).map_err(|e| anyhow!("Read {} failed: {}", path.display(), e))?,
+    );
+    rustls_pemfile::read_all(&mut reader)?
+        .into_iter()
+        .find_map(|item| match item {
+            Item::RSAKey(key) | Item::PKCS8Key(key) | Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("No private key found in {}", path.display()))
+}
+
+/// Generated for every start, so the browsers will warn about it
+fn self_signed() -> Result<(Vec<Certificate>, PrivateKey)> {
+    let cert =
+        rcgen::generate_simple_self_signed(vec!["localhost".to_string(), "127.0.0.1".to_string()])?;
+    Ok((
+        vec![Certificate(cert.serialize_der()?)],
+        PrivateKey(cert.serialize_private_key_der()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::server_config;
+    use crate::config::HttpsConfig;
+
+    #[test]
+    fn test_self_signed() {
+        let config = server_config(&HttpsConfig::default(), false, &PathBuf::from("/")).unwrap();
+        assert_eq!(config.alpn_protocols, vec![b"http/1.1".to_vec()]);
+    }
+
+    #[test]
+    fn test_http2_alpn() {
+        let config = server_config(&HttpsConfig::default(), true, &PathBuf::from("/")).unwrap();
+        assert_eq!(config.alpn_protocols[0], b"h2".to_vec());
+    }
+
+    #[test]
+    fn test_cert_not_found() {
+        let https = HttpsConfig {
+            cert: Some("not-exists.pem".to_string()),
+            key: Some("not-exists-key.pem".to_string()),
+        };
+        assert!(server_config(&https, false, &PathBuf::from("/")).is_err());
+    }
+}
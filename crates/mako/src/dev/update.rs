@@ -9,8 +9,10 @@ use tracing::debug;
 
 use crate::build::BuildError;
 use crate::compiler::Compiler;
+use crate::config::glob_entry_name;
+use crate::features::dotenv::Dotenv;
 use crate::generate::transform::transform_modules;
-use crate::module::{Dependency, Module, ModuleId, ResolveType};
+use crate::module::{Dependency, Module, ModuleAst, ModuleId, ResolveType};
 use crate::module_graph::ModuleGraph;
 use crate::resolve::{self, clear_resolver_cache};
 
@@ -79,6 +81,52 @@ dep_changed:{:?}
 
 impl Compiler {
     pub fn update(&self, paths: Vec<PathBuf>) -> Result<UpdateResult> {
+        let invalidated = self
+            .context
+            .invalidated_modules
+            .lock()
+            .unwrap()
+            .drain()
+            .collect::<Vec<_>>();
+        let result = self.update_paths(paths, &invalidated);
+        if result.is_err() && self.context.cancellation.is_cancelled() {
+            // the cancelled update is retried with the same paths by the next rebuild
+            self.context
+                .invalidated_modules
+                .lock()
+                .unwrap()
+                .extend(invalidated);
+            self.discard_unbuilt_modules();
+        }
+        result
+    }
+
+    /// Removes the modules added to the graph but not built yet by a cancelled update, and
+    /// invalidates their dependents, so that they're added again when the dependents are rebuilt
+    fn discard_unbuilt_modules(&self) {
+        let mut module_graph = self.context.module_graph.write().unwrap();
+        let unbuilt = module_graph
+            .modules()
+            .into_iter()
+            .filter(|module| module.info.is_none())
+            .map(|module| module.id.clone())
+            .collect::<Vec<_>>();
+        let mut invalidated_modules = self.context.invalidated_modules.lock().unwrap();
+        for module_id in &unbuilt {
+            for (dependent, _) in module_graph.get_dependents(module_id) {
+                if !unbuilt.contains(dependent) {
+                    invalidated_modules.insert(dependent.clone());
+                }
+            }
+        }
+        for module_id in &unbuilt {
+            module_graph.remove_module(module_id);
+        }
+    }
+
+    fn update_paths(&self, paths: Vec<PathBuf>, invalidated: &[ModuleId]) -> Result<UpdateResult> {
+        let paths = self.with_env_changes(paths)?;
+        let paths = self.with_file_dependency_changes(paths);
         let module_graph = self.context.module_graph.read().unwrap();
         let paths = paths
             .into_iter()
@@ -88,6 +136,9 @@ impl Compiler {
                     if module_graph.has_module(&path.clone().into())
                         || module_graph.has_module(&format!("{}?modules", path).into())
                         || module_graph.has_module(&format!("{}?watch=parent", path).into())
+                        || module_graph.has_module(&format!("{}?scopedstyle", path).into())
+                        || module_graph.has_module(&format!("{}?sheet", path).into())
+                        || !layered_css_modules(&module_graph, &path).is_empty()
                     {
                         UpdateType::Modify
                     } else {
@@ -99,8 +150,32 @@ impl Compiler {
                 (path, update_type)
             })
             .collect::<Vec<_>>();
+        let dir_dependents = self.dir_dependency_changes(&paths, &module_graph);
+        let mut paths = paths;
+        // the modules invalidated by the plugins or by their dir dependencies are rebuilt as
+        // modified, e.g. the context modules whose ids have the queries
+        for id in invalidated.iter().chain(dir_dependents.iter()) {
+            let path = id.to_path();
+            if module_graph.has_module(id) && !paths.iter().any(|(p, _)| p == &path) {
+                paths.push((path, UpdateType::Modify));
+            }
+        }
         drop(module_graph);
         debug!("update: {:?}", &paths);
+        // the cached resolve results may be changed by the added or removed files and the packages
+        if self
+            .context
+            .resolve_cache
+            .check_lockfiles(&self.context.root)
+        {
+            debug!("clear resolve cache since the lockfiles are changed");
+        } else if paths.iter().any(|(path, update_type)| {
+            !matches!(update_type, UpdateType::Modify) || resolve::is_resolution_file(path)
+        }) {
+            debug!("clear resolve cache");
+            self.context.resolve_cache.clear();
+        }
+        let (added_entries, removed_entries) = self.update_glob_entries(&paths);
         let mut update_result: UpdateResult = Default::default();
 
         let mut modified = vec![];
@@ -163,17 +238,20 @@ impl Compiler {
                     new_paths.push((p.clone(), update_type.clone()));
                 }
                 let path = p.to_string_lossy().to_string();
-                let watch_parent_searches = vec!["?modules", "?watch=parent"];
-                for search in watch_parent_searches {
-                    let id: ModuleId = format!("{}{}", path, search).into();
+                let watch_parent_searches =
+                    vec!["?modules", "?watch=parent", "?scopedstyle", "?sheet"];
+                let ids = watch_parent_searches
+                    .into_iter()
+                    .map(|search| ModuleId::from(format!("{}{}", path, search)))
+                    .chain(layered_css_modules(&module_graph, &path));
+                for id in ids {
                     if module_graph.has_module(&id) {
                         debug!("  > {} is filtered", &id.id);
-                        new_paths.push((PathBuf::from(&id.id), update_type.clone()));
+                        new_paths.push((id.to_path(), update_type.clone()));
                         let dependents = module_graph.get_dependents(&id);
                         for dependent in dependents {
                             debug!("  > {} is filtered", dependent.0.id);
-                            new_paths
-                                .push((PathBuf::from(dependent.0.id.clone()), update_type.clone()));
+                            new_paths.push((dependent.0.to_path(), update_type.clone()));
                         }
                     }
                 }
@@ -212,6 +290,15 @@ impl Compiler {
             }
         }
 
+        // the removed glob entries are filtered above since their ids may have the ?hmr query
+        for entry in removed_entries {
+            if !removed.contains(&entry) {
+                removed.push(entry.clone());
+            }
+            // regroup the chunks since the entry chunk is gone
+            update_result.dep_changed.insert(ModuleId::from_path(entry));
+        }
+
         // 先做删除
         debug!("remove: {:?}", &removed);
         let (removed_module_ids, affected_module_ids) = self.build_by_remove(removed);
@@ -252,10 +339,179 @@ impl Compiler {
         );
         update_result.added.extend(added_module_ids);
 
+        if !added_entries.is_empty() {
+            debug!("add entries: {:?}", &added_entries);
+            let files = added_entries
+                .iter()
+                .map(|entry| self.entry_file(entry))
+                .collect::<Vec<_>>();
+            let entry_ids = files
+                .iter()
+                .map(|file| ModuleId::new(file.path.to_string_lossy().to_string()))
+                .collect::<Vec<_>>();
+            let added_module_ids = self.build(files)?;
+            update_result.added.extend(added_module_ids);
+            // regroup the chunks to create the new entry chunks
+            update_result.dep_changed.extend(entry_ids);
+        }
+
+        // the rebuilt modules are parsed again, the asts of the other dropped modules are restored
+        // when their chunks are rendered again
+        self.context.dropped_asts.lock().unwrap().retain(|id| {
+            !update_result.modified.contains(id) && !update_result.removed.contains(id)
+        });
+
         debug!("update_result: {:?}", &update_result);
         Result::Ok(update_result)
     }
 
+    // the files matching the glob entries are added to or removed from the entries,
+    // returns the paths of the added entries and the module paths of the removed ones
+    fn update_glob_entries(&self, paths: &[(PathBuf, UpdateType)]) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        let mut added = vec![];
+        let mut removed = vec![];
+        let entry_globs = &self.context.config.entry_globs;
+        if entry_globs.is_empty() {
+            return (added, removed);
+        }
+        let module_graph = self.context.module_graph.read().unwrap();
+        let mut entries = self.context.entries.write().unwrap();
+        for (path, update_type) in paths {
+            for (name, pattern) in entry_globs {
+                let Some(entry_name) = glob_entry_name(&self.context.root, name, pattern, path)
+                else {
+                    continue;
+                };
+                match update_type {
+                    UpdateType::Add => {
+                        if !entries.values().any(|entry| entry == path) {
+                            debug!("  > {} is added as entry {}", path.display(), entry_name);
+                            entries.insert(entry_name, path.clone());
+                            added.push(path.clone());
+                        }
+                    }
+                    UpdateType::Remove => {
+                        if entries.get(&entry_name) == Some(path) {
+                            debug!("  > entry {} is removed", entry_name);
+                            entries.remove(&entry_name);
+                            let id = self.entry_file(path).path.to_string_lossy().to_string();
+                            if module_graph.has_module(&id.clone().into()) {
+                                removed.push(PathBuf::from(id));
+                            }
+                        }
+                    }
+                    UpdateType::Modify => {}
+                }
+            }
+        }
+        (added, removed)
+    }
+
+    // env variables are inlined into the modules,
+    // so all the modules need to be rebuilt when the .env files change
+    fn with_env_changes(&self, mut paths: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
+        let root = &self.context.root;
+        let config = &self.context.config;
+        if !paths
+            .iter()
+            .any(|path| Dotenv::is_env_file(path, root, config))
+        {
+            return Ok(paths);
+        }
+        let env = Dotenv::load(root, config)?;
+        let mut current_env = self.context.env.write().unwrap();
+        if *current_env == env {
+            return Ok(paths);
+        }
+        debug!("env changed, rebuild all modules");
+        *current_env = env;
+        let module_graph = self.context.module_graph.read().unwrap();
+        for module in module_graph.modules() {
+            if let Some(info) = &module.info
+                && info.external.is_none()
+                && !info.file.is_under_node_modules
+                && matches!(info.ast, ModuleAst::Script(_))
+            {
+                let path = module.id.to_path();
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+        Ok(paths)
+    }
+
+    // the modules are rebuilt as their file dependencies change, and all the modules are rebuilt
+    // as the build dependencies change
+    fn with_file_dependency_changes(&self, mut paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        let build_dependency_changed = {
+            let build_dependencies = self.context.build_dependencies.read().unwrap();
+            paths.iter().any(|path| build_dependencies.contains(path))
+        };
+        let module_graph = self.context.module_graph.read().unwrap();
+        let dependents = if build_dependency_changed {
+            debug!("build dependencies changed, rebuild all modules");
+            module_graph
+                .modules()
+                .into_iter()
+                .filter(|module| {
+                    module.info.as_ref().is_some_and(|info| {
+                        info.external.is_none() && !info.file.is_under_node_modules
+                    })
+                })
+                .map(|module| module.id.clone())
+                .collect::<Vec<_>>()
+        } else {
+            let file_dependencies = self.context.file_dependencies.read().unwrap();
+            paths
+                .iter()
+                .filter_map(|path| file_dependencies.get(path))
+                .flatten()
+                .filter(|module_id| module_graph.has_module(module_id))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        for module_id in dependents {
+            // the queried modules are rebuilt with their files, e.g. a.module.css?modules
+            let path = PathBuf::from(module_id.id.split('?').next().unwrap());
+            if !paths.contains(&path) {
+                debug!("  > {} is rebuilt by the file dependencies", path.display());
+                paths.push(path);
+            }
+        }
+        paths
+    }
+
+    // the modules are rebuilt as the files are added to or removed from their dir dependencies
+    fn dir_dependency_changes(
+        &self,
+        paths: &[(PathBuf, UpdateType)],
+        module_graph: &ModuleGraph,
+    ) -> Vec<ModuleId> {
+        let dir_dependencies = self.context.dir_dependencies.read().unwrap();
+        if dir_dependencies.is_empty() {
+            return vec![];
+        }
+        let mut dependents = paths
+            .iter()
+            .filter(|(_, update_type)| !matches!(update_type, UpdateType::Modify))
+            .flat_map(|(path, _)| {
+                dir_dependencies
+                    .iter()
+                    .filter(move |(dir, _)| path.starts_with(dir) && path != *dir)
+                    .flat_map(|(_, modules)| modules)
+            })
+            .filter(|module_id| module_graph.has_module(module_id))
+            .cloned()
+            .collect::<Vec<_>>();
+        dependents.sort();
+        dependents.dedup();
+        for module_id in &dependents {
+            debug!("  > {} is rebuilt by the dir dependencies", module_id.id);
+        }
+        dependents
+    }
+
     pub fn transform_for_change(&self, update_result: &UpdateResult) -> Result<()> {
         let mut changes: Vec<ModuleId> = vec![];
         for module_id in &update_result.added {
@@ -278,8 +534,8 @@ impl Compiler {
                 debug!("build by modify: {:?} start", entry);
                 // first build
                 let is_entry = {
-                    let mut entries = self.context.config.entry.values();
-                    entries.any(|e| e.eq(entry))
+                    let entries = self.context.entries.read().unwrap();
+                    entries.values().any(|e| e.eq(entry))
                 };
 
                 let path = entry.to_string_lossy().to_string();
@@ -302,13 +558,13 @@ impl Compiler {
                         .modules_with_missing_deps
                         .write()
                         .unwrap()
-                        .retain(|id| id != &module.id.id);
+                        .retain(|id| id != module.id.as_str());
                 } else {
                     self.context
                         .modules_with_missing_deps
                         .write()
                         .unwrap()
-                        .push(module.id.id.clone());
+                        .push(module.id.id.to_string());
                 }
 
                 // diff
@@ -437,6 +693,7 @@ impl Compiler {
             let module_id = ModuleId::from_path(path);
             let dependants = module_graph.dependant_module_ids(&module_id);
             module_graph.remove_module_and_deps(&module_id);
+            self.context.clear_emitted_assets(&module_id);
             affected_module_ids.extend(dependants);
             removed_module_ids.insert(module_id);
         }
@@ -481,6 +738,17 @@ impl Diff {
     }
 }
 
+// the css imported with layer() are rebuilt with their files, e.g. a.css?layer=base
+fn layered_css_modules(module_graph: &ModuleGraph, path: &str) -> Vec<ModuleId> {
+    let prefix = format!("{}?layer", path);
+    module_graph
+        .modules()
+        .into_iter()
+        .filter(|module| module.id.id.starts_with(&prefix))
+        .map(|module| module.id.clone())
+        .collect()
+}
+
 // 比较两个依赖列表的差异
 // 未变化的模块算作 modified，因为依赖数据必然发生了变化；eg: order，span
 fn diff(origin: &[(ModuleId, Dependency)], new_deps: &[(ModuleId, Dependency)]) -> Diff {
@@ -89,6 +89,8 @@ impl<'a> Watcher<'a> {
             self.watch_dir_recursive(dir.into(), &self.get_ignore_list(false))?;
             Ok(())
         })?;
+        drop(module_graph);
+        self.watch_file_dependencies()?;
 
         let t_watch_duration = t_watch.elapsed();
         debug!(
@@ -103,6 +105,39 @@ impl<'a> Watcher<'a> {
         Ok(())
     }
 
+    /// Watch the file, dir and build dependencies registered by the loaders, which may be outside
+    /// the root or in the ignored dirs like node_modules, called again after each rebuild
+    pub fn watch_file_dependencies(&mut self) -> anyhow::Result<()> {
+        let paths = {
+            let context = &self.compiler.context;
+            let file_dependencies = context.file_dependencies.read().unwrap();
+            let dir_dependencies = context.dir_dependencies.read().unwrap();
+            let build_dependencies = context.build_dependencies.read().unwrap();
+            file_dependencies
+                .keys()
+                .chain(dir_dependencies.keys())
+                .chain(build_dependencies.iter())
+                .filter(|path| {
+                    !self.watched_files.contains(*path)
+                        && !self.watched_dirs.iter().any(|dir| path.starts_with(dir))
+                })
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        for path in paths {
+            if path.is_dir() {
+                self.watcher
+                    .watch(path.as_path(), notify::RecursiveMode::Recursive)?;
+                self.watched_dirs.insert(path);
+            } else if path.is_file() {
+                self.watcher
+                    .watch(path.as_path(), notify::RecursiveMode::NonRecursive)?;
+                self.watched_files.insert(path);
+            }
+        }
+        Ok(())
+    }
+
     fn get_ignore_list(&self, with_output_dir: bool) -> Vec<PathBuf> {
         let mut ignore_list = vec![".git", "node_modules", ".DS_Store", ".node"];
         if with_output_dir {
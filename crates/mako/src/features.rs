@@ -1,2 +1,3 @@
+pub mod dotenv;
 pub mod node;
 pub mod rsc;
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use crate::config::Config;
+
+pub struct Dotenv {}
+
+impl Dotenv {
+    /// The env files ordered by priority from low to high
+    pub fn files(root: &Path, config: &Config) -> Vec<PathBuf> {
+        let mode = config.mode.to_string();
+        vec![
+            root.join(".env"),
+            root.join(".env.local"),
+            root.join(format!(".env.{}", mode)),
+            root.join(format!(".env.{}.local", mode)),
+        ]
+    }
+
+    pub fn is_env_file(path: &Path, root: &Path, config: &Config) -> bool {
+        Self::files(root, config).iter().any(|file| file == path)
+    }
+
+    /// Load the variables starting with `envPrefix` from the env files and
+    /// the process env, the process env has the highest priority
+    pub fn load(root: &Path, config: &Config) -> Result<HashMap<String, String>> {
+        Self::load_with_vars(root, config, std::env::vars())
+    }
+
+    fn load_with_vars(
+        root: &Path,
+        config: &Config,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<HashMap<String, String>> {
+        if config.env_prefix.is_empty() {
+            return Err(anyhow!(
+                "envPrefix can't be empty, otherwise all the env variables will be exposed"
+            ));
+        }
+        let mut env = HashMap::new();
+        for file in Self::files(root, config) {
+            if file.exists() {
+                env.extend(parse(&fs::read_to_string(&file)?));
+            }
+        }
+        env.extend(vars);
+        env.retain(|key, _| key.starts_with(&config.env_prefix));
+        Ok(env)
+    }
+
+    /// The `process.env.*` defines of the variables
+    pub fn define(env: &HashMap<String, String>) -> HashMap<String, Value> {
+        env.iter()
+            .map(|(key, value)| {
+                (
+                    format!("process.env.{}", key),
+                    Value::String(serde_json::to_string(value).unwrap()),
+                )
+            })
+            .collect()
+    }
+}
+
+fn parse(content: &str) -> HashMap<String, String> {
+    let mut env = HashMap::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        let value = if let Some(value) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+            value.replace("\\n", "\n").replace("\\\"", "\"")
+        } else if let Some(value) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+            value.to_string()
+        } else {
+            // inline comments are only allowed for unquoted values
+            value
+                .split_once(" #")
+                .map_or(value, |(value, _)| value)
+                .trim_end()
+                .to_string()
+        };
+        env.insert(key.trim().to_string(), value);
+    }
+    env
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{parse, Dotenv};
+    use crate::config::Config;
+
+    #[test]
+    fn test_parse() {
+        let env = parse(
+            r#"
+# comment
+MAKO_A=1
+export MAKO_B = "hello\nworld"
+MAKO_C='a # b'
+MAKO_D=foo # comment
+INVALID
+"#,
+        );
+        assert_eq!(env.get("MAKO_A").unwrap(), "1");
+        assert_eq!(env.get("MAKO_B").unwrap(), "hello\nworld");
+        assert_eq!(env.get("MAKO_C").unwrap(), "a # b");
+        assert_eq!(env.get("MAKO_D").unwrap(), "foo");
+        assert_eq!(env.len(), 4);
+    }
+
+    #[test]
+    fn test_load() {
+        let root = std::env::current_dir().unwrap().join("test/build/dotenv");
+        let vars = [
+            ("MAKO_PORT".to_string(), "8000".to_string()),
+            ("HOME".to_string(), "/root".to_string()),
+        ];
+        let env = Dotenv::load_with_vars(&root, &Config::default(), vars).unwrap();
+        assert_eq!(env.get("MAKO_APP_NAME").unwrap(), "mako");
+        // overridden by .env.development
+        assert_eq!(env.get("MAKO_API").unwrap(), "http://localhost");
+        assert_eq!(env.get("MAKO_PORT").unwrap(), "8000");
+        // without prefix
+        assert!(!env.contains_key("SECRET"));
+        assert!(!env.contains_key("HOME"));
+        assert_eq!(env.len(), 3);
+    }
+
+    #[test]
+    fn test_define() {
+        let env = HashMap::from([("MAKO_A".to_string(), "a\"b".to_string())]);
+        let define = Dotenv::define(&env);
+        assert_eq!(
+            define.get("process.env.MAKO_A").unwrap().as_str().unwrap(),
+            r#""a\"b""#
+        );
+    }
+}
@@ -3,13 +3,17 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use pathdiff::diff_paths;
-use serde_json::Value;
 use swc_core::common::{Mark, DUMMY_SP};
-use swc_core::ecma::ast::{Expr, Lit, Str};
+use swc_core::ecma::ast::{
+    CallExpr, Callee, Expr, Ident, KeyValueProp, Lit, MemberExpr, MemberProp, MetaPropExpr,
+    MetaPropKind, Prop, PropName, Str,
+};
+use swc_core::ecma::utils::quote_ident;
 use swc_core::ecma::visit::{VisitMut, VisitMutWith};
 
+use crate::ast::utils::id;
 use crate::compiler::Context;
-use crate::config::{Config, ExternalConfig, Platform};
+use crate::config::{Config, ExternalConfig, OutputFormat, Platform, Provider};
 
 pub struct Node {}
 
@@ -24,15 +28,10 @@ impl Node {
                 "^(node:)?({})(/|$)",
                 Self::get_all_node_modules().join("|")
             ));
-            // polifyll __dirname & __filename is supported with MockFilenameAndDirname Visitor
+            // polifyll __dirname & __filename is supported with NodeShims Visitor
         } else {
-            // polyfill __dirname & __filename for browser
-            config
-                .define
-                .insert("__dirname".into(), Value::String("'/'".into()));
-            config
-                .define
-                .insert("__filename".into(), Value::String("'/index.js'".into()));
+            // __dirname, __filename and global are shimmed with NodeShims Visitor
+            // according to config.node
             // polyfill with equivalent modules
             for name in Self::get_polyfill_modules().iter() {
                 config.resolve.alias.push((
@@ -52,19 +51,31 @@ impl Node {
                 );
             }
             // polyfill identifiers
+            if config.node.as_ref().is_some_and(|node| node.process) {
+                config
+                    .providers
+                    .insert("process".into(), Provider::new("process", &[]));
+            }
             config
                 .providers
-                .insert("process".into(), ("process".into(), "".into()));
-            config
-                .providers
-                .insert("Buffer".into(), ("buffer".into(), "Buffer".into()));
-            config.providers.insert(
-                "global".into(),
-                ("node-libs-browser-okam/polyfill/global".into(), "".into()),
-            );
+                .insert("Buffer".into(), Provider::new("buffer", &["Buffer"]));
         }
     }
 
+    /// whether the outputs are es modules running in node, which keep their own
+    /// `import.meta` and have no `require` in scope
+    pub fn is_esm_output(config: &Config) -> bool {
+        config.platform == Platform::Node && config.output.format == OutputFormat::Esm
+    }
+
+    /// the ignored built-in modules are still required in the es module outputs
+    pub fn esm_require_shim() -> &'static str {
+        concat!(
+            "import { createRequire as __mako_create_require__ } from \"module\";\n",
+            "var require = __mako_create_require__(import.meta.url);\n",
+        )
+    }
+
     fn get_polyfill_modules() -> Vec<String> {
         vec![
             "assert",
@@ -130,35 +141,138 @@ impl Node {
     }
 }
 
-pub struct MockFilenameAndDirname {
+/// Replace the unresolved node globals, `__dirname` and `__filename` become the module
+/// path relative to root, `global` becomes `globalThis`
+pub struct NodeShims {
     pub unresolved_mark: Mark,
     pub current_path: PathBuf,
     pub context: Arc<Context>,
+    pub dirname: bool,
+    pub filename: bool,
+    pub global: bool,
 }
 
-impl VisitMut for MockFilenameAndDirname {
-    fn visit_mut_expr(&mut self, expr: &mut Expr) {
-        if let Expr::Ident(ident) = expr
+impl NodeShims {
+    pub fn new(unresolved_mark: Mark, current_path: PathBuf, context: Arc<Context>) -> Self {
+        let (dirname, filename, global) = match context.config.platform {
+            Platform::Node => (true, true, false),
+            Platform::Browser => context
+                .config
+                .node
+                .as_ref()
+                .map_or((false, false, false), |node| {
+                    (node.dirname, node.filename, node.global)
+                }),
+        };
+        Self {
+            unresolved_mark,
+            current_path,
+            context,
+            dirname,
+            filename,
+            global,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.dirname || self.filename || self.global
+    }
+
+    fn shim(&self, ident: &Ident) -> Option<Expr> {
+        if ident.ctxt.outer() != self.unresolved_mark {
+            return None;
+        }
+        let is_filename = self.filename && ident.sym == "__filename";
+        let is_dirname = self.dirname && ident.sym == "__dirname";
+        if is_filename || is_dirname {
+            let path = diff_paths(&self.current_path, &self.context.root).unwrap_or("".into());
+            let value = if is_filename {
+                path
+            } else {
+                path.parent().unwrap_or(&PathBuf::from("")).into()
+            };
+
+            Some(Expr::Lit(Lit::Str(Str {
+                span: DUMMY_SP,
+                value: value.to_string_lossy().into(),
+                raw: None,
+            })))
+        } else if self.global && ident.sym == "global" {
+            Some(Expr::Ident(id("globalThis")))
+        } else {
+            None
+        }
+    }
+}
+
+// createRequire(__filename) or module.createRequire(__filename)
+fn is_create_require(callee: &Callee) -> bool {
+    match callee {
+        Callee::Expr(box Expr::Ident(ident)) => ident.sym == "createRequire",
+        Callee::Expr(box Expr::Member(MemberExpr {
+            prop: MemberProp::Ident(prop),
+            ..
+        })) => prop.sym == "createRequire",
+        _ => false,
+    }
+}
+
+impl VisitMut for NodeShims {
+    fn visit_mut_call_expr(&mut self, call: &mut CallExpr) {
+        // the required modules are resolved from the output at runtime, so __filename of
+        // createRequire is kept, or becomes import.meta.url in the es module outputs
+        if self.filename
+            && self.context.config.platform == Platform::Node
+            && is_create_require(&call.callee)
+            && let Some(arg) = call.args.first_mut()
+            && let box Expr::Ident(ident) = &arg.expr
+            && ident.sym == "__filename"
             && ident.ctxt.outer() == self.unresolved_mark
         {
-            let is_filename = ident.sym == "__filename";
-            let is_dirname = ident.sym == "__dirname";
-            if is_filename || is_dirname {
-                let path = diff_paths(&self.current_path, &self.context.root).unwrap_or("".into());
-                let value = if is_filename {
-                    path
-                } else {
-                    path.parent().unwrap_or(&PathBuf::from("")).into()
-                };
-
-                *expr = Expr::Lit(Lit::Str(Str {
+            if Node::is_esm_output(&self.context.config) {
+                arg.expr = MemberExpr {
                     span: DUMMY_SP,
-                    value: value.to_string_lossy().into(),
-                    raw: None,
-                }));
+                    obj: MetaPropExpr {
+                        span: DUMMY_SP,
+                        kind: MetaPropKind::ImportMeta,
+                    }
+                    .into(),
+                    prop: quote_ident!("url").into(),
+                }
+                .into();
             }
+            call.callee.visit_mut_with(self);
+            call.args
+                .iter_mut()
+                .skip(1)
+                .for_each(|arg| arg.visit_mut_with(self));
+            return;
+        }
+
+        call.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        if let Expr::Ident(ident) = expr
+            && let Some(shim) = self.shim(ident)
+        {
+            *expr = shim;
         }
 
         expr.visit_mut_children_with(self);
     }
+
+    fn visit_mut_prop(&mut self, prop: &mut Prop) {
+        // { __dirname } -> { __dirname: "src" }
+        if let Prop::Shorthand(ident) = prop
+            && let Some(shim) = self.shim(ident)
+        {
+            *prop = Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(ident.clone().into()),
+                value: shim.into(),
+            });
+        }
+
+        prop.visit_mut_children_with(self);
+    }
 }
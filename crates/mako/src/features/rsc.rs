@@ -2,7 +2,13 @@ use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
 use serde::Serialize;
-use swc_core::ecma::ast::{Expr, ExprStmt, Lit, Module, ModuleItem, Stmt, Str};
+use swc_core::common::Span;
+use swc_core::ecma::ast::{
+    ArrowExpr, BlockStmtOrExpr, Decl, ExportSpecifier, Expr, ExprStmt, FnDecl, FnExpr, Function,
+    Id, Lit, Module, ModuleDecl, ModuleExportName, ModuleItem, Stmt, Str,
+};
+use swc_core::ecma::utils::find_pat_ids;
+use swc_core::ecma::visit::{Visit, VisitWith};
 
 use crate::ast::css_ast::CssAst;
 use crate::ast::file::File;
@@ -27,6 +33,15 @@ pub struct RscCssModules {
     pub modules: bool,
 }
 
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RscServerAction {
+    pub id: String,
+    pub path: String,
+    pub module_id: String,
+    pub name: String,
+}
+
 pub struct Rsc {}
 
 impl Rsc {
@@ -42,6 +57,13 @@ impl Rsc {
             }
         }
         if let Some(rsc_client) = &context.config.rsc_client {
+            // the client components are the references of the client manifest
+            if Rsc::is_client(ast)? {
+                Rsc::emit_client(file, context.clone());
+            }
+            if let Some(tpl) = &rsc_client.server_action_tpl {
+                return Self::generate_server_actions(file, ast, tpl, context.clone());
+            }
             let is_server = Rsc::is_server(ast)?;
             if is_server && matches!(rsc_client.log_server_component, LogServerComponent::Error) {
                 return Err(anyhow!(ParseError::UnsupportedServerAction {
@@ -52,7 +74,7 @@ impl Rsc {
         Ok(None)
     }
 
-    fn is_client(ast: &JsAst) -> Result<bool> {
+    pub(crate) fn is_client(ast: &JsAst) -> Result<bool> {
         contains_directive(&ast.ast, "use client")
     }
 
@@ -71,6 +93,52 @@ impl Rsc {
         )
     }
 
+    /// The `"use server"` modules become the stubs of their exports, and the bodies of the
+    /// `"use server"` functions are replaced with the stubs, the stubs are generated from the
+    /// template with `{{id}}`, `{{name}}` and `{{path}}`, e.g.
+    /// `require("@/rsc").createServerReference("{{id}}")`
+    fn generate_server_actions(
+        file: &File,
+        ast: &JsAst,
+        tpl: &str,
+        context: Arc<Context>,
+    ) -> Result<Option<ModuleAst>> {
+        let module_id = ModuleId::new(file.path.to_string_lossy().to_string()).generate(&context);
+        let path = file.relative_path.to_string_lossy().to_string();
+        let mut stub = |name: &str| {
+            let id = format!("{}#{}", module_id, name);
+            context.stats_info.add_rsc_server_action(RscServerAction {
+                id: id.clone(),
+                path: path.clone(),
+                module_id: module_id.clone(),
+                name: name.to_string(),
+            });
+            tpl.replace("{{id}}", &id)
+                .replace("{{name}}", name)
+                .replace("{{path}}", &path)
+        };
+        let code = if Rsc::is_server(ast)? {
+            server_module_stubs(&ast.ast, &mut stub)
+        } else {
+            let source = file.get_content_raw();
+            let cm = context.meta.script.cm.clone();
+            let offset = |span: Span| {
+                let lo = cm.lookup_byte_offset(span.lo).pos.0 as usize;
+                let hi = cm.lookup_byte_offset(span.hi).pos.0 as usize;
+                (lo, hi)
+            };
+            match server_function_stubs(&ast.ast, &source, offset, &mut stub) {
+                Some(code) => code,
+                None => return Ok(None),
+            }
+        };
+        Ok(Some(ModuleAst::Script(JsAst::build(
+            file.path.to_str().unwrap(),
+            &code,
+            context.clone(),
+        )?)))
+    }
+
     fn emit_client(file: &File, context: Arc<Context>) {
         let stats_info = &context.stats_info;
         let module_id = ModuleId::from_path(file.path.clone()).generate(&context);
@@ -118,6 +186,165 @@ impl Rsc {
     }
 }
 
+// export const foo = async () => {}; -> const $$action_0 = (stub); export { $$action_0 as foo };
+fn server_module_stubs(ast: &Module, stub: &mut impl FnMut(&str) -> String) -> String {
+    let mut names: Vec<String> = vec![];
+    for item in &ast.body {
+        let ModuleItem::ModuleDecl(decl) = item else {
+            continue;
+        };
+        match decl {
+            ModuleDecl::ExportDecl(export) => match &export.decl {
+                Decl::Fn(f) => names.push(f.ident.sym.to_string()),
+                Decl::Class(c) => names.push(c.ident.sym.to_string()),
+                Decl::Var(var) => {
+                    for declarator in &var.decls {
+                        let ids: Vec<Id> = find_pat_ids(&declarator.name);
+                        names.extend(ids.into_iter().map(|id| id.0.to_string()));
+                    }
+                }
+                _ => {}
+            },
+            ModuleDecl::ExportNamed(export) => {
+                for specifier in &export.specifiers {
+                    let name = match specifier {
+                        ExportSpecifier::Named(named) => {
+                            named.exported.as_ref().unwrap_or(&named.orig)
+                        }
+                        ExportSpecifier::Namespace(namespace) => &namespace.name,
+                        ExportSpecifier::Default(default) => {
+                            names.push(default.exported.sym.to_string());
+                            continue;
+                        }
+                    };
+                    names.push(match name {
+                        ModuleExportName::Ident(ident) => ident.sym.to_string(),
+                        ModuleExportName::Str(str) => str.value.to_string(),
+                    });
+                }
+            }
+            ModuleDecl::ExportDefaultDecl(_) | ModuleDecl::ExportDefaultExpr(_) => {
+                names.push("default".to_string())
+            }
+            _ => {}
+        }
+    }
+    names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let is_ident = name.chars().enumerate().all(|(i, c)| {
+                c == '_' || c == '$' || c.is_alphabetic() || (i > 0 && c.is_numeric())
+            });
+            let exported = if is_ident {
+                name.clone()
+            } else {
+                serde_json::to_string(name).unwrap()
+            };
+            format!(
+                "const $$action_{index} = ({});\nexport {{ $$action_{index} as {} }};\n",
+                stub(name),
+                exported
+            )
+        })
+        .collect()
+}
+
+// function foo() { "use server"; ... } -> function foo() { return (stub).apply(this, arguments); }
+// async () => { "use server"; ... } -> (stub)
+fn server_function_stubs(
+    ast: &Module,
+    source: &str,
+    offset: impl Fn(Span) -> (usize, usize),
+    stub: &mut impl FnMut(&str) -> String,
+) -> Option<String> {
+    let mut collector = ServerFunctions::default();
+    ast.visit_with(&mut collector);
+    if collector.functions.is_empty() {
+        return None;
+    }
+    let mut code = source.to_string();
+    let mut functions = collector
+        .functions
+        .into_iter()
+        .enumerate()
+        .map(|(index, (span, name, is_arrow))| {
+            let name = name.unwrap_or_else(|| format!("$$ACTION_{}", index));
+            let stub = stub(&name);
+            let replacement = if is_arrow {
+                format!("({})", stub)
+            } else {
+                format!("{{ return ({}).apply(this, arguments); }}", stub)
+            };
+            (offset(span), replacement)
+        })
+        .collect::<Vec<_>>();
+    // replace from the end, so that the offsets of the others are kept
+    functions.sort_by_key(|((lo, _), _)| std::cmp::Reverse(*lo));
+    for ((lo, hi), replacement) in functions {
+        code.replace_range(lo..hi, &replacement);
+    }
+    Some(code)
+}
+
+#[derive(Default)]
+struct ServerFunctions {
+    // the body span of the functions or the span of the arrow functions
+    functions: Vec<(Span, Option<String>, bool)>,
+}
+
+impl ServerFunctions {
+    fn is_server_function(stmts: &[Stmt]) -> bool {
+        stmts
+            .iter()
+            .map_while(|stmt| match stmt {
+                Stmt::Expr(ExprStmt {
+                    expr: box Expr::Lit(Lit::Str(Str { value, .. })),
+                    ..
+                }) => Some(value),
+                _ => None,
+            })
+            .any(|value| value == "use server")
+    }
+
+    fn visit_named_function(&mut self, function: &Function, name: Option<String>) {
+        if let Some(body) = &function.body
+            && Self::is_server_function(&body.stmts)
+        {
+            self.functions.push((body.span, name, false));
+        } else {
+            function.visit_children_with(self);
+        }
+    }
+}
+
+impl Visit for ServerFunctions {
+    fn visit_fn_decl(&mut self, n: &FnDecl) {
+        self.visit_named_function(&n.function, Some(n.ident.sym.to_string()));
+    }
+
+    fn visit_fn_expr(&mut self, n: &FnExpr) {
+        self.visit_named_function(
+            &n.function,
+            n.ident.as_ref().map(|ident| ident.sym.to_string()),
+        );
+    }
+
+    fn visit_function(&mut self, n: &Function) {
+        self.visit_named_function(n, None);
+    }
+
+    fn visit_arrow_expr(&mut self, n: &ArrowExpr) {
+        if let box BlockStmtOrExpr::BlockStmt(body) = &n.body
+            && Self::is_server_function(&body.stmts)
+        {
+            self.functions.push((n.span, None, true));
+        } else {
+            n.visit_children_with(self);
+        }
+    }
+}
+
 fn contains_directive(ast: &Module, directive: &str) -> Result<bool> {
     let mut is_directive = true;
     let mut is_target_directive = false;
@@ -170,6 +397,75 @@ mod tests {
         assert!(Rsc::is_server(&build_ast(r#""use server""#)).unwrap());
     }
 
+    #[test]
+    fn test_server_module_stubs() {
+        let ast = build_ast(
+            r#""use server";
+export async function foo() {}
+export const bar = async () => {}, { baz } = {};
+const qux = 1;
+export { qux as "q-x" };
+export default async function () {}
+"#,
+        );
+        let code = server_module_stubs(&ast.ast, &mut |name| format!("stub({})", name));
+        assert_eq!(
+            code,
+            r#"const $$action_0 = (stub(foo));
+export { $$action_0 as foo };
+const $$action_1 = (stub(bar));
+export { $$action_1 as bar };
+const $$action_2 = (stub(baz));
+export { $$action_2 as baz };
+const $$action_3 = (stub(q-x));
+export { $$action_3 as "q-x" };
+const $$action_4 = (stub(default));
+export { $$action_4 as default };
+"#
+        );
+    }
+
+    #[test]
+    fn test_server_function_stubs() {
+        let context: Arc<Context> = Arc::new(Default::default());
+        let source = r#"export function Form() {
+  async function submit(data) {
+    "use server";
+    await db.save(data);
+  }
+  const remove = async () => { "use server"; await db.remove(); };
+  return [submit, remove];
+}
+"#;
+        let ast = JsAst::build("test.js", source, context.clone()).unwrap();
+        let cm = context.meta.script.cm.clone();
+        let offset = |span: Span| {
+            (
+                cm.lookup_byte_offset(span.lo).pos.0 as usize,
+                cm.lookup_byte_offset(span.hi).pos.0 as usize,
+            )
+        };
+        let code = server_function_stubs(&ast.ast, source, offset, &mut |name| {
+            format!("stub(\"{}\")", name)
+        });
+        assert_eq!(
+            code.unwrap(),
+            r#"export function Form() {
+  async function submit(data) { return (stub("submit")).apply(this, arguments); }
+  const remove = (stub("$$ACTION_1"));
+  return [submit, remove];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_server_function_stubs_without_actions() {
+        let ast = build_ast("export function foo() {}");
+        let code = server_function_stubs(&ast.ast, "", |_| (0, 0), &mut |_| unreachable!());
+        assert!(code.is_none());
+    }
+
     fn build_ast(content: &str) -> JsAst {
         JsAst::build("test.ts", content, Default::default()).unwrap()
     }
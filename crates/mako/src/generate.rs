@@ -2,19 +2,24 @@ pub(crate) mod analyze;
 pub(crate) mod chunk;
 pub(crate) mod chunk_graph;
 pub(crate) mod chunk_pot;
+pub(crate) mod compress;
+pub(crate) mod debug_id;
 pub(crate) mod generate_chunks;
 pub(crate) mod group_chunk;
 pub(crate) mod hmr;
+pub(crate) mod mangle_exports;
+pub(crate) mod memory;
 pub(crate) mod minify;
 pub(crate) mod optimize_chunk;
 pub(crate) mod runtime;
 pub(crate) mod swc_helpers;
 pub(crate) mod transform;
+pub(crate) mod worklet;
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::ops::DerefMut;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use analyze::Analyze;
@@ -27,10 +32,13 @@ use tracing::debug;
 use crate::compiler::{Compiler, Context};
 use crate::config::{DevtoolConfig, OutputMode, TreeShakingStrategy};
 use crate::dev::update::UpdateResult;
-use crate::generate::generate_chunks::{ChunkFile, ChunkFileType};
+use crate::generate::chunk::ChunkId;
+use crate::generate::generate_chunks::{ChunkFile, ChunkFileEmitter, ChunkFileType};
+use crate::generate::worklet::is_worklet_chunk;
 use crate::module::{Dependency, ModuleId};
+use crate::plugin::PluginEmittedSourceMap;
 use crate::plugins::bundless_compiler::BundlessCompiler;
-use crate::stats::StatsJsonMap;
+use crate::stats::{write_stats, StatsJsonMap};
 use crate::utils::base64_encode;
 use crate::visitors::async_module::mark_async;
 
@@ -111,6 +119,19 @@ impl Compiler {
             return Ok(stats);
         }
 
+        // the renamed exports would break the hmr updates of the changed modules
+        if !self.context.args.watch
+            && let Some(mangle) = self
+                .context
+                .config
+                .optimization
+                .as_ref()
+                .and_then(|o| o.mangle_exports.as_ref())
+        {
+            let mut module_graph = self.context.module_graph.write().unwrap();
+            mangle_exports::mangle_exports(module_graph.deref_mut(), mangle);
+        }
+
         let t_group_chunks = Instant::now();
         self.group_chunk();
         let t_group_chunks = t_group_chunks.elapsed();
@@ -149,7 +170,8 @@ impl Compiler {
         }
 
         let full_hash = self.full_hash();
-        let (t_generate_chunks, t_ast_to_code_and_write) = self.write_chunk_files(full_hash)?;
+        let (t_generate_chunks, t_ast_to_code_and_write, chunk_files) =
+            self.write_chunk_files(full_hash)?;
 
         // write assets
         if config.emit_assets {
@@ -157,20 +179,28 @@ impl Compiler {
             debug!("write assets");
             {
                 let assets_info = &(*self.context.assets_info.lock().unwrap());
+                check_asset_conflicts(&self.context.root, assets_info)?;
                 for (k, v) in assets_info {
                     let asset_path = &self.context.root.join(k);
                     let asset_output_path = &config.output.path.join(v);
                     if asset_path.exists() {
+                        // the filenames of config.asset_rules may have dirs
+                        if let Some(parent) = asset_output_path.parent() {
+                            fs::create_dir_all(parent)?;
+                        }
                         fs::copy(asset_path, asset_output_path)?;
                     } else {
                         return Err(anyhow!("asset not found: {}", asset_path.display()));
                     }
                 }
+                self.context.write_emitted_assets()?;
             }
             let t_write_assets = t_write_assets.elapsed();
             debug!("  - write assets: {}ms", t_write_assets.as_millis());
         }
 
+        self.compress_output_files(&chunk_files)?;
+
         // generate stats
         let stats = self.create_stats_info();
 
@@ -179,13 +209,18 @@ impl Compiler {
             .plugin_driver
             .build_success(&stats, &self.context)?;
 
+        if self.context.config.stats.is_some() {
+            write_stats(&self.context.config.output.path, &stats);
+        }
+
         // print stats
-        if !self.context.args.watch {
+        // the size report plugin prints a more detailed table instead
+        if !self.context.args.watch && self.context.config.size_report.is_none() {
             self.print_stats();
         }
 
         if self.context.config.analyze.is_some() {
-            Analyze::write_analyze(&stats, &self.context.config.output.path)?;
+            Analyze::write_analyze(&stats, &self.context)?;
         }
 
         debug!("generate done in {}ms", t_generate.elapsed().as_millis());
@@ -205,11 +240,30 @@ impl Compiler {
         Ok(stats)
     }
 
-    fn write_chunk_files(&self, full_hash: u64) -> Result<(Duration, Duration)> {
+    fn write_chunk_files(&self, full_hash: u64) -> Result<(Duration, Duration, Vec<ChunkFile>)> {
         // generate chunks
         let t_generate_chunks = Instant::now();
         debug!("generate chunks");
-        let chunk_files = self.generate_chunk_files(full_hash)?;
+        // in build mode, normal chunks are written to disk as soon as they are generated,
+        // the debug ids of their source maps are kept for the sourceMapsEmitted hook
+        let debug_ids = Mutex::new(HashMap::new());
+        let emitter = |file: &ChunkFile| -> Result<()> {
+            if let Some(debug_id) = self.emit_chunk_file(file)? {
+                debug_ids.lock().unwrap().insert(file.disk_name(), debug_id);
+            }
+            Ok(())
+        };
+        let (mut chunk_files, normal_chunk_files) = self.generate_chunk_files_with_emitter(
+            full_hash,
+            if self.context.args.watch {
+                None
+            } else {
+                Some(&emitter)
+            },
+            None,
+        )?;
+        let entry_chunk_files_count = chunk_files.len();
+        chunk_files.extend(normal_chunk_files);
         self.context
             .plugin_driver
             .after_generate_chunk_files(&chunk_files, &self.context)?;
@@ -219,19 +273,79 @@ impl Compiler {
         let t_ast_to_code_and_write = if self.context.args.watch {
             self.generate_chunk_mem_file(&chunk_files)?
         } else {
-            self.generate_chunk_disk_file(&chunk_files)?
+            self.generate_chunk_disk_file(&chunk_files[..entry_chunk_files_count], &emitter)?
         };
 
-        Ok((t_generate_chunks, t_ast_to_code_and_write))
+        if !self.context.args.watch
+            && matches!(self.context.config.devtool, Some(DevtoolConfig::SourceMap))
+        {
+            let mut debug_ids = debug_ids.into_inner().unwrap();
+            let output_path = &self.context.config.output.path;
+            let source_maps = chunk_files
+                .iter()
+                .filter(|chunk_file| chunk_file.source_map.is_some())
+                .map(|chunk_file| PluginEmittedSourceMap {
+                    chunk: output_path.join(chunk_file.disk_name()),
+                    source_map: output_path.join(chunk_file.source_map_disk_name()),
+                    debug_id: debug_ids.remove(&chunk_file.disk_name()),
+                })
+                .collect::<Vec<_>>();
+            self.context
+                .plugin_driver
+                .source_maps_emitted(&source_maps, &self.context)?;
+        }
+
+        Ok((t_generate_chunks, t_ast_to_code_and_write, chunk_files))
+    }
+
+    /// Write the compressed siblings of the chunk files and the assets, after they're written
+    /// and the afterGenerateChunkFiles hook, so the final files are compressed
+    fn compress_output_files(&self, chunk_files: &[ChunkFile]) -> Result<()> {
+        let Some(compress) = &self.context.config.output.compress else {
+            return Ok(());
+        };
+        let output_path = &self.context.config.output.path;
+        if self.context.args.watch {
+            // the chunk files are served from the memory in watch mode
+            chunk_files.par_iter().try_for_each(|file| -> Result<()> {
+                let Some(content) = self.context.get_static_content(file.disk_name()) else {
+                    return Ok(());
+                };
+                for (name, compressed) in
+                    compress::compress(&file.disk_name(), &content, compress)?
+                {
+                    self.context
+                        .write_static_content(name, compressed, file.raw_hash)?;
+                }
+                Ok(())
+            })?;
+        } else {
+            chunk_files.par_iter().try_for_each(|file| {
+                compress::compress_file(&output_path.join(file.disk_name()), compress)
+            })?;
+        }
+        let assets = {
+            let assets_info = self.context.assets_info.lock().unwrap();
+            let emitted_assets = self.context.emitted_assets.lock().unwrap();
+            assets_info
+                .values()
+                .chain(emitted_assets.keys())
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        assets
+            .par_iter()
+            .try_for_each(|asset| compress::compress_file(&output_path.join(asset), compress))
     }
 
-    fn generate_chunk_disk_file(&self, chunk_files: &Vec<ChunkFile>) -> Result<Duration> {
+    fn generate_chunk_disk_file(
+        &self,
+        chunk_files: &[ChunkFile],
+        emitter: &ChunkFileEmitter,
+    ) -> Result<Duration> {
         let t_ast_to_code_and_write = Instant::now();
         debug!("ast to code and write");
-        chunk_files.par_iter().try_for_each(|file| -> Result<()> {
-            self.emit_chunk_file(file);
-            Ok(())
-        })?;
+        chunk_files.par_iter().try_for_each(emitter)?;
         let t_ast_to_code_and_write = t_ast_to_code_and_write.elapsed();
 
         Ok(t_ast_to_code_and_write)
@@ -251,14 +365,18 @@ impl Compiler {
         Ok(t_ast_to_code_and_write)
     }
 
-    pub fn emit_chunk_file(&self, chunk_file: &ChunkFile) {
-        emit_chunk_file(&self.context, chunk_file);
+    /// Write the chunk file to disk, returns the debug id injected into its source map
+    pub fn emit_chunk_file(&self, chunk_file: &ChunkFile) -> Result<Option<String>> {
+        emit_chunk_file(&self.context, chunk_file)
     }
 
+    /// Emit the chunk files in watch mode, with `on_demand`, the active chunks are emitted first
+    /// and the callback is called before the other chunks are emitted
     pub fn emit_dev_chunks(
         &self,
         current_hmr_hash: u64,
         last_hmr_hash: u64,
+        on_demand: Option<(&HashSet<ChunkId>, &dyn Fn())>,
     ) -> Result<StatsJsonMap> {
         crate::mako_profile_function!("emit_dev_chunks");
 
@@ -283,58 +401,68 @@ impl Compiler {
 
         // generate chunks
         let t_generate_chunks = Instant::now();
-        let chunk_files = self.generate_chunk_files(current_hmr_hash)?;
-
-        if config.hmr.is_some() {
-            let mut chunk_id_url_map = ChunksUrlMap {
-                js: HashMap::new(),
-                css: HashMap::new(),
-            };
-
-            chunk_files.iter().for_each(|c| match c.file_type {
-                ChunkFileType::JS => {
-                    chunk_id_url_map
-                        .js
-                        .insert(c.chunk_id.clone(), c.disk_name());
-                }
-                ChunkFileType::Css => {
-                    chunk_id_url_map
-                        .css
-                        .insert(c.chunk_id.clone(), c.disk_name());
-                }
-            });
-
-            self.write_to_dist(
-                format!("{}.hot-update-url-map.json", last_hmr_hash),
-                serde_json::to_string(&chunk_id_url_map).unwrap(),
-            );
-        }
+        let (chunk_files, t_ast_to_code_and_write) = match on_demand {
+            Some((active_chunks, on_active_emitted)) => {
+                let mut chunk_files =
+                    self.generate_chunk_files(current_hmr_hash, Some(active_chunks))?;
+                self.write_hot_update_url_map(&chunk_files, last_hmr_hash);
+                let t_active = self.generate_chunk_mem_file(&chunk_files)?;
+                debug!("active chunks emitted: {}", chunk_files.len());
+                on_active_emitted();
+
+                let deferred_chunks = {
+                    let chunk_graph = self.context.chunk_graph.read().unwrap();
+                    chunk_graph
+                        .get_chunks()
+                        .into_iter()
+                        .map(|chunk| chunk.id.clone())
+                        .filter(|chunk_id| !active_chunks.contains(chunk_id))
+                        .collect::<HashSet<_>>()
+                };
+                let deferred_chunk_files =
+                    self.generate_chunk_files(current_hmr_hash, Some(&deferred_chunks))?;
+                let t_deferred = self.generate_chunk_mem_file(&deferred_chunk_files)?;
+                chunk_files.extend(deferred_chunk_files);
+                self.write_hot_update_url_map(&chunk_files, last_hmr_hash);
+                (chunk_files, t_active + t_deferred)
+            }
+            None => {
+                let chunk_files = self.generate_chunk_files(current_hmr_hash, None)?;
+                self.write_hot_update_url_map(&chunk_files, last_hmr_hash);
+                let t_ast_to_code_and_write = self.generate_chunk_mem_file(&chunk_files)?;
+                (chunk_files, t_ast_to_code_and_write)
+            }
+        };
 
         self.context
             .plugin_driver
             .after_generate_chunk_files(&chunk_files, &self.context)?;
 
-        let t_generate_chunks = t_generate_chunks.elapsed();
-
-        // ast to code and sourcemap, then write
-        debug!("ast to code and write");
-        let t_ast_to_code_and_write = self.generate_chunk_mem_file(&chunk_files)?;
+        let t_generate_chunks = t_generate_chunks
+            .elapsed()
+            .saturating_sub(t_ast_to_code_and_write);
 
         // write assets
         let t_write_assets = Instant::now();
         debug!("write assets");
         {
             let assets_info = &(*self.context.assets_info.lock().unwrap());
+            check_asset_conflicts(&self.context.root, assets_info)?;
             for (k, v) in assets_info {
                 let asset_path = &self.context.root.join(k);
                 let asset_output_path = &config.output.path.join(v);
                 if asset_path.exists() {
+                    if let Some(parent) = asset_output_path.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
                     fs::copy(asset_path, asset_output_path)?;
                 } else {
                     panic!("asset not found: {}", asset_path.display());
                 }
             }
+            self.context.write_emitted_assets()?;
         }
+        self.compress_output_files(&chunk_files)?;
         let t_write_assets = t_write_assets.elapsed();
 
         let stats = self.create_stats_info();
@@ -355,6 +483,34 @@ impl Compiler {
         Ok(stats)
     }
 
+    fn write_hot_update_url_map(&self, chunk_files: &[ChunkFile], last_hmr_hash: u64) {
+        if self.context.config.hmr.is_none() {
+            return;
+        }
+        let mut chunk_id_url_map = ChunksUrlMap {
+            js: HashMap::new(),
+            css: HashMap::new(),
+        };
+
+        chunk_files.iter().for_each(|c| match c.file_type {
+            ChunkFileType::JS => {
+                chunk_id_url_map
+                    .js
+                    .insert(c.chunk_id.clone(), c.disk_name());
+            }
+            ChunkFileType::Css => {
+                chunk_id_url_map
+                    .css
+                    .insert(c.chunk_id.clone(), c.disk_name());
+            }
+        });
+
+        self.write_to_dist(
+            format!("{}.hot-update-url-map.json", last_hmr_hash),
+            serde_json::to_string(&chunk_id_url_map).unwrap(),
+        );
+    }
+
     // TODO: integrate into generate fn
     pub fn generate_hot_update_chunks(
         &self,
@@ -419,6 +575,7 @@ impl Compiler {
                 let modified_chunks: Vec<String> = cg
                     .get_chunks()
                     .iter()
+                    .filter(|c| !is_worklet_chunk(c))
                     .filter(|c| {
                         let is_modified = updated_modules
                             .modified
@@ -543,13 +700,14 @@ fn write_dev_chunk_file(context: &Arc<Context>, chunk: &ChunkFile) -> Result<()>
     Ok(())
 }
 
-fn emit_chunk_file(context: &Arc<Context>, chunk_file: &ChunkFile) {
+fn emit_chunk_file(context: &Arc<Context>, chunk_file: &ChunkFile) -> Result<Option<String>> {
     crate::mako_profile_function!(&chunk_file.file_name);
 
     let dist_name = chunk_file.disk_name();
 
     let to: PathBuf = context.config.output.path.join(dist_name.as_str());
     let stats_info = &context.stats_info;
+    let mut emitted_debug_id = None;
 
     match context.config.devtool {
         Some(DevtoolConfig::SourceMap) => {
@@ -557,6 +715,13 @@ fn emit_chunk_file(context: &Arc<Context>, chunk_file: &ChunkFile) {
             code.extend_from_slice(&chunk_file.content);
 
             if let Some(source_map) = &chunk_file.source_map {
+                let debug_id = chunk_debug_id(context, chunk_file);
+                let source_map = debug_id::process_source_map(
+                    source_map,
+                    debug_id.as_deref(),
+                    &context.root,
+                    &context.config.source_map,
+                )?;
                 let size = source_map.len() as u64;
 
                 let source_map_file_path = context
@@ -576,7 +741,12 @@ fn emit_chunk_file(context: &Arc<Context>, chunk_file: &ChunkFile) {
                     .output
                     .path
                     .join(chunk_file.source_map_disk_name());
-                write_to_file(to.to_str().unwrap(), source_map).unwrap();
+                write_to_file(to.to_str().unwrap(), &source_map)?;
+
+                if let Some(debug_id) = &debug_id {
+                    code.extend_from_slice(format!("\n//# debugId={}", debug_id).as_bytes());
+                }
+                emitted_debug_id = debug_id;
 
                 let source_map_url_line = match chunk_file.file_type {
                     ChunkFileType::JS => {
@@ -603,13 +773,23 @@ fn emit_chunk_file(context: &Arc<Context>, chunk_file: &ChunkFile) {
                 to.to_string_lossy().to_string(),
                 dist_name.clone(),
             );
-            write_to_file(to.to_str().unwrap(), &code).unwrap();
+            write_to_file(to.to_str().unwrap(), &code)?;
         }
         Some(DevtoolConfig::InlineSourceMap) => {
             let mut code = Vec::new();
             code.extend_from_slice(&chunk_file.content);
 
             if let Some(source_map) = &chunk_file.source_map {
+                let debug_id = chunk_debug_id(context, chunk_file);
+                let source_map = debug_id::process_source_map(
+                    source_map,
+                    debug_id.as_deref(),
+                    &context.root,
+                    &context.config.source_map,
+                )?;
+                if let Some(debug_id) = &debug_id {
+                    code.extend_from_slice(format!("\n//# debugId={}", debug_id).as_bytes());
+                }
                 code.extend_from_slice(
                     format!(
                         "\n//# sourceMappingURL=data:application/json;charset=utf-8;base64,{}",
@@ -627,7 +807,7 @@ fn emit_chunk_file(context: &Arc<Context>, chunk_file: &ChunkFile) {
                 to.to_string_lossy().to_string(),
                 dist_name.clone(),
             );
-            write_to_file(to.to_str().unwrap(), &code).unwrap();
+            write_to_file(to.to_str().unwrap(), &code)?;
         }
         None => {
             stats_info.add_assets(
@@ -637,12 +817,20 @@ fn emit_chunk_file(context: &Arc<Context>, chunk_file: &ChunkFile) {
                 to.to_string_lossy().to_string(),
                 dist_name,
             );
-            write_to_file(to.to_str().unwrap(), &chunk_file.content).unwrap();
+            write_to_file(to.to_str().unwrap(), &chunk_file.content)?;
         }
     }
+
+    Ok(emitted_debug_id)
 }
 
-fn write_to_file(path: &str, content: &Vec<u8>) -> std::io::Result<()> {
+/// The debug ids are only injected into the js chunks
+fn chunk_debug_id(context: &Arc<Context>, chunk_file: &ChunkFile) -> Option<String> {
+    (context.config.source_map.debug_ids && matches!(chunk_file.file_type, ChunkFileType::JS))
+        .then(|| debug_id::debug_id(&chunk_file.content))
+}
+
+fn write_to_file(path: &str, content: &[u8]) -> std::io::Result<()> {
     if let Some(parent) = std::path::Path::new(path).parent() {
         if !parent.exists() {
             fs::create_dir_all(parent)?;
@@ -651,6 +839,33 @@ fn write_to_file(path: &str, content: &Vec<u8>) -> std::io::Result<()> {
     fs::write(path, content)
 }
 
+/// The different assets emitted with the same filename overwrite each other, e.g. the ones of
+/// the same name with assetFingerprint query or the filenames of config.asset_rules without
+/// `[hash]`, the copies of the same content are written once
+fn check_asset_conflicts(root: &Path, assets_info: &HashMap<String, String>) -> Result<()> {
+    let mut origins = HashMap::<&String, &String>::new();
+    for (origin, output) in assets_info {
+        let Some(other) = origins.insert(output, origin) else {
+            continue;
+        };
+        if fs::read(root.join(origin)).ok() == fs::read(root.join(other)).ok() {
+            continue;
+        }
+        let (a, b) = if other < origin {
+            (other, origin)
+        } else {
+            (origin, other)
+        };
+        return Err(anyhow!(
+            "Assets {} and {} are both emitted as {}, rename one of them or add [hash] to the filename",
+            a,
+            b,
+            output
+        ));
+    }
+    Ok(())
+}
+
 fn to_hot_update_chunk_name(chunk_name: &String, hash: u64) -> String {
     match chunk_name.rsplit_once('.') {
         None => {
@@ -673,3 +888,33 @@ struct HotUpdateManifest {
     // #[serde(rename(serialize = "c"))]
     // removed_modules: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::check_asset_conflicts;
+
+    #[test]
+    fn test_check_asset_conflicts() {
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/asset-conflicts");
+        let assets_info = |origins: &[&str]| {
+            origins
+                .iter()
+                .map(|origin| (origin.to_string(), "logo.svg".to_string()))
+                .collect::<HashMap<_, _>>()
+        };
+        // the copies of the same content
+        assert!(check_asset_conflicts(&root, &assets_info(&["a/logo.svg", "c/logo.svg"])).is_ok());
+        let err = check_asset_conflicts(&root, &assets_info(&["a/logo.svg", "b/logo.svg"]))
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "Assets a/logo.svg and b/logo.svg are both emitted as logo.svg, rename one of them or \
+             add [hash] to the filename"
+        );
+    }
+}
@@ -1,15 +1,32 @@
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::io::Write;
+use std::ops::AddAssign;
+use std::sync::Arc;
 
 use anyhow::Result;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rayon::prelude::*;
+use serde::Serialize;
+use swc_core::css::codegen::writer::basic::{BasicCssWriter, BasicCssWriterConfig};
+use swc_core::css::codegen::{CodeGenerator, CodegenConfig, Emit};
+use swc_core::ecma::codegen::text_writer::JsWriter;
+use swc_core::ecma::codegen::{Config as JsCodegenConfig, Emitter};
 
+use crate::ast::css_ast::CssAst;
+use crate::ast::js_ast::JsAst;
+use crate::compiler::Context;
+use crate::generate::minify::{minify_css, minify_js};
+use crate::module::{relative_to_root, Module, ModuleAst};
 use crate::stats::StatsJsonMap;
 
 pub struct Analyze {}
 
 impl Analyze {
-    pub fn write_analyze(stats: &StatsJsonMap, path: &Path) -> Result<()> {
+    pub fn write_analyze(stats: &StatsJsonMap, context: &Arc<Context>) -> Result<()> {
         let stats_json = serde_json::to_string_pretty(&stats).unwrap();
+        let treemap_json = serde_json::to_string(&Self::treemap(context)).unwrap();
         let html_str = format!(
             r#"<!DOCTYPE html>
 <html>
@@ -22,15 +39,17 @@ impl Analyze {
     <div id="root"></div>
     <script>
       window.chartData = {};
+      window.treemapData = {};
     </script>
     <script>{}</script>
   </body>
 </html>"#,
             include_str!("../../../../client/dist/index.css"),
             stats_json,
+            treemap_json.replace("</script>", "<\\/script>"),
             include_str!("../../../../client/dist/index.js").replace("</script>", "<\\/script>")
         );
-        let report_path = path.join("analyze-report.html");
+        let report_path = context.config.output.path.join("analyze-report.html");
         fs::write(&report_path, html_str).unwrap();
         println!(
             "Analyze report generated at: {}",
@@ -38,4 +57,241 @@ impl Analyze {
         );
         Ok(())
     }
+
+    /// One group per chunk, each chunk contains its modules grouped by folders.
+    fn treemap(context: &Arc<Context>) -> Vec<TreemapNode> {
+        let chunk_graph = context.chunk_graph.read().unwrap();
+        let module_graph = context.module_graph.read().unwrap();
+
+        chunk_graph
+            .get_chunks()
+            .into_iter()
+            .map(|chunk| {
+                let modules = chunk
+                    .get_modules()
+                    .iter()
+                    .collect::<Vec<_>>()
+                    .par_iter()
+                    .filter_map(|module_id| module_graph.get_module(module_id))
+                    .map(|module| {
+                        (
+                            relative_to_root(module.id.as_str(), &context.root),
+                            module_sizes(module, context),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                build_chunk_tree(chunk.filename(), modules)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ModuleSizes {
+    /// size of the source
+    pub stat: u64,
+    /// size after transform and minify
+    pub parsed: u64,
+    /// gzipped size of `parsed`
+    pub gzip: u64,
+}
+
+impl AddAssign for ModuleSizes {
+    fn add_assign(&mut self, rhs: Self) {
+        self.stat += rhs.stat;
+        self.parsed += rhs.parsed;
+        self.gzip += rhs.gzip;
+    }
+}
+
+/// The data format used by the foamtree in the client
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TreemapNode {
+    pub label: String,
+    pub path: String,
+    pub stat_size: u64,
+    pub parsed_size: u64,
+    pub gzip_size: u64,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_asset: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<TreemapNode>,
+}
+
+impl TreemapNode {
+    fn new(label: String, path: String, sizes: ModuleSizes) -> Self {
+        Self {
+            label,
+            path,
+            stat_size: sizes.stat,
+            parsed_size: sizes.parsed,
+            gzip_size: sizes.gzip,
+            is_asset: false,
+            groups: vec![],
+        }
+    }
+}
+
+#[derive(Default)]
+struct Folder {
+    sizes: ModuleSizes,
+    folders: BTreeMap<String, Folder>,
+    modules: Vec<TreemapNode>,
+}
+
+impl Folder {
+    fn insert(&mut self, path: &str, sizes: ModuleSizes) {
+        let segments = path
+            .split('/')
+            .filter(|s| !s.is_empty() && *s != ".")
+            .collect::<Vec<_>>();
+        let Some((name, folders)) = segments.split_last() else {
+            return;
+        };
+        let mut folder = self;
+        folder.sizes += sizes;
+        for segment in folders {
+            folder = folder.folders.entry(segment.to_string()).or_default();
+            folder.sizes += sizes;
+        }
+        folder
+            .modules
+            .push(TreemapNode::new(name.to_string(), path.to_string(), sizes));
+    }
+
+    fn into_groups(self, path: &str) -> Vec<TreemapNode> {
+        let mut groups = self
+            .folders
+            .into_iter()
+            .map(|(name, folder)| folder.into_node(name, path))
+            .collect::<Vec<_>>();
+        groups.extend(self.modules);
+        groups
+    }
+
+    fn into_node(mut self, mut label: String, parent: &str) -> TreemapNode {
+        // merge folders which only contain one folder, e.g. node_modules/@scope/pkg
+        while self.modules.is_empty() && self.folders.len() == 1 {
+            let (name, child) = self.folders.pop_first().unwrap();
+            label = format!("{}/{}", label, name);
+            self = child;
+        }
+        let path = format!("{}/{}", parent, label);
+        let mut node = TreemapNode::new(label, path.clone(), self.sizes);
+        node.groups = self.into_groups(&path);
+        node
+    }
+}
+
+fn build_chunk_tree(filename: String, modules: Vec<(String, ModuleSizes)>) -> TreemapNode {
+    let mut root = Folder::default();
+    for (path, sizes) in modules {
+        root.insert(&path, sizes);
+    }
+    let mut node = TreemapNode::new(filename, "".to_string(), root.sizes);
+    node.is_asset = true;
+    node.groups = root.into_groups(".");
+    node
+}
+
+fn module_sizes(module: &Module, context: &Arc<Context>) -> ModuleSizes {
+    let Some(info) = module.info.as_ref() else {
+        return ModuleSizes::default();
+    };
+    let code = match &info.ast {
+        ModuleAst::Script(ast) => minified_js(ast, context),
+        ModuleAst::Css(ast) => minified_css(ast, context),
+        ModuleAst::None => Ok("".to_string()),
+    }
+    .unwrap_or_default();
+    ModuleSizes {
+        stat: info.raw.len() as u64,
+        parsed: code.len() as u64,
+        gzip: gzip_size(code.as_bytes()),
+    }
+}
+
+fn minified_js(ast: &JsAst, context: &Arc<Context>) -> Result<String> {
+    let mut ast = ast.clone();
+    minify_js(&mut ast, context)?;
+    let mut buf = vec![];
+    let cm = context.meta.script.cm.clone();
+    {
+        let mut emitter = Emitter {
+            cfg: JsCodegenConfig::default()
+                .with_minify(true)
+                .with_target(context.config.output.es_version)
+                .with_omit_last_semi(true),
+            cm: cm.clone(),
+            comments: None,
+            wr: Box::new(JsWriter::new(cm, "\n", &mut buf, None)),
+        };
+        emitter.emit_module(&ast.ast)?;
+    }
+    Ok(String::from_utf8(buf)?)
+}
+
+fn minified_css(ast: &CssAst, context: &Arc<Context>) -> Result<String> {
+    let mut stylesheet = ast.ast.clone();
+    minify_css(&mut stylesheet, context)?;
+    let mut code = String::new();
+    let writer = BasicCssWriter::new(&mut code, None, BasicCssWriterConfig::default());
+    let mut gen = CodeGenerator::new(writer, CodegenConfig { minify: true });
+    gen.emit(&stylesheet)?;
+    Ok(code)
+}
+
+pub(crate) fn gzip_size(content: &[u8]) -> u64 {
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    if encoder.write_all(content).is_err() {
+        return 0;
+    }
+    encoder.finish().map(|gz| gz.len() as u64).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sizes(stat: u64) -> ModuleSizes {
+        ModuleSizes {
+            stat,
+            parsed: stat / 2,
+            gzip: stat / 4,
+        }
+    }
+
+    #[test]
+    fn test_build_chunk_tree() {
+        let tree = build_chunk_tree(
+            "index.js".to_string(),
+            vec![
+                ("./src/index.ts".to_string(), sizes(400)),
+                ("./node_modules/@scope/pkg/a.js".to_string(), sizes(40)),
+                ("./node_modules/@scope/pkg/b.js".to_string(), sizes(80)),
+            ],
+        );
+        assert!(tree.is_asset);
+        assert_eq!(tree.stat_size, 520);
+        assert_eq!(tree.gzip_size, 130);
+        let labels = tree
+            .groups
+            .iter()
+            .map(|g| g.label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(labels, vec!["node_modules/@scope/pkg", "src"]);
+        let pkg = &tree.groups[0];
+        assert_eq!(pkg.path, "./node_modules/@scope/pkg");
+        assert_eq!(pkg.stat_size, 120);
+        assert_eq!(pkg.groups.len(), 2);
+        assert_eq!(pkg.groups[1].path, "./node_modules/@scope/pkg/b.js");
+    }
+
+    #[test]
+    fn test_gzip_size() {
+        let content = "a".repeat(10000);
+        let size = gzip_size(content.as_bytes());
+        assert!(size > 0 && size < 100);
+    }
 }
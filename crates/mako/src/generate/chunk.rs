@@ -9,6 +9,7 @@ use crate::ast::file::parse_path;
 use crate::module::ModuleId;
 use crate::module_graph::ModuleGraph;
 use crate::utils::url_safe_base64_encode;
+use crate::visitors::worker_module::SERVICE_WORKER_QUERY;
 
 // TODO: Refact ChunkId
 /*
@@ -78,6 +79,14 @@ impl Chunk {
             ChunkType::Runtime => "runtime.js".into(),
             // foo/bar.tsx -> bar.js
             ChunkType::Entry(_, name, _) => format!("{}.js", name),
+            // foo/sw.ts -> sw.js
+            ChunkType::Worker(_) if self.is_service_worker() => {
+                let (path, ..) = parse_path(&self.id.id).unwrap();
+                let name = Path::new(&path)
+                    .file_stem()
+                    .map_or(path.clone(), |stem| stem.to_string_lossy().to_string());
+                format!("{}.js", name)
+            }
             // foo/bar.tsx -> foo_bar_tsx-async.js
             ChunkType::Async | ChunkType::Sync | ChunkType::Worker(_) => {
                 let (path, search, ..) = parse_path(&self.id.id).unwrap();
@@ -117,6 +126,16 @@ impl Chunk {
         }
     }
 
+    /// The service workers are registered with the stable urls and can't load the chunks after
+    /// they're installed, so they're emitted as the single unhashed files with the modules of
+    /// the dynamic imports bundled
+    pub fn is_service_worker(&self) -> bool {
+        matches!(
+            &self.chunk_type,
+            ChunkType::Worker(module_id) if module_id.id.ends_with(SERVICE_WORKER_QUERY)
+        )
+    }
+
     pub fn add_module(&mut self, module_id: ModuleId) {
         self.modules.insert(module_id);
     }
@@ -173,4 +192,27 @@ mod tests {
         let chunk = Chunk::new(ModuleId::new("foo/bar.tsx".into()), ChunkType::Runtime);
         assert_eq!(chunk.filename(), "runtime.js");
     }
+
+    #[test]
+    fn test_service_worker_filename() {
+        let worker = ModuleId::new("/src/sw.ts?asworker".into());
+        let chunk = Chunk::new(worker.clone(), ChunkType::Worker(worker));
+        assert!(!chunk.is_service_worker());
+        assert!(chunk.filename().ends_with("-worker.js"));
+
+        let service_worker = ModuleId::new("/src/sw.ts?asserviceworker".into());
+        let chunk = Chunk::new(
+            service_worker.clone(),
+            ChunkType::Worker(service_worker.clone()),
+        );
+        assert!(chunk.is_service_worker());
+        assert_eq!(chunk.filename(), "sw.js");
+
+        // named by the magic comment
+        let chunk = Chunk::new(
+            ModuleId::new("my-sw".into()),
+            ChunkType::Worker(service_worker),
+        );
+        assert_eq!(chunk.filename(), "my-sw.js");
+    }
 }
@@ -13,12 +13,12 @@ use swc_core::css::ast::Stylesheet;
 use crate::compiler::Context;
 use crate::config::Mode;
 use crate::generate::chunk::Chunk;
-pub use crate::generate::chunk_pot::util::CHUNK_FILE_NAME_HASH_LENGTH;
 use crate::generate::chunk_pot::util::{hash_hashmap, hash_vec};
 use crate::generate::generate_chunks::ChunkFile;
 use crate::module::{Module, ModuleAst, ModuleId};
 use crate::module_graph::ModuleGraph;
 use crate::ternary;
+use crate::visitors::css_layers::css_layer_names;
 
 pub struct ChunkPot<'a> {
     pub chunk_id: String,
@@ -26,6 +26,8 @@ pub struct ChunkPot<'a> {
     pub module_map: HashMap<String, (&'a Module, u64)>,
     pub js_hash: u64,
     pub stylesheet: Option<CssModules<'a>>,
+    /// the chunk files imported by the entry chunk in esm format
+    pub imports: Vec<String>,
 }
 
 impl<'cp> ChunkPot<'cp> {
@@ -38,10 +40,11 @@ impl<'cp> ChunkPot<'cp> {
 
         ChunkPot {
             js_name: chunk.filename(),
-            chunk_id: chunk.id.id.clone(),
+            chunk_id: chunk.id.id.to_string(),
             module_map: js_modules.module_map,
             js_hash: js_modules.raw_hash,
             stylesheet,
+            imports: vec![],
         }
     }
 
@@ -59,18 +62,11 @@ impl<'cp> ChunkPot<'cp> {
             return Ok(files);
         }
 
+        // the normal chunk files are cached by ChunkFilesCache in watch mode
         let js_chunk_file = ternary!(
             self.use_chunk_parallel(context),
-            ternary!(
-                context.args.watch,
-                str_impl::render_normal_js_chunk,
-                str_impl::render_normal_js_chunk_no_cache
-            ),
-            ternary!(
-                context.args.watch,
-                ast_impl::render_normal_js_chunk,
-                ast_impl::render_normal_js_chunk_no_cache
-            )
+            str_impl::render_normal_js_chunk,
+            ast_impl::render_normal_js_chunk
         )(self, context)?;
 
         if js_chunk_file.content.is_empty() {
@@ -80,11 +76,7 @@ impl<'cp> ChunkPot<'cp> {
         files.push(js_chunk_file);
 
         if self.stylesheet.is_some() {
-            let css_chunk_file = ternary!(
-                context.args.watch,
-                ast_impl::render_css_chunk,
-                ast_impl::render_css_chunk_no_cache
-            )(self, chunk, context)?;
+            let css_chunk_file = ast_impl::render_css_chunk_no_cache(self, chunk, context)?;
             files.push(css_chunk_file);
         }
 
@@ -134,6 +126,15 @@ impl<'cp> ChunkPot<'cp> {
         Ok(files)
     }
 
+    /// The hash of the js and css modules of the chunk, the chunk files are rendered again only
+    /// when it changes in watch mode
+    pub fn modules_hash(&self) -> u64 {
+        hash_vec(&[
+            self.js_hash,
+            self.stylesheet.as_ref().map_or(0, |css| css.raw_hash),
+        ])
+    }
+
     fn use_chunk_parallel(&self, context: &Arc<Context>) -> bool {
         // parallel emit chunk when in watch mode
         context.config.chunk_parallel
@@ -148,7 +149,7 @@ impl<'cp> ChunkPot<'cp> {
     ) -> (JsModules<'a>, Option<CssModules<'a>>) {
         crate::mako_profile_function!(module_ids.len().to_string());
         let mut module_map: HashMap<String, (&Module, u64)> = Default::default();
-        let mut merged_css_modules: Vec<(String, &Stylesheet)> = vec![];
+        let mut merged_css_modules: Vec<(&ModuleId, &Stylesheet)> = vec![];
 
         let mut module_raw_hash_map: HashMap<String, u64> = Default::default();
         let mut css_raw_hashes = vec![];
@@ -164,15 +165,27 @@ impl<'cp> ChunkPot<'cp> {
             let ast = &module_info.ast;
 
             if let ModuleAst::Script(_) = ast {
-                module_raw_hash_map.insert(module.id.id.clone(), module_info.raw_hash);
+                module_raw_hash_map.insert(module.id.id.to_string(), module_info.raw_hash);
                 module_map.insert(module.id.generate(context), (module, module_info.raw_hash));
             }
 
             if let ModuleAst::Css(ast) = ast {
                 // not add empty css to chunk
                 if !ast.ast.rules.is_empty() {
-                    merged_css_modules.push((module.id.id.clone(), &ast.ast));
+                    merged_css_modules.push((&module.id, &ast.ast));
+                    css_raw_hashes.push(module_info.raw_hash);
+                }
+            }
+
+            // the modules whose asts are dropped are restored before their chunks are rendered,
+            // their raw hashes keep the cached chunk files valid
+            if matches!(ast, ModuleAst::None)
+                && context.dropped_asts.lock().unwrap().contains(module_id)
+            {
+                if module_info.file.is_css() {
                     css_raw_hashes.push(module_info.raw_hash);
+                } else {
+                    module_raw_hash_map.insert(module.id.id.to_string(), module_info.raw_hash);
                 }
             }
         });
@@ -182,6 +195,13 @@ impl<'cp> ChunkPot<'cp> {
         if !merged_css_modules.is_empty() {
             crate::mako_profile_scope!("iter_chunk_css_modules");
 
+            let layer_names = css_layer_names(
+                &merged_css_modules
+                    .iter()
+                    .map(|(id, _)| *id)
+                    .collect::<Vec<_>>(),
+                module_graph,
+            );
             let mut stylesheets = vec![];
 
             for (_, ast) in merged_css_modules {
@@ -197,6 +217,7 @@ impl<'cp> ChunkPot<'cp> {
                 },
                 Some(CssModules {
                     stylesheets,
+                    layer_names,
                     raw_hash: css_raw_hash,
                 }),
             )
@@ -219,6 +240,7 @@ struct JsModules<'a> {
 
 pub struct CssModules<'a> {
     stylesheets: Vec<&'a Stylesheet>,
+    layer_names: Vec<String>,
     raw_hash: u64,
 }
 
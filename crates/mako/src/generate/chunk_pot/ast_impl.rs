@@ -11,8 +11,8 @@ use swc_core::css::ast::Stylesheet;
 use swc_core::css::codegen::writer::basic::{BasicCssWriter, BasicCssWriterConfig};
 use swc_core::css::codegen::{CodeGenerator, CodegenConfig, Emit};
 use swc_core::ecma::ast::{
-    BlockStmt, FnExpr, Function, KeyValueProp, Lit, Module as SwcModule, Number, ObjectLit, Prop,
-    PropOrSpread, Stmt, UnaryExpr, UnaryOp, VarDeclKind,
+    BlockStmt, FnExpr, Function, KeyValueProp, Lit, Module as SwcModule, ModuleItem, Number,
+    ObjectLit, Prop, PropOrSpread, Stmt, UnaryExpr, UnaryOp, VarDeclKind,
 };
 use swc_core::ecma::utils::{quote_ident, quote_str, ExprFactory};
 
@@ -20,9 +20,10 @@ use crate::ast::js_ast::JsAst;
 use crate::ast::sourcemap::{build_source_map, merge_source_map};
 use crate::compiler::Context;
 use crate::config::Mode;
+use crate::features::node::Node;
 use crate::generate::chunk::{Chunk, ChunkType};
 use crate::generate::chunk_pot::util::{
-    file_content_hash, pot_to_chunk_module, pot_to_module_object, runtime_code,
+    esm_entry_imports, file_content_hash, pot_to_chunk_module, pot_to_module_object, runtime_code,
 };
 use crate::generate::chunk_pot::{get_css_chunk_filename, util, ChunkPot};
 use crate::generate::generate_chunks::{ChunkFile, ChunkFileType};
@@ -64,7 +65,7 @@ pub(crate) fn render_css_chunk(
 
     {
         crate::mako_profile_scope!("transform_css_generate");
-        transform_css_generate(&mut stylesheet, context);
+        transform_css_generate(&mut stylesheet, ast.layer_names.clone(), context);
     }
 
     if context.config.minify && matches!(context.config.mode, Mode::Production) {
@@ -95,8 +96,8 @@ pub(crate) fn render_css_chunk(
                     if let Some(info) = module.info.as_ref()
                         && matches!(info.ast, crate::module::ModuleAst::Css(_))
                     {
-                        let relative_source = diff_paths(&module_id.id, &context.root)
-                            .map_or(module_id.id.clone(), |p| p.to_string_lossy().to_string());
+                        let relative_source = diff_paths(module_id.as_str(), &context.root)
+                            .map_or(module_id.id.to_string(), |p| p.to_string_lossy().to_string());
 
                         chain_map.insert(
                             relative_source,
@@ -120,7 +121,7 @@ pub(crate) fn render_css_chunk(
     };
 
     let css_hash = if context.config.hash {
-        Some(file_content_hash(&css_code))
+        Some(file_content_hash(&css_code, &context.config.output))
     } else {
         None
     };
@@ -136,25 +137,22 @@ pub(crate) fn render_css_chunk(
     })
 }
 
-#[cached(
-    result = true,
-    type = "SizedCache<String , ChunkFile>",
-    create = "{ SizedCache::with_size(500) }",
-    key = "String",
-    convert = r#"{format!("{}.{:x}", chunk_pot.chunk_id, chunk_pot.js_hash)}"#
-)]
 pub(crate) fn render_normal_js_chunk(
     chunk_pot: &ChunkPot,
     context: &Arc<Context>,
 ) -> Result<ChunkFile> {
     crate::mako_profile_function!();
 
-    let module = pot_to_chunk_module(
+    let mut module = pot_to_chunk_module(
         chunk_pot,
         context.config.output.chunk_loading_global.clone(),
         context,
     )?;
 
+    if Node::is_esm_output(&context.config) {
+        module.body.splice(0..0, node_esm_require_shim(context)?);
+    }
+
     let mut ast = GLOBALS.set(&context.meta.script.globals, || JsAst {
         ast: module,
         unresolved_mark: Mark::new(),
@@ -170,7 +168,7 @@ pub(crate) fn render_normal_js_chunk(
     let (buf, source_map) = util::render_module_js(&ast.ast, context)?;
 
     let hash = if context.config.hash {
-        Some(file_content_hash(&buf))
+        Some(file_content_hash(&buf, &context.config.output))
     } else {
         None
     };
@@ -288,7 +286,7 @@ fn render_entry_chunk_js_without_full_hash(
     let mut ast = {
         crate::mako_profile_scope!("parse_runtime_entry");
 
-        let runtime_content = runtime_code(context)?;
+        let runtime_content = runtime_code(context, chunk)?;
 
         JsAst::build(
             "_mako_internal/runtime_entry.js",
@@ -316,6 +314,17 @@ fn render_entry_chunk_js_without_full_hash(
             .splice(0..0, stmts.into_iter().map(|s| s.into()));
 
         ast.ast = wrap_in_iife(ast.ast);
+
+        // e.g. import __mako_chunk_0__ from "./vendors.js";
+        if let Some(esm_imports) = esm_entry_imports(pot, context) {
+            let esm_imports =
+                JsAst::build("_mako_internal/esm_imports.js", &esm_imports, context.clone())?;
+            ast.ast.body.splice(0..0, esm_imports.ast.body);
+        }
+
+        if Node::is_esm_output(&context.config) {
+            ast.ast.body.splice(0..0, node_esm_require_shim(context)?);
+        }
     }
 
     if context.config.minify && matches!(context.config.mode, Mode::Production) {
@@ -326,7 +335,7 @@ fn render_entry_chunk_js_without_full_hash(
 
     let hash = if context.config.hash {
         crate::mako_profile_scope!("entryHash");
-        Some(file_content_hash(&buf))
+        Some(file_content_hash(&buf, &context.config.output))
     } else {
         None
     };
@@ -382,6 +391,16 @@ fn to_object_lit(value: &HashMap<String, String>) -> ObjectLit {
     }
 }
 
+// e.g. var require = createRequire(import.meta.url);
+fn node_esm_require_shim(context: &Arc<Context>) -> Result<Vec<ModuleItem>> {
+    let shim = JsAst::build(
+        "_mako_internal/node_esm_require.js",
+        Node::esm_require_shim(),
+        context.clone(),
+    )?;
+    Ok(shim.ast.body)
+}
+
 fn wrap_in_iife(module: SwcModule) -> SwcModule {
     let stmts = module
         .body
@@ -6,14 +6,18 @@ use cached::proc_macro::cached;
 use cached::SizedCache;
 use rayon::prelude::*;
 use swc_core::base::sourcemap;
+use swc_core::common::comments::Comments;
 use swc_core::ecma::codegen::text_writer::JsWriter;
 use swc_core::ecma::codegen::{Config as JsCodegenConfig, Emitter};
 
+use crate::ast::comments::EmitComments;
 use crate::ast::sourcemap::{build_source_map, RawSourceMap};
 use crate::compiler::Context;
+use crate::config::OutputFormat;
+use crate::features::node::Node;
 use crate::generate::chunk::{Chunk, ChunkType};
 use crate::generate::chunk_pot::ast_impl::{render_css_chunk, render_css_chunk_no_cache};
-use crate::generate::chunk_pot::util::runtime_code;
+use crate::generate::chunk_pot::util::{esm_entry_imports, runtime_code};
 use crate::generate::chunk_pot::ChunkPot;
 use crate::generate::generate_chunks::{ChunkFile, ChunkFileType};
 use crate::module::{generate_module_id, Module, ModuleAst};
@@ -76,9 +80,15 @@ pub(super) fn render_entry_js_chunk(
     lines.push(init_install_css_chunk);
     lines.push(format!("var e = \"{}\";", chunk_root_module_id));
 
-    let runtime_content = runtime_code(context)?.replace("_%full_hash%_", &hmr_hash.to_string());
+    let runtime_content =
+        runtime_code(context, chunk)?.replace("_%full_hash%_", &hmr_hash.to_string());
 
-    let entry_prefix_code = "!(function(){\n";
+    // e.g. import __mako_chunk_0__ from "./vendors.js";
+    let entry_prefix_code = node_esm_require_shim(context)
+        .into_iter()
+        .chain(esm_entry_imports(pot, context))
+        .chain(["!(function(){\n".to_string()])
+        .collect::<String>();
 
     let (chunk_content, chunk_raw_sourcemap) =
         pot_to_chunk_module_object_string(pot, context, entry_prefix_code.lines().count() as u32)?;
@@ -106,13 +116,6 @@ pub(super) fn render_entry_js_chunk(
     })
 }
 
-#[cached(
-    result = true,
-    type = "SizedCache<String , ChunkFile>",
-    create = "{ SizedCache::with_size(500) }",
-    key = "String",
-    convert = r#"{format!("{}.{:x}", chunk_pot.chunk_id, chunk_pot.js_hash)}"#
-)]
 pub(super) fn render_normal_js_chunk(
     chunk_pot: &ChunkPot,
     context: &Arc<Context>,
@@ -123,11 +126,28 @@ pub(super) fn render_normal_js_chunk(
         // to avoid ' or " been included in chunk_loading_global
         let safe_prop = serde_json::to_string(&context.config.output.chunk_loading_global).unwrap();
 
-        let chunk_prefix_code = format!(
-            r#"((typeof globalThis !== 'undefined' ? globalThis : self)[{}] = (typeof globalThis !== 'undefined' ? globalThis : self)[{}] || []).push([
+        // the es module chunks export the chunk instead of pushing it to the global
+        let (chunk_prefix_code, chunk_suffix_code) = if context.config.output.format
+            == OutputFormat::Esm
+        {
+            let chunk_prefix_code = format!(
+                r#"{}export default [
+        ['{}'],"#,
+                node_esm_require_shim(context).unwrap_or_default(),
+                pot.chunk_id,
+            );
+            (chunk_prefix_code, "];")
+        } else {
+            let chunk_prefix_code = format!(
+                r#"{}((typeof globalThis !== 'undefined' ? globalThis : self)[{}] = (typeof globalThis !== 'undefined' ? globalThis : self)[{}] || []).push([
         ['{}'],"#,
-            safe_prop, safe_prop, pot.chunk_id,
-        );
+                node_esm_require_shim(context).unwrap_or_default(),
+                safe_prop,
+                safe_prop,
+                pot.chunk_id,
+            );
+            (chunk_prefix_code, "]);")
+        };
 
         let (chunk_content, chunk_raw_sourcemap) = pot_to_chunk_module_object_string(
             pot,
@@ -139,7 +159,10 @@ pub(super) fn render_normal_js_chunk(
         sourcemap::SourceMap::from(chunk_raw_sourcemap).to_writer(&mut source_map_buf)?;
 
         (
-            format!("{}\n{}]);", chunk_prefix_code, chunk_content),
+            format!(
+                "{}\n{}{}",
+                chunk_prefix_code, chunk_content, chunk_suffix_code
+            ),
             source_map_buf,
         )
     };
@@ -155,6 +178,11 @@ pub(super) fn render_normal_js_chunk(
     })
 }
 
+// e.g. var require = createRequire(import.meta.url);
+fn node_esm_require_shim(context: &Arc<Context>) -> Option<String> {
+    Node::is_esm_output(&context.config).then(|| Node::esm_require_shim().to_string())
+}
+
 type EmittedWithMapping = (String, Option<RawSourceMap>);
 
 #[cached(
@@ -175,6 +203,10 @@ fn emit_module_with_mapping(
             let cm = context.meta.script.cm.clone();
             let comments = context.meta.script.origin_comments.read().unwrap();
             let swc_comments = comments.get_swc_comments();
+            let emit_comments = EmitComments::new(
+                swc_comments,
+                context.config.output.comments_or_default(false),
+            );
 
             let mut buf = vec![];
             let mut source_mappings = Vec::new();
@@ -182,10 +214,10 @@ fn emit_module_with_mapping(
                 cfg: JsCodegenConfig::default()
                     .with_minify(false)
                     .with_target(context.config.output.es_version)
-                    .with_ascii_only(false)
+                    .with_ascii_only(context.config.output.ascii_only_or_default(false))
                     .with_omit_last_semi(true),
                 cm: cm.clone(),
-                comments: Some(swc_comments),
+                comments: emit_comments.as_ref().map(|c| c as &dyn Comments),
                 wr: Box::new(JsWriter::new(
                     cm.clone(),
                     "\n",
@@ -326,7 +358,10 @@ mod tests {
                 minify: true,
                 ..Default::default()
             },
-            args: Args { watch: true },
+            args: Args {
+                watch: true,
+                ..Default::default()
+            },
             ..Default::default()
         });
 
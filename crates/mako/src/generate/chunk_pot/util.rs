@@ -1,17 +1,19 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::hash::{Hash, Hasher};
+use std::path::Path;
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
-use md5;
+use regex::Regex;
 use sailfish::TemplateOnce;
 use swc_core::base::try_with_handler;
 use swc_core::common::comments::{Comment, CommentKind, Comments};
 use swc_core::common::errors::HANDLER;
 use swc_core::common::{Span, DUMMY_SP, GLOBALS};
 use swc_core::ecma::ast::{
-    ArrayLit, AssignOp, BinaryOp, BlockStmt, CondExpr, Expr, ExprOrSpread, FnExpr, Function,
-    KeyValueProp, Module as SwcModule, ObjectLit, Prop, PropOrSpread, UnaryExpr, UnaryOp,
+    ArrayLit, AssignOp, BinaryOp, BlockStmt, CondExpr, ExportDefaultExpr, Expr, ExprOrSpread,
+    FnExpr, Function, KeyValueProp, Module as SwcModule, ModuleDecl, ModuleItem, ObjectLit, Prop,
+    PropOrSpread, UnaryExpr, UnaryOp,
 };
 use swc_core::ecma::atoms::js_word;
 use swc_core::ecma::codegen::text_writer::JsWriter;
@@ -19,13 +21,17 @@ use swc_core::ecma::codegen::{Config as JsCodegenConfig, Emitter};
 use swc_core::ecma::utils::{quote_ident, quote_str, ExprFactory};
 use twox_hash::XxHash64;
 
+use crate::ast::comments::EmitComments;
 use crate::ast::sourcemap::build_source_map_to_buf;
 use crate::compiler::Context;
-use crate::config::Mode;
+use crate::config::{Mode, OutputConfig, OutputFormat, WorkerChunkLoading};
+use crate::generate::chunk::{Chunk, ChunkType};
+use crate::generate::chunk_graph::ChunkGraph;
 use crate::generate::chunk_pot::ChunkPot;
 use crate::generate::runtime::AppRuntimeTemplate;
-use crate::module::{relative_to_root, Module, ModuleAst};
+use crate::module::{relative_to_root, Module, ModuleAst, ResolveType};
 use crate::utils::get_pkg_name;
+use crate::utils::hash::content_hash;
 
 pub(crate) fn render_module_js(
     ast: &SwcModule,
@@ -39,19 +45,19 @@ pub(crate) fn render_module_js(
     let with_minify = context.config.minify && matches!(context.config.mode, Mode::Production);
     let comments = context.meta.script.origin_comments.read().unwrap();
     let swc_comments = comments.get_swc_comments();
+    let emit_comments = EmitComments::new(
+        swc_comments,
+        context.config.output.comments_or_default(with_minify),
+    );
 
     let mut emitter = Emitter {
         cfg: JsCodegenConfig::default()
             .with_minify(with_minify)
             .with_target(context.config.output.es_version)
-            .with_ascii_only(with_minify)
+            .with_ascii_only(context.config.output.ascii_only_or_default(with_minify))
             .with_omit_last_semi(true),
         cm: cm.clone(),
-        comments: if with_minify {
-            None
-        } else {
-            Some(swc_comments)
-        },
+        comments: emit_comments.as_ref().map(|c| c as &dyn Comments),
         wr: Box::new(JsWriter::new(cm, "\n", &mut buf, Some(&mut source_map_buf))),
     };
     emitter.emit_module(ast)?;
@@ -94,17 +100,36 @@ pub(crate) fn empty_module_fn_expr() -> FnExpr {
     }
 }
 
-pub(crate) fn runtime_code(context: &Arc<Context>) -> Result<String> {
+pub(crate) fn runtime_code(context: &Arc<Context>, chunk: &Chunk) -> Result<String> {
     let umd = context.config.umd.clone();
+    let is_worker = matches!(chunk.chunk_type, ChunkType::Worker(_));
     let chunk_graph = context.chunk_graph.read().unwrap();
     let has_dynamic_chunks = chunk_graph.get_all_chunks().len() > 1;
     let has_hmr = context.args.watch;
+    let chunk_load_retry = context.config.output.chunk_load_retry.as_ref();
+    let style_injection = context.config.style_injection.as_ref();
+    let chunk_hints = if has_dynamic_chunks {
+        chunk_hints(context, &chunk_graph)?
+    } else {
+        Default::default()
+    };
+    let has_chunk_hints = has_dynamic_chunks
+        && (context.config.chunk_hints.is_some()
+            || !chunk_hints.prefetch.is_empty()
+            || !chunk_hints.preload.is_empty());
     let app_runtime = AppRuntimeTemplate {
         has_dynamic_chunks,
         has_hmr,
         umd,
         is_browser: matches!(context.config.platform, crate::config::Platform::Browser),
+        output_root: serde_json::to_string(&output_root(&chunk.filename())).unwrap(),
+        is_worker,
+        is_service_worker: chunk.is_service_worker(),
+        worker_import_scripts: is_worker
+            && context.config.output.worker_chunk_loading_or_default()
+                == WorkerChunkLoading::ImportScripts,
         cjs: context.config.cjs,
+        esm: context.config.output.format == OutputFormat::Esm,
         chunk_loading_global: serde_json::to_string(&context.config.output.chunk_loading_global)
             .unwrap(),
         cross_origin_loading: context
@@ -120,6 +145,30 @@ pub(crate) fn runtime_code(context: &Arc<Context>) -> Result<String> {
             .as_ref()
             .map_or(false, |o| o.concatenate_modules.unwrap_or(false)),
         global_module_registry: context.config.output.global_module_registry,
+        chunk_load_retry: chunk_load_retry.is_some_and(|retry| retry.retries > 0),
+        chunk_load_retries: chunk_load_retry.map_or(0, |retry| retry.retries),
+        chunk_load_retry_delay: chunk_load_retry.map_or(0, |retry| retry.delay),
+        chunk_load_fallback_public_paths: serde_json::to_string(
+            &chunk_load_retry.map_or(vec![], |retry| retry.fallback_public_paths.clone()),
+        )
+        .unwrap(),
+        chunk_hints: has_chunk_hints,
+        chunk_prefetch_map: serde_json::to_string(&chunk_hints.prefetch).unwrap(),
+        chunk_preload_map: serde_json::to_string(&chunk_hints.preload).unwrap(),
+        runtime_global: context
+            .config
+            .output
+            .runtime_global
+            .as_ref()
+            .map(|name| serde_json::to_string(name).unwrap()),
+        style_injection: style_injection.is_some() || context.config.shadow_dom,
+        style_insert: style_injection
+            .and_then(|style_injection| style_injection.insert.as_ref())
+            .map(|insert| serde_json::to_string(insert).unwrap()),
+        style_constructable: style_injection
+            .is_some_and(|style_injection| style_injection.constructable),
+        shadow_dom: context.config.shadow_dom,
+        micro_frontend: context.config.micro_frontend.is_some(),
     };
     let app_runtime = app_runtime.render_once()?;
     let app_runtime = app_runtime.replace(
@@ -129,6 +178,107 @@ pub(crate) fn runtime_code(context: &Arc<Context>) -> Result<String> {
     Ok(app_runtime)
 }
 
+/// The imports of the shared chunks of the esm entry chunk, which export the chunks to install
+/// instead of pushing them to the global, the runtime installs them from `__mako_chunks__`, e.g.
+/// import __mako_chunk_0__ from "./vendors.js";
+/// var __mako_chunks__ = [__mako_chunk_0__];
+pub(crate) fn esm_entry_imports(pot: &ChunkPot, context: &Arc<Context>) -> Option<String> {
+    if context.config.output.format != OutputFormat::Esm {
+        return None;
+    }
+    let local = |i: usize| format!("__mako_chunk_{}__", i);
+    let imports = pot
+        .imports
+        .iter()
+        .enumerate()
+        .map(|(i, js_filename)| format!("import {} from \"./{}\";\n", local(i), js_filename))
+        .collect::<String>();
+    let locals = (0..pot.imports.len())
+        .map(local)
+        .collect::<Vec<_>>()
+        .join(", ");
+    Some(format!("{}var __mako_chunks__ = [{}];\n", imports, locals))
+}
+
+/// The relative path from the dir of the file to the output dir, e.g. `../` for `pages/index.js`
+fn output_root(filename: &str) -> String {
+    let depth = Path::new(filename)
+        .parent()
+        .map_or(0, |dir| dir.components().count());
+    "../".repeat(depth)
+}
+
+#[derive(Default)]
+struct ChunkHints {
+    prefetch: BTreeMap<String, Vec<String>>,
+    preload: BTreeMap<String, Vec<String>>,
+}
+
+/// The async chunks to prefetch or preload when the modules importing them are executed, keyed
+/// by the ids of the importers, the chunks are marked by the magic comments or config.chunkHints
+fn chunk_hints(context: &Arc<Context>, chunk_graph: &ChunkGraph) -> Result<ChunkHints> {
+    let compile = |patterns: Option<&Vec<String>>| -> Result<Vec<Regex>> {
+        patterns
+            .map_or(&vec![], |patterns| patterns)
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .map_err(|e| anyhow!("Invalid chunkHints pattern {}: {}", pattern, e))
+            })
+            .collect()
+    };
+    let config = context.config.chunk_hints.as_ref();
+    let prefetch_patterns = compile(config.map(|c| &c.prefetch))?;
+    let preload_patterns = compile(config.map(|c| &c.preload))?;
+    let matches = |patterns: &[Regex], path: &str| patterns.iter().any(|re| re.is_match(path));
+
+    let module_graph = context.module_graph.read().unwrap();
+    let mut hints = ChunkHints::default();
+    for module_id in module_graph.get_module_ids() {
+        for (dep_module_id, dep) in module_graph.get_dependencies(&module_id) {
+            let ResolveType::DynamicImport(options) = &dep.resolve_type else {
+                continue;
+            };
+            let path: &str = &dep_module_id.id;
+            let preload = options.preload || matches(&preload_patterns, path);
+            let prefetch = options.prefetch || matches(&prefetch_patterns, path);
+            if !preload && !prefetch {
+                continue;
+            }
+            // the same as the chunks loaded by the dynamic import
+            let chunk_id = options.chunk_id(dep_module_id, context);
+            let Some(chunk) = chunk_graph.chunk(&chunk_id.into()) else {
+                continue;
+            };
+            let chunk_ids = [
+                chunk_graph.sync_dependencies_chunk(&chunk.id),
+                vec![chunk.id.clone()],
+            ]
+            .concat()
+            .into_iter()
+            .filter(|chunk_id| {
+                chunk_graph
+                    .chunk(chunk_id)
+                    .is_some_and(|c| !c.modules.is_empty())
+            })
+            .map(|chunk_id| chunk_id.id);
+            // preload wins if the chunk is marked by both
+            let map = if preload {
+                &mut hints.preload
+            } else {
+                &mut hints.prefetch
+            };
+            let ids = map.entry(module_id.generate(context)).or_default();
+            chunk_ids.for_each(|chunk_id| {
+                if !ids.contains(&chunk_id) {
+                    ids.push(chunk_id);
+                }
+            });
+        }
+    }
+    Ok(hints)
+}
+
 pub(crate) fn hash_hashmap<K, V>(map: &HashMap<K, V>) -> u64
 where
     K: Hash + Eq + Ord,
@@ -250,20 +400,27 @@ pub(crate) fn pot_to_chunk_module(
         .make_assign_to(AssignOp::Assign, chunk_global_expr.clone().into())
         .wrap_with_paren()
         .make_member(quote_ident!("push"));
-    let chunk_register_stmt = chunk_global_obj
-        .as_call(
-            DUMMY_SP,
-            // [[ "module id"], { module object }]
-            vec![to_array_lit(vec![
-                to_array_lit(vec![quote_str!(pot.chunk_id.clone()).as_arg()]).as_arg(),
-                module_object.as_arg(),
-            ])
-            .as_arg()],
-        )
-        .into_stmt();
+    // [[ "module id"], { module object }]
+    let chunk_lit = to_array_lit(vec![
+        to_array_lit(vec![quote_str!(pot.chunk_id.clone()).as_arg()]).as_arg(),
+        module_object.as_arg(),
+    ]);
+    // the es module chunks export the chunk instead of pushing it to the global
+    let chunk_register_item: ModuleItem = if context.config.output.format == OutputFormat::Esm {
+        ModuleDecl::ExportDefaultExpr(ExportDefaultExpr {
+            span: DUMMY_SP,
+            expr: Box::new(chunk_lit.into()),
+        })
+        .into()
+    } else {
+        chunk_global_obj
+            .as_call(DUMMY_SP, vec![chunk_lit.as_arg()])
+            .into_stmt()
+            .into()
+    };
 
     Ok(SwcModule {
-        body: vec![chunk_register_stmt.into()],
+        body: vec![chunk_register_item],
         shebang: None,
         span: DUMMY_SP,
     })
@@ -328,11 +485,51 @@ fn to_module_fn_expr(module: &Module) -> Result<FnExpr> {
     }
 }
 
-pub const CHUNK_FILE_NAME_HASH_LENGTH: usize = 8;
+pub fn file_content_hash<T: AsRef<[u8]>>(content: T, output: &OutputConfig) -> String {
+    content_hash(content, output.hash_function, output.hash_length())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{chunk_hints, output_root};
+    use crate::module::ModuleId;
+    use crate::utils::test_helper::setup_compiler;
+
+    #[test]
+    fn test_chunk_hints() {
+        let compiler = setup_compiler("test/build/chunk-hints", false);
+        compiler.compile().unwrap();
+        let context = &compiler.context;
+        let chunk_graph = context.chunk_graph.read().unwrap();
+        let hints = chunk_hints(context, &chunk_graph).unwrap();
+
+        let index = ModuleId::from(context.root.join("index.ts")).generate(context);
+        let chunk_id = |file: &str| {
+            let module_id = ModuleId::from(context.root.join(file));
+            chunk_graph
+                .get_chunk_for_module(&module_id)
+                .unwrap()
+                .id
+                .id
+                .to_string()
+        };
+        // a.ts is marked by the magic comment, b.ts by config.chunkHints and c.ts is not hinted
+        assert_eq!(
+            hints.prefetch,
+            BTreeMap::from([(index.clone(), vec![chunk_id("a.ts")])])
+        );
+        assert_eq!(
+            hints.preload,
+            BTreeMap::from([(index, vec![chunk_id("b.ts")])])
+        );
+    }
 
-pub fn file_content_hash<T: AsRef<[u8]>>(content: T) -> String {
-    let digest = md5::compute(content);
-    let mut hash = format!("{:x}", digest);
-    hash.truncate(CHUNK_FILE_NAME_HASH_LENGTH);
-    hash
+    #[test]
+    fn test_output_root() {
+        assert_eq!(output_root("index.js"), "");
+        assert_eq!(output_root("pages/index.js"), "../");
+        assert_eq!(output_root("pages/home/index.js"), "../../");
+    }
 }
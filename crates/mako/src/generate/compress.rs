@@ -0,0 +1,109 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::Result;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::config::CompressConfig;
+use crate::utils::thread_pool;
+
+// same as the defaults of webpack's compression-webpack-plugin
+const BROTLI_BUFFER_SIZE: usize = 4096;
+const BROTLI_QUALITY: u32 = 11;
+const BROTLI_LG_WINDOW_SIZE: u32 = 22;
+
+/// The `.gz` and/or `.br` siblings of the file, e.g. `("index.js.gz", ..)`, the two encoders
+/// run in parallel, the files smaller than the threshold are not compressed
+pub fn compress(
+    file_name: &str,
+    content: &[u8],
+    config: &CompressConfig,
+) -> Result<Vec<(String, Vec<u8>)>> {
+    if content.len() < config.threshold {
+        return Ok(vec![]);
+    }
+
+    let (gzip, brotli) = thread_pool::join(
+        || config.gzip.then(|| gzip_compress(content)).transpose(),
+        || config.brotli.then(|| brotli_compress(content)).transpose(),
+    );
+
+    Ok([("gz", gzip?), ("br", brotli?)]
+        .into_iter()
+        .filter_map(|(suffix, compressed)| {
+            compressed.map(|compressed| (format!("{}.{}", file_name, suffix), compressed))
+        })
+        .collect())
+}
+
+/// Write the compressed siblings next to the emitted file
+pub fn compress_file(path: &Path, config: &CompressConfig) -> Result<()> {
+    if !path.is_file() {
+        return Ok(());
+    }
+    let content = fs::read(path)?;
+    let file_name = path.file_name().unwrap().to_string_lossy();
+    for (name, compressed) in compress(&file_name, &content, config)? {
+        fs::write(path.with_file_name(name), compressed)?;
+    }
+    Ok(())
+}
+
+fn gzip_compress(content: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(content)?;
+    Ok(encoder.finish()?)
+}
+
+fn brotli_compress(content: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = brotli::CompressorWriter::new(
+        Vec::new(),
+        BROTLI_BUFFER_SIZE,
+        BROTLI_QUALITY,
+        BROTLI_LG_WINDOW_SIZE,
+    );
+    encoder.write_all(content)?;
+    Ok(encoder.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::GzDecoder;
+
+    use super::*;
+
+    #[test]
+    fn test_compress() {
+        let config = CompressConfig {
+            gzip: true,
+            brotli: true,
+            threshold: 1024,
+        };
+        let content = "console.log('mako');".repeat(100);
+        let names = compress("index.js", content.as_bytes(), &config)
+            .unwrap()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["index.js.gz", "index.js.br"]);
+        // the small files are not compressed
+        assert!(compress("a.js", b"a", &config).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_gzip_round_trip() {
+        let content = "console.log('mako');".repeat(100);
+        let compressed = gzip_compress(content.as_bytes()).unwrap();
+        assert!(compressed.len() < content.len());
+
+        let mut decoded = String::new();
+        GzDecoder::new(compressed.as_slice())
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, content);
+    }
+}
@@ -0,0 +1,116 @@
+use std::path::Path;
+
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::config::SourceMapConfig;
+use crate::module::relative_to_root;
+
+/// The debug id of a chunk, which is derived from the content so it's stable between builds,
+/// ref: https://github.com/tc39/source-map/blob/main/proposals/debug-id.md
+pub fn debug_id(content: &[u8]) -> String {
+    let digest = md5::compute(content).0;
+    // formatted as a uuid v4
+    let mut bytes = digest;
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = bytes
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// Apply the source map config, e.g. add the debug id and strip the sources content,
+/// the absolute sources are made relative to root so the maps are the same on every machine
+pub fn process_source_map(
+    source_map: &[u8],
+    debug_id: Option<&str>,
+    root: &Path,
+    config: &SourceMapConfig,
+) -> Result<Vec<u8>> {
+    // nothing to change unless there are sources under root to be made relative
+    let has_root_sources = std::str::from_utf8(source_map).map_or(true, |source_map| {
+        source_map.contains(&*root.to_string_lossy())
+    });
+    if debug_id.is_none() && config.sources_content && !has_root_sources {
+        return Ok(source_map.to_vec());
+    }
+    let mut source_map: Value = serde_json::from_slice(source_map)?;
+    if let Value::Object(map) = &mut source_map {
+        if let Some(Value::Array(sources)) = map.get_mut("sources") {
+            for source in sources.iter_mut() {
+                if let Value::String(path) = source
+                    && Path::new(path.as_str()).is_absolute()
+                {
+                    *path = relative_to_root(path, root);
+                }
+            }
+        }
+        if let Some(debug_id) = debug_id {
+            map.insert("debugId".to_string(), Value::String(debug_id.to_string()));
+            // the legacy field read by sentry
+            map.insert("debug_id".to_string(), Value::String(debug_id.to_string()));
+        }
+        if !config.sources_content {
+            map.remove("sourcesContent");
+        }
+    }
+    Ok(serde_json::to_vec(&source_map)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_id() {
+        let id = debug_id(b"console.log(1);");
+        assert_eq!(id.len(), 36);
+        assert_eq!(&id[14..15], "4");
+        assert_eq!(id, debug_id(b"console.log(1);"));
+        assert_ne!(id, debug_id(b"console.log(2);"));
+    }
+
+    #[test]
+    fn test_process_source_map() {
+        let source_map =
+            br#"{"version":3,"sources":["a.js"],"sourcesContent":["a"],"mappings":""}"#;
+        let config = SourceMapConfig {
+            debug_ids: true,
+            sources_content: false,
+        };
+        let processed =
+            process_source_map(source_map, Some("id"), Path::new("/root"), &config).unwrap();
+        let processed: Value = serde_json::from_slice(&processed).unwrap();
+        assert_eq!(processed["debugId"], "id");
+        assert_eq!(processed["debug_id"], "id");
+        assert!(processed.get("sourcesContent").is_none());
+    }
+
+    #[test]
+    fn test_relative_sources() {
+        let source_map =
+            br#"{"version":3,"sources":["/root/src/a.js","webpack://b.js"],"mappings":""}"#;
+        let processed = process_source_map(
+            source_map,
+            None,
+            Path::new("/root"),
+            &SourceMapConfig {
+                debug_ids: false,
+                sources_content: true,
+            },
+        )
+        .unwrap();
+        let processed: Value = serde_json::from_slice(&processed).unwrap();
+        assert_eq!(processed["sources"][0], "./src/a.js");
+        assert_eq!(processed["sources"][1], "webpack://b.js");
+    }
+}
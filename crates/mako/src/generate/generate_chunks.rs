@@ -1,7 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::hash::Hasher;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use anyhow::{anyhow, Result};
 use indexmap::IndexSet;
@@ -14,12 +14,15 @@ use tracing::warn;
 use twox_hash::XxHash64;
 
 use crate::compiler::{Compiler, Context};
-use crate::generate::chunk::{Chunk, ChunkType};
+use crate::config::OutputFormat;
+use crate::generate::chunk::{Chunk, ChunkId, ChunkType};
 use crate::generate::chunk_pot::util::file_content_hash;
-use crate::generate::chunk_pot::{get_css_chunk_filename, ChunkPot, CHUNK_FILE_NAME_HASH_LENGTH};
+use crate::generate::chunk_pot::{get_css_chunk_filename, ChunkPot};
 use crate::generate::transform::transform_css_generate;
+use crate::generate::worklet::is_worklet_chunk;
 use crate::module::{ModuleAst, ModuleId};
 use crate::utils::thread_pool;
+use crate::visitors::css_layers::css_layer_names;
 
 #[derive(Clone)]
 pub enum ChunkFileType {
@@ -58,15 +61,109 @@ impl ChunkFile {
     }
 }
 
+/// The normal chunk files of the last generate in watch mode, keyed by the chunk ids with the
+/// hashes of the chunk modules, the chunks whose modules are unchanged are served from them
+/// instead of being rendered and minified again
+#[derive(Default)]
+pub struct ChunkFilesCache {
+    files: Mutex<HashMap<String, (u64, Vec<ChunkFile>)>>,
+}
+
+impl ChunkFilesCache {
+    pub fn get(&self, chunk_id: &str, modules_hash: u64) -> Option<Vec<ChunkFile>> {
+        self.files
+            .lock()
+            .unwrap()
+            .get(chunk_id)
+            .filter(|(hash, _)| *hash == modules_hash)
+            .map(|(_, chunk_files)| chunk_files.clone())
+    }
+
+    pub fn contains(&self, chunk_id: &str, modules_hash: u64) -> bool {
+        self.files
+            .lock()
+            .unwrap()
+            .get(chunk_id)
+            .is_some_and(|(hash, _)| *hash == modules_hash)
+    }
+
+    pub fn insert(&self, chunk_id: String, modules_hash: u64, chunk_files: Vec<ChunkFile>) {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(chunk_id, (modules_hash, chunk_files));
+    }
+
+    pub fn size(&self) -> usize {
+        self.files
+            .lock()
+            .unwrap()
+            .values()
+            .flat_map(|(_, chunk_files)| chunk_files)
+            .map(|chunk_file| {
+                chunk_file.content.len() + chunk_file.source_map.as_ref().map_or(0, |m| m.len())
+            })
+            .sum()
+    }
+
+    /// Drop the chunk files of the chunks removed from the chunk graph
+    pub fn retain(&self, chunk_ids: &HashSet<String>) {
+        self.files
+            .lock()
+            .unwrap()
+            .retain(|chunk_id, _| chunk_ids.contains(chunk_id));
+    }
+}
+
 type ChunksHashPlaceholder = HashMap<String, String>;
+
+pub type ChunkFileEmitter = dyn Fn(&ChunkFile) -> Result<()> + Sync;
 type ChunksHashReplacer = HashMap<String, String>;
 
 impl Compiler {
-    pub fn generate_chunk_files(&self, hmr_hash: u64) -> Result<Vec<ChunkFile>> {
+    /// Generate the chunk files of the chunks, or all the chunks if `chunk_ids` is None
+    pub fn generate_chunk_files(
+        &self,
+        hmr_hash: u64,
+        chunk_ids: Option<&HashSet<ChunkId>>,
+    ) -> Result<Vec<ChunkFile>> {
+        let (mut chunk_files, normal_chunk_files) =
+            self.generate_chunk_files_with_emitter(hmr_hash, None, chunk_ids)?;
+        chunk_files.extend(normal_chunk_files);
+        Ok(chunk_files)
+    }
+
+    /// Returns entry chunk files and normal chunk files separately. Normal chunk
+    /// files don't depend on any other chunk, so they're passed to `emitter` as
+    /// soon as their codegen is done, instead of waiting for all chunks.
+    pub fn generate_chunk_files_with_emitter(
+        &self,
+        hmr_hash: u64,
+        emitter: Option<&ChunkFileEmitter>,
+        chunk_ids: Option<&HashSet<ChunkId>>,
+    ) -> Result<(Vec<ChunkFile>, Vec<ChunkFile>)> {
         crate::mako_profile_function!();
         let chunk_graph = self.context.chunk_graph.read().unwrap();
         let chunks = chunk_graph.get_chunks();
 
+        // the cached files of the chunks which are not generated this time are kept
+        if self.context.args.watch {
+            self.context
+                .chunk_files_cache
+                .retain(&chunks.iter().map(|chunk| chunk.id.id.to_string()).collect());
+        }
+        // the modules of the worklets are emitted as the es modules by the transform
+        let chunks = chunks
+            .into_iter()
+            .filter(|chunk| !is_worklet_chunk(chunk))
+            .filter(|chunk| chunk_ids.map_or(true, |chunk_ids| chunk_ids.contains(&chunk.id)))
+            .collect::<Vec<_>>();
+        let service_workers = chunks
+            .iter()
+            .filter(|chunk| chunk.is_service_worker())
+            .map(|chunk| chunk.id.id.to_string())
+            .collect::<HashSet<_>>();
+
         let (entry_chunks, normal_chunks): (Vec<&Chunk>, Vec<&Chunk>) = chunks
             .into_iter()
             .partition(|chunk| match chunk.chunk_type {
@@ -77,7 +174,7 @@ impl Compiler {
 
         let (entry_chunk_files_with_placeholder, normal_chunk_files) = thread_pool::join(
             || self.generate_entry_chunk_files(entry_chunks, hmr_hash),
-            || self.generate_normal_chunk_files(normal_chunks),
+            || self.generate_normal_chunk_files(normal_chunks, emitter),
         );
 
         let normal_chunk_files = normal_chunk_files?;
@@ -115,8 +212,10 @@ impl Compiler {
                             css_chunks_hash_placeholder,
                             &css_chunks_hash_replacer,
                         )?;
+                        let output = &self.context.config.output;
                         chunk_files.iter_mut().for_each(|cf| {
-                            cf.hash = Some(file_content_hash(&cf.content));
+                            cf.hash = (!service_workers.contains(&cf.chunk_id))
+                                .then(|| file_content_hash(&cf.content, output));
                         });
 
                         Ok(())
@@ -129,7 +228,7 @@ impl Compiler {
             .flat_map(|e| e.0)
             .collect();
 
-        Ok([entry_chunk_files, normal_chunk_files].concat())
+        Ok((entry_chunk_files, normal_chunk_files))
     }
 
     fn generate_entry_chunk_files(
@@ -144,7 +243,7 @@ impl Compiler {
                 let module_graph = context.module_graph.read().unwrap();
                 let chunk_graph = self.context.chunk_graph.read().unwrap();
 
-                let (js_chunks_hash_placeholder, css_chunks_hash_placeholder) = chunk_graph
+                let (mut js_chunks_hash_placeholder, css_chunks_hash_placeholder) = chunk_graph
                     .installable_descendants_chunk(&chunk.id)
                     .iter()
                     .fold(
@@ -157,20 +256,21 @@ impl Compiler {
                                 ChunkPot::from(descendant_chunk, &module_graph, &context);
 
                             if self.context.config.hash {
-                                let placeholder = nanoid!(CHUNK_FILE_NAME_HASH_LENGTH);
+                                let hash_length = self.context.config.output.hash_length();
+                                let placeholder = nanoid!(hash_length);
 
                                 let js_filename = chunk_pot.js_name;
 
                                 if chunk_pot.stylesheet.is_some() {
                                     let css_filename = get_css_chunk_filename(&js_filename);
                                     acc_css.insert(
-                                        descendant_chunk_id.id.clone(),
+                                        descendant_chunk_id.id.to_string(),
                                         hash_file_name(&css_filename, &placeholder),
                                     );
                                 }
 
                                 acc_js.insert(
-                                    descendant_chunk_id.id.clone(),
+                                    descendant_chunk_id.id.to_string(),
                                     hash_file_name(&js_filename, &placeholder),
                                 );
                             } else {
@@ -178,17 +278,49 @@ impl Compiler {
 
                                 if chunk_pot.stylesheet.is_some() {
                                     let css_filename = get_css_chunk_filename(&js_filename);
-                                    acc_css.insert(descendant_chunk_id.id.clone(), css_filename);
+                                    acc_css
+                                        .insert(descendant_chunk_id.id.to_string(), css_filename);
                                 }
 
-                                acc_js.insert(descendant_chunk_id.id.clone(), js_filename);
+                                acc_js.insert(descendant_chunk_id.id.to_string(), js_filename);
                             }
                             (acc_js, acc_css)
                         },
                     );
 
+                // the shared entry chunks are imported by the esm entry chunk, instead of being
+                // injected by script tags
+                let esm_imports = if context.config.output.format == OutputFormat::Esm {
+                    let mut entry_dependencies = chunk_graph.entry_dependencies_chunk(&chunk.id);
+                    // the neighbors ordering is reversed
+                    entry_dependencies.reverse();
+                    entry_dependencies
+                        .into_iter()
+                        .filter_map(|dependency_chunk_id| {
+                            let dependency_chunk = chunk_graph.chunk(&dependency_chunk_id)?;
+                            if dependency_chunk.modules.is_empty() {
+                                return None;
+                            }
+                            let js_filename = if self.context.config.hash {
+                                let hash_length = self.context.config.output.hash_length();
+                                let placeholder = nanoid!(hash_length);
+                                hash_file_name(&dependency_chunk.filename(), &placeholder)
+                            } else {
+                                hash_too_long_file_name(&dependency_chunk.filename())
+                            };
+                            Some((dependency_chunk_id.id.to_string(), js_filename))
+                        })
+                        .collect::<Vec<_>>()
+                } else {
+                    vec![]
+                };
+
                 let chunk_files = {
-                    let chunk_pot = ChunkPot::from(chunk, &module_graph, &context);
+                    let mut chunk_pot = ChunkPot::from(chunk, &module_graph, &context);
+                    chunk_pot.imports = esm_imports
+                        .iter()
+                        .map(|(_, js_filename)| js_filename.clone())
+                        .collect();
                     chunk_pot
                         .to_entry_chunk_files(
                             &context,
@@ -198,6 +330,8 @@ impl Compiler {
                             hmr_hash,
                         )
                         .map(|chunk_files| {
+                            // the hash placeholders of the imports are replaced with the others
+                            js_chunks_hash_placeholder.extend(esm_imports);
                             (
                                 chunk_files,
                                 js_chunks_hash_placeholder,
@@ -232,7 +366,11 @@ impl Compiler {
         Ok(chunk_files)
     }
 
-    fn generate_normal_chunk_files(&self, chunks: Vec<&Chunk>) -> Result<Vec<ChunkFile>> {
+    fn generate_normal_chunk_files(
+        &self,
+        chunks: Vec<&Chunk>,
+        emitter: Option<&ChunkFileEmitter>,
+    ) -> Result<Vec<ChunkFile>> {
         let chunk_file_results: Vec<_> = chunks
             .par_iter()
             .map(|chunk| {
@@ -242,10 +380,35 @@ impl Compiler {
                 let module_graph = context.module_graph.read().unwrap();
                 let chunk = chunk_graph.chunk(&chunk_id).unwrap();
 
-                let chunk_files = ChunkPot::from(chunk, &module_graph, &context)
-                    .to_normal_chunk_files(chunk, &context);
+                let chunk_pot = ChunkPot::from(chunk, &module_graph, &context);
+                let chunk_files = if context.args.watch {
+                    let modules_hash = chunk_pot.modules_hash();
+                    match context
+                        .chunk_files_cache
+                        .get(&chunk_pot.chunk_id, modules_hash)
+                    {
+                        Some(chunk_files) => Ok(chunk_files),
+                        None => chunk_pot.to_normal_chunk_files(chunk, &context).inspect(
+                            |chunk_files| {
+                                context.chunk_files_cache.insert(
+                                    chunk_pot.chunk_id.clone(),
+                                    modules_hash,
+                                    chunk_files.clone(),
+                                );
+                            },
+                        ),
+                    }
+                } else {
+                    chunk_pot.to_normal_chunk_files(chunk, &context)
+                };
 
-                chunk_files
+                match (emitter, chunk_files) {
+                    (Some(emitter), Ok(chunk_files)) => chunk_files
+                        .iter()
+                        .try_for_each(emitter)
+                        .map(|_| chunk_files),
+                    (_, chunk_files) => chunk_files,
+                }
             })
             .collect();
 
@@ -339,7 +502,7 @@ pub fn modules_to_js_stmts(
     context: &Arc<Context>,
 ) -> Result<(Vec<PropOrSpread>, Option<Stylesheet>)> {
     let mut js_stmts = vec![];
-    let mut merged_css_modules: Vec<(String, Stylesheet)> = vec![];
+    let mut merged_css_modules: Vec<(&ModuleId, Stylesheet)> = vec![];
 
     let module_ids: Vec<_> = module_ids.iter().collect();
 
@@ -360,11 +523,11 @@ pub fn modules_to_js_stmts(
             // make sure the rules order is correct
             if let Some(index) = merged_css_modules
                 .iter()
-                .position(|(id, _)| id.eq(&module.id.id))
+                .position(|(id, _)| **id == module.id)
             {
                 merged_css_modules.remove(index);
             }
-            merged_css_modules.push((module.id.id.clone(), ast.ast.clone()));
+            merged_css_modules.push((&module.id, ast.ast.clone()));
         }
     }
     if !merged_css_modules.is_empty() {
@@ -373,11 +536,18 @@ pub fn modules_to_js_stmts(
             rules: vec![],
         };
 
+        let layer_names = css_layer_names(
+            &merged_css_modules
+                .iter()
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>(),
+            module_graph,
+        );
         for (_, ast) in merged_css_modules {
             merged_css_ast.rules.extend(ast.rules);
         }
 
-        transform_css_generate(&mut merged_css_ast, context);
+        transform_css_generate(&mut merged_css_ast, layer_names, context);
         Ok((js_stmts, Some(merged_css_ast)))
     } else {
         Ok((js_stmts, None))
@@ -414,3 +584,36 @@ fn hash_too_long_file_name(file_name: &String) -> String {
 
     format_file_name.to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk_file(chunk_id: &str, content: &str) -> ChunkFile {
+        ChunkFile {
+            raw_hash: 0,
+            content: content.as_bytes().to_vec(),
+            source_map: None,
+            hash: None,
+            file_name: format!("{}.js", chunk_id),
+            chunk_id: chunk_id.to_string(),
+            file_type: ChunkFileType::JS,
+        }
+    }
+
+    #[test]
+    fn test_chunk_files_cache() {
+        let cache = ChunkFilesCache::default();
+        cache.insert("a".to_string(), 1, vec![chunk_file("a", "a1")]);
+        cache.insert("b".to_string(), 1, vec![chunk_file("b", "b1")]);
+        assert_eq!(cache.get("a", 1).unwrap()[0].content, b"a1");
+        // the modules of the chunk are changed
+        assert!(cache.get("a", 2).is_none());
+        cache.insert("a".to_string(), 2, vec![chunk_file("a", "a2")]);
+        assert_eq!(cache.get("a", 2).unwrap()[0].content, b"a2");
+        // the chunk b is removed
+        cache.retain(&HashSet::from(["a".to_string()]));
+        assert!(cache.get("b", 1).is_none());
+        assert!(cache.get("a", 2).is_some());
+    }
+}
@@ -9,7 +9,7 @@ use crate::compiler::Compiler;
 use crate::dev::update::UpdateResult;
 use crate::generate::chunk::{Chunk, ChunkId, ChunkType};
 use crate::generate::chunk_graph::ChunkGraph;
-use crate::module::{generate_module_id, ImportOptions, ModuleId, ResolveType};
+use crate::module::{ImportOptions, ModuleId, ResolveType};
 
 pub type GroupUpdateResult = Option<(Vec<ChunkId>, Vec<(ModuleId, ChunkId, ChunkType)>)>;
 
@@ -24,12 +24,15 @@ impl Compiler {
         let mut chunk_graph = self.context.chunk_graph.write().unwrap();
         chunk_graph.clear();
 
-        let entries = module_graph.get_entry_modules();
+        let mut entries = module_graph.get_entry_modules();
+        // sort the entries to keep the order of chunks stable between builds
+        entries.sort();
         debug!("entries: {:?}", entries);
+        let entry_configs = self.context.entries.read().unwrap();
         for entry in entries {
             let mut entry_chunk_name = "index";
 
-            for (key, value) in &self.context.config.entry {
+            for (key, value) in entry_configs.iter() {
                 // hmr entry id has query '?hmr'
                 if parse_path(&value.to_string_lossy()).unwrap().0
                     == parse_path(&entry.id).unwrap().0
@@ -55,12 +58,7 @@ impl Compiler {
                     .map(|dep| {
                         (
                             chunk.id.clone(),
-                            match dep.1.get_chunk_name() {
-                                Some(chunk_name) => {
-                                    generate_module_id(chunk_name, &self.context).into()
-                                }
-                                None => dep.0.generate(&self.context).into(),
-                            },
+                            dep.1.chunk_id(&dep.0, &self.context).into(),
                         )
                     }),
             );
@@ -123,12 +121,7 @@ impl Compiler {
                     .map(|dep| {
                         (
                             chunk.id.clone(),
-                            match dep.1.get_chunk_name() {
-                                Some(chunk_name) => {
-                                    generate_module_id(chunk_name, &self.context).into()
-                                }
-                                None => dep.0.generate(&self.context).into(),
-                            },
+                            dep.1.chunk_id(&dep.0, &self.context).into(),
                         )
                     }),
             );
@@ -182,12 +175,7 @@ impl Compiler {
                     .map(|dep| {
                         (
                             chunk.id.clone(),
-                            match dep.1.get_chunk_name() {
-                                Some(chunk_name) => {
-                                    generate_module_id(chunk_name, &self.context).into()
-                                }
-                                None => dep.0.generate(&self.context).into(),
-                            },
+                            dep.1.chunk_id(&dep.0, &self.context).into(),
                         )
                     }),
             );
@@ -243,9 +231,7 @@ impl Compiler {
         if !update_result.removed.is_empty() {
             // remove chunk if it is the entry module of chunk
             for module_id in &update_result.removed {
-                let chunk_id = ChunkId {
-                    id: module_id.generate(&self.context),
-                };
+                let chunk_id = ChunkId::new(module_id.generate(&self.context));
 
                 if let Some(chunk) = chunk_graph.chunk(&chunk_id) {
                     let dependent_chunks = chunk_graph.dependents_chunk(&chunk.id);
@@ -447,11 +433,9 @@ impl Compiler {
         let mut dynamic_entries = vec![];
         let mut worker_entries = vec![];
 
-        let chunk_id_str = match import_options.get_chunk_name() {
-            Some(chunk_name) => generate_module_id(chunk_name, &self.context),
-            None => chunk_id.generate(&self.context),
-        };
+        let chunk_id_str = import_options.chunk_id(chunk_id, &self.context);
         let mut chunk = Chunk::new(chunk_id_str.into(), chunk_type.clone());
+        let is_service_worker = chunk.is_service_worker();
 
         let module_graph = self.context.module_graph.read().unwrap();
 
@@ -460,11 +444,15 @@ impl Compiler {
 
             for (dep_module_id, dep) in module_graph.get_dependencies(head) {
                 match &dep.resolve_type {
+                    // the service worker is not split
+                    ResolveType::DynamicImport(_) if is_service_worker => {
+                        next_module_ids.push(dep_module_id.clone());
+                    }
                     ResolveType::DynamicImport(chunk_group) => {
-                        dynamic_entries.push((dep_module_id.clone(), chunk_group.clone()));
+                        dynamic_entries.push((dep_module_id.clone(), chunk_group.chunk_options()));
                     }
                     ResolveType::Worker(chunk_group) => {
-                        worker_entries.push((dep_module_id.clone(), chunk_group.clone()));
+                        worker_entries.push((dep_module_id.clone(), chunk_group.chunk_options()));
                     }
                     // skip shared modules from entry chunks, but except worker chunk modules
                     _ if matches!(chunk_type, ChunkType::Worker(_))
@@ -522,12 +510,7 @@ impl Compiler {
                                 // ref existing chunk
                                 Some(chunk) => chunk.id.clone(),
                                 // ref new chunk
-                                None => match dep.1.get_chunk_name() {
-                                    Some(chunk_name) => {
-                                        generate_module_id(chunk_name, &self.context).into()
-                                    }
-                                    None => dep.0.generate(&self.context).into(),
-                                },
+                                None => dep.1.chunk_id(&dep.0, &self.context).into(),
                             },
                         )
                     }),
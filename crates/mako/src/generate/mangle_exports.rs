@@ -0,0 +1,385 @@
+use std::collections::{HashMap, HashSet};
+
+use swc_core::common::DUMMY_SP;
+use swc_core::ecma::ast::{
+    BindingIdent, Decl, ExportNamedSpecifier, ExportSpecifier, Expr, Ident, ImportSpecifier,
+    Module as SwcModule, ModuleDecl, ModuleExportName, ModuleItem, NamedExport, Stmt,
+};
+use swc_core::ecma::utils::quote_ident;
+use swc_core::ecma::visit::{Visit, VisitWith};
+
+use crate::ast::utils::is_esm;
+use crate::config::MangleExportsConfig;
+use crate::module::{ModuleAst, ModuleId, ResolveType};
+use crate::module_graph::ModuleGraph;
+
+const NAME_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_$";
+const RESERVED_WORDS: [&str; 3] = ["do", "if", "in"];
+
+/// Mangle the export names of the esm modules which are only imported by the named and default
+/// imports of the other esm modules, the exports and the imports are renamed consistently, so
+/// the property accesses of the exports are shortened across the chunks. The exports of the
+/// entries, and of the modules imported as namespaces, re-exported with `export *`, required
+/// or dynamically imported are kept.
+pub(crate) fn mangle_exports(module_graph: &mut ModuleGraph, config: &MangleExportsConfig) {
+    let reserved: HashSet<&str> = config.reserved.iter().map(|s| s.as_str()).collect();
+
+    // the renames of the exports of each mangled module
+    let mut renames: HashMap<ModuleId, HashMap<String, String>> = HashMap::new();
+    for module_id in module_graph.get_module_ids() {
+        let Some(names) = mangleable_exports(module_graph, &module_id) else {
+            continue;
+        };
+        let module_renames = mangled_names(names, &reserved);
+        if !module_renames.is_empty() {
+            renames.insert(module_id, module_renames);
+        }
+    }
+    if renames.is_empty() {
+        return;
+    }
+
+    for module_id in module_graph.get_module_ids() {
+        // the renames of the imported modules by the sources
+        let source_renames: HashMap<String, HashMap<String, String>> = module_graph
+            .get_dependencies(&module_id)
+            .into_iter()
+            .filter_map(|(dep_id, dep)| Some((dep.source.clone(), renames.get(dep_id)?.clone())))
+            .collect();
+        let own_renames = renames.get(&module_id);
+        if own_renames.is_none() && source_renames.is_empty() {
+            continue;
+        }
+        let empty = HashMap::new();
+        let own_renames = own_renames.unwrap_or(&empty);
+        let module = module_graph.get_module_mut(&module_id).unwrap();
+        if let Some(ModuleAst::Script(ast)) = module.info.as_mut().map(|info| &mut info.ast) {
+            rename_exports(&mut ast.ast, own_renames, &source_renames);
+        }
+    }
+}
+
+/// Returns the export names of the module if it's safe to mangle them
+fn mangleable_exports(module_graph: &ModuleGraph, module_id: &ModuleId) -> Option<Vec<String>> {
+    let module = module_graph.get_module(module_id)?;
+    if module.is_entry {
+        return None;
+    }
+    let info = module.info.as_ref()?;
+    if info.external.is_some() {
+        return None;
+    }
+    let ModuleAst::Script(ast) = &info.ast else {
+        return None;
+    };
+    if !is_esm(&ast.ast) {
+        return None;
+    }
+    let dependents = module_graph.get_dependents(module_id);
+    if dependents.is_empty() {
+        return None;
+    }
+    for (dependent_id, dep) in dependents {
+        if !matches!(
+            dep.resolve_type,
+            ResolveType::Import(_) | ResolveType::ExportNamed(_)
+        ) {
+            return None;
+        }
+        let dependent = module_graph.get_module(dependent_id)?.info.as_ref()?;
+        let ModuleAst::Script(dependent_ast) = &dependent.ast else {
+            return None;
+        };
+        if imports_as_namespace(&dependent_ast.ast, &dep.source) {
+            return None;
+        }
+    }
+    let names = export_names(&ast.ast);
+    (!names.is_empty()).then_some(names)
+}
+
+fn imports_as_namespace(ast: &SwcModule, source: &str) -> bool {
+    ast.body.iter().any(|item| match item {
+        ModuleItem::ModuleDecl(ModuleDecl::Import(import)) if &*import.src.value == source => {
+            import
+                .specifiers
+                .iter()
+                .any(|s| matches!(s, ImportSpecifier::Namespace(_)))
+        }
+        ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(export))
+            if export.src.as_ref().is_some_and(|src| &*src.value == source) =>
+        {
+            export
+                .specifiers
+                .iter()
+                .any(|s| matches!(s, ExportSpecifier::Namespace(_)))
+        }
+        ModuleItem::ModuleDecl(ModuleDecl::ExportAll(export)) => &*export.src.value == source,
+        _ => false,
+    })
+}
+
+fn export_names(ast: &SwcModule) -> Vec<String> {
+    let mut names = vec![];
+    for item in &ast.body {
+        match item {
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export)) => {
+                names.extend(decl_idents(&export.decl).iter().map(|i| i.sym.to_string()));
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(export)) => {
+                for specifier in &export.specifiers {
+                    match specifier {
+                        ExportSpecifier::Named(named) => {
+                            names.push(export_name(named.exported.as_ref().unwrap_or(&named.orig)))
+                        }
+                        ExportSpecifier::Namespace(namespace) => {
+                            names.push(export_name(&namespace.name))
+                        }
+                        ExportSpecifier::Default(_) => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    names.retain(|name| name != "default");
+    names.sort();
+    names.dedup();
+    names
+}
+
+fn mangled_names(names: Vec<String>, reserved: &HashSet<&str>) -> HashMap<String, String> {
+    // the mangled names never collide with the kept names
+    let existing: HashSet<&str> = names.iter().map(|n| n.as_str()).collect();
+    let mut index = 0;
+    let mut renames = HashMap::new();
+    for name in names.iter().filter(|n| !reserved.contains(n.as_str())) {
+        let mangled = loop {
+            let candidate = short_name(index);
+            index += 1;
+            if !existing.contains(candidate.as_str())
+                && !RESERVED_WORDS.contains(&candidate.as_str())
+            {
+                break candidate;
+            }
+        };
+        if mangled.len() < name.len() {
+            renames.insert(name.clone(), mangled);
+        }
+    }
+    renames
+}
+
+/// 0 => a, 1 => b, .., 54 => aa
+fn short_name(mut index: usize) -> String {
+    let mut name = String::new();
+    loop {
+        name.push(NAME_CHARS[index % NAME_CHARS.len()] as char);
+        index /= NAME_CHARS.len();
+        if index == 0 {
+            break;
+        }
+        index -= 1;
+    }
+    name
+}
+
+fn rename_exports(
+    ast: &mut SwcModule,
+    own_renames: &HashMap<String, String>,
+    source_renames: &HashMap<String, HashMap<String, String>>,
+) {
+    let rename_own = |name: &mut Option<ModuleExportName>, orig: &str| {
+        if let Some(mangled) = own_renames.get(orig) {
+            *name = Some(ModuleExportName::Ident(quote_ident!(mangled.as_str())));
+        }
+    };
+
+    let mut body = Vec::with_capacity(ast.body.len());
+    for mut item in ast.body.drain(..) {
+        match &mut item {
+            ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => {
+                if let Some(renames) = source_renames.get(&import.src.value.to_string()) {
+                    for specifier in import.specifiers.iter_mut() {
+                        if let ImportSpecifier::Named(named) = specifier {
+                            let imported = match &named.imported {
+                                Some(imported) => export_name(imported),
+                                None => named.local.sym.to_string(),
+                            };
+                            if let Some(mangled) = renames.get(&imported) {
+                                named.imported =
+                                    Some(ModuleExportName::Ident(quote_ident!(mangled.as_str())));
+                            }
+                        }
+                    }
+                }
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(export)) => {
+                let renames = export
+                    .src
+                    .as_ref()
+                    .and_then(|src| source_renames.get(&src.value.to_string()));
+                for specifier in export.specifiers.iter_mut() {
+                    match specifier {
+                        ExportSpecifier::Named(named) => {
+                            let orig = export_name(&named.orig);
+                            let exported = named
+                                .exported
+                                .as_ref()
+                                .map(export_name)
+                                .unwrap_or_else(|| orig.clone());
+                            if let Some(mangled) = renames.and_then(|r| r.get(&orig)) {
+                                named.orig =
+                                    ModuleExportName::Ident(quote_ident!(mangled.as_str()));
+                                named.exported =
+                                    Some(ModuleExportName::Ident(quote_ident!(exported.as_str())));
+                            }
+                            rename_own(&mut named.exported, &exported);
+                        }
+                        ExportSpecifier::Namespace(namespace) => {
+                            let name = export_name(&namespace.name);
+                            let mut renamed = None;
+                            rename_own(&mut renamed, &name);
+                            if let Some(renamed) = renamed {
+                                namespace.name = renamed;
+                            }
+                        }
+                        ExportSpecifier::Default(_) => {}
+                    }
+                }
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export)) => {
+                let idents = decl_idents(&export.decl);
+                if idents
+                    .iter()
+                    .any(|i| own_renames.contains_key(i.sym.as_str()))
+                {
+                    // export const foo = 1 => const foo = 1; export { foo as a }
+                    let specifiers = idents
+                        .into_iter()
+                        .map(|ident| {
+                            let mut exported = None;
+                            rename_own(&mut exported, ident.sym.as_str());
+                            ExportSpecifier::Named(ExportNamedSpecifier {
+                                span: DUMMY_SP,
+                                orig: ModuleExportName::Ident(ident),
+                                exported,
+                                is_type_only: false,
+                            })
+                        })
+                        .collect();
+                    body.push(ModuleItem::Stmt(Stmt::Decl(export.decl.clone())));
+                    body.push(ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(
+                        NamedExport {
+                            span: export.span,
+                            specifiers,
+                            src: None,
+                            type_only: false,
+                            with: None,
+                        },
+                    )));
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        body.push(item);
+    }
+    ast.body = body;
+}
+
+fn decl_idents(decl: &Decl) -> Vec<Ident> {
+    match decl {
+        Decl::Fn(f) => vec![f.ident.clone()],
+        Decl::Class(c) => vec![c.ident.clone()],
+        Decl::Var(var) => {
+            let mut collector = BindingCollector { idents: vec![] };
+            var.decls
+                .iter()
+                .for_each(|decl| decl.name.visit_with(&mut collector));
+            collector.idents
+        }
+        _ => vec![],
+    }
+}
+
+struct BindingCollector {
+    idents: Vec<Ident>,
+}
+
+impl Visit for BindingCollector {
+    fn visit_binding_ident(&mut self, n: &BindingIdent) {
+        self.idents.push(n.id.clone());
+    }
+
+    // the default values of the patterns don't declare the exports
+    fn visit_expr(&mut self, _n: &Expr) {}
+}
+
+fn export_name(name: &ModuleExportName) -> String {
+    match name {
+        ModuleExportName::Ident(ident) => ident.sym.to_string(),
+        ModuleExportName::Str(str) => str.value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use super::{mangled_names, rename_exports, short_name};
+    use crate::ast::tests::TestUtils;
+
+    #[test]
+    fn test_short_name() {
+        assert_eq!(short_name(0), "a");
+        assert_eq!(short_name(53), "$");
+        assert_eq!(short_name(54), "aa");
+        assert_eq!(short_name(55), "ba");
+    }
+
+    #[test]
+    fn test_mangled_names() {
+        let names = vec!["a".to_string(), "bar".to_string(), "foo".to_string()];
+        let renames = mangled_names(names, &HashSet::from(["a"]));
+        assert_eq!(
+            renames,
+            HashMap::from([
+                ("bar".to_string(), "b".to_string()),
+                ("foo".to_string(), "c".to_string())
+            ])
+        );
+    }
+
+    #[test]
+    fn test_rename_exports() {
+        let mut test_utils = TestUtils::gen_js_ast(
+            r#"
+import { foo, bar as baz } from "./a";
+export { foo as qux } from "./a";
+export const value = 1, other = 2;
+export function fn() {}
+export default 1;
+            "#,
+        );
+        let own = HashMap::from([
+            ("value".to_string(), "v".to_string()),
+            ("qux".to_string(), "q".to_string()),
+        ]);
+        let sources = HashMap::from([(
+            "./a".to_string(),
+            HashMap::from([
+                ("foo".to_string(), "a".to_string()),
+                ("bar".to_string(), "b".to_string()),
+            ]),
+        )]);
+        rename_exports(&mut test_utils.ast.js_mut().ast, &own, &sources);
+        let code = test_utils.js_ast_to_code();
+        assert!(code.contains(r#"import { a as foo, b as baz } from "./a";"#));
+        assert!(code.contains(r#"export { a as q } from "./a";"#));
+        assert!(code.contains("const value = 1, other = 2;"));
+        assert!(code.contains("export { value as v, other };"));
+        assert!(code.contains("export function fn() {}"));
+        assert!(code.contains("export default 1;"));
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use serde::Serialize;
+use tracing::{debug, warn};
+
+use crate::build::parse::Parse;
+use crate::build::transform::Transform;
+use crate::compiler::Compiler;
+use crate::generate::chunk::ChunkType;
+use crate::generate::chunk_pot::ChunkPot;
+use crate::generate::transform::transform_modules;
+use crate::module::{ModuleAst, ModuleId, ModuleInfo};
+use crate::stats::human_readable_size;
+
+/// The swc asts take roughly ten times the memory of the source code
+const AST_SIZE_FACTOR: usize = 10;
+
+/// The approximate memory held by the compiler in bytes
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryUsage {
+    /// the sources and the source maps of the modules
+    pub module_graph: usize,
+    pub asts: usize,
+    /// the emitted files kept in memory in watch mode
+    pub assets: usize,
+    pub total: usize,
+}
+
+impl Compiler {
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let module_graph = self.context.module_graph.read().unwrap();
+        let mut usage = MemoryUsage::default();
+        module_graph
+            .modules()
+            .iter()
+            .filter_map(|module| module.info.as_ref())
+            .for_each(|info| {
+                usage.module_graph += info.raw.len()
+                    + info
+                        .source_map_chain
+                        .iter()
+                        .map(|map| map.len())
+                        .sum::<usize>();
+                usage.asts += ast_size(info);
+            });
+        usage.assets = self.context.static_cache.read().unwrap().size()
+            + self.context.chunk_files_cache.size();
+        usage.total = usage.module_graph + usage.asts + usage.assets;
+        usage
+    }
+
+    /// Drop the asts of the largest modules until the memory usage is under config.memoryLimit,
+    /// they are parsed again by restore_dropped_asts_of_rendered_chunks when their chunks are
+    /// rendered again
+    pub fn drop_asts_over_limit(&self) {
+        let Some(limit) = self.context.config.memory_limit else {
+            return;
+        };
+        let limit = limit * 1024 * 1024;
+        let usage = self.memory_usage();
+        debug!("memory usage: {:?}", usage);
+        if usage.total <= limit {
+            return;
+        }
+
+        let mut module_graph = self.context.module_graph.write().unwrap();
+        let mut modules = module_graph
+            .modules()
+            .iter()
+            .filter_map(|module| {
+                let size = ast_size(module.info.as_ref()?);
+                (size > 0).then(|| (module.id.clone(), size))
+            })
+            .collect::<Vec<_>>();
+        modules.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.id.cmp(&b.0.id)));
+
+        let mut over = usage.total - limit;
+        let mut dropped_asts = self.context.dropped_asts.lock().unwrap();
+        for (module_id, size) in modules {
+            if over == 0 {
+                break;
+            }
+            let info = module_graph
+                .get_module_mut(&module_id)
+                .and_then(|module| module.info.as_mut())
+                .unwrap();
+            info.ast = ModuleAst::None;
+            dropped_asts.insert(module_id);
+            over = over.saturating_sub(size);
+        }
+        warn!(
+            "Memory usage {} exceeds memoryLimit, the asts of {} modules are dropped",
+            human_readable_size(usage.total as u64),
+            dropped_asts.len()
+        );
+    }
+
+    /// Restore the dropped asts of the modules in the chunks rendered by the next generate, i.e.
+    /// the entry chunks and the chunks whose cached files are outdated, the asts of the modules
+    /// in the cached chunks are kept dropped
+    pub fn restore_dropped_asts_of_rendered_chunks(&self) -> Result<()> {
+        if self.context.dropped_asts.lock().unwrap().is_empty() {
+            return Ok(());
+        }
+        let module_ids = {
+            let chunk_graph = self.context.chunk_graph.read().unwrap();
+            let module_graph = self.context.module_graph.read().unwrap();
+            chunk_graph
+                .get_chunks()
+                .into_iter()
+                .filter(|chunk| match chunk.chunk_type {
+                    ChunkType::Entry(_, _, false) | ChunkType::Worker(_) => true,
+                    _ => {
+                        let chunk_pot = ChunkPot::from(chunk, &module_graph, &self.context);
+                        !self
+                            .context
+                            .chunk_files_cache
+                            .contains(&chunk_pot.chunk_id, chunk_pot.modules_hash())
+                    }
+                })
+                .flat_map(|chunk| chunk.get_modules().iter().cloned())
+                .collect::<HashSet<_>>()
+        };
+        self.restore_dropped_asts(&module_ids)
+    }
+
+    /// Parse and transform the given modules again if their asts are dropped by
+    /// drop_asts_over_limit
+    pub fn restore_dropped_asts(&self, module_ids: &HashSet<ModuleId>) -> Result<()> {
+        let dropped_asts = {
+            let mut dropped_asts = self.context.dropped_asts.lock().unwrap();
+            let restoring = dropped_asts
+                .intersection(module_ids)
+                .cloned()
+                .collect::<Vec<_>>();
+            restoring.iter().for_each(|module_id| {
+                dropped_asts.remove(module_id);
+            });
+            restoring
+        };
+        if dropped_asts.is_empty() {
+            return Ok(());
+        }
+        debug!("restore {} dropped asts", dropped_asts.len());
+
+        let mut module_graph = self.context.module_graph.write().unwrap();
+        let mut restored = vec![];
+        for module_id in dropped_asts {
+            // the module is removed since its ast is dropped
+            let Some(info) = module_graph
+                .get_module_mut(&module_id)
+                .and_then(|module| module.info.as_mut())
+            else {
+                continue;
+            };
+            let mut ast = Parse::parse(&info.file, self.context.clone())?;
+            if !(matches!(ast, ModuleAst::Script(_)) && self.context.is_no_parse(&info.file)) {
+                Transform::transform(&mut ast, &info.file, self.context.clone())?;
+            }
+            info.ast = ast;
+            restored.push(module_id);
+        }
+        drop(module_graph);
+        transform_modules(restored, &self.context)
+    }
+}
+
+/// The estimated memory of the ast, the modules without ast like the dropped ones take none
+fn ast_size(info: &ModuleInfo) -> usize {
+    match info.ast {
+        ModuleAst::Script(_) | ModuleAst::Css(_) => info.raw.len() * AST_SIZE_FACTOR,
+        ModuleAst::None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::{Args, Compiler};
+    use crate::config::Config;
+    use crate::module::ModuleAst;
+    use crate::utils::test_helper::{get_module, setup_logger};
+
+    #[test]
+    fn test_drop_and_restore_asts() {
+        setup_logger();
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/memory-limit");
+        let mut config = Config::new(&root, None, None).unwrap();
+        config.memory_limit = Some(0);
+        let compiler = Compiler::new(config, root, Default::default(), None).unwrap();
+        compiler.build_entries().unwrap();
+
+        let usage = compiler.memory_usage();
+        assert!(usage.asts > 0);
+        assert_eq!(usage.total, usage.module_graph + usage.asts + usage.assets);
+
+        compiler.drop_asts_over_limit();
+        assert_eq!(compiler.memory_usage().asts, 0);
+        let foo = get_module(&compiler, "foo.ts");
+        assert!(matches!(foo.info.unwrap().ast, ModuleAst::None));
+
+        let module_ids = compiler
+            .context
+            .module_graph
+            .read()
+            .unwrap()
+            .modules()
+            .iter()
+            .map(|module| module.id.clone())
+            .collect();
+        compiler.restore_dropped_asts(&module_ids).unwrap();
+        assert_eq!(compiler.memory_usage(), usage);
+        let foo = get_module(&compiler, "foo.ts");
+        assert!(matches!(foo.info.unwrap().ast, ModuleAst::Script(_)));
+        let css = get_module(&compiler, "index.css");
+        assert!(matches!(css.info.unwrap().ast, ModuleAst::Css(_)));
+    }
+
+    #[test]
+    fn test_restore_dropped_asts_of_rendered_chunks() {
+        setup_logger();
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/memory-limit");
+        let mut config = Config::new(&root, None, None).unwrap();
+        config.memory_limit = Some(0);
+        let args = Args {
+            watch: true,
+            max_workers: None,
+        };
+        let compiler = Compiler::new(config, root, args, None).unwrap();
+        // the asts are dropped after the first build in watch mode
+        compiler.compile().unwrap();
+        assert_eq!(compiler.memory_usage().asts, 0);
+
+        compiler.restore_dropped_asts_of_rendered_chunks().unwrap();
+        let foo = get_module(&compiler, "foo.ts");
+        assert!(matches!(foo.info.unwrap().ast, ModuleAst::Script(_)));
+        // the chunk of the dynamic import is served from the cache
+        let lazy = get_module(&compiler, "lazy.ts");
+        assert!(matches!(lazy.info.unwrap().ast, ModuleAst::None));
+    }
+}
@@ -7,7 +7,7 @@ use swc_core::common::GLOBALS;
 use swc_core::css::ast::Stylesheet;
 use swc_core::css::minifier;
 use swc_core::ecma::minifier::optimize;
-use swc_core::ecma::minifier::option::{ExtraOptions, MinifyOptions};
+use swc_core::ecma::minifier::option::{CompressOptions, ExtraOptions, MinifyOptions};
 use swc_core::ecma::transforms::base::fixer::{fixer, paren_remover};
 use swc_core::ecma::transforms::base::helpers::{Helpers, HELPERS};
 use swc_core::ecma::transforms::base::resolver;
@@ -45,7 +45,11 @@ pub fn minify_js(ast: &mut JsAst, context: &Arc<Context>) -> Result<()> {
                             Some(comments),
                             None,
                             &MinifyOptions {
-                                compress: Some(Default::default()),
+                                // don't emit the syntax newer than the output es version
+                                compress: Some(CompressOptions {
+                                    ecma: context.config.output.es_version,
+                                    ..Default::default()
+                                }),
                                 mangle: Some(Default::default()),
                                 ..Default::default()
                             },
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::path::Path;
 use std::string::String;
 
 use hashlink::LinkedHashSet;
@@ -13,7 +14,8 @@ use crate::config::{
 };
 use crate::generate::chunk::{Chunk, ChunkId, ChunkType};
 use crate::generate::group_chunk::GroupUpdateResult;
-use crate::module::{Module, ModuleId, ModuleInfo};
+use crate::module::{relative_to_root, Module, ModuleId, ModuleInfo, ModuleType, ResolveType};
+use crate::module_graph::ModuleGraph;
 use crate::resolve::{ResolvedResource, ResolverResource};
 use crate::utils::{create_cached_regex, url_safe_base64_encode};
 
@@ -57,6 +59,19 @@ impl Compiler {
                 *optimize_info = Some(optimize_chunks_infos);
             }
         }
+
+        // stage: hoist the css modules shared by the remaining chunks
+        if self.hoist_shared_css() {
+            self.hoist_shared_css_modules();
+        }
+    }
+
+    fn hoist_shared_css(&self) -> bool {
+        self.context
+            .config
+            .optimization
+            .as_ref()
+            .is_some_and(|optimization| optimization.hoist_shared_css.unwrap_or(false))
     }
 
     pub fn optimize_hot_update_chunk(&self, group_result: &GroupUpdateResult) {
@@ -65,6 +80,10 @@ impl Compiler {
 
         // skip if code splitting disabled or group result is invalid
         if self.context.config.code_splitting.is_none() || group_result.is_none() {
+            // the new async chunks may share the css modules with the others
+            if group_result.is_some() && self.hoist_shared_css() {
+                self.hoist_shared_css_modules();
+            }
             return;
         }
 
@@ -110,6 +129,10 @@ impl Compiler {
 
             // stage: apply
             self.apply_hot_update_optimize_infos(&optimize_infos);
+
+            if self.hoist_shared_css() {
+                self.hoist_shared_css_modules();
+            }
         }
     }
 
@@ -212,7 +235,7 @@ impl Compiler {
                         .keys()
                         .cloned()
                         .collect::<LinkedHashSet<_>>(),
-                    id: ChunkId { id: "".to_string() },
+                    id: ChunkId::new("".to_string()),
                     chunk_type: ChunkType::Sync,
                     content: None,
                     source_map: None,
@@ -242,7 +265,7 @@ impl Compiler {
             let mut package_size_map = chunk_modules.iter().fold(
                 IndexMap::<String, (usize, IndexMap<ModuleId, Vec<ChunkId>>)>::new(),
                 |mut size_map, mtc| {
-                    let pkg_name = self.get_package_name(mtc.0).unwrap_or(mtc.0.id.clone());
+                    let pkg_name = self.get_package_name(mtc.0).unwrap_or(mtc.0.id.to_string());
 
                     let module_size = module_graph.get_module(mtc.0).unwrap().get_module_size();
 
@@ -375,7 +398,8 @@ impl Compiler {
                                 let mut stable_dependents = dependents.clone();
                                 stable_dependents.sort();
 
-                                let dependents_md5 = md5_chunk_ids(&stable_dependents);
+                                let dependents_md5 =
+                                    md5_chunk_ids(&stable_dependents, &self.context.root);
 
                                 let package_entry = module_to_dependents_md5_map
                                     .entry(dependents_md5)
@@ -417,14 +441,13 @@ impl Compiler {
     }
 
     fn apply_optimize_infos(&self, optimize_chunks_infos: &Vec<OptimizeChunksInfo>) {
-        let mut edges_map: HashMap<ModuleId, IndexSet<ModuleId>> = HashMap::new();
+        // keep the insertion order of edges, which decides the loading order of chunks
+        let mut edges_map: IndexMap<ModuleId, IndexSet<ModuleId>> = IndexMap::new();
         let mut chunk_graph = self.context.chunk_graph.write().unwrap();
 
         for info in optimize_chunks_infos {
             // create new chunk
-            let info_chunk_id = ChunkId {
-                id: info.group_options.name.clone(),
-            };
+            let info_chunk_id = ChunkId::new(info.group_options.name.clone());
             let info_chunk_type = if matches!(info.group_options.allow_chunks, AllowChunks::Async) {
                 ChunkType::Sync
             } else {
@@ -474,6 +497,87 @@ impl Compiler {
             });
     }
 
+    /**
+     * Move the css modules which are still shared by several async chunks into a shared sync
+     * chunk, so that the same rules are not injected twice and override the rules loaded in
+     * between, the chunks sharing the same css modules are grouped into the same shared chunk.
+     * The modules keep the order they first appear in the chunks, which is the source order.
+     */
+    fn hoist_shared_css_modules(&self) {
+        let module_graph = self.context.module_graph.read().unwrap();
+        let mut chunk_graph = self.context.chunk_graph.write().unwrap();
+
+        let mut css_to_chunks: IndexMap<ModuleId, Vec<ChunkId>> = IndexMap::new();
+        chunk_graph
+            .get_chunks()
+            .into_iter()
+            // the entry chunks are loaded by different pages, so there's nothing to share
+            .filter(|chunk| matches!(chunk.chunk_type, ChunkType::Async | ChunkType::Sync))
+            .for_each(|chunk| {
+                chunk
+                    .modules
+                    .iter()
+                    .filter(|module_id| {
+                        module_graph
+                            .get_module(module_id)
+                            .is_some_and(|module| module.get_module_type() == ModuleType::Css)
+                    })
+                    // the root modules of the async chunks should stay in them,
+                    // e.g. import('./a.css')
+                    .filter(|module_id| {
+                        chunk.chunk_type != ChunkType::Async
+                            || !is_dynamic_import_root(&module_graph, module_id)
+                    })
+                    .for_each(|module_id| {
+                        css_to_chunks
+                            .entry(module_id.clone())
+                            .or_default()
+                            .push(chunk.id.clone());
+                    });
+            });
+
+        let mut shared_groups: IndexMap<Vec<ChunkId>, Vec<ModuleId>> = IndexMap::new();
+        for (module_id, mut chunk_ids) in css_to_chunks {
+            if chunk_ids.len() < 2 {
+                continue;
+            }
+            chunk_ids.sort();
+            shared_groups.entry(chunk_ids).or_default().push(module_id);
+        }
+
+        for (chunk_ids, module_ids) in shared_groups {
+            let shared_chunk_id = ChunkId::new(format!(
+                "shared-css_{}",
+                md5_chunk_ids(&chunk_ids, &self.context.root)
+            ));
+            debug!(
+                "hoist shared css modules {:?} into {}",
+                module_ids, shared_chunk_id.id
+            );
+            for chunk_id in &chunk_ids {
+                let chunk = chunk_graph.mut_chunk(chunk_id).unwrap();
+                module_ids
+                    .iter()
+                    .for_each(|module_id| chunk.remove_module(module_id));
+            }
+            // the shared chunk is already there in the hot updates
+            if let Some(shared_chunk) = chunk_graph.mut_chunk(&shared_chunk_id) {
+                shared_chunk.modules.extend(module_ids);
+                continue;
+            }
+            chunk_graph.add_chunk(Chunk {
+                modules: module_ids.into_iter().collect::<LinkedHashSet<_>>(),
+                id: shared_chunk_id.clone(),
+                chunk_type: ChunkType::Sync,
+                content: None,
+                source_map: None,
+            });
+            for chunk_id in &chunk_ids {
+                chunk_graph.add_edge(chunk_id, &shared_chunk_id);
+            }
+        }
+    }
+
     fn apply_hot_update_optimize_infos(&self, optimize_chunks_infos: &Vec<OptimizeChunksInfo>) {
         let mut edges = HashMap::new();
         let mut chunk_graph = self.context.chunk_graph.write().unwrap();
@@ -482,9 +586,7 @@ impl Compiler {
             for (module_id, chunk_ids) in &info.module_to_chunks {
                 // get chunk
                 let info_chunk = chunk_graph
-                    .mut_chunk(&ChunkId {
-                        id: info.group_options.name.clone(),
-                    })
+                    .mut_chunk(&ChunkId::new(info.group_options.name.clone()))
                     .unwrap();
                 let info_chunk_id = info_chunk.id.clone();
 
@@ -652,12 +754,22 @@ fn code_splitting_strategy_granular(
     }
 }
 
-fn md5_chunk_ids(chunk_ids: &[ChunkId]) -> String {
+/// Hash the chunk ids relative to root, so the names derived from them don't depend on where
+/// the project is located
+pub(crate) fn md5_chunk_ids(chunk_ids: &[ChunkId], root: &Path) -> String {
     let mut context = md5::Context::new();
     chunk_ids.iter().for_each(|cd| {
-        context.consume(cd.id.as_bytes());
+        context.consume(relative_to_root(&cd.id, root).as_bytes());
     });
     let digest = context.compute();
     let hash = url_safe_base64_encode(digest.0);
     hash[..8].to_string()
 }
+
+/// Whether the module is imported with `import()`, i.e. the root of an async chunk
+fn is_dynamic_import_root(module_graph: &ModuleGraph, module_id: &ModuleId) -> bool {
+    module_graph
+        .get_dependents(module_id)
+        .iter()
+        .any(|(_, dep)| matches!(dep.resolve_type, ResolveType::DynamicImport(_)))
+}
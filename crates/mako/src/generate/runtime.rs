@@ -7,10 +7,40 @@ pub struct AppRuntimeTemplate {
     pub has_hmr: bool,
     pub umd: Option<String>,
     pub cjs: bool,
+    pub esm: bool,
     pub pkg_name: Option<String>,
     pub chunk_loading_global: String,
     pub is_browser: bool,
+    /// the output dir relative to the dir of the runtime chunk as a js string, e.g. `"../"` for
+    /// `pages/index.js`, the chunks and the addons required on node are relative to it
+    pub output_root: String,
+    /// rendered for a worker chunk, there's no document to load the chunks and styles by
+    pub is_worker: bool,
+    /// the modules of the dynamic imports are bundled into the service worker chunk
+    pub is_service_worker: bool,
+    /// the worker loads its async chunks by `importScripts()` instead of `import()`
+    pub worker_import_scripts: bool,
     pub concatenate_enabled: bool,
     pub cross_origin_loading: Option<String>,
     pub global_module_registry: bool,
+    pub chunk_load_retry: bool,
+    pub chunk_load_retries: usize,
+    pub chunk_load_retry_delay: usize,
+    pub chunk_load_fallback_public_paths: String,
+    /// the runtime of the chunk hints, only with config.chunkHints or the chunks marked by the
+    /// magic comments
+    pub chunk_hints: bool,
+    pub chunk_prefetch_map: String,
+    pub chunk_preload_map: String,
+    pub runtime_global: Option<String>,
+    /// the styles are inserted by the runtime into the target in order, only with the
+    /// styleInjection config or in the shadow dom, they're appended to document.head otherwise
+    pub style_injection: bool,
+    /// the selector of the element the styles are inserted into, as a js string
+    pub style_insert: Option<String>,
+    /// the styles are loaded as the constructable stylesheets
+    pub style_constructable: bool,
+    pub shadow_dom: bool,
+    /// the entry exports are the lifecycles of qiankun and single-spa
+    pub micro_frontend: bool,
 }
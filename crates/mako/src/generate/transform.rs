@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::mpsc::channel;
 use std::sync::Arc;
 use std::time::Instant;
@@ -20,17 +20,22 @@ use swc_core::ecma::visit::VisitMutWith;
 use tracing::debug;
 
 use crate::ast::js_ast::JsAst;
+use crate::ast::utils::is_esm;
 use crate::compiler::{Compiler, Context};
-use crate::module::{generate_module_id, Dependency, ModuleAst, ModuleId, ModuleType, ResolveType};
+use crate::config::OutputFormat;
+use crate::generate::worklet::emit_worklet_module;
+use crate::module::{Dependency, ModuleAst, ModuleId, ModuleInfo, ModuleType, ResolveType};
 use crate::share::helpers::SWC_HELPERS;
 use crate::utils::thread_pool;
 use crate::visitors::async_module::{mark_async, AsyncModule};
 use crate::visitors::common_js::common_js;
 use crate::visitors::css_imports::CSSImports;
+use crate::visitors::css_layers::CSSLayers;
 use crate::visitors::dep_replacer::{DepReplacer, DependenciesToReplace};
 use crate::visitors::dynamic_import::DynamicImport;
 use crate::visitors::mako_require::MakoRequire;
 use crate::visitors::meta_url_replacer::MetaUrlReplacer;
+use crate::visitors::node_interop::NodeInterop;
 use crate::visitors::optimize_define_utils::OptimizeDefineUtils;
 
 impl Compiler {
@@ -83,6 +88,30 @@ pub fn transform_modules_in_thread(
         thread_pool::spawn(move || {
             let module_graph = context.module_graph.read().unwrap();
             let deps = module_graph.get_dependencies(&module_id);
+            let module = module_graph.get_module(&module_id).unwrap();
+            let info = module.info.as_ref().unwrap();
+            // in node's esm, only the imports of commonjs modules use the node interop
+            let node_interop_sources: HashSet<String> = if info.is_node_esm() {
+                deps.iter()
+                    .filter(|(id, dep)| {
+                        dep.resolve_type.is_sync_esm()
+                            && module_graph
+                                .get_module(id)
+                                .and_then(|m| m.info.as_ref())
+                                .is_some_and(is_commonjs)
+                    })
+                    .map(|(_, dep)| dep.source.clone())
+                    .collect()
+            } else {
+                HashSet::new()
+            };
+            if let ModuleAst::Script(ast) = &info.ast
+                && let Err(err) =
+                    emit_worklet_module(&module_id, ast, &deps, &info.deps.missing_deps, &context)
+            {
+                rs.send(Err(err)).unwrap();
+                return;
+            }
             let mut resolved_deps: HashMap<String, (String, String)> = deps
                 .into_iter()
                 .map(|(id, dep)| {
@@ -91,33 +120,21 @@ pub fn transform_modules_in_thread(
                         (
                             match &dep.resolve_type {
                                 ResolveType::Worker(import_options) => {
-                                    let chunk_id = match import_options.get_chunk_name() {
-                                        Some(chunk_name) => {
-                                            generate_module_id(chunk_name, &context)
-                                        }
-                                        None => id.generate(&context),
-                                    };
+                                    let chunk_id = import_options.chunk_id(id, &context);
                                     let chunk_graph = context.chunk_graph.read().unwrap();
                                     chunk_graph.chunk(&chunk_id.into()).unwrap().filename()
                                 }
                                 ResolveType::DynamicImport(import_options) => {
-                                    match import_options.get_chunk_name() {
-                                        Some(chunk_name) => {
-                                            generate_module_id(chunk_name, &context)
-                                        }
-                                        None => id.generate(&context),
-                                    }
+                                    import_options.chunk_id(id, &context)
                                 }
                                 _ => id.generate(&context),
                             },
-                            id.id.clone(),
+                            id.id.to_string(),
                         ),
                     )
                 })
                 .collect();
             insert_swc_helper_replace(&mut resolved_deps, &context);
-            let module = module_graph.get_module(&module_id).unwrap();
-            let info = module.info.as_ref().unwrap();
             let ast = info.ast.clone();
             let deps_to_replace = DependenciesToReplace {
                 resolved: resolved_deps,
@@ -134,6 +151,7 @@ pub fn transform_modules_in_thread(
                     async_deps: &async_deps,
                     wrap_async,
                     top_level_await: info.top_level_await,
+                    node_interop_sources: &node_interop_sources,
                 });
                 let message = match ret {
                     Ok(_) => Ok((module_id, ModuleAst::Script(ast))),
@@ -164,10 +182,19 @@ pub fn transform_modules_in_thread(
 fn insert_swc_helper_replace(map: &mut HashMap<String, (String, String)>, context: &Arc<Context>) {
     SWC_HELPERS.into_iter().for_each(|h| {
         let m_id: ModuleId = h.to_string().into();
-        map.insert(m_id.id.clone(), (m_id.generate(context), h.to_string()));
+        map.insert(m_id.id.to_string(), (m_id.generate(context), h.to_string()));
     });
 }
 
+/// Whether the module is commonjs in node's view, the externals are commonjs except the es
+/// module ones, which are imported as namespaces
+fn is_commonjs(info: &ModuleInfo) -> bool {
+    match &info.external {
+        Some(external) => !external.starts_with("import("),
+        None => matches!(&info.ast, ModuleAst::Script(ast) if !is_esm(&ast.ast)),
+    }
+}
+
 pub struct TransformJsParam<'a> {
     pub module_id: &'a ModuleId,
     pub context: &'a Arc<Context>,
@@ -176,6 +203,7 @@ pub struct TransformJsParam<'a> {
     pub async_deps: &'a Vec<Dependency>,
     pub wrap_async: bool,
     pub top_level_await: bool,
+    pub node_interop_sources: &'a HashSet<String>,
 }
 
 pub fn transform_js_generate(transform_js_param: TransformJsParam) -> Result<()> {
@@ -188,6 +216,7 @@ pub fn transform_js_generate(transform_js_param: TransformJsParam) -> Result<()>
         async_deps,
         wrap_async,
         top_level_await,
+        node_interop_sources,
     } = transform_js_param;
     GLOBALS.set(&context.meta.script.globals, || {
         try_with_handler(
@@ -209,6 +238,12 @@ pub fn transform_js_generate(transform_js_param: TransformJsParam) -> Result<()>
                             unresolved_mark,
                             import_interop,
                         ));
+                        if !node_interop_sources.is_empty() {
+                            ast.ast.visit_mut_with(&mut NodeInterop {
+                                unresolved_mark,
+                                sources: node_interop_sources,
+                            });
+                        }
 
                         ast.ast.visit_mut_with(&mut OptimizeDefineUtils {
                             top_level_mark,
@@ -230,7 +265,10 @@ pub fn transform_js_generate(transform_js_param: TransformJsParam) -> Result<()>
                         };
                         ast.ast.visit_mut_with(&mut dep_replacer);
 
-                        let mut meta_url_replacer = MetaUrlReplacer {};
+                        let mut meta_url_replacer = MetaUrlReplacer {
+                            platform: context.config.platform,
+                            esm: context.config.output.format == OutputFormat::Esm,
+                        };
                         ast.ast.visit_mut_with(&mut meta_url_replacer);
 
                         let mut dynamic_import = DynamicImport::new(context.clone(), dep_map);
@@ -266,8 +304,16 @@ pub fn transform_js_generate(transform_js_param: TransformJsParam) -> Result<()>
     })
 }
 
-pub fn transform_css_generate(ast: &mut ast::Stylesheet, _context: &Arc<Context>) {
+/// `layer_names` are the cascade layers of the concatenated css modules in their order, which
+/// is collected by `css_layer_names` before the modules are concatenated
+pub fn transform_css_generate(
+    ast: &mut ast::Stylesheet,
+    layer_names: Vec<String>,
+    _context: &Arc<Context>,
+) {
     crate::mako_profile_function!();
+    // declare the order of the layers before the imports are hoisted
+    ast.visit_mut_with(&mut CSSLayers { names: layer_names });
     // replace deps
     let mut css_handler = CSSImports {};
     ast.visit_mut_with(&mut css_handler);
@@ -338,7 +384,7 @@ mod tests {
         let path = path.unwrap_or("test.css");
         let context: Arc<Context> = Arc::new(Default::default());
         let mut ast = CssAst::build(path, content, context.clone(), false).unwrap();
-        transform_css_generate(&mut ast.ast, &context);
+        transform_css_generate(&mut ast.ast, vec![], &context);
         let code = ast.generate(context.clone()).unwrap().code;
         println!("{}", code);
         code
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use swc_core::common::GLOBALS;
+use swc_core::ecma::visit::VisitMutWith;
+
+use crate::ast::js_ast::JsAst;
+use crate::ast::utils::is_esm;
+use crate::compiler::{Context, EmittedAsset};
+use crate::generate::chunk::{Chunk, ChunkType};
+use crate::module::{Dependency, ModuleId};
+use crate::visitors::dep_replacer::{DepReplacer, DependenciesToReplace};
+use crate::visitors::worker_module::WORKLET_QUERY;
+
+/// The worklets are loaded by `addModule()` as the ES modules which can't load the chunks, so
+/// the worklet chunks are not rendered with the runtime, the modules of them are emitted as
+/// the plain ES modules instead, the entry at the file of the chunk and the dependencies next
+/// to it, with the imports replaced with the files
+pub(crate) fn is_worklet_chunk(chunk: &Chunk) -> bool {
+    matches!(
+        &chunk.chunk_type,
+        ChunkType::Worker(module_id) if module_id.id.ends_with(WORKLET_QUERY)
+    )
+}
+
+/// Emit the module as an ES module if it's in a worklet chunk, the ast is the one before the
+/// commonjs transform of the chunks
+pub(crate) fn emit_worklet_module(
+    module_id: &ModuleId,
+    ast: &JsAst,
+    deps: &[(&ModuleId, &Dependency)],
+    missing_deps: &HashMap<String, Dependency>,
+    context: &Arc<Context>,
+) -> Result<()> {
+    let chunk_graph = context.chunk_graph.read().unwrap();
+    let Some(chunk) = chunk_graph
+        .get_chunks()
+        .into_iter()
+        .find(|chunk| is_worklet_chunk(chunk) && chunk.has_module(module_id))
+    else {
+        return Ok(());
+    };
+    if !is_esm(&ast.ast) {
+        return Err(anyhow!(
+            "The module {} of the worklet {} must be an ES module",
+            module_id.id,
+            chunk.id.id
+        ));
+    }
+
+    let resolved = deps
+        .iter()
+        .filter(|(id, _)| chunk.has_module(id))
+        .map(|(id, dep)| {
+            let file_name = format!("./{}", worklet_file_name(id, chunk, context));
+            (dep.source.clone(), (file_name, id.id.to_string()))
+        })
+        .collect();
+    let to_replace = DependenciesToReplace {
+        resolved,
+        missing: missing_deps.clone(),
+    };
+    let mut ast = ast.clone();
+    GLOBALS.set(&context.meta.script.globals, || {
+        ast.ast.visit_mut_with(&mut DepReplacer {
+            module_id,
+            to_replace: &to_replace,
+            context,
+            unresolved_mark: ast.unresolved_mark,
+        });
+    });
+    let code = ast.generate(context.clone())?.code;
+    context.emitted_assets.lock().unwrap().insert(
+        worklet_file_name(module_id, chunk, context),
+        EmittedAsset {
+            content: code.into_bytes(),
+            module_id: Some(module_id.clone()),
+        },
+    );
+    Ok(())
+}
+
+fn worklet_file_name(module_id: &ModuleId, chunk: &Chunk, context: &Arc<Context>) -> String {
+    match &chunk.chunk_type {
+        ChunkType::Worker(entry) if entry == module_id => chunk.filename(),
+        _ => {
+            let name = module_id
+                .generate(context)
+                .replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "_");
+            format!("{}-worklet.js", name)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_worklet_chunk() {
+        let worklet = ModuleId::new(format!("/src/processor.ts{}", WORKLET_QUERY));
+        assert!(is_worklet_chunk(&Chunk::new(
+            worklet.clone(),
+            ChunkType::Worker(worklet)
+        )));
+        let worker = ModuleId::new("/src/worker.ts?asworker".to_string());
+        assert!(!is_worklet_chunk(&Chunk::new(
+            worker.clone(),
+            ChunkType::Worker(worker)
+        )));
+    }
+}
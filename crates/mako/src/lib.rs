@@ -7,6 +7,7 @@ mod build;
 pub mod cli;
 pub mod compiler;
 pub mod config;
+pub mod daemon;
 pub mod dev;
 mod features;
 mod generate;
@@ -1,14 +1,21 @@
 #![feature(box_patterns)]
 #![feature(let_chains)]
 
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
 use clap::Parser;
-use mako::compiler::{self, Args};
+#[cfg(not(feature = "profile"))]
+use colored::Colorize;
+use mako::compiler::{self, Args, GraphDumpFormat, GraphDumpOptions};
+#[cfg(not(feature = "profile"))]
+use mako::daemon::DaemonResponse;
+use mako::daemon::{self, DaemonRequest};
 #[cfg(not(feature = "profile"))]
 use mako::dev;
-use mako::utils::logger::init_logger;
+use mako::stats::diff::{BundleDiff, DiffStats};
+use mako::utils::logger::{init_logger_with_options, LoggerOptions};
 #[cfg(feature = "profile")]
 use mako::utils::profile_gui::ProfileApp;
 use mako::utils::tokio_runtime;
@@ -34,25 +41,63 @@ fn main() -> Result<()> {
 }
 
 async fn run() -> Result<()> {
-    // logger
-    init_logger();
-
     // cli
     let cli = cli::Cli::parse();
+
+    // logger
+    let mut logger_options = LoggerOptions::from_env();
+    if let Some(level) = cli.log_level {
+        logger_options.level = level;
+    }
+    if let Some(format) = cli.log_format {
+        logger_options.format = format;
+    }
+    logger_options
+        .debug_scopes
+        .extend(cli.debug.iter().copied());
+    init_logger_with_options(&logger_options);
+    match cli.command {
+        Some(cli::Command::Daemon(args)) => return run_daemon(args),
+        Some(cli::Command::Diff(args)) => return run_diff(&args.before, &args.after),
+        None => {}
+    }
+    // root is required without the subcommands
+    let root = absolute_root(cli.root.unwrap())?;
     debug!(
         "cli: watch = {}, mode = {}, root = {}",
         cli.watch,
         cli.mode,
-        cli.root.to_str().unwrap()
+        root.to_str().unwrap()
     );
-    let root = if cli.root.is_absolute() {
-        cli.root
-    } else {
-        std::env::current_dir()?.join(cli.root)
-    };
-    let root = root
-        .canonicalize()
-        .map_err(|_| anyhow!("Root directory {:?} not found", root))?;
+
+    // the builds with the outputs of the compiler in this process are not sent to the daemon
+    #[cfg(not(feature = "profile"))]
+    if !cli.watch
+        && !cli.no_daemon
+        && !cli.analyze
+        && cli.dump_graph.is_none()
+        && cli.compare_with.is_none()
+    {
+        let request = DaemonRequest::Build {
+            mode: cli.mode.clone(),
+            max_workers: cli.max_workers,
+        };
+        match daemon::request(&root, &request) {
+            Ok(Some(DaemonResponse::Built { time })) => {
+                println!(
+                    "{}",
+                    format!("✓ Built in {} by the daemon", format!("{}ms", time).bold()).green()
+                );
+                return Ok(());
+            }
+            Ok(Some(DaemonResponse::Failed { error })) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+            Ok(response) => debug!("build without the daemon: {:?}", response),
+            Err(e) => debug!("build without the daemon: {:?}", e),
+        }
+    }
 
     // config
     let cli_args = format!(
@@ -67,11 +112,31 @@ async fn run() -> Result<()> {
         .map_err(|e| anyhow!(format!("Load config failed: {}", e)))?;
 
     config.mode = cli.mode;
+    if cli.analyze {
+        config.analyze = Some(config::AnalyzeConfig {});
+    }
+    // the stats.json of the build is compared
+    if cli.compare_with.is_some() && config.stats.is_none() {
+        config.stats = Some(config::StatsConfig { modules: false });
+    }
+    if let Some(open) = cli.open
+        && let Some(dev_server) = config.dev_server.as_mut()
+    {
+        dev_server.open = Some(Some(open));
+    }
 
     debug!("config: {:?}", config);
 
     // compiler
-    let compiler = compiler::Compiler::new(config, root.clone(), Args { watch: cli.watch }, None)?;
+    let compiler = compiler::Compiler::new(
+        config,
+        root.clone(),
+        Args {
+            watch: cli.watch,
+            max_workers: cli.max_workers,
+        },
+        None,
+    )?;
     let compiler = Arc::new(compiler);
 
     #[cfg(feature = "profile")]
@@ -88,10 +153,41 @@ async fn run() -> Result<()> {
 
     #[cfg(not(feature = "profile"))]
     {
+        // Ctrl-C aborts the in-flight build instead of waiting for all the build tasks, and
+        // exits once it has returned so that the outputs aren't left half emitted
+        if cli.watch {
+            let compiler = compiler.clone();
+            tokio_runtime::spawn(async move {
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    let _ = tokio::task::spawn_blocking(move || compiler.cancel_and_wait()).await;
+                    std::process::exit(130);
+                }
+            });
+        }
         if let Err(e) = compiler.compile() {
             eprintln!("{}", e);
             std::process::exit(1);
         }
+        if let Some(format) = cli.dump_graph {
+            let options = GraphDumpOptions {
+                format,
+                filter: cli.dump_graph_filter,
+                depth: cli.dump_graph_depth,
+            };
+            let file_name = match format {
+                GraphDumpFormat::Dot => "module-graph.dot",
+                GraphDumpFormat::Json => "module-graph.json",
+            };
+            let output_path = &compiler.context.config.output.path;
+            std::fs::create_dir_all(output_path)?;
+            let path = output_path.join(file_name);
+            std::fs::write(&path, compiler.dump_module_graph(&options))?;
+            println!("Module graph dumped to {}", path.display());
+        }
+        if let Some(compare_with) = &cli.compare_with {
+            let stats = compiler.context.config.output.path.join("stats.json");
+            run_diff(compare_with, &stats)?;
+        }
         if cli.watch {
             let d = dev::DevServer::new(root.clone(), compiler);
             // TODO: when in Dev Mode, Dev Server should start asap, and provider a loading  while in first compiling
@@ -100,3 +196,39 @@ async fn run() -> Result<()> {
     }
     Ok(())
 }
+
+fn absolute_root(root: PathBuf) -> Result<PathBuf> {
+    let root = if root.is_absolute() {
+        root
+    } else {
+        std::env::current_dir()?.join(root)
+    };
+    root.canonicalize()
+        .map_err(|_| anyhow!("Root directory {:?} not found", root))
+}
+
+fn run_diff(before: &Path, after: &Path) -> Result<()> {
+    let diff = BundleDiff::new(&DiffStats::read(before)?, &DiffStats::read(after)?);
+    println!("{}", diff.render());
+    Ok(())
+}
+
+fn run_daemon(args: cli::DaemonArgs) -> Result<()> {
+    let root = absolute_root(args.root)?;
+    if args.stop {
+        match daemon::request(&root, &DaemonRequest::Stop)? {
+            Some(_) => println!("Mako daemon is stopped"),
+            None => println!("No mako daemon is running for {}", root.display()),
+        }
+        return Ok(());
+    }
+    #[cfg(unix)]
+    {
+        daemon::Daemon::new(root, args.mode, args.max_workers).serve()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (root, args.mode, args.max_workers);
+        Err(anyhow!("mako daemon is not supported on this platform"))
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 use std::fmt::{Debug, Formatter};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
@@ -19,8 +19,9 @@ use crate::ast::file::{win_path, File};
 use crate::ast::js_ast::JsAst;
 use crate::build::analyze_deps::AnalyzeDepsResult;
 use crate::compiler::Context;
-use crate::config::ModuleIdStrategy;
-use crate::resolve::ResolverResource;
+use crate::config::{ChunkNaming, ModuleIdStrategy};
+use crate::resolve::{ResolvedResource, ResolverResource};
+use crate::utils::hash::content_hash;
 
 pub type Dependencies = HashSet<Dependency>;
 
@@ -130,12 +131,38 @@ impl From<&NamedExport> for NamedExportType {
 pub struct ImportOptions {
     pub chunk_name: Option<String>,
     pub ignore: bool,
+    /// the async chunk is prefetched when the importer is executed
+    pub prefetch: bool,
+    /// the async chunk is preloaded when the importer is executed
+    pub preload: bool,
 }
 
 impl ImportOptions {
     pub fn get_chunk_name(&self) -> &Option<String> {
         &self.chunk_name
     }
+
+    /// The id of the async chunk of the imported module, the chunk names are kept readable
+    /// with the route chunk naming
+    pub fn chunk_id(&self, module_id: &ModuleId, context: &Arc<Context>) -> String {
+        match &self.chunk_name {
+            Some(chunk_name) if context.config.chunk_naming == ChunkNaming::Route => {
+                chunk_name.clone()
+            }
+            Some(chunk_name) => generate_module_id(chunk_name, context),
+            None => module_id.generate(context),
+        }
+    }
+
+    /// The options deciding the chunk, the imports of a module differing only in the resource
+    /// hints share the same chunk
+    pub fn chunk_options(&self) -> ImportOptions {
+        ImportOptions {
+            prefetch: false,
+            preload: false,
+            ..self.clone()
+        }
+    }
 }
 
 #[derive(Eq, Hash, PartialEq, Serialize, Debug, Clone)]
@@ -212,50 +239,112 @@ impl Default for ModuleInfo {
     }
 }
 
-fn md5_hash(source_str: &str, lens: usize) -> String {
-    format!("{:x}", md5::compute(source_str))
-        .chars()
-        .take(lens)
-        .collect::<String>()
+impl ModuleInfo {
+    /// Whether the module is esm in node's view, i.e. `.mjs` files or files in a package
+    /// with `"type": "module"`. In these modules, the default import of a cjs module is
+    /// always `module.exports`, even if the cjs module has the `__esModule` mark.
+    pub fn is_node_esm(&self) -> bool {
+        if matches!(self.file.extname.as_str(), "mjs" | "mts") {
+            return true;
+        }
+        match &self.resolved_resource {
+            Some(ResolverResource::Resolved(ResolvedResource(resolution))) => resolution
+                .package_json()
+                .and_then(|pkg| {
+                    pkg.raw_json()
+                        .get("type")
+                        .and_then(|t| t.as_str())
+                        .map(|t| t == "module")
+                })
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    /// A copy of the info without the ast and the source map chain, e.g. to keep the module
+    /// graph after the asts are printed, the fields are destructured so that the new ones are
+    /// not missed
+    pub fn clone_without_ast(&self) -> Self {
+        let ModuleInfo {
+            ast: _,
+            file,
+            deps,
+            external,
+            raw,
+            raw_hash,
+            top_level_await,
+            is_async,
+            is_ignored,
+            resolved_resource,
+            source_map_chain: _,
+        } = self;
+        ModuleInfo {
+            ast: ModuleAst::None,
+            file: file.clone(),
+            deps: deps.clone(),
+            external: external.clone(),
+            raw: raw.clone(),
+            raw_hash: *raw_hash,
+            top_level_await: *top_level_await,
+            is_async: *is_async,
+            is_ignored: *is_ignored,
+            resolved_resource: resolved_resource.clone(),
+            source_map_chain: vec![],
+        }
+    }
+}
+
+fn hash_module_id(origin_module_id: &str, context: &Arc<Context>) -> String {
+    let output = &context.config.output;
+    content_hash(
+        relative_to_root(origin_module_id, &context.root),
+        output.hash_function,
+        output.hash_length(),
+    )
 }
 
 pub fn generate_module_id(origin_module_id: &str, context: &Arc<Context>) -> String {
     match context.config.module_id_strategy {
-        ModuleIdStrategy::Hashed => md5_hash(origin_module_id, 8),
+        // hash the path relative to root so the ids don't depend on where the project is
+        ModuleIdStrategy::Hashed => hash_module_id(origin_module_id, context),
+        // readable ids for debugging usage
         ModuleIdStrategy::Named => {
-            // readable ids for debugging usage
             let absolute_path = PathBuf::from(origin_module_id);
             let relative_path = diff_paths(&absolute_path, &context.root).unwrap_or(absolute_path);
             win_path(relative_path.to_str().unwrap())
         }
-        ModuleIdStrategy::Numeric => {
+        ModuleIdStrategy::Numeric | ModuleIdStrategy::Deterministic => {
             let numeric_ids_map = context.numeric_ids_map.read().unwrap();
             if let Some(numeric_id) = numeric_ids_map.get(origin_module_id) {
                 numeric_id.to_string()
             } else {
-                md5_hash(origin_module_id, 8)
+                hash_module_id(origin_module_id, context)
             }
         }
     }
 }
 
-pub fn relative_to_root(module_path: &String, root: &PathBuf) -> String {
+/// The path relative to root with unix separators, e.g. /root/src/a.ts => ./src/a.ts,
+/// which is the same on every machine the project is built on
+pub fn relative_to_root(module_path: &str, root: &Path) -> String {
     let absolute_path = PathBuf::from(module_path);
     let relative_path = diff_paths(&absolute_path, root).unwrap_or(absolute_path);
     // diff_paths result always starts with ".."/"." or not
-    if relative_path.starts_with("..") || relative_path.starts_with(".") {
-        relative_path.to_string_lossy().to_string()
+    let relative_path = if relative_path.starts_with("..") || relative_path.starts_with(".") {
+        relative_path
     } else {
-        PathBuf::from(".")
-            .join(relative_path)
-            .to_string_lossy()
-            .to_string()
-    }
+        PathBuf::from(".").join(relative_path)
+    };
+    win_path(&relative_path.to_string_lossy())
 }
 
+/// Module ids are cloned a lot (graph keys, deps, chunks, stats...), so the
+/// underlying string is shared instead of being copied on every clone.
+pub type ModuleIdStr = Arc<str>;
+
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
 pub struct ModuleId {
-    pub id: String,
+    pub id: ModuleIdStr,
 }
 
 impl Ord for ModuleId {
@@ -273,7 +362,7 @@ impl PartialOrd for ModuleId {
 impl ModuleId {
     // we use absolute path as module id now
     pub fn new(id: String) -> Self {
-        Self { id }
+        Self { id: id.into() }
     }
 
     pub fn generate(&self, context: &Arc<Context>) -> String {
@@ -283,32 +372,36 @@ impl ModuleId {
 
     pub fn from_path(path_buf: PathBuf) -> Self {
         Self {
-            id: path_buf.to_string_lossy().to_string(),
+            id: path_buf.to_string_lossy().into(),
         }
     }
 
     // FIXME: 这里暂时直接通过 module_id 转换为 path，后续如果改了逻辑要记得改
     pub fn to_path(&self) -> PathBuf {
-        PathBuf::from(self.id.clone())
+        PathBuf::from(&*self.id)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.id
     }
 }
 
 impl From<String> for ModuleId {
     fn from(id: String) -> Self {
-        Self { id }
+        Self { id: id.into() }
     }
 }
 
 impl From<&str> for ModuleId {
     fn from(id: &str) -> Self {
-        Self { id: id.to_string() }
+        Self { id: id.into() }
     }
 }
 
 impl From<PathBuf> for ModuleId {
     fn from(path: PathBuf) -> Self {
         Self {
-            id: path.to_string_lossy().to_string(),
+            id: path.to_string_lossy().into(),
         }
     }
 }
@@ -407,6 +500,16 @@ impl Module {
         self.info = info;
     }
 
+    /// A copy of the module without the ast, see [ModuleInfo::clone_without_ast]
+    pub fn clone_without_ast(&self) -> Self {
+        Module {
+            id: self.id.clone(),
+            is_entry: self.is_entry,
+            info: self.info.as_ref().map(ModuleInfo::clone_without_ast),
+            side_effects: self.side_effects,
+        }
+    }
+
     pub fn is_external(&self) -> bool {
         self.info
             .as_ref()
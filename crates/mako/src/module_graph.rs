@@ -1,17 +1,35 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
+use std::path::Path;
 
+use clap::ValueEnum;
 use fixedbitset::FixedBitSet;
 use petgraph::graph::{DefaultIx, NodeIndex};
 use petgraph::prelude::{Dfs, EdgeRef};
 use petgraph::stable_graph::{StableDiGraph, WalkNeighbors};
 use petgraph::visit::IntoEdgeReferences;
 use petgraph::Direction;
+use serde_json::json;
 use tracing::{debug, warn};
 
-use crate::module::{Dependencies, Dependency, Module, ModuleId};
+use crate::module::{relative_to_root, Dependencies, Dependency, Module, ModuleId};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GraphDumpFormat {
+    Dot,
+    Json,
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphDumpOptions {
+    pub format: GraphDumpFormat,
+    /// only keep modules whose path relative to root starts with this prefix
+    pub filter: Option<String>,
+    /// only keep modules which are at most `depth` imports away from the entries
+    pub depth: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
 pub struct ModuleGraph {
     pub id_index_map: HashMap<ModuleId, NodeIndex<DefaultIx>>,
     pub graph: StableDiGraph<Module, Dependencies>,
@@ -28,11 +46,12 @@ impl ModuleGraph {
     }
 
     pub fn get_entry_modules(&self) -> Vec<ModuleId> {
-        self.entries.iter().cloned().collect()
+        let mut entries = self.entries.iter().cloned().collect::<Vec<_>>();
+        entries.sort();
+        entries
     }
 
     pub fn add_module(&mut self, module: Module) {
-        // TODO: module.id 能否用引用以减少内存占用？
         let id_for_map = module.id.clone();
         let id_for_entry = module.id.clone();
         let is_entry = module.is_entry;
@@ -43,6 +62,46 @@ impl ModuleGraph {
         }
     }
 
+    /// Rebuilds the graph in the order of the module ids. The modules are added in the order the
+    /// parallel build tasks complete, which would otherwise leak into the iteration orders of the
+    /// modules and the dependents, and then into the outputs, e.g. the mangled exports
+    pub fn sort(&mut self) {
+        let (node_count, edge_count) = (self.graph.node_count(), self.graph.edge_count());
+        let mut graph = std::mem::replace(
+            &mut self.graph,
+            StableDiGraph::with_capacity(node_count, edge_count),
+        );
+        let mut edges = graph
+            .edge_indices()
+            .map(|edge| {
+                let (from, to) = graph.edge_endpoints(edge).unwrap();
+                (graph[from].id.clone(), graph[to].id.clone(), edge)
+            })
+            .collect::<Vec<_>>();
+        edges.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        let edges = edges
+            .into_iter()
+            .map(|(from, to, edge)| (from, to, graph.remove_edge(edge).unwrap()))
+            .collect::<Vec<_>>();
+
+        let mut nodes = graph.node_indices().collect::<Vec<_>>();
+        nodes.sort_by(|a, b| graph[*a].id.cmp(&graph[*b].id));
+        self.id_index_map.clear();
+        for node in nodes {
+            let module = graph.remove_node(node).unwrap();
+            let module_id = module.id.clone();
+            let index = self.graph.add_node(module);
+            self.id_index_map.insert(module_id, index);
+        }
+        for (from, to, dependencies) in edges {
+            self.graph.add_edge(
+                self.id_index_map[&from],
+                self.id_index_map[&to],
+                dependencies,
+            );
+        }
+    }
+
     pub fn has_module(&self, module_id: &ModuleId) -> bool {
         self.id_index_map.contains_key(module_id)
     }
@@ -57,6 +116,10 @@ impl ModuleGraph {
         self.graph.node_weights().collect()
     }
 
+    pub fn modules_mut(&mut self) -> Vec<&mut Module> {
+        self.graph.node_weights_mut().collect()
+    }
+
     pub fn remove_module_and_deps(&mut self, module_id: &ModuleId) -> Module {
         let mut deps_module_ids = vec![];
         self.get_dependencies(module_id)
@@ -76,6 +139,7 @@ impl ModuleGraph {
             .id_index_map
             .remove(module_id)
             .unwrap_or_else(|| panic!("module_id {:?} not found in the module graph", module_id));
+        self.entries.remove(module_id);
         self.graph.remove_node(index).unwrap()
     }
 
@@ -401,6 +465,101 @@ impl ModuleGraph {
     pub fn dfs(&self, start: &ModuleId) -> Dfs<NodeIndex, FixedBitSet> {
         Dfs::new(&self.graph, *self.id_index_map.get(start).unwrap())
     }
+
+    /// Dumps the (filtered) module graph as DOT or JSON, module ids are printed
+    /// relative to `root` to keep the output readable.
+    pub fn dump(&self, options: &GraphDumpOptions, root: &Path) -> String {
+        let nodes = self.dump_nodes(options, root);
+        let mut edges = self
+            .graph
+            .edge_references()
+            .filter(|edge| nodes.contains_key(&edge.source()) && nodes.contains_key(&edge.target()))
+            .map(|edge| {
+                let mut sources = edge
+                    .weight()
+                    .iter()
+                    .map(|dep| dep.source.clone())
+                    .collect::<Vec<_>>();
+                sources.sort();
+                (&nodes[&edge.source()], &nodes[&edge.target()], sources)
+            })
+            .collect::<Vec<_>>();
+        edges.sort();
+        let mut names = nodes.values().collect::<Vec<_>>();
+        names.sort();
+
+        match options.format {
+            GraphDumpFormat::Dot => {
+                let mut dot = String::from("digraph {\n");
+                for name in names {
+                    dot.push_str(&format!("    {};\n", dot_id(name)));
+                }
+                for (from, to, _) in edges {
+                    dot.push_str(&format!("    {} -> {};\n", dot_id(from), dot_id(to)));
+                }
+                dot.push_str("}\n");
+                dot
+            }
+            GraphDumpFormat::Json => {
+                let edges = edges
+                    .into_iter()
+                    .map(
+                        |(from, to, sources)| json!({ "from": from, "to": to, "sources": sources }),
+                    )
+                    .collect::<Vec<_>>();
+                serde_json::to_string_pretty(&json!({ "nodes": names, "edges": edges })).unwrap()
+            }
+        }
+    }
+
+    fn dump_nodes(&self, options: &GraphDumpOptions, root: &Path) -> HashMap<NodeIndex, String> {
+        let candidates: Vec<NodeIndex> = match options.depth {
+            Some(depth) => {
+                let mut visited = HashSet::new();
+                let mut queue = self
+                    .entries
+                    .iter()
+                    .filter_map(|id| self.id_index_map.get(id))
+                    .map(|idx| (*idx, 0))
+                    .collect::<VecDeque<_>>();
+                while let Some((idx, level)) = queue.pop_front() {
+                    if !visited.insert(idx) || level == depth {
+                        continue;
+                    }
+                    for next in self.graph.neighbors_directed(idx, Direction::Outgoing) {
+                        queue.push_back((next, level + 1));
+                    }
+                }
+                visited.into_iter().collect()
+            }
+            None => self.graph.node_indices().collect(),
+        };
+
+        let root = root.to_path_buf();
+        let filter = options
+            .filter
+            .as_ref()
+            .map(|filter| filter.trim_start_matches("./"));
+        candidates
+            .into_iter()
+            .map(|idx| (idx, relative_to_root(&self.graph[idx].id.id, &root)))
+            .filter(|(_, name)| {
+                filter.map_or(true, |filter| {
+                    name.trim_start_matches("./").starts_with(filter)
+                })
+            })
+            .collect()
+    }
+}
+
+/// The quoted DOT id, the double quotes are escaped, and the backslashes too since they start
+/// the escape sequences of the labels, e.g. `\N` of the windows paths
+fn dot_id(name: &str) -> String {
+    let escaped = name
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
 }
 
 impl fmt::Display for ModuleGraph {
@@ -425,3 +584,15 @@ impl Default for ModuleGraph {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dot_id() {
+        assert_eq!(dot_id("./src/index.ts"), r#""./src/index.ts""#);
+        assert_eq!(dot_id(r#"C:\Node\"a".ts"#), r#""C:\\Node\\\"a\".ts""#);
+        assert_eq!(dot_id("./src/你好.ts"), r#""./src/你好.ts""#);
+    }
+}
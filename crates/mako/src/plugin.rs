@@ -1,22 +1,25 @@
 use std::any::Any;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use glob_match::glob_match;
+use regex::Regex;
 use serde::Serialize;
 use swc_core::common::errors::Handler;
 use swc_core::common::Mark;
 use swc_core::ecma::ast::Module;
 
 use crate::ast::file::{Content, File};
-use crate::compiler::{Args, Compiler, Context};
+use crate::compiler::{Args, Compiler, Context, EmittedAsset};
 use crate::config::Config;
 use crate::generate::chunk_graph::ChunkGraph;
 use crate::generate::generate_chunks::ChunkFile;
-use crate::module::{Dependency, ModuleAst, ModuleId};
+use crate::module::{relative_to_root, Dependency, ImportType, ModuleAst, ModuleId, ResolveType};
 use crate::module_graph::ModuleGraph;
-use crate::resolve::ResolverResource;
+use crate::resolve::{resolve, ResolverResource};
 use crate::stats::StatsJsonMap;
+use crate::utils::logger::print_warning;
 
 #[derive(Debug)]
 pub struct PluginLoadParam<'a> {
@@ -47,6 +50,200 @@ pub struct PluginGenerateEndParams {
     pub stats: StatsJsonMap,
 }
 
+/// A source map file written to the output with the chunk it belongs to
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginEmittedSourceMap {
+    pub chunk: PathBuf,
+    pub source_map: PathBuf,
+    pub debug_id: Option<String>,
+}
+
+/// The delta of a rebuild in watch mode, the modules are the module ids and the chunks are the
+/// chunk files which contain the added or modified modules
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginRebuildEndParams {
+    pub changed_files: Vec<String>,
+    pub added_modules: Vec<String>,
+    pub removed_modules: Vec<String>,
+    pub modified_modules: Vec<String>,
+    pub updated_chunks: Vec<String>,
+    pub time: i64,
+    pub errors: Vec<String>,
+}
+
+/// The telemetry of a built module, `source_size` is the size of the loaded content and `size`
+/// is the size after the load transforms, in bytes
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginModuleBuiltParams {
+    pub path: String,
+    pub source_size: usize,
+    pub size: usize,
+    pub durations: PluginModuleBuiltDurations,
+    pub dependencies: usize,
+}
+
+/// The durations of the build phases of a module in ms
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginModuleBuiltDurations {
+    pub load: f64,
+    pub parse: f64,
+    pub transform: f64,
+    pub analyze_deps: f64,
+}
+
+/// The files the load, parse and transform hooks of a plugin run on, the patterns are globs
+/// matched against the paths relative to the root, e.g. `src/**/*.mdx`, or regexes matched
+/// against the absolute paths, e.g. `\.(png|jpe?g)$`
+#[derive(Debug, Default)]
+pub struct PluginFilter {
+    include: Vec<FilterPattern>,
+    exclude: Vec<FilterPattern>,
+}
+
+#[derive(Debug)]
+pub enum FilterPattern {
+    Glob(String),
+    Regex(Regex),
+}
+
+impl FilterPattern {
+    pub fn glob(pattern: &str) -> Self {
+        FilterPattern::Glob(pattern.trim_start_matches("./").to_string())
+    }
+
+    pub fn regex(pattern: &str) -> Result<Self> {
+        Regex::new(pattern)
+            .map(FilterPattern::Regex)
+            .map_err(|e| anyhow!("Invalid plugin filter /{}/: {}", pattern, e))
+    }
+
+    fn is_match(&self, path: &str, relative_path: &str) -> bool {
+        match self {
+            FilterPattern::Glob(glob) => glob_match(glob, relative_path),
+            FilterPattern::Regex(regex) => regex.is_match(path),
+        }
+    }
+}
+
+impl PluginFilter {
+    pub fn new(include: Vec<FilterPattern>, exclude: Vec<FilterPattern>) -> Self {
+        Self { include, exclude }
+    }
+
+    /// Whether the file is included and not excluded, all the files are included when there is
+    /// no include pattern
+    pub fn is_match(&self, path: &str, root: &PathBuf) -> bool {
+        let relative_path = relative_to_root(path, root);
+        let relative_path = relative_path.trim_start_matches("./");
+        (self.include.is_empty() || self.include.iter().any(|p| p.is_match(path, relative_path)))
+            && !self.exclude.iter().any(|p| p.is_match(path, relative_path))
+    }
+}
+
+/// The handle of a plugin to the build, the methods only lock the state they change, so they
+/// are safe to call from the concurrent build_module tasks, e.g. in the load and transform hooks
+pub struct PluginContext<'a> {
+    name: &'a str,
+    module_path: Option<&'a Path>,
+    context: &'a Arc<Context>,
+}
+
+impl<'a> PluginContext<'a> {
+    pub fn new(name: &'a str, context: &'a Arc<Context>) -> Self {
+        Self {
+            name,
+            module_path: None,
+            context,
+        }
+    }
+
+    /// Scope the handle to the module being built, the watch files are then the dependencies of
+    /// the module instead of the whole build
+    pub fn with_module(mut self, module_path: &'a Path) -> Self {
+        self.module_path = Some(module_path);
+        self
+    }
+
+    /// Emit a file to the output dir, e.g. a generated manifest, the file name is relative to
+    /// the output dir and the last emitted content of the same name wins
+    pub fn emit_asset(&self, file_name: &str, content: impl Into<Vec<u8>>) -> Result<()> {
+        let path = Path::new(file_name);
+        if !path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(anyhow!(
+                "[{}] the emitted asset {} must be relative to the output dir",
+                self.name,
+                file_name
+            ));
+        }
+        let file_name = path
+            .components()
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let module_id = self
+            .module_path
+            .map(|module_path| ModuleId::new(module_path.to_string_lossy().to_string()));
+        self.context.emitted_assets.lock().unwrap().insert(
+            file_name,
+            EmittedAsset {
+                content: content.into(),
+                module_id,
+            },
+        );
+        Ok(())
+    }
+
+    /// Watch a file read by the plugin, the module is rebuilt when the file changes in watch
+    /// mode, or all the modules are without the module scope
+    pub fn add_watch_file(&self, path: PathBuf) {
+        match self.module_path {
+            Some(module_path) => self.context.add_file_dependency(module_path, path),
+            None => self.context.add_build_dependency(path),
+        }
+    }
+
+    /// Watch a dir scanned by the plugin, the module is rebuilt when the files are added to or
+    /// removed from the dir, the dir is only watched in the module scope
+    pub fn add_watch_dir(&self, dir: PathBuf) {
+        if let Some(module_path) = self.module_path {
+            self.context.add_dir_dependency(module_path, dir);
+        }
+    }
+
+    pub fn warn(&self, message: &str) {
+        match self.module_path {
+            Some(module_path) => print_warning(&format!(
+                "[{}] {}: {}",
+                self.name,
+                relative_to_root(&module_path.to_string_lossy(), &self.context.root),
+                message
+            )),
+            None => print_warning(&format!("[{}] {}", self.name, message)),
+        }
+    }
+
+    /// Resolve the source as an import of the importer, with the resolve_id hooks of the
+    /// plugins, so the resolve_id hook which calls it should skip the sources it resolves
+    pub fn resolve(&self, source: &str, importer: &str) -> Result<ResolverResource> {
+        let dep = Dependency {
+            source: source.to_string(),
+            resolve_as: None,
+            resolve_type: ResolveType::Import(ImportType::Default),
+            order: 0,
+            span: None,
+        };
+        resolve(importer, &dep, &self.context.resolvers, self.context)
+    }
+}
+
 pub trait Plugin: Any + Send + Sync {
     fn name(&self) -> &str;
 
@@ -54,6 +251,11 @@ pub trait Plugin: Any + Send + Sync {
         None
     }
 
+    /// The load, parse and transform hooks are skipped for the files not matched by the filter
+    fn filter(&self) -> Option<&PluginFilter> {
+        None
+    }
+
     fn modify_config(&self, _config: &mut Config, _root: &Path, _args: &Args) -> Result<()> {
         Ok(())
     }
@@ -151,10 +353,44 @@ pub trait Plugin: Any + Send + Sync {
         Ok(())
     }
 
+    /// Called after the chunks and their separate source maps are written in build mode, e.g.
+    /// to upload the source maps to the error tracking services and delete them from the output
+    fn source_maps_emitted(
+        &self,
+        _source_maps: &[PluginEmittedSourceMap],
+        _context: &Arc<Context>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// The last hook of a build, called after `generate_end` and `write_bundle`, including the
+    /// rebuilds in watch mode, unlike `build_success` which is only called for the full builds;
+    /// the stats has the start and end time of the build
+    fn done(&self, _stats: &StatsJsonMap, _context: &Arc<Context>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called for each changed file in watch mode before the rebuild, the event is one of
+    /// `create`, `delete` and `update`
     fn watch_changes(&self, _id: &str, _event: &str, _context: &Arc<Context>) -> Result<()> {
         Ok(())
     }
 
+    /// Called after each module is built with its sizes, the durations of the build phases and
+    /// the count of its dependencies, e.g. to track the build performance of the modules
+    fn module_built(
+        &self,
+        _params: &PluginModuleBuiltParams,
+        _context: &Arc<Context>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called after each rebuild in watch mode, including the failed ones with the errors
+    fn rebuild_end(&self, _params: &PluginRebuildEndParams, _context: &Arc<Context>) -> Result<()> {
+        Ok(())
+    }
+
     fn runtime_plugins(&self, _context: &Arc<Context>) -> Result<Vec<String>> {
         Ok(Vec::new())
     }
@@ -183,6 +419,12 @@ pub trait Plugin: Any + Send + Sync {
     fn before_write_fs(&self, _path: &Path, _content: &[u8]) -> Result<()> {
         Ok(())
     }
+
+    /// Replace the asset file to emit or inline, e.g. with an optimized copy of it, the
+    /// content hash of the returned file is used in the filename
+    fn transform_asset(&self, _file: &File, _context: &Arc<Context>) -> Result<Option<File>> {
+        Ok(None)
+    }
 }
 
 #[derive(Default)]
@@ -201,6 +443,18 @@ impl PluginDriver {
         Self { plugins }
     }
 
+    fn filtered_plugins<'a>(
+        &'a self,
+        path: &'a str,
+        context: &'a Arc<Context>,
+    ) -> impl Iterator<Item = &'a Arc<dyn Plugin>> {
+        self.plugins.iter().filter(move |plugin| {
+            plugin
+                .filter()
+                .map_or(true, |filter| filter.is_match(path, &context.root))
+        })
+    }
+
     pub fn next_build(&self, param: &NextBuildParam) -> bool {
         self.plugins.iter().all(|p| p.next_build(param))
     }
@@ -221,7 +475,8 @@ impl PluginDriver {
     }
 
     pub fn load(&self, param: &PluginLoadParam, context: &Arc<Context>) -> Result<Option<Content>> {
-        for plugin in &self.plugins {
+        let path = param.file.path.to_string_lossy();
+        for plugin in self.filtered_plugins(&path, context) {
             let ret = plugin.load(param, context)?;
             if ret.is_some() {
                 return Ok(ret);
@@ -235,7 +490,8 @@ impl PluginDriver {
         param: &PluginParseParam,
         context: &Arc<Context>,
     ) -> Result<Option<ModuleAst>> {
-        for plugin in &self.plugins {
+        let path = param.file.path.to_string_lossy();
+        for plugin in self.filtered_plugins(&path, context) {
             let ret = plugin.parse(param, context)?;
             if ret.is_some() {
                 return Ok(ret);
@@ -250,7 +506,7 @@ impl PluginDriver {
         ast: &mut Module,
         context: &Arc<Context>,
     ) -> Result<()> {
-        for plugin in &self.plugins {
+        for plugin in self.filtered_plugins(param.path, context) {
             plugin.transform_js(param, ast, context)?;
         }
         Ok(())
@@ -339,6 +595,24 @@ impl PluginDriver {
         Ok(())
     }
 
+    pub fn source_maps_emitted(
+        &self,
+        source_maps: &[PluginEmittedSourceMap],
+        context: &Arc<Context>,
+    ) -> Result<()> {
+        for plugin in &self.plugins {
+            plugin.source_maps_emitted(source_maps, context)?;
+        }
+        Ok(())
+    }
+
+    pub fn done(&self, stats: &StatsJsonMap, context: &Arc<Context>) -> Result<()> {
+        for plugin in &self.plugins {
+            plugin.done(stats, context)?;
+        }
+        Ok(())
+    }
+
     pub fn watch_changes(&self, id: &str, event: &str, context: &Arc<Context>) -> Result<()> {
         for plugin in &self.plugins {
             plugin.watch_changes(id, event, context)?;
@@ -346,6 +620,28 @@ impl PluginDriver {
         Ok(())
     }
 
+    pub fn module_built(
+        &self,
+        params: &PluginModuleBuiltParams,
+        context: &Arc<Context>,
+    ) -> Result<()> {
+        for plugin in &self.plugins {
+            plugin.module_built(params, context)?;
+        }
+        Ok(())
+    }
+
+    pub fn rebuild_end(
+        &self,
+        params: &PluginRebuildEndParams,
+        context: &Arc<Context>,
+    ) -> Result<()> {
+        for plugin in &self.plugins {
+            plugin.rebuild_end(params, context)?;
+        }
+        Ok(())
+    }
+
     pub fn generate_begin(&self, context: &Arc<Context>) -> Result<()> {
         for plugin in &self.plugins {
             plugin.generate_begin(context)?;
@@ -413,13 +709,23 @@ impl PluginDriver {
         Ok(())
     }
 
+    pub fn transform_asset(&self, file: &File, context: &Arc<Context>) -> Result<Option<File>> {
+        for p in &self.plugins {
+            let ret = p.transform_asset(file, context)?;
+            if ret.is_some() {
+                return Ok(ret);
+            }
+        }
+        Ok(None)
+    }
+
     pub fn load_transform(
         &self,
         content: &mut Content,
         path: &str,
         context: &Arc<Context>,
     ) -> Result<Content> {
-        for plugin in &self.plugins {
+        for plugin in self.filtered_plugins(path, context) {
             if let Some(transformed) = plugin.load_transform(content, path, context)? {
                 *content = transformed;
             }
@@ -427,3 +733,82 @@ impl PluginDriver {
         Ok(content.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plugin_context() {
+        let context = Arc::new(Context::default());
+        let plugin_context = PluginContext::new("test", &context);
+        plugin_context.emit_asset("./manifest.json", "{}").unwrap();
+        assert!(plugin_context.emit_asset("../escaped.json", "{}").is_err());
+        assert!(plugin_context
+            .emit_asset("/root/escaped.json", "{}")
+            .is_err());
+        plugin_context.add_watch_file(PathBuf::from("/root/tailwind.config.js"));
+        let module_context =
+            PluginContext::new("test", &context).with_module(Path::new("/root/src/a.css"));
+        module_context.add_watch_file(PathBuf::from("/root/src/_vars.css"));
+        module_context.emit_asset("css/a.css.map", "{}").unwrap();
+
+        assert_eq!(
+            context.emitted_assets.lock().unwrap()["manifest.json"].content,
+            b"{}".to_vec()
+        );
+        assert!(context
+            .build_dependencies
+            .read()
+            .unwrap()
+            .contains(&PathBuf::from("/root/tailwind.config.js")));
+        assert!(context
+            .file_dependencies
+            .read()
+            .unwrap()
+            .contains_key(&PathBuf::from("/root/src/_vars.css")));
+
+        // the assets of the module are emitted again when it's rebuilt
+        context.clear_emitted_assets(&ModuleId::new("/root/src/a.css".to_string()));
+        let emitted_assets = context.emitted_assets.lock().unwrap();
+        assert!(emitted_assets.contains_key("manifest.json"));
+        assert!(!emitted_assets.contains_key("css/a.css.map"));
+    }
+
+    #[test]
+    fn test_plugin_filter() {
+        let root = PathBuf::from("/root");
+        let filter = PluginFilter::new(
+            vec![
+                FilterPattern::glob("src/**/*.mdx"),
+                FilterPattern::regex(r"\.svg$").unwrap(),
+            ],
+            vec![FilterPattern::glob("**/node_modules/**")],
+        );
+        assert!(filter.is_match("/root/src/docs/a.mdx", &root));
+        assert!(filter.is_match("/root/assets/logo.svg", &root));
+        assert!(!filter.is_match("/root/src/index.ts", &root));
+        assert!(!filter.is_match("/root/node_modules/foo/logo.svg", &root));
+    }
+
+    #[test]
+    fn test_plugin_filter_without_include() {
+        let root = PathBuf::from("/root");
+        let filter = PluginFilter::new(vec![], vec![FilterPattern::glob("./src/legacy/**")]);
+        assert!(filter.is_match("/root/src/index.ts", &root));
+        assert!(!filter.is_match("/root/src/legacy/index.ts", &root));
+    }
+
+    #[test]
+    fn test_slash_wrapped_glob() {
+        // only the RegExps of js are regexes, the strings are always globs
+        let root = PathBuf::from("/root");
+        let filter = PluginFilter::new(vec![FilterPattern::glob("/src/")], vec![]);
+        assert!(!filter.is_match("/root/src/index.ts", &root));
+    }
+
+    #[test]
+    fn test_invalid_plugin_filter() {
+        assert!(FilterPattern::regex("(").is_err());
+    }
+}
@@ -1,20 +1,42 @@
 pub mod async_runtime;
 pub mod bundless_compiler;
+pub mod cjs_named_exports;
+pub mod conditional_compilation;
 pub mod context_module;
 pub mod copy;
+pub mod data_uri;
 pub mod detect_circular_dependence;
+pub mod duplicate_modules;
 pub mod duplicate_package_checker;
 pub mod emotion;
+pub mod extra_outputs;
+pub mod font_subset;
+pub mod fs_routes;
+pub mod graphql;
 pub mod graphviz;
 pub mod hmr_runtime;
 pub mod ignore;
 pub mod import;
 pub mod invalid_webpack_syntax;
+pub mod legacy;
+pub mod lint;
 pub mod manifest;
+pub mod metafile;
 pub mod minifish;
+pub mod mpa;
+pub mod node_addon_runtime;
+pub mod performance_budgets;
 pub mod progress;
+pub mod purge_css;
+pub mod remote_modules;
 pub mod require_context;
+pub mod resolve_cache;
+pub mod rsc_client;
 pub mod runtime;
+pub mod service_worker;
+pub mod size_report;
+pub mod ssr;
 pub mod ssu;
 pub mod tree_shaking;
+pub mod type_check;
 pub mod wasm_runtime;
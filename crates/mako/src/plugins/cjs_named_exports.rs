@@ -0,0 +1,203 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use swc_core::common::GLOBALS;
+use swc_core::ecma::ast::{
+    ExportSpecifier, ImportSpecifier, Module as SwcModule, ModuleDecl, ModuleExportName, ModuleItem,
+};
+use swc_core::ecma::visit::VisitWith;
+
+use crate::ast::utils::is_esm;
+use crate::compiler::Context;
+use crate::module::{relative_to_root, ModuleAst, ModuleId};
+use crate::module_graph::ModuleGraph;
+use crate::plugin::Plugin;
+use crate::utils::logger::print_warning;
+use crate::visitors::cjs_exports_lexer::CjsExportsLexer;
+
+/**
+ * Warn about the named imports from commonjs modules which can't be found in the
+ * exports detected by the cjs-module-lexer style analysis, they are `undefined` at
+ * runtime and are probably typos or default exports imported as named ones. The build
+ * fails with them when `emit_error` is true.
+ */
+pub struct CjsNamedExportsChecker {
+    pub emit_error: bool,
+}
+
+impl Plugin for CjsNamedExportsChecker {
+    fn name(&self) -> &str {
+        "cjs_named_exports_checker"
+    }
+
+    fn generate_begin(&self, context: &Arc<Context>) -> Result<()> {
+        if context.args.watch {
+            return Ok(());
+        }
+
+        let module_graph = context.module_graph.read().unwrap();
+        let mut cache = HashMap::new();
+        let mut warnings = vec![];
+        GLOBALS.set(&context.meta.script.globals, || {
+            for module in module_graph.modules() {
+                let Some(info) = &module.info else {
+                    continue;
+                };
+                // users can't fix the imports in node_modules
+                if info.file.is_under_node_modules {
+                    continue;
+                }
+                let ModuleAst::Script(ast) = &info.ast else {
+                    continue;
+                };
+                if !is_esm(&ast.ast) {
+                    continue;
+                }
+                for (source, name) in named_imports(&ast.ast) {
+                    let Some(dep_id) = resolve_source(&module_graph, &module.id, &source) else {
+                        continue;
+                    };
+                    let Some(exports) =
+                        cjs_exports(&module_graph, &dep_id, &mut cache, &mut HashSet::new())
+                    else {
+                        continue;
+                    };
+                    if !exports.contains(&name) {
+                        warnings.push(format!(
+                            "export '{}' was not found in '{}' (imported by {})",
+                            name,
+                            source,
+                            relative_to_root(module.id.as_str(), &context.root)
+                        ));
+                    }
+                }
+            }
+        });
+
+        warnings.sort();
+        if self.emit_error && !warnings.is_empty() {
+            return Err(anyhow!(warnings.join("\n")));
+        }
+        for warning in warnings {
+            print_warning(&warning);
+        }
+        Ok(())
+    }
+}
+
+fn named_imports(ast: &SwcModule) -> Vec<(String, String)> {
+    let mut imports = vec![];
+    for item in &ast.body {
+        match item {
+            ModuleItem::ModuleDecl(ModuleDecl::Import(import)) if !import.type_only => {
+                for specifier in &import.specifiers {
+                    if let ImportSpecifier::Named(named) = specifier
+                        && !named.is_type_only
+                    {
+                        let name = match &named.imported {
+                            Some(imported) => export_name(imported),
+                            None => named.local.sym.to_string(),
+                        };
+                        imports.push((import.src.value.to_string(), name));
+                    }
+                }
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(export)) if !export.type_only => {
+                if let Some(src) = &export.src {
+                    for specifier in &export.specifiers {
+                        if let ExportSpecifier::Named(named) = specifier
+                            && !named.is_type_only
+                        {
+                            imports.push((src.value.to_string(), export_name(&named.orig)));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    imports
+        .into_iter()
+        .filter(|(_, name)| name != "default")
+        .collect()
+}
+
+fn export_name(name: &ModuleExportName) -> String {
+    match name {
+        ModuleExportName::Ident(ident) => ident.sym.to_string(),
+        ModuleExportName::Str(str) => str.value.to_string(),
+    }
+}
+
+fn resolve_source(
+    module_graph: &ModuleGraph,
+    module_id: &ModuleId,
+    source: &str,
+) -> Option<ModuleId> {
+    module_graph
+        .get_dependencies(module_id)
+        .into_iter()
+        .find(|(_, dep)| dep.source == source)
+        .map(|(id, _)| id.clone())
+}
+
+/// Returns `None` if the module is not commonjs or its exports can't be fully detected
+fn cjs_exports(
+    module_graph: &ModuleGraph,
+    module_id: &ModuleId,
+    cache: &mut HashMap<ModuleId, Option<HashSet<String>>>,
+    visiting: &mut HashSet<ModuleId>,
+) -> Option<HashSet<String>> {
+    if let Some(exports) = cache.get(module_id) {
+        return exports.clone();
+    }
+    // circular re-exports
+    if !visiting.insert(module_id.clone()) {
+        return None;
+    }
+
+    let exports = (|| {
+        let info = module_graph.get_module(module_id)?.info.as_ref()?;
+        if info.external.is_some() {
+            return None;
+        }
+        let ModuleAst::Script(ast) = &info.ast else {
+            return None;
+        };
+        if is_esm(&ast.ast) {
+            return None;
+        }
+        let mut lexer = CjsExportsLexer::new(ast.unresolved_mark);
+        ast.ast.visit_with(&mut lexer);
+        // nothing detected, e.g. exports via `this.a = 1`
+        if lexer.exports.unknown
+            || (lexer.exports.names.is_empty() && lexer.exports.reexports.is_empty())
+        {
+            return None;
+        }
+        let mut names = lexer.exports.names;
+        for source in &lexer.exports.reexports {
+            let dep_id = resolve_source(module_graph, module_id, source)?;
+            names.extend(cjs_exports(module_graph, &dep_id, cache, visiting)?);
+        }
+        Some(names)
+    })();
+
+    cache.insert(module_id.clone(), exports.clone());
+    exports
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_helper::setup_compiler;
+
+    #[test]
+    fn test_missing_named_import_error() {
+        let compiler = setup_compiler("test/build/cjs-named-exports", false);
+        let err = compiler.compile().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("export 'missing' was not found in './lib' (imported by index.ts)"));
+    }
+}
@@ -0,0 +1,292 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+
+use crate::ast::file::{Content, JsContent};
+use crate::compiler::Context;
+use crate::config::{Config, Mode, Platform};
+use crate::plugin::Plugin;
+
+/**
+ * Keep the code between `// #if <condition>` and `// #endif` only when the condition is
+ * active, with `#elif` and `#else` branches, the conditions are `resolve.conditions`, the mode
+ * and the platform, e.g. `// #if development && !worker`. The stripped lines are kept as empty
+ * lines so that the source maps still match. It's enabled by `conditionalCompilation`.
+ */
+pub struct ConditionalCompilationPlugin {}
+
+impl Plugin for ConditionalCompilationPlugin {
+    fn name(&self) -> &str {
+        "conditional_compilation"
+    }
+
+    fn load_transform(
+        &self,
+        content: &mut Content,
+        path: &str,
+        context: &Arc<Context>,
+    ) -> Result<Option<Content>> {
+        let source = match content {
+            Content::Js(JsContent { content, .. }) | Content::Css(content) => content,
+            Content::Assets(_) => return Ok(None),
+        };
+        // the packages are built already, the stray `#if` comments in them are not directives
+        if path.contains("node_modules") || !source.contains("#if") {
+            return Ok(None);
+        }
+        let conditions = active_conditions(&context.config);
+        let Some(stripped) = strip_inactive_code(source, &conditions)
+            .map_err(|e| anyhow!("Conditional compilation error in {}: {}", path, e))?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(match content {
+            Content::Js(js) => Content::Js(JsContent {
+                content: stripped,
+                is_jsx: js.is_jsx,
+            }),
+            _ => Content::Css(stripped),
+        }))
+    }
+}
+
+fn active_conditions(config: &Config) -> HashSet<String> {
+    let mode = match config.mode {
+        Mode::Development => "development",
+        Mode::Production => "production",
+    };
+    let platform = match config.platform {
+        Platform::Browser => "browser",
+        Platform::Node => "node",
+    };
+    config
+        .resolve
+        .conditions
+        .iter()
+        .map(|c| c.as_str())
+        .chain([mode, platform])
+        .map(|c| c.to_string())
+        .collect()
+}
+
+enum Directive<'a> {
+    If(&'a str),
+    Elif(&'a str),
+    Else,
+    Endif,
+}
+
+// `// #if a`, `/* #if a */`
+fn parse_directive(line: &str) -> Option<Directive> {
+    let line = line.trim();
+    let comment = if let Some(comment) = line.strip_prefix("//") {
+        comment
+    } else {
+        line.strip_prefix("/*")?.strip_suffix("*/")?
+    };
+    let directive = comment.trim().strip_prefix('#')?;
+    let (keyword, expr) = directive
+        .split_once(char::is_whitespace)
+        .map_or((directive, ""), |(keyword, expr)| (keyword, expr.trim()));
+    match keyword {
+        "if" => Some(Directive::If(expr)),
+        "elif" => Some(Directive::Elif(expr)),
+        "else" => Some(Directive::Else),
+        "endif" => Some(Directive::Endif),
+        _ => None,
+    }
+}
+
+// `a || b && !c`, `&&` binds tighter than `||`
+fn eval_condition(expr: &str, conditions: &HashSet<String>) -> Result<bool> {
+    if expr.is_empty() {
+        return Err(anyhow!("missing condition"));
+    }
+    expr.split("||")
+        .map(|any| {
+            any.split("&&")
+                .map(|term| {
+                    let term = term.trim();
+                    let name = term.trim_start_matches('!').trim();
+                    if name.is_empty()
+                        || !name
+                            .chars()
+                            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+                    {
+                        return Err(anyhow!("invalid condition \"{}\"", term));
+                    }
+                    let negated = (term.len() - term.trim_start_matches('!').len()) % 2 == 1;
+                    Ok(conditions.contains(name) != negated)
+                })
+                .try_fold(true, |all, term| Ok::<_, anyhow::Error>(term? && all))
+        })
+        .try_fold(false, |any, all| Ok(all? || any))
+}
+
+struct Branch {
+    // whether the enclosing code is kept
+    parent_active: bool,
+    // whether one of the branches has been taken
+    taken: bool,
+    active: bool,
+    has_else: bool,
+    line: usize,
+}
+
+/// Returns `None` when there is no directive in the source.
+fn strip_inactive_code(source: &str, conditions: &HashSet<String>) -> Result<Option<String>> {
+    let mut output = String::with_capacity(source.len());
+    let mut branches: Vec<Branch> = vec![];
+    let mut has_directive = false;
+
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        let line_no = index + 1;
+        let active = branches.last().map_or(true, |b| b.active);
+        let directive = parse_directive(line);
+        if directive.is_some() {
+            has_directive = true;
+        }
+        match directive {
+            Some(Directive::If(expr)) => {
+                let matched = active && eval_condition(expr, conditions)?;
+                branches.push(Branch {
+                    parent_active: active,
+                    taken: matched,
+                    active: matched,
+                    has_else: false,
+                    line: line_no,
+                });
+            }
+            Some(Directive::Elif(expr)) => {
+                let branch = branches
+                    .last_mut()
+                    .ok_or_else(|| anyhow!("#elif without #if at line {}", line_no))?;
+                if branch.has_else {
+                    return Err(anyhow!("#elif after #else at line {}", line_no));
+                }
+                let matched =
+                    branch.parent_active && !branch.taken && eval_condition(expr, conditions)?;
+                branch.taken |= matched;
+                branch.active = matched;
+            }
+            Some(Directive::Else) => {
+                let branch = branches
+                    .last_mut()
+                    .ok_or_else(|| anyhow!("#else without #if at line {}", line_no))?;
+                if branch.has_else {
+                    return Err(anyhow!("duplicate #else at line {}", line_no));
+                }
+                branch.has_else = true;
+                branch.active = branch.parent_active && !branch.taken;
+                branch.taken = true;
+            }
+            Some(Directive::Endif) => {
+                branches
+                    .pop()
+                    .ok_or_else(|| anyhow!("#endif without #if at line {}", line_no))?;
+            }
+            None if active => {
+                output.push_str(line);
+                continue;
+            }
+            None => {}
+        }
+        // keep the line breaks of the directives and the stripped lines
+        if line.ends_with("\r\n") {
+            output.push_str("\r\n");
+        } else if line.ends_with('\n') {
+            output.push('\n');
+        }
+    }
+
+    if let Some(branch) = branches.last() {
+        return Err(anyhow!("unclosed #if at line {}", branch.line));
+    }
+    Ok(has_directive.then_some(output))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{eval_condition, strip_inactive_code};
+
+    fn conditions(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_eval_condition() {
+        let conditions = conditions(&["development", "worker"]);
+        assert!(eval_condition("development", &conditions).unwrap());
+        assert!(!eval_condition("!worker", &conditions).unwrap());
+        assert!(eval_condition("react-server || worker", &conditions).unwrap());
+        assert!(!eval_condition("development && react-server", &conditions).unwrap());
+        assert!(eval_condition("react-server && node || !!worker", &conditions).unwrap());
+        assert!(eval_condition("", &conditions).is_err());
+        assert!(eval_condition("a b", &conditions).is_err());
+    }
+
+    #[test]
+    fn test_strip_inactive_code() {
+        let source = r#"a;
+// #if worker
+b;
+// #elif development
+c;
+/* #else */
+d;
+// #endif
+e;
+"#;
+        assert_eq!(
+            strip_inactive_code(source, &conditions(&["worker"])).unwrap(),
+            Some("a;\n\nb;\n\n\n\n\n\ne;\n".to_string())
+        );
+        assert_eq!(
+            strip_inactive_code(source, &conditions(&["development"])).unwrap(),
+            Some("a;\n\n\n\nc;\n\n\n\ne;\n".to_string())
+        );
+        assert_eq!(
+            strip_inactive_code(source, &conditions(&[])).unwrap(),
+            Some("a;\n\n\n\n\n\nd;\n\ne;\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_strip_nested() {
+        let source = "// #if a\n// #if b\nab;\n// #else\na;\n// #endif\n// #endif\n";
+        assert_eq!(
+            strip_inactive_code(source, &conditions(&["a"])).unwrap(),
+            Some("\n\n\n\na;\n\n\n".to_string())
+        );
+        assert_eq!(
+            strip_inactive_code(source, &conditions(&["b"])).unwrap(),
+            Some("\n\n\n\n\n\n\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_strip_without_directive() {
+        assert_eq!(
+            strip_inactive_code("// #iframe\n", &conditions(&[])).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_strip_errors() {
+        let error = |source: &str| {
+            strip_inactive_code(source, &conditions(&[]))
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(error("// #if a\n"), "unclosed #if at line 1");
+        assert_eq!(error("a;\n// #endif\n"), "#endif without #if at line 2");
+        assert_eq!(
+            error("// #if a\n// #else\n// #else\n// #endif\n"),
+            "duplicate #else at line 3"
+        );
+    }
+}
@@ -16,7 +16,7 @@ use crate::ast::utils::{is_commonjs_require, is_dynamic_import};
 use crate::ast::DUMMY_CTXT;
 use crate::build::load::JS_EXTENSIONS;
 use crate::compiler::Context;
-use crate::plugin::{Plugin, PluginLoadParam};
+use crate::plugin::{Plugin, PluginContext, PluginLoadParam};
 use crate::resolve::get_module_extensions;
 
 pub struct ContextModulePlugin {}
@@ -26,7 +26,7 @@ impl Plugin for ContextModulePlugin {
         "context_module"
     }
 
-    fn load(&self, param: &PluginLoadParam, _context: &Arc<Context>) -> Result<Option<Content>> {
+    fn load(&self, param: &PluginLoadParam, context: &Arc<Context>) -> Result<Option<Content>> {
         if let (Some(glob_pattern), true) = (
             param
                 .file
@@ -35,6 +35,10 @@ impl Plugin for ContextModulePlugin {
                 .find_map(|(k, v)| k.eq("glob").then_some(v)),
             param.file.pathname.is_dir(),
         ) {
+            // the map is expanded again as the files are added to or removed from the dir
+            PluginContext::new(self.name(), context)
+                .with_module(&param.file.path)
+                .add_watch_dir(param.file.pathname.clone());
             let glob_pattern = param.file.pathname.clone().join(glob_pattern);
             let paths = glob(glob_pattern.to_str().unwrap())?;
 
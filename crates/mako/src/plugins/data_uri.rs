@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose;
+use base64::Engine;
+use percent_encoding::percent_decode_str;
+use thiserror::Error;
+
+use crate::ast::file::{Content, JsContent};
+use crate::compiler::Context;
+use crate::plugin::{Plugin, PluginLoadParam, PluginResolveIdParams};
+use crate::resolve::ResolverResource;
+
+#[derive(Debug, Error)]
+enum DataUriError {
+    #[error("Invalid data uri: {uri}, {reason}")]
+    InvalidDataUri { uri: String, reason: String },
+    #[error(
+        "Unsupported media type of data uri: {media_type}, \
+         only javascript, css and json are supported"
+    )]
+    UnsupportedMediaType { media_type: String },
+}
+
+/**
+ * Resolve the `data:` imports like `import "data:text/javascript,console.log(1)"` to virtual
+ * modules, the decoded contents are kept by the plugin and loaded by the media types, so the
+ * code generated by the libraries and the inline test fixtures can be imported as modules
+ */
+#[derive(Default)]
+pub struct DataUriPlugin {
+    /// the decoded contents of the data uris, keyed by their virtual paths
+    modules: Mutex<HashMap<PathBuf, Content>>,
+}
+
+impl Plugin for DataUriPlugin {
+    fn name(&self) -> &str {
+        "data_uri"
+    }
+
+    fn resolve_id(
+        &self,
+        source: &str,
+        _importer: &str,
+        _params: &PluginResolveIdParams,
+        _context: &Arc<Context>,
+    ) -> Result<Option<ResolverResource>> {
+        if !source.starts_with("data:") {
+            return Ok(None);
+        }
+        let (ext, content) = parse_data_uri(source)?;
+        // the extension decides how the virtual module is parsed
+        let path = PathBuf::from(format!(
+            "virtual:data-uri/{:x}.{}",
+            md5::compute(source),
+            ext
+        ));
+        self.modules.lock().unwrap().insert(path.clone(), content);
+        Ok(Some(ResolverResource::Virtual(path)))
+    }
+
+    fn load(&self, param: &PluginLoadParam, _context: &Arc<Context>) -> Result<Option<Content>> {
+        Ok(self.modules.lock().unwrap().get(&param.file.path).cloned())
+    }
+}
+
+/// Parse `data:[<media type>][;base64],<data>` into the extension and the content of the module,
+/// the json is loaded as a js module like the json files
+fn parse_data_uri(uri: &str) -> Result<(&'static str, Content)> {
+    let invalid = |reason: &str| {
+        anyhow!(DataUriError::InvalidDataUri {
+            uri: uri.chars().take(50).collect(),
+            reason: reason.to_string(),
+        })
+    };
+    let (meta, data) = uri
+        .trim_start_matches("data:")
+        .split_once(',')
+        .ok_or_else(|| invalid("missing the comma before the data"))?;
+    let mut meta = meta.split(';');
+    let media_type = meta.next().unwrap_or_default().trim().to_lowercase();
+    let is_base64 = meta.any(|param| param.trim() == "base64");
+    let data = if is_base64 {
+        general_purpose::STANDARD
+            .decode(data)
+            .map_err(|e| invalid(&e.to_string()))?
+    } else {
+        percent_decode_str(data).collect::<Vec<u8>>()
+    };
+    let data = String::from_utf8(data).map_err(|e| invalid(&e.to_string()))?;
+    match media_type.as_str() {
+        ""
+        | "text/javascript"
+        | "application/javascript"
+        | "text/ecmascript"
+        | "application/ecmascript" => Ok((
+            "js",
+            Content::Js(JsContent {
+                content: data,
+                ..Default::default()
+            }),
+        )),
+        "text/css" => Ok(("css", Content::Css(data))),
+        "application/json" => {
+            serde_json::from_str::<serde_json::Value>(&data)
+                .map_err(|e| invalid(&e.to_string()))?;
+            Ok((
+                "js",
+                Content::Js(JsContent {
+                    content: format!("module.exports = {}", data),
+                    ..Default::default()
+                }),
+            ))
+        }
+        _ => Err(anyhow!(DataUriError::UnsupportedMediaType { media_type })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_js_data_uri() {
+        let (ext, content) =
+            parse_data_uri("data:text/javascript,export%20default%20%7B%20a%3A%201%20%7D").unwrap();
+        assert_eq!(ext, "js");
+        assert_eq!(
+            content,
+            Content::Js(JsContent {
+                content: "export default { a: 1 }".to_string(),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_base64_css_data_uri() {
+        // .a{color:red}
+        let (ext, content) = parse_data_uri("data:text/css;base64,LmF7Y29sb3I6cmVkfQ==").unwrap();
+        assert_eq!(ext, "css");
+        assert_eq!(content, Content::Css(".a{color:red}".to_string()));
+    }
+
+    #[test]
+    fn test_parse_json_data_uri() {
+        let (ext, content) = parse_data_uri(r#"data:application/json,{"a":1}"#).unwrap();
+        assert_eq!(ext, "js");
+        assert_eq!(
+            content,
+            Content::Js(JsContent {
+                content: r#"module.exports = {"a":1}"#.to_string(),
+                ..Default::default()
+            })
+        );
+        assert!(parse_data_uri("data:application/json,{").is_err());
+    }
+
+    #[test]
+    fn test_parse_invalid_data_uri() {
+        assert!(parse_data_uri("data:text/javascript").is_err());
+        assert!(parse_data_uri("data:image/png;base64,AAAA")
+            .unwrap_err()
+            .to_string()
+            .contains("Unsupported media type"));
+    }
+}
@@ -2,11 +2,11 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use anyhow::Result;
-use colored::Colorize;
 use pathdiff::diff_paths;
 
 use crate::compiler::Context;
 use crate::plugin::Plugin;
+use crate::utils::logger::print_warning;
 
 pub struct LoopDetector {}
 
@@ -49,7 +49,7 @@ impl Plugin for LoopDetector {
                         .iter()
                         .chain(std::iter::once(&loop_end))
                         .map(|id| {
-                            let absolute_path = PathBuf::from(id.id.clone());
+                            let absolute_path = id.to_path();
                             let relative_path =
                                 diff_paths(&absolute_path, &context.root).unwrap_or(absolute_path);
                             let relative_path = relative_path.to_string_lossy().to_string();
@@ -63,7 +63,7 @@ impl Plugin for LoopDetector {
 
             if !loop_lines.is_empty() {
                 for l in &loop_lines {
-                    println!("{} Circular Dependencies: {}", "Warning".yellow(), l);
+                    print_warning(&format!("Circular Dependencies: {}", l));
                 }
 
                 if detect_loop.graphviz {
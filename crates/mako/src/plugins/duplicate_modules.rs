@@ -0,0 +1,298 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use colored::Colorize;
+use hashlink::LinkedHashSet;
+use indexmap::IndexMap;
+use tracing::debug;
+
+use crate::compiler::Context;
+use crate::config::DuplicateModulesConfig;
+use crate::generate::chunk::{Chunk, ChunkId, ChunkType};
+use crate::generate::chunk_graph::ChunkGraph;
+use crate::generate::optimize_chunk::md5_chunk_ids;
+use crate::module::{relative_to_root, ModuleId};
+use crate::module_graph::ModuleGraph;
+use crate::plugin::Plugin;
+use crate::stats::human_readable_size;
+
+/**
+ * With `report`, report the modules emitted into multiple chunks by the splitting decisions
+ * with the bytes wasted by the copies, and with `hoist`, move the modules duplicated in at least
+ * `minChunks` async chunks into shared chunks first, the chunks sharing the same modules are
+ * grouped into the same shared chunk, like the shared css modules
+ */
+pub struct DuplicateModulesPlugin {
+    config: DuplicateModulesConfig,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct DuplicateModule {
+    id: ModuleId,
+    size: u64,
+    chunks: Vec<ChunkId>,
+}
+
+impl DuplicateModule {
+    fn wasted_size(&self) -> u64 {
+        self.size * (self.chunks.len() as u64 - 1)
+    }
+}
+
+impl DuplicateModulesPlugin {
+    pub fn new(config: &DuplicateModulesConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+}
+
+impl Plugin for DuplicateModulesPlugin {
+    fn name(&self) -> &str {
+        "duplicate_modules"
+    }
+
+    fn optimize_chunk(
+        &self,
+        chunk_graph: &mut ChunkGraph,
+        module_graph: &mut ModuleGraph,
+        context: &Arc<Context>,
+    ) -> Result<()> {
+        if self.config.hoist {
+            hoist_duplicate_modules(chunk_graph, self.config.min_chunks, &context.root);
+        }
+        let duplicates = find_duplicate_modules(chunk_graph, |id| {
+            module_graph
+                .get_module(id)
+                .map_or(0, |module| module.get_module_size() as u64)
+        });
+        if self.config.report && !duplicates.is_empty() {
+            println!("{}", report(&duplicates, chunk_graph, context));
+        }
+        Ok(())
+    }
+}
+
+/// The modules in more than one chunk, sorted by the wasted size desc
+fn find_duplicate_modules(
+    chunk_graph: &ChunkGraph,
+    module_size: impl Fn(&ModuleId) -> u64,
+) -> Vec<DuplicateModule> {
+    let mut module_to_chunks: IndexMap<ModuleId, Vec<ChunkId>> = IndexMap::new();
+    for chunk in chunk_graph.get_chunks() {
+        for module_id in chunk.get_modules() {
+            module_to_chunks
+                .entry(module_id.clone())
+                .or_default()
+                .push(chunk.id.clone());
+        }
+    }
+    let mut duplicates = module_to_chunks
+        .into_iter()
+        .filter(|(_, chunks)| chunks.len() > 1)
+        .map(|(id, mut chunks)| {
+            chunks.sort();
+            DuplicateModule {
+                size: module_size(&id),
+                id,
+                chunks,
+            }
+        })
+        .collect::<Vec<_>>();
+    duplicates.sort_by(|a, b| {
+        b.wasted_size()
+            .cmp(&a.wasted_size())
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    duplicates
+}
+
+/// Move the modules duplicated in at least `min_chunks` async chunks into the shared chunks,
+/// the entry chunks are loaded by different pages so they keep their modules. The shared chunks
+/// are loaded with the async chunks as their sync dependencies, the sync chunks are not hoisted
+/// since the chunks depending on them only load their direct sync dependencies
+fn hoist_duplicate_modules(chunk_graph: &mut ChunkGraph, min_chunks: usize, root: &Path) {
+    let mut module_to_chunks: IndexMap<ModuleId, Vec<ChunkId>> = IndexMap::new();
+    chunk_graph
+        .get_chunks()
+        .into_iter()
+        .filter(|chunk| matches!(chunk.chunk_type, ChunkType::Async))
+        .for_each(|chunk| {
+            // the root module of async chunk should stay in it
+            let root_module = chunk.modules.iter().last();
+            chunk
+                .modules
+                .iter()
+                .filter(|module_id| Some(*module_id) != root_module)
+                .for_each(|module_id| {
+                    module_to_chunks
+                        .entry(module_id.clone())
+                        .or_default()
+                        .push(chunk.id.clone());
+                });
+        });
+
+    let mut shared_groups: IndexMap<Vec<ChunkId>, Vec<ModuleId>> = IndexMap::new();
+    for (module_id, mut chunk_ids) in module_to_chunks {
+        if chunk_ids.len() < min_chunks.max(2) {
+            continue;
+        }
+        chunk_ids.sort();
+        shared_groups.entry(chunk_ids).or_default().push(module_id);
+    }
+
+    for (chunk_ids, module_ids) in shared_groups {
+        let shared_chunk_id = ChunkId::new(format!("shared_{}", md5_chunk_ids(&chunk_ids, root)));
+        debug!(
+            "hoist duplicate modules {:?} into {}",
+            module_ids, shared_chunk_id.id
+        );
+        for chunk_id in &chunk_ids {
+            let chunk = chunk_graph.mut_chunk(chunk_id).unwrap();
+            module_ids
+                .iter()
+                .for_each(|module_id| chunk.remove_module(module_id));
+        }
+        chunk_graph.add_chunk(Chunk {
+            modules: module_ids.into_iter().collect::<LinkedHashSet<_>>(),
+            id: shared_chunk_id.clone(),
+            chunk_type: ChunkType::Sync,
+            content: None,
+            source_map: None,
+        });
+        for chunk_id in &chunk_ids {
+            chunk_graph.add_edge(chunk_id, &shared_chunk_id);
+        }
+    }
+}
+
+fn report(duplicates: &[DuplicateModule], chunk_graph: &ChunkGraph, context: &Context) -> String {
+    let wasted_size = duplicates.iter().map(|d| d.wasted_size()).sum::<u64>();
+    let mut message = format!(
+        "{} {} modules are emitted into multiple chunks, {} wasted",
+        "Warning:".yellow().bold(),
+        duplicates.len(),
+        human_readable_size(wasted_size).bold()
+    );
+    for duplicate in duplicates {
+        let chunks = duplicate
+            .chunks
+            .iter()
+            .filter_map(|id| chunk_graph.chunk(id).map(|chunk| chunk.filename()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        message.push_str(&format!(
+            "\n  {} {} in {} chunks, {} wasted\n    {}",
+            relative_to_root(&duplicate.id.id, &context.root),
+            human_readable_size(duplicate.size).truecolor(128, 128, 128),
+            duplicate.chunks.len(),
+            human_readable_size(duplicate.wasted_size()),
+            chunks.truecolor(128, 128, 128)
+        ));
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(id: &str, chunk_type: ChunkType, modules: &[&str]) -> Chunk {
+        let mut chunk = Chunk::new(id.into(), chunk_type);
+        modules
+            .iter()
+            .for_each(|module| chunk.add_module((*module).into()));
+        chunk
+    }
+
+    fn chunk_graph() -> ChunkGraph {
+        let mut chunk_graph = ChunkGraph::new();
+        chunk_graph.add_chunk(chunk(
+            "index",
+            ChunkType::Entry("index".into(), "index".to_string(), false),
+            &["utils", "index"],
+        ));
+        chunk_graph.add_chunk(chunk("a", ChunkType::Async, &["utils", "lodash", "a"]));
+        chunk_graph.add_chunk(chunk("b", ChunkType::Async, &["utils", "lodash", "b"]));
+        chunk_graph
+    }
+
+    #[test]
+    fn test_find_duplicate_modules() {
+        let duplicates = find_duplicate_modules(&chunk_graph(), |id| match &*id.id {
+            "lodash" => 100,
+            _ => 10,
+        });
+        assert_eq!(
+            duplicates,
+            vec![
+                DuplicateModule {
+                    id: "lodash".into(),
+                    size: 100,
+                    chunks: vec!["a".into(), "b".into()],
+                },
+                DuplicateModule {
+                    id: "utils".into(),
+                    size: 10,
+                    chunks: vec!["a".into(), "b".into(), "index".into()],
+                },
+            ]
+        );
+        assert_eq!(duplicates[1].wasted_size(), 20);
+    }
+
+    #[test]
+    fn test_hoist_duplicate_modules() {
+        let mut chunk_graph = chunk_graph();
+        hoist_duplicate_modules(&mut chunk_graph, 2, Path::new("/root"));
+        let shared_chunk_id = ChunkId::new(format!(
+            "shared_{}",
+            md5_chunk_ids(&["a".into(), "b".into()], Path::new("/root"))
+        ));
+        let shared_chunk = chunk_graph.chunk(&shared_chunk_id).unwrap();
+        assert_eq!(
+            shared_chunk.get_modules().iter().collect::<Vec<_>>(),
+            vec![&ModuleId::from("utils"), &ModuleId::from("lodash")]
+        );
+        assert!(!chunk_graph
+            .chunk(&"a".into())
+            .unwrap()
+            .has_module(&"lodash".into()));
+        assert!(chunk_graph
+            .chunk(&"a".into())
+            .unwrap()
+            .has_module(&"a".into()));
+        // the entry chunk keeps its modules
+        assert!(chunk_graph
+            .chunk(&"index".into())
+            .unwrap()
+            .has_module(&"utils".into()));
+        assert_eq!(
+            chunk_graph.sync_dependencies_chunk(&"a".into()),
+            vec![shared_chunk_id]
+        );
+    }
+
+    #[test]
+    fn test_hoist_skips_sync_chunks() {
+        let mut chunk_graph = ChunkGraph::new();
+        chunk_graph.add_chunk(chunk("a", ChunkType::Async, &["lodash", "a"]));
+        chunk_graph.add_chunk(chunk("vendors", ChunkType::Sync, &["lodash"]));
+        chunk_graph.add_chunk(chunk("b", ChunkType::Async, &["b"]));
+        chunk_graph.add_edge(&"b".into(), &"vendors".into());
+        hoist_duplicate_modules(&mut chunk_graph, 2, Path::new("/root"));
+        assert_eq!(chunk_graph.get_chunks().len(), 3);
+        assert!(chunk_graph
+            .chunk(&"vendors".into())
+            .unwrap()
+            .has_module(&"lodash".into()));
+    }
+
+    #[test]
+    fn test_hoist_with_min_chunks() {
+        let mut chunk_graph = chunk_graph();
+        hoist_duplicate_modules(&mut chunk_graph, 3, Path::new("/root"));
+        assert_eq!(chunk_graph.get_chunks().len(), 3);
+    }
+}
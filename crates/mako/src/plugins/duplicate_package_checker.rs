@@ -1,10 +1,11 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 
 use semver::Version;
 
 use crate::compiler::Context;
+use crate::module::ModuleId;
 use crate::module_graph::ModuleGraph;
 use crate::plugin::Plugin;
 use crate::resolve::ResolverResource;
@@ -14,6 +15,8 @@ struct PackageInfo {
     name: String,
     version: Version,
     path: PathBuf,
+    // one of the modules in this package, used to find out who imports it
+    module_id: ModuleId,
 }
 
 #[derive(Default)]
@@ -70,18 +73,61 @@ impl DuplicatePackageCheckerPlugin {
         let mut package_map: HashMap<String, Vec<PackageInfo>> = HashMap::new();
 
         for package in packages {
-            package_map
-                .entry(package.name.clone())
-                .or_default()
-                .push(package);
+            let instances = package_map.entry(package.name.clone()).or_default();
+            // a package with many modules is still a single instance
+            if !instances.iter().any(|p| p.path == package.path) {
+                instances.push(package);
+            }
         }
 
         package_map
             .into_iter()
-            .filter(|(_, versions)| versions.len() > 1)
+            .filter(|(_, instances)| {
+                instances
+                    .iter()
+                    .map(|p| &p.version)
+                    .collect::<HashSet<_>>()
+                    .len()
+                    > 1
+            })
+            .map(|(name, mut instances)| {
+                instances.sort_by(|a, b| a.version.cmp(&b.version));
+                (name, instances)
+            })
             .collect()
     }
 
+    /// Finds the shortest import chain from an entry to the given module.
+    fn import_chain(module_graph: &ModuleGraph, module_id: &ModuleId) -> Vec<ModuleId> {
+        let entries = module_graph
+            .get_entry_modules()
+            .into_iter()
+            .collect::<HashSet<_>>();
+        let mut parents: HashMap<ModuleId, ModuleId> = HashMap::new();
+        let mut visited = HashSet::from([module_id.clone()]);
+        let mut queue = VecDeque::from([module_id.clone()]);
+
+        while let Some(current) = queue.pop_front() {
+            if entries.contains(&current) {
+                let mut chain = vec![current.clone()];
+                let mut node = &current;
+                while let Some(child) = parents.get(node) {
+                    chain.push(child.clone());
+                    node = child;
+                }
+                return chain;
+            }
+            for (dependent, _) in module_graph.get_dependents(&current) {
+                if visited.insert(dependent.clone()) {
+                    parents.insert(dependent.clone(), current.clone());
+                    queue.push_back(dependent.clone());
+                }
+            }
+        }
+
+        vec![module_id.clone()]
+    }
+
     fn check_duplicates(
         &self,
         module_graph: &RwLock<ModuleGraph>,
@@ -109,6 +155,7 @@ impl DuplicatePackageCheckerPlugin {
                                         name,
                                         version,
                                         path: package_json.path.clone(),
+                                        module_id: module.id.clone(),
                                     };
                                     packages.push(package_info);
                                 }
@@ -135,9 +182,13 @@ impl Plugin for DuplicatePackageCheckerPlugin {
         let duplicates = self.check_duplicates(&context.module_graph);
 
         if !duplicates.is_empty() && self.verbose {
+            let module_graph = context.module_graph.read().unwrap();
             let mut message = String::new();
 
-            for (name, instances) in duplicates {
+            let mut names = duplicates.keys().collect::<Vec<_>>();
+            names.sort();
+            for name in names {
+                let instances = &duplicates[name];
                 message.push_str(&format!("\nMultiple versions of {} found:\n", name));
                 for instance in instances {
                     let mut line = format!("  {} {}", instance.version, instance.name);
@@ -148,6 +199,33 @@ impl Plugin for DuplicatePackageCheckerPlugin {
                     ));
                     message.push_str(&line);
                     message.push('\n');
+
+                    let chain = Self::import_chain(&module_graph, &instance.module_id)
+                        .iter()
+                        .map(|id| {
+                            clean_path_relative_to_context(&id.to_path(), &context.root)
+                                .display()
+                                .to_string()
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" -> ");
+                    message.push_str(&format!("    imported by: {}\n", chain));
+                }
+
+                if self.show_help {
+                    // the instances are sorted by version, suggest the latest one
+                    let latest = instances.last().unwrap();
+                    if let Some(package_dir) = latest.path.parent() {
+                        let package_dir =
+                            clean_path_relative_to_context(package_dir, &context.root)
+                                .display()
+                                .to_string()
+                                .replace("/~/", "/node_modules/");
+                        message.push_str(&format!(
+                            "  To dedupe, add [\"{}\", \"{}\"] to resolve.alias\n",
+                            name, package_dir
+                        ));
+                    }
                 }
             }
 
@@ -170,9 +248,11 @@ impl Plugin for DuplicatePackageCheckerPlugin {
 mod tests {
     use std::path::PathBuf;
 
+    use semver::Version;
+
     use crate::config::{Config, DuplicatePackageCheckerConfig};
     use crate::plugin::Plugin;
-    use crate::plugins::duplicate_package_checker::DuplicatePackageCheckerPlugin;
+    use crate::plugins::duplicate_package_checker::{DuplicatePackageCheckerPlugin, PackageInfo};
     use crate::utils::test_helper::setup_compiler;
 
     #[test]
@@ -199,4 +279,45 @@ mod tests {
 
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_find_duplicates_ignores_same_instance() {
+        let package = |version: &str, path: &str, module: &str| PackageInfo {
+            name: "a".to_string(),
+            version: Version::parse(version).unwrap(),
+            path: PathBuf::from(path),
+            module_id: module.into(),
+        };
+        let duplicates = DuplicatePackageCheckerPlugin::find_duplicates(vec![
+            package(
+                "1.0.0",
+                "/node_modules/a/package.json",
+                "/node_modules/a/index.js",
+            ),
+            package(
+                "1.0.0",
+                "/node_modules/a/package.json",
+                "/node_modules/a/foo.js",
+            ),
+        ]);
+        assert!(duplicates.is_empty());
+
+        let duplicates = DuplicatePackageCheckerPlugin::find_duplicates(vec![
+            package(
+                "2.0.0",
+                "/node_modules/a/package.json",
+                "/node_modules/a/index.js",
+            ),
+            package(
+                "1.0.0",
+                "/node_modules/b/node_modules/a/package.json",
+                "/node_modules/b/node_modules/a/index.js",
+            ),
+        ]);
+        let versions = duplicates["a"]
+            .iter()
+            .map(|p| p.version.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(versions, vec!["1.0.0", "2.0.0"]);
+    }
 }
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+
+use crate::compiler::{Args, Compiler, Context};
+use crate::config::{Config, ExternalConfig, ExtraOutputConfig};
+use crate::plugin::{Plugin, PluginGenerateEndParams};
+use crate::plugins::legacy::GraphSnapshot;
+use crate::utils::logger::print_warning;
+
+/**
+ * Emit the extra outputs of config.outputs, e.g. the esm, cjs and umd outputs of a library, the
+ * modules built for the main output are snapshotted after the build and generated again with
+ * the options of each output, so they are resolved, loaded and transformed only once. The
+ * outputs of another platform are built from scratch since the resolving differs.
+ */
+pub struct ExtraOutputsPlugin {
+    outputs: Vec<ExtraOutputConfig>,
+    snapshot: Mutex<Option<GraphSnapshot>>,
+}
+
+impl ExtraOutputsPlugin {
+    pub fn new(outputs: &[ExtraOutputConfig]) -> Self {
+        Self {
+            outputs: outputs.to_vec(),
+            snapshot: Mutex::new(None),
+        }
+    }
+
+    fn output_config(&self, output: &ExtraOutputConfig, context: &Arc<Context>) -> Config {
+        let main = &context.config;
+        let mut config = main.clone();
+        // the extra outputs are only built once along with the main one
+        config.outputs = vec![];
+        config.legacy = None;
+        config.ssr = None;
+        config.hmr = None;
+        config.dev_server = None;
+        config.type_check = None;
+        config.lint = None;
+        config.clean = false;
+        if !is_rebuilt(output, main) {
+            // the source maps of the snapshot are dropped
+            config.devtool = None;
+        }
+        config.platform = output.platform.unwrap_or(main.platform);
+        if let Some(targets) = &output.targets {
+            config.targets = targets.clone();
+        }
+        config.cjs = output.cjs;
+        config.umd = output.umd.clone();
+        config.externals.extend(
+            output.externals.iter().map(|(source, external)| {
+                (source.clone(), ExternalConfig::Basic(external.clone()))
+            }),
+        );
+        config.output.path = context.root.join(&output.path);
+        if let Some(format) = &output.format {
+            config.output.format = format.clone();
+        }
+        // the outputs may be loaded in the same page, so the chunks of them are not mixed up
+        config.output.chunk_loading_global = format!(
+            "{}_{}",
+            main.output.chunk_loading_global,
+            output
+                .path
+                .to_string_lossy()
+                .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+        );
+        config
+    }
+}
+
+/// The modules are resolved with the conditions of the platform, e.g. `browser` of the package
+/// exports, so the output of another platform is built from scratch instead of the snapshot
+fn is_rebuilt(output: &ExtraOutputConfig, main: &Config) -> bool {
+    output
+        .platform
+        .is_some_and(|platform| platform != main.platform)
+}
+
+impl Plugin for ExtraOutputsPlugin {
+    fn name(&self) -> &str {
+        "extra_outputs"
+    }
+
+    fn after_build(&self, context: &Arc<Context>, _compiler: &Compiler) -> Result<()> {
+        if context.args.watch {
+            print_warning("The extra outputs of config.outputs are not emitted in watch mode");
+            return Ok(());
+        }
+        let snapshot = GraphSnapshot::new(context)?;
+        *self.snapshot.lock().unwrap() = Some(snapshot);
+        Ok(())
+    }
+
+    fn generate_end(
+        &self,
+        _params: &PluginGenerateEndParams,
+        context: &Arc<Context>,
+    ) -> Result<()> {
+        let Some(snapshot) = self.snapshot.lock().unwrap().take() else {
+            return Ok(());
+        };
+        for output in &self.outputs {
+            let config = self.output_config(output, context);
+            let targets = config.targets.clone();
+            let rebuilt = is_rebuilt(output, &context.config);
+            // the plugins of the main build, e.g. the js plugins, run for the outputs too
+            let compiler = Compiler::new(
+                config,
+                context.root.clone(),
+                Args::default(),
+                Some(context.extra_plugins.clone()),
+            )?;
+            if rebuilt {
+                compiler.compile()?;
+                continue;
+            }
+            let externals = output
+                .externals
+                .iter()
+                .map(|(source, external)| (source.clone(), ExternalConfig::Basic(external.clone())))
+                .collect::<HashMap<_, _>>();
+            let salt = output.path.to_string_lossy();
+            snapshot.generate(&compiler, &targets, &externals, &salt)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::ast::file::Content;
+    use crate::config::{DevtoolConfig, OutputFormat, Platform};
+    use crate::plugin::PluginLoadParam;
+
+    fn output(path: &str, platform: Option<Platform>) -> ExtraOutputConfig {
+        ExtraOutputConfig {
+            path: PathBuf::from(path),
+            format: Some(OutputFormat::Esm),
+            platform,
+            cjs: false,
+            umd: None,
+            targets: None,
+            externals: HashMap::from([("react".to_string(), "React".to_string())]),
+        }
+    }
+
+    #[test]
+    fn test_output_config() {
+        let mut context = Context::default();
+        context.config.cjs = true;
+        context.config.minify = false;
+        context.config.output.chunk_loading_global = "makoChunk_app".to_string();
+        let context = Arc::new(context);
+        let output = output("dist/esm", None);
+        let plugin = ExtraOutputsPlugin::new(&[output.clone()]);
+        let config = plugin.output_config(&output, &context);
+        assert_eq!(config.output.path, context.root.join("dist/esm"));
+        assert_eq!(config.output.format, OutputFormat::Esm);
+        assert_eq!(config.platform, context.config.platform);
+        assert!(!config.cjs);
+        // the other options are inherited from the main config
+        assert!(!config.minify);
+        assert_eq!(config.targets, context.config.targets);
+        assert!(config.devtool.is_none());
+        assert!(config.outputs.is_empty());
+        assert!(config.externals.contains_key("react"));
+        assert_eq!(config.output.chunk_loading_global, "makoChunk_app_dist_esm");
+    }
+
+    #[test]
+    fn test_output_config_of_another_platform() {
+        let mut context = Context::default();
+        context.config.platform = Platform::Node;
+        context.config.devtool = Some(DevtoolConfig::SourceMap);
+        let context = Arc::new(context);
+        let output = output("dist/browser", Some(Platform::Browser));
+        assert!(is_rebuilt(&output, &context.config));
+        let config = ExtraOutputsPlugin::new(&[]).output_config(&output, &context);
+        assert_eq!(config.platform, Platform::Browser);
+        // it's built from scratch, so the source maps are kept
+        assert!(config.devtool.is_some());
+    }
+
+    struct CountLoadsPlugin {
+        loads: Arc<AtomicUsize>,
+    }
+
+    impl Plugin for CountLoadsPlugin {
+        fn name(&self) -> &str {
+            "count_loads"
+        }
+
+        fn load(
+            &self,
+            param: &PluginLoadParam,
+            _context: &Arc<Context>,
+        ) -> Result<Option<Content>> {
+            if param.file.path.ends_with("index.ts") {
+                self.loads.fetch_add(1, Ordering::SeqCst);
+            }
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_extra_plugins_of_rebuilt_output() {
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/extra-outputs");
+        let config = Config::new(&root, None, None).unwrap();
+        let loads = Arc::new(AtomicUsize::new(0));
+        let plugin: Arc<dyn Plugin> = Arc::new(CountLoadsPlugin {
+            loads: loads.clone(),
+        });
+        let compiler =
+            Compiler::new(config, root.clone(), Args::default(), Some(vec![plugin])).unwrap();
+        compiler.compile().unwrap();
+        // loaded by the main build and by the output of another platform
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+        assert!(root.join("dist/browser/index.js").exists());
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use glob::glob;
+
+use crate::ast::file::File;
+use crate::compiler::Context;
+use crate::config::FontSubsetConfig;
+use crate::plugin::Plugin;
+
+const FONT_EXTENSIONS: [&str; 4] = ["woff2", "woff", "ttf", "otf"];
+
+/**
+ * Subset the fonts referenced by the scripts and the styles to the characters used in the
+ * files of config.font_subset.content, with pyftsubset of fonttools by default. The subsets
+ * are cached under node_modules/.cache_mako/font-subset, and emitted instead of the original
+ * fonts with the content hashes of the subsets. Only works with the build command.
+ */
+pub struct FontSubsetPlugin {
+    config: FontSubsetConfig,
+    /// the used characters, which are collected once as the first font is emitted
+    text: Mutex<Option<Arc<String>>>,
+    /// the fonts referenced by multiple modules are subset once
+    subsetting: Mutex<()>,
+}
+
+impl FontSubsetPlugin {
+    pub fn new(config: &FontSubsetConfig) -> Self {
+        Self {
+            config: config.clone(),
+            text: Mutex::new(None),
+            subsetting: Mutex::new(()),
+        }
+    }
+
+    fn text(&self, root: &Path) -> Result<Arc<String>> {
+        let mut text = self.text.lock().unwrap();
+        if let Some(text) = text.as_ref() {
+            return Ok(text.clone());
+        }
+        let mut chars = BTreeSet::new();
+        for pattern in &self.config.content {
+            let paths = glob(&root.join(pattern).to_string_lossy())
+                .map_err(|e| anyhow!("Invalid fontSubset content {}: {}", pattern, e))?;
+            for path in paths.flatten().filter(|path| path.is_file()) {
+                // the binary files are skipped
+                if let Ok(content) = fs::read_to_string(&path) {
+                    chars.extend(content.chars().filter(|c| !c.is_control()));
+                }
+            }
+        }
+        let collected = Arc::new(chars.into_iter().collect::<String>());
+        *text = Some(collected.clone());
+        Ok(collected)
+    }
+
+    fn subset(&self, file: &File, text: &str, context: &Arc<Context>) -> Result<File> {
+        let cache_key = format!("{}{}", file.get_content_hash(&context.config.output)?, text);
+        let cache_dir = context
+            .root
+            .join("node_modules/.cache_mako/font-subset")
+            .join(format!("{:x}", md5::compute(cache_key)));
+        // keep the filename, so the stem and the extension of the emitted asset are the same
+        let output = cache_dir.join(file.pathname.file_name().unwrap());
+        if !output.exists() {
+            fs::create_dir_all(&cache_dir)?;
+            let text_file = cache_dir.join("text.txt");
+            fs::write(&text_file, text)?;
+            let mut parts = self.config.command.split_whitespace();
+            let mut command = Command::new(parts.next().unwrap_or("pyftsubset"));
+            command
+                .args(parts)
+                .arg(&file.pathname)
+                .arg(format!("--text-file={}", text_file.to_string_lossy()))
+                .arg(format!("--output-file={}", output.to_string_lossy()));
+            if file.extname == "woff2" || file.extname == "woff" {
+                command.arg(format!("--flavor={}", file.extname));
+            }
+            let result = command
+                .output()
+                .map_err(|e| anyhow!("Failed to start the font subsetter: {}", e))?;
+            if !result.status.success() {
+                return Err(anyhow!(
+                    "Failed to subset the font {}: {}",
+                    file.pathname.to_string_lossy(),
+                    String::from_utf8_lossy(&result.stderr)
+                ));
+            }
+        }
+        Ok(File::new(
+            output.to_string_lossy().to_string(),
+            context.clone(),
+        ))
+    }
+}
+
+impl Plugin for FontSubsetPlugin {
+    fn name(&self) -> &str {
+        "font_subset"
+    }
+
+    fn transform_asset(&self, file: &File, context: &Arc<Context>) -> Result<Option<File>> {
+        if context.args.watch || !FONT_EXTENSIONS.contains(&file.extname.as_str()) {
+            return Ok(None);
+        }
+        let text = self.text(&context.root)?;
+        let _guard = self.subsetting.lock().unwrap();
+        self.subset(file, &text, context).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_text() {
+        let plugin = FontSubsetPlugin::new(&FontSubsetConfig {
+            content: vec!["src/**/*".to_string()],
+            command: "pyftsubset".to_string(),
+        });
+        let root = std::env::current_dir().unwrap().join("test/font_subset");
+        assert_eq!(plugin.text(&root).unwrap().as_str(), " ab你好");
+    }
+}
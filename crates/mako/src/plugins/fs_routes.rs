@@ -0,0 +1,310 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::ast::file::{win_path, Content, JsContent};
+use crate::compiler::Context;
+use crate::config::FsRoutesConfig;
+use crate::plugin::{Plugin, PluginContext, PluginLoadParam};
+
+/// The virtual module which exports the routes, e.g. `import routes from "virtual:routes"`
+pub const FS_ROUTES_MODULE: &str = "virtual:routes";
+
+const PAGE_EXTENSIONS: [&str; 4] = ["tsx", "ts", "jsx", "js"];
+const LAYOUT: &str = "_layout";
+
+/**
+ * Generate the routes from the files of the pages dir, like the conventions of umi and next
+ * - src/pages/index.tsx => /
+ * - src/pages/users/[id].tsx => /users/:id
+ * - src/pages/docs/[...slug].tsx => /docs/*
+ * - src/pages/users/_layout.tsx wraps the routes of src/pages/users as the nested `routes`
+ * the pages are loaded lazily with `import()`, the files and dirs starting with `_` or `.`,
+ * the `components` dirs and the declaration or test files are ignored, the routes are
+ * regenerated as the pages are added to or removed from the dir
+ */
+pub struct FsRoutesPlugin {
+    dir: PathBuf,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Route {
+    path: String,
+    component: PathBuf,
+    routes: Vec<Route>,
+}
+
+impl FsRoutesPlugin {
+    pub fn new(config: &FsRoutesConfig, root: &Path) -> Self {
+        Self {
+            dir: root.join(&config.dir),
+        }
+    }
+
+    fn render(&self) -> Result<String> {
+        let mut files = vec![];
+        if self.dir.is_dir() {
+            collect_page_files(&self.dir, Path::new(""), &mut files)?;
+        }
+        let routes = build_routes(&files, &self.dir, "");
+        Ok(format!(
+            "const routes = {};\nexport default routes;\n",
+            render_routes(&routes, 0)
+        ))
+    }
+}
+
+impl Plugin for FsRoutesPlugin {
+    fn name(&self) -> &str {
+        "fs_routes"
+    }
+
+    fn load(&self, param: &PluginLoadParam, context: &Arc<Context>) -> Result<Option<Content>> {
+        if param.file.path.to_string_lossy() != FS_ROUTES_MODULE {
+            return Ok(None);
+        }
+        // the routes are regenerated as the pages are added to or removed from the dir
+        PluginContext::new(self.name(), context)
+            .with_module(&param.file.path)
+            .add_watch_dir(self.dir.clone());
+        Ok(Some(Content::Js(JsContent {
+            content: self.render()?,
+            is_jsx: false,
+        })))
+    }
+}
+
+fn is_page_file(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let is_ignored = name.ends_with(".d.ts") || name.contains(".test.") || name.contains(".spec.");
+    !is_ignored
+        && path
+            .extension()
+            .is_some_and(|ext| PAGE_EXTENSIONS.contains(&ext.to_string_lossy().as_ref()))
+}
+
+fn is_ignored_name(name: &str) -> bool {
+    name.starts_with('.') || (name.starts_with('_') && !name.starts_with(LAYOUT))
+}
+
+/// The page files relative to the pages dir, sorted to keep the routes stable
+fn collect_page_files(dir: &Path, relative_dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+    entries.sort();
+    for path in entries {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        if is_ignored_name(&name) {
+            continue;
+        }
+        if path.is_dir() {
+            if name != "components" {
+                collect_page_files(&path, &relative_dir.join(&name), files)?;
+            }
+        } else if is_page_file(&path) {
+            files.push(relative_dir.join(&name));
+        }
+    }
+    Ok(())
+}
+
+fn build_routes(files: &[PathBuf], dir: &Path, prefix: &str) -> Vec<Route> {
+    let mut routes = vec![];
+    let mut layout = None;
+    let mut sub_dirs: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for file in files {
+        let mut components = file.components();
+        let first = components
+            .next()
+            .unwrap()
+            .as_os_str()
+            .to_string_lossy()
+            .to_string();
+        let rest = components.as_path();
+        if !rest.as_os_str().is_empty() {
+            sub_dirs.entry(first).or_default().push(rest.to_path_buf());
+            continue;
+        }
+        let stem = file.file_stem().unwrap().to_string_lossy().to_string();
+        if stem == LAYOUT {
+            layout = Some(dir.join(file));
+        } else {
+            routes.push(Route {
+                path: join_route(prefix, &to_segment(&stem)),
+                component: dir.join(file),
+                routes: vec![],
+            });
+        }
+    }
+    for (name, files) in sub_dirs {
+        let prefix = join_route(prefix, &to_segment(&name));
+        routes.extend(build_routes(&files, &dir.join(&name), &prefix));
+    }
+    // the static routes go before the dynamic ones, and the catch-all ones are the last
+    routes.sort_by_key(|route| (route_rank(&route.path), route.path.clone()));
+    match layout {
+        Some(layout) => vec![Route {
+            path: join_route(prefix, ""),
+            component: layout,
+            routes,
+        }],
+        None => routes,
+    }
+}
+
+/// e.g. index => "", [id] => :id, [...slug] => *
+fn to_segment(name: &str) -> String {
+    if name == "index" {
+        return "".to_string();
+    }
+    match name
+        .strip_prefix('[')
+        .and_then(|name| name.strip_suffix(']'))
+    {
+        Some(param) if param.starts_with("...") => "*".to_string(),
+        Some(param) => format!(":{}", param),
+        None => name.to_string(),
+    }
+}
+
+fn join_route(prefix: &str, segment: &str) -> String {
+    match (prefix, segment) {
+        ("", "") => "/".to_string(),
+        (prefix, "") => prefix.to_string(),
+        (prefix, segment) => format!("{}/{}", prefix.trim_end_matches('/'), segment),
+    }
+}
+
+fn route_rank(path: &str) -> usize {
+    if path.contains('*') {
+        2
+    } else if path.contains(':') {
+        1
+    } else {
+        0
+    }
+}
+
+fn render_routes(routes: &[Route], depth: usize) -> String {
+    if routes.is_empty() {
+        return "[]".to_string();
+    }
+    let indent = "  ".repeat(depth + 1);
+    let items = routes
+        .iter()
+        .map(|route| {
+            let mut item = format!(
+                "{}{{ path: {}, component: () => import({})",
+                indent,
+                serde_json::to_string(&route.path).unwrap(),
+                serde_json::to_string(&win_path(&route.component.to_string_lossy())).unwrap(),
+            );
+            if !route.routes.is_empty() {
+                item.push_str(&format!(
+                    ", routes: {}",
+                    render_routes(&route.routes, depth + 1)
+                ));
+            }
+            item.push_str(" }");
+            item
+        })
+        .collect::<Vec<_>>();
+    format!("[\n{},\n{}]", items.join(",\n"), "  ".repeat(depth))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn routes(files: &[&str]) -> Vec<Route> {
+        let files = files.iter().map(PathBuf::from).collect::<Vec<_>>();
+        build_routes(&files, Path::new("/pages"), "")
+    }
+
+    fn route(path: &str, component: &str, routes: Vec<Route>) -> Route {
+        Route {
+            path: path.to_string(),
+            component: PathBuf::from(component),
+            routes,
+        }
+    }
+
+    #[test]
+    fn test_routes() {
+        assert_eq!(
+            routes(&[
+                "about.tsx",
+                "docs/[...slug].tsx",
+                "index.tsx",
+                "users/[id].tsx",
+                "users/index.tsx",
+            ]),
+            vec![
+                route("/", "/pages/index.tsx", vec![]),
+                route("/about", "/pages/about.tsx", vec![]),
+                route("/users", "/pages/users/index.tsx", vec![]),
+                route("/users/:id", "/pages/users/[id].tsx", vec![]),
+                route("/docs/*", "/pages/docs/[...slug].tsx", vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_layouts() {
+        assert_eq!(
+            routes(&[
+                "_layout.tsx",
+                "index.tsx",
+                "users/_layout.tsx",
+                "users/[id].tsx",
+            ]),
+            vec![route(
+                "/",
+                "/pages/_layout.tsx",
+                vec![
+                    route("/", "/pages/index.tsx", vec![]),
+                    route(
+                        "/users",
+                        "/pages/users/_layout.tsx",
+                        vec![route("/users/:id", "/pages/users/[id].tsx", vec![])],
+                    ),
+                ],
+            )]
+        );
+    }
+
+    #[test]
+    fn test_ignored_files() {
+        assert!(is_page_file(Path::new("/pages/a.tsx")));
+        assert!(!is_page_file(Path::new("/pages/a.d.ts")));
+        assert!(!is_page_file(Path::new("/pages/a.test.tsx")));
+        assert!(!is_page_file(Path::new("/pages/a.less")));
+        assert!(is_ignored_name("_utils.ts"));
+        assert!(!is_ignored_name("_layout.tsx"));
+    }
+
+    #[test]
+    fn test_render_routes() {
+        let code = render_routes(
+            &[route(
+                "/",
+                "/pages/_layout.tsx",
+                vec![route("/", "/pages/index.tsx", vec![])],
+            )],
+            0,
+        );
+        assert_eq!(
+            code,
+            r#"[
+  { path: "/", component: () => import("/pages/_layout.tsx"), routes: [
+    { path: "/", component: () => import("/pages/index.tsx") },
+  ] },
+]"#
+        );
+    }
+}
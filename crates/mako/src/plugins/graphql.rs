@@ -0,0 +1,681 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+use crate::ast::file::{Content, JsContent};
+use crate::build::load::FileSystem;
+use crate::compiler::Context;
+use crate::plugin::{Plugin, PluginLoadParam};
+
+const GRAPHQL_EXTENSIONS: [&str; 2] = ["graphql", "gql"];
+
+#[derive(Debug, Error)]
+#[error("GraphQL syntax error: {message} in {path}:{line}:{column}")]
+struct GraphqlError {
+    path: String,
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+/**
+ * Load the `.graphql` and `.gql` files as the pre-compiled document ASTs, which are the same
+ * as the ones created by graphql-tag, so the graphql clients don't parse them at runtime.
+ * The fragments imported with `#import "./fragment.graphql"` are required as dependencies
+ * and appended to the definitions of the document, deduplicated by their names.
+ */
+pub struct GraphqlPlugin {}
+
+impl Plugin for GraphqlPlugin {
+    fn name(&self) -> &str {
+        "graphql"
+    }
+
+    fn load(&self, param: &PluginLoadParam, context: &Arc<Context>) -> Result<Option<Content>> {
+        let file = param.file;
+        if !GRAPHQL_EXTENSIONS.contains(&file.extname.as_str()) || !file.pathname.is_file() {
+            return Ok(None);
+        }
+        let source = FileSystem::read_file(&file.pathname, context.config.lossy_utf8)?;
+        let document = parse_document(&source).map_err(|(offset, message)| {
+            let (line, column) = line_column(&source, offset);
+            anyhow!(GraphqlError {
+                path: file.pathname.to_string_lossy().to_string(),
+                line,
+                column,
+                message,
+            })
+        })?;
+        Ok(Some(Content::Js(JsContent {
+            content: render_module(&document, &source, &collect_imports(&source))?,
+            ..Default::default()
+        })))
+    }
+}
+
+/// The sources of the `#import` comments, in the order they are written
+fn collect_imports(source: &str) -> Vec<String> {
+    let regex = Regex::new(r#"(?m)^#\s*import\s+(?:'([^']*)'|"([^"]*)")"#).unwrap();
+    regex
+        .captures_iter(source)
+        .filter_map(|caps| caps.get(1).or_else(|| caps.get(2)))
+        .map(|m| m.as_str().to_string())
+        .collect()
+}
+
+fn render_module(document: &Value, source: &str, imports: &[String]) -> Result<String> {
+    let mut code = format!(
+        "var doc = {};\ndoc.loc.source = {};\n",
+        serde_json::to_string(document)?,
+        serde_json::to_string(&json!({
+            "body": source,
+            "name": "GraphQL request",
+            "locationOffset": { "line": 1, "column": 1 },
+        }))?
+    );
+    if !imports.is_empty() {
+        code.push_str(
+            r#"var names = {};
+doc.definitions.forEach(function (def) {
+  if (def.kind === "FragmentDefinition") names[def.name.value] = true;
+});
+function unique(defs) {
+  return defs.filter(function (def) {
+    if (def.kind !== "FragmentDefinition") return true;
+    if (names[def.name.value]) return false;
+    names[def.name.value] = true;
+    return true;
+  });
+}
+"#,
+        );
+        for import in imports {
+            code.push_str(&format!(
+                "doc.definitions = doc.definitions.concat(unique(require({}).definitions));\n",
+                serde_json::to_string(import)?
+            ));
+        }
+    }
+    code.push_str("module.exports = doc;\n");
+    Ok(code)
+}
+
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+    (line, column)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Punctuator(&'static str),
+    Name(String),
+    Int(String),
+    Float(String),
+    String { value: String, block: bool },
+    Eof,
+}
+
+type ParseResult<T> = std::result::Result<T, (usize, String)>;
+
+/// Tokenize the document, the commas and the comments are insignificant and skipped
+fn tokenize(source: &str) -> ParseResult<Vec<(usize, Token)>> {
+    const PUNCTUATORS: [&str; 14] = [
+        "...", "!", "$", "&", "(", ")", ":", "=", "@", "[", "]", "{", "|", "}",
+    ];
+    let bytes = source.as_bytes();
+    let mut tokens = vec![];
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let c = bytes[pos];
+        if c.is_ascii_whitespace() || c == b',' {
+            pos += 1;
+        } else if c == b'#' {
+            while pos < bytes.len() && bytes[pos] != b'\n' {
+                pos += 1;
+            }
+        } else if let Some(p) = PUNCTUATORS.iter().find(|p| source[pos..].starts_with(**p)) {
+            tokens.push((pos, Token::Punctuator(*p)));
+            pos += p.len();
+        } else if c == b'_' || c.is_ascii_alphabetic() {
+            let start = pos;
+            while pos < bytes.len() && (bytes[pos] == b'_' || bytes[pos].is_ascii_alphanumeric()) {
+                pos += 1;
+            }
+            tokens.push((start, Token::Name(source[start..pos].to_string())));
+        } else if c == b'-' || c.is_ascii_digit() {
+            let start = pos;
+            let mut is_float = false;
+            pos += 1;
+            while pos < bytes.len() {
+                match bytes[pos] {
+                    b'0'..=b'9' => {}
+                    b'.' | b'e' | b'E' => is_float = true,
+                    b'+' | b'-' if matches!(bytes[pos - 1], b'e' | b'E') => {}
+                    _ => break,
+                }
+                pos += 1;
+            }
+            let value = source[start..pos].to_string();
+            if value == "-" {
+                return Err((start, "Invalid number".to_string()));
+            }
+            let token = if is_float {
+                Token::Float(value)
+            } else {
+                Token::Int(value)
+            };
+            tokens.push((start, token));
+        } else if source[pos..].starts_with("\"\"\"") {
+            let start = pos;
+            pos += 3;
+            let mut raw = String::new();
+            loop {
+                if pos >= bytes.len() {
+                    return Err((start, "Unterminated string".to_string()));
+                }
+                if source[pos..].starts_with("\\\"\"\"") {
+                    raw.push_str("\"\"\"");
+                    pos += 4;
+                } else if source[pos..].starts_with("\"\"\"") {
+                    pos += 3;
+                    break;
+                } else {
+                    let ch = source[pos..].chars().next().unwrap();
+                    raw.push(ch);
+                    pos += ch.len_utf8();
+                }
+            }
+            let value = dedent_block_string(&raw);
+            tokens.push((start, Token::String { value, block: true }));
+        } else if c == b'"' {
+            let start = pos;
+            pos += 1;
+            let mut value = String::new();
+            loop {
+                let Some(ch) = source[pos..].chars().next() else {
+                    return Err((start, "Unterminated string".to_string()));
+                };
+                pos += ch.len_utf8();
+                match ch {
+                    '"' => break,
+                    '\n' | '\r' => return Err((start, "Unterminated string".to_string())),
+                    '\\' => {
+                        let escaped = source[pos..].chars().next();
+                        pos += 1;
+                        value.push(match escaped {
+                            Some('"') => '"',
+                            Some('\\') => '\\',
+                            Some('/') => '/',
+                            Some('b') => '\u{8}',
+                            Some('f') => '\u{c}',
+                            Some('n') => '\n',
+                            Some('r') => '\r',
+                            Some('t') => '\t',
+                            Some('u') => {
+                                let code = source
+                                    .get(pos..pos + 4)
+                                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                                    .and_then(char::from_u32)
+                                    .ok_or((pos - 2, "Invalid unicode escape".to_string()))?;
+                                pos += 4;
+                                code
+                            }
+                            _ => return Err((pos - 2, "Invalid escape".to_string())),
+                        });
+                    }
+                    _ => value.push(ch),
+                }
+            }
+            tokens.push((
+                start,
+                Token::String {
+                    value,
+                    block: false,
+                },
+            ));
+        } else {
+            let ch = source[pos..].chars().next().unwrap();
+            return Err((pos, format!("Unexpected character {:?}", ch)));
+        }
+    }
+    tokens.push((source.len(), Token::Eof));
+    Ok(tokens)
+}
+
+/// Remove the common indentation and the leading and trailing blank lines of a block string
+fn dedent_block_string(raw: &str) -> String {
+    let lines = raw.lines().collect::<Vec<_>>();
+    let indent = lines
+        .iter()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let mut lines = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            if i == 0 {
+                *line
+            } else {
+                line.get(indent..).unwrap_or("")
+            }
+        })
+        .collect::<Vec<_>>();
+    while lines.first().is_some_and(|line| line.trim().is_empty()) {
+        lines.remove(0);
+    }
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+/// Parse an executable document to the AST of graphql-js, the locations of the nodes are
+/// omitted except the one of the document itself
+fn parse_document(source: &str) -> ParseResult<Value> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        index: 0,
+    };
+    let mut definitions = vec![];
+    while parser.peek() != &Token::Eof {
+        definitions.push(parser.parse_definition()?);
+    }
+    if definitions.is_empty() {
+        return Err((0, "Empty document".to_string()));
+    }
+    Ok(json!({
+        "kind": "Document",
+        "definitions": definitions,
+        "loc": { "start": 0, "end": source.len() },
+    }))
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.index].1
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.index].1.clone();
+        if token != Token::Eof {
+            self.index += 1;
+        }
+        token
+    }
+
+    fn unexpected<T>(&self) -> ParseResult<T> {
+        let (offset, token) = &self.tokens[self.index];
+        let found = match token {
+            Token::Punctuator(p) => format!("\"{}\"", p),
+            Token::Name(name) => format!("Name \"{}\"", name),
+            Token::Int(value) | Token::Float(value) => format!("Number \"{}\"", value),
+            Token::String { .. } => "String".to_string(),
+            Token::Eof => "<EOF>".to_string(),
+        };
+        Err((*offset, format!("Unexpected {}", found)))
+    }
+
+    fn is(&self, punctuator: &str) -> bool {
+        matches!(self.peek(), Token::Punctuator(p) if *p == punctuator)
+    }
+
+    fn skip(&mut self, punctuator: &str) -> bool {
+        let is = self.is(punctuator);
+        if is {
+            self.next();
+        }
+        is
+    }
+
+    fn expect(&mut self, punctuator: &str) -> ParseResult<()> {
+        if self.skip(punctuator) {
+            Ok(())
+        } else {
+            self.unexpected()
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Token::Name(name) if name == keyword)
+    }
+
+    fn parse_name(&mut self) -> ParseResult<Value> {
+        match self.peek() {
+            Token::Name(name) => {
+                let name = json!({ "kind": "Name", "value": name });
+                self.next();
+                Ok(name)
+            }
+            _ => self.unexpected(),
+        }
+    }
+
+    fn parse_definition(&mut self) -> ParseResult<Value> {
+        if self.is("{") {
+            return Ok(json!({
+                "kind": "OperationDefinition",
+                "operation": "query",
+                "variableDefinitions": [],
+                "directives": [],
+                "selectionSet": self.parse_selection_set()?,
+            }));
+        }
+        let Token::Name(keyword) = self.peek().clone() else {
+            return self.unexpected();
+        };
+        match keyword.as_str() {
+            "query" | "mutation" | "subscription" => {
+                self.next();
+                let mut definition = Map::new();
+                definition.insert("kind".into(), json!("OperationDefinition"));
+                definition.insert("operation".into(), json!(keyword));
+                if matches!(self.peek(), Token::Name(_)) {
+                    definition.insert("name".into(), self.parse_name()?);
+                }
+                definition.insert(
+                    "variableDefinitions".into(),
+                    json!(self.parse_variable_definitions()?),
+                );
+                definition.insert("directives".into(), json!(self.parse_directives(false)?));
+                definition.insert("selectionSet".into(), self.parse_selection_set()?);
+                Ok(Value::Object(definition))
+            }
+            "fragment" => {
+                self.next();
+                if self.is_keyword("on") {
+                    return self.unexpected();
+                }
+                let name = self.parse_name()?;
+                Ok(json!({
+                    "kind": "FragmentDefinition",
+                    "name": name,
+                    "typeCondition": self.parse_type_condition()?,
+                    "directives": self.parse_directives(false)?,
+                    "selectionSet": self.parse_selection_set()?,
+                }))
+            }
+            _ => Err((
+                self.tokens[self.index].0,
+                format!(
+                    "Unexpected Name \"{}\", only the operations and the fragments are supported",
+                    keyword
+                ),
+            )),
+        }
+    }
+
+    fn parse_variable_definitions(&mut self) -> ParseResult<Vec<Value>> {
+        let mut definitions = vec![];
+        if !self.skip("(") {
+            return Ok(definitions);
+        }
+        loop {
+            let mut definition = Map::new();
+            definition.insert("kind".into(), json!("VariableDefinition"));
+            definition.insert("variable".into(), self.parse_variable()?);
+            self.expect(":")?;
+            definition.insert("type".into(), self.parse_type()?);
+            if self.skip("=") {
+                definition.insert("defaultValue".into(), self.parse_value(true)?);
+            }
+            definition.insert("directives".into(), json!(self.parse_directives(true)?));
+            definitions.push(Value::Object(definition));
+            if self.skip(")") {
+                return Ok(definitions);
+            }
+        }
+    }
+
+    fn parse_variable(&mut self) -> ParseResult<Value> {
+        self.expect("$")?;
+        Ok(json!({ "kind": "Variable", "name": self.parse_name()? }))
+    }
+
+    fn parse_type(&mut self) -> ParseResult<Value> {
+        let ty = if self.skip("[") {
+            let ty = self.parse_type()?;
+            self.expect("]")?;
+            json!({ "kind": "ListType", "type": ty })
+        } else {
+            json!({ "kind": "NamedType", "name": self.parse_name()? })
+        };
+        if self.skip("!") {
+            return Ok(json!({ "kind": "NonNullType", "type": ty }));
+        }
+        Ok(ty)
+    }
+
+    fn parse_type_condition(&mut self) -> ParseResult<Value> {
+        if !self.is_keyword("on") {
+            return self.unexpected();
+        }
+        self.next();
+        Ok(json!({ "kind": "NamedType", "name": self.parse_name()? }))
+    }
+
+    fn parse_selection_set(&mut self) -> ParseResult<Value> {
+        self.expect("{")?;
+        let mut selections = vec![];
+        loop {
+            selections.push(self.parse_selection()?);
+            if self.skip("}") {
+                return Ok(json!({ "kind": "SelectionSet", "selections": selections }));
+            }
+        }
+    }
+
+    fn parse_selection(&mut self) -> ParseResult<Value> {
+        if !self.skip("...") {
+            return self.parse_field();
+        }
+        if matches!(self.peek(), Token::Name(_)) && !self.is_keyword("on") {
+            return Ok(json!({
+                "kind": "FragmentSpread",
+                "name": self.parse_name()?,
+                "directives": self.parse_directives(false)?,
+            }));
+        }
+        let mut fragment = Map::new();
+        fragment.insert("kind".into(), json!("InlineFragment"));
+        if self.is_keyword("on") {
+            fragment.insert("typeCondition".into(), self.parse_type_condition()?);
+        }
+        fragment.insert("directives".into(), json!(self.parse_directives(false)?));
+        fragment.insert("selectionSet".into(), self.parse_selection_set()?);
+        Ok(Value::Object(fragment))
+    }
+
+    fn parse_field(&mut self) -> ParseResult<Value> {
+        let mut field = Map::new();
+        field.insert("kind".into(), json!("Field"));
+        let name = self.parse_name()?;
+        if self.skip(":") {
+            field.insert("alias".into(), name);
+            field.insert("name".into(), self.parse_name()?);
+        } else {
+            field.insert("name".into(), name);
+        }
+        field.insert("arguments".into(), json!(self.parse_arguments(false)?));
+        field.insert("directives".into(), json!(self.parse_directives(false)?));
+        if self.is("{") {
+            field.insert("selectionSet".into(), self.parse_selection_set()?);
+        }
+        Ok(Value::Object(field))
+    }
+
+    fn parse_arguments(&mut self, is_const: bool) -> ParseResult<Vec<Value>> {
+        let mut arguments = vec![];
+        if !self.skip("(") {
+            return Ok(arguments);
+        }
+        loop {
+            let name = self.parse_name()?;
+            self.expect(":")?;
+            arguments.push(json!({
+                "kind": "Argument",
+                "name": name,
+                "value": self.parse_value(is_const)?,
+            }));
+            if self.skip(")") {
+                return Ok(arguments);
+            }
+        }
+    }
+
+    fn parse_directives(&mut self, is_const: bool) -> ParseResult<Vec<Value>> {
+        let mut directives = vec![];
+        while self.skip("@") {
+            let name = self.parse_name()?;
+            directives.push(json!({
+                "kind": "Directive",
+                "name": name,
+                "arguments": self.parse_arguments(is_const)?,
+            }));
+        }
+        Ok(directives)
+    }
+
+    fn parse_value(&mut self, is_const: bool) -> ParseResult<Value> {
+        if self.is("$") && !is_const {
+            return self.parse_variable();
+        }
+        if self.skip("[") {
+            let mut values = vec![];
+            while !self.skip("]") {
+                values.push(self.parse_value(is_const)?);
+            }
+            return Ok(json!({ "kind": "ListValue", "values": values }));
+        }
+        if self.skip("{") {
+            let mut fields = vec![];
+            while !self.skip("}") {
+                let name = self.parse_name()?;
+                self.expect(":")?;
+                fields.push(json!({
+                    "kind": "ObjectField",
+                    "name": name,
+                    "value": self.parse_value(is_const)?,
+                }));
+            }
+            return Ok(json!({ "kind": "ObjectValue", "fields": fields }));
+        }
+        let value = match self.peek() {
+            Token::Int(value) => json!({ "kind": "IntValue", "value": value }),
+            Token::Float(value) => json!({ "kind": "FloatValue", "value": value }),
+            Token::String { value, block } => {
+                json!({ "kind": "StringValue", "value": value, "block": block })
+            }
+            Token::Name(name) => match name.as_str() {
+                "true" | "false" => json!({ "kind": "BooleanValue", "value": name == "true" }),
+                "null" => json!({ "kind": "NullValue" }),
+                _ => json!({ "kind": "EnumValue", "value": name }),
+            },
+            _ => return self.unexpected(),
+        };
+        self.next();
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn fragment_names(document: &Value) -> HashSet<String> {
+        document["definitions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|def| def["kind"] == "FragmentDefinition")
+            .map(|def| def["name"]["value"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_document() {
+        let source = r#"
+query GetUser($id: ID!, $first: Int = 10) @cached {
+  user(id: $id) {
+    ...UserFields
+    avatar: picture(size: [1, 2.5], filter: { kind: LARGE, note: "a\"b" })
+    ... on Admin { role }
+  }
+}
+
+fragment UserFields on User {
+  id
+  name @include(if: true)
+}
+"#;
+        let document = parse_document(source).unwrap();
+        let operation = &document["definitions"][0];
+        assert_eq!(operation["operation"], "query");
+        assert_eq!(operation["name"]["value"], "GetUser");
+        assert_eq!(
+            operation["variableDefinitions"][0]["type"],
+            json!({
+                "kind": "NonNullType",
+                "type": { "kind": "NamedType", "name": { "kind": "Name", "value": "ID" } },
+            })
+        );
+        assert_eq!(
+            operation["variableDefinitions"][1]["defaultValue"],
+            json!({ "kind": "IntValue", "value": "10" })
+        );
+        let selections = &operation["selectionSet"]["selections"][0]["selectionSet"]["selections"];
+        assert_eq!(selections[0]["kind"], "FragmentSpread");
+        assert_eq!(selections[1]["alias"]["value"], "avatar");
+        assert_eq!(
+            selections[1]["arguments"][1]["value"]["fields"][1]["value"]["value"],
+            "a\"b"
+        );
+        assert_eq!(selections[2]["typeCondition"]["name"]["value"], "Admin");
+        assert_eq!(
+            fragment_names(&document),
+            HashSet::from(["UserFields".to_string()])
+        );
+        assert_eq!(document["loc"], json!({ "start": 0, "end": source.len() }));
+    }
+
+    #[test]
+    fn test_parse_document_error() {
+        let source = "query {\n  user(id: ) { id }\n}";
+        let (offset, message) = parse_document(source).unwrap_err();
+        assert_eq!(line_column(source, offset), (2, 12));
+        assert_eq!(message, "Unexpected \")\"");
+    }
+
+    #[test]
+    fn test_block_string() {
+        let document =
+            parse_document("{ a(text: \"\"\"\n    hello\n      world\n  \"\"\") }").unwrap();
+        assert_eq!(
+            document["definitions"][0]["selectionSet"]["selections"][0]["arguments"][0]["value"],
+            json!({ "kind": "StringValue", "value": "hello\n  world", "block": true })
+        );
+    }
+
+    #[test]
+    fn test_collect_imports() {
+        let source = "#import \"./user.graphql\"\n# import './post.gql'\nquery { a }";
+        assert_eq!(
+            collect_imports(source),
+            vec!["./user.graphql".to_string(), "./post.gql".to_string()]
+        );
+    }
+}
@@ -8,6 +8,8 @@
 !(function () {
   let currentParents = [];
   let currentChildModule;
+  // data passed from the dispose handlers to the updated module, i.e. module.hot.data
+  const currentModuleData = {};
   requireModule.hmrC = {};
   const createHmrRequire = (require, moduleId) => {
     const me = modulesRegistry[moduleId];
@@ -33,9 +35,23 @@
     Object.assign(fn, require);
     return fn;
   };
+  // the update can't be applied if it reaches a module without accepting parents,
+  // e.g. the entry, fall back to a full reload instead of doing nothing
+  const fullReload = (reason, moduleId, propagatedFrom) => {
+    const chain = [moduleId];
+    while (propagatedFrom[chain[0]]) {
+      chain.unshift(propagatedFrom[chain[0]]);
+    }
+    console.warn(
+      `[HMR] Full reload, the update of ${chain.join(' -> ')} ${reason}.`,
+    );
+    location.reload();
+  };
   const applyHotUpdate = (_chunkId, update) => {
     const { modules, removedModules } = update;
     const outdatedModules = [];
+    // parent -> the child module which the update is propagated from
+    const propagatedFrom = {};
     for (const moduleId of Object.keys(modules)) {
       if (!modulesRegistry[moduleId]) continue;
       if (outdatedModules.includes(moduleId)) continue;
@@ -46,14 +62,20 @@
         const module = modulesRegistry[item];
         if (!module) continue;
         if (module.hot._main) {
-          location.reload();
+          fullReload('reaches the entry', item, propagatedFrom);
+          return;
         }
         if (module.hot._selfAccepted) {
           continue;
         }
+        if (!module.parents.length) {
+          fullReload('is not accepted by any parent', item, propagatedFrom);
+          return;
+        }
         for (const parentModule of module.parents) {
           if (outdatedModules.includes(parentModule)) continue;
           outdatedModules.push(parentModule);
+          propagatedFrom[parentModule] = item;
           queue.push(parentModule);
         }
       }
@@ -67,9 +89,11 @@
     }
     for (const moduleId of outdatedModules) {
       const module = modulesRegistry[moduleId];
+      const data = {};
       for (const handler of module.hot._disposeHandlers) {
-        handler();
+        handler(data);
       }
+      currentModuleData[moduleId] = data;
       module.hot.active = false;
       delete modulesRegistry[moduleId];
       for (const childModule of module.children) {
@@ -102,6 +126,7 @@
       },
       _main,
       active: true,
+      data: currentModuleData[moduleId],
       accept() {
         this._selfAccepted = true;
       },
@@ -154,6 +179,7 @@
       },
     };
     currentChildModule = undefined;
+    delete currentModuleData[moduleId];
     return hot;
   };
   requireModule.hmrC.jsonp = (chunkId, update, promises) => {
@@ -165,7 +191,9 @@
     );
   };
   requireModule.hmrC.css = (chunkId, _update, promises) => {
-    if (cssChunksIdToUrlMap[chunkId]) {
+    if (cssChunksIdToUrlMap[chunkId] && requireModule.reloadStyleSheet) {
+      promises.push(requireModule.reloadStyleSheet(chunkId));
+    } else if (cssChunksIdToUrlMap[chunkId]) {
       promises.push(
         new Promise((resolve, reject) => {
           let url = cssChunksIdToUrlMap[chunkId];
@@ -1,11 +1,16 @@
-use std::sync::Arc;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use path_clean::PathClean;
 use regex::Regex;
 
 use crate::compiler::Context;
+use crate::config::Config;
 use crate::module::{Dependency, ResolveType};
-use crate::plugin::Plugin;
+use crate::plugin::{Plugin, PluginResolveIdParams};
+use crate::resolve::ResolverResource;
 
 pub struct IgnorePlugin {
     pub ignores: Vec<Regex>,
@@ -32,3 +37,161 @@ impl Plugin for IgnorePlugin {
         Ok(())
     }
 }
+
+/**
+ * Replace the requests matching `resourceRegExp` within the importer directory matching
+ * `contextRegExp` with empty modules, like the IgnorePlugin of webpack, and the
+ * `localeFilter` preset which only keeps the given locales of moment and dayjs
+ */
+pub struct IgnoreModulesPlugin {
+    rules: Vec<(Regex, Option<Regex>)>,
+    locales: Option<HashSet<String>>,
+}
+
+impl IgnoreModulesPlugin {
+    pub fn new(config: &Config) -> Result<Self> {
+        let to_regex = |pattern: &str| {
+            Regex::new(pattern).map_err(|e| anyhow!("Invalid ignoreModules {}: {}", pattern, e))
+        };
+        let rules = config
+            .ignore_modules
+            .iter()
+            .map(|rule| {
+                Ok((
+                    to_regex(&rule.resource_reg_exp)?,
+                    rule.context_reg_exp.as_deref().map(to_regex).transpose()?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let locales = config.locale_filter.as_ref().map(|locale_filter| {
+            locale_filter
+                .locales
+                .iter()
+                .map(|locale| locale.to_lowercase())
+                .collect()
+        });
+        Ok(Self { rules, locales })
+    }
+
+    fn is_ignored(&self, request: &str, importer: &str) -> bool {
+        let context = Path::new(importer).parent().unwrap_or(Path::new(""));
+        let context_str = context.to_string_lossy();
+        let ignored_by_rules = self.rules.iter().any(|(resource, context_regex)| {
+            resource.is_match(request)
+                && context_regex
+                    .as_ref()
+                    .map_or(true, |re| re.is_match(&context_str))
+        });
+        if ignored_by_rules {
+            return true;
+        }
+
+        match &self.locales {
+            Some(locales) => locale_name(&request_path(request, importer).to_string_lossy())
+                .is_some_and(|locale| !locales.contains(&locale)),
+            None => false,
+        }
+    }
+}
+
+impl Plugin for IgnoreModulesPlugin {
+    fn name(&self) -> &str {
+        "ignore_modules"
+    }
+
+    fn resolve_id(
+        &self,
+        source: &str,
+        importer: &str,
+        _params: &PluginResolveIdParams,
+        _context: &Arc<Context>,
+    ) -> Result<Option<ResolverResource>> {
+        // context requests like `./locale/?context&glob=**/*`
+        let request = source.split('?').next().unwrap_or(source);
+        let request = request.trim_end_matches('/');
+        if self.is_ignored(request, importer) {
+            return Ok(Some(ResolverResource::Ignored(request_path(
+                request, importer,
+            ))));
+        }
+        Ok(None)
+    }
+}
+
+fn request_path(request: &str, importer: &str) -> PathBuf {
+    if request.starts_with('.') {
+        Path::new(importer)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join(request)
+            .clean()
+    } else {
+        PathBuf::from(request)
+    }
+}
+
+/// The locale name of the locale files of moment and dayjs, e.g. `moment/locale/zh-cn.js`
+fn locale_name(path: &str) -> Option<String> {
+    static LOCALE_REGEXP: OnceLock<Regex> = OnceLock::new();
+    let locale_regexp = LOCALE_REGEXP.get_or_init(|| {
+        Regex::new(r"(?:^|[/\\])(?:moment|dayjs)(?:[/\\](?:dist|esm|min))?[/\\]locale[/\\]([^/\\]+?)(?:\.js)?$")
+            .unwrap()
+    });
+    locale_regexp
+        .captures(path)
+        .map(|caps| caps[1].to_lowercase())
+        // dayjs/locale/types.d.ts etc.
+        .filter(|locale| !locale.contains('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plugin(rules: &[(&str, Option<&str>)], locales: Option<&[&str]>) -> IgnoreModulesPlugin {
+        IgnoreModulesPlugin {
+            rules: rules
+                .iter()
+                .map(|(resource, context)| {
+                    (
+                        Regex::new(resource).unwrap(),
+                        context.map(|c| Regex::new(c).unwrap()),
+                    )
+                })
+                .collect(),
+            locales: locales.map(|l| l.iter().map(|s| s.to_string()).collect()),
+        }
+    }
+
+    #[test]
+    fn test_ignore_within_context() {
+        let plugin = plugin(&[(r"^\./locale$", Some("moment$"))], None);
+        let importer = "/p/node_modules/moment/moment.js";
+        assert!(plugin.is_ignored("./locale", importer));
+        assert!(!plugin.is_ignored("./locale", "/p/src/index.js"));
+        assert!(!plugin.is_ignored("./locale/zh-cn", importer));
+    }
+
+    #[test]
+    fn test_ignore_without_context() {
+        let plugin = plugin(&[(r"\.md$", None)], None);
+        assert!(plugin.is_ignored("./README.md", "/p/src/index.js"));
+        assert!(!plugin.is_ignored("./index.css", "/p/src/index.js"));
+    }
+
+    #[test]
+    fn test_locale_filter() {
+        let plugin = plugin(&[], Some(&["zh-cn"]));
+        assert!(plugin.is_ignored("./locale/fr", "/p/node_modules/moment/moment.js"));
+        assert!(!plugin.is_ignored("./locale/zh-cn", "/p/node_modules/moment/moment.js"));
+        assert!(plugin.is_ignored("moment/locale/fr", "/p/src/index.js"));
+        assert!(plugin.is_ignored("dayjs/locale/fr.js", "/p/src/index.js"));
+        assert!(!plugin.is_ignored("dayjs/locale/zh-cn.js", "/p/src/index.js"));
+        assert!(plugin.is_ignored(
+            "/p/node_modules/dayjs/esm/locale/ja.js",
+            "/p/node_modules/dayjs/esm/locale"
+        ));
+        assert!(!plugin.is_ignored("./fr", "/p/src/index.js"));
+        assert!(!plugin.is_ignored("dayjs/plugin/utc", "/p/src/index.js"));
+    }
+}
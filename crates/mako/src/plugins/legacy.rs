@@ -0,0 +1,402 @@
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use colored::Colorize;
+use rayon::prelude::*;
+use swc_core::base::try_with_handler;
+use swc_core::common::errors::HANDLER;
+use swc_core::common::GLOBALS;
+use swc_core::css::codegen::writer::basic::{BasicCssWriter, BasicCssWriterConfig};
+use swc_core::css::codegen::{CodeGenerator, CodegenConfig, Emit};
+use swc_core::ecma::ast::EsVersion;
+use swc_core::ecma::codegen::text_writer::JsWriter;
+use swc_core::ecma::codegen::{Config as JsCodegenConfig, Emitter};
+use swc_core::ecma::preset_env::{self as swc_preset_env};
+use swc_core::ecma::transforms::base::feature::FeatureFlag;
+use swc_core::ecma::transforms::base::helpers::{Helpers, HELPERS};
+use swc_core::ecma::transforms::base::resolver;
+use swc_core::ecma::visit::{Fold, VisitMut};
+
+use crate::ast::css_ast::CssAst;
+use crate::ast::js_ast::JsAst;
+use crate::build::targets::swc_preset_env_targets_from_map;
+use crate::compiler::{Args, Compiler, Context};
+use crate::config::{Config, ExternalConfig, LegacyConfig};
+use crate::module::{Dependency, Module, ModuleAst, ModuleId};
+use crate::module_graph::ModuleGraph;
+use crate::plugin::{Plugin, PluginGenerateEndParams};
+use crate::resolve::{get_external_target, ExternalResource, ResolverResource};
+
+/// The legacy output is emitted to this sub directory of the output path, with the same
+/// file names as the modern output
+pub const LEGACY_DIR: &str = "legacy";
+/// The chunk id of the polyfills in the stats of the main output, which are loaded before the
+/// legacy entries
+pub const POLYFILLS_CHUNK_ID: &str = "legacy_polyfills";
+const POLYFILLS_ENTRY: &str = "node_modules/.cache_mako/legacy/polyfills.js";
+
+/**
+ * Emit a legacy variant of the output, the modules built for the modern output are
+ * printed after the build, then re-parsed and transformed to the legacy targets,
+ * so the resolving, loading and transforming of the build stage are shared.
+ */
+pub struct LegacyPlugin {
+    config: LegacyConfig,
+    snapshot: Mutex<Option<GraphSnapshot>>,
+}
+
+/// The module graph before it's modified by the tree shaking and transforms of the
+/// generate stage, with the asts printed to code
+pub(crate) struct GraphSnapshot {
+    modules: Vec<(Module, Option<SnapshotCode>)>,
+    dependencies: Vec<(ModuleId, ModuleId, Dependency)>,
+}
+
+enum SnapshotCode {
+    Js(String),
+    Css(String),
+}
+
+impl LegacyPlugin {
+    pub fn new(config: &LegacyConfig) -> Self {
+        Self {
+            config: config.clone(),
+            snapshot: Mutex::new(None),
+        }
+    }
+
+    fn legacy_config(&self, context: &Arc<Context>) -> Config {
+        let main = &context.config;
+        let mut config = Config::default();
+        // the entries of context have the glob entries added or removed in watch mode
+        config.entry = context.entries.read().unwrap().clone();
+        config.mode = main.mode.clone();
+        config.minify = main.minify;
+        config.hash = main.hash;
+        config.devtool = None;
+        config.clean = false;
+        config.targets = self.config.targets.clone();
+        config.code_splitting = main.code_splitting.clone();
+        config.optimization = main.optimization.clone();
+        config._tree_shaking = main._tree_shaking;
+        config.module_id_strategy = main.module_id_strategy;
+        config.public_path = if main.public_path == "runtime" {
+            main.public_path.clone()
+        } else {
+            format!("{}{}/", main.public_path, LEGACY_DIR)
+        };
+        config.output.path = main.output.path.join(LEGACY_DIR);
+        config.output.es_version = EsVersion::Es5;
+        config.output.cross_origin_loading = main.output.cross_origin_loading.clone();
+        config.output.chunk_load_retry = main.output.chunk_load_retry.clone();
+        // safari 10.1 runs both the module and nomodule scripts
+        config.output.chunk_loading_global = format!("{}_legacy", main.output.chunk_loading_global);
+        config
+    }
+
+    /// Build the core-js polyfills required by the legacy targets into `polyfills.js`, the
+    /// `core-js/stable` import of the entry is expanded to the modules of the targets
+    fn build_polyfills(&self, context: &Arc<Context>) -> Result<Option<Compiler>> {
+        if !context.root.join("node_modules/core-js").exists() {
+            println!(
+                "{} core-js is not installed, the legacy output is not polyfilled",
+                "Warning".yellow()
+            );
+            return Ok(None);
+        }
+        let entry = context.root.join(POLYFILLS_ENTRY);
+        if let Some(dir) = entry.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&entry, "import 'core-js/stable';\n")?;
+        let mut config = self.legacy_config(context);
+        config.entry = HashMap::from([("polyfills".to_string(), entry)]);
+        config.code_splitting = None;
+        // the polyfills have their own runtime, which must not take the chunks of the entries
+        config.output.chunk_loading_global =
+            format!("{}_polyfills", config.output.chunk_loading_global);
+        let compiler = Compiler::new(config, context.root.clone(), Args::default(), None)?;
+        compiler.compile()?;
+        Ok(Some(compiler))
+    }
+}
+
+impl Plugin for LegacyPlugin {
+    fn name(&self) -> &str {
+        "legacy"
+    }
+
+    fn after_build(&self, context: &Arc<Context>, _compiler: &Compiler) -> Result<()> {
+        if context.args.watch {
+            return Ok(());
+        }
+        let snapshot = GraphSnapshot::new(context)?;
+        *self.snapshot.lock().unwrap() = Some(snapshot);
+        Ok(())
+    }
+
+    fn generate_end(
+        &self,
+        _params: &PluginGenerateEndParams,
+        context: &Arc<Context>,
+    ) -> Result<()> {
+        let Some(snapshot) = self.snapshot.lock().unwrap().take() else {
+            return Ok(());
+        };
+        let compiler = Compiler::new(
+            self.legacy_config(context),
+            context.root.clone(),
+            Args::default(),
+            None,
+        )?;
+        snapshot.generate(&compiler, &self.config.targets, &HashMap::new(), LEGACY_DIR)?;
+        add_legacy_assets(context, &compiler.context, None);
+        if self.config.polyfills {
+            if let Some(polyfills) = self.build_polyfills(context)? {
+                add_legacy_assets(context, &polyfills.context, Some(POLYFILLS_CHUNK_ID));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl GraphSnapshot {
+    pub(crate) fn new(context: &Arc<Context>) -> Result<Self> {
+        let module_graph = context.module_graph.read().unwrap();
+        let modules = module_graph
+            .modules()
+            .par_iter()
+            .map(|module| {
+                let code = match module.info.as_ref().map(|info| &info.ast) {
+                    Some(ModuleAst::Script(ast)) => Some(SnapshotCode::Js(print_js(ast, context)?)),
+                    Some(ModuleAst::Css(ast)) => Some(SnapshotCode::Css(print_css(ast)?)),
+                    _ => None,
+                };
+                Ok((module.clone_without_ast(), code))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let dependencies = module_graph
+            .modules()
+            .iter()
+            .flat_map(|module| {
+                module_graph
+                    .get_dependencies(&module.id)
+                    .into_iter()
+                    .map(move |(id, dep)| (module.id.clone(), id.clone(), dep.clone()))
+            })
+            .collect();
+        Ok(GraphSnapshot {
+            modules,
+            dependencies,
+        })
+    }
+
+    /// Generate the output of the compiler from the modules of the snapshot, the scripts are
+    /// transformed to the targets, and the raw hashes are salted to not hit the caches of the
+    /// other outputs, the modules imported by the sources of the externals are replaced with
+    /// the external modules
+    pub(crate) fn generate(
+        &self,
+        compiler: &Compiler,
+        targets: &HashMap<String, f32>,
+        externals: &HashMap<String, ExternalConfig>,
+        salt: &str,
+    ) -> Result<()> {
+        let context = compiler.context.clone();
+        let externalized = self
+            .dependencies
+            .iter()
+            .filter_map(|(_, to, dep)| {
+                let (external, script) = get_external_target(externals, &dep.source)?;
+                let resource = ResolverResource::External(ExternalResource {
+                    source: dep.source.clone(),
+                    external,
+                    script,
+                });
+                let mut module = Compiler::create_external_module(&resource, context.clone());
+                module.id = to.clone();
+                Some((to.clone(), module))
+            })
+            .collect::<HashMap<_, _>>();
+        let modules = self
+            .modules
+            .par_iter()
+            .filter(|(module, _)| !externalized.contains_key(&module.id))
+            .map(|(module, code)| {
+                let mut module = module.clone();
+                if let Some(info) = module.info.as_mut() {
+                    info.raw_hash = salted_hash(info.raw_hash, salt);
+                    info.ast = match code {
+                        Some(SnapshotCode::Js(code)) => ModuleAst::Script(transform_js(
+                            &info.file.path.to_string_lossy(),
+                            code,
+                            targets,
+                            &context,
+                        )?),
+                        Some(SnapshotCode::Css(code)) => ModuleAst::Css(CssAst::build(
+                            &info.file.path.to_string_lossy(),
+                            code,
+                            context.clone(),
+                            false,
+                        )?),
+                        None => ModuleAst::None,
+                    };
+                }
+                Ok(module)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        {
+            let mut module_graph = ModuleGraph::new();
+            for module in modules.into_iter().chain(externalized.values().cloned()) {
+                module_graph.add_module(module);
+            }
+            // the dependencies of the externalized modules are left out, so are the modules only
+            // imported by them since they are not reachable from the entries
+            for (from, to, dep) in &self.dependencies {
+                if !externalized.contains_key(from) {
+                    module_graph.add_dependency(from, to, dep.clone());
+                }
+            }
+            *context.module_graph.write().unwrap() = module_graph;
+        }
+
+        context.plugin_driver.before_generate(&context)?;
+        compiler.generate()?;
+        Ok(())
+    }
+}
+
+/// Add the files of the legacy output to the stats of the main output under the legacy dir, e.g.
+/// to be injected into the html as the nomodule scripts, the chunk ids are the same as the modern
+/// ones since the outputs share the module graph
+fn add_legacy_assets(context: &Arc<Context>, legacy: &Arc<Context>, chunk_id: Option<&str>) {
+    for asset in legacy.stats_info.get_assets() {
+        context.stats_info.add_assets(
+            asset.size,
+            asset.name,
+            chunk_id.map_or(asset.chunk_id, |chunk_id| chunk_id.to_string()),
+            asset.path,
+            format!("{}/{}", LEGACY_DIR, asset.hashname),
+        );
+    }
+}
+
+fn transform_js(
+    path: &str,
+    code: &str,
+    targets: &HashMap<String, f32>,
+    context: &Arc<Context>,
+) -> Result<JsAst> {
+    let mut ast = JsAst::build(path, code, context.clone())?;
+    let cm = context.meta.script.cm.clone();
+    GLOBALS.set(&context.meta.script.globals, || {
+        try_with_handler(cm, Default::default(), |handler| {
+            // the helpers are inlined, since the helper modules which are not
+            // used by the modern output don't exist in the module graph
+            HELPERS.set(&Helpers::new(false), || {
+                HANDLER.set(handler, || {
+                    let unresolved_mark = ast.unresolved_mark;
+                    let top_level_mark = ast.top_level_mark;
+                    let comments = context
+                        .meta
+                        .script
+                        .origin_comments
+                        .read()
+                        .unwrap()
+                        .get_swc_comments()
+                        .clone();
+                    let mut visitors: Vec<Box<dyn VisitMut>> =
+                        vec![Box::new(resolver(unresolved_mark, top_level_mark, false))];
+                    let mut folders: Vec<Box<dyn Fold>> =
+                        vec![Box::new(swc_preset_env::preset_env(
+                            unresolved_mark,
+                            Some(comments),
+                            swc_preset_env::Config {
+                                targets: Some(swc_preset_env_targets_from_map(targets.clone())),
+                                ..Default::default()
+                            },
+                            Default::default(),
+                            &mut FeatureFlag::default(),
+                        ))];
+                    ast.transform(&mut visitors, &mut folders, true, context.clone())
+                })
+            })
+        })
+    })?;
+    Ok(ast)
+}
+
+/// The rendered chunks are cached by the raw hashes of the modules, the modules of the other
+/// outputs must not hit the caches of the main one
+fn salted_hash(raw_hash: u64, salt: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    raw_hash.hash(&mut hasher);
+    salt.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn print_js(ast: &JsAst, context: &Arc<Context>) -> Result<String> {
+    let mut buf = vec![];
+    let cm = context.meta.script.cm.clone();
+    {
+        // keep the comments, e.g. the magic comments of dynamic imports
+        let comments = context.meta.script.origin_comments.read().unwrap();
+        let mut emitter = Emitter {
+            cfg: JsCodegenConfig::default().with_target(context.config.output.es_version),
+            cm: cm.clone(),
+            comments: Some(comments.get_swc_comments()),
+            wr: Box::new(JsWriter::new(cm, "\n", &mut buf, None)),
+        };
+        emitter.emit_module(&ast.ast)?;
+    }
+    Ok(String::from_utf8(buf)?)
+}
+
+fn print_css(ast: &CssAst) -> Result<String> {
+    let mut code = String::new();
+    let writer = BasicCssWriter::new(&mut code, None, BasicCssWriterConfig::default());
+    let mut gen = CodeGenerator::new(writer, CodegenConfig { minify: false });
+    gen.emit(&ast.ast)?;
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::tests::TestUtils;
+
+    #[test]
+    fn test_transform_to_legacy_targets() {
+        let context = Arc::new(Context::default());
+        let ast = transform_js(
+            "/test.js",
+            "export const f = (a, ...b) => `${a}`;",
+            &HashMap::from([("ie".to_string(), 11.0)]),
+            &context,
+        )
+        .unwrap();
+        let code = print_js(&ast, &context).unwrap();
+        assert!(code.contains("var f"));
+        assert!(!code.contains("=>"));
+        assert!(!code.contains("..."));
+        assert!(!code.contains('`'));
+    }
+
+    #[test]
+    fn test_print_css() {
+        let mut test_utils = TestUtils::gen_css_ast(".a{color:red}".to_string(), false);
+        let code = print_css(test_utils.ast.css_mut()).unwrap();
+        assert!(code.contains(".a"));
+        assert!(code.contains("color: red"));
+    }
+
+    #[test]
+    fn test_legacy_hash() {
+        assert_ne!(salted_hash(1, LEGACY_DIR), 1);
+        assert_eq!(salted_hash(1, LEGACY_DIR), salted_hash(1, LEGACY_DIR));
+    }
+}
@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use serde::Deserialize;
+
+use crate::compiler::Context;
+use crate::config::LintConfig;
+use crate::plugin::Plugin;
+use crate::stats::StatsJsonMap;
+
+const LINT_EXTENSIONS: [&str; 8] = ["js", "jsx", "ts", "tsx", "mjs", "cjs", "mts", "cts"];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct LintMessage {
+    line: usize,
+    column: usize,
+    message: String,
+    is_error: bool,
+    rule: Option<String>,
+}
+
+/**
+ * Lint the project modules of the module graph after each build with the linter of
+ * config.lint, only the modules whose contents are changed since the last lint are passed to
+ * the linter, the results of the others are reused. The lint errors fail the build in build
+ * mode, and are printed as the warnings in watch mode.
+ */
+pub struct LintPlugin {
+    config: LintConfig,
+    cache: Mutex<HashMap<PathBuf, (u64, Vec<LintMessage>)>>,
+}
+
+impl LintPlugin {
+    pub fn new(config: &LintConfig) -> Self {
+        Self {
+            config: config.clone(),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn command(&self, root: &Path) -> Command {
+        let mut parts = match &self.config.command {
+            Some(command) => command
+                .split_whitespace()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>(),
+            None => {
+                let bin = root.join("node_modules/.bin");
+                let local = ["oxlint", "eslint"]
+                    .iter()
+                    .map(|name| bin.join(name))
+                    .find(|path| path.exists());
+                match local {
+                    Some(local) => vec![local.to_string_lossy().to_string()],
+                    None => vec!["oxlint".to_string()],
+                }
+            }
+        };
+        let program = parts.remove(0);
+        let mut command = Command::new(program);
+        command
+            .args(parts)
+            .args(["--format", "json"])
+            .current_dir(root);
+        command
+    }
+
+    fn lint(&self, files: &[PathBuf], root: &Path) -> Result<HashMap<PathBuf, Vec<LintMessage>>> {
+        let mut results: HashMap<PathBuf, Vec<LintMessage>> =
+            files.iter().map(|file| (file.clone(), vec![])).collect();
+        if files.is_empty() {
+            return Ok(results);
+        }
+        let output = self
+            .command(root)
+            .args(files)
+            .output()
+            .map_err(|e| anyhow!("Failed to start the linter: {}", e))?;
+        let messages = match parse_lint_output(&String::from_utf8_lossy(&output.stdout)) {
+            Ok(messages) => messages,
+            // the linter exits with errors of its own, e.g. an invalid config
+            Err(_) if !output.status.success() => {
+                return Err(anyhow!(
+                    "Lint failed: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                ));
+            }
+            Err(e) => return Err(anyhow!("Failed to parse the output of the linter: {}", e)),
+        };
+        for (file, message) in messages {
+            let file = root.join(file);
+            results.entry(file).or_default().push(message);
+        }
+        Ok(results)
+    }
+}
+
+impl Plugin for LintPlugin {
+    fn name(&self) -> &str {
+        "lint"
+    }
+
+    fn done(&self, _stats: &StatsJsonMap, context: &Arc<Context>) -> Result<()> {
+        let modules: HashMap<PathBuf, u64> = {
+            let module_graph = context.module_graph.read().unwrap();
+            module_graph
+                .modules()
+                .into_iter()
+                .filter_map(|module| {
+                    let info = module.info.as_ref()?;
+                    let file = &info.file;
+                    let is_lintable = !file.is_under_node_modules
+                        && !file.is_virtual
+                        && LINT_EXTENSIONS.contains(&file.extname.as_str());
+                    is_lintable.then(|| (file.pathname.clone(), info.raw_hash))
+                })
+                .collect()
+        };
+
+        let mut cache = self.cache.lock().unwrap();
+        cache.retain(|path, _| modules.contains_key(path));
+        let mut changed = modules
+            .iter()
+            .filter(|(path, hash)| cache.get(*path).map(|(h, _)| h) != Some(*hash))
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        changed.sort();
+
+        let results = match self.lint(&changed, &context.root) {
+            Ok(results) => results,
+            // the dev loop is not interrupted by the linter
+            Err(e) if context.args.watch => {
+                eprintln!("{}", e.to_string().red());
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+        for file in &changed {
+            let messages = results.get(file).cloned().unwrap_or_default();
+            print_messages(file, &messages, &context.root);
+            cache.insert(file.clone(), (modules[file], messages));
+        }
+
+        let errors = cache
+            .values()
+            .flat_map(|(_, messages)| messages)
+            .filter(|message| message.is_error)
+            .count();
+        if errors > 0 && self.config.fail_on_error && !context.args.watch {
+            return Err(anyhow!("Lint failed with {} errors", errors));
+        }
+        Ok(())
+    }
+}
+
+fn print_messages(file: &Path, messages: &[LintMessage], root: &Path) {
+    let file = file.strip_prefix(root).unwrap_or(file).to_string_lossy();
+    for message in messages {
+        let rule = message
+            .rule
+            .as_ref()
+            .map(|rule| format!(" ({})", rule))
+            .unwrap_or_default();
+        let location = format!("{}:{}:{}", file, message.line, message.column);
+        if message.is_error {
+            eprintln!(
+                "{} {} {}{}",
+                "Lint error:".red(),
+                location,
+                message.message,
+                rule
+            );
+        } else {
+            eprintln!(
+                "{} {} {}{}",
+                "Lint warning:".yellow(),
+                location,
+                message.message,
+                rule
+            );
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LintOutput {
+    /// the results of the files of eslint
+    Eslint(Vec<EslintResult>),
+    /// the diagnostics of oxlint
+    Oxlint { diagnostics: Vec<OxlintDiagnostic> },
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EslintResult {
+    file_path: String,
+    messages: Vec<EslintMessage>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EslintMessage {
+    rule_id: Option<String>,
+    /// 1 for the warnings and 2 for the errors
+    severity: u8,
+    message: String,
+    #[serde(default)]
+    line: usize,
+    #[serde(default)]
+    column: usize,
+}
+
+#[derive(Deserialize)]
+struct OxlintDiagnostic {
+    message: String,
+    /// e.g. `eslint(no-unused-vars)`
+    code: Option<String>,
+    severity: String,
+    filename: String,
+    #[serde(default)]
+    labels: Vec<OxlintLabel>,
+}
+
+#[derive(Deserialize)]
+struct OxlintLabel {
+    span: OxlintSpan,
+}
+
+#[derive(Deserialize)]
+struct OxlintSpan {
+    #[serde(default)]
+    line: usize,
+    #[serde(default)]
+    column: usize,
+}
+
+/// Parse the output of `--format json` of eslint and oxlint, which are the results of the files
+/// and the diagnostics of all the files respectively
+fn parse_lint_output(output: &str) -> Result<Vec<(String, LintMessage)>> {
+    let messages = match serde_json::from_str(output)? {
+        LintOutput::Eslint(results) => results
+            .into_iter()
+            .flat_map(|result| {
+                let file_path = result.file_path;
+                result.messages.into_iter().map(move |message| {
+                    (
+                        file_path.clone(),
+                        LintMessage {
+                            line: message.line,
+                            column: message.column,
+                            message: message.message,
+                            is_error: message.severity == 2,
+                            rule: message.rule_id,
+                        },
+                    )
+                })
+            })
+            .collect(),
+        LintOutput::Oxlint { diagnostics } => diagnostics
+            .into_iter()
+            .map(|diagnostic| {
+                let (line, column) = diagnostic
+                    .labels
+                    .first()
+                    .map_or((0, 0), |label| (label.span.line, label.span.column));
+                // eslint(no-unused-vars) -> no-unused-vars
+                let rule = diagnostic.code.map(|code| match code.split_once('(') {
+                    Some((_, rule)) => rule.trim_end_matches(')').to_string(),
+                    None => code,
+                });
+                (
+                    diagnostic.filename,
+                    LintMessage {
+                        line,
+                        column,
+                        message: diagnostic.message,
+                        is_error: diagnostic.severity == "error",
+                        rule,
+                    },
+                )
+            })
+            .collect(),
+    };
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_eslint_output() {
+        let output = r#"[
+  {
+    "filePath": "/root/src/index.ts",
+    "messages": [
+      {
+        "ruleId": "no-unused-vars",
+        "severity": 2,
+        "message": "'a' is assigned a value but never used.",
+        "line": 1,
+        "column": 7
+      },
+      { "ruleId": "no-console", "severity": 1, "message": "Unexpected console statement.", "line": 3, "column": 1 }
+    ],
+    "errorCount": 1,
+    "warningCount": 1
+  },
+  { "filePath": "/root/src/foo.js", "messages": [], "errorCount": 0, "warningCount": 0 }
+]"#;
+        assert_eq!(
+            parse_lint_output(output).unwrap(),
+            vec![
+                (
+                    "/root/src/index.ts".to_string(),
+                    LintMessage {
+                        line: 1,
+                        column: 7,
+                        message: "'a' is assigned a value but never used.".to_string(),
+                        is_error: true,
+                        rule: Some("no-unused-vars".to_string()),
+                    }
+                ),
+                (
+                    "/root/src/index.ts".to_string(),
+                    LintMessage {
+                        line: 3,
+                        column: 1,
+                        message: "Unexpected console statement.".to_string(),
+                        is_error: false,
+                        rule: Some("no-console".to_string()),
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_oxlint_output() {
+        let output = r#"{
+  "diagnostics": [
+    {
+      "message": "Variable 'a' is declared but never used.",
+      "code": "eslint(no-unused-vars)",
+      "severity": "warning",
+      "causes": [],
+      "filename": "src/index.ts",
+      "labels": [{ "label": "'a' is declared here", "span": { "offset": 6, "length": 1, "line": 1, "column": 7 } }],
+      "related": []
+    }
+  ],
+  "number_of_files": 1,
+  "number_of_rules": 100
+}"#;
+        assert_eq!(
+            parse_lint_output(output).unwrap(),
+            vec![(
+                "src/index.ts".to_string(),
+                LintMessage {
+                    line: 1,
+                    column: 7,
+                    message: "Variable 'a' is declared but never used.".to_string(),
+                    is_error: false,
+                    rule: Some("no-unused-vars".to_string()),
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_lint_output() {
+        assert!(parse_lint_output("Oops! Something went wrong!").is_err());
+    }
+}
@@ -29,9 +29,10 @@ impl Plugin for ManifestPlugin {
             let base_path = manifest_config.base_path.clone();
 
             let path = normalize_path(base_path);
+            let hash_reg = hash_regex(context.config.output.hash_length());
 
             for asset in assets {
-                let key = format!("{}{}", path, remove_key_hash(&asset.hashname));
+                let key = format!("{}{}", path, hash_reg.replace_all(&asset.hashname, "$1"));
                 manifest.insert(key, asset.hashname.clone());
             }
 
@@ -53,8 +54,30 @@ fn normalize_path(mut path: String) -> String {
     path
 }
 
-fn remove_key_hash(key: &str) -> String {
-    let reg = Regex::new(r"[a-fA-F0-9]{8}\.?").unwrap();
-    let val = reg.replace_all(key, "").to_string();
-    val
+/// the content hashes of the file names, of the configured length, between the name and the
+/// extension, which is kept by replacing the matches with `$1`
+fn hash_regex(hash_length: usize) -> Regex {
+    Regex::new(&format!(r"\.[a-fA-F0-9]{{{}}}(\.)", hash_length)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hash_regex;
+
+    #[test]
+    fn test_hash_regex() {
+        let reg = hash_regex(8);
+        assert_eq!(reg.replace_all("index.1a2b3c4d.js", "$1"), "index.js");
+        // the hex characters of the names aren't hashes
+        assert_eq!(reg.replace_all("facebook.js", "$1"), "facebook.js");
+        assert_eq!(reg.replace_all("deadbeef.js", "$1"), "deadbeef.js");
+        assert_eq!(reg.replace_all("facebook.1a2b3c4d.js", "$1"), "facebook.js");
+        assert_eq!(reg.replace_all("deadbeef.1a2b3c4d.js", "$1"), "deadbeef.js");
+        let reg = hash_regex(12);
+        assert_eq!(reg.replace_all("index.1a2b3c4d5e6f.js", "$1"), "index.js");
+        assert_eq!(
+            reg.replace_all("index.1a2b3c4d.js", "$1"),
+            "index.1a2b3c4d.js"
+        );
+    }
 }
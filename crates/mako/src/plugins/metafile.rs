@@ -0,0 +1,390 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use pathdiff::diff_paths;
+use serde::Serialize;
+use swc_core::ecma::ast::{
+    Decl, ExportSpecifier, Id, Module, ModuleDecl, ModuleExportName, ModuleItem,
+};
+use swc_core::ecma::utils::find_pat_ids;
+
+use crate::ast::file::win_path;
+use crate::compiler::Context;
+use crate::config::MetafileConfig;
+use crate::generate::chunk::ChunkType;
+use crate::module::{ModuleAst, ModuleId, ResolveType};
+use crate::plugin::{Plugin, PluginTransformJsParam};
+use crate::stats::StatsJsonMap;
+
+/**
+ * Write a metafile of the inputs and outputs after each build in the schema of the esbuild
+ * metafile, so the analyzers of esbuild like https://esbuild.github.io/analyze/ work with it,
+ * the paths are relative to the root, and the `bytesInOutput` of the inputs are estimated by
+ * distributing the bytes of the output file over its modules by their source sizes, since the
+ * modules are minified and concatenated as a whole
+ */
+pub struct MetafilePlugin {
+    config: MetafileConfig,
+    /// the export names of the entry modules, which are collected before they're transformed to
+    /// the commonjs of the runtime
+    entry_exports: Mutex<HashMap<String, Vec<String>>>,
+}
+
+#[derive(Serialize, Default, Debug)]
+struct Metafile {
+    inputs: BTreeMap<String, MetafileInput>,
+    outputs: BTreeMap<String, MetafileOutput>,
+}
+
+#[derive(Serialize, Debug)]
+struct MetafileInput {
+    bytes: u64,
+    imports: Vec<MetafileImport>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct MetafileImport {
+    path: String,
+    kind: &'static str,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    external: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    original: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct MetafileOutput {
+    bytes: u64,
+    inputs: BTreeMap<String, MetafileOutputInput>,
+    imports: Vec<MetafileImport>,
+    exports: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entry_point: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    css_bundle: Option<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct MetafileOutputInput {
+    bytes_in_output: u64,
+}
+
+impl MetafilePlugin {
+    pub fn new(config: &MetafileConfig) -> Self {
+        Self {
+            config: config.clone(),
+            entry_exports: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn collect(&self, context: &Arc<Context>) -> Metafile {
+        let root = &context.root;
+        let chunk_graph = context.chunk_graph.read().unwrap();
+        let module_graph = context.module_graph.read().unwrap();
+        let entry_exports = self.entry_exports.lock().unwrap();
+        let mut metafile = Metafile::default();
+
+        for module in module_graph.modules() {
+            let Some(info) = &module.info else {
+                continue;
+            };
+            if info.external.is_some() {
+                continue;
+            }
+            let mut imports = module_graph
+                .get_dependencies(&module.id)
+                .into_iter()
+                .map(|(dep_id, dep)| {
+                    let external = module_graph
+                        .get_module(dep_id)
+                        .and_then(|dep_module| dep_module.info.as_ref())
+                        .is_some_and(|dep_info| dep_info.external.is_some());
+                    MetafileImport {
+                        path: if external {
+                            dep.source.clone()
+                        } else {
+                            relative_path(&dep_id.id, root)
+                        },
+                        kind: import_kind(&dep.resolve_type),
+                        external,
+                        original: Some(dep.source.clone()),
+                    }
+                })
+                .collect::<Vec<_>>();
+            imports.dedup();
+            metafile.inputs.insert(
+                relative_path(&module.id.id, root),
+                MetafileInput {
+                    bytes: info.raw.len() as u64,
+                    imports,
+                },
+            );
+        }
+
+        let module_chunks = chunk_graph
+            .get_chunks()
+            .into_iter()
+            .flat_map(|chunk| chunk.get_modules().iter().map(|id| (id, &chunk.id)))
+            .collect::<HashMap<_, _>>();
+        let output_path = |hashname: &str| {
+            relative_path(
+                &context.config.output.path.join(hashname).to_string_lossy(),
+                root,
+            )
+        };
+        let mut assets = context.stats_info.get_assets();
+        assets.sort();
+        // the output files of the chunks by their types
+        let chunk_outputs = assets
+            .iter()
+            .filter(|asset| !asset.chunk_id.is_empty())
+            .map(|asset| {
+                let is_css = asset.hashname.ends_with(".css");
+                (
+                    (asset.chunk_id.clone(), is_css),
+                    output_path(&asset.hashname),
+                )
+            })
+            .filter(|(_, output)| !output.ends_with(".map"))
+            .collect::<HashMap<_, _>>();
+
+        for asset in &assets {
+            let mut output = MetafileOutput {
+                bytes: asset.size,
+                inputs: BTreeMap::new(),
+                imports: vec![],
+                exports: vec![],
+                entry_point: None,
+                css_bundle: None,
+            };
+            let chunk = chunk_graph.chunk(&ModuleId::new(asset.chunk_id.clone()));
+            if let Some(chunk) = chunk
+                && !asset.hashname.ends_with(".map")
+            {
+                let is_css = asset.hashname.ends_with(".css");
+                // the js and css files of a chunk share the chunk modules by their types
+                let modules = chunk
+                    .get_modules()
+                    .iter()
+                    .filter_map(|id| module_graph.get_module(id))
+                    .filter(|module| {
+                        module.info.as_ref().is_some_and(|info| {
+                            info.external.is_none()
+                                && matches!(info.ast, ModuleAst::Css(_)) == is_css
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let sizes = modules
+                    .iter()
+                    .map(|module| {
+                        (
+                            relative_path(&module.id.id, root),
+                            module.info.as_ref().unwrap().raw.len() as u64,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                output.inputs = bytes_in_output(asset.size, &sizes);
+
+                if !is_css {
+                    let mut imports = modules
+                        .iter()
+                        .flat_map(|module| module_graph.get_dependencies(&module.id))
+                        .filter_map(|(dep_id, dep)| {
+                            let dep_info = module_graph.get_module(dep_id)?.info.as_ref()?;
+                            if dep_info.external.is_some() {
+                                return Some(MetafileImport {
+                                    path: dep.source.clone(),
+                                    kind: import_kind(&dep.resolve_type),
+                                    external: true,
+                                    original: None,
+                                });
+                            }
+                            let dep_chunk = module_chunks.get(dep_id)?;
+                            if *dep_chunk == &chunk.id {
+                                return None;
+                            }
+                            let is_css = matches!(dep_info.ast, ModuleAst::Css(_));
+                            Some(MetafileImport {
+                                path: chunk_outputs.get(&(dep_chunk.id.clone(), is_css))?.clone(),
+                                kind: import_kind(&dep.resolve_type),
+                                external: false,
+                                original: None,
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    imports.sort();
+                    imports.dedup();
+                    output.imports = imports;
+
+                    if let ChunkType::Entry(entry_id, _, false) = &chunk.chunk_type {
+                        output.entry_point = Some(relative_path(&entry_id.id, root));
+                        output.exports =
+                            entry_exports.get(&entry_id.id).cloned().unwrap_or_default();
+                        output.css_bundle =
+                            chunk_outputs.get(&(asset.chunk_id.clone(), true)).cloned();
+                    }
+                }
+            }
+            metafile
+                .outputs
+                .insert(output_path(&asset.hashname), output);
+        }
+        metafile
+    }
+}
+
+impl Plugin for MetafilePlugin {
+    fn name(&self) -> &str {
+        "metafile"
+    }
+
+    fn transform_js(
+        &self,
+        param: &PluginTransformJsParam,
+        ast: &mut Module,
+        context: &Arc<Context>,
+    ) -> Result<()> {
+        let is_entry = context
+            .entries
+            .read()
+            .unwrap()
+            .values()
+            .any(|entry| entry.to_string_lossy() == param.path);
+        if is_entry {
+            self.entry_exports
+                .lock()
+                .unwrap()
+                .insert(param.path.to_string(), export_names(ast));
+        }
+        Ok(())
+    }
+
+    fn done(&self, _stats: &StatsJsonMap, context: &Arc<Context>) -> Result<()> {
+        let metafile = self.collect(context);
+        let path = context.config.output.path.join(&self.config.file_name);
+        fs::write(path, serde_json::to_string_pretty(&metafile)?)?;
+        Ok(())
+    }
+}
+
+fn relative_path(path: &str, root: &Path) -> String {
+    let relative_path = diff_paths(path, root).unwrap_or_else(|| path.into());
+    win_path(&relative_path.to_string_lossy())
+}
+
+// the kinds of the imports in the esbuild metafile
+fn import_kind(resolve_type: &ResolveType) -> &'static str {
+    match resolve_type {
+        ResolveType::Import(_) | ResolveType::ExportNamed(_) | ResolveType::ExportAll => {
+            "import-statement"
+        }
+        ResolveType::Require => "require-call",
+        // the workers are loaded lazily like the dynamic imports
+        ResolveType::DynamicImport(_) | ResolveType::Worker(_) => "dynamic-import",
+        ResolveType::Css => "import-rule",
+    }
+}
+
+// distribute the bytes of the output over the inputs by their source sizes
+fn bytes_in_output(bytes: u64, sizes: &[(String, u64)]) -> BTreeMap<String, MetafileOutputInput> {
+    let total = sizes.iter().map(|(_, size)| size).sum::<u64>();
+    sizes
+        .iter()
+        .map(|(path, size)| {
+            let bytes_in_output = if total == 0 {
+                0
+            } else {
+                (bytes as u128 * *size as u128 / total as u128) as u64
+            };
+            (path.clone(), MetafileOutputInput { bytes_in_output })
+        })
+        .collect()
+}
+
+fn export_names(ast: &Module) -> Vec<String> {
+    let export_name = |name: &ModuleExportName| match name {
+        ModuleExportName::Ident(ident) => ident.sym.to_string(),
+        ModuleExportName::Str(str) => str.value.to_string(),
+    };
+    let mut names = vec![];
+    for item in &ast.body {
+        match item {
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export)) => match &export.decl {
+                Decl::Fn(f) => names.push(f.ident.sym.to_string()),
+                Decl::Class(c) => names.push(c.ident.sym.to_string()),
+                Decl::Var(var) => {
+                    let ids: Vec<Id> = find_pat_ids(&var.decls);
+                    names.extend(ids.into_iter().map(|id| id.0.to_string()));
+                }
+                _ => {}
+            },
+            ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(export)) => {
+                for specifier in &export.specifiers {
+                    names.push(match specifier {
+                        ExportSpecifier::Named(named) => {
+                            export_name(named.exported.as_ref().unwrap_or(&named.orig))
+                        }
+                        ExportSpecifier::Namespace(namespace) => export_name(&namespace.name),
+                        ExportSpecifier::Default(default) => default.exported.sym.to_string(),
+                    });
+                }
+            }
+            ModuleItem::ModuleDecl(
+                ModuleDecl::ExportDefaultDecl(_) | ModuleDecl::ExportDefaultExpr(_),
+            ) => names.push("default".to_string()),
+            _ => {}
+        }
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{bytes_in_output, export_names, relative_path};
+    use crate::ast::tests::TestUtils;
+
+    #[test]
+    fn test_relative_path() {
+        let root = Path::new("/project");
+        assert_eq!(relative_path("/project/src/index.ts", root), "src/index.ts");
+        assert_eq!(relative_path("virtual:routes", root), "virtual:routes");
+    }
+
+    #[test]
+    fn test_bytes_in_output() {
+        let inputs = bytes_in_output(100, &[("a.ts".to_string(), 300), ("b.ts".to_string(), 100)]);
+        assert_eq!(inputs["a.ts"].bytes_in_output, 75);
+        assert_eq!(inputs["b.ts"].bytes_in_output, 25);
+        assert_eq!(
+            bytes_in_output(100, &[("a.ts".to_string(), 0)])["a.ts"].bytes_in_output,
+            0
+        );
+    }
+
+    #[test]
+    fn test_export_names() {
+        let test_utils = TestUtils::gen_js_ast(
+            r#"
+export const a = 1, { b } = {};
+export function c() {}
+export { d as e, f as "g" };
+export * as h from './h';
+export default 1;
+const d = 1, f = 2;
+"#,
+        );
+        assert_eq!(
+            export_names(&test_utils.js().ast),
+            vec!["a", "b", "c", "default", "e", "g", "h"]
+        );
+    }
+}
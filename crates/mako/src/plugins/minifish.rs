@@ -71,7 +71,8 @@ impl Plugin for MinifishPlugin {
                 }))),
 
                 None => {
-                    let content = FileSystem::read_file(&param.file.pathname)?;
+                    let content =
+                        FileSystem::read_file(&param.file.pathname, _context.config.lossy_utf8)?;
                     // let content = read_content(param.file.pathname)?;
 
                     let asset = Asset {
@@ -185,7 +186,7 @@ impl Plugin for MinifishPlugin {
                         .get_dependencies(id)
                         .iter()
                         .map(|dep| Dependency {
-                            module: dep.0.id.clone(),
+                            module: dep.0.id.to_string(),
                             import_type: dep.1.resolve_type.clone(),
                         })
                         .collect();
@@ -201,7 +202,7 @@ impl Plugin for MinifishPlugin {
 
                     Module {
                         filename,
-                        id: id.id.clone(),
+                        id: id.id.to_string(),
                         dependencies: deps,
                     }
                 })
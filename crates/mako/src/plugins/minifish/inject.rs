@@ -491,7 +491,10 @@ my.call("toast");
     fn injected_require_treat_as_dep() {
         let code = r#"my.call("toast");"#;
         let context = Context {
-            args: Args { watch: true },
+            args: Args {
+                watch: true,
+                ..Default::default()
+            },
             ..Context::default()
         };
         let context = Arc::new(context);
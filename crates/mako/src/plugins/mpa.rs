@@ -0,0 +1,307 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+
+use crate::compiler::Context;
+use crate::config::{MpaConfig, OutputFormat};
+use crate::generate::chunk::ChunkType;
+use crate::plugin::Plugin;
+use crate::plugins::legacy::{LEGACY_DIR, POLYFILLS_CHUNK_ID};
+use crate::stats::StatsJsonMap;
+
+const DEFAULT_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title><%= title %></title>
+</head>
+<body>
+<div id="root"></div>
+</body>
+</html>
+"#;
+
+/**
+ * Emit a html for each entry of the multi-page application, e.g. `foo.html` for the entry foo,
+ * with the css and js files of the entry chunk and the chunks it depends on injected, each page
+ * has its own runtime in the entry chunk and the shared chunks are loaded before it
+ */
+pub struct MpaPlugin {
+    config: MpaConfig,
+    /// the pages of the last build, the removed entries' pages are deleted in watch mode
+    pages: Mutex<HashSet<String>>,
+}
+
+impl MpaPlugin {
+    pub fn new(config: &MpaConfig) -> Self {
+        Self {
+            config: config.clone(),
+            pages: Default::default(),
+        }
+    }
+
+    fn template(&self, context: &Arc<Context>) -> Result<String> {
+        match &self.config.template {
+            Some(template) => fs::read_to_string(context.root.join(template))
+                .map_err(|_| anyhow!("mpa.template {} not found", template)),
+            None => Ok(DEFAULT_TEMPLATE.to_string()),
+        }
+    }
+
+    /// The css and js files of the entry, the files of the shared chunks go first
+    fn entry_files(&self, entry_chunk_id: &str, context: &Arc<Context>) -> Vec<String> {
+        let chunk_graph = context.chunk_graph.read().unwrap();
+        let mut chunk_ids = chunk_graph
+            .entry_dependencies_chunk(&entry_chunk_id.into())
+            .into_iter()
+            .map(|id| id.id.to_string())
+            .collect::<Vec<_>>();
+        // the neighbors ordering is reversed
+        chunk_ids.reverse();
+        chunk_ids.push(entry_chunk_id.to_string());
+        // the polyfills of the legacy output go before all the legacy files
+        if context.config.legacy.is_some() {
+            chunk_ids.insert(0, POLYFILLS_CHUNK_ID.to_string());
+        }
+
+        let mut assets = context.stats_info.get_assets();
+        assets.sort();
+        chunk_ids
+            .iter()
+            .flat_map(|chunk_id| {
+                assets
+                    .iter()
+                    .filter(|asset| &asset.chunk_id == chunk_id)
+                    .map(|asset| asset.hashname.clone())
+                    .filter(|file| file.ends_with(".js") || file.ends_with(".css"))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// The contents of the scripts to inline, the esm scripts are not inlined since the imports of
+    /// the shared chunks are relative to the scripts, nor are the scripts with the legacy output
+    /// since the inline scripts can't be told from the nomodule ones
+    fn inlined_scripts(&self, files: &[String], context: &Arc<Context>) -> HashMap<String, String> {
+        if self.config.inline_limit == 0
+            || context.config.output.format == OutputFormat::Esm
+            || context.config.legacy.is_some()
+        {
+            return HashMap::new();
+        }
+        files
+            .iter()
+            .filter(|file| file.ends_with(".js"))
+            .filter_map(|file| {
+                let content = fs::read_to_string(context.config.output.path.join(file)).ok()?;
+                (content.len() <= self.config.inline_limit).then(|| (file.clone(), content))
+            })
+            .collect()
+    }
+}
+
+impl Plugin for MpaPlugin {
+    fn name(&self) -> &str {
+        "mpa"
+    }
+
+    fn done(&self, _stats: &StatsJsonMap, context: &Arc<Context>) -> Result<()> {
+        if context.config.output.skip_write {
+            return Ok(());
+        }
+        let template = self.template(context)?;
+        let entries = {
+            let chunk_graph = context.chunk_graph.read().unwrap();
+            chunk_graph
+                .get_chunks()
+                .into_iter()
+                .filter_map(|chunk| match &chunk.chunk_type {
+                    ChunkType::Entry(_, name, false) => {
+                        Some((name.clone(), chunk.id.id.to_string()))
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        let names = entries
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect::<HashSet<_>>();
+        let mut pages = self.pages.lock().unwrap();
+        for name in pages.difference(&names) {
+            let _ = fs::remove_file(context.config.output.path.join(format!("{}.html", name)));
+        }
+        *pages = names;
+        drop(pages);
+        for (name, chunk_id) in entries {
+            let files = self.entry_files(&chunk_id, context);
+            let inlined = self.inlined_scripts(&files, context);
+            let html = render_html(&template, &name, &files, &inlined, context);
+            let path = context.config.output.path.join(format!("{}.html", name));
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(path, html)?;
+        }
+        Ok(())
+    }
+}
+
+fn render_html(
+    template: &str,
+    name: &str,
+    files: &[String],
+    inlined: &HashMap<String, String>,
+    context: &Arc<Context>,
+) -> String {
+    let public_path = match context.config.public_path.as_str() {
+        "runtime" => "/",
+        public_path => public_path,
+    };
+    let cross_origin = context
+        .config
+        .output
+        .cross_origin_loading
+        .as_ref()
+        .map(|cross_origin| format!(r#" crossorigin="{}""#, cross_origin))
+        .unwrap_or_default();
+    let nonce = context
+        .config
+        .output
+        .csp_nonce
+        .as_ref()
+        .map(|nonce| format!(r#" nonce="{}""#, nonce))
+        .unwrap_or_default();
+    // the legacy scripts are loaded by the browsers without the es modules support instead
+    let is_legacy = |file: &str| file.starts_with(&format!("{}/", LEGACY_DIR));
+    let script_type = match context.config.output.format {
+        OutputFormat::Esm => r#" type="module""#,
+        OutputFormat::Iife if context.config.legacy.is_some() => r#" type="module""#,
+        OutputFormat::Iife => "",
+    };
+    let links = files
+        .iter()
+        .filter(|file| file.ends_with(".css") && !is_legacy(file))
+        .map(|file| {
+            format!(
+                r#"<link rel="stylesheet" href="{}{}"{}{}>"#,
+                public_path, file, cross_origin, nonce
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let scripts = files
+        .iter()
+        .filter(|file| file.ends_with(".js"))
+        .map(|file| match inlined.get(file) {
+            // avoid closing the script tag early
+            Some(content) => format!(
+                "<script{}>{}</script>",
+                nonce,
+                content.replace("</script", "<\\/script")
+            ),
+            None if is_legacy(file) => format!(
+                r#"<script nomodule src="{}{}"{}{}></script>"#,
+                public_path, file, cross_origin, nonce
+            ),
+            None => format!(
+                r#"<script{} src="{}{}"{}{}></script>"#,
+                script_type, public_path, file, cross_origin, nonce
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let html = template.replace("<%= title %>", name);
+    let html = inject_before(&html, "</head>", &links);
+    inject_before(&html, "</body>", &scripts)
+}
+
+/// Inject the tags before the last closing tag, or append them if the tag is missing
+fn inject_before(html: &str, tag: &str, tags: &str) -> String {
+    if tags.is_empty() {
+        return html.to_string();
+    }
+    match html.rfind(tag) {
+        Some(index) => format!("{}{}\n{}", &html[..index], tags, &html[index..]),
+        None => format!("{}{}\n", html, tags),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LegacyConfig;
+
+    #[test]
+    fn test_render_html() {
+        let context = Arc::new(Context::default());
+        let files = vec![
+            "vendors-async.js".to_string(),
+            "foo.css".to_string(),
+            "foo.js".to_string(),
+        ];
+        let html = render_html(DEFAULT_TEMPLATE, "foo", &files, &HashMap::new(), &context);
+        assert!(html.contains("<title>foo</title>"));
+        assert!(html.contains("<link rel=\"stylesheet\" href=\"/foo.css\">\n</head>"));
+        assert!(html.contains(
+            "<script src=\"/vendors-async.js\"></script>\n<script src=\"/foo.js\"></script>\n</body>"
+        ));
+    }
+
+    #[test]
+    fn test_render_html_with_inlined_scripts() {
+        let context = Arc::new(Context::default());
+        let files = vec!["vendors-async.js".to_string(), "foo.js".to_string()];
+        let inlined = HashMap::from([("foo.js".to_string(), "var s = '</script>';".to_string())]);
+        let html = render_html(DEFAULT_TEMPLATE, "foo", &files, &inlined, &context);
+        assert!(html.contains(
+            "<script src=\"/vendors-async.js\"></script>\n<script>var s = '<\\/script>';</script>\n</body>"
+        ));
+    }
+
+    #[test]
+    fn test_render_html_with_legacy() {
+        let mut context = Context::default();
+        context.config.legacy = Some(LegacyConfig {
+            targets: HashMap::new(),
+            polyfills: true,
+        });
+        let context = Arc::new(context);
+        let files = [
+            "legacy/polyfills.js",
+            "index.css",
+            "index.js",
+            "legacy/index.css",
+            "legacy/index.js",
+        ]
+        .map(String::from);
+        let html = render_html(DEFAULT_TEMPLATE, "index", &files, &HashMap::new(), &context);
+        assert!(html.contains("<link rel=\"stylesheet\" href=\"/index.css\">\n</head>"));
+        assert!(!html.contains("legacy/index.css"));
+        assert!(html.contains(
+            "<script nomodule src=\"/legacy/polyfills.js\"></script>\n<script type=\"module\" src=\"/index.js\"></script>\n<script nomodule src=\"/legacy/index.js\"></script>\n</body>"
+        ));
+    }
+
+    #[test]
+    fn test_render_html_with_csp_nonce() {
+        let mut context = Context::default();
+        context.config.output.csp_nonce = Some("{{nonce}}".to_string());
+        let context = Arc::new(context);
+        let files = vec!["foo.css".to_string(), "foo.js".to_string()];
+        let html = render_html(DEFAULT_TEMPLATE, "foo", &files, &HashMap::new(), &context);
+        assert!(html.contains("<link rel=\"stylesheet\" href=\"/foo.css\" nonce=\"{{nonce}}\">"));
+        assert!(html.contains("<script src=\"/foo.js\" nonce=\"{{nonce}}\"></script>"));
+    }
+
+    #[test]
+    fn test_inject_without_tag() {
+        assert_eq!(
+            inject_before("<div></div>", "</body>", "<script></script>"),
+            "<div></div><script></script>\n"
+        );
+    }
+}
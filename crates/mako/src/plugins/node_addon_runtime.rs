@@ -0,0 +1,31 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::compiler::Context;
+use crate::plugin::Plugin;
+
+pub struct NodeAddonRuntimePlugin {}
+
+impl Plugin for NodeAddonRuntimePlugin {
+    fn name(&self) -> &str {
+        "node_addon_runtime"
+    }
+
+    fn runtime_plugins(&self, context: &Arc<Context>) -> Result<Vec<String>> {
+        if context
+            .assets_info
+            .lock()
+            .unwrap()
+            .values()
+            .any(|info| info.ends_with(".node"))
+        {
+            Ok(vec![include_str!(
+                "./node_addon_runtime/node_addon_runtime.js"
+            )
+            .to_string()])
+        } else {
+            Ok(vec![])
+        }
+    }
+}
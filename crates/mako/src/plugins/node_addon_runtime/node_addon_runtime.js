@@ -0,0 +1,4 @@
+requireModule._requireNodeAddon = (addonPath) => {
+  // the addons are emitted to the output dir, which is the parent of the nested entries
+  return require('./' + requireModule.outputRoot + addonPath);
+};
@@ -0,0 +1,219 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+
+use crate::compiler::Context;
+use crate::config::{PerformanceBudgets, PerformanceHints};
+use crate::generate::chunk::ChunkType;
+use crate::plugin::Plugin;
+use crate::stats::{human_readable_size, AssetsInfo, StatsJsonMap};
+
+pub struct PerformanceBudgetsPlugin {}
+
+#[derive(Debug, PartialEq, Eq)]
+enum BudgetViolation {
+    Asset { name: String, size: u64, limit: u64 },
+    Entry { name: String, size: u64, limit: u64 },
+    Total { size: u64, limit: u64 },
+}
+
+impl std::fmt::Display for BudgetViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BudgetViolation::Asset { name, size, limit } => write!(
+                f,
+                "asset {} ({}) exceeds maxAssetSize ({})",
+                name.bold(),
+                human_readable_size(*size),
+                human_readable_size(*limit)
+            ),
+            BudgetViolation::Entry { name, size, limit } => write!(
+                f,
+                "entry {} ({}) exceeds maxEntrySize ({})",
+                name.bold(),
+                human_readable_size(*size),
+                human_readable_size(*limit)
+            ),
+            BudgetViolation::Total { size, limit } => write!(
+                f,
+                "total size ({}) exceeds maxTotalSize ({})",
+                human_readable_size(*size),
+                human_readable_size(*limit)
+            ),
+        }
+    }
+}
+
+impl Plugin for PerformanceBudgetsPlugin {
+    fn name(&self) -> &str {
+        "performance_budgets"
+    }
+
+    fn build_success(&self, _stats: &StatsJsonMap, context: &Arc<Context>) -> Result<()> {
+        let Some(performance) = &context.config.performance else {
+            return Ok(());
+        };
+
+        let assets = context
+            .stats_info
+            .get_assets()
+            .into_iter()
+            .filter(|asset| !asset.hashname.ends_with(".map"))
+            .collect::<Vec<_>>();
+        let entry_sizes = entry_sizes(&assets, context);
+        let violations = check_budgets(&performance.budgets, &assets, &entry_sizes);
+
+        println!("{}", "Entry sizes:".bold());
+        for (name, size) in &entry_sizes {
+            println!("  {} {}", name.cyan(), human_readable_size(*size));
+        }
+
+        if violations.is_empty() {
+            return Ok(());
+        }
+
+        let message = violations
+            .iter()
+            .map(|v| format!("  - {}", v))
+            .collect::<Vec<_>>()
+            .join("\n");
+        // never fail the build in watch mode, otherwise the dev server will stop
+        if performance.hints == PerformanceHints::Error && !context.args.watch {
+            Err(anyhow!(
+                "{}\n{}",
+                "Performance budgets exceeded:".red(),
+                message
+            ))
+        } else {
+            println!("{}\n{}", "Performance budgets exceeded:".yellow(), message);
+            Ok(())
+        }
+    }
+}
+
+/// Size of every entry, which is the sum of the entry chunk and the sync chunks it depends on.
+fn entry_sizes(assets: &[AssetsInfo], context: &Arc<Context>) -> BTreeMap<String, u64> {
+    let chunk_graph = context.chunk_graph.read().unwrap();
+
+    chunk_graph
+        .get_chunks()
+        .into_iter()
+        .filter_map(|chunk| match &chunk.chunk_type {
+            ChunkType::Entry(_, name, false) => {
+                let mut chunk_ids = chunk_graph
+                    .entry_dependencies_chunk(&chunk.id)
+                    .into_iter()
+                    .map(|id| id.id.to_string())
+                    .collect::<HashSet<_>>();
+                chunk_ids.insert(chunk.id.id.to_string());
+
+                let size = assets
+                    .iter()
+                    .filter(|asset| chunk_ids.contains(&asset.chunk_id))
+                    .map(|asset| asset.size)
+                    .sum();
+                Some((name.clone(), size))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn check_budgets(
+    budgets: &PerformanceBudgets,
+    assets: &[AssetsInfo],
+    entry_sizes: &BTreeMap<String, u64>,
+) -> Vec<BudgetViolation> {
+    let mut violations = vec![];
+
+    if let Some(limit) = budgets.max_asset_size {
+        for asset in assets.iter().filter(|asset| asset.size > limit) {
+            violations.push(BudgetViolation::Asset {
+                name: asset.hashname.clone(),
+                size: asset.size,
+                limit,
+            });
+        }
+    }
+
+    if let Some(limit) = budgets.max_entry_size {
+        for (name, size) in entry_sizes.iter().filter(|(_, size)| **size > limit) {
+            violations.push(BudgetViolation::Entry {
+                name: name.clone(),
+                size: *size,
+                limit,
+            });
+        }
+    }
+
+    if let Some(limit) = budgets.max_total_size {
+        let size = assets.iter().map(|asset| asset.size).sum::<u64>();
+        if size > limit {
+            violations.push(BudgetViolation::Total { size, limit });
+        }
+    }
+
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(name: &str, size: u64) -> AssetsInfo {
+        AssetsInfo {
+            assets_type: "asset".to_string(),
+            size,
+            name: name.to_string(),
+            hashname: name.to_string(),
+            chunk_id: name.to_string(),
+            path: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_check_budgets() {
+        let assets = vec![asset("index.js", 300), asset("async.js", 100)];
+        let entry_sizes = BTreeMap::from([("index".to_string(), 300)]);
+        let budgets = PerformanceBudgets {
+            max_entry_size: Some(200),
+            max_asset_size: Some(250),
+            max_total_size: Some(350),
+        };
+
+        assert_eq!(
+            check_budgets(&budgets, &assets, &entry_sizes),
+            vec![
+                BudgetViolation::Asset {
+                    name: "index.js".to_string(),
+                    size: 300,
+                    limit: 250
+                },
+                BudgetViolation::Entry {
+                    name: "index".to_string(),
+                    size: 300,
+                    limit: 200
+                },
+                BudgetViolation::Total {
+                    size: 400,
+                    limit: 350
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_budgets_within_limits() {
+        let assets = vec![asset("index.js", 100)];
+        let entry_sizes = BTreeMap::from([("index".to_string(), 100)]);
+        let budgets = PerformanceBudgets {
+            max_entry_size: Some(200),
+            max_asset_size: None,
+            max_total_size: Some(200),
+        };
+
+        assert!(check_budgets(&budgets, &assets, &entry_sizes).is_empty());
+    }
+}
@@ -26,6 +26,10 @@ use crate::plugin::{Plugin, PluginLoadParam};
  * 13. runtime_plugins
  * 14. after_generate_chunk_files
  * 15. build_success
+ * 16. generate_end
+ * 17. write_bundle
+ * 18. done
+ * watch 模式下，watch_changes 在每个文件变更后、重新构建前执行，done 在每次重新构建后执行
  * after_generate_transform_js、before_write_fs 仅 mode bundless 执行
  */
 
@@ -0,0 +1,236 @@
+use std::collections::HashSet;
+use std::fs;
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{anyhow, Result};
+use glob::glob;
+use regex::Regex;
+use swc_core::css::ast::{
+    ClassSelector, ComplexSelector, ComplexSelectorChildren, ComponentValue, QualifiedRule,
+    QualifiedRulePrelude, Rule, SubclassSelector,
+};
+use swc_core::css::visit::{VisitMut, VisitMutWith};
+use tracing::debug;
+
+use crate::compiler::Context;
+use crate::config::PurgeCssConfig;
+use crate::module::ModuleAst;
+use crate::plugin::Plugin;
+
+/**
+ * Remove the selectors whose class names are not used in js/html content,
+ * similar to purgecss
+ */
+pub struct PurgeCssPlugin {
+    safelist: Vec<Regex>,
+    content: Vec<String>,
+}
+
+impl PurgeCssPlugin {
+    pub fn new(config: &PurgeCssConfig) -> Result<Self> {
+        let safelist = config
+            .safelist
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .map_err(|e| anyhow!("Invalid purgeCSS.safelist {}: {}", pattern, e))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            safelist,
+            content: config.content.clone(),
+        })
+    }
+
+    fn used_tokens(&self, context: &Arc<Context>) -> Result<HashSet<String>> {
+        let mut tokens = HashSet::new();
+        let module_graph = context.module_graph.read().unwrap();
+        module_graph
+            .modules()
+            .iter()
+            .filter_map(|module| module.info.as_ref())
+            .filter(|info| matches!(info.ast, ModuleAst::Script(_)))
+            .for_each(|info| extract_tokens(&info.raw, &mut tokens));
+
+        for pattern in &self.content {
+            let pattern = context.root.join(pattern);
+            for path in glob(&pattern.to_string_lossy())? {
+                let content = fs::read_to_string(path?)?;
+                extract_tokens(&content, &mut tokens);
+            }
+        }
+        Ok(tokens)
+    }
+}
+
+impl Plugin for PurgeCssPlugin {
+    fn name(&self) -> &str {
+        "purge_css"
+    }
+
+    fn before_optimize_chunk(&self, context: &Arc<Context>) -> Result<()> {
+        // newly added class names can't be known ahead in watch mode
+        if context.args.watch {
+            return Ok(());
+        }
+
+        let used = self.used_tokens(context)?;
+        let mut remover = UnusedSelectorRemover {
+            used: &used,
+            safelist: &self.safelist,
+        };
+        let mut module_graph = context.module_graph.write().unwrap();
+        for module in module_graph.modules_mut() {
+            // class names of css modules are referenced by the generated js object
+            if module.id.id.contains("?modules") {
+                continue;
+            }
+            if let Some(info) = module.info.as_mut()
+                && let ModuleAst::Css(ast) = &mut info.ast
+            {
+                debug!("purge css: {}", module.id.id);
+                ast.ast.visit_mut_with(&mut remover);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Collect all the words which may be class names, same as the default extractor of purgecss
+fn extract_tokens(content: &str, tokens: &mut HashSet<String>) {
+    static TOKEN_REGEXP: OnceLock<Regex> = OnceLock::new();
+    let token_regexp = TOKEN_REGEXP.get_or_init(|| Regex::new(r"[\w\-:/]+").unwrap());
+    for token in token_regexp.find_iter(content) {
+        tokens.insert(token.as_str().to_string());
+    }
+}
+
+struct UnusedSelectorRemover<'a> {
+    used: &'a HashSet<String>,
+    safelist: &'a [Regex],
+}
+
+impl UnusedSelectorRemover<'_> {
+    fn is_used_class(&self, class: &str) -> bool {
+        self.used.contains(class) || self.safelist.iter().any(|re| re.is_match(class))
+    }
+
+    // only the class names of compound selectors are checked, the ones in pseudo classes
+    // like `.a:not(.b)` don't decide whether the selector matches or not
+    fn is_used_selector(&self, selector: &ComplexSelector) -> bool {
+        selector.children.iter().all(|child| match child {
+            ComplexSelectorChildren::CompoundSelector(compound) => {
+                compound.subclass_selectors.iter().all(|sub| match sub {
+                    SubclassSelector::Class(ClassSelector { text, .. }) => {
+                        self.is_used_class(&text.value)
+                    }
+                    _ => true,
+                })
+            }
+            _ => true,
+        })
+    }
+
+    /// Returns false when all the selectors of the rule are removed
+    fn purge_rule(&self, rule: &mut QualifiedRule) -> bool {
+        match &mut rule.prelude {
+            QualifiedRulePrelude::SelectorList(list) => {
+                list.children
+                    .retain(|selector| self.is_used_selector(selector));
+                !list.children.is_empty()
+            }
+            _ => true,
+        }
+    }
+}
+
+impl VisitMut for UnusedSelectorRemover<'_> {
+    fn visit_mut_rules(&mut self, rules: &mut Vec<Rule>) {
+        rules.retain_mut(|rule| match rule {
+            Rule::QualifiedRule(rule) => self.purge_rule(rule),
+            _ => true,
+        });
+        rules.visit_mut_children_with(self);
+    }
+
+    // rules nested in at rules, e.g. @media
+    fn visit_mut_component_values(&mut self, values: &mut Vec<ComponentValue>) {
+        values.retain_mut(|value| match value {
+            ComponentValue::QualifiedRule(rule) => self.purge_rule(rule),
+            _ => true,
+        });
+        values.visit_mut_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use regex::Regex;
+    use swc_core::css::visit::VisitMutWith;
+
+    use super::{extract_tokens, UnusedSelectorRemover};
+    use crate::ast::tests::TestUtils;
+
+    #[test]
+    fn test_extract_tokens() {
+        let mut tokens = HashSet::new();
+        extract_tokens(
+            r#"<div className={cx("btn btn-primary", styles.foo)} class="w-1/2 hover:underline">"#,
+            &mut tokens,
+        );
+        for token in ["btn", "btn-primary", "foo", "w-1/2", "hover:underline"] {
+            assert!(tokens.contains(token), "missing {}", token);
+        }
+    }
+
+    #[test]
+    fn test_remove_unused_selectors() {
+        assert_eq!(
+            run(".a{color:red}.b{color:red}.a,.b{color:blue}", "a", &[]),
+            ".a{color:red}.a{color:blue}"
+        );
+        assert_eq!(run("div,.b .c{color:red}", "c", &[]), "div{color:red}");
+        let code = run(
+            "@media (max-width:100px){.a{color:red}.b{color:red}}",
+            "a",
+            &[],
+        );
+        assert!(code.contains(".a{color:red}"));
+        assert!(!code.contains(".b"));
+    }
+
+    #[test]
+    fn test_keep_pseudo_class_arguments() {
+        assert_eq!(
+            run(".a:not(.b){color:red}", "a", &[]),
+            ".a:not(.b){color:red}"
+        );
+    }
+
+    #[test]
+    fn test_safelist() {
+        assert_eq!(
+            run(".a{color:red}.is-active{color:red}", "", &["^is-"]),
+            ".is-active{color:red}"
+        );
+    }
+
+    fn run(css_code: &str, content: &str, safelist: &[&str]) -> String {
+        let mut used = HashSet::new();
+        extract_tokens(content, &mut used);
+        let safelist = safelist
+            .iter()
+            .map(|s| Regex::new(s).unwrap())
+            .collect::<Vec<_>>();
+        let mut test_utils = TestUtils::gen_css_ast(css_code.to_string(), true);
+        let ast = test_utils.ast.css_mut();
+        let mut visitor = UnusedSelectorRemover {
+            used: &used,
+            safelist: &safelist,
+        };
+        ast.ast.visit_mut_with(&mut visitor);
+        test_utils.css_ast_to_code()
+    }
+}
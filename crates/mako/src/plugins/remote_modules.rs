@@ -0,0 +1,284 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tracing::debug;
+use url::Url;
+
+use crate::compiler::Context;
+use crate::config::RemoteModulesConfig;
+use crate::plugin::{Plugin, PluginResolveIdParams};
+use crate::resolve::{Resolution, ResolvedResource, ResolverResource};
+use crate::stats::StatsJsonMap;
+use crate::utils::base64_encode;
+
+const REMOTE_EXTENSIONS: [&str; 8] = ["js", "mjs", "cjs", "jsx", "ts", "tsx", "css", "json"];
+
+#[derive(Debug, Error)]
+enum RemoteModulesError {
+    #[error("Failed to download remote module {url}: {message}")]
+    DownloadFailed { url: String, message: String },
+    #[error(
+        "Integrity check failed for remote module {url}, expected {expected} but got {actual}, \
+         remove it from the lockfile if the change is expected"
+    )]
+    IntegrityMismatch {
+        url: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("Remote module {url} is not in the lockfile, which is frozen by frozenLockfile")]
+    NotLocked { url: String },
+}
+
+/// The lockfile of the remote modules, the integrities are keyed by the urls the contents are
+/// downloaded from, and the redirects map the imported urls to them, e.g. the versionless urls
+#[derive(Deserialize, Serialize, Default, Debug, PartialEq)]
+struct RemoteLock {
+    #[serde(default)]
+    remote: BTreeMap<String, String>,
+    #[serde(default)]
+    redirects: BTreeMap<String, String>,
+}
+
+/**
+ * Resolve the http(s) imports to the modules downloaded into the cache dir, the relative and
+ * absolute imports in the remote modules are resolved against their urls, and the integrities
+ * of the downloaded contents are recorded in the lockfile and verified by the later builds
+ */
+pub struct RemoteModulesPlugin {
+    config: RemoteModulesConfig,
+    cache_dir: PathBuf,
+    lockfile: PathBuf,
+    lock: Mutex<RemoteLock>,
+    /// the urls of the remote modules, keyed by their paths in the cache dir
+    urls: Mutex<HashMap<PathBuf, Url>>,
+}
+
+impl RemoteModulesPlugin {
+    pub fn new(config: &RemoteModulesConfig, root: &Path) -> Result<Self> {
+        let lockfile = root.join(&config.lockfile);
+        let lock = if lockfile.exists() {
+            serde_json::from_str(&fs::read_to_string(&lockfile)?)
+                .map_err(|e| anyhow!("Parse {} failed: {}", lockfile.display(), e))?
+        } else {
+            RemoteLock::default()
+        };
+        Ok(Self {
+            config: config.clone(),
+            cache_dir: root.join(&config.cache_dir),
+            lockfile,
+            lock: Mutex::new(lock),
+            urls: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn fetch(&self, url: &Url) -> Result<PathBuf> {
+        let redirect = self
+            .lock
+            .lock()
+            .unwrap()
+            .redirects
+            .get(url.as_str())
+            .cloned();
+        let locked_url = match redirect {
+            Some(redirect) => Url::parse(&redirect)?,
+            None => url.clone(),
+        };
+        let expected = self
+            .lock
+            .lock()
+            .unwrap()
+            .remote
+            .get(locked_url.as_str())
+            .cloned();
+
+        // the locked modules are read from the cache dir if they are downloaded already
+        if let Some(expected) = &expected {
+            let path = cache_path(&self.cache_dir, &locked_url);
+            if let Ok(content) = fs::read(&path) {
+                verify(&locked_url, expected, &content)?;
+                self.urls.lock().unwrap().insert(path.clone(), locked_url);
+                return Ok(path);
+            }
+        } else if self.config.frozen_lockfile {
+            return Err(anyhow!(RemoteModulesError::NotLocked {
+                url: url.to_string(),
+            }));
+        }
+
+        debug!("download remote module {}", url);
+        let (final_url, content) = download(url)?;
+        let mut lock = self.lock.lock().unwrap();
+        match lock.remote.get(final_url.as_str()).or(expected.as_ref()) {
+            Some(expected) => verify(&final_url, expected, &content)?,
+            None => {
+                lock.remote
+                    .insert(final_url.to_string(), integrity(&content));
+            }
+        }
+        if final_url != *url {
+            lock.redirects
+                .insert(url.to_string(), final_url.to_string());
+        }
+        drop(lock);
+
+        let path = cache_path(&self.cache_dir, &final_url);
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, content)?;
+        self.urls.lock().unwrap().insert(path.clone(), final_url);
+        Ok(path)
+    }
+
+    fn write_lockfile(&self) -> Result<()> {
+        let lock = self.lock.lock().unwrap();
+        let content = format!("{}\n", serde_json::to_string_pretty(&*lock)?);
+        if fs::read_to_string(&self.lockfile).ok().as_deref() != Some(content.as_str()) {
+            fs::write(&self.lockfile, content)?;
+        }
+        Ok(())
+    }
+}
+
+impl Plugin for RemoteModulesPlugin {
+    fn name(&self) -> &str {
+        "remote_modules"
+    }
+
+    fn resolve_id(
+        &self,
+        source: &str,
+        importer: &str,
+        _params: &PluginResolveIdParams,
+        _context: &Arc<Context>,
+    ) -> Result<Option<ResolverResource>> {
+        let url = if is_remote_url(source) {
+            Url::parse(source)?
+        } else if source.starts_with('.') || source.starts_with('/') {
+            match self.urls.lock().unwrap().get(Path::new(importer)) {
+                Some(base) => base.join(source)?,
+                None => return Ok(None),
+            }
+        } else {
+            return Ok(None);
+        };
+        let path = self.fetch(&url)?;
+        Ok(Some(ResolverResource::Resolved(ResolvedResource(
+            Resolution {
+                path,
+                query: None,
+                fragment: None,
+                package_json: None,
+            },
+        ))))
+    }
+
+    fn done(&self, _stats: &StatsJsonMap, _context: &Arc<Context>) -> Result<()> {
+        if self.config.frozen_lockfile {
+            return Ok(());
+        }
+        self.write_lockfile()
+    }
+}
+
+fn is_remote_url(source: &str) -> bool {
+    source.starts_with("https://") || source.starts_with("http://")
+}
+
+fn download(url: &Url) -> Result<(Url, Vec<u8>)> {
+    let download_failed = |message: String| {
+        anyhow!(RemoteModulesError::DownloadFailed {
+            url: url.to_string(),
+            message,
+        })
+    };
+    let response = ureq::get(url.as_str())
+        .call()
+        .map_err(|e| download_failed(e.to_string()))?;
+    // the relative imports are resolved against the url after the redirects
+    let final_url = Url::parse(response.get_url())?;
+    let mut content = vec![];
+    response
+        .into_reader()
+        .read_to_end(&mut content)
+        .map_err(|e| download_failed(e.to_string()))?;
+    Ok((final_url, content))
+}
+
+/// The path of the remote module in the cache dir, grouped by the hosts, the extension of the
+/// url is kept so the module is loaded with the right type, and defaults to `.js`
+fn cache_path(cache_dir: &Path, url: &Url) -> PathBuf {
+    let ext = Path::new(url.path())
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .filter(|ext| REMOTE_EXTENSIONS.contains(ext))
+        .unwrap_or("js");
+    cache_dir
+        .join(url.host_str().unwrap_or("unknown"))
+        .join(format!("{:x}.{}", md5::compute(url.as_str()), ext))
+}
+
+fn integrity(content: &[u8]) -> String {
+    format!("sha256-{}", base64_encode(Sha256::digest(content)))
+}
+
+fn verify(url: &Url, expected: &str, content: &[u8]) -> Result<()> {
+    let actual = integrity(content);
+    if actual != expected {
+        return Err(anyhow!(RemoteModulesError::IntegrityMismatch {
+            url: url.to_string(),
+            expected: expected.to_string(),
+            actual,
+        }));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_path() {
+        let cache_dir = PathBuf::from("/root/cache");
+        let url = Url::parse("https://esm.sh/lodash-es@4.17.21/es2022/lodash-es.mjs").unwrap();
+        let path = cache_path(&cache_dir, &url);
+        assert!(path.starts_with("/root/cache/esm.sh"));
+        assert_eq!(path.extension().unwrap(), "mjs");
+        let url = Url::parse("https://esm.sh/lodash-es?target=es2015").unwrap();
+        assert_eq!(cache_path(&cache_dir, &url).extension().unwrap(), "js");
+    }
+
+    #[test]
+    fn test_verify() {
+        let url = Url::parse("https://esm.sh/foo.js").unwrap();
+        let expected = integrity(b"export default 1;");
+        assert!(expected.starts_with("sha256-"));
+        assert!(verify(&url, &expected, b"export default 1;").is_ok());
+        assert!(verify(&url, &expected, b"export default 2;")
+            .unwrap_err()
+            .to_string()
+            .contains("Integrity check failed"));
+    }
+
+    #[test]
+    fn test_relative_imports() {
+        let base = Url::parse("https://esm.sh/lodash-es@4.17.21/es2022/lodash-es.mjs").unwrap();
+        assert_eq!(
+            base.join("./add.mjs").unwrap().as_str(),
+            "https://esm.sh/lodash-es@4.17.21/es2022/add.mjs"
+        );
+        assert_eq!(
+            base.join("/v135/react@18.2.0/es2022/react.mjs")
+                .unwrap()
+                .as_str(),
+            "https://esm.sh/v135/react@18.2.0/es2022/react.mjs"
+        );
+    }
+}
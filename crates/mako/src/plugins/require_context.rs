@@ -13,7 +13,7 @@ use self::render::VirtualContextModuleRender;
 use self::visitor::RequireContextVisitor;
 use crate::ast::file::{Content, JsContent};
 use crate::compiler::Context;
-use crate::plugin::{Plugin, PluginLoadParam, PluginTransformJsParam};
+use crate::plugin::{Plugin, PluginContext, PluginLoadParam, PluginTransformJsParam};
 
 pub struct RequireContextPlugin {}
 
@@ -25,7 +25,7 @@ impl Plugin for RequireContextPlugin {
     fn load(
         &self,
         param: &PluginLoadParam,
-        _context: &Arc<Context>,
+        context: &Arc<Context>,
     ) -> anyhow::Result<Option<Content>> {
         if param
             .file
@@ -42,7 +42,11 @@ impl Plugin for RequireContextPlugin {
 
             let render = VirtualContextModuleRender::try_from(params)?;
 
-            return render.render(_context.clone()).map(|content| {
+            PluginContext::new(self.name(), context)
+                .with_module(&param.file.path)
+                .add_watch_dir(render.dir(context));
+
+            return render.render(context.clone()).map(|content| {
                 Some(Content::Js(JsContent {
                     content,
                     is_jsx: false,
@@ -1,4 +1,5 @@
 use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
@@ -98,13 +99,14 @@ impl VirtualContextModuleRender {
         )
     }
 
+    /// The dir scanned for the matched files, the module is rebuilt as the files are added to or
+    /// removed from it in watch mode
+    pub fn dir(&self, context: &Arc<Context>) -> PathBuf {
+        context.root.join(&self.root).clean()
+    }
+
     fn matched_files(&self, context: &Arc<Context>) -> Result<BTreeMap<String, String>> {
-        let root_path = context
-            .root
-            .join(&self.root)
-            .clean()
-            .to_string_lossy()
-            .to_string();
+        let root_path = self.dir(context).to_string_lossy().to_string();
 
         if self.root.starts_with("../") {
             return Err(anyhow!(
@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::compiler::{Compiler, Context};
+use crate::config::{HashFunction, ResolveCacheConfig};
+use crate::plugin::Plugin;
+use crate::resolve::PersistedResult;
+use crate::utils::hash::content_hash;
+
+const CACHE_FILE: &str = "resolutions.json";
+/// the files whose changes may change the resolutions of the packages
+const RESOLUTION_FILES: [&str; 6] = [
+    "package.json",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "tsconfig.json",
+];
+
+#[derive(Deserialize, Serialize)]
+struct CacheFile {
+    /// the version of mako, the config and the lockfiles the resolutions are made with
+    fingerprint: String,
+    results: Vec<PersistedResult>,
+}
+
+/**
+ * Persist the results of the resolve cache of the packages at the end of the build, and restore
+ * them in the next startup, so the packages are not searched in the node_modules and their
+ * exports are not matched again. Only the resolutions are persisted, the module graph isn't, so
+ * the modules are still loaded, parsed and transformed, and the restored paths are checked to
+ * be still the nearest packages of the importers. The cache is dropped when mako, the config,
+ * tsconfig.json or the lockfiles change.
+ */
+pub struct ResolveCachePlugin {
+    file: PathBuf,
+}
+
+impl ResolveCachePlugin {
+    pub fn new(config: &ResolveCacheConfig, root: &Path) -> Self {
+        Self {
+            file: root.join(&config.cache_dir).join(CACHE_FILE),
+        }
+    }
+
+    fn write(&self, context: &Arc<Context>) -> Result<()> {
+        let cache = CacheFile {
+            fingerprint: fingerprint(context),
+            results: context.resolve_cache.persist(),
+        };
+        if let Some(dir) = self.file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.file, serde_json::to_string(&cache)?)?;
+        Ok(())
+    }
+}
+
+impl Plugin for ResolveCachePlugin {
+    fn name(&self) -> &str {
+        "resolve_cache"
+    }
+
+    fn build_start(&self, context: &Arc<Context>) -> Result<()> {
+        let Ok(content) = fs::read_to_string(&self.file) else {
+            return Ok(());
+        };
+        match serde_json::from_str::<CacheFile>(&content) {
+            Ok(cache) if cache.fingerprint == fingerprint(context) => {
+                debug!("resolve cache: {} results", cache.results.len());
+                context.resolve_cache.restore(cache.results);
+            }
+            _ => debug!("resolve cache is outdated"),
+        }
+        Ok(())
+    }
+
+    fn after_build(&self, context: &Arc<Context>, _compiler: &Compiler) -> Result<()> {
+        // the rebuilds in watch mode resolve the dependencies as usual
+        context.resolve_cache.restore(vec![]);
+        self.write(context)
+    }
+}
+
+fn fingerprint(context: &Arc<Context>) -> String {
+    let mut content = env!("CARGO_PKG_VERSION").to_string();
+    content.push_str(&serde_json::to_string(&context.config).unwrap_or_default());
+    for file in RESOLUTION_FILES {
+        if let Ok(file_content) = fs::read(context.root.join(file)) {
+            content.push_str(&content_hash(file_content, HashFunction::Xxhash64, 16));
+        }
+    }
+    content_hash(content, HashFunction::Xxhash64, 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::Args;
+    use crate::config::Config;
+
+    #[test]
+    fn test_resolve_cache() {
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/resolve-cache");
+        let compile = || {
+            let config = Config::new(&root, None, None).unwrap();
+            let compiler = Compiler::new(config, root.clone(), Args::default(), None).unwrap();
+            compiler.compile().unwrap();
+            fs::read_to_string(root.join("dist/index.js")).unwrap()
+        };
+        let _ = fs::remove_dir_all(root.join("dist"));
+        let output = compile();
+        let content = fs::read_to_string(root.join("dist/.cache").join(CACHE_FILE)).unwrap();
+        let cache = serde_json::from_str::<CacheFile>(&content).unwrap();
+        // foo of src and src/nested, the relative ./nested isn't persisted
+        assert_eq!(cache.results.len(), 2);
+        // the restored results resolve to the same packages
+        assert_eq!(compile(), output);
+        assert!(output.contains("nested foo"));
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use glob::glob;
+use serde::Serialize;
+
+use crate::ast::file::{Content, JsContent};
+use crate::ast::js_ast::JsAst;
+use crate::compiler::Context;
+use crate::config::RscClientConfig;
+use crate::features::rsc::Rsc;
+use crate::generate::chunk::ChunkId;
+use crate::generate::chunk_graph::ChunkGraph;
+use crate::plugin::{Plugin, PluginContext};
+use crate::stats::StatsJsonMap;
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ServerActionReference {
+    path: String,
+    module_id: String,
+    name: String,
+}
+
+#[derive(Serialize, Debug)]
+struct ClientReference {
+    id: String,
+    // [chunk id, chunk file, ...]
+    chunks: Vec<String>,
+    name: String,
+}
+
+/**
+ * The client build of the react server components, the `"use client"` modules matched by
+ * `clientReferences` are imported lazily by the entries, so that each of them is built into
+ * its own async chunk, and the chunks of the client components are emitted in the client
+ * reference manifest, keyed by the paths relative to the root, which is the same as the
+ * `rscClientComponents` of the server build, e.g.
+ * { "src/Button.tsx": { "id": "...", "chunks": ["<chunk id>", "<chunk file>"], "name": "*" } }
+ * With `serverActionTpl`, the server actions replaced by the stubs are emitted in the server
+ * actions manifest, keyed by the action ids.
+ */
+pub struct RscClientPlugin {
+    config: RscClientConfig,
+}
+
+impl RscClientPlugin {
+    pub fn new(config: &RscClientConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+
+    /// The client components matched by the patterns, the entry is rebuilt in watch mode when
+    /// the matched files change or the files are added to the dirs of the patterns, so that the
+    /// new `"use client"` modules are picked up
+    fn client_references(&self, entry: &Path, context: &Arc<Context>) -> Result<Vec<PathBuf>> {
+        let plugin_context = PluginContext::new(self.name(), context).with_module(entry);
+        let mut paths = vec![];
+        for pattern in &self.config.client_references {
+            plugin_context.add_watch_dir(context.root.join(glob_base(pattern)));
+            let matched = glob(&context.root.join(pattern).to_string_lossy())
+                .map_err(|e| anyhow!("Invalid rscClient clientReferences {}: {}", pattern, e))?;
+            for path in matched.flatten().filter(|path| path.is_file()) {
+                plugin_context.add_watch_file(path.clone());
+                let Ok(content) = fs::read_to_string(&path) else {
+                    continue;
+                };
+                // only the modules with the directive on top are the client components
+                if !content.contains("use client") {
+                    continue;
+                }
+                let ast = JsAst::build(&path.to_string_lossy(), &content, context.clone())?;
+                if Rsc::is_client(&ast).unwrap_or(false) && !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
+    fn write_server_actions_manifest(&self, context: &Arc<Context>) -> Result<()> {
+        let manifest = context
+            .stats_info
+            .get_rsc_server_actions()
+            .into_iter()
+            .map(|action| {
+                (
+                    action.id,
+                    ServerActionReference {
+                        path: action.path,
+                        module_id: action.module_id,
+                        name: action.name,
+                    },
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        fs::write(
+            context
+                .config
+                .output
+                .path
+                .join(&self.config.server_actions_manifest),
+            serde_json::to_string_pretty(&manifest)?,
+        )?;
+        Ok(())
+    }
+}
+
+impl Plugin for RscClientPlugin {
+    fn name(&self) -> &str {
+        "rsc_client"
+    }
+
+    fn load_transform(
+        &self,
+        content: &mut Content,
+        path: &str,
+        context: &Arc<Context>,
+    ) -> Result<Option<Content>> {
+        let Content::Js(js) = content else {
+            return Ok(None);
+        };
+        if self.config.client_references.is_empty()
+            || !context
+                .config
+                .entry
+                .values()
+                .any(|entry| entry.to_string_lossy() == path)
+        {
+            return Ok(None);
+        }
+        let references = self.client_references(Path::new(path), context)?;
+        if references.is_empty() {
+            return Ok(None);
+        }
+        // the function is never called, the imports are the split points of the chunks
+        let imports = references
+            .iter()
+            .map(|path| {
+                format!(
+                    "import({})",
+                    serde_json::to_string(&path.to_string_lossy()).unwrap()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        Ok(Some(Content::Js(JsContent {
+            content: format!(
+                "{}\n;(function __mako_rsc_client_references__() {{ return [{}]; }});\n",
+                js.content, imports
+            ),
+            is_jsx: js.is_jsx,
+        })))
+    }
+
+    fn build_success(&self, _stats: &StatsJsonMap, context: &Arc<Context>) -> Result<()> {
+        if self.config.server_action_tpl.is_some() {
+            self.write_server_actions_manifest(context)?;
+        }
+
+        let components = context
+            .stats_info
+            .get_rsc_client_components()
+            .into_iter()
+            .map(|component| (component.module_id, component.path))
+            .collect::<HashMap<_, _>>();
+
+        let mut chunk_files: HashMap<String, Vec<String>> = HashMap::new();
+        for asset in context.stats_info.get_assets() {
+            if asset.hashname.ends_with(".js") {
+                chunk_files
+                    .entry(asset.chunk_id)
+                    .or_default()
+                    .push(asset.hashname);
+            }
+        }
+
+        let chunk_graph = context.chunk_graph.read().unwrap();
+        let mut manifest: BTreeMap<String, ClientReference> = BTreeMap::new();
+        for chunk in chunk_graph.get_chunks() {
+            for module_id in chunk.get_modules() {
+                let id = module_id.generate(context);
+                let Some(path) = components.get(&id) else {
+                    continue;
+                };
+                if manifest.contains_key(path) {
+                    continue;
+                }
+                // the chunk of the component and all the shared chunks it depends on
+                let mut chunks = vec![];
+                for chunk_id in sync_chunks(&chunk_graph, &chunk.id) {
+                    for file in chunk_files.get(chunk_id.id.as_ref()).into_iter().flatten() {
+                        chunks.push(chunk_id.id.to_string());
+                        chunks.push(file.clone());
+                    }
+                }
+                manifest.insert(
+                    path.clone(),
+                    ClientReference {
+                        id,
+                        chunks,
+                        name: "*".to_string(),
+                    },
+                );
+            }
+        }
+
+        fs::write(
+            context.config.output.path.join(&self.config.manifest),
+            serde_json::to_string_pretty(&manifest)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// The chunk and the sync chunks it depends on transitively, the dependencies come first
+fn sync_chunks(chunk_graph: &ChunkGraph, chunk_id: &ChunkId) -> Vec<ChunkId> {
+    fn visit(chunk_graph: &ChunkGraph, chunk_id: &ChunkId, chunks: &mut Vec<ChunkId>) {
+        if chunks.contains(chunk_id) {
+            return;
+        }
+        chunks.push(chunk_id.clone());
+        for dep in chunk_graph.sync_dependencies_chunk(chunk_id) {
+            visit(chunk_graph, &dep, chunks);
+        }
+    }
+    let mut chunks = vec![];
+    visit(chunk_graph, chunk_id, &mut chunks);
+    chunks.reverse();
+    chunks
+}
+
+/// The dir of the pattern before the first component with the wildcards
+fn glob_base(pattern: &str) -> PathBuf {
+    Path::new(pattern)
+        .components()
+        .take_while(|component| {
+            !component
+                .as_os_str()
+                .to_string_lossy()
+                .contains(['*', '?', '[', '{'])
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::chunk::{Chunk, ChunkType};
+
+    #[test]
+    fn test_sync_chunks() {
+        let mut chunk_graph = ChunkGraph::new();
+        for (id, chunk_type) in [
+            ("button", ChunkType::Async),
+            ("vendors", ChunkType::Sync),
+            ("react", ChunkType::Sync),
+            ("lazy", ChunkType::Async),
+        ] {
+            chunk_graph.add_chunk(Chunk::new(id.into(), chunk_type));
+        }
+        chunk_graph.add_edge(&"button".into(), &"vendors".into());
+        chunk_graph.add_edge(&"vendors".into(), &"react".into());
+        chunk_graph.add_edge(&"button".into(), &"lazy".into());
+        let chunks = sync_chunks(&chunk_graph, &"button".into())
+            .into_iter()
+            .map(|chunk_id| chunk_id.id.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(chunks, vec!["react", "vendors", "button"]);
+    }
+
+    #[test]
+    fn test_glob_base() {
+        assert_eq!(
+            glob_base("src/components/*.tsx"),
+            PathBuf::from("src/components")
+        );
+        assert_eq!(glob_base("src/**/client/*.tsx"), PathBuf::from("src"));
+        assert_eq!(glob_base("*.tsx"), PathBuf::from(""));
+    }
+}
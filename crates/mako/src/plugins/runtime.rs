@@ -7,6 +7,9 @@ use crate::generate::swc_helpers::SwcHelpers;
 use crate::module::ModuleId;
 use crate::plugin::Plugin;
 
+const QIANKUN_PUBLIC_PATH: &str =
+    "(typeof window !== 'undefined' && window.__INJECTED_PUBLIC_PATH_BY_QIANKUN__)";
+
 pub struct MakoRuntime {}
 
 impl Plugin for MakoRuntime {
@@ -31,6 +34,12 @@ impl MakoRuntime {
         } else {
             format!("\"{}\"", public_path)
         };
+        // the public path injected by the micro-frontend container, e.g. qiankun
+        let public_path = if context.config.shadow_dom {
+            format!("{} || {}", QIANKUN_PUBLIC_PATH, public_path)
+        } else {
+            public_path
+        };
 
         format!(
             r#"
@@ -128,12 +137,16 @@ function(module, exports, __mako_require__) {
         var cache = _getRequireWildcardCache(nodeInterop);
         if (cache && cache.has(obj)) return cache.get(obj);
         var newObj = {};
-        var hasPropertyDescriptor = Object.defineProperty && Object.getOwnPropertyDescriptor;
-        for(var key in obj)if (key !== "default" && Object.prototype.hasOwnProperty.call(obj, key)) {
-            var desc = hasPropertyDescriptor ? Object.getOwnPropertyDescriptor(obj, key) : null;
-            if (desc && (desc.get || desc.set)) Object.defineProperty(newObj, key, desc);
-            else newObj[key] = obj[key];
-        }
+        Object.keys(obj).forEach(function(key) {
+            if (key === "default") return;
+            var desc = Object.getOwnPropertyDescriptor(obj, key);
+            Object.defineProperty(newObj, key, desc && (desc.get || desc.set) ? desc : {
+                enumerable: true,
+                get: function() {
+                    return obj[key];
+                }
+            });
+        });
         newObj.default = obj;
         if (cache) cache.set(obj, newObj);
         return newObj;
@@ -0,0 +1,207 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::compiler::{Args, Compiler, Context};
+use crate::config::{Config, Platform, ServiceWorkerConfig};
+use crate::plugin::{Plugin, PluginGenerateEndParams};
+
+/// Referenced in the service worker to get the precache manifest, like `self.__WB_MANIFEST`
+/// of workbox
+const PRECACHE_MANIFEST: &str = "self.__MAKO_PRECACHE_MANIFEST";
+
+/**
+ * Build the service worker entry separately after the main build, with the
+ * precache manifest of the emitted files injected, and register it in the entries
+ */
+pub struct ServiceWorkerPlugin {
+    config: ServiceWorkerConfig,
+    exclude: Vec<Regex>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+struct PrecacheEntry {
+    url: String,
+    revision: String,
+}
+
+impl ServiceWorkerPlugin {
+    pub fn new(config: &ServiceWorkerConfig) -> Result<Self> {
+        if !config.filename.ends_with(".js") {
+            return Err(anyhow!(
+                "serviceWorker.filename must end with .js, but got {}",
+                config.filename
+            ));
+        }
+        let exclude = config
+            .exclude
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .map_err(|e| anyhow!("Invalid serviceWorker.exclude {}: {}", pattern, e))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            config: config.clone(),
+            exclude,
+        })
+    }
+
+    fn is_precached(&self, file_name: &str) -> bool {
+        !file_name.ends_with(".map")
+            && file_name != self.config.filename
+            && !self.exclude.iter().any(|re| re.is_match(file_name))
+    }
+
+    fn precache_manifest(&self, context: &Arc<Context>) -> Result<Vec<PrecacheEntry>> {
+        // the urls are relative to the service worker which is emitted to the output root
+        let mut files = BTreeMap::new();
+        for asset in context.stats_info.get_assets() {
+            if self.is_precached(&asset.hashname) {
+                files.insert(asset.hashname, asset.path);
+            }
+        }
+        files
+            .into_iter()
+            .map(|(url, path)| {
+                let content = fs::read(&path)?;
+                Ok(PrecacheEntry {
+                    url,
+                    revision: format!("{:x}", md5::compute(content)),
+                })
+            })
+            .collect()
+    }
+
+    fn build_service_worker(
+        &self,
+        manifest: &[PrecacheEntry],
+        context: &Arc<Context>,
+    ) -> Result<()> {
+        let entry = context
+            .root
+            .join(&self.config.entry)
+            .canonicalize()
+            .map_err(|_| anyhow!("serviceWorker.entry {} not found", self.config.entry))?;
+        let name = self.config.filename.trim_end_matches(".js").to_string();
+
+        let mut config = Config::default();
+        config.entry = HashMap::from([(name, entry)]);
+        config.mode = context.config.mode.clone();
+        config.minify = context.config.minify;
+        config.devtool = None;
+        config.clean = false;
+        config.public_path = context.config.public_path.clone();
+        config.targets = context.config.targets.clone();
+        config.resolve.alias = context.config.resolve.alias.clone();
+        config.output.path = context.config.output.path.clone();
+        config.output.chunk_loading_global = context.config.output.chunk_loading_global.clone();
+        config.define = context.config.define.clone();
+        config.define.insert(
+            PRECACHE_MANIFEST.to_string(),
+            serde_json::Value::String(serde_json::to_string(manifest)?),
+        );
+
+        let compiler = Compiler::new(config, context.root.clone(), Args::default(), None)?;
+        compiler.compile()
+    }
+
+    fn register_code(&self) -> String {
+        let options = match &self.config.scope {
+            Some(scope) => format!(", {{ scope: {} }}", serde_json::to_string(scope).unwrap()),
+            None => "".to_string(),
+        };
+        format!(
+            r#"
+  /* mako/runtime/service worker */
+  !(function () {{
+    if (typeof navigator === 'undefined' || !('serviceWorker' in navigator)) return;
+    window.addEventListener('load', function () {{
+      navigator.serviceWorker.register(requireModule.publicPath + {}{});
+    }});
+  }})();"#,
+            serde_json::to_string(&self.config.filename).unwrap(),
+            options
+        )
+    }
+}
+
+impl Plugin for ServiceWorkerPlugin {
+    fn name(&self) -> &str {
+        "service_worker"
+    }
+
+    fn runtime_plugins(&self, context: &Arc<Context>) -> Result<Vec<String>> {
+        // the service worker is only built for production, the stale caches
+        // would break hmr in watch mode
+        if !self.config.register
+            || context.args.watch
+            || context.config.platform != Platform::Browser
+        {
+            return Ok(vec![]);
+        }
+        Ok(vec![self.register_code()])
+    }
+
+    fn generate_end(
+        &self,
+        _params: &PluginGenerateEndParams,
+        context: &Arc<Context>,
+    ) -> Result<()> {
+        if context.args.watch {
+            return Ok(());
+        }
+        let manifest = self.precache_manifest(context)?;
+        self.build_service_worker(&manifest, context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plugin(exclude: &[&str]) -> ServiceWorkerPlugin {
+        ServiceWorkerPlugin::new(&ServiceWorkerConfig {
+            entry: "src/sw.ts".to_string(),
+            filename: "sw.js".to_string(),
+            register: true,
+            scope: Some("/app/".to_string()),
+            exclude: exclude.iter().map(|s| s.to_string()).collect(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_is_precached() {
+        let plugin = plugin(&[r"\.txt$"]);
+        assert!(plugin.is_precached("index.5a3c8b1e.js"));
+        assert!(plugin.is_precached("logo.png"));
+        assert!(!plugin.is_precached("index.js.map"));
+        assert!(!plugin.is_precached("sw.js"));
+        assert!(!plugin.is_precached("LICENSE.txt"));
+    }
+
+    #[test]
+    fn test_register_code() {
+        let code = plugin(&[]).register_code();
+        assert!(
+            code.contains(r#"register(requireModule.publicPath + "sw.js", { scope: "/app/" })"#)
+        );
+    }
+
+    #[test]
+    fn test_invalid_filename() {
+        let config = ServiceWorkerConfig {
+            entry: "src/sw.ts".to_string(),
+            filename: "sw".to_string(),
+            register: true,
+            scope: None,
+            exclude: vec![],
+        };
+        assert!(ServiceWorkerPlugin::new(&config).is_err());
+    }
+}
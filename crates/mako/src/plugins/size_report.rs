@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use colored::*;
+
+use crate::compiler::Context;
+use crate::config::SizeReportConfig;
+use crate::generate::analyze::gzip_size;
+use crate::module::{relative_to_root, ModuleAst};
+use crate::plugin::Plugin;
+use crate::stats::{human_readable_size, pad_string, StatsJsonMap};
+
+/**
+ * Print a table of the emitted files with their raw, minified and gzip sizes after each build,
+ * the raw size is the sum of the sources of the chunk modules and the minified size is the size
+ * of the emitted file, the largest modules are listed under each chunk file, and in watch mode
+ * the size changes against the previous build are printed too
+ */
+pub struct SizeReportPlugin {
+    config: SizeReportConfig,
+    /// the file sizes of the previous build, keyed by the file names without hash
+    last_sizes: Mutex<HashMap<String, u64>>,
+}
+
+#[derive(Debug, Default)]
+struct FileSizes {
+    name: String,
+    hashname: String,
+    raw: Option<u64>,
+    size: u64,
+    gzip: Option<u64>,
+    top_modules: Vec<(String, u64)>,
+}
+
+impl SizeReportPlugin {
+    pub fn new(config: &SizeReportConfig) -> Self {
+        Self {
+            config: config.clone(),
+            last_sizes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn collect(&self, context: &Arc<Context>) -> Vec<FileSizes> {
+        let chunk_graph = context.chunk_graph.read().unwrap();
+        let module_graph = context.module_graph.read().unwrap();
+        let mut assets = context.stats_info.get_assets();
+        assets.sort();
+        let mut files = assets
+            .into_iter()
+            .filter(|asset| !asset.hashname.ends_with(".map"))
+            .map(|asset| {
+                let is_css = asset.hashname.ends_with(".css");
+                // the js and css files of a chunk share the chunk modules by their types
+                let modules = chunk_graph
+                    .chunk(&asset.chunk_id.as_str().into())
+                    .map(|chunk| {
+                        chunk
+                            .get_modules()
+                            .iter()
+                            .filter_map(|id| module_graph.get_module(id))
+                            .filter_map(|module| {
+                                let info = module.info.as_ref()?;
+                                if matches!(info.ast, ModuleAst::Css(_)) != is_css {
+                                    return None;
+                                }
+                                Some((
+                                    relative_to_root(&module.id.id, &context.root),
+                                    info.raw.len() as u64,
+                                ))
+                            })
+                            .collect::<Vec<_>>()
+                    });
+                let gzip = fs::read(context.config.output.path.join(&asset.hashname))
+                    .ok()
+                    .map(|content| gzip_size(&content));
+                let (raw, top_modules) = match modules {
+                    Some(mut modules) => {
+                        modules.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                        let raw = modules.iter().map(|(_, size)| size).sum();
+                        modules.truncate(self.config.top_modules);
+                        (Some(raw), modules)
+                    }
+                    None => (None, vec![]),
+                };
+                FileSizes {
+                    name: asset.name,
+                    hashname: asset.hashname,
+                    raw,
+                    size: asset.size,
+                    gzip,
+                    top_modules,
+                }
+            })
+            .collect::<Vec<_>>();
+        files.sort_by_key(|file| std::cmp::Reverse(file.size));
+        files
+    }
+}
+
+impl Plugin for SizeReportPlugin {
+    fn name(&self) -> &str {
+        "size_report"
+    }
+
+    fn done(&self, _stats: &StatsJsonMap, context: &Arc<Context>) -> Result<()> {
+        let files = self.collect(context);
+        let mut last_sizes = self.last_sizes.lock().unwrap();
+        let last = if last_sizes.is_empty() {
+            None
+        } else {
+            Some(&*last_sizes)
+        };
+        println!("{}", render_report(&files, last));
+        *last_sizes = files
+            .iter()
+            .map(|file| (file.name.clone(), file.size))
+            .collect();
+        Ok(())
+    }
+}
+
+fn render_report(files: &[FileSizes], last_sizes: Option<&HashMap<String, u64>>) -> String {
+    let optional_size = |size: Option<u64>| {
+        size.map(human_readable_size)
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let mut rows = vec![vec![
+        "File".to_string(),
+        "Raw".to_string(),
+        "Minified".to_string(),
+        "Gzip".to_string(),
+    ]];
+    if last_sizes.is_some() {
+        rows[0].push("Delta".to_string());
+    }
+    for file in files {
+        let mut row = vec![
+            file.hashname.clone(),
+            optional_size(file.raw),
+            human_readable_size(file.size),
+            optional_size(file.gzip),
+        ];
+        if let Some(last_sizes) = last_sizes {
+            row.push(size_delta(file.size, last_sizes.get(&file.name).copied()));
+        }
+        rows.push(row);
+    }
+    let widths = (0..rows[0].len())
+        .map(|i| rows.iter().map(|row| row[i].chars().count()).max().unwrap())
+        .collect::<Vec<_>>();
+
+    let mut s = String::new();
+    for (index, row) in rows.iter().enumerate() {
+        let line = row
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                let cell = pad_string(cell, widths[i], i != 0);
+                match (index, i) {
+                    (0, _) => cell.bold().to_string(),
+                    (_, 0) if cell.trim_end().ends_with(".js") => cell.cyan().to_string(),
+                    (_, 0) if cell.trim_end().ends_with(".css") => cell.magenta().to_string(),
+                    (_, 0) => cell.green().to_string(),
+                    (_, 4) if cell.trim_start().starts_with('+') => cell.red().to_string(),
+                    (_, 4) if cell.trim_start().starts_with('-') => cell.green().to_string(),
+                    _ => cell,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        s.push_str(line.trim_end());
+        s.push('\n');
+        if index == 0 {
+            continue;
+        }
+        for (module, size) in &files[index - 1].top_modules {
+            s.push_str(&format!(
+                "  {} {} {}\n",
+                "└".truecolor(128, 128, 128),
+                module.yellow(),
+                human_readable_size(*size).truecolor(128, 128, 128)
+            ));
+        }
+    }
+    s.trim_end_matches('\n').to_string()
+}
+
+fn size_delta(size: u64, last_size: Option<u64>) -> String {
+    match last_size {
+        None => "new".to_string(),
+        Some(last_size) if last_size == size => "".to_string(),
+        Some(last_size) if size > last_size => {
+            format!("+{}", human_readable_size(size - last_size))
+        }
+        Some(last_size) => format!("-{}", human_readable_size(last_size - size)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_delta() {
+        assert_eq!(size_delta(1500, None), "new");
+        assert_eq!(size_delta(1500, Some(1500)), "");
+        assert_eq!(size_delta(1500, Some(1000)), "+0.50 kB");
+        assert_eq!(size_delta(1000, Some(1500)), "-0.50 kB");
+    }
+
+    #[test]
+    fn test_render_report() {
+        colored::control::set_override(false);
+        let files = vec![FileSizes {
+            name: "index.js".to_string(),
+            hashname: "index.js".to_string(),
+            raw: Some(3000),
+            size: 2000,
+            gzip: Some(1000),
+            top_modules: vec![("src/index.ts".to_string(), 3000)],
+        }];
+        assert_eq!(
+            render_report(&files, None),
+            r#"File          Raw  Minified     Gzip
+index.js  3.00 kB   2.00 kB  1.00 kB
+  └ src/index.ts 3.00 kB"#
+        );
+        let last_sizes = HashMap::from([("index.js".to_string(), 1000)]);
+        assert!(render_report(&files, Some(&last_sizes)).contains("Delta\nindex.js"));
+    }
+}
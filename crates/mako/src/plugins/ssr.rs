@@ -0,0 +1,174 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+
+use crate::compiler::{Args, Compiler, Context};
+use crate::config::{Config, Platform, SsrConfig};
+use crate::module::relative_to_root;
+use crate::plugin::Plugin;
+use crate::stats::StatsJsonMap;
+
+/**
+ * Build the server bundle of the app after the client one in the same invocation,
+ * and emit the ssr manifest which maps the source files to the client files they
+ * are bundled in, so the server can inject the preload links of the rendered modules.
+ */
+pub struct SsrPlugin {
+    config: SsrConfig,
+}
+
+impl SsrPlugin {
+    pub fn new(config: &SsrConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+
+    fn ssr_manifest(&self, context: &Arc<Context>) -> BTreeMap<String, BTreeSet<String>> {
+        let public_path = if context.config.public_path == "runtime" {
+            ""
+        } else {
+            context.config.public_path.as_str()
+        };
+        let mut chunk_files: HashMap<String, Vec<String>> = HashMap::new();
+        for asset in context.stats_info.get_assets() {
+            if !asset.chunk_id.is_empty() && !asset.hashname.ends_with(".map") {
+                chunk_files
+                    .entry(asset.chunk_id)
+                    .or_default()
+                    .push(format!("{}{}", public_path, asset.hashname));
+            }
+        }
+
+        let chunk_graph = context.chunk_graph.read().unwrap();
+        let mut manifest: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for chunk in chunk_graph.get_chunks() {
+            // the split vendors chunks are loaded along with the chunk
+            let mut chunk_ids = chunk_graph.sync_dependencies_chunk(&chunk.id);
+            chunk_ids.push(chunk.id.clone());
+            let files = chunk_ids
+                .iter()
+                .filter_map(|id| chunk_files.get(&id.id))
+                .flatten()
+                .cloned()
+                .collect::<Vec<_>>();
+            for module_id in chunk.get_modules() {
+                // ?modules is the virtual css module of ?asmodule
+                if module_id.id.contains("?modules") {
+                    continue;
+                }
+                manifest
+                    .entry(manifest_key(&module_id.id, context))
+                    .or_default()
+                    .extend(files.iter().cloned());
+            }
+        }
+        manifest
+    }
+
+    fn server_config(&self, context: &Arc<Context>) -> Result<Config> {
+        let main = &context.config;
+        let entry = if self.config.entry.is_empty() {
+            context.entries.read().unwrap().clone()
+        } else {
+            self.config
+                .entry
+                .iter()
+                .map(|(name, path)| {
+                    let entry = context
+                        .root
+                        .join(path)
+                        .canonicalize()
+                        .map_err(|_| anyhow!("ssr.entry {} not found", path))?;
+                    Ok((name.clone(), entry))
+                })
+                .collect::<Result<HashMap<_, _>>>()?
+        };
+
+        let mut config = main.clone();
+        config.entry = entry;
+        // the server bundle is only built once along with the client one
+        config.ssr = None;
+        config.outputs = vec![];
+        config.legacy = None;
+        config.hmr = None;
+        config.dev_server = None;
+        config.type_check = None;
+        config.lint = None;
+        config.clean = false;
+        // the client files and their reports
+        config.manifest = None;
+        config.service_worker = None;
+        config.sri = None;
+        config.copy = vec![];
+        // the assets are emitted by the client build with the same names
+        config.emit_assets = false;
+        config.css_modules_export_only_locales = true;
+        // the server bundle is a single file required by the server with a stable name
+        config.hash = false;
+        config.code_splitting = None;
+        config.dynamic_import_to_require = true;
+        config.platform = Platform::Node;
+        config.targets = HashMap::from([("node".to_string(), 14.0)]);
+        config.output.path = main.output.path.join(&self.config.output_path);
+        config.output.compress = None;
+        Ok(config)
+    }
+}
+
+impl Plugin for SsrPlugin {
+    fn name(&self) -> &str {
+        "ssr"
+    }
+
+    fn build_success(&self, _stats: &StatsJsonMap, context: &Arc<Context>) -> Result<()> {
+        if context.args.watch {
+            return Ok(());
+        }
+
+        let manifest = self.ssr_manifest(context);
+        fs::write(
+            context.config.output.path.join(&self.config.manifest),
+            serde_json::to_string_pretty(&manifest)?,
+        )?;
+
+        let compiler = Compiler::new(
+            self.server_config(context)?,
+            context.root.clone(),
+            Args::default(),
+            None,
+        )?;
+        compiler.compile()
+    }
+}
+
+/// The path relative to root without the leading `./`, e.g. `src/App.tsx`
+fn manifest_key(module_id: &str, context: &Arc<Context>) -> String {
+    relative_to_root(module_id, &context.root)
+        .trim_start_matches("./")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use super::manifest_key;
+    use crate::compiler::Context;
+
+    #[test]
+    fn test_manifest_key() {
+        let context = Arc::new(Context {
+            root: PathBuf::from("/root"),
+            ..Default::default()
+        });
+        assert_eq!(manifest_key("/root/src/App.tsx", &context), "src/App.tsx");
+        assert_eq!(
+            manifest_key("/root/src/a.css?asmodule", &context),
+            "src/a.css?asmodule"
+        );
+    }
+}
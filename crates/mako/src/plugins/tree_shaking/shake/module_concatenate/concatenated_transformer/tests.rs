@@ -727,6 +727,8 @@ fn inner_trans_code_imported_as(
             optimization: Some(OptimizationConfig {
                 concatenate_modules: Some(true),
                 skip_modules: Some(true),
+                mangle_exports: None,
+                hoist_shared_css: None,
             }),
             mode: Mode::Production,
             minify: false,
@@ -0,0 +1,211 @@
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::compiler::Context;
+use crate::config::TypeCheckConfig;
+use crate::plugin::Plugin;
+use crate::stats::StatsJsonMap;
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct TypeDiagnostic {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub code: String,
+    pub message: String,
+}
+
+/**
+ * Run the type checker of config.type_check in a child process, the transforms are not blocked
+ * by it. In watch mode the checker is run with `--watch`, the diagnostics of each pass are
+ * printed and pushed to the error overlay of the dev server. In build mode the build fails
+ * after the outputs are written if there are type errors.
+ */
+pub struct TypeCheckPlugin {
+    config: TypeCheckConfig,
+    child: Mutex<Option<Child>>,
+}
+
+impl TypeCheckPlugin {
+    pub fn new(config: &TypeCheckConfig) -> Self {
+        Self {
+            config: config.clone(),
+            child: Mutex::new(None),
+        }
+    }
+
+    fn command(&self, root: &Path, watch: bool) -> Command {
+        let mut parts = match &self.config.command {
+            Some(command) => command
+                .split_whitespace()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>(),
+            None => {
+                let local = root.join("node_modules/.bin/tsc");
+                if local.exists() {
+                    vec![local.to_string_lossy().to_string()]
+                } else {
+                    vec!["tsc".to_string()]
+                }
+            }
+        };
+        let program = parts.remove(0);
+        let mut command = Command::new(program);
+        command
+            .args(parts)
+            .args(["--noEmit", "--pretty", "false"])
+            .current_dir(root);
+        if let Some(tsconfig) = &self.config.tsconfig {
+            command.args(["-p", tsconfig]);
+        }
+        if watch {
+            command.args(["--watch", "--preserveWatchOutput"]);
+        }
+        command
+    }
+
+    fn report(diagnostics: &[TypeDiagnostic]) {
+        for diagnostic in diagnostics {
+            eprintln!(
+                "{} {}:{}:{} - {}",
+                diagnostic.code.red(),
+                diagnostic.file,
+                diagnostic.line,
+                diagnostic.column,
+                diagnostic.message
+            );
+        }
+        if !diagnostics.is_empty() {
+            eprintln!(
+                "{}",
+                format!("Found {} type errors.", diagnostics.len()).red()
+            );
+        }
+    }
+}
+
+impl Plugin for TypeCheckPlugin {
+    fn name(&self) -> &str {
+        "type_check"
+    }
+
+    fn build_start(&self, context: &Arc<Context>) -> Result<()> {
+        let watch = context.args.watch;
+        let mut child = self
+            .command(&context.root, watch)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| anyhow!("Failed to start the type checker: {}", e))?;
+        if watch {
+            let stdout = child.stdout.take().unwrap();
+            let context = context.clone();
+            std::thread::spawn(move || {
+                let watch_end = Regex::new(r"Found \d+ errors?\.").unwrap();
+                let mut lines = vec![];
+                for line in BufReader::new(stdout).lines().map_while(|line| line.ok()) {
+                    // each pass of tsc --watch ends with the summary line
+                    if watch_end.is_match(&line) {
+                        let diagnostics = parse_diagnostics(&lines.join("\n"));
+                        Self::report(&diagnostics);
+                        context.set_type_diagnostics(diagnostics);
+                        lines.clear();
+                    } else {
+                        lines.push(line);
+                    }
+                }
+            });
+        }
+        *self.child.lock().unwrap() = Some(child);
+        Ok(())
+    }
+
+    fn done(&self, _stats: &StatsJsonMap, context: &Arc<Context>) -> Result<()> {
+        if context.args.watch {
+            return Ok(());
+        }
+        let child = self.child.lock().unwrap().take();
+        if let Some(child) = child {
+            let output = child.wait_with_output()?;
+            let diagnostics = parse_diagnostics(&String::from_utf8_lossy(&output.stdout));
+            Self::report(&diagnostics);
+            let count = diagnostics.len();
+            context.set_type_diagnostics(diagnostics);
+            if count > 0 {
+                return Err(anyhow!("Type check failed with {} errors", count));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TypeCheckPlugin {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.lock().unwrap().take() {
+            let _ = child.kill();
+        }
+    }
+}
+
+/// Parse the diagnostics printed by `tsc --pretty false`, the indented lines following a
+/// diagnostic are the related information of it
+fn parse_diagnostics(output: &str) -> Vec<TypeDiagnostic> {
+    let regex = Regex::new(r"^(.+)\((\d+),(\d+)\): (error|warning) (TS\d+): (.*)$").unwrap();
+    let mut diagnostics: Vec<TypeDiagnostic> = vec![];
+    for line in output.lines() {
+        if let Some(caps) = regex.captures(line) {
+            diagnostics.push(TypeDiagnostic {
+                file: caps[1].to_string(),
+                line: caps[2].parse().unwrap_or(0),
+                column: caps[3].parse().unwrap_or(0),
+                code: caps[5].to_string(),
+                message: caps[6].to_string(),
+            });
+        } else if line.starts_with(' ') {
+            if let Some(last) = diagnostics.last_mut() {
+                last.message.push('\n');
+                last.message.push_str(line.trim());
+            }
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_diagnostics() {
+        let output = r#"src/index.ts(2,7): error TS2322: Type 'string' is not assignable to type 'number'.
+src/foo.ts(10,1): error TS2304: Cannot find name 'bar'.
+  The related information.
+10:00:00 AM - Found 2 errors. Watching for file changes."#;
+        assert_eq!(
+            parse_diagnostics(output),
+            vec![
+                TypeDiagnostic {
+                    file: "src/index.ts".to_string(),
+                    line: 2,
+                    column: 7,
+                    code: "TS2322".to_string(),
+                    message: "Type 'string' is not assignable to type 'number'.".to_string(),
+                },
+                TypeDiagnostic {
+                    file: "src/foo.ts".to_string(),
+                    line: 10,
+                    column: 1,
+                    code: "TS2304".to_string(),
+                    message: "Cannot find name 'bar'.\nThe related information.".to_string(),
+                },
+            ]
+        );
+    }
+}
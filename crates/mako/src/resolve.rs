@@ -1,17 +1,21 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
-use std::sync::Arc;
-use std::vec;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use std::{fs, vec};
 
 use anyhow::{anyhow, Result};
 use convert_case::{Case, Casing};
 use oxc_resolver::{Alias, AliasValue, ResolveError as OxcResolveError, ResolveOptions, Resolver};
 use regex::Captures;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tracing::debug;
 
+mod persisted;
 mod resolution;
 mod resource;
+pub use persisted::PersistedResult;
 pub use resolution::Resolution;
 pub use resource::{ExternalResource, ResolvedResource, ResolverResource};
 
@@ -33,7 +37,7 @@ struct ResolveError {
     from: String,
 }
 
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Deserialize, Serialize)]
 pub enum ResolverType {
     Cjs,
     Esm,
@@ -43,6 +47,84 @@ pub enum ResolverType {
 
 pub type Resolvers = HashMap<ResolverType, Resolver>;
 
+type ResolveCacheKey = (ResolverType, PathBuf, String);
+
+const LOCKFILES: [&str; 4] = [
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+];
+
+/// The results of the resolvers keyed by the resolver type, the importer dir and the source,
+/// they are kept across the rebuilds in watch mode, and cleared when the files are added or
+/// removed, or the package.json, node_modules and lockfiles are changed
+#[derive(Default)]
+pub struct ResolveCache {
+    results: Mutex<HashMap<ResolveCacheKey, ResolverResource>>,
+    /// the modified times of the lockfiles of the root, node_modules is not watched, but the
+    /// lockfiles are changed by the installs
+    lockfiles: Mutex<Vec<Option<SystemTime>>>,
+    /// the results of the packages restored from the last build with the resolveCache config,
+    /// each one is checked when it's first used
+    persisted: Mutex<HashMap<ResolveCacheKey, persisted::PersistedResource>>,
+}
+
+impl ResolveCache {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            results: Default::default(),
+            lockfiles: Mutex::new(lockfiles_modified(root)),
+            persisted: Default::default(),
+        }
+    }
+
+    /// Clear the results if the lockfiles of the root are changed since the last check, returns
+    /// whether the results are cleared
+    pub fn check_lockfiles(&self, root: &Path) -> bool {
+        let modified = lockfiles_modified(root);
+        let mut lockfiles = self.lockfiles.lock().unwrap();
+        if *lockfiles == modified {
+            return false;
+        }
+        *lockfiles = modified;
+        self.clear();
+        true
+    }
+
+    fn get(&self, key: &ResolveCacheKey) -> Option<ResolverResource> {
+        self.results.lock().unwrap().get(key).cloned()
+    }
+
+    fn insert(&self, key: ResolveCacheKey, resource: ResolverResource) {
+        self.results.lock().unwrap().insert(key, resource);
+    }
+
+    pub fn clear(&self) {
+        self.results.lock().unwrap().clear();
+        self.persisted.lock().unwrap().clear();
+    }
+}
+
+fn lockfiles_modified(root: &Path) -> Vec<Option<SystemTime>> {
+    LOCKFILES
+        .iter()
+        .map(|file| {
+            fs::metadata(root.join(file))
+                .and_then(|m| m.modified())
+                .ok()
+        })
+        .collect()
+}
+
+/// Whether the change of the path may change the resolve results of the unchanged sources
+pub fn is_resolution_file(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == "package.json")
+        || path
+            .components()
+            .any(|c| c == Component::Normal("node_modules".as_ref()))
+}
+
 pub fn resolve(
     path: &str,
     dep: &Dependency,
@@ -73,23 +155,37 @@ pub fn resolve(
         .2
         .iter()
         .any(|(k, _)| *k == "context");
-    let resolver = if has_context_query {
-        resolvers.get(&ResolverType::Ctxt)
+    let resolver_type = if has_context_query {
+        ResolverType::Ctxt
     } else if dep.resolve_type == ResolveType::Require {
-        resolvers.get(&ResolverType::Cjs)
+        ResolverType::Cjs
     } else if dep.resolve_type == ResolveType::Css {
-        resolvers.get(&ResolverType::Css)
+        ResolverType::Css
     } else {
-        resolvers.get(&ResolverType::Esm)
-    }
-    .unwrap();
+        ResolverType::Esm
+    };
+    let resolver = resolvers.get(&resolver_type).unwrap();
 
     let source = dep.resolve_as.as_ref().unwrap_or(&dep.source);
 
+    // the sources are resolved from the dir of the importer, so the importers in the same dir
+    // share the results, and the failures are not cached to resolve the files added later
+    let Some(dir) = Path::new(path).parent() else {
+        return do_resolve(path, source, resolver, Some(&context.config.externals));
+    };
+    let key = (resolver_type, dir.to_path_buf(), source.to_string());
+    if let Some(resource) = context.resolve_cache.get(&key) {
+        return Ok(resource);
+    }
+    if let Some(resource) = context.resolve_cache.get_persisted(&key, context) {
+        context.resolve_cache.insert(key, resource.clone());
+        return Ok(resource);
+    }
     do_resolve(path, source, resolver, Some(&context.config.externals))
+        .inspect(|resource| context.resolve_cache.insert(key, resource.clone()))
 }
 
-fn get_external_target(
+pub(crate) fn get_external_target(
     externals: &HashMap<String, ExternalConfig>,
     source: &str,
 ) -> Option<(String, Option<String>)> {
@@ -104,6 +200,8 @@ fn get_external_target(
                     "''".to_string()
                 } else if external.starts_with("commonjs ") {
                     format!("require(\"{}\")", external.replace("commonjs ", ""))
+                } else if external.starts_with("module ") {
+                    get_external_target_from_module(&external.replace("module ", ""))
                 } else {
                     get_external_target_from_global_obj(global_obj, external)
                 },
@@ -114,6 +212,8 @@ fn get_external_target(
                     "''".to_string()
                 } else if config.module_type.as_ref().is_some_and(|t| t == "commonjs") {
                     format!("require(\"{}\")", config.root)
+                } else if config.module_type.as_ref().is_some_and(|t| t == "module") {
+                    get_external_target_from_module(&config.root)
                 } else {
                     get_external_target_from_global_obj(global_obj, &config.root)
                 },
@@ -231,6 +331,34 @@ fn get_external_target_from_global_obj(global_obj_name: &str, external: &str) ->
     format!("{}{}", global_obj_name, external)
 }
 
+/*
+ * es module externals can only be imported asynchronously, the namespace is marked
+ * with __esModule so that the default export is interop-ed as is
+ */
+fn get_external_target_from_module(external: &str) -> String {
+    format!(
+        "import(\"{}\").then(function (m) {{ return Object.assign({{ __esModule: true }}, m); }})",
+        external
+    )
+}
+
+/// Resolve the absolute path of a resolved module, e.g. the one restored from the module graph
+/// cache, which gets the package.json of the path without searching the node_modules
+pub(crate) fn resolve_path(
+    from: &str,
+    path: &str,
+    context: &Arc<Context>,
+) -> Result<ResolverResource> {
+    let has_context_query = parse_path(path)?.2.iter().any(|(k, _)| *k == "context");
+    let resolver_type = if has_context_query {
+        ResolverType::Ctxt
+    } else {
+        ResolverType::Esm
+    };
+    let resolver = context.resolvers.get(&resolver_type).unwrap();
+    do_resolve(from, path, resolver, None)
+}
+
 fn do_resolve(
     path: &str,
     source: &str,
@@ -326,7 +454,7 @@ fn get_resolver(config: &Config, resolver_type: ResolverType) -> Resolver {
     let alias = parse_alias(config.resolve.alias.clone());
     let is_browser = config.platform == Platform::Browser;
     let extensions = get_module_extensions();
-    let options = match (resolver_type, is_browser) {
+    let mut options = match (resolver_type, is_browser) {
         (ResolverType::Cjs, true) => ResolveOptions {
             alias,
             extensions,
@@ -413,6 +541,12 @@ fn get_resolver(config: &Config, resolver_type: ResolverType) -> Resolver {
             ..Default::default()
         },
     };
+    // e.g. "development", "worker"
+    if !options.condition_names.is_empty() {
+        options
+            .condition_names
+            .splice(0..0, config.resolve.conditions.iter().cloned());
+    }
 
     Resolver::new(options)
 }
@@ -442,6 +576,39 @@ mod tests {
     };
     use crate::resolve::ResolverType;
 
+    #[test]
+    fn test_resolve_cache_lockfiles() {
+        use std::fs;
+
+        use super::ResolveCache;
+
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/tmp/resolve-cache");
+        fs::create_dir_all(&root).unwrap();
+        let lockfile = root.join("package-lock.json");
+        fs::write(&lockfile, "{}").unwrap();
+        let cache = ResolveCache::new(&root);
+        assert!(!cache.check_lockfiles(&root));
+        fs::remove_file(&lockfile).unwrap();
+        assert!(cache.check_lockfiles(&root));
+        assert!(!cache.check_lockfiles(&root));
+    }
+
+    #[test]
+    fn test_is_resolution_file() {
+        use std::path::Path;
+
+        use super::is_resolution_file;
+
+        assert!(is_resolution_file(Path::new("/root/package.json")));
+        assert!(is_resolution_file(Path::new(
+            "/root/node_modules/foo/index.js"
+        )));
+        assert!(!is_resolution_file(Path::new("/root/src/index.ts")));
+        assert!(!is_resolution_file(Path::new("/root/src/node_modules.ts")));
+    }
+
     #[test]
     fn test_resolve() {
         let x = resolve("test/resolve/normal", None, None, "index.ts", "./source");
@@ -477,6 +644,28 @@ mod tests {
         assert_eq!(x, "node_modules/foo/esm-browser.js".to_string());
     }
 
+    #[test]
+    fn test_resolve_custom_conditions() {
+        let current_dir = std::env::current_dir().unwrap();
+        let fixture = current_dir.join("test/resolve/conditions");
+        let mut config: Config = Default::default();
+        let resolve = |config: &Config| {
+            let resolver = super::get_resolver(config, ResolverType::Esm);
+            super::do_resolve(
+                &fixture.join("index.ts").to_string_lossy(),
+                "foo",
+                &resolver,
+                None,
+            )
+            .unwrap()
+            .get_resolved_path()
+            .replace(format!("{}/", fixture.to_str().unwrap()).as_str(), "")
+        };
+        assert_eq!(resolve(&config), "node_modules/foo/index.js");
+        config.resolve.conditions = vec!["worker".to_string()];
+        assert_eq!(resolve(&config), "node_modules/foo/worker.js");
+    }
+
     #[test]
     fn test_resolve_alias() {
         let alias = vec![("bar".to_string(), "foo".to_string())];
@@ -518,6 +707,10 @@ mod tests {
                 ExternalConfig::Basic("window._".to_string()),
             ),
             ("empty".to_string(), ExternalConfig::Basic("".to_string())),
+            (
+                "chalk".to_string(),
+                ExternalConfig::Basic("module chalk".to_string()),
+            ),
         ]);
         let x = external_resolve(
             "test/resolve/normal",
@@ -597,6 +790,24 @@ mod tests {
             "empty",
         );
         assert_eq!(x, ("empty".to_string(), Some("''".to_string()), None));
+        let x = external_resolve(
+            "test/resolve/normal",
+            None,
+            Some(&externals),
+            "index.ts",
+            "chalk",
+        );
+        assert_eq!(
+            x,
+            (
+                "chalk".to_string(),
+                Some(
+                    "import(\"chalk\").then(function (m) { return Object.assign({ __esModule: true }, m); })"
+                        .to_string()
+                ),
+                None,
+            )
+        );
     }
 
     #[test]
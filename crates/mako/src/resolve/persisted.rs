@@ -0,0 +1,204 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    do_resolve, ExternalResource, ResolveCache, ResolveCacheKey, ResolverResource, ResolverType,
+};
+use crate::compiler::Context;
+
+/// A result of the resolve cache written to the disk, see the resolveCache config
+#[derive(Deserialize, Serialize)]
+pub struct PersistedResult {
+    resolver_type: ResolverType,
+    dir: PathBuf,
+    source: String,
+    resource: PersistedResource,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PersistedResource {
+    Resolved {
+        path: String,
+    },
+    External {
+        source: String,
+        external: String,
+        script: Option<String>,
+    },
+    Ignored {
+        path: PathBuf,
+    },
+}
+
+impl PersistedResource {
+    fn new(source: &str, resource: &ResolverResource) -> Option<Self> {
+        // only the packages are persisted, the relative files are cheap to resolve and may be
+        // shadowed by the new files, e.g. ./utils.ts by ./utils/index.ts
+        package_name(source)?;
+        match resource {
+            ResolverResource::Resolved(_) => {
+                let path = resource.get_resolved_path();
+                path.contains("node_modules")
+                    .then_some(PersistedResource::Resolved { path })
+            }
+            ResolverResource::External(ExternalResource {
+                source,
+                external,
+                script,
+            }) => Some(PersistedResource::External {
+                source: source.clone(),
+                external: external.clone(),
+                script: script.clone(),
+            }),
+            ResolverResource::Ignored(path) => {
+                Some(PersistedResource::Ignored { path: path.clone() })
+            }
+            ResolverResource::Virtual(_) => None,
+        }
+    }
+}
+
+impl ResolveCache {
+    /// The results of the packages to be written to the disk at the end of the build
+    pub fn persist(&self) -> Vec<PersistedResult> {
+        let mut results = self
+            .results
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|((resolver_type, dir, source), resource)| {
+                Some(PersistedResult {
+                    resolver_type: *resolver_type,
+                    dir: dir.clone(),
+                    source: source.clone(),
+                    resource: PersistedResource::new(source, resource)?,
+                })
+            })
+            .collect::<Vec<_>>();
+        results.sort_by(|a, b| {
+            (&a.dir, &a.source, a.resolver_type).cmp(&(&b.dir, &b.source, b.resolver_type))
+        });
+        results
+    }
+
+    /// Restore the results written by the last build, which are checked when they're used
+    pub fn restore(&self, results: Vec<PersistedResult>) {
+        *self.persisted.lock().unwrap() = results
+            .into_iter()
+            .map(|result| {
+                (
+                    (result.resolver_type, result.dir, result.source),
+                    result.resource,
+                )
+            })
+            .collect();
+    }
+
+    pub(super) fn get_persisted(
+        &self,
+        key: &ResolveCacheKey,
+        context: &Arc<Context>,
+    ) -> Option<ResolverResource> {
+        let resource = self.persisted.lock().unwrap().remove(key)?;
+        let (resolver_type, dir, source) = key;
+        match resource {
+            PersistedResource::Resolved { path } => {
+                // a package may be installed nearer to the importer since the last build
+                if !is_nearest_package(dir, package_name(source)?, Path::new(&path)) {
+                    return None;
+                }
+                // the resolved path is checked and its package.json is read again, only the
+                // dir of the importer is used
+                let resolver = context.resolvers.get(resolver_type)?;
+                let from = dir.join("_");
+                do_resolve(&from.to_string_lossy(), &path, resolver, None).ok()
+            }
+            PersistedResource::External {
+                source,
+                external,
+                script,
+            } => Some(ResolverResource::External(ExternalResource {
+                source,
+                external,
+                script,
+            })),
+            PersistedResource::Ignored { path } => Some(ResolverResource::Ignored(path)),
+        }
+    }
+}
+
+/// The package name of a bare specifier, e.g. `@scope/pkg` of `@scope/pkg/lib/a.js?raw`
+fn package_name(source: &str) -> Option<&str> {
+    let source = source.split('?').next().unwrap_or(source);
+    if source.is_empty() || source.starts_with('.') || Path::new(source).is_absolute() {
+        return None;
+    }
+    let end = if source.starts_with('@') {
+        source.match_indices('/').nth(1).map(|(i, _)| i)
+    } else {
+        source.find('/')
+    };
+    Some(&source[..end.unwrap_or(source.len())])
+}
+
+/// Whether the path is in the package found in the nearest node_modules of the dir
+fn is_nearest_package(dir: &Path, name: &str, path: &Path) -> bool {
+    dir.ancestors()
+        .map(|dir| dir.join("node_modules").join(name))
+        .find(|package| package.exists())
+        .and_then(|package| package.canonicalize().ok())
+        .is_some_and(|package| path.starts_with(package))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolve::{Resolution, ResolvedResource};
+
+    #[test]
+    fn test_persisted_resource() {
+        let resolved = |path: &str| {
+            ResolverResource::Resolved(ResolvedResource(Resolution {
+                path: PathBuf::from(path),
+                query: None,
+                fragment: None,
+                package_json: None,
+            }))
+        };
+        assert_eq!(
+            PersistedResource::new("foo", &resolved("/node_modules/foo/index.js")),
+            Some(PersistedResource::Resolved {
+                path: "/node_modules/foo/index.js".to_string()
+            })
+        );
+        assert_eq!(PersistedResource::new("./a", &resolved("/src/a.js")), None);
+        assert_eq!(PersistedResource::new("@/a", &resolved("/src/a.js")), None);
+    }
+
+    #[test]
+    fn test_package_name() {
+        assert_eq!(package_name("foo"), Some("foo"));
+        assert_eq!(package_name("foo/lib/a.js?raw"), Some("foo"));
+        assert_eq!(package_name("@scope/pkg/lib/a.js"), Some("@scope/pkg"));
+        assert_eq!(package_name("@scope/pkg"), Some("@scope/pkg"));
+        assert_eq!(package_name("./a"), None);
+        assert_eq!(package_name("/src/a.ts"), None);
+    }
+
+    #[test]
+    fn test_is_nearest_package() {
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/resolve-cache")
+            .canonicalize()
+            .unwrap();
+        let foo = root.join("node_modules/foo/index.js");
+        assert!(is_nearest_package(&root.join("src"), "foo", &foo));
+        // shadowed by the foo installed in src/nested
+        assert!(!is_nearest_package(&root.join("src/nested"), "foo", &foo));
+        assert!(!is_nearest_package(&root.join("src"), "bar", &foo));
+    }
+}
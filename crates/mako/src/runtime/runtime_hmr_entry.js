@@ -76,8 +76,44 @@ self.$RefreshSig$ = () => (type) => type;
     }
   }
 
+  const overlayId = '__mako_type_check_overlay__';
+
+  function renderDiagnostics(diagnostics) {
+    let overlay = document.getElementById(overlayId);
+    if (!diagnostics.length) {
+      overlay && overlay.remove();
+      return;
+    }
+    if (!overlay) {
+      overlay = document.createElement('div');
+      overlay.id = overlayId;
+      overlay.style.cssText =
+        'position:fixed;inset:0;z-index:2147483647;overflow:auto;padding:16px;' +
+        'background:rgba(0,0,0,0.85);color:#e8e8e8;font:13px/1.5 monospace;' +
+        'white-space:pre-wrap;';
+      overlay.addEventListener('click', () => overlay.remove());
+      document.body.appendChild(overlay);
+    }
+    overlay.textContent = '';
+    const title = document.createElement('div');
+    title.style.cssText = 'color:#ff5555;font-size:16px;margin-bottom:12px;';
+    title.textContent = `[Mako] Found ${diagnostics.length} type errors`;
+    overlay.appendChild(title);
+    diagnostics.forEach((d) => {
+      const item = document.createElement('div');
+      item.style.marginBottom = '12px';
+      item.textContent = `${d.file}:${d.line}:${d.column}\n${d.code}: ${d.message}`;
+      overlay.appendChild(item);
+    });
+  }
+
   socket.addEventListener('message', (rawMessage) => {
     const msg = JSON.parse(rawMessage.data);
+    // the type errors don't block the hmr updates
+    if (msg.type === 'diagnostics') {
+      renderDiagnostics(msg.diagnostics);
+      return;
+    }
     latestHash = msg.hash;
 
     if (!updating) {
@@ -1,3 +1,5 @@
+pub mod diff;
+
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fs;
@@ -9,11 +11,15 @@ use colored::*;
 use indexmap::IndexMap;
 use pathdiff::diff_paths;
 use serde::Serialize;
+use sha2::{Digest, Sha256, Sha384, Sha512};
 use swc_core::common::source_map::SmallPos;
 
 use crate::compiler::{Compiler, Context};
-use crate::features::rsc::{RscClientInfo, RscCssModules};
+use crate::config::SriAlgorithm;
+use crate::features::rsc::{RscClientInfo, RscCssModules, RscServerAction};
 use crate::generate::chunk::ChunkType;
+use crate::generate::memory::MemoryUsage;
+use crate::utils::base64_encode;
 
 impl Compiler {
     pub fn create_stats_info(&self) -> StatsJsonMap {
@@ -31,6 +37,7 @@ impl Compiler {
         stats_map.hash = hash;
         stats_map.root_path = root_path;
         stats_map.output_path = output_path;
+        stats_map.memory = self.memory_usage();
 
         let stats_info = &context.stats_info;
 
@@ -56,6 +63,26 @@ impl Compiler {
                     asset.1.clone(),
                 );
             });
+        self.context
+            .emitted_assets
+            .lock()
+            .unwrap()
+            .iter()
+            .for_each(|(file_name, asset)| {
+                stats_info.add_assets(
+                    asset.content.len() as u64,
+                    file_name.clone(),
+                    "".to_string(),
+                    self.context
+                        .config
+                        .output
+                        .path
+                        .join(file_name)
+                        .to_string_lossy()
+                        .to_string(),
+                    file_name.clone(),
+                );
+            });
 
         // 获取 assets
         stats_map.assets = stats_info
@@ -66,6 +93,12 @@ impl Compiler {
                 size: asset.size,
                 name: asset.hashname.clone(),
                 path: asset.path.clone(),
+                integrity: context.config.sri.as_ref().and_then(|sri| {
+                    integrity(
+                        &context.config.output.path.join(&asset.hashname),
+                        sri.algorithm,
+                    )
+                }),
             })
             .collect();
 
@@ -82,7 +115,7 @@ impl Compiler {
             .map(|chunk| {
                 let modules = chunk.get_modules();
                 let entry = matches!(chunk.chunk_type, ChunkType::Entry(_, _, _));
-                let id = chunk.id.id.clone();
+                let id = chunk.id.id.to_string();
                 let chunk_modules: Vec<StatsJsonChunkModuleItem> = modules
                     .iter()
                     .filter(|module| {
@@ -91,7 +124,7 @@ impl Compiler {
                         !module.id.contains("?modules")
                     })
                     .map(|module| {
-                        let id = module.id.clone();
+                        let id = module.id.to_string();
                         // 去拿 module 的文件 size 时，有可能 module 不存在，size 则设为 0
                         // 场景: xlsx 中引入了 fs 模块
                         let size = file_size(&id).unwrap_or_default();
@@ -100,7 +133,7 @@ impl Compiler {
                             size,
                             id,
                             // TODO: 现在是从每个 chunk 中找到包含的 module, 所以 chunk_id 是单个, 但是一个 module 有可能存在于多个 chunk 中
-                            chunks: vec![chunk.id.id.clone()],
+                            chunks: vec![chunk.id.id.to_string()],
                         };
                         chunk_modules.push(module.clone());
                         module
@@ -115,7 +148,7 @@ impl Compiler {
                 let siblings = chunk_graph
                     .sync_dependencies_chunk(&chunk.id)
                     .iter()
-                    .map(|id| id.id.clone())
+                    .map(|id| id.id.to_string())
                     .collect::<Vec<_>>();
                 let origin_chunk_modules = match chunk.chunk_type {
                     // sync chunk is the common dependency of async chunk
@@ -140,8 +173,8 @@ impl Compiler {
                             origins_set.insert(
                                 unique_key,
                                 StatsJsonChunkOriginItem {
-                                    module: id.id.clone(),
-                                    module_identifier: id.id.clone(),
+                                    module: id.id.to_string(),
+                                    module_identifier: id.id.to_string(),
                                     module_name: module_graph
                                         .get_module(id)
                                         .and_then(|module| {
@@ -183,10 +216,10 @@ impl Compiler {
                     let mut chunks = chunk_graph
                         .entry_dependencies_chunk(&chunk.id)
                         .into_iter()
-                        .map(|id| id.id)
+                        .map(|id| id.id.to_string())
                         .collect::<Vec<_>>();
 
-                    chunks.push(chunk.id.id.clone());
+                    chunks.push(chunk.id.id.to_string());
 
                     Some((
                         name.clone(),
@@ -342,6 +375,7 @@ pub struct StatsInfo {
     pub assets: Mutex<Vec<AssetsInfo>>,
     pub rsc_client_components: Mutex<Vec<RscClientInfo>>,
     pub rsc_css_modules: Mutex<Vec<RscCssModules>>,
+    pub rsc_server_actions: Mutex<Vec<RscServerAction>>,
     pub modules: Mutex<HashMap<String, ModuleInfo>>,
 }
 
@@ -351,6 +385,7 @@ impl StatsInfo {
             assets: Mutex::new(vec![]),
             rsc_client_components: Mutex::new(vec![]),
             rsc_css_modules: Mutex::new(vec![]),
+            rsc_server_actions: Mutex::new(vec![]),
             modules: Mutex::new(HashMap::new()),
         }
     }
@@ -430,6 +465,17 @@ impl StatsInfo {
     pub fn add_rsc_css_module(&self, rsc_css_module: RscCssModules) {
         self.rsc_css_modules.lock().unwrap().push(rsc_css_module)
     }
+
+    pub fn get_rsc_server_actions(&self) -> Vec<RscServerAction> {
+        self.rsc_server_actions.lock().unwrap().clone()
+    }
+
+    pub fn add_rsc_server_action(&self, rsc_server_action: RscServerAction) {
+        self.rsc_server_actions
+            .lock()
+            .unwrap()
+            .push(rsc_server_action)
+    }
 }
 
 impl Default for StatsInfo {
@@ -455,6 +501,9 @@ pub struct StatsJsonAssetsItem {
     pub size: u64,
     pub name: String,
     pub path: String,
+    /// subresource integrity of the js and css files, e.g. `sha384-...`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub integrity: Option<String>,
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -512,6 +561,7 @@ pub struct StatsJsonMap {
     rsc_client_components: Vec<RscClientInfo>,
     #[serde(rename = "rscCSSModules")]
     rsc_css_modules: Vec<RscCssModules>,
+    memory: MemoryUsage,
     pub start_time: i64,
     pub end_time: i64,
 }
@@ -530,6 +580,7 @@ impl StatsJsonMap {
             entrypoints: HashMap::new(),
             rsc_client_components: vec![],
             rsc_css_modules: vec![],
+            memory: Default::default(),
             start_time: 0,
             end_time: 0,
         }
@@ -557,7 +608,7 @@ pub fn human_readable_size(size: u64) -> String {
     format!("{:.2} {}", size, units[i])
 }
 
-fn pad_string(text: &str, max_length: usize, front: bool) -> String {
+pub(crate) fn pad_string(text: &str, max_length: usize, front: bool) -> String {
     let mut padded_text = String::from(text);
     let pad_length = max_length - text.chars().count();
     if front {
@@ -570,7 +621,46 @@ fn pad_string(text: &str, max_length: usize, front: bool) -> String {
         padded_text
     }
 }
+fn integrity(path: &Path, algorithm: SriAlgorithm) -> Option<String> {
+    let ext = path.extension()?;
+    if ext != "js" && ext != "css" {
+        return None;
+    }
+    // the files are not written to disk when writeToDisk is false in dev
+    let content = fs::read(path).ok()?;
+    let (prefix, digest) = match algorithm {
+        SriAlgorithm::Sha256 => ("sha256", Sha256::digest(&content).to_vec()),
+        SriAlgorithm::Sha384 => ("sha384", Sha384::digest(&content).to_vec()),
+        SriAlgorithm::Sha512 => ("sha512", Sha512::digest(&content).to_vec()),
+    };
+    Some(format!("{}-{}", prefix, base64_encode(digest)))
+}
+
 fn file_size(path: &str) -> Result<u64> {
     let metadata = fs::metadata(path)?;
     Ok(metadata.len())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integrity() {
+        let dir = std::env::temp_dir().join("mako_stats_integrity");
+        fs::create_dir_all(&dir).unwrap();
+        let js = dir.join("index.js");
+        fs::write(&js, "alert(1)").unwrap();
+        // echo -n "alert(1)" | openssl dgst -sha384 -binary | openssl base64 -A
+        assert_eq!(
+            integrity(&js, SriAlgorithm::Sha384).unwrap(),
+            "sha384-HT2E9NfWiuQ/w1PRai+hTyqW16NIoCGA/m8VQDUopfAtcz6YQjtsMmQd5uRbVDpW"
+        );
+        assert!(integrity(&js, SriAlgorithm::Sha256)
+            .unwrap()
+            .starts_with("sha256-"));
+        let png = dir.join("logo.png");
+        fs::write(&png, "").unwrap();
+        assert_eq!(integrity(&png, SriAlgorithm::Sha384), None);
+    }
+}
@@ -0,0 +1,343 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use colored::*;
+use serde::Deserialize;
+
+use crate::stats::{human_readable_size, pad_string};
+
+/// The parts of the stats.json which are compared
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffStats {
+    #[serde(default)]
+    root_path: String,
+    #[serde(default)]
+    assets: Vec<DiffAsset>,
+    #[serde(default)]
+    chunks: Vec<DiffChunk>,
+}
+
+#[derive(Deserialize, Debug)]
+struct DiffAsset {
+    name: String,
+    size: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct DiffChunk {
+    id: String,
+    files: Vec<String>,
+    modules: Vec<DiffChunkModule>,
+}
+
+#[derive(Deserialize, Debug)]
+struct DiffChunkModule {
+    id: String,
+}
+
+impl DiffStats {
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Read stats {} failed: {}", path.display(), e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Parse stats {} failed: {}", path.display(), e))
+    }
+
+    // the module ids are relative to the roots, so the stats of different checkouts are compared
+    fn relative(&self, id: &str) -> String {
+        if self.root_path.is_empty() {
+            return id.to_string();
+        }
+        id.strip_prefix(&self.root_path)
+            .map(|id| id.trim_start_matches('/').to_string())
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    fn modules(&self) -> BTreeSet<String> {
+        self.chunks
+            .iter()
+            .flat_map(|chunk| &chunk.modules)
+            .map(|module| self.relative(&module.id))
+            .collect()
+    }
+
+    // the sizes of the emitted files of the chunks, without the source maps
+    fn chunk_sizes(&self) -> BTreeMap<String, u64> {
+        let asset_sizes = self
+            .assets
+            .iter()
+            .map(|asset| (asset.name.as_str(), asset.size))
+            .collect::<BTreeMap<_, _>>();
+        self.chunks
+            .iter()
+            .map(|chunk| {
+                let size = chunk
+                    .files
+                    .iter()
+                    .filter(|file| !file.ends_with(".map"))
+                    .filter_map(|file| asset_sizes.get(file.as_str()))
+                    .sum();
+                (self.relative(&chunk.id), size)
+            })
+            .collect()
+    }
+
+    // the dirs of the packages in node_modules by the package names
+    fn packages(&self) -> BTreeMap<String, BTreeSet<String>> {
+        let mut packages: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for module in self.modules() {
+            if let Some((name, dir)) = package_of(&module) {
+                packages.entry(name).or_default().insert(dir);
+            }
+        }
+        packages
+    }
+}
+
+/// The differences of two builds, the modules added or removed, the size changes of the chunks
+/// and the packages which are newly duplicated
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct BundleDiff {
+    pub added_modules: Vec<String>,
+    pub removed_modules: Vec<String>,
+    /// (chunk, size before, size after), the sizes are None for the added or removed chunks
+    pub chunks: Vec<(String, Option<u64>, Option<u64>)>,
+    /// the duplicated packages with their dirs in the second build
+    pub duplicate_packages: Vec<(String, Vec<String>)>,
+}
+
+impl BundleDiff {
+    pub fn new(before: &DiffStats, after: &DiffStats) -> Self {
+        let (before_modules, after_modules) = (before.modules(), after.modules());
+        let added_modules = after_modules.difference(&before_modules).cloned().collect();
+        let removed_modules = before_modules.difference(&after_modules).cloned().collect();
+
+        let (before_chunks, after_chunks) = (before.chunk_sizes(), after.chunk_sizes());
+        let chunks = before_chunks
+            .keys()
+            .chain(after_chunks.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|chunk| {
+                (
+                    chunk.clone(),
+                    before_chunks.get(chunk).copied(),
+                    after_chunks.get(chunk).copied(),
+                )
+            })
+            .filter(|(_, before, after)| before != after)
+            .collect();
+
+        let before_packages = before.packages();
+        let duplicate_packages = after
+            .packages()
+            .into_iter()
+            .filter(|(name, dirs)| {
+                dirs.len() > 1
+                    && before_packages
+                        .get(name)
+                        .map_or(true, |before_dirs| !dirs.is_subset(before_dirs))
+            })
+            .map(|(name, dirs)| (name, dirs.into_iter().collect()))
+            .collect();
+
+        Self {
+            added_modules,
+            removed_modules,
+            chunks,
+            duplicate_packages,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn render(&self) -> String {
+        if self.is_empty() {
+            return "No differences".to_string();
+        }
+        let mut lines = vec![];
+        if !self.added_modules.is_empty() || !self.removed_modules.is_empty() {
+            lines.push(format!(
+                "{} {} {}",
+                "Modules".bold(),
+                format!("+{}", self.added_modules.len()).red(),
+                format!("-{}", self.removed_modules.len()).green()
+            ));
+            for module in &self.added_modules {
+                lines.push(format!("  {} {}", "+".red(), module));
+            }
+            for module in &self.removed_modules {
+                lines.push(format!("  {} {}", "-".green(), module));
+            }
+        }
+        if !self.chunks.is_empty() {
+            lines.push("Chunks".bold().to_string());
+            let size = |size: &Option<u64>| {
+                size.map(human_readable_size)
+                    .unwrap_or_else(|| "-".to_string())
+            };
+            let rows = self
+                .chunks
+                .iter()
+                .map(|(chunk, before, after)| {
+                    (chunk, size(before), size(after), size_delta(before, after))
+                })
+                .collect::<Vec<_>>();
+            let name_width = rows.iter().map(|row| row.0.chars().count()).max().unwrap();
+            let before_width = rows.iter().map(|row| row.1.chars().count()).max().unwrap();
+            let after_width = rows.iter().map(|row| row.2.chars().count()).max().unwrap();
+            for (chunk, before, after, delta) in rows {
+                let delta = if delta.starts_with('+') || delta == "new" {
+                    delta.red()
+                } else {
+                    delta.green()
+                };
+                lines.push(format!(
+                    "  {}  {} → {}  {}",
+                    pad_string(chunk, name_width, false).cyan(),
+                    pad_string(&before, before_width, true),
+                    pad_string(&after, after_width, true),
+                    delta
+                ));
+            }
+        }
+        if !self.duplicate_packages.is_empty() {
+            lines.push("New duplicate packages".bold().to_string());
+            for (name, dirs) in &self.duplicate_packages {
+                lines.push(format!("  {}", name.yellow()));
+                for dir in dirs {
+                    lines.push(format!("    {} {}", "└".truecolor(128, 128, 128), dir));
+                }
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+fn size_delta(before: &Option<u64>, after: &Option<u64>) -> String {
+    match (before, after) {
+        (None, _) => "new".to_string(),
+        (_, None) => "removed".to_string(),
+        (Some(before), Some(after)) if after >= before => {
+            format!("+{}", human_readable_size(after - before))
+        }
+        (Some(before), Some(after)) => format!("-{}", human_readable_size(before - after)),
+    }
+}
+
+// node_modules/a/node_modules/@b/c/index.js => (@b/c, node_modules/a/node_modules/@b/c)
+fn package_of(module: &str) -> Option<(String, String)> {
+    let module = module.split('?').next().unwrap();
+    let index = module.rfind("node_modules/")? + "node_modules/".len();
+    let mut segments = module[index..].split('/');
+    let mut name = segments.next()?.to_string();
+    if name.starts_with('@') {
+        name = format!("{}/{}", name, segments.next()?);
+    }
+    let dir = format!("{}{}", &module[..index], name);
+    Some((name, dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{package_of, BundleDiff, DiffStats};
+
+    fn stats(root: &str, chunks: &[(&str, u64, &[&str])]) -> DiffStats {
+        let json = serde_json::json!({
+            "rootPath": root,
+            "assets": chunks
+                .iter()
+                .map(|(id, size, _)| {
+                    serde_json::json!({ "name": format!("{}.js", id), "size": size })
+                })
+                .collect::<Vec<_>>(),
+            "chunks": chunks
+                .iter()
+                .map(|(id, _, modules)| serde_json::json!({
+                    "id": id,
+                    "files": [format!("{}.js", id), format!("{}.js.map", id)],
+                    "modules": modules
+                        .iter()
+                        .map(|module| serde_json::json!({ "id": format!("{}/{}", root, module) }))
+                        .collect::<Vec<_>>(),
+                }))
+                .collect::<Vec<_>>(),
+        });
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_package_of() {
+        assert_eq!(package_of("src/index.ts"), None);
+        assert_eq!(
+            package_of("node_modules/a/node_modules/@b/c/index.js"),
+            Some((
+                "@b/c".to_string(),
+                "node_modules/a/node_modules/@b/c".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_bundle_diff() {
+        let before = stats(
+            "/a",
+            &[
+                (
+                    "index",
+                    1000,
+                    &["src/index.ts", "node_modules/lodash/index.js"],
+                ),
+                ("lazy", 500, &["src/lazy.ts"]),
+            ],
+        );
+        let after = stats(
+            "/b",
+            &[
+                (
+                    "index",
+                    1500,
+                    &[
+                        "src/index.ts",
+                        "node_modules/lodash/index.js",
+                        "node_modules/a/node_modules/lodash/index.js",
+                    ],
+                ),
+                ("other", 200, &["src/other.ts"]),
+            ],
+        );
+        let diff = BundleDiff::new(&before, &after);
+        assert_eq!(
+            diff.added_modules,
+            vec![
+                "node_modules/a/node_modules/lodash/index.js".to_string(),
+                "src/other.ts".to_string()
+            ]
+        );
+        assert_eq!(diff.removed_modules, vec!["src/lazy.ts".to_string()]);
+        assert_eq!(
+            diff.chunks,
+            vec![
+                ("index".to_string(), Some(1000), Some(1500)),
+                ("lazy".to_string(), Some(500), None),
+                ("other".to_string(), None, Some(200)),
+            ]
+        );
+        assert_eq!(
+            diff.duplicate_packages,
+            vec![(
+                "lodash".to_string(),
+                vec![
+                    "node_modules/a/node_modules/lodash".to_string(),
+                    "node_modules/lodash".to_string()
+                ]
+            )]
+        );
+        assert!(BundleDiff::new(&after, &after).is_empty());
+    }
+}
@@ -1,3 +1,5 @@
+pub mod cancellation;
+pub mod hash;
 pub(crate) mod id_helper;
 pub mod logger;
 #[cfg(feature = "profile")]
@@ -0,0 +1,104 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+
+use anyhow::{anyhow, Result};
+
+use crate::build::BuildError;
+
+/// The token shared by the spawned build tasks, a cancelled build stops scheduling the modules
+/// and the in-flight tasks bail out before their next phase, instead of building the whole graph
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<Inner>);
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    running: Mutex<usize>,
+    idle: Condvar,
+}
+
+/// Marks a build as in-flight until it's dropped, see `CancellationToken::wait_idle`
+pub struct RunningBuild(Arc<Inner>);
+
+impl Drop for RunningBuild {
+    fn drop(&mut self) {
+        let mut running = self.0.running.lock().unwrap();
+        *running -= 1;
+        if *running == 0 {
+            self.0.idle.notify_all();
+        }
+    }
+}
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Called before each build, the cancellations of the previous builds are discarded
+    pub fn reset(&self) {
+        self.0.cancelled.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(anyhow!(BuildError::Cancelled))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Held by the build for its whole duration
+    pub fn start(&self) -> RunningBuild {
+        *self.0.running.lock().unwrap() += 1;
+        RunningBuild(self.0.clone())
+    }
+
+    /// Blocks until the in-flight builds have returned
+    pub fn wait_idle(&self) {
+        let running = self.0.running.lock().unwrap();
+        let _running = self.0.idle.wait_while(running, |n| *n > 0).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::CancellationToken;
+
+    #[test]
+    fn test_cancellation_token() {
+        let token = CancellationToken::default();
+        let cloned = token.clone();
+        assert!(token.check().is_ok());
+        cloned.cancel();
+        assert!(token.is_cancelled());
+        assert_eq!(token.check().unwrap_err().to_string(), "Build cancelled.");
+        token.reset();
+        assert!(!cloned.is_cancelled());
+    }
+
+    #[test]
+    fn test_wait_idle() {
+        let token = CancellationToken::default();
+        // returns at once without the in-flight builds
+        token.wait_idle();
+        let running = token.start();
+        let (tx, rx) = mpsc::channel();
+        let waiting = token.clone();
+        thread::spawn(move || {
+            waiting.wait_idle();
+            tx.send(()).unwrap();
+        });
+        assert!(rx.recv_timeout(Duration::from_millis(50)).is_err());
+        drop(running);
+        assert!(rx.recv_timeout(Duration::from_secs(5)).is_ok());
+    }
+}
@@ -0,0 +1,74 @@
+use std::hash::Hasher;
+
+use sha2::{Digest, Sha256};
+use twox_hash::XxHash64;
+
+use crate::config::HashFunction;
+
+/// Computes the hex digest of the contents with the `output.hashFunction`, the contents can be
+/// fed in parts, e.g. read from a large file
+pub enum ContentHasher {
+    Md5(md5::Context),
+    Xxhash64(XxHash64),
+    Sha256(Sha256),
+}
+
+impl ContentHasher {
+    pub fn new(hash_function: HashFunction) -> Self {
+        match hash_function {
+            HashFunction::Md5 => Self::Md5(md5::Context::new()),
+            HashFunction::Xxhash64 => Self::Xxhash64(XxHash64::with_seed(0)),
+            HashFunction::Sha256 => Self::Sha256(Sha256::new()),
+        }
+    }
+
+    pub fn update<T: AsRef<[u8]>>(&mut self, content: T) {
+        match self {
+            Self::Md5(context) => context.consume(content),
+            Self::Xxhash64(hasher) => hasher.write(content.as_ref()),
+            Self::Sha256(hasher) => hasher.update(content),
+        }
+    }
+
+    /// The hex digest truncated to the length, the digest of xxhash64 has at most 16 chars
+    pub fn finish(self, length: usize) -> String {
+        let mut hash = match self {
+            Self::Md5(context) => format!("{:x}", context.compute()),
+            Self::Xxhash64(hasher) => format!("{:016x}", hasher.finish()),
+            Self::Sha256(hasher) => format!("{:x}", hasher.finalize()),
+        };
+        hash.truncate(length);
+        hash
+    }
+}
+
+pub fn content_hash<T: AsRef<[u8]>>(
+    content: T,
+    hash_function: HashFunction,
+    length: usize,
+) -> String {
+    let mut hasher = ContentHasher::new(hash_function);
+    hasher.update(content);
+    hasher.finish(length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::content_hash;
+    use crate::config::HashFunction;
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(content_hash("mako", HashFunction::Md5, 8).len(), 8);
+        assert_eq!(
+            content_hash("mako", HashFunction::Md5, 32),
+            format!("{:x}", md5::compute("mako"))
+        );
+        assert_eq!(content_hash("mako", HashFunction::Xxhash64, 64).len(), 16);
+        assert_eq!(content_hash("mako", HashFunction::Sha256, 64).len(), 64);
+        assert_ne!(
+            content_hash("mako", HashFunction::Sha256, 8),
+            content_hash("mako", HashFunction::Md5, 8)
+        );
+    }
+}
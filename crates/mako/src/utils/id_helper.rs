@@ -1,29 +1,129 @@
-use std::cmp::Ordering;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::hash::Hasher;
+use std::path::Path;
 
 use petgraph::Direction::Incoming;
+use twox_hash::XxHash64;
 
-use crate::module::ModuleId;
+use crate::module::{relative_to_root, ModuleId};
 use crate::module_graph::ModuleGraph;
 
-pub fn compare_modules_by_incoming_edges(
+/// The modules with more incoming edges get the smaller ids, the modules are collected in
+/// parallel, so the ties are broken by the relative ids to keep the numeric ids stable between
+/// builds and machines
+pub fn incoming_edges_sort_key(
     module_graph: &ModuleGraph,
-    a: &ModuleId,
-    b: &ModuleId,
-) -> std::cmp::Ordering {
-    module_graph
-        .get_edges_count(b, Incoming)
-        .cmp(&module_graph.get_edges_count(a, Incoming))
+    module_id: &ModuleId,
+    root: &Path,
+) -> (Reverse<usize>, String) {
+    (
+        Reverse(module_graph.get_edges_count(module_id, Incoming)),
+        relative_to_root(&module_id.id, root),
+    )
 }
 
-pub fn assign_numeric_ids<T>(
+pub fn assign_numeric_ids<T, K: Ord>(
     mut items: Vec<T>,
-    comparator: impl Fn(&T, &T) -> Ordering,
+    get_sort_key: impl Fn(&T) -> K,
     mut assign_id: impl FnMut(&T, usize),
 ) {
-    items.sort_unstable_by(comparator);
+    items.sort_by_cached_key(get_sort_key);
 
     items
         .iter()
         .enumerate()
         .for_each(|(i, item)| assign_id(item, i))
 }
+
+/// The fixed range of the deterministic ids, wide enough to keep the collisions rare, so the ids
+/// don't change with the number of the modules
+const DETERMINISTIC_ID_RANGE: u64 = 10_000_000;
+
+/// Assign the numeric ids derived from the hashes of the keys, so that the id of an item doesn't
+/// change when the other items are added, removed or renamed. The items keep the ids assigned
+/// before, the collided keys of the others are hashed again with the next seeds in the order of
+/// the keys, so a new item never takes the id of an item assigned before
+pub fn assign_deterministic_ids<T>(
+    mut items: Vec<T>,
+    get_key: impl Fn(&T) -> String,
+    get_assigned_id: impl Fn(&T) -> Option<usize>,
+    mut assign_id: impl FnMut(&T, usize),
+) {
+    items.sort_by_cached_key(|item| get_key(item));
+    let mut used_ids = HashSet::new();
+    let items = items
+        .into_iter()
+        .filter(|item| match get_assigned_id(item) {
+            Some(id) if used_ids.insert(id) => {
+                assign_id(item, id);
+                false
+            }
+            _ => true,
+        })
+        .collect::<Vec<_>>();
+    for item in &items {
+        let key = get_key(item);
+        let mut seed = 0;
+        loop {
+            let mut hasher = XxHash64::with_seed(seed);
+            hasher.write(key.as_bytes());
+            let id = (hasher.finish() % DETERMINISTIC_ID_RANGE) as usize;
+            if used_ids.insert(id) {
+                assign_id(item, id);
+                break;
+            }
+            seed += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::assign_deterministic_ids;
+
+    fn ids(keys: &[&str], assigned: &HashMap<String, usize>) -> HashMap<String, usize> {
+        let mut ids = HashMap::new();
+        assign_deterministic_ids(
+            keys.to_vec(),
+            |key| key.to_string(),
+            |key| assigned.get(*key).copied(),
+            |key, id| {
+                ids.insert(key.to_string(), id);
+            },
+        );
+        ids
+    }
+
+    #[test]
+    fn test_deterministic_ids() {
+        let none = HashMap::new();
+        let before = ids(&["src/a.ts", "src/b.ts", "src/c.ts"], &none);
+        let after = ids(&["src/a.ts", "src/b.ts", "src/renamed.ts"], &none);
+        assert_eq!(before["src/a.ts"], after["src/a.ts"]);
+        assert_eq!(before["src/b.ts"], after["src/b.ts"]);
+
+        // the range doesn't grow with the number of the items
+        let keys = (0..5000)
+            .map(|i| format!("src/{}.ts", i))
+            .collect::<Vec<_>>();
+        let keys = keys.iter().map(|key| key.as_str()).collect::<Vec<_>>();
+        let many = ids(&keys, &none);
+        let unique = many.values().collect::<std::collections::HashSet<_>>();
+        assert_eq!(unique.len(), 5000);
+        assert_eq!(many["src/a.ts"], ids(&["src/a.ts"], &none)["src/a.ts"]);
+    }
+
+    #[test]
+    fn test_deterministic_ids_keep_assigned() {
+        let none = HashMap::new();
+        let hash_of_a = ids(&["src/a.ts"], &none)["src/a.ts"];
+        // src/z.ts was assigned the id before src/a.ts is added, which sorts before it
+        let assigned = HashMap::from([("src/z.ts".to_string(), hash_of_a)]);
+        let after = ids(&["src/a.ts", "src/z.ts"], &assigned);
+        assert_eq!(after["src/z.ts"], hash_of_a);
+        assert_ne!(after["src/a.ts"], hash_of_a);
+    }
+}
@@ -1,11 +1,149 @@
+use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use clap::ValueEnum;
+use colored::Colorize;
 use tracing_subscriber::{fmt, EnvFilter};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// one json object per line, for the log ingestion of CI
+    Json,
+}
+
+/// Namespaces of the debug logs, which can be enabled regardless of the log level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DebugScope {
+    Resolve,
+    Graph,
+    Emit,
+}
+
+impl DebugScope {
+    fn targets(&self) -> &'static [&'static str] {
+        match self {
+            DebugScope::Resolve => &["mako::resolve"],
+            DebugScope::Graph => &["mako::build", "mako::module_graph", "mako::dev::update"],
+            DebugScope::Emit => &["mako::generate"],
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct LoggerOptions {
+    pub level: LogLevel,
+    pub format: LogFormat,
+    pub debug_scopes: Vec<DebugScope>,
+}
+
+impl LoggerOptions {
+    /// Read from MAKO_LOG_LEVEL, MAKO_LOG_FORMAT and MAKO_DEBUG (comma separated scopes),
+    /// for the usages without cli arguments, e.g. the node binding
+    pub fn from_env() -> Self {
+        let level = env::var("MAKO_LOG_LEVEL")
+            .ok()
+            .and_then(|level| LogLevel::from_str(&level, true).ok())
+            .unwrap_or_default();
+        let format = env::var("MAKO_LOG_FORMAT")
+            .ok()
+            .and_then(|format| LogFormat::from_str(&format, true).ok())
+            .unwrap_or_default();
+        let debug_scopes = env::var("MAKO_DEBUG")
+            .map(|scopes| {
+                scopes
+                    .split(',')
+                    .filter_map(|scope| DebugScope::from_str(scope.trim(), true).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            level,
+            format,
+            debug_scopes,
+        }
+    }
+
+    fn directives(&self) -> String {
+        let mut directives = vec![format!("mako={}", self.level.as_str())];
+        for scope in &self.debug_scopes {
+            for target in scope.targets() {
+                directives.push(format!("{}=debug", target));
+            }
+        }
+        directives.join(",")
+    }
+}
+
+static JSON_FORMAT: AtomicBool = AtomicBool::new(false);
+
+/// Print the warning for users, which is logged as a `warn` event in the json format so
+/// that the log stream is kept valid json lines
+pub fn print_warning(message: &str) {
+    if JSON_FORMAT.load(Ordering::Relaxed) {
+        tracing::warn!("{}", message);
+    } else {
+        println!("{} {}", "Warning".yellow(), message);
+    }
+}
+
 pub fn init_logger() {
-    fmt()
-        .with_env_filter(
-            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("mako=info")),
-        )
-        .with_span_events(fmt::format::FmtSpan::NONE)
-        .without_time()
-        .init();
+    init_logger_with_options(&LoggerOptions::from_env());
+}
+
+pub fn init_logger_with_options(options: &LoggerOptions) {
+    // RUST_LOG takes precedence over the options
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(options.directives()));
+    let builder = fmt()
+        .with_env_filter(filter)
+        .with_span_events(fmt::format::FmtSpan::NONE);
+    JSON_FORMAT.store(options.format == LogFormat::Json, Ordering::Relaxed);
+    match options.format {
+        LogFormat::Text => builder.without_time().init(),
+        // keep the timestamps for the ingested logs
+        LogFormat::Json => builder.json().init(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DebugScope, LogLevel, LoggerOptions};
+
+    #[test]
+    fn test_directives() {
+        assert_eq!(LoggerOptions::default().directives(), "mako=info");
+        let options = LoggerOptions {
+            level: LogLevel::Warn,
+            debug_scopes: vec![DebugScope::Resolve, DebugScope::Emit],
+            ..Default::default()
+        };
+        assert_eq!(
+            options.directives(),
+            "mako=warn,mako::resolve=debug,mako::generate=debug"
+        );
+    }
 }
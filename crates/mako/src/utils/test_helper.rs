@@ -1,10 +1,12 @@
+use std::collections::BTreeMap;
 use std::fs;
+use std::path::Path;
 
 use tracing_subscriber::{fmt, EnvFilter};
 
 use crate::compiler::{self, Compiler};
 use crate::config::{Config, Mode};
-use crate::module::{Module, ModuleId};
+use crate::module::{relative_to_root, Module, ModuleAst, ModuleId};
 
 #[macro_export]
 macro_rules! assert_display_snapshot {
@@ -77,3 +79,70 @@ pub fn setup_logger() {
         // .without_time()
         .try_init();
 }
+
+/// The module graph and the transformed code of the modules outside node_modules of the
+/// fixture at `root`, for the snapshot tests of the build fixtures, e.g.
+/// `insta::glob!("../test/snapshot", "*/mako.config.json", ...)`
+pub fn build_snapshot(root: &Path) -> String {
+    setup_logger();
+    let mut config = Config::new(root, None, None).unwrap();
+    config.hmr = None;
+    config.minify = false;
+    config.devtool = None;
+    let compiler =
+        compiler::Compiler::new(config, root.to_path_buf(), Default::default(), None).unwrap();
+    if let Err(err) = compiler.build_entries() {
+        panic!("failed to build the fixture {}: {}", root.display(), err);
+    }
+
+    let mut snapshot = module_graph_snapshot(&compiler);
+    for (relative_id, code) in module_code_snapshots(&compiler) {
+        snapshot.push_str(&format!("\n// {}\n{}", relative_id, code.trim_end()));
+        snapshot.push('\n');
+    }
+    snapshot
+}
+
+/// The modules sorted by the ids relative to root, each followed by the sources of its
+/// dependencies and the modules they are resolved to
+fn module_graph_snapshot(compiler: &Compiler) -> String {
+    let module_graph = compiler.context.module_graph.read().unwrap();
+    let root = &compiler.context.root;
+    let mut modules = module_graph
+        .modules()
+        .into_iter()
+        .map(|module| (relative_to_root(&module.id.id, root), &module.id))
+        .collect::<Vec<_>>();
+    modules.sort();
+
+    let mut lines = vec![];
+    for (relative_id, module_id) in modules {
+        lines.push(relative_id);
+        for (dep_module_id, dep) in module_graph.get_dependencies(module_id) {
+            lines.push(format!(
+                "  {} -> {}",
+                dep.source,
+                relative_to_root(&dep_module_id.id, root)
+            ));
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+fn module_code_snapshots(compiler: &Compiler) -> BTreeMap<String, String> {
+    let context = &compiler.context;
+    let module_graph = context.module_graph.read().unwrap();
+    module_graph
+        .modules()
+        .into_iter()
+        .filter(|module| !module.id.id.contains("node_modules"))
+        .filter_map(|module| {
+            let code = match &module.info.as_ref()?.ast {
+                ModuleAst::Script(ast) => ast.generate(context.clone()).unwrap().code,
+                ModuleAst::Css(ast) => ast.generate(context.clone()).unwrap().code,
+                ModuleAst::None => return None,
+            };
+            Some((relative_to_root(&module.id.id, &context.root), code))
+        })
+        .collect()
+}
@@ -5,12 +5,30 @@ use rayon::{Scope, ThreadPool, ThreadPoolBuilder};
 static THREAD_POOL: OnceLock<ThreadPool> = OnceLock::new();
 
 fn build_rayon_thread_pool() -> ThreadPool {
+    build_rayon_thread_pool_with(None)
+}
+
+fn build_rayon_thread_pool_with(num_threads: Option<usize>) -> ThreadPool {
     ThreadPoolBuilder::new()
         .thread_name(|i| format!("Mako thread {}", i))
+        // 0 means rayon's default, which is the number of logical CPUs
+        .num_threads(num_threads.unwrap_or(0))
         .build()
         .expect("Mako failed to create thread pool.")
 }
 
+/// Initialize the global thread pool with the given number of workers. It
+/// only takes effect if called before the pool is used for the first time.
+pub fn init(num_threads: Option<usize>) {
+    THREAD_POOL.get_or_init(|| build_rayon_thread_pool_with(num_threads));
+}
+
+pub fn current_num_threads() -> usize {
+    THREAD_POOL
+        .get_or_init(build_rayon_thread_pool)
+        .current_num_threads()
+}
+
 pub fn spawn<F>(func: F)
 where
     F: FnOnce() + Send + 'static,
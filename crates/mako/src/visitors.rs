@@ -1,11 +1,16 @@
 pub(crate) mod amd_define_overrides;
 pub(crate) mod async_module;
+pub(crate) mod cjs_exports_lexer;
 pub(crate) mod common_js;
 pub(crate) mod css_assets;
+pub(crate) mod css_color_mix;
 pub(crate) mod css_dep_analyzer;
 pub(crate) mod css_flexbugs;
 pub(crate) mod css_imports;
+pub(crate) mod css_layers;
+pub(crate) mod css_logical_properties;
 pub(crate) mod css_px2rem;
+pub(crate) mod css_scoped;
 pub(crate) mod default_export_namer;
 pub(crate) mod dep_analyzer;
 pub(crate) mod dep_replacer;
@@ -14,11 +19,13 @@ pub(crate) mod dynamic_import_to_require;
 pub(crate) mod env_replacer;
 pub(crate) mod fix_helper_inject_position;
 pub(crate) mod fix_symbol_conflict;
+pub(crate) mod import_attributes;
 pub(crate) mod import_meta_env_replacer;
 pub(crate) mod import_template_to_string_literal;
 pub(crate) mod mako_require;
 pub(crate) mod meta_url_replacer;
 pub(crate) mod new_url_assets;
+pub(crate) mod node_interop;
 pub(crate) mod optimize_define_utils;
 pub(crate) mod provide;
 pub(crate) mod public_path_assignment;
@@ -0,0 +1,240 @@
+use std::collections::HashSet;
+
+use swc_core::common::Mark;
+use swc_core::ecma::ast::{
+    AssignExpr, AssignTarget, CallExpr, Expr, Lit, MemberExpr, MemberProp, ObjectLit, Prop,
+    PropName, PropOrSpread, SimpleAssignTarget,
+};
+use swc_core::ecma::visit::{Visit, VisitWith};
+
+use crate::ast::utils::{get_first_str_arg, is_commonjs_require, is_ident_undefined};
+
+/// The statically detectable exports of a commonjs module
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CjsExports {
+    pub names: HashSet<String>,
+    /// sources of the re-exported modules, e.g. `module.exports = require('./a')`
+    pub reexports: Vec<String>,
+    /// `module.exports` or `exports[key]` is assigned with something which
+    /// can't be analyzed statically, so `names` may be incomplete
+    pub unknown: bool,
+}
+
+/**
+ * Detect the named exports of commonjs modules like cjs-module-lexer does, the patterns:
+ * - exports.a = / module.exports.a = / exports['a'] =
+ * - Object.defineProperty(exports, 'a', {})
+ * - module.exports = { a, b: 1, ...require('./c') }
+ * - module.exports = require('./a')
+ * - __exportStar(require('./a'), exports) / _export_star(require('./a'), exports)
+ */
+pub struct CjsExportsLexer {
+    unresolved_mark: Mark,
+    pub exports: CjsExports,
+}
+
+impl CjsExportsLexer {
+    pub fn new(unresolved_mark: Mark) -> Self {
+        Self {
+            unresolved_mark,
+            exports: Default::default(),
+        }
+    }
+
+    // `exports` or `module.exports`
+    fn is_exports(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Ident(ident) => is_ident_undefined(ident, "exports", &self.unresolved_mark),
+            Expr::Member(member) => self.is_module_exports(member),
+            _ => false,
+        }
+    }
+
+    // `module.exports`
+    fn is_module_exports(&self, member: &MemberExpr) -> bool {
+        matches!(&*member.obj, Expr::Ident(obj) if is_ident_undefined(obj, "module", &self.unresolved_mark))
+            && is_member_prop(&member.prop, "exports")
+    }
+
+    fn require_source(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Call(call_expr) if is_commonjs_require(call_expr, &self.unresolved_mark) => {
+                get_first_str_arg(call_expr)
+            }
+            _ => None,
+        }
+    }
+
+    fn add_name(&mut self, name: &str) {
+        if name != "__esModule" {
+            self.exports.names.insert(name.to_string());
+        }
+    }
+
+    fn add_object_lit(&mut self, obj: &ObjectLit) {
+        for prop in &obj.props {
+            match prop {
+                PropOrSpread::Prop(box Prop::Shorthand(ident)) => self.add_name(&ident.sym),
+                PropOrSpread::Prop(box Prop::KeyValue(kv)) => self.add_prop_name(&kv.key),
+                PropOrSpread::Prop(box Prop::Method(method)) => self.add_prop_name(&method.key),
+                PropOrSpread::Prop(box Prop::Getter(getter)) => self.add_prop_name(&getter.key),
+                PropOrSpread::Spread(spread) => match self.require_source(&spread.expr) {
+                    Some(source) => self.exports.reexports.push(source),
+                    None => self.exports.unknown = true,
+                },
+                _ => {}
+            }
+        }
+    }
+
+    fn add_prop_name(&mut self, key: &PropName) {
+        match key {
+            PropName::Ident(ident) => self.add_name(&ident.sym),
+            PropName::Str(str) => self.add_name(&str.value),
+            _ => self.exports.unknown = true,
+        }
+    }
+}
+
+impl Visit for CjsExportsLexer {
+    fn visit_assign_expr(&mut self, n: &AssignExpr) {
+        if let AssignTarget::Simple(SimpleAssignTarget::Member(member)) = &n.left {
+            if self.is_exports(&member.obj) {
+                // exports.a = 1
+                match &member.prop {
+                    MemberProp::Ident(ident) => self.add_name(&ident.sym),
+                    MemberProp::Computed(computed) => match &*computed.expr {
+                        Expr::Lit(Lit::Str(str)) => self.add_name(&str.value),
+                        _ => self.exports.unknown = true,
+                    },
+                    _ => {}
+                }
+            } else if self.is_module_exports(member) {
+                // module.exports = xxx
+                match &*n.right {
+                    Expr::Object(obj) => self.add_object_lit(obj),
+                    right => match self.require_source(right) {
+                        Some(source) => self.exports.reexports.push(source),
+                        None => self.exports.unknown = true,
+                    },
+                }
+            }
+        }
+        n.visit_children_with(self);
+    }
+
+    fn visit_call_expr(&mut self, n: &CallExpr) {
+        if let Some(callee) = n.callee.as_expr() {
+            let args = n.args.iter().map(|arg| &*arg.expr).collect::<Vec<_>>();
+            match &**callee {
+                // Object.defineProperty(exports, 'a', {})
+                Expr::Member(MemberExpr {
+                    obj: box Expr::Ident(obj),
+                    prop,
+                    ..
+                }) if obj.sym == "Object" && is_member_prop(prop, "defineProperty") => {
+                    if let [target, Expr::Lit(Lit::Str(name)), ..] = args.as_slice()
+                        && self.is_exports(target)
+                    {
+                        self.add_name(&name.value);
+                    }
+                }
+                // __exportStar(require('./a'), exports)
+                Expr::Ident(ident)
+                    if matches!(
+                        ident.sym.as_ref(),
+                        "__exportStar" | "__export" | "_export_star"
+                    ) =>
+                {
+                    if let Some(source) = args.first().and_then(|arg| self.require_source(arg)) {
+                        self.exports.reexports.push(source);
+                    }
+                }
+                _ => {}
+            }
+        }
+        n.visit_children_with(self);
+    }
+}
+
+fn is_member_prop(prop: &MemberProp, name: &str) -> bool {
+    match prop {
+        MemberProp::Ident(ident) => ident.sym == *name,
+        MemberProp::Computed(computed) => {
+            matches!(&*computed.expr, Expr::Lit(Lit::Str(str)) if str.value == *name)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::common::GLOBALS;
+    use swc_core::ecma::visit::VisitWith;
+
+    use super::{CjsExports, CjsExportsLexer};
+    use crate::ast::tests::TestUtils;
+
+    #[test]
+    fn test_exports_assign() {
+        let exports = run(r#"
+Object.defineProperty(exports, "__esModule", { value: true });
+exports.a = 1;
+module.exports.b = 2;
+exports["c"] = 3;
+Object.defineProperty(exports, "d", { enumerable: true, get: function() { return 4; } });
+        "#);
+        assert_eq!(names(&exports), vec!["a", "b", "c", "d"]);
+        assert!(!exports.unknown);
+    }
+
+    #[test]
+    fn test_module_exports_object() {
+        let exports = run(r#"
+const a = 1;
+module.exports = { a, b: 2, "c": 3, d() {}, ...require("./e") };
+        "#);
+        assert_eq!(names(&exports), vec!["a", "b", "c", "d"]);
+        assert_eq!(exports.reexports, vec!["./e"]);
+        assert!(!exports.unknown);
+    }
+
+    #[test]
+    fn test_reexports() {
+        let exports = run(r#"
+module.exports = require("./a");
+__exportStar(require("./b"), exports);
+        "#);
+        assert_eq!(exports.reexports, vec!["./a", "./b"]);
+    }
+
+    #[test]
+    fn test_unknown() {
+        assert!(run("module.exports = function() {};").unknown);
+        assert!(run("exports[key] = 1;").unknown);
+    }
+
+    #[test]
+    fn test_local_exports_binding() {
+        let exports = run(r#"
+function f(exports) { exports.a = 1; }
+        "#);
+        assert!(exports.names.is_empty());
+    }
+
+    fn names(exports: &CjsExports) -> Vec<&str> {
+        let mut names = exports.names.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    fn run(js_code: &str) -> CjsExports {
+        let mut test_utils = TestUtils::gen_js_ast(js_code);
+        let ast = test_utils.ast.js_mut();
+        GLOBALS.set(&test_utils.context.meta.script.globals, || {
+            let mut lexer = CjsExportsLexer::new(ast.unresolved_mark);
+            ast.ast.visit_with(&mut lexer);
+            lexer.exports
+        })
+    }
+}
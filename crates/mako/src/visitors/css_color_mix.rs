@@ -0,0 +1,252 @@
+use swc_core::css::ast::{
+    AbsoluteColorBase, Color, ComponentValue, DelimiterValue, Function, FunctionName, HexColor,
+    LengthPercentage, Token,
+};
+use swc_core::css::visit::{VisitMut, VisitMutWith};
+
+/// The basic named colors of css, the others are kept as is
+const NAMED_COLORS: [(&str, [u8; 3]); 16] = [
+    ("black", [0, 0, 0]),
+    ("silver", [192, 192, 192]),
+    ("gray", [128, 128, 128]),
+    ("white", [255, 255, 255]),
+    ("maroon", [128, 0, 0]),
+    ("red", [255, 0, 0]),
+    ("purple", [128, 0, 128]),
+    ("fuchsia", [255, 0, 255]),
+    ("green", [0, 128, 0]),
+    ("lime", [0, 255, 0]),
+    ("olive", [128, 128, 0]),
+    ("yellow", [255, 255, 0]),
+    ("navy", [0, 0, 128]),
+    ("blue", [0, 0, 255]),
+    ("teal", [0, 128, 128]),
+    ("aqua", [0, 255, 255]),
+];
+
+/**
+ * Compute the color-mix() of static colors in the srgb color space at build time, e.g.
+ * color-mix(in srgb, red 40%, blue) => #660099
+ * the mixes of other color spaces or css variables are kept as is
+ */
+pub struct CSSColorMix {}
+
+impl VisitMut for CSSColorMix {
+    fn visit_mut_component_value(&mut self, value: &mut ComponentValue) {
+        value.visit_mut_children_with(self);
+        let mixed = match value {
+            ComponentValue::Function(function) => mix(function),
+            ComponentValue::Color(box Color::Function(function)) => mix(function),
+            _ => None,
+        };
+        if let Some(mixed) = mixed {
+            *value = mixed;
+        }
+    }
+}
+
+/// rgba in 0..=1
+type Rgba = [f64; 4];
+
+fn mix(function: &Function) -> Option<ComponentValue> {
+    let is_color_mix = match &function.name {
+        FunctionName::Ident(ident) => ident.value.eq_ignore_ascii_case("color-mix"),
+        FunctionName::DashedIdent(_) => false,
+    };
+    if !is_color_mix {
+        return None;
+    }
+    let args = function
+        .value
+        .split(is_comma)
+        .map(|arg| {
+            arg.iter()
+                .filter(|value| !is_whitespace(value))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let [space, first, second] = args.as_slice() else {
+        return None;
+    };
+    let is_srgb = matches!(
+        space.as_slice(),
+        [ComponentValue::Ident(keyword), ComponentValue::Ident(color_space)]
+            if keyword.value.eq_ignore_ascii_case("in")
+                && color_space.value.eq_ignore_ascii_case("srgb")
+    );
+    if !is_srgb {
+        return None;
+    }
+    let (first, p1) = parse_color_and_percentage(first)?;
+    let (second, p2) = parse_color_and_percentage(second)?;
+    let (p1, p2) = match (p1, p2) {
+        (None, None) => (0.5, 0.5),
+        (Some(p1), None) => (p1, 1.0 - p1),
+        (None, Some(p2)) => (1.0 - p2, p2),
+        (Some(p1), Some(p2)) => (p1, p2),
+    };
+    let sum = p1 + p2;
+    if sum <= 0.0 {
+        return None;
+    }
+    Some(to_hex(mix_srgb(
+        first,
+        second,
+        p1 / sum,
+        p2 / sum,
+        sum.min(1.0),
+    )))
+}
+
+/// ref: https://www.w3.org/TR/css-color-5/#color-mix-result, the colors are
+/// interpolated with premultiplied alpha
+fn mix_srgb(first: Rgba, second: Rgba, p1: f64, p2: f64, alpha_multiplier: f64) -> Rgba {
+    let alpha = first[3] * p1 + second[3] * p2;
+    let mut mixed = [0.0, 0.0, 0.0, alpha * alpha_multiplier];
+    if alpha > 0.0 {
+        for (i, channel) in mixed.iter_mut().take(3).enumerate() {
+            *channel = (first[i] * first[3] * p1 + second[i] * second[3] * p2) / alpha;
+        }
+    }
+    mixed
+}
+
+fn parse_color_and_percentage(values: &[&ComponentValue]) -> Option<(Rgba, Option<f64>)> {
+    match values {
+        [color] => Some((parse_color(color)?, None)),
+        // the percentage can be before or after the color
+        [a, b] => match (parse_color(a), parse_percentage(b)) {
+            (Some(color), Some(percentage)) => Some((color, Some(percentage))),
+            _ => Some((parse_color(b)?, Some(parse_percentage(a)?))),
+        },
+        _ => None,
+    }
+}
+
+fn parse_color(value: &ComponentValue) -> Option<Rgba> {
+    match value {
+        ComponentValue::Color(box Color::AbsoluteColorBase(AbsoluteColorBase::HexColor(hex))) => {
+            parse_hex(&hex.value)
+        }
+        ComponentValue::Color(box Color::AbsoluteColorBase(
+            AbsoluteColorBase::NamedColorOrTransparent(ident),
+        ))
+        | ComponentValue::Ident(box ident) => parse_named(&ident.value),
+        _ => None,
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Rgba> {
+    let expanded = match hex.len() {
+        3 | 4 => hex.chars().flat_map(|c| [c, c]).collect::<String>(),
+        6 | 8 => hex.to_string(),
+        _ => return None,
+    };
+    let mut rgba = [1.0; 4];
+    for (i, channel) in rgba.iter_mut().enumerate().take(expanded.len() / 2) {
+        *channel = u8::from_str_radix(&expanded[i * 2..i * 2 + 2], 16).ok()? as f64 / 255.0;
+    }
+    Some(rgba)
+}
+
+fn parse_named(name: &str) -> Option<Rgba> {
+    if name.eq_ignore_ascii_case("transparent") {
+        return Some([0.0; 4]);
+    }
+    NAMED_COLORS
+        .iter()
+        .find(|(named, _)| named.eq_ignore_ascii_case(name))
+        .map(|(_, [r, g, b])| [*r as f64 / 255.0, *g as f64 / 255.0, *b as f64 / 255.0, 1.0])
+}
+
+fn parse_percentage(value: &ComponentValue) -> Option<f64> {
+    let percentage = match value {
+        ComponentValue::Percentage(box percentage) => percentage,
+        ComponentValue::LengthPercentage(box LengthPercentage::Percentage(percentage)) => {
+            percentage
+        }
+        _ => return None,
+    };
+    let value = percentage.value.value;
+    (0.0..=100.0).contains(&value).then_some(value / 100.0)
+}
+
+fn to_hex(rgba: Rgba) -> ComponentValue {
+    let channel = |value: f64| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let mut hex = format!(
+        "{:02x}{:02x}{:02x}",
+        channel(rgba[0]),
+        channel(rgba[1]),
+        channel(rgba[2])
+    );
+    // the hex colors with alpha are lowered by the compat compiler if they're unsupported
+    if channel(rgba[3]) != 255 {
+        hex.push_str(&format!("{:02x}", channel(rgba[3])));
+    }
+    ComponentValue::Color(Box::new(Color::AbsoluteColorBase(
+        AbsoluteColorBase::HexColor(HexColor {
+            span: Default::default(),
+            value: hex.into(),
+            raw: None,
+        }),
+    )))
+}
+
+fn is_comma(value: &ComponentValue) -> bool {
+    match value {
+        ComponentValue::Delimiter(delimiter) => matches!(delimiter.value, DelimiterValue::Comma),
+        _ => false,
+    }
+}
+
+fn is_whitespace(value: &ComponentValue) -> bool {
+    match value {
+        ComponentValue::PreservedToken(token) => matches!(token.token, Token::WhiteSpace { .. }),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::css::visit::VisitMutWith;
+
+    use super::CSSColorMix;
+    use crate::ast::tests::TestUtils;
+
+    #[test]
+    fn test_mix_srgb() {
+        assert_eq!(
+            run(".a{color:color-mix(in srgb, red, blue)}"),
+            ".a{color:#800080}"
+        );
+        assert_eq!(
+            run(".a{color:color-mix(in srgb, red 40%, blue)}"),
+            ".a{color:#660099}"
+        );
+        assert_eq!(
+            run(".a{color:color-mix(in srgb, #fff 25%, #000000 25%)}"),
+            ".a{color:#80808080}"
+        );
+    }
+
+    #[test]
+    fn test_mix_with_transparent() {
+        assert_eq!(
+            run(".a{color:color-mix(in srgb, red, transparent)}"),
+            ".a{color:#ff000080}"
+        );
+    }
+
+    #[test]
+    fn test_unsupported_mix_untouched() {
+        assert!(run(".a{color:color-mix(in oklch, red, blue)}").contains("color-mix("));
+        assert!(run(".a{color:color-mix(in srgb, var(--a), blue)}").contains("color-mix("));
+    }
+
+    fn run(css_code: &str) -> String {
+        let mut test_utils = TestUtils::gen_css_ast(css_code.to_string(), true);
+        let ast = test_utils.ast.css_mut();
+        ast.ast.visit_mut_with(&mut CSSColorMix {});
+        test_utils.css_ast_to_code()
+    }
+}
@@ -1,21 +1,27 @@
-use swc_core::css::ast::{ImportHref, UrlValue};
-use swc_core::css::visit::Visit;
+use swc_core::css::ast::{ImportHref, ImportPrelude, UrlValue};
+use swc_core::css::visit::{Visit, VisitWith};
 
 use crate::ast::utils;
 use crate::module::{Dependency, ResolveType};
+use crate::visitors::css_layers::{import_layer_name, join_layer_names};
 
 pub struct CSSDepAnalyzer {
     pub dependencies: Vec<Dependency>,
     order: usize,
+    // the layer of the module, the imports of a.css?layer=base are nested in base
+    layer: Option<String>,
+    import_layer: Option<String>,
 }
 
 impl CSSDepAnalyzer {
-    pub fn new() -> Self {
+    pub fn new(layer: Option<String>) -> Self {
         Self {
             dependencies: vec![],
             // start with 1
             // 0 for swc helpers
             order: 1,
+            layer,
+            import_layer: None,
         }
     }
 
@@ -24,6 +30,18 @@ impl CSSDepAnalyzer {
             return;
         }
         let url = utils::remove_first_tilde(url);
+        // @import "a.css" layer(base) => a.css?layer=base, the module is wrapped in the layer
+        let url = match &self.import_layer {
+            Some(layer) => {
+                let separator = if url.contains('?') { '&' } else { '?' };
+                if layer.is_empty() {
+                    format!("{}{}layer", url, separator)
+                } else {
+                    format!("{}{}layer={}", url, separator, layer)
+                }
+            }
+            None => url,
+        };
         self.dependencies.push(Dependency {
             source: url,
             resolve_as: None,
@@ -36,6 +54,16 @@ impl CSSDepAnalyzer {
 }
 
 impl Visit for CSSDepAnalyzer {
+    fn visit_import_prelude(&mut self, n: &ImportPrelude) {
+        let import_layer = n.layer_name.as_ref().map(|name| import_layer_name(name));
+        self.import_layer = match (&self.layer, import_layer) {
+            (Some(parent), Some(name)) => Some(join_layer_names(parent, &name)),
+            (parent, name) => name.or_else(|| parent.clone()),
+        };
+        n.href.visit_with(self);
+        self.import_layer = None;
+    }
+
     fn visit_import_href(&mut self, n: &ImportHref) {
         match n {
             // e.g.
@@ -95,6 +123,27 @@ mod tests {
         assert!(run(r#"@import url(#a);"#).is_empty());
     }
 
+    #[test]
+    fn test_layer() {
+        assert_eq!(
+            run(r#"@import url(a.css) layer(base);"#),
+            vec!["a.css?layer=base"]
+        );
+        assert_eq!(run(r#"@import "a.css" layer;"#), vec!["a.css?layer"]);
+        assert_eq!(
+            run(r#"@import "a.css?raw" layer(a.b);"#),
+            vec!["a.css?raw&layer=a.b"]
+        );
+        assert_eq!(
+            run_with_layer(r#"@import "a.css";"#, Some("base")),
+            vec!["a.css?layer=base"]
+        );
+        assert_eq!(
+            run_with_layer(r#"@import "a.css" layer(reset);"#, Some("base")),
+            vec!["a.css?layer=base.reset"]
+        );
+    }
+
     #[test]
     fn test_multiple() {
         assert_eq!(
@@ -107,9 +156,13 @@ mod tests {
     }
 
     fn run(css_code: &str) -> Vec<String> {
+        run_with_layer(css_code, None)
+    }
+
+    fn run_with_layer(css_code: &str, layer: Option<&str>) -> Vec<String> {
         let mut test_utils = TestUtils::gen_css_ast(css_code.to_string(), false);
         let ast = test_utils.ast.css_mut();
-        let mut analyzer = super::CSSDepAnalyzer::new();
+        let mut analyzer = super::CSSDepAnalyzer::new(layer.map(|layer| layer.to_string()));
         ast.ast.visit_with(&mut analyzer);
         let sources = analyzer
             .dependencies
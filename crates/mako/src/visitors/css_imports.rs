@@ -3,6 +3,7 @@ use swc_core::css::ast::{AtRule, AtRulePrelude, ImportHref, Rule, Stylesheet, Ur
 use swc_core::css::visit::{VisitMut, VisitMutWith};
 
 use crate::ast::utils::is_remote_or_data_or_hash;
+use crate::visitors::css_layers::is_layer_statement;
 
 pub struct CSSImports;
 
@@ -15,8 +16,12 @@ impl VisitMut for CSSImports {
         // if import is not hoisted, it's not invalid in browser render
         // relative imports will be removed later
         // so only non-relative imports make sense here
+        // the @layer statements which declare the order of the layers are kept before them
         n.rules.sort_by_key(|rule| {
             let mut ret: i8 = 1;
+            if is_layer_statement(rule) {
+                return -1;
+            }
             if let Rule::AtRule {
                 0:
                     box AtRule {
@@ -0,0 +1,440 @@
+use std::collections::HashSet;
+
+use swc_core::common::util::take::Take;
+use swc_core::common::DUMMY_SP;
+use swc_core::css::ast::{
+    AtRule, AtRuleName, AtRulePrelude, ComponentValue, Ident, ImportHref, ImportLayerName,
+    ImportPrelude, LayerName, LayerNameList, LayerPrelude, Rule, SimpleBlock, Stylesheet, Token,
+    TokenAndSpan, UrlValue,
+};
+use swc_core::css::visit::{Visit, VisitMut, VisitWith};
+
+use crate::ast::utils::is_remote_or_data_or_hash;
+use crate::module::{ModuleAst, ModuleId, ResolveType};
+use crate::module_graph::ModuleGraph;
+
+/**
+ * The order of the cascade layers is the order they first appear in, which may be changed
+ * when the imports are hoisted in the concatenated css of the chunk, so the layers are declared
+ * in their order by a single `@layer a, b, c;` at the top, which replaces the `@layer`
+ * statements of the modules. The modules of the chunk are concatenated with the imports first,
+ * so `names` are collected from the import tree of the modules by `css_layer_names`, the layers
+ * which are not in `names` are declared after them in the order of the stylesheet
+ */
+pub struct CSSLayers {
+    pub names: Vec<String>,
+}
+
+impl VisitMut for CSSLayers {
+    fn visit_mut_stylesheet(&mut self, n: &mut Stylesheet) {
+        let mut names = self.names.clone();
+        for rule in &n.rules {
+            if let Rule::AtRule(at_rule) = rule {
+                collect_layer_names(at_rule, "", &mut names);
+            }
+        }
+        if names.is_empty() {
+            return;
+        }
+        n.rules.retain(|rule| !is_layer_statement(rule));
+        let names = names
+            .iter()
+            .map(|name| to_layer_name(name))
+            .collect::<Vec<_>>();
+        n.rules
+            .insert(0, layer_rule(Some(to_layer_prelude(names)), None));
+    }
+}
+
+/// The layers of the css modules in the order they first appear in as if the imports were
+/// inlined, i.e. the layers of a module in the source order, with the layers of an import
+/// collected at the `@import`, before the rules after it
+pub fn css_layer_names(module_ids: &[&ModuleId], module_graph: &ModuleGraph) -> Vec<String> {
+    let in_chunk = module_ids.iter().copied().collect::<HashSet<_>>();
+    // start from the modules which are not imported by the other css modules of the chunk
+    let roots = module_ids.iter().filter(|module_id| {
+        !module_graph
+            .get_dependents(module_id)
+            .iter()
+            .any(|(dependent, dep)| {
+                matches!(dep.resolve_type, ResolveType::Css) && in_chunk.contains(dependent)
+            })
+    });
+    let mut names = vec![];
+    let mut visited = HashSet::new();
+    for module_id in roots.chain(module_ids.iter()) {
+        collect_module_layer_names(module_id, module_graph, &in_chunk, &mut visited, &mut names);
+    }
+    names
+}
+
+fn collect_module_layer_names<'a>(
+    module_id: &'a ModuleId,
+    module_graph: &'a ModuleGraph,
+    in_chunk: &HashSet<&ModuleId>,
+    visited: &mut HashSet<&'a ModuleId>,
+    names: &mut Vec<String>,
+) {
+    if !visited.insert(module_id) {
+        return;
+    }
+    let Some(info) = module_graph
+        .get_module(module_id)
+        .and_then(|module| module.info.as_ref())
+    else {
+        return;
+    };
+    let ModuleAst::Css(ast) = &info.ast else {
+        return;
+    };
+    // the imports of a.css?layer=base are nested in base
+    let layer = info.file.param("layer").unwrap_or_default();
+    // the local imports are the css dependencies in the same order
+    let mut imports = module_graph
+        .get_dependencies(module_id)
+        .into_iter()
+        .filter(|(_, dep)| matches!(dep.resolve_type, ResolveType::Css))
+        .map(|(dep_module_id, _)| dep_module_id);
+    for rule in &ast.ast.rules {
+        let Rule::AtRule(at_rule) = rule else {
+            continue;
+        };
+        let Some(box AtRulePrelude::ImportPrelude(prelude)) = &at_rule.prelude else {
+            collect_layer_names(at_rule, "", names);
+            continue;
+        };
+        if let Some(layer_name) = &prelude.layer_name {
+            let name = join_layer_names(&layer, &import_layer_name(layer_name));
+            if !name.is_empty() && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        if import_href(prelude).is_some_and(|href| !is_remote_or_data_or_hash(&href))
+            && let Some(dep_module_id) = imports.next()
+            && in_chunk.contains(dep_module_id)
+        {
+            collect_module_layer_names(dep_module_id, module_graph, in_chunk, visited, names);
+        }
+    }
+}
+
+/// `@layer a, b;`, the statements are kept before the imports
+pub fn is_layer_statement(rule: &Rule) -> bool {
+    matches!(
+        rule,
+        Rule::AtRule(box AtRule {
+            prelude: Some(box AtRulePrelude::LayerPrelude(_)),
+            block: None,
+            ..
+        })
+    )
+}
+
+/// The css imported by `@import "a.css" layer(base)` is the module `a.css?layer=base`, its rules
+/// are wrapped in `@layer base { ... }`, and in an anonymous layer for the empty name
+pub fn wrap_in_layer(ast: &mut Stylesheet, layer: &str) {
+    // @import and @charset can't be nested
+    let (outer, inner): (Vec<Rule>, Vec<Rule>) = ast.rules.take().into_iter().partition(|rule| {
+        matches!(
+            rule,
+            Rule::AtRule(box AtRule {
+                name: AtRuleName::Ident(Ident { value, .. }),
+                ..
+            }) if value.eq_ignore_ascii_case("import") || value.eq_ignore_ascii_case("charset")
+        )
+    });
+    let prelude = if layer.is_empty() {
+        None
+    } else {
+        Some(LayerPrelude::Name(to_layer_name(layer)))
+    };
+    let block = SimpleBlock {
+        span: DUMMY_SP,
+        name: TokenAndSpan {
+            span: DUMMY_SP,
+            token: Token::LBrace,
+        },
+        value: inner
+            .into_iter()
+            .map(|rule| match rule {
+                Rule::QualifiedRule(rule) => ComponentValue::QualifiedRule(rule),
+                Rule::AtRule(rule) => ComponentValue::AtRule(rule),
+                Rule::ListOfComponentValues(values) => {
+                    ComponentValue::ListOfComponentValues(values)
+                }
+            })
+            .collect(),
+    };
+    ast.rules = outer;
+    ast.rules.push(layer_rule(prelude, Some(block)));
+}
+
+/// The name of the layer of `@import "a.css" layer(a.b)`, empty for the anonymous `layer`
+pub fn import_layer_name(name: &ImportLayerName) -> String {
+    match name {
+        ImportLayerName::Ident(_) => "".to_string(),
+        ImportLayerName::Function(function) => {
+            let mut collector = LayerNameCollector { names: vec![] };
+            function.value.visit_with(&mut collector);
+            collector.names.join(".")
+        }
+    }
+}
+
+fn import_href(prelude: &ImportPrelude) -> Option<String> {
+    match &*prelude.href {
+        ImportHref::Url(url) => url.value.as_ref().map(|box value| match value {
+            UrlValue::Str(str) => str.value.to_string(),
+            UrlValue::Raw(raw) => raw.value.to_string(),
+        }),
+        ImportHref::Str(str) => Some(str.value.to_string()),
+    }
+}
+
+// a.b => a.b.c for the nested layers
+pub fn join_layer_names(parent: &str, name: &str) -> String {
+    match (parent.is_empty(), name.is_empty()) {
+        (true, _) => name.to_string(),
+        (_, true) => parent.to_string(),
+        _ => format!("{}.{}", parent, name),
+    }
+}
+
+fn collect_layer_names(at_rule: &AtRule, parent: &str, names: &mut Vec<String>) {
+    let mut add = |name: String| {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    };
+    match &at_rule.prelude {
+        Some(box AtRulePrelude::LayerPrelude(prelude)) => {
+            let layer_names = match prelude {
+                LayerPrelude::Name(name) => vec![name],
+                LayerPrelude::NameList(list) => list.name_list.iter().collect(),
+            };
+            for name in &layer_names {
+                add(join_layer_names(parent, &layer_name_string(name)));
+            }
+            // the names in the anonymous layers can't be referenced
+            if let Some(block) = &at_rule.block
+                && let [name] = layer_names.as_slice()
+            {
+                let parent = join_layer_names(parent, &layer_name_string(name));
+                collect_block_layer_names(block, &parent, names);
+            }
+        }
+        Some(box AtRulePrelude::ImportPrelude(prelude)) => {
+            if let Some(layer_name) = &prelude.layer_name {
+                let name = import_layer_name(layer_name);
+                if !name.is_empty() {
+                    add(join_layer_names(parent, &name));
+                }
+            }
+        }
+        // e.g. @layer {}
+        _ if is_layer(at_rule) => {}
+        // e.g. @media, @supports
+        _ => {
+            if let Some(block) = &at_rule.block {
+                collect_block_layer_names(block, parent, names);
+            }
+        }
+    }
+}
+
+fn collect_block_layer_names(block: &SimpleBlock, parent: &str, names: &mut Vec<String>) {
+    for value in &block.value {
+        if let ComponentValue::AtRule(at_rule) = value {
+            collect_layer_names(at_rule, parent, names);
+        }
+    }
+}
+
+fn is_layer(at_rule: &AtRule) -> bool {
+    match &at_rule.name {
+        AtRuleName::Ident(Ident { value, .. }) => value.eq_ignore_ascii_case("layer"),
+        _ => false,
+    }
+}
+
+fn layer_name_string(name: &LayerName) -> String {
+    name.name
+        .iter()
+        .map(|ident| ident.value.to_string())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn to_layer_name(name: &str) -> LayerName {
+    LayerName {
+        span: DUMMY_SP,
+        name: name
+            .split('.')
+            .map(|name| Ident {
+                span: DUMMY_SP,
+                value: name.into(),
+                raw: None,
+            })
+            .collect(),
+    }
+}
+
+fn to_layer_prelude(mut names: Vec<LayerName>) -> LayerPrelude {
+    if names.len() == 1 {
+        LayerPrelude::Name(names.remove(0))
+    } else {
+        LayerPrelude::NameList(LayerNameList {
+            span: DUMMY_SP,
+            name_list: names,
+        })
+    }
+}
+
+fn layer_rule(prelude: Option<LayerPrelude>, block: Option<SimpleBlock>) -> Rule {
+    Rule::AtRule(Box::new(AtRule {
+        span: DUMMY_SP,
+        name: AtRuleName::Ident(Ident {
+            span: DUMMY_SP,
+            value: "layer".into(),
+            raw: None,
+        }),
+        prelude: prelude.map(|prelude| Box::new(AtRulePrelude::LayerPrelude(prelude))),
+        block,
+    }))
+}
+
+// the arguments of layer() may be parsed as the idents or as the tokens
+struct LayerNameCollector {
+    names: Vec<String>,
+}
+
+impl Visit for LayerNameCollector {
+    fn visit_ident(&mut self, n: &Ident) {
+        self.names.push(n.value.to_string());
+    }
+
+    fn visit_token(&mut self, n: &Token) {
+        if let Token::Ident { value, .. } = n {
+            self.names.push(value.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::css::visit::VisitMutWith;
+
+    use super::{css_layer_names, join_layer_names, wrap_in_layer, CSSLayers};
+    use crate::ast::tests::TestUtils;
+    use crate::utils::test_helper::setup_compiler;
+    use crate::visitors::css_imports::CSSImports;
+
+    #[test]
+    fn test_join_layer_names() {
+        assert_eq!(join_layer_names("", "a"), "a");
+        assert_eq!(join_layer_names("a", ""), "a");
+        assert_eq!(join_layer_names("a", "b.c"), "a.b.c");
+    }
+
+    #[test]
+    fn test_hoist_layer_order() {
+        assert_eq!(
+            run(
+                r#"
+@layer components { .a { color: red; } }
+@layer reset, components;
+@import url(https://a) layer(remote);
+@media screen { @layer utilities { .b { color: blue; } } }
+@layer components { @layer button { .c { color: green; } } }
+                "#,
+                None
+            ),
+            r#"
+@layer components, reset, remote, utilities, components.button;
+@import url(https://a) layer(remote);
+@layer components {
+  .a {
+    color: red;
+  }
+}
+@media screen {
+  @layer utilities {
+    .b {
+      color: blue;
+    }
+  }
+}
+@layer components {
+  @layer button {
+    .c {
+      color: green;
+    }
+  }
+}
+            "#
+            .trim()
+        );
+    }
+
+    #[test]
+    fn test_css_layer_names() {
+        let compiler = setup_compiler("test/build/css-layers", false);
+        compiler.build_entries().unwrap();
+        let module_graph = compiler.context.module_graph.read().unwrap();
+        let mut module_ids = module_graph
+            .modules()
+            .into_iter()
+            .map(|module| &module.id)
+            .filter(|module_id| module_id.id.contains(".css"))
+            .collect::<Vec<_>>();
+        // the imports are concatenated before their importers in the chunk
+        module_ids.sort_by_key(|module_id| module_id.id.contains("index.css"));
+        assert_eq!(
+            css_layer_names(&module_ids, &module_graph),
+            vec!["reset", "theme"]
+        );
+    }
+
+    #[test]
+    fn test_no_layers() {
+        assert_eq!(run(".a { color: red; }", None), ".a {\n  color: red;\n}");
+    }
+
+    #[test]
+    fn test_wrap_in_layer() {
+        assert_eq!(
+            run(
+                r#"
+@import url(https://a);
+.a { color: red; }
+                "#,
+                Some("base.reset")
+            ),
+            r#"
+@layer base.reset;
+@import url(https://a);
+@layer base.reset {
+  .a {
+    color: red;
+  }
+}
+            "#
+            .trim()
+        );
+        assert_eq!(
+            run(".a { color: red; }", Some("")),
+            "@layer {\n  .a {\n    color: red;\n  }\n}"
+        );
+    }
+
+    fn run(css_code: &str, layer: Option<&str>) -> String {
+        let mut test_utils = TestUtils::gen_css_ast(css_code.to_string(), false);
+        let ast = test_utils.ast.css_mut();
+        if let Some(layer) = layer {
+            wrap_in_layer(&mut ast.ast, layer);
+        }
+        ast.ast.visit_mut_with(&mut CSSLayers { names: vec![] });
+        ast.ast.visit_mut_with(&mut CSSImports {});
+        test_utils.css_ast_to_code()
+    }
+}
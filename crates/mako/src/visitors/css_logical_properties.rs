@@ -0,0 +1,317 @@
+use std::mem::take;
+
+use swc_core::css::ast::{ComponentValue, Declaration, DeclarationName, Ident, SimpleBlock, Token};
+use swc_core::css::visit::{VisitMut, VisitMutWith};
+
+const SIDES: [(&str, &str); 4] = [
+    ("inline-start", "left"),
+    ("inline-end", "right"),
+    ("block-start", "top"),
+    ("block-end", "bottom"),
+];
+const AXES: [(&str, &str, &str); 2] = [("inline", "left", "right"), ("block", "top", "bottom")];
+const SIZES: [(&str, &str); 6] = [
+    ("inline-size", "width"),
+    ("block-size", "height"),
+    ("min-inline-size", "min-width"),
+    ("min-block-size", "min-height"),
+    ("max-inline-size", "max-width"),
+    ("max-block-size", "max-height"),
+];
+const BORDER_PARTS: [&str; 3] = ["width", "style", "color"];
+
+/**
+ * Lower the logical properties to the physical ones, e.g.
+ * margin-inline: 1px 2px => margin-left: 1px; margin-right: 2px
+ * the writing mode is assumed to be horizontal-tb, and the direction is ltr unless `rtl` is
+ * true, same as the `dir` option of postcss-logical
+ */
+pub struct CSSLogicalProperties {
+    /// e.g. margin-inline-start and inline-size, which are supported much earlier
+    /// (chrome 69, safari 12.1) than the others
+    pub longhands: bool,
+    /// e.g. margin-inline, border-block and the inset properties
+    pub shorthands: bool,
+    /// the inline start is the right side
+    pub rtl: bool,
+}
+
+impl VisitMut for CSSLogicalProperties {
+    fn visit_mut_simple_block(&mut self, block: &mut SimpleBlock) {
+        block.visit_mut_children_with(self);
+        block.value = take(&mut block.value)
+            .into_iter()
+            .flat_map(|value| match value {
+                ComponentValue::Declaration(box decl) => self
+                    .to_physical(decl)
+                    .into_iter()
+                    .map(|decl| ComponentValue::Declaration(Box::new(decl)))
+                    .collect(),
+                value => vec![value],
+            })
+            .collect();
+    }
+}
+
+impl CSSLogicalProperties {
+    fn to_physical(&self, decl: Declaration) -> Vec<Declaration> {
+        let name = match &decl.name {
+            DeclarationName::Ident(ident) => ident.value.to_ascii_lowercase(),
+            DeclarationName::DashedIdent(_) => return vec![decl],
+        };
+        if !name.contains("inline") && !name.contains("block") && name != "inset" {
+            return vec![decl];
+        }
+        if let Some(side) = name.strip_prefix("inset-")
+            && let Some(physical) = physical_side(side, self.rtl)
+        {
+            return if self.shorthands {
+                vec![rename(decl, physical)]
+            } else {
+                vec![decl]
+            };
+        }
+        if let Some(physical) = longhand(&name, self.rtl) {
+            return if self.longhands {
+                vec![rename(decl, &physical)]
+            } else {
+                vec![decl]
+            };
+        }
+        if !self.shorthands {
+            return vec![decl];
+        }
+        let values = decl
+            .value
+            .iter()
+            .filter(|value| !is_whitespace(value))
+            .cloned()
+            .collect::<Vec<_>>();
+        if let Some((start, end, duplicated)) = shorthand(&name, self.rtl) {
+            let (start_value, end_value) = match values.as_slice() {
+                _ if duplicated => (decl.value.clone(), decl.value.clone()),
+                [value] => (vec![value.clone()], vec![value.clone()]),
+                [start_value, end_value] => (vec![start_value.clone()], vec![end_value.clone()]),
+                _ => return vec![decl],
+            };
+            return vec![
+                with_value(rename(decl.clone(), &start), start_value),
+                with_value(rename(decl, &end), end_value),
+            ];
+        }
+        if name == "inset" {
+            let (top, right, bottom, left) = match values.as_slice() {
+                [all] => (all, all, all, all),
+                [vertical, horizontal] => (vertical, horizontal, vertical, horizontal),
+                [top, horizontal, bottom] => (top, horizontal, bottom, horizontal),
+                [top, right, bottom, left] => (top, right, bottom, left),
+                _ => return vec![decl],
+            };
+            return [
+                ("top", top),
+                ("right", right),
+                ("bottom", bottom),
+                ("left", left),
+            ]
+            .into_iter()
+            .map(|(name, value)| with_value(rename(decl.clone(), name), vec![value.clone()]))
+            .collect();
+        }
+        vec![decl]
+    }
+}
+
+fn physical_side(logical: &str, rtl: bool) -> Option<&'static str> {
+    SIDES
+        .iter()
+        .find(|(side, _)| *side == logical)
+        .map(|(_, physical)| if rtl { flip(*physical) } else { *physical })
+}
+
+/// The horizontal sides are flipped in rtl
+fn flip(side: &'static str) -> &'static str {
+    match side {
+        "left" => "right",
+        "right" => "left",
+        side => side,
+    }
+}
+
+/// e.g. margin-inline-start => margin-left, border-block-end-color => border-bottom-color
+fn longhand(name: &str, rtl: bool) -> Option<String> {
+    if let Some((_, physical)) = SIZES.iter().find(|(logical, _)| *logical == name) {
+        return Some(physical.to_string());
+    }
+    let (prop, side) = name.split_once('-')?;
+    if !matches!(prop, "margin" | "padding" | "border") {
+        return None;
+    }
+    if let Some(physical) = physical_side(side, rtl) {
+        return Some(format!("{}-{}", prop, physical));
+    }
+    if prop == "border"
+        && let Some((side, part)) = side.rsplit_once('-')
+        && BORDER_PARTS.contains(&part)
+        && let Some(physical) = physical_side(side, rtl)
+    {
+        return Some(format!("border-{}-{}", physical, part));
+    }
+    None
+}
+
+/// e.g. margin-inline => (margin-left, margin-right), the whole value is used for both
+/// sides of border-inline since it's the shorthand of width, style and color
+fn shorthand(name: &str, rtl: bool) -> Option<(String, String, bool)> {
+    let (prop, rest) = name.split_once('-')?;
+    let (axis, part) = match rest.split_once('-') {
+        Some((axis, part)) => (axis, Some(part)),
+        None => (rest, None),
+    };
+    let (_, start, end) = AXES.iter().find(|(logical, _, _)| *logical == axis)?;
+    let (start, end) = if rtl {
+        (flip(*start), flip(*end))
+    } else {
+        (*start, *end)
+    };
+    match (prop, part) {
+        ("inset", None) => Some((start.to_string(), end.to_string(), false)),
+        ("margin" | "padding", None) => Some((
+            format!("{}-{}", prop, start),
+            format!("{}-{}", prop, end),
+            false,
+        )),
+        ("border", None) => Some((format!("border-{}", start), format!("border-{}", end), true)),
+        ("border", Some(part)) if BORDER_PARTS.contains(&part) => Some((
+            format!("border-{}-{}", start, part),
+            format!("border-{}-{}", end, part),
+            false,
+        )),
+        _ => None,
+    }
+}
+
+fn rename(mut decl: Declaration, name: &str) -> Declaration {
+    let span = match &decl.name {
+        DeclarationName::Ident(ident) => ident.span,
+        DeclarationName::DashedIdent(ident) => ident.span,
+    };
+    decl.name = DeclarationName::Ident(Ident {
+        span,
+        value: name.into(),
+        raw: None,
+    });
+    decl
+}
+
+fn is_whitespace(value: &ComponentValue) -> bool {
+    match value {
+        ComponentValue::PreservedToken(token) => matches!(token.token, Token::WhiteSpace { .. }),
+        _ => false,
+    }
+}
+
+fn with_value(mut decl: Declaration, value: Vec<ComponentValue>) -> Declaration {
+    decl.value = value;
+    decl
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::css::visit::VisitMutWith;
+
+    use super::CSSLogicalProperties;
+    use crate::ast::tests::TestUtils;
+
+    #[test]
+    fn test_longhand() {
+        assert_eq!(
+            run(".a{margin-inline-start:1px;border-block-end-color:red;inset-inline-end:0}"),
+            ".a{margin-left:1px;border-bottom-color:red;right:0}"
+        );
+        assert_eq!(
+            run(".a{max-inline-size:10px;block-size:2px}"),
+            ".a{max-width:10px;height:2px}"
+        );
+    }
+
+    #[test]
+    fn test_shorthand() {
+        assert_eq!(
+            run(".a{margin-inline:1px 2px;padding-block:3px}"),
+            ".a{margin-left:1px;margin-right:2px;padding-top:3px;padding-bottom:3px}"
+        );
+        assert_eq!(
+            run(".a{border-inline:1px solid red}"),
+            ".a{border-left:1px solid red;border-right:1px solid red}"
+        );
+        assert_eq!(
+            run(".a{inset:0 1px}"),
+            ".a{top:0;right:1px;bottom:0;left:1px}"
+        );
+    }
+
+    #[test]
+    fn test_important_and_nested_blocks() {
+        assert_eq!(
+            run("@media (min-width:1px){.a{margin-inline:0!important}}"),
+            "@media(min-width:1px){.a{margin-left:0!important;margin-right:0!important}}"
+        );
+    }
+
+    #[test]
+    fn test_physical_properties_untouched() {
+        assert_eq!(
+            run(".a{margin:0 auto;--margin-inline:1px}"),
+            ".a{margin:0 auto;--margin-inline:1px}"
+        );
+    }
+
+    #[test]
+    fn test_only_shorthands() {
+        let mut test_utils = TestUtils::gen_css_ast(
+            ".a{margin-inline-start:1px;inline-size:2px;margin-inline:0;inset-inline-end:0}"
+                .to_string(),
+            true,
+        );
+        let ast = test_utils.ast.css_mut();
+        ast.ast.visit_mut_with(&mut CSSLogicalProperties {
+            longhands: false,
+            shorthands: true,
+            rtl: false,
+        });
+        assert_eq!(
+            test_utils.css_ast_to_code(),
+            ".a{margin-inline-start:1px;inline-size:2px;margin-left:0;margin-right:0;right:0}"
+        );
+    }
+
+    #[test]
+    fn test_rtl() {
+        assert_eq!(
+            run_with_dir(
+                ".a{margin-inline-start:1px;border-inline-end-color:red;padding-inline:1px 2px}",
+                true
+            ),
+            ".a{margin-right:1px;border-left-color:red;padding-right:1px;padding-left:2px}"
+        );
+        assert_eq!(
+            run_with_dir(".a{inset-inline-start:0;margin-block:1px 2px}", true),
+            ".a{right:0;margin-top:1px;margin-bottom:2px}"
+        );
+    }
+
+    fn run(css_code: &str) -> String {
+        run_with_dir(css_code, false)
+    }
+
+    fn run_with_dir(css_code: &str, rtl: bool) -> String {
+        let mut test_utils = TestUtils::gen_css_ast(css_code.to_string(), true);
+        let ast = test_utils.ast.css_mut();
+        ast.ast.visit_mut_with(&mut CSSLogicalProperties {
+            longhands: true,
+            shorthands: true,
+            rtl,
+        });
+        test_utils.css_ast_to_code()
+    }
+}
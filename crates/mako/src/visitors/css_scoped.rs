@@ -0,0 +1,167 @@
+use std::path::Path;
+
+use swc_core::common::DUMMY_SP;
+use swc_core::css::ast::{
+    AttributeSelector, ComplexSelector, ComplexSelectorChildren, CompoundSelector, Ident,
+    QualifiedRule, QualifiedRulePrelude, SubclassSelector, WqName,
+};
+use swc_core::css::visit::{VisitMut, VisitMutWith};
+
+use crate::ast::file::win_path;
+use crate::module::relative_to_root;
+
+/// `import scope from "./a.css?scoped"`, the selectors of the css are scoped by an attribute,
+/// which is exported to the js, e.g. `<div {...scope} />`
+pub const SCOPED_CSS_PARAM: &str = "scoped";
+/// The css of the scoped import with the rewritten selectors
+pub const SCOPED_STYLE_PARAM: &str = "scopedstyle";
+
+/// The attribute of the scoped css, by the path relative to the root so that the ids don't
+/// depend on where the project is
+pub fn scope_id(path: &Path, root: &Path) -> String {
+    let path = relative_to_root(&path.to_string_lossy(), root);
+    let hash = format!("{:x}", md5::compute(path));
+    format!("data-s-{}", &hash[..8])
+}
+
+pub fn scoped_css_exports(path: &Path, root: &Path) -> String {
+    let scope_id = scope_id(path, root);
+    format!(
+        r#"
+import {};
+export var scopeId = "{}";
+export default {{ "{}": "" }};
+"#,
+        serde_json::to_string(&win_path(&format!(
+            "{}?{}",
+            path.to_string_lossy(),
+            SCOPED_STYLE_PARAM
+        )))
+        .unwrap(),
+        scope_id,
+        scope_id
+    )
+}
+
+/**
+ * Add the scope attribute to the last compound selector of each selector, e.g.
+ * .a .b:hover::before => .a .b:hover[data-s-xxx]::before
+ * the selectors in the pseudo classes like :not() and the keyframes are kept
+ */
+pub struct CSSScoped {
+    pub scope_id: String,
+}
+
+impl CSSScoped {
+    fn scope(&self, selector: &mut ComplexSelector) {
+        let compound = selector
+            .children
+            .iter_mut()
+            .rev()
+            .find_map(|child| match child {
+                ComplexSelectorChildren::CompoundSelector(compound) => Some(compound),
+                _ => None,
+            });
+        // `&` is the parent selector, which is scoped already
+        if let Some(compound) = compound
+            && compound.nesting_selector.is_none()
+        {
+            self.scope_compound(compound);
+        }
+    }
+
+    fn scope_compound(&self, compound: &mut CompoundSelector) {
+        let attribute = SubclassSelector::Attribute(Box::new(AttributeSelector {
+            span: DUMMY_SP,
+            name: WqName {
+                span: DUMMY_SP,
+                prefix: None,
+                value: Ident {
+                    span: DUMMY_SP,
+                    value: self.scope_id.clone().into(),
+                    raw: None,
+                },
+            },
+            matcher: None,
+            value: None,
+            modifier: None,
+        }));
+        // the attribute goes before the pseudo elements
+        let index = compound
+            .subclass_selectors
+            .iter()
+            .position(|selector| matches!(selector, SubclassSelector::PseudoElement(_)))
+            .unwrap_or(compound.subclass_selectors.len());
+        compound.subclass_selectors.insert(index, attribute);
+    }
+}
+
+impl VisitMut for CSSScoped {
+    fn visit_mut_qualified_rule(&mut self, n: &mut QualifiedRule) {
+        match &mut n.prelude {
+            QualifiedRulePrelude::SelectorList(list) => list
+                .children
+                .iter_mut()
+                .for_each(|selector| self.scope(selector)),
+            QualifiedRulePrelude::RelativeSelectorList(list) => list
+                .children
+                .iter_mut()
+                .for_each(|selector| self.scope(&mut selector.selector)),
+            QualifiedRulePrelude::ListOfComponentValues(_) => {}
+        }
+        // the nested rules are scoped on their own, as the rules in @media
+        n.block.visit_mut_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use swc_core::css::visit::VisitMutWith;
+
+    use super::{scope_id, scoped_css_exports, CSSScoped};
+    use crate::ast::tests::TestUtils;
+
+    #[test]
+    fn test_scope_id() {
+        let id = scope_id(Path::new("/root/src/a.css"), Path::new("/root"));
+        assert!(id.starts_with("data-s-"));
+        assert_eq!(id.len(), 15);
+        assert_eq!(
+            id,
+            scope_id(Path::new("/other/src/a.css"), Path::new("/other"))
+        );
+    }
+
+    #[test]
+    fn test_scoped() {
+        assert_eq!(run(".a{color:red}"), ".a[data-s-1]{color:red}");
+        assert_eq!(
+            run(".a .b:hover::before,div{color:red}"),
+            ".a .b:hover[data-s-1]::before,div[data-s-1]{color:red}"
+        );
+        assert!(run("@media (max-width:100px){.a:not(.b){color:red}}")
+            .contains(".a:not(.b)[data-s-1]{color:red}"));
+        assert!(!run("@keyframes a{from{color:red}}").contains("data-s-1"));
+        let nested = run(".a{color:red;.b{color:blue}&:hover{color:green}}");
+        assert!(nested.starts_with(".a[data-s-1]{"));
+        assert!(nested.contains(".b[data-s-1]{color:blue}"));
+        assert!(nested.contains("&:hover{color:green}"));
+    }
+
+    #[test]
+    fn test_scoped_css_exports() {
+        let code = scoped_css_exports(Path::new("/root/src/a\"b.css"), Path::new("/root"));
+        assert!(code.contains(r#"import "/root/src/a\"b.css?scopedstyle";"#));
+    }
+
+    fn run(css_code: &str) -> String {
+        let mut test_utils = TestUtils::gen_css_ast(css_code.to_string(), true);
+        let ast = test_utils.ast.css_mut();
+        ast.ast.visit_mut_with(&mut CSSScoped {
+            scope_id: "data-s-1".to_string(),
+        });
+        test_utils.css_ast_to_code()
+    }
+}
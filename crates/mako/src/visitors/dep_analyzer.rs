@@ -3,7 +3,7 @@ use std::sync::Arc;
 use regex::Regex;
 use swc_core::common::comments::Comments;
 use swc_core::common::{BytePos, Mark, Span, Spanned};
-use swc_core::ecma::ast::{CallExpr, Expr, Lit, ModuleDecl, NewExpr, Str};
+use swc_core::ecma::ast::{CallExpr, Expr, ExprOrSpread, Lit, ModuleDecl, NewExpr, Str};
 use swc_core::ecma::visit::{Visit, VisitWith};
 
 use crate::ast::utils;
@@ -58,15 +58,36 @@ impl DepAnalyzer {
                 .and_then(|matched| matched.get(2).map(|m| m.as_str().to_string()))
         });
 
-        let ignore = comments_texts.iter().any(|t| {
-            get_magic_comment_ignore_regex()
-                .captures(t.trim())
-                .map_or(false, |cap| {
+        let is_enabled = |regex: Regex| {
+            comments_texts.iter().any(|t| {
+                regex.captures(t.trim()).map_or(false, |cap| {
                     cap.get(2).map_or(false, |m| m.as_str() == "true")
                 })
-        });
+            })
+        };
+
+        ImportOptions {
+            chunk_name,
+            ignore: is_enabled(get_magic_comment_ignore_regex()),
+            prefetch: is_enabled(get_magic_comment_prefetch_regex()),
+            preload: is_enabled(get_magic_comment_preload_regex()),
+        }
+    }
 
-        ImportOptions { chunk_name, ignore }
+    fn add_worker_dependency(&mut self, url: &Expr) {
+        let Some(str) = resolve_worker_url(url, self.unresolved_mark) else {
+            return;
+        };
+        let import_options = if self.context.config.experimental.magic_comment {
+            self.analyze_import_options(url.span().lo)
+        } else {
+            ImportOptions::default()
+        };
+        self.add_dependency(
+            str.value.to_string(),
+            ResolveType::Worker(import_options),
+            Some(str.span),
+        );
     }
 }
 
@@ -152,6 +173,16 @@ impl Visit for DepAnalyzer {
                 return;
             }
         }
+        // e.g.
+        // navigator.serviceWorker.register(new URL('a', import.meta.url));
+        // CSS.paintWorklet.addModule(new URL('a', import.meta.url));
+        else if utils::is_service_worker_register(expr, &self.unresolved_mark)
+            || utils::is_worklet_add_module(expr, &self.unresolved_mark)
+        {
+            if let Some(arg) = expr.args.first() {
+                self.add_worker_dependency(&arg.expr);
+            }
+        }
         expr.visit_children_with(self);
     }
 
@@ -159,30 +190,12 @@ impl Visit for DepAnalyzer {
         // Web workers
         // e.g.
         // new Worker(new URL('a', import.meta.url));
-        if let Some(str) = resolve_web_worker(expr, self.unresolved_mark) {
-            let maybe_magic_comments_pos = expr.args.as_ref().and_then(|args| {
-                if !args.is_empty() {
-                    match &args[0].expr {
-                        box Expr::New(s) => Some(s.span().lo),
-                        _ => None,
-                    }
-                } else {
-                    None
-                }
-            });
-
-            let import_options = if self.context.config.experimental.magic_comment {
-                maybe_magic_comments_pos.map_or(ImportOptions::default(), |magic_comments_pos| {
-                    self.analyze_import_options(magic_comments_pos)
-                })
-            } else {
-                ImportOptions::default()
-            };
-            self.add_dependency(
-                str.value.to_string(),
-                ResolveType::Worker(import_options),
-                Some(str.span),
-            );
+        // new SharedWorker(new URL('a', import.meta.url));
+        if let box Expr::Ident(ident) = &expr.callee
+            && utils::is_worker_constructor(ident, &self.unresolved_mark)
+            && let Some(arg) = expr.args.as_ref().and_then(|args| args.first())
+        {
+            self.add_worker_dependency(&arg.expr);
         }
         expr.visit_children_with(self);
     }
@@ -191,46 +204,30 @@ impl Visit for DepAnalyzer {
 // get the value of url when the following conditions are met
 // notice: only add dependency when the second argument is import.meta.url
 // e.g.
-// new Worker(new URL('a', import.meta.url));
-fn resolve_web_worker(expr: &NewExpr, unresolved_mark: Mark) -> Option<&Str> {
-    if !expr.args.as_ref().is_some_and(|args| !args.is_empty()) || !expr.callee.is_ident() {
+// new URL('a', import.meta.url)
+fn resolve_worker_url(expr: &Expr, unresolved_mark: Mark) -> Option<&Str> {
+    let Expr::New(NewExpr {
+        callee: box Expr::Ident(ident),
+        args: Some(args),
+        ..
+    }) = expr
+    else {
+        return None;
+    };
+    if !utils::is_ident_undefined(ident, "URL", &unresolved_mark)
+        || !args
+            .get(1)
+            .is_some_and(|arg| utils::is_import_meta_url(&arg.expr))
+    {
         return None;
     }
-
-    if let box Expr::Ident(ident) = &expr.callee {
-        #[allow(clippy::needless_borrow)]
-        if utils::is_ident_undefined(&ident, "Worker", &unresolved_mark) {
-            let args = expr.args.as_ref().unwrap();
-            if let Expr::New(expr) = &*args[0].expr {
-                // TODO: refactor
-                // use too many not operation, not intuitive
-                if !expr.args.as_ref().is_some_and(|args| !args.is_empty())
-                    || !expr.callee.is_ident()
-                {
-                    return None;
-                }
-
-                if let box Expr::Ident(ident) = &expr.callee {
-                    #[allow(clippy::needless_borrow)]
-                    if utils::is_ident_undefined(&ident, "URL", &unresolved_mark) {
-                        let args = expr.args.as_ref().unwrap();
-                        if args
-                            .get(1)
-                            .is_some_and(|arg| utils::is_import_meta_url(&arg.expr))
-                        {
-                            if let box Expr::Lit(Lit::Str(ref str)) = &args[0].expr {
-                                if !utils::is_remote_or_data(&str.value) {
-                                    return Some(str);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
+    match args.first() {
+        Some(ExprOrSpread {
+            expr: box Expr::Lit(Lit::Str(str)),
+            ..
+        }) if !utils::is_remote_or_data(&str.value) => Some(str),
+        _ => None,
     }
-
-    None
 }
 
 fn get_magic_comment_chunk_name_regex() -> Regex {
@@ -241,12 +238,24 @@ fn get_magic_comment_ignore_regex() -> Regex {
     create_cached_regex(r#"(makoIgnore|webpackIgnore):\s*(true|false)"#)
 }
 
+fn get_magic_comment_prefetch_regex() -> Regex {
+    create_cached_regex(r#"(makoPrefetch|webpackPrefetch):\s*(true|false)"#)
+}
+
+fn get_magic_comment_preload_regex() -> Regex {
+    create_cached_regex(r#"(makoPreload|webpackPreload):\s*(true|false)"#)
+}
+
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use swc_core::common::GLOBALS;
     use swc_core::ecma::visit::VisitWith;
 
-    use crate::ast::tests::TestUtils;
+    use crate::ast::tests::{TestUtils, TestUtilsOpts};
+    use crate::compiler::Context;
+    use crate::module::{ImportOptions, ResolveType};
 
     #[test]
     fn test_normal() {
@@ -295,6 +304,52 @@ mod tests {
         assert!(run(r#"new Worker(new URL('https://a', import.meta.url));"#).is_empty());
     }
 
+    #[test]
+    fn test_shared_worker_and_service_worker() {
+        assert_eq!(
+            run(r#"new SharedWorker(new URL('a', import.meta.url));"#),
+            vec!["a"]
+        );
+        assert_eq!(
+            run(r#"navigator.serviceWorker.register(new URL('sw', import.meta.url));"#),
+            vec!["sw"]
+        );
+        // navigator is defined
+        assert!(run(
+            r#"const navigator = {};navigator.serviceWorker.register(new URL('sw', import.meta.url));"#
+        )
+        .is_empty());
+        // the service worker of a plain url is not compiled
+        assert!(run(r#"navigator.serviceWorker.register('/sw.js');"#).is_empty());
+    }
+
+    #[test]
+    fn test_worklet() {
+        assert_eq!(
+            run(r#"ctx.audioWorklet.addModule(new URL('a', import.meta.url));"#),
+            vec!["a"]
+        );
+        assert_eq!(
+            run(r#"CSS.paintWorklet.addModule(new URL('b', import.meta.url));"#),
+            vec!["b"]
+        );
+        // CSS is defined
+        assert!(run(
+            r#"const CSS = {};CSS.paintWorklet.addModule(new URL('b', import.meta.url));"#
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_dynamic_import_resource_hints() {
+        let options = run_import_options(r#"import(/* makoPrefetch: true */ 'a');"#);
+        assert!(options.prefetch && !options.preload);
+        let options = run_import_options(r#"import(/* webpackPreload: true */ 'a');"#);
+        assert!(!options.prefetch && options.preload);
+        let options = run_import_options(r#"import(/* makoPrefetch: false */ 'a');"#);
+        assert!(!options.prefetch && !options.preload);
+    }
+
     #[test]
     fn test_embedded() {
         assert_eq!(run(r#"export function a() { require('b') }"#), vec!["b"]);
@@ -316,4 +371,25 @@ mod tests {
             .collect();
         sources
     }
+
+    fn run_import_options(js_code: &str) -> ImportOptions {
+        let mut context = Context::default();
+        context.config.experimental.magic_comment = true;
+        let mut test_utils = TestUtils::with_context(
+            TestUtilsOpts {
+                file: Some("test.js".to_string()),
+                content: Some(js_code.to_string()),
+            },
+            Arc::new(context),
+        );
+        let ast = test_utils.ast.js_mut();
+        let mut analyzer = super::DepAnalyzer::new(ast.unresolved_mark, test_utils.context.clone());
+        GLOBALS.set(&test_utils.context.meta.script.globals, || {
+            ast.ast.visit_with(&mut analyzer);
+        });
+        match &analyzer.dependencies[0].resolve_type {
+            ResolveType::DynamicImport(options) => options.clone(),
+            _ => panic!("not a dynamic import"),
+        }
+    }
 }
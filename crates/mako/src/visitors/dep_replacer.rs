@@ -3,14 +3,17 @@ use std::sync::Arc;
 
 use swc_core::common::{Mark, DUMMY_SP};
 use swc_core::ecma::ast::{
-    AssignOp, BlockStmt, Expr, ExprOrSpread, FnExpr, Function, Ident, ImportDecl, Lit, NamedExport,
-    NewExpr, Stmt, Str, ThrowStmt, VarDeclKind,
+    AssignOp, BlockStmt, CallExpr, ExportAll, Expr, ExprOrSpread, FnExpr, Function, ImportDecl,
+    Lit, NamedExport, NewExpr, Stmt, Str, ThrowStmt, VarDeclKind,
 };
 use swc_core::ecma::utils::{member_expr, quote_ident, quote_str, ExprFactory};
 use swc_core::ecma::visit::{VisitMut, VisitMutWith};
 
 use crate::ast::file::parse_path;
-use crate::ast::utils::{is_commonjs_require, is_dynamic_import, is_remote_or_data};
+use crate::ast::utils::{
+    is_commonjs_require, is_dynamic_import, is_ident_undefined, is_remote_or_data,
+    is_service_worker_register, is_worker_constructor, is_worklet_add_module,
+};
 use crate::ast::DUMMY_CTXT;
 use crate::compiler::Context;
 use crate::module::{Dependency, ModuleId};
@@ -91,6 +94,11 @@ pub fn miss_throw_stmt<T: AsRef<str>>(source: T) -> Expr {
 
 impl VisitMut for DepReplacer<'_> {
     fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        if let Expr::Call(call_expr) = expr
+            && let Some(str) = resolve_worker_call_mut(call_expr, self.unresolved_mark)
+        {
+            self.replace_source(str);
+        }
         if let Expr::Call(call_expr) = expr {
             let is_commonjs_require_flag = is_commonjs_require(call_expr, &self.unresolved_mark);
             let is_dynamic_import_flag = is_dynamic_import(call_expr);
@@ -151,7 +159,7 @@ impl VisitMut for DepReplacer<'_> {
                                     chunk_graph.get_chunk_for_module(&dep_module_id.clone());
 
                                 if let Some(chunk) = chunk {
-                                    let chunk_id = chunk.id.id.clone();
+                                    let chunk_id = chunk.id.id.to_string();
                                     // `import('./xxx.css')` => `__mako_require__.ensure('./xxx.css')`
                                     *expr =
                                         member_expr!(DUMMY_CTXT, DUMMY_SP, __mako_require__.ensure)
@@ -189,6 +197,10 @@ impl VisitMut for DepReplacer<'_> {
             self.replace_source(src.as_mut());
         }
     }
+
+    fn visit_mut_export_all(&mut self, n: &mut ExportAll) {
+        self.replace_source(&mut n.src);
+    }
 }
 
 impl DepReplacer<'_> {
@@ -204,39 +216,51 @@ impl DepReplacer<'_> {
 
 // TODO: duplicated code with dep_analyzer.rs
 pub fn resolve_web_worker_mut(new_expr: &mut NewExpr, unresolved_mark: Mark) -> Option<&mut Str> {
-    if !new_expr.args.as_ref().is_some_and(|args| !args.is_empty()) || !new_expr.callee.is_ident() {
-        return None;
+    // `Worker` and `SharedWorker` must be unresolved
+    match &new_expr.callee {
+        box Expr::Ident(ident) if is_worker_constructor(ident, &unresolved_mark) => {}
+        _ => return None,
     }
+    // new Worker(new URL(''), base);
+    let arg = new_expr.args.as_mut()?.first_mut()?;
+    resolve_worker_url_mut(&mut arg.expr, unresolved_mark)
+}
 
-    if let box Expr::Ident(Ident { sym, ctxt, .. }) = &mut new_expr.callee {
-        // `Worker` must be unresolved
-        if sym == "Worker" && (ctxt.outer() == unresolved_mark) {
-            let args = new_expr.args.as_mut().unwrap();
-
-            // new Worker(new URL(''), base);
-            if let Expr::New(new_expr) = &mut *args[0].expr {
-                if !new_expr.args.as_ref().is_some_and(|args| !args.is_empty())
-                    || !new_expr.callee.is_ident()
-                {
-                    return None;
-                }
-
-                if let box Expr::Ident(Ident { sym, ctxt, .. }) = &new_expr.callee {
-                    if sym == "URL" && (ctxt.outer() == unresolved_mark) {
-                        // new URL('');
-                        let args = new_expr.args.as_mut().unwrap();
-                        if let box Expr::Lit(Lit::Str(ref mut str)) = &mut args[0].expr {
-                            if !is_remote_or_data(&str.value) {
-                                return Some(str);
-                            }
-                        }
-                    }
-                }
-            }
-        }
+pub fn resolve_worker_call_mut(
+    call_expr: &mut CallExpr,
+    unresolved_mark: Mark,
+) -> Option<&mut Str> {
+    if !is_service_worker_register(call_expr, &unresolved_mark)
+        && !is_worklet_add_module(call_expr, &unresolved_mark)
+    {
+        return None;
     }
+    // navigator.serviceWorker.register(new URL(''));
+    // CSS.paintWorklet.addModule(new URL(''));
+    let arg = call_expr.args.first_mut()?;
+    resolve_worker_url_mut(&mut arg.expr, unresolved_mark)
+}
 
-    None
+// new URL('');
+fn resolve_worker_url_mut(expr: &mut Expr, unresolved_mark: Mark) -> Option<&mut Str> {
+    let Expr::New(NewExpr {
+        callee: box Expr::Ident(ident),
+        args: Some(args),
+        ..
+    }) = expr
+    else {
+        return None;
+    };
+    if !is_ident_undefined(ident, "URL", &unresolved_mark) {
+        return None;
+    }
+    match args.first_mut() {
+        Some(ExprOrSpread {
+            expr: box Expr::Lit(Lit::Str(str)),
+            ..
+        }) if !is_remote_or_data(&str.value) => Some(str),
+        _ => None,
+    }
 }
 
 #[cfg(test)]
@@ -297,6 +321,30 @@ mod tests {
             ),
             r#"new Worker(new URL("/x/index.js"), base);"#,
         );
+        assert_eq!(
+            run(
+                r#"new SharedWorker(new URL('x'), base)"#,
+                build_resolved("x", "/x/index.js"),
+                Default::default()
+            ),
+            r#"new SharedWorker(new URL("/x/index.js"), base);"#,
+        );
+        assert_eq!(
+            run(
+                r#"navigator.serviceWorker.register(new URL('x'), base)"#,
+                build_resolved("x", "/x/index.js"),
+                Default::default()
+            ),
+            r#"navigator.serviceWorker.register(new URL("/x/index.js"), base);"#,
+        );
+        assert_eq!(
+            run(
+                r#"ctx.audioWorklet.addModule(new URL('x'), base)"#,
+                build_resolved("x", "/x/index.js"),
+                Default::default()
+            ),
+            r#"ctx.audioWorklet.addModule(new URL("/x/index.js"), base);"#,
+        );
     }
 
     #[test]
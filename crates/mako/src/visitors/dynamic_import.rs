@@ -124,7 +124,7 @@ impl<'a> VisitMut for DynamicImport<'a> {
                                         .chunk(chunk_id)
                                         .is_some_and(|c| !c.modules.is_empty())
                                     {
-                                        Some(chunk_id.id.clone())
+                                        Some(chunk_id.id.to_string())
                                     } else {
                                         None
                                     }
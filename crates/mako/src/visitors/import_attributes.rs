@@ -0,0 +1,194 @@
+use std::path::Path;
+
+use swc_core::common::errors::HANDLER;
+use swc_core::common::Span;
+use swc_core::ecma::ast::{
+    CallExpr, ExportAll, Expr, ImportDecl, Lit, NamedExport, ObjectLit, Prop, PropName,
+    PropOrSpread, Str,
+};
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
+
+use crate::ast::utils::is_dynamic_import;
+use crate::build::parse::CSS_SHEET_PARAM;
+
+/// The loaded file is parsed as json regardless of its extension
+pub const JSON_MODULE_PARAM: &str = "json";
+
+/**
+ * Route the imports with attributes to the loaders, e.g.
+ * import data from './data.txt' with { type: 'json' } => import data from './data.txt?json'
+ * import sheet from './a.css' with { type: 'css' } => import sheet from './a.css?sheet'
+ * the `assert` keyword of the former proposal is handled the same way.
+ */
+pub struct ImportAttributes {}
+
+impl VisitMut for ImportAttributes {
+    fn visit_mut_import_decl(&mut self, import_decl: &mut ImportDecl) {
+        if let Some(with) = import_decl.with.take() {
+            replace_source(&mut import_decl.src, &with);
+        }
+        import_decl.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_named_export(&mut self, export: &mut NamedExport) {
+        if let Some(src) = export.src.as_mut()
+            && let Some(with) = export.with.take()
+        {
+            replace_source(src, &with);
+        }
+        export.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_export_all(&mut self, export: &mut ExportAll) {
+        if let Some(with) = export.with.take() {
+            replace_source(&mut export.src, &with);
+        }
+        export.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_call_expr(&mut self, expr: &mut CallExpr) {
+        // e.g. import('./data.json', { with: { type: 'json' } })
+        if is_dynamic_import(expr)
+            && expr.args.len() == 2
+            && let Some(options) = expr.args[1].expr.as_object()
+            && let Some(with) = get_object_prop(options, &["with", "assert"])
+            && let Expr::Object(with) = with
+        {
+            let with = with.clone();
+            if let Some(Lit::Str(src)) = expr.args[0].expr.as_mut_lit() {
+                replace_source(src, &with);
+                expr.args.truncate(1);
+            }
+        }
+        expr.visit_mut_children_with(self);
+    }
+}
+
+fn replace_source(src: &mut Str, with: &ObjectLit) {
+    let Some(attr_type) = get_object_prop(with, &["type"]) else {
+        return;
+    };
+    let Some(Lit::Str(attr_type)) = attr_type.as_lit() else {
+        emit_error(
+            with.span,
+            "The value of import attribute \"type\" must be a string",
+        );
+        return;
+    };
+    let source = src.value.to_string();
+    let extname = Path::new(source.split('?').next().unwrap_or(&source))
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let param = match attr_type.value.as_ref() {
+        // the json files are already loaded as json
+        "json" if extname == "json" || extname == "json5" => return,
+        "json" => JSON_MODULE_PARAM,
+        "css" if extname == "css" => CSS_SHEET_PARAM,
+        "css" => {
+            emit_error(
+                attr_type.span,
+                &format!(
+                    "Import attribute type \"css\" is only supported for .css files, got \"{}\"",
+                    source
+                ),
+            );
+            return;
+        }
+        other => {
+            emit_error(
+                attr_type.span,
+                &format!(
+                    "Unsupported import attribute type \"{}\", the supported types are \"json\" and \"css\"",
+                    other
+                ),
+            );
+            return;
+        }
+    };
+    let separator = if source.contains('?') { '&' } else { '?' };
+    let span = src.span;
+    *src = Str::from(format!("{}{}{}", source, separator, param));
+    src.span = span;
+}
+
+fn get_object_prop<'a>(object: &'a ObjectLit, keys: &[&str]) -> Option<&'a Expr> {
+    object.props.iter().find_map(|prop| match prop {
+        PropOrSpread::Prop(box Prop::KeyValue(kv)) => {
+            let key = match &kv.key {
+                PropName::Ident(ident) => ident.sym.to_string(),
+                PropName::Str(str) => str.value.to_string(),
+                _ => return None,
+            };
+            keys.contains(&key.as_str()).then_some(&*kv.value)
+        }
+        _ => None,
+    })
+}
+
+fn emit_error(span: Span, message: &str) {
+    HANDLER.with(|handler| handler.struct_span_err(span, message).emit());
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::base::try_with_handler;
+    use swc_core::common::errors::HANDLER;
+    use swc_core::common::GLOBALS;
+    use swc_core::ecma::visit::VisitMutWith;
+
+    use super::ImportAttributes;
+    use crate::ast::tests::TestUtils;
+
+    #[test]
+    fn test_json_type() {
+        assert_eq!(
+            run(r#"import a from "./a.json" with { type: "json" };"#).unwrap(),
+            r#"import a from "./a.json";"#
+        );
+        assert_eq!(
+            run(r#"import a from "./a.txt" with { type: "json" };"#).unwrap(),
+            r#"import a from "./a.txt?json";"#
+        );
+        assert_eq!(
+            run(r#"export { a } from "./a.txt" with { type: "json" };"#).unwrap(),
+            r#"export { a } from "./a.txt?json";"#
+        );
+        assert_eq!(
+            run(r#"import("./a.txt", { with: { type: "json" } });"#).unwrap(),
+            r#"import("./a.txt?json");"#
+        );
+    }
+
+    #[test]
+    fn test_css_type() {
+        assert_eq!(
+            run(r#"import sheet from "./a.css" with { type: "css" };"#).unwrap(),
+            r#"import sheet from "./a.css?sheet";"#
+        );
+        assert!(run(r#"import sheet from "./a.less" with { type: "css" };"#).is_err());
+    }
+
+    #[test]
+    fn test_unknown_type() {
+        let err = run(r#"import a from "./a.wasm" with { type: "webassembly" };"#).unwrap_err();
+        assert!(err.contains("Unsupported import attribute type \"webassembly\""));
+    }
+
+    fn run(js_code: &str) -> Result<String, String> {
+        let mut test_utils = TestUtils::gen_js_ast(js_code);
+        let cm = test_utils.context.meta.script.cm.clone();
+        let ast = test_utils.ast.js_mut();
+        GLOBALS
+            .set(&test_utils.context.meta.script.globals, || {
+                try_with_handler(cm, Default::default(), |handler| {
+                    HANDLER.set(handler, || {
+                        ast.ast.visit_mut_with(&mut ImportAttributes {});
+                    });
+                    Ok(())
+                })
+            })
+            .map_err(|e| format!("{:?}", e))?;
+        Ok(test_utils.js_ast_to_code())
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use swc_core::common::DUMMY_SP;
 use swc_core::ecma::ast::{
     Expr, IdentName, KeyValueProp, MemberExpr, MemberProp, MetaPropExpr, MetaPropKind, ObjectLit,
@@ -9,46 +11,81 @@ use swc_core::ecma::visit::{VisitMut, VisitMutWith};
 #[derive(Debug)]
 pub(crate) struct ImportMetaEnvReplacer {
     pub(crate) mode: String,
+    /// variables loaded from the .env files
+    pub(crate) env: HashMap<String, String>,
 }
 
 impl ImportMetaEnvReplacer {
-    pub(crate) fn new(mode: String) -> Self {
-        Self { mode }
+    pub(crate) fn new(mode: String, env: HashMap<String, String>) -> Self {
+        Self { mode, env }
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        if key == "MODE" {
+            Some(self.mode.clone())
+        } else {
+            self.env.get(key).cloned()
+        }
     }
 }
 
+fn is_import_meta_env(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Member(MemberExpr {
+            obj: box Expr::MetaProp(MetaPropExpr {
+                kind: MetaPropKind::ImportMeta,
+                ..
+            }),
+            prop: MemberProp::Ident(IdentName { sym, .. }),
+            ..
+        }) if sym == "env"
+    )
+}
+
 impl VisitMut for ImportMetaEnvReplacer {
     fn visit_mut_expr(&mut self, expr: &mut Expr) {
-        match expr {
-            Expr::Member(MemberExpr {
-                obj:
-                    box Expr::MetaProp(MetaPropExpr {
-                        kind: MetaPropKind::ImportMeta,
-                        ..
-                    }),
-                prop: MemberProp::Ident(IdentName { sym, .. }),
-                ..
-            }) if sym == "env" => {
-                // replace import.meta.env with "({ MODE: 'production' })"
-                *expr = ObjectLit {
-                    props: vec![PropOrSpread::Prop(
+        // replace import.meta.env.MODE with "production"
+        if let Expr::Member(MemberExpr {
+            obj,
+            prop: MemberProp::Ident(IdentName { sym, .. }),
+            ..
+        }) = expr
+            && is_import_meta_env(obj)
+            && let Some(value) = self.get(sym)
+        {
+            *expr = quote_str!(value).into();
+        } else if is_import_meta_env(expr) {
+            // replace import.meta.env with "({ MODE: 'production', MAKO_FOO: 'foo' })"
+            let mut keys = self.env.keys().collect::<Vec<_>>();
+            keys.sort();
+            let props = std::iter::once(("MODE", self.mode.clone()))
+                .chain(keys.into_iter().map(|k| (k.as_str(), self.env[k].clone())))
+                .map(|(key, value)| {
+                    PropOrSpread::Prop(
                         Prop::KeyValue(KeyValueProp {
-                            key: quote_ident!("MODE").into(),
-                            value: quote_str!(self.mode.clone()).into(),
+                            key: quote_ident!(key).into(),
+                            value: quote_str!(value).into(),
                         })
                         .into(),
-                    )],
-                    span: DUMMY_SP,
-                }
-                .wrap_with_paren();
+                    )
+                })
+                .collect();
+            *expr = ObjectLit {
+                props,
+                span: DUMMY_SP,
             }
-            _ => expr.visit_mut_children_with(self),
+            .wrap_with_paren();
+        } else {
+            expr.visit_mut_children_with(self);
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use swc_core::common::GLOBALS;
     use swc_core::ecma::visit::VisitMutWith;
 
@@ -62,10 +99,20 @@ mod tests {
                 r#"typeof import.meta.env === "object" ? import.meta.env.MODE : process.env.NODE_ENV"#
             ),
             r#"typeof ({
-    MODE: "development"
-}) === "object" ? ({
-    MODE: "development"
-}).MODE : process.env.NODE_ENV;"#
+    MODE: "development",
+    MAKO_FOO: "foo"
+}) === "object" ? "development" : process.env.NODE_ENV;"#
+        );
+    }
+
+    #[test]
+    fn test_import_meta_env_variables() {
+        assert_eq!(
+            run(r#"log(import.meta.env.MAKO_FOO, import.meta.env.MAKO_BAR)"#),
+            r#"log("foo", ({
+    MODE: "development",
+    MAKO_FOO: "foo"
+}).MAKO_BAR);"#
         );
     }
 
@@ -73,7 +120,10 @@ mod tests {
         let mut test_utils = TestUtils::gen_js_ast(js_code);
         let ast = test_utils.ast.js_mut();
         GLOBALS.set(&test_utils.context.meta.script.globals, || {
-            let mut visitor = ImportMetaEnvReplacer::new("development".to_string());
+            let mut visitor = ImportMetaEnvReplacer::new(
+                "development".to_string(),
+                HashMap::from([("MAKO_FOO".to_string(), "foo".to_string())]),
+            );
             ast.ast.visit_mut_with(&mut visitor);
         });
         test_utils.js_ast_to_code()
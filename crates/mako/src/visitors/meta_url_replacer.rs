@@ -1,23 +1,44 @@
 use swc_core::common::DUMMY_SP;
 use swc_core::ecma::ast::{CondExpr, Expr};
-use swc_core::ecma::utils::member_expr;
+use swc_core::ecma::utils::{member_expr, quote_ident, quote_str, ExprFactory};
 use swc_core::ecma::visit::{VisitMut, VisitMutWith};
 
 use crate::ast::utils::is_import_meta_url;
 use crate::ast::DUMMY_CTXT;
+use crate::config::Platform;
 
-pub struct MetaUrlReplacer {}
+pub struct MetaUrlReplacer {
+    pub platform: Platform,
+    // node es modules have their own import.meta.url
+    pub esm: bool,
+}
 
 impl VisitMut for MetaUrlReplacer {
     fn visit_mut_expr(&mut self, expr: &mut Expr) {
         if is_import_meta_url(expr) {
-            // Compatible with workers: self.document ? self.document.baseURI : self.location.href
-            *expr = Expr::Cond(CondExpr {
-                span: DUMMY_SP,
-                test: member_expr!(DUMMY_CTXT, DUMMY_SP, self.document).into(),
-                cons: member_expr!(DUMMY_CTXT, DUMMY_SP, self.document.baseURI).into(),
-                alt: member_expr!(DUMMY_CTXT, DUMMY_SP, self.location.href).into(),
-            });
+            match (self.platform, self.esm) {
+                (Platform::Browser, _) => {
+                    // Compatible with workers:
+                    // self.document ? self.document.baseURI : self.location.href
+                    *expr = Expr::Cond(CondExpr {
+                        span: DUMMY_SP,
+                        test: member_expr!(DUMMY_CTXT, DUMMY_SP, self.document).into(),
+                        cons: member_expr!(DUMMY_CTXT, DUMMY_SP, self.document.baseURI).into(),
+                        alt: member_expr!(DUMMY_CTXT, DUMMY_SP, self.location.href).into(),
+                    });
+                }
+                // e.g. createRequire(import.meta.url) in the commonjs outputs
+                // require("url").pathToFileURL(__filename).href
+                (Platform::Node, false) => {
+                    *expr = quote_ident!("require")
+                        .as_call(DUMMY_SP, vec![quote_str!("url").as_arg()])
+                        .make_member(quote_ident!("pathToFileURL"))
+                        .as_call(DUMMY_SP, vec![quote_ident!("__filename").as_arg()])
+                        .make_member(quote_ident!("href"))
+                        .into();
+                }
+                (Platform::Node, true) => {}
+            }
         }
 
         expr.visit_mut_children_with(self);
@@ -31,6 +52,7 @@ mod tests {
 
     use super::MetaUrlReplacer;
     use crate::ast::tests::TestUtils;
+    use crate::config::Platform;
 
     #[test]
     fn test_normal() {
@@ -40,11 +62,35 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_node_cjs() {
+        assert_eq!(
+            run_with_platform(
+                "const require = createRequire(import.meta.url)",
+                Platform::Node,
+                false
+            ),
+            r#"const require = createRequire(require("url").pathToFileURL(__filename).href);"#
+        )
+    }
+
+    #[test]
+    fn test_node_esm() {
+        assert_eq!(
+            run_with_platform("import.meta.url", Platform::Node, true),
+            "import.meta.url;"
+        )
+    }
+
     fn run(js_code: &str) -> String {
+        run_with_platform(js_code, Platform::Browser, false)
+    }
+
+    fn run_with_platform(js_code: &str, platform: Platform, esm: bool) -> String {
         let mut test_utils = TestUtils::gen_js_ast(js_code);
         let ast = test_utils.ast.js_mut();
         GLOBALS.set(&test_utils.context.meta.script.globals, || {
-            let mut visitor = MetaUrlReplacer {};
+            let mut visitor = MetaUrlReplacer { platform, esm };
             ast.ast.visit_mut_with(&mut visitor);
         });
         test_utils.js_ast_to_code()
@@ -3,15 +3,16 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use swc_core::common::{Mark, DUMMY_SP};
-use swc_core::ecma::ast::{BinExpr, BinaryOp, Expr, Lit};
+use swc_core::ecma::ast::{BinExpr, BinaryOp, CallExpr, Expr, Lit};
 use swc_core::ecma::utils::{member_expr, quote_str};
-use swc_core::ecma::visit::VisitMut;
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
 
 use crate::ast::file::File;
 use crate::ast::{utils, DUMMY_CTXT};
 use crate::build::load::Load;
 use crate::compiler::Context;
 use crate::config::Platform;
+use crate::features::node::Node;
 use crate::module::{Dependency, ResolveType};
 use crate::resolve;
 
@@ -67,6 +68,17 @@ impl NewUrlAssets {
 }
 
 impl VisitMut for NewUrlAssets {
+    fn visit_mut_call_expr(&mut self, n: &mut CallExpr) {
+        // the urls of the service workers and the worklets are compiled as the worker entries
+        // instead of the assets
+        if utils::is_service_worker_register(n, &self.unresolved_mark)
+            || utils::is_worklet_add_module(n, &self.unresolved_mark)
+        {
+            return;
+        }
+        n.visit_mut_children_with(self);
+    }
+
     fn visit_mut_new_expr(&mut self, n: &mut swc_core::ecma::ast::NewExpr) {
         // new URL('', import.meta.url)
         if let box Expr::Ident(ident) = &n.callee {
@@ -103,7 +115,11 @@ impl VisitMut for NewUrlAssets {
                             } else {
                                 Lit::Str(url.into()).into()
                             };
-                            args[1].expr = self.build_import_meta_url(self.context.clone()).into();
+                            // the assets are emitted next to the node es module chunks
+                            if !Node::is_esm_output(&self.context.config) {
+                                args[1].expr =
+                                    self.build_import_meta_url(self.context.clone()).into();
+                            }
                         }
                     }
                 }
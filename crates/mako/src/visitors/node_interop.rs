@@ -0,0 +1,132 @@
+use std::collections::HashSet;
+
+use swc_core::common::{Mark, DUMMY_SP};
+use swc_core::ecma::ast::{CallExpr, Callee, Expr, Lit, MemberExpr, MemberProp};
+use swc_core::ecma::utils::{quote_ident, quote_str, ExprFactory};
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
+
+use crate::ast::utils::{get_first_str_arg, is_commonjs_require};
+use crate::ast::DUMMY_CTXT;
+
+const INTEROP_HELPERS: [&str; 2] = ["_interop_require_default", "_interop_require_wildcard"];
+
+/// In node's esm (`.mjs` files or packages with `"type": "module"`), the default import of a
+/// commonjs module is always `module.exports`, even if it's marked with `__esModule`. Since the
+/// esm modules are compiled to commonjs with the `__esModule` mark too, the interop can't be
+/// decided at runtime, so it's decided per import with the `sources` which resolve to commonjs
+/// modules, and only their interop helper calls are replaced after the commonjs transform.
+///
+/// _interop_require_default._(require("cjs"))
+/// -> require("@swc/helpers/_/_interop_require_wildcard")._(require("cjs"), true)
+pub struct NodeInterop<'a> {
+    pub unresolved_mark: Mark,
+    pub sources: &'a HashSet<String>,
+}
+
+impl NodeInterop<'_> {
+    fn is_interop_helper(&self, call_expr: &CallExpr) -> bool {
+        matches!(
+            &call_expr.callee,
+            Callee::Expr(box Expr::Member(MemberExpr {
+                obj: box Expr::Ident(helper),
+                prop: MemberProp::Ident(prop),
+                ..
+            })) if prop.sym == *"_" && INTEROP_HELPERS.contains(&helper.sym.as_ref())
+        )
+    }
+
+    fn cjs_require<'b>(&self, call_expr: &'b CallExpr) -> Option<&'b CallExpr> {
+        if call_expr.args.len() != 1 {
+            return None;
+        }
+        match &call_expr.args[0].expr {
+            box Expr::Call(require)
+                if is_commonjs_require(require, &self.unresolved_mark)
+                    && get_first_str_arg(require)
+                        .is_some_and(|src| self.sources.contains(&src)) =>
+            {
+                Some(require)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl VisitMut for NodeInterop<'_> {
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        if let Expr::Call(call_expr) = expr
+            && self.is_interop_helper(call_expr)
+            && let Some(require) = self.cjs_require(call_expr)
+        {
+            let ctxt = DUMMY_CTXT.apply_mark(self.unresolved_mark);
+            let interop = quote_ident!(ctxt, "require").as_call(
+                DUMMY_SP,
+                vec![quote_str!("@swc/helpers/_/_interop_require_wildcard").as_arg()],
+            );
+            *expr = interop.make_member(quote_ident!("_")).as_call(
+                DUMMY_SP,
+                vec![
+                    require.clone().as_arg(),
+                    Expr::Lit(Lit::Bool(true.into())).as_arg(),
+                ],
+            );
+            return;
+        }
+        expr.visit_mut_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use swc_core::common::GLOBALS;
+    use swc_core::ecma::visit::VisitMutWith;
+
+    use super::NodeInterop;
+    use crate::ast::tests::TestUtils;
+
+    #[test]
+    fn test_cjs_default_import() {
+        assert_eq!(
+            run(r#"
+const _lib = _interop_require_default._(require("./lib.js"));
+const _ns = _interop_require_wildcard._(require("./lib.js"));
+            "#),
+            r#"
+const _lib = require("@swc/helpers/_/_interop_require_wildcard")._(require("./lib.js"), true);
+const _ns = require("@swc/helpers/_/_interop_require_wildcard")._(require("./lib.js"), true);
+            "#
+            .trim()
+        );
+    }
+
+    #[test]
+    fn test_esm_and_named_import() {
+        assert_eq!(
+            run(r#"
+const _esm = _interop_require_default._(require("./esm.js"));
+const _lib = require("./lib.js");
+            "#),
+            r#"
+const _esm = _interop_require_default._(require("./esm.js"));
+const _lib = require("./lib.js");
+            "#
+            .trim()
+        );
+    }
+
+    fn run(js_code: &str) -> String {
+        let mut test_utils = TestUtils::gen_js_ast(js_code);
+        let ast = test_utils.ast.js_mut();
+        let sources = HashSet::from(["./lib.js".to_string()]);
+        GLOBALS.set(&test_utils.context.meta.script.globals, || {
+            let mut visitor = NodeInterop {
+                unresolved_mark: ast.unresolved_mark,
+                sources: &sources,
+            };
+            ast.ast.visit_mut_with(&mut visitor);
+        });
+        test_utils.js_ast_to_code()
+    }
+}
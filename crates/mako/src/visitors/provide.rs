@@ -42,35 +42,28 @@ impl VisitMut for Provide {
     fn visit_mut_ident(&mut self, n: &mut Ident) {
         let has_binding = n.ctxt.outer() != self.unresolved_mark;
         let name = &n.sym.to_string();
-        let provider = self.providers.get(name);
-
-        if !has_binding && provider.is_some() {
-            if let Some((from, key)) = provider {
-                let require_decl: ModuleItem = {
-                    if key.is_empty() {
-                        // eg: const process = require('process');
-                        quote_ident!("__mako_require__")
-                            .as_call(DUMMY_SP, vec![quote_str!(from.as_str()).as_arg()])
-                            .into_var_decl(VarDeclKind::Const, n.clone().into())
-                            .into()
-                    } else {
-                        // require("buffer")
-                        let require_expr = quote_ident!("__mako_require__")
-                            .as_call(DUMMY_SP, vec![quote_str!(from.as_str()).as_arg()]);
-
-                        // eg const Buffer = require("buffer").Buffer;
-                        Expr::Member(MemberExpr {
-                            obj: require_expr.into(),
-                            span: DUMMY_SP,
-                            prop: quote_ident!(key.as_str()).into(),
-                        })
-                        .into_var_decl(VarDeclKind::Const, n.clone().into())
-                        .into()
-                    }
-                };
-
-                self.var_decls.insert(name.to_string(), require_decl);
-            }
+        if has_binding || self.var_decls.contains_key(name) {
+            return;
+        }
+
+        if let Some((from, props)) = self.providers.get(name).and_then(|p| p.parse()) {
+            // eg: const process = require('process');
+            let require_expr: Expr = quote_ident!("__mako_require__")
+                .as_call(DUMMY_SP, vec![quote_str!(from).as_arg()])
+                .into();
+            // eg: const Buffer = require("buffer").Buffer;
+            let expr = props.into_iter().fold(require_expr, |obj, prop| {
+                Expr::Member(MemberExpr {
+                    obj: obj.into(),
+                    span: DUMMY_SP,
+                    prop: quote_ident!(prop).into(),
+                })
+            });
+            let require_decl: ModuleItem = expr
+                .into_var_decl(VarDeclKind::Const, n.clone().into())
+                .into();
+
+            self.var_decls.insert(name.to_string(), require_decl);
         }
     }
 }
@@ -119,6 +112,7 @@ mod tests {
 
     use super::Provide;
     use crate::ast::tests::TestUtils;
+    use crate::config::Provider;
 
     #[test]
     fn test_provide_normal() {
@@ -167,13 +161,37 @@ console.log({
         );
     }
 
+    #[test]
+    fn test_provide_module_and_nested_props() {
+        assert_eq!(
+            run(r#"
+$('.a');
+$('.b');
+console.log(Fragment);
+            "#),
+            r#"
+const $ = __mako_require__("jquery");
+const Fragment = __mako_require__("react").default.Fragment;
+$('.a');
+$('.b');
+console.log(Fragment);
+            "#
+            .trim()
+        );
+    }
+
     fn run(js_code: &str) -> String {
         let mut test_utils = TestUtils::gen_js_ast(js_code);
         let ast = test_utils.ast.js_mut();
         GLOBALS.set(&test_utils.context.meta.script.globals, || {
             let mut providers = HashMap::new();
-            providers.insert("process".into(), ("process".into(), "".into()));
-            providers.insert("Buffer".into(), ("buffer".into(), "Buffer".into()));
+            providers.insert("process".into(), Provider::new("process", &[""]));
+            providers.insert("Buffer".into(), Provider::new("buffer", &["Buffer"]));
+            providers.insert("$".into(), Provider::Module("jquery".into()));
+            providers.insert(
+                "Fragment".into(),
+                Provider::new("react", &["default", "Fragment"]),
+            );
             let mut visitor = Provide::new(providers, ast.unresolved_mark, ast.top_level_mark);
             ast.ast.visit_mut_with(&mut visitor);
         });
@@ -1,10 +1,13 @@
 use swc_core::common::util::take::Take;
 use swc_core::common::Mark;
 use swc_core::ecma::ast::{Module, Program};
-use swc_core::ecma::transforms::typescript::strip;
+use swc_core::ecma::transforms::typescript;
 use swc_core::ecma::visit::{VisitMut, VisitMutWith};
 
+use crate::config::{ImportsNotUsedAsValues, TypescriptConfig};
+
 pub struct TypescriptStrip {
+    config: TypescriptConfig,
     top_level_mark: Mark,
     unresolved_mark: Mark,
 }
@@ -12,15 +15,101 @@ pub struct TypescriptStrip {
 impl VisitMut for TypescriptStrip {
     fn visit_mut_module(&mut self, n: &mut Module) {
         let mut p = Program::Module(n.take());
-        p.visit_mut_with(&mut strip(self.unresolved_mark, self.top_level_mark));
+        p.visit_mut_with(&mut typescript::typescript(
+            strip_config(&self.config),
+            self.unresolved_mark,
+            self.top_level_mark,
+        ));
 
         *n = p.module().unwrap();
     }
 }
 
-pub fn ts_strip(unresolved_mark: Mark, top_level_mark: Mark) -> impl VisitMut {
+pub fn ts_strip(
+    config: TypescriptConfig,
+    unresolved_mark: Mark,
+    top_level_mark: Mark,
+) -> impl VisitMut {
     TypescriptStrip {
+        config,
         top_level_mark,
         unresolved_mark,
     }
 }
+
+pub(crate) fn strip_config(config: &TypescriptConfig) -> typescript::Config {
+    typescript::Config {
+        verbatim_module_syntax: config.verbatim_module_syntax,
+        import_not_used_as_values: match config.imports_not_used_as_values {
+            ImportsNotUsedAsValues::Remove => typescript::ImportsNotUsedAsValues::Remove,
+            ImportsNotUsedAsValues::Preserve => typescript::ImportsNotUsedAsValues::Preserve,
+        },
+        // keep the class fields as is to be defined by preset_env
+        native_class_properties: config.use_define_for_class_fields.unwrap_or(false),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::common::GLOBALS;
+    use swc_core::ecma::transforms::base::resolver;
+    use swc_core::ecma::visit::VisitMutWith;
+
+    use super::ts_strip;
+    use crate::ast::tests::{TestUtils, TestUtilsOpts};
+    use crate::config::{ImportsNotUsedAsValues, TypescriptConfig};
+
+    const CODE: &str = r#"
+import type { A } from "./types";
+import { B } from "./b";
+import { type C, D } from "./c";
+let x: B;
+"#;
+
+    #[test]
+    fn test_remove_unused_imports() {
+        let code = run(CODE, false, ImportsNotUsedAsValues::Remove);
+        assert!(!code.contains("./types"));
+        assert!(!code.contains("./b"));
+        assert!(!code.contains("./c"));
+    }
+
+    #[test]
+    fn test_preserve_imports_for_side_effects() {
+        let code = run(CODE, false, ImportsNotUsedAsValues::Preserve);
+        assert!(!code.contains("./types"));
+        assert!(code.contains(r#"import "./b";"#));
+    }
+
+    #[test]
+    fn test_verbatim_module_syntax() {
+        let code = run(CODE, true, ImportsNotUsedAsValues::Remove);
+        assert!(!code.contains("./types"));
+        assert!(code.contains(r#"import { B } from "./b";"#));
+        assert!(code.contains(r#"import { D } from "./c";"#));
+    }
+
+    fn run(
+        code: &str,
+        verbatim_module_syntax: bool,
+        imports_not_used_as_values: ImportsNotUsedAsValues,
+    ) -> String {
+        let mut test_utils = TestUtils::new(TestUtilsOpts {
+            file: Some("test.ts".to_string()),
+            content: Some(code.to_string()),
+        });
+        let ast = test_utils.ast.js_mut();
+        GLOBALS.set(&test_utils.context.meta.script.globals, || {
+            ast.ast
+                .visit_mut_with(&mut resolver(ast.unresolved_mark, ast.top_level_mark, true));
+            let config = TypescriptConfig {
+                verbatim_module_syntax,
+                imports_not_used_as_values,
+            };
+            let mut visitor = ts_strip(config, ast.unresolved_mark, ast.top_level_mark);
+            ast.ast.visit_mut_with(&mut visitor);
+        });
+        test_utils.js_ast_to_code()
+    }
+}
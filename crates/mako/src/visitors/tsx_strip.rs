@@ -9,6 +9,7 @@ use swc_core::ecma::transforms::typescript::TsxConfig;
 use swc_core::ecma::visit::{VisitMut, VisitMutWith};
 
 use crate::compiler::Context;
+use crate::visitors::ts_strip::strip_config;
 
 pub struct TsxStrip {
     cm: Arc<swc_core::common::SourceMap>,
@@ -27,7 +28,7 @@ impl VisitMut for TsxStrip {
         let mut p = Program::Module(n.take());
         p.visit_mut_with(&mut typescript::tsx(
             self.cm.clone(),
-            Default::default(),
+            strip_config(&self.context.config.typescript),
             tsx_config,
             comments,
             self.unresolved_mark,
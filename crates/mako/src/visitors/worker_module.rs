@@ -1,8 +1,15 @@
 use swc_core::common::Mark;
-use swc_core::ecma::ast::{Expr, Lit, NewExpr, Str};
+use swc_core::ecma::ast::{CallExpr, NewExpr, Str};
 use swc_core::ecma::visit::{VisitMut, VisitMutWith};
 
-use crate::ast::utils::{is_ident_undefined, is_remote_or_data};
+use crate::ast::utils::{is_service_worker_register, is_worklet_add_module};
+use crate::visitors::dep_replacer::{resolve_web_worker_mut, resolve_worker_call_mut};
+
+/// The query of the worklet entries, which are emitted as the plain ES modules
+pub(crate) const WORKLET_QUERY: &str = "?asworklet";
+/// The query of the service worker entries, which are emitted as the single unhashed files
+pub(crate) const SERVICE_WORKER_QUERY: &str = "?asserviceworker";
+const WORKER_QUERY: &str = "?asworker";
 
 pub struct WorkerModule {
     unresolved_mark: Mark,
@@ -16,51 +23,37 @@ impl WorkerModule {
 
 impl VisitMut for WorkerModule {
     fn visit_mut_new_expr(&mut self, new_expr: &mut NewExpr) {
-        if !new_expr.args.as_ref().is_some_and(|args| !args.is_empty())
-            || !new_expr.callee.is_ident()
-        {
-            return;
+        // new Worker(new URL(''), base);
+        if let Some(str) = resolve_web_worker_mut(new_expr, self.unresolved_mark) {
+            self.replace_source(str, WORKER_QUERY);
         }
+        new_expr.visit_mut_children_with(self);
+    }
 
-        if let box Expr::Ident(ident) = &mut new_expr.callee {
-            #[allow(clippy::needless_borrow)]
-            if is_ident_undefined(&ident, "Worker", &self.unresolved_mark) {
-                let args = new_expr.args.as_mut().unwrap();
-
-                // new Worker(new URL(''), base);
-                if let Expr::New(new_expr) = &mut *args[0].expr {
-                    if !new_expr.args.as_ref().is_some_and(|args| !args.is_empty())
-                        || !new_expr.callee.is_ident()
-                    {
-                        return;
-                    }
-
-                    if let Some(ident) = &new_expr.callee.as_ident() {
-                        if is_ident_undefined(ident, "URL", &self.unresolved_mark) {
-                            // new URL('');
-                            let args = new_expr.args.as_mut().unwrap();
-                            if let Some(Lit::Str(ref mut str)) = &mut args[0].expr.as_mut_lit() {
-                                if !is_remote_or_data(&str.value) {
-                                    self.replace_source(str);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+    fn visit_mut_call_expr(&mut self, call_expr: &mut CallExpr) {
+        // navigator.serviceWorker.register(new URL(''));
+        // CSS.paintWorklet.addModule(new URL(''));
+        let query = if is_worklet_add_module(call_expr, &self.unresolved_mark) {
+            WORKLET_QUERY
+        } else if is_service_worker_register(call_expr, &self.unresolved_mark) {
+            SERVICE_WORKER_QUERY
+        } else {
+            WORKER_QUERY
+        };
+        if let Some(str) = resolve_worker_call_mut(call_expr, self.unresolved_mark) {
+            self.replace_source(str, query);
         }
-
-        new_expr.visit_mut_children_with(self);
+        call_expr.visit_mut_children_with(self);
     }
 }
 
 impl WorkerModule {
-    fn replace_source(&mut self, source: &mut Str) {
+    fn replace_source(&mut self, source: &mut Str, query: &str) {
         /* A source file can be a async module and a worker entry at the same time,
          * we need to add a worker query to distinguish worker from async module, or else
          * those two chunks will use the same id, bundled dist will be broken.
          */
-        let to_replace = format!("{}?asworker", &source.value.to_string());
+        let to_replace = format!("{}{}", &source.value.to_string(), query);
         let span = source.span;
         *source = Str::from(to_replace);
         source.span = span;
@@ -0,0 +1,3 @@
+import(/* makoPrefetch: true */ './a');
+import('./b');
+import('./c');
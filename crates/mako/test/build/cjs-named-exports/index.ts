@@ -0,0 +1,3 @@
+import { foo, missing } from './lib';
+
+console.log(foo, missing);
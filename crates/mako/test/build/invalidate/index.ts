@@ -0,0 +1,3 @@
+import data from "./data";
+
+console.log(data);
@@ -0,0 +1,5 @@
+import { foo } from "./foo";
+import "./index.css";
+
+console.log(foo);
+import("./lazy").then(({ lazy }) => console.log(lazy));
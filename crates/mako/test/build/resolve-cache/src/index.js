@@ -0,0 +1,2 @@
+require('foo');
+require('./nested');
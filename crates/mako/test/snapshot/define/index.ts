@@ -0,0 +1,3 @@
+import { version } from './version';
+
+console.log(version, process.env.NODE_ENV);
@@ -0,0 +1 @@
+export const version: string = VERSION;
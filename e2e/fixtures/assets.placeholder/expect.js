@@ -0,0 +1,17 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+assert(
+  /src:\s*`\$\{__mako_require__\.publicPath\}umi-logo\.\w+\.png`/.test(content),
+  "should export the url of the image",
+);
+assert(
+  /width:\s*\d+,\s*height:\s*\d+/.test(content),
+  "should export the dimensions of the image",
+);
+assert(
+  content.includes('placeholder: "data:image/png;base64,'),
+  "should export the placeholder of the image",
+);
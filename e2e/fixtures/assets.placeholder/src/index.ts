@@ -0,0 +1,3 @@
+import logo from "./umi-logo.png?placeholder";
+
+console.log(logo.src, logo.width, logo.height, logo.placeholder);
@@ -3,3 +3,7 @@ const { parseBuildResult } = require("../../../scripts/test-utils");
 const { files } = parseBuildResult(__dirname);
 
 assert("vendors-async.js" in files, "should have vendors-async.js");
+assert(
+  !files["index.js"].includes("retryChunkLoad"),
+  "should not include the chunk load retry runtime without chunkLoadRetry"
+);
@@ -0,0 +1,13 @@
+const assert = require("assert");
+const { parseBuildResult, moduleReg } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+assert(files["umi-logo.png"] !== undefined, "should keep the original filename");
+assert.match(
+  files["index.js"],
+  moduleReg(
+    "src/umi-logo.png",
+    "module.exports = `\\${__mako_require__.publicPath}umi-logo.png\\?v=(.*)`;"
+  ),
+  "should add the hash to the reference as the query"
+);
@@ -0,0 +1,2 @@
+import logo from "./umi-logo.png";
+console.log(logo);
@@ -0,0 +1,13 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+assert(files["images/umi-logo.png"] !== undefined, "should emit the asset with the rule filename");
+assert(
+  files["index.css"].includes("url(images/umi-logo.png)"),
+  "the asset referenced from css should not be inlined",
+);
+assert(
+  files["index.js"].includes("images/umi-logo.png"),
+  "the asset imported from js should not be inlined",
+);
@@ -0,0 +1,4 @@
+import "./index.css";
+import logo from "./umi-logo.png";
+
+console.log(logo);
@@ -0,0 +1,16 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+assert(
+  files["pages_user_settings-async.js"].includes('console.log("settings")'),
+  "the page chunk should be named after the page path",
+);
+assert(
+  files["pages_user-async.js"].includes('console.log("user")'),
+  "the index page chunk should be named after the dir",
+);
+assert(
+  !Object.keys(files).some((file) => file.includes("lazy")),
+  "the chunks of the other modules should be named after the hashed ids",
+);
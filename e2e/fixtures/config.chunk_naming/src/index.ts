@@ -0,0 +1,3 @@
+import("./pages/user/settings");
+import("./pages/user/index");
+import("./lazy");
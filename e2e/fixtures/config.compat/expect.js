@@ -0,0 +1,7 @@
+const assert = require("assert");
+const { parseBuildResult, moduleReg } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+assert.match(content, moduleReg("src/index.js", "this.bar = 1"), "should assign the class properties");
+assert(!content.includes("new WeakMap()"), "should not use WeakMap for the private fields");
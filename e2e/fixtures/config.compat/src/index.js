@@ -0,0 +1,8 @@
+class Foo {
+  bar = 1;
+  #baz = 2;
+  getBaz() {
+    return this.#baz;
+  }
+}
+console.log(new Foo().getBaz());
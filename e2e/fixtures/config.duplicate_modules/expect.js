@@ -0,0 +1,17 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const chunks = Object.keys(files).filter((name) => name.endsWith(".js"));
+const withUtils = chunks.filter((name) => files[name].includes("duplicated_module_content"));
+assert.strictEqual(withUtils.length, 1, `should emit the duplicated module once`);
+assert(withUtils[0].startsWith("shared_"), `should hoist the duplicated module into the shared chunk`);
+
+module.exports = async () => {
+  const result = await require("./dist").run();
+  assert.strictEqual(
+    result,
+    "a duplicated_module_content, b duplicated_module_content",
+    `should load the shared chunk with the async chunks`,
+  );
+};
@@ -0,0 +1,2 @@
+import { shared } from "./utils";
+export const a = `a ${shared}`;
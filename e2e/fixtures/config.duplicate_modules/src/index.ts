@@ -0,0 +1,4 @@
+export async function run() {
+  const [{ a }, { b }] = await Promise.all([import("./a"), import("./b")]);
+  return `${a}, ${b}`;
+}
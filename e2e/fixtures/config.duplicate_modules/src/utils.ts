@@ -0,0 +1 @@
+export const shared = "duplicated_module_content";
@@ -0,0 +1,7 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+assert(files["pages/home/index.js"].includes(`"home"`), `should have pages/home/index.js`);
+assert(files["pages/about/index.js"].includes(`"about"`), `should have pages/about/index.js`);
+assert(!Object.keys(files).some((name) => name.includes("utils")), `should not build utils.ts`);
@@ -0,0 +1,10 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+assert(content.includes(`path: "/users/:id"`), `should have the dynamic route`);
+assert(content.includes(`routes: [`), `should nest the routes in the layout`);
+const asyncChunks = Object.keys(files).filter((name) => name.endsWith("-async.js"));
+assert.strictEqual(asyncChunks.length, 3, `should load the layout and pages lazily`);
+assert(!Object.keys(files).some((name) => name.includes("Button")), `should ignore the components dir`);
@@ -20,3 +20,7 @@ assert(
 assert(
   content.includes(`@import "//c";`, `should keep remote imports`),
 );
+assert(
+  !content.includes(`requireModule.insertStyle`),
+  `should append the styles to the head without styleInjection`,
+);
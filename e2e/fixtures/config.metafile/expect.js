@@ -0,0 +1,30 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+assert("metafile.json" in files, "should have metafile.json");
+const { inputs, outputs } = JSON.parse(files["metafile.json"]);
+
+const index = inputs["src/index.ts"];
+assert(index.bytes > 0, "should have the bytes of the inputs");
+assert.deepStrictEqual(
+  index.imports.map(({ path, kind, external }) => [path, kind, !!external]),
+  [
+    ["react", "import-statement", true],
+    ["src/foo.ts", "import-statement", false],
+    ["src/index.css", "import-statement", false],
+    ["src/lazy.ts", "dynamic-import", false],
+  ],
+  "should have the imports of the inputs",
+);
+
+const entry = outputs["dist/index.js"];
+assert.strictEqual(entry.entryPoint, "src/index.ts", "should have the entry point");
+assert.strictEqual(entry.cssBundle, "dist/index.css", "should have the css bundle");
+assert.deepStrictEqual(entry.exports, ["bar", "default"], "should have the exports");
+assert("src/foo.ts" in entry.inputs, "should have the inputs of the outputs");
+assert(
+  entry.imports.some(({ kind, path }) => kind === "dynamic-import" && path.startsWith("dist/")),
+  "should import the async chunk",
+);
+assert("src/index.css" in outputs["dist/index.css"].inputs, "should have the css inputs");
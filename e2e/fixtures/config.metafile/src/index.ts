@@ -0,0 +1,8 @@
+import React from 'react';
+import { foo } from './foo';
+import './index.css';
+
+export const bar = foo + React.version;
+export default function main() {
+  return import('./lazy');
+}
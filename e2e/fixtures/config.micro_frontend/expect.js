@@ -0,0 +1,16 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const js = files["index.js"];
+
+assert(js.includes(`root['app1'] = factory()`), "the lifecycles should be exposed by umd");
+assert(
+  js.includes(`['bootstrap', 'mount', 'unmount', 'update']`),
+  "the lifecycles should be wrapped"
+);
+assert(
+  !js.includes("global.__mako_require_module__ = requireModule"),
+  "the runtime globals should not be exposed"
+);
+assert(js.includes("makoChunk_app1"), "the chunk loading global should be named after the app");
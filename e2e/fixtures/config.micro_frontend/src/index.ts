@@ -0,0 +1,5 @@
+import './index.css';
+
+export async function mount(props: { container: HTMLElement }) {
+  props.container.innerHTML = '<div class="app">app1</div>';
+}
@@ -0,0 +1,8 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+assert(files["foo.html"], `should emit foo.html`);
+assert(!files["foo.html"].includes(`<script src="/foo.js"></script>`), `should not load the small entry script`);
+assert(files["foo.html"].includes(`function createRuntime(`), `should inline the runtime with the entry script`);
+assert(files["foo.js"], `should still emit the entry script`);
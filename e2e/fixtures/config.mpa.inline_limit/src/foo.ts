@@ -0,0 +1 @@
+import("./lazy").then(({ lazy }) => console.log("foo", lazy));
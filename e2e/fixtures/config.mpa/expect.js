@@ -0,0 +1,13 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+assert(files["foo.html"], `should emit foo.html`);
+assert(files["bar.html"], `should emit bar.html`);
+assert(files["foo.html"].includes(`<title>foo</title>`), `should replace the title`);
+assert(files["foo.html"].includes(`<script src="/foo.js"></script>`), `should inject the entry script`);
+assert(!files["foo.html"].includes(`bar.js`), `should not inject the scripts of other pages`);
+const commonChunk = Object.keys(files).find((name) => name.startsWith("common") && name.endsWith(".js"));
+assert(commonChunk, `should split the shared modules into the common chunk`);
+assert(files["foo.html"].includes(`<script src="/${commonChunk}"></script>`), `should inject the common chunk`);
+assert(files["bar.html"].includes(`<script src="/${commonChunk}"></script>`), `should inject the common chunk`);
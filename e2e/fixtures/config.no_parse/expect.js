@@ -0,0 +1,7 @@
+const assert = require("assert");
+const { parseBuildResult, moduleReg } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+assert.match(content, moduleReg("src/prebuilt.js", "const sum = \\(a, b\\) => a \\+ b;"), "should keep the no parse module as is");
+assert.doesNotMatch(content, moduleReg("src/index.js", "const double = \\(n\\) =>"), "should still transform the other modules");
@@ -0,0 +1,4 @@
+import prebuilt from './prebuilt';
+
+const double = (n) => n * 2;
+console.log(prebuilt.sum(1, double(2)));
@@ -0,0 +1,10 @@
+(function (root, factory) {
+  if (typeof exports === 'object' && typeof module !== 'undefined') {
+    module.exports = factory();
+  } else {
+    root.prebuilt = factory();
+  }
+})(this, function () {
+  const sum = (a, b) => a + b;
+  return { sum };
+});
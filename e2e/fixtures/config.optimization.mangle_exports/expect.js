@@ -0,0 +1,8 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+assert(!content.includes(".longExportName"), "the export name should be mangled");
+assert(content.includes('"long"'), "the mangled export should be kept");
+assert(content.includes(".keptExportName"), "the reserved export name should be kept");
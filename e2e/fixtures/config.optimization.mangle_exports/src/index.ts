@@ -0,0 +1,3 @@
+import { longExportName, keptExportName } from "./utils";
+
+console.log(longExportName, keptExportName());
@@ -0,0 +1,4 @@
+export const longExportName = "long";
+export function keptExportName() {
+  return "kept";
+}
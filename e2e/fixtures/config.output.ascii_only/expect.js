@@ -0,0 +1,7 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+assert(!/[^\x00-\x7F]/.test(content), `should only contain ascii characters`);
+assert(/\\u4f60\\u597d/i.test(content), `should escape the non-ascii characters`);
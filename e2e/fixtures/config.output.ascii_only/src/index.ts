@@ -0,0 +1,2 @@
+const greeting = "你好";
+console.log(greeting, "café");
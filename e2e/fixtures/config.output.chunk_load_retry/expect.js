@@ -0,0 +1,36 @@
+const assert = require("assert");
+const fs = require("fs");
+const path = require("path");
+const { parseBuildResult, testWithBrowser } = require("../../../scripts/test-utils");
+const { files, distDir } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+
+assert(
+  content.includes(`var fallbackPublicPaths = ["/fallback/"];`),
+  "should inject the fallback public paths"
+);
+assert(content.includes("if (attempt >= 2) return false;"), "should retry 2 times");
+assert(content.includes("}, 100 * Math.pow(2, attempt));"), "should back off from 100ms");
+assert(content.includes("requireModule.onChunkError"), "should call the onChunkError hook");
+
+// the async chunk is only served from the fallback public path, so it's loaded by the retry
+const chunk = Object.keys(files).find((fileName) => fileName.endsWith("-async.js"));
+assert(chunk, "should generate the async chunk");
+fs.mkdirSync(path.join(distDir, "fallback"), { recursive: true });
+fs.renameSync(path.join(distDir, chunk), path.join(distDir, "fallback", chunk));
+
+const test = async () => {
+  try {
+    await testWithBrowser({
+      cwd: __dirname,
+      fn: async ({ page }) => {
+        await page.waitForSelector("#root[data-a=a]");
+      },
+      entry: "index.js",
+    });
+  } catch (e) {
+    throw new Error(e);
+  }
+};
+module.exports = test;
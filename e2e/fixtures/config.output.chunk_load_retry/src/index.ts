@@ -0,0 +1,3 @@
+import('./a').then((m) => {
+  document.getElementById('root')!.dataset.a = m.a;
+});
@@ -0,0 +1,8 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+assert(content.includes("keep-this-license"), `should keep the /*! comments`);
+assert(content.includes("keep-this-annotated-license"), `should keep the @license comments`);
+assert(!content.includes("drop-this-comment"), `should drop the other comments`);
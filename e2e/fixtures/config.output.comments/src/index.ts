@@ -0,0 +1,9 @@
+/*! keep-this-license v1.0.0 | MIT */
+/* drop-this-comment */
+/**
+ * @license keep-this-annotated-license
+ */
+export function hello() {
+  return "hello";
+}
+console.log(hello());
@@ -0,0 +1,12 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const names = Object.keys(files);
+
+assert(files["index.js.gz"], "should compress the chunk files with gzip");
+assert(files["index.js.br"], "should compress the chunk files with brotli");
+assert(
+  names.some((name) => /^logo\.(.*)\.png\.gz$/.test(name)),
+  "should compress the assets"
+);
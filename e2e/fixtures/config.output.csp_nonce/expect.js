@@ -0,0 +1,7 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+assert(files["foo.html"].includes(`<script src="/foo.js" nonce="{{nonce}}"></script>`), `should add the nonce to the scripts`);
+assert(files["foo.js"].includes(`script.nonce = requireModule.nonce()`), `should add the nonce to the scripts loaded by the runtime`);
+assert(files["foo.js"].includes(`global.__mako_nonce__`), `should read the nonce from __mako_nonce__`);
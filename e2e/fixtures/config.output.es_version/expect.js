@@ -0,0 +1,8 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+assert(!content.includes("?."), `should lower the optional chaining`);
+assert(!content.includes("??"), `should lower the nullish coalescing`);
+assert(!content.includes("async function main"), `should lower the async functions`);
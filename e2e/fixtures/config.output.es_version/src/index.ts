@@ -0,0 +1,6 @@
+const config = { a: { b: 1 } };
+console.log(config?.a?.b ?? 0);
+async function main() {
+  await Promise.resolve();
+}
+main();
@@ -0,0 +1,28 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+
+assert(content.includes("import(importUrl).then("), "should load async chunks by import()");
+assert(
+  !content.includes("requireModule.loadScript(url, onLoadEnd"),
+  "should not load async chunks by script tags"
+);
+assert(
+  content.includes("jsonpCallback(chunk.default)"),
+  "should install the modules exported by the async chunks"
+);
+assert(
+  content.includes("'retry=' + attempt"),
+  "should import the retried chunks with a query to skip the module cache"
+);
+assert(files["src_a_ts-async.js"], "should emit the async chunk");
+assert(
+  files["src_a_ts-async.js"].includes("export default ["),
+  "should export the modules from the async chunk"
+);
+assert(
+  !files["src_a_ts-async.js"].includes(".push(["),
+  "should not push the modules to the global"
+);
@@ -0,0 +1 @@
+import('./a').then((m) => console.log(m.a));
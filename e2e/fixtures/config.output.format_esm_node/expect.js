@@ -0,0 +1,28 @@
+const assert = require("assert");
+const fs = require("fs");
+const path = require("path");
+const { pathToFileURL } = require("url");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+
+assert(content.includes("import.meta.url"), "should preserve import.meta.url");
+assert(!content.includes("self.document"), "should not replace import.meta.url");
+assert(content.includes("createRequire"), "should require the built-in modules with createRequire");
+assert(
+  content.includes('import("./" + chunksIdToUrlMap[chunkId])'),
+  "should load async chunks by import()"
+);
+assert(!content.includes("module.exports"), "should not wrap the entry as cjs");
+
+module.exports = async () => {
+  const dist = path.join(__dirname, "dist");
+  fs.writeFileSync(path.join(dist, "package.json"), '{ "type": "module" }');
+  const entry = pathToFileURL(path.join(dist, "index.js")).href;
+  await import(entry);
+  const result = await globalThis.formatEsmNode;
+  assert.strictEqual(result.a, "a", "should load the async chunk");
+  assert.strictEqual(result.url, entry, "import.meta.url should be the url of the entry chunk");
+  assert(result.content.includes("createRequire"), "should read the entry chunk by import.meta.url");
+};
@@ -0,0 +1,8 @@
+import { readFileSync } from 'fs';
+import { fileURLToPath } from 'url';
+
+globalThis.formatEsmNode = import('./a').then((m) => ({
+  a: m.a,
+  url: import.meta.url,
+  content: readFileSync(fileURLToPath(import.meta.url), 'utf-8'),
+}));
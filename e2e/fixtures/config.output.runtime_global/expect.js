@@ -0,0 +1,22 @@
+const assert = require("assert");
+
+const { getRemote } = require("./dist/index");
+const app = globalThis.myApp;
+
+assert(app, "runtime api should be exposed as the runtime global");
+assert.strictEqual(getRemote(), undefined, "unknown modules should be undefined");
+
+assert(app.define("remote", { value: 1 }));
+assert.deepStrictEqual(getRemote(), { value: 1 }, "defined modules should be required by mako modules");
+assert(!app.define("remote", { value: 2 }), "defined modules should not be overwritten");
+
+assert(app.register("factory", function (module) {
+  module.exports = 2;
+}));
+assert(app.has("factory"));
+assert.strictEqual(app.require("factory"), 2, "registered factories should be executed");
+assert(!app.register("factory", function () {}), "executed modules should not be overwritten");
+
+assert.strictEqual(app.share("react", 1), 1);
+assert.strictEqual(app.share("react", 2), 1, "the first shared exports should win");
+assert.strictEqual(globalThis.__mako_share_scope__.react, 1);
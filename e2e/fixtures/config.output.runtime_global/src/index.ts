@@ -0,0 +1 @@
+export const getRemote = () => (globalThis as any).myApp.get("remote");
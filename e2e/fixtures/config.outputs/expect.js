@@ -0,0 +1,15 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+parseBuildResult(__dirname);
+
+assert.strictEqual(require("./dist").value, "platform:node", "main output should work");
+assert.strictEqual(
+  require("./dist/node").value,
+  "platform:node",
+  "extra output of the same platform should work",
+);
+assert.strictEqual(
+  require("./dist/umd").value,
+  "platform:browser",
+  "extra output of another platform should be resolved with its conditions",
+);
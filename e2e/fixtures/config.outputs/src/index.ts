@@ -0,0 +1,3 @@
+import { platform } from 'env';
+
+export const value = `platform:${platform}`;
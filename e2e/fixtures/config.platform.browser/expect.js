@@ -6,11 +6,11 @@ const content = files["index.js"];
 
 assert.match(
   content,
-  /console\.log\('filename', '\/index\.js'\)/,
+  /console\.log\('filename', "src\/index\.ts"\)/,
   "should transform __filename"
 )
 assert.match(
   content,
-  /console\.log\('dirname', '\/'\)/,
+  /console\.log\('dirname', "src"\)/,
   "should transform __dirname"
 )
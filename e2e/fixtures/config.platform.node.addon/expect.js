@@ -0,0 +1,24 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const addon = Object.keys(files).find((file) => /^addon\.\w+\.node$/.test(file));
+assert(addon, "should emit the node addon to the output dir");
+
+const content = files["index.js"];
+assert(
+  content.includes(`._requireNodeAddon("${addon}")`),
+  "should require the node addon from the output dir"
+);
+assert(
+  content.includes("requireModule._requireNodeAddon"),
+  "should inject the node addon runtime"
+);
+assert(
+  content.includes(`requireModule.outputRoot = "";`),
+  "should require the addons of the entry at the output dir from the same dir"
+);
+assert(
+  files["pages/home.js"].includes(`requireModule.outputRoot = "../";`),
+  "should require the addons of the nested entry from the parent dir"
+);
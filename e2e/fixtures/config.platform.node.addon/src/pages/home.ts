@@ -0,0 +1,3 @@
+const addon = require('../addon.node');
+
+console.log(addon);
@@ -0,0 +1,24 @@
+const assert = require("assert");
+const fs = require("fs");
+const path = require("path");
+const { pathToFileURL } = require("url");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+
+assert(
+  content.includes('require("url").pathToFileURL(__filename).href'),
+  "should emulate import.meta.url in the commonjs outputs"
+);
+assert(content.includes("createRequire(__filename)"), "should keep __filename of createRequire");
+assert(!content.includes("self.document"), "should not use the browser base url");
+
+module.exports = async () => {
+  const dist = path.join(__dirname, "dist");
+  fs.writeFileSync(path.join(dist, "package.json"), '{ "name": "create-require" }');
+  const result = require("./dist");
+  assert.strictEqual(result.url, pathToFileURL(path.join(dist, "index.js")).href);
+  assert.strictEqual(result.pkg.name, "create-require", "should require from the outputs at runtime");
+  assert(result.sameRequire, "should resolve from the same location");
+};
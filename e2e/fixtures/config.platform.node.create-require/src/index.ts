@@ -0,0 +1,8 @@
+import { createRequire } from 'module';
+
+const requireFromUrl = createRequire(import.meta.url);
+const requireFromFilename = createRequire(__filename);
+
+export const url = import.meta.url;
+export const pkg = requireFromUrl('./package.json');
+export const sameRequire = requireFromFilename.resolve('./package.json') === requireFromUrl.resolve('./package.json');
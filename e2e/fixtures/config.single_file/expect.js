@@ -0,0 +1,11 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const names = Object.keys(files).filter((name) => !name.endsWith(".map"));
+const content = files["index.js"];
+
+assert.deepStrictEqual(names, ["index.js"], "should only emit index.js");
+assert(content.includes("moduleToDom("), "css should be injected at runtime");
+assert(content.includes("data:image/png;base64,"), "assets should be inlined");
+assert(content.includes(`"src/lazy.ts"`), "async chunks should be inlined");
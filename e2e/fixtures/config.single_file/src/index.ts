@@ -0,0 +1,2 @@
+import './index.css';
+import('./lazy').then(({ lazy }) => console.log(lazy));
@@ -0,0 +1,11 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+const sourceMap = JSON.parse(files["index.js.map"]);
+
+const match = content.match(/\/\/# debugId=([0-9a-f-]{36})\n\/\/# sourceMappingURL=index\.js\.map/);
+assert(match, "should inject the debugId comment before sourceMappingURL");
+assert.strictEqual(sourceMap.debugId, match[1], "source map should have the same debugId");
+assert(!sourceMap.sourcesContent, "sourcesContent should be stripped");
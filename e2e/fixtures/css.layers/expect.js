@@ -0,0 +1,31 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.css"];
+
+assert(
+  content.startsWith("@layer reset, theme;"),
+  "the order of the layers should be declared at the top"
+);
+assert.strictEqual(
+  content.match(/@layer/g).length,
+  4,
+  "the layered imports should be wrapped in the layers"
+);
+assert(
+  /@layer theme \{\s*\.button \{/.test(content),
+  "the imports of the layered css should be nested in the layer"
+);
+assert(
+  /@layer theme \{\s*\.theme \{/.test(content),
+  "the layered css should be wrapped in the layer"
+);
+assert(
+  /@layer reset \{\s*\* \{/.test(content),
+  "the layered css should be wrapped in the layer"
+);
+assert(
+  !/@layer[^{]*\{\s*\.app/.test(content),
+  "the unlayered css should not be wrapped"
+);
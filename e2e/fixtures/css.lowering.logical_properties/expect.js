@@ -0,0 +1,8 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.css"];
+
+assert(content.includes("margin-inline: 8px 16px;"), "logical properties should be kept without logicalPropertiesDir");
+assert(content.includes("inset-inline-start: 0;"), "logical properties should be kept without logicalPropertiesDir");
@@ -0,0 +1,12 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.css"];
+
+assert(content.includes(".card .title"), "nesting should be lowered");
+assert(content.includes("margin-right: 8px;") && content.includes("margin-left: 16px;"), "logical properties should be lowered as rtl");
+assert(content.includes("right: 0;"), "inset-inline-start should be lowered as rtl");
+assert(content.includes("padding-inline-start: 4px;"), "logical longhands supported by chrome 80 should be kept");
+assert(content.includes("#660099"), "color-mix() should be computed");
+assert(!content.includes("color-mix("), "color-mix() should be removed");
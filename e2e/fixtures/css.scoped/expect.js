@@ -0,0 +1,14 @@
+const assert = require("assert");
+const { parseBuildResult, trim } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const css = trim(files["index.css"]);
+const js = files["index.js"];
+const scopeId = css.match(/\.btn:hover\[(data-s-[0-9a-f]{8})\]::before\{/)[1];
+
+assert(scopeId, "the selectors of the scoped css should be rewritten");
+assert(css.includes(".global{"), "the css imported without ?scoped should be kept");
+assert(
+  js.includes(`"${scopeId}"`),
+  "the scope attribute should be exported to the js"
+);
@@ -0,0 +1,4 @@
+import scope, { scopeId } from './button.css?scoped';
+import './global.css';
+
+console.log(scope, scopeId);
@@ -0,0 +1,16 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const sharedCss = Object.keys(files).filter(
+  (name) => name.startsWith("shared-css_") && name.endsWith(".css")
+);
+
+assert.strictEqual(sharedCss.length, 1, "should hoist the shared css into one chunk");
+assert(files[sharedCss[0]].includes(".shared"), "shared css chunk should contain the shared rules");
+assert(
+  !files["src_a_ts-async.css"].includes(".shared") &&
+    !files["src_b_ts-async.css"].includes(".shared"),
+  "shared css should not be duplicated in the async chunks"
+);
+assert(files["src_a_ts-async.css"].includes(".a"), "async chunk should keep its own css");
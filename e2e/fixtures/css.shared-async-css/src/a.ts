@@ -0,0 +1,3 @@
+import './shared.css';
+import './a.css';
+export const a = 'a';
@@ -0,0 +1,3 @@
+import './shared.css';
+import './b.css';
+export const b = 'b';
@@ -0,0 +1,2 @@
+import('./a').then((r) => console.log(r));
+import('./b').then((r) => console.log(r));
@@ -0,0 +1,15 @@
+const assert = require("assert");
+const { parseBuildResult, trim } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const css = trim(files["index.css"]);
+const js = files["index.js"];
+
+assert(css.includes(".global{"), "the css imported without ?sheet should be injected");
+assert(!css.includes(".btn{"), "the css imported with ?sheet should not be injected");
+assert(!css.includes(".base{"), "the @imports of the ?sheet css should not be injected");
+assert(js.includes("new CSSStyleSheet()"), "the css should be exported as a CSSStyleSheet");
+assert(js.includes(".btn"), "the css text should be in the js");
+assert(js.includes(".base"), "the css text of the @imports should be in the js");
+assert(!css.includes(".card{"), "the css imported with type css should not be injected");
+assert(js.includes("card.css?sheet"), "the css imported with type css should be imported as ?sheet");
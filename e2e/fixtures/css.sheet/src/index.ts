@@ -0,0 +1,5 @@
+import sheet, { cssText } from './button.css?sheet';
+import cardSheet from './card.css' with { type: 'css' };
+import './global.css';
+
+console.log(sheet, cssText, cardSheet);
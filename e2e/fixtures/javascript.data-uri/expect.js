@@ -0,0 +1,10 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+
+assert(content.includes("'data-uri-' + 42"), "should load the js data uri as a module");
+assert(content.includes("data-uri-json"), "should load the json data uri as a module");
+assert(!content.includes("data:text/javascript"), "should not keep the data uri imports");
+assert(files["index.css"].includes(".data-uri"), "should load the css data uri as a module");
@@ -0,0 +1,5 @@
+import answer from "data:text/javascript,export default 'data-uri-' + 42;";
+import config from 'data:application/json,{"name":"data-uri-json"}';
+import "data:text/css;base64,LmRhdGEtdXJpIHsgY29sb3I6IHJlZDsgfQ==";
+
+console.log(answer, config.name);
@@ -0,0 +1,10 @@
+const assert = require("assert");
+const { parseBuildResult, moduleReg } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+
+assert.match(content, moduleReg("src/data.txt\\?json", "import-attributes"), "should load .txt as json with type: 'json'");
+assert.match(content, moduleReg("src/config.json", "1.0.0"), "should load .json as json");
+assert.match(content, moduleReg("src/style.css\\?sheet", "new CSSStyleSheet\\(\\)"), "should export a CSSStyleSheet with type: 'css'");
+assert(!("index.css" in files), "css module scripts should not be emitted as css");
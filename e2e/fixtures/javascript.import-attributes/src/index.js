@@ -0,0 +1,6 @@
+import data from './data.txt' with { type: 'json' };
+import config from './config.json' with { type: 'json' };
+import sheet from './style.css' with { type: 'css' };
+
+console.log(data.name, config.version);
+document.adoptedStyleSheets = [sheet];
@@ -0,0 +1,14 @@
+const {
+  parseBuildResult,
+  injectSimpleJest,
+} = require("../../../scripts/test-utils");
+parseBuildResult(__dirname);
+
+injectSimpleJest();
+globalThis.extLib = {
+  __esModule: true,
+  default: 'ext default',
+  foo: 'ext foo',
+  bar: 'bar',
+};
+require("./dist/index.js");
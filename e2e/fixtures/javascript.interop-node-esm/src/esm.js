@@ -0,0 +1,3 @@
+import lib from './lib.js';
+
+export const value = lib;
@@ -0,0 +1,41 @@
+import lib, { foo } from './lib.js';
+import esmDefault, { named } from './esm-default.js';
+import { count, increment } from './counter.js';
+import { count as reexportedCount } from './reexport.mjs';
+import { value } from './esm.js';
+import extLib, { foo as extFoo } from 'ext-lib';
+import * as extNs from 'ext-lib';
+
+it('default import of cjs in .mjs should be module.exports', () => {
+  expect(lib.default).toBe('default');
+  expect(lib.foo).toBe('foo');
+  expect(foo).toBe('foo');
+});
+
+it('default import of esm in .mjs should be the default export', () => {
+  expect(esmDefault).toBe('esm default');
+  expect(named).toBe('named');
+});
+
+it('named imports and export * of cjs should be live bindings', () => {
+  expect(count).toBe(0);
+  expect(reexportedCount).toBe(0);
+  increment();
+  expect(count).toBe(1);
+  expect(reexportedCount).toBe(1);
+});
+
+it('default import of cjs with __esModule in .js should be exports.default', () => {
+  expect(value).toBe('default');
+});
+
+it('default import of commonjs externals in .mjs should be module.exports', () => {
+  expect(extLib.default).toBe('ext default');
+  expect(extFoo).toBe('ext foo');
+});
+
+it('namespace import of externals should be live bindings', () => {
+  expect(extNs.bar).toBe('bar');
+  globalThis.extLib.bar = 'changed';
+  expect(extNs.bar).toBe('changed');
+});
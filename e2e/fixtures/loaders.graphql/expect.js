@@ -0,0 +1,16 @@
+const assert = require("assert");
+const { parseBuildResult, moduleReg } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+assert(content.includes('"kind": "Document"'), "should load the document ast");
+assert(content.includes('"value": "GetUser"'), "should parse the operation");
+assert.match(
+  content,
+  moduleReg("src/user.graphql", '"value": "UserFields"', true),
+  "should load the imported fragment as a module",
+);
+assert(
+  content.includes('__mako_require__("src/user.graphql")'),
+  "should require the imported fragment",
+);
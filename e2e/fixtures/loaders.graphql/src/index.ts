@@ -0,0 +1,3 @@
+import query from "./query.gql";
+
+console.log(query);
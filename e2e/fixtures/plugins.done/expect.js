@@ -0,0 +1,9 @@
+const assert = require("assert");
+const fs = require("fs");
+const path = require("path");
+
+const done = JSON.parse(
+  fs.readFileSync(path.join(__dirname, "dist/done.json"), "utf-8")
+);
+assert(done.written, "should be called after the outputs are written");
+assert(done.endTime >= done.startTime, "should be called with the stats");
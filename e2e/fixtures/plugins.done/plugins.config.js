@@ -0,0 +1,17 @@
+const fs = require("fs");
+const path = require("path");
+
+module.exports = [
+  {
+    done(stats) {
+      fs.writeFileSync(
+        path.join(__dirname, "dist/done.json"),
+        JSON.stringify({
+          written: fs.existsSync(path.join(__dirname, "dist/index.js")),
+          startTime: stats.startTime,
+          endTime: stats.endTime,
+        }),
+      );
+    },
+  },
+];
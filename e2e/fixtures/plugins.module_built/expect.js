@@ -0,0 +1,21 @@
+const assert = require("assert");
+const fs = require("fs");
+const path = require("path");
+
+const modules = JSON.parse(
+  fs.readFileSync(path.join(__dirname, "dist/telemetry.json"), "utf-8")
+);
+const find = (name) => modules.find((module) => module.path.endsWith(name));
+
+const index = find("src/index.ts");
+assert(index, "should report the entry");
+assert(index.dependencies >= 2, "should report the dependency count");
+assert(index.sourceSize > 0 && index.size > 0, "should report the sizes");
+for (const phase of ["load", "parse", "transform", "analyzeDeps"]) {
+  assert(index.durations[phase] >= 0, `should report the ${phase} duration`);
+}
+
+const math = find("src/math.ts");
+assert(math, "should report the imported modules");
+assert.strictEqual(math.dependencies, 0);
+assert(find("src/style.css"), "should report the css modules");
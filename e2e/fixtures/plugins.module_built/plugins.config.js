@@ -0,0 +1,18 @@
+const fs = require("fs");
+const path = require("path");
+
+const modules = [];
+
+module.exports = [
+  {
+    moduleBuilt(module) {
+      modules.push(module);
+    },
+    generateEnd() {
+      fs.writeFileSync(
+        path.join(__dirname, "dist/telemetry.json"),
+        JSON.stringify(modules, null, 2),
+      );
+    },
+  },
+];
@@ -0,0 +1,4 @@
+import { add } from "./math";
+import "./style.css";
+
+console.log(add(1, 2));
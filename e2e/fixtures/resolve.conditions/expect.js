@@ -0,0 +1,12 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+
+assert(content.includes("foo:worker"), "should resolve the exports with the custom conditions");
+assert(!content.includes("foo:index"), "should not resolve the default exports");
+assert(content.includes("variant:worker"), "should keep the code of the active conditions");
+assert(!content.includes("variant:development"), "should strip the #elif branch");
+assert(!content.includes("variant:default"), "should strip the #else branch");
+assert(!content.includes("variant:not-worker"), "should strip the negated conditions");
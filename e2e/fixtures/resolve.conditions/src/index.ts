@@ -0,0 +1,15 @@
+import foo from 'foo';
+
+// #if worker
+console.log('variant:worker');
+// #elif development
+console.log('variant:development');
+// #else
+console.log('variant:default');
+// #endif
+
+/* #if !worker */
+console.log('variant:not-worker');
+/* #endif */
+
+console.log(foo);
@@ -0,0 +1,56 @@
+const assert = require("assert");
+const fs = require("fs");
+const path = require("path");
+const { parseBuildResult, testWithBrowser } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const manifest = JSON.parse(
+  fs.readFileSync(path.join(__dirname, "dist/react-client-manifest.json"), "utf-8")
+);
+
+assert.deepStrictEqual(
+  Object.keys(manifest),
+  ["src/components/Button.tsx", "src/components/Counter.tsx"],
+  "should only reference the client components"
+);
+for (const reference of Object.values(manifest)) {
+  assert.strictEqual(reference.name, "*");
+  assert(reference.chunks.length >= 4, "should list the shared chunks of the client component");
+  const file = reference.chunks[reference.chunks.length - 1];
+  assert(files[file], `should emit the chunk ${file}`);
+  assert(files[file].includes(reference.id), "the chunk should contain the client component");
+}
+assert(!files["index.js"].includes("function Button"), "should split the client components");
+assert(
+  !Object.values(files).some((content) => content.includes("server:shared")),
+  "should not build the modules without the directive"
+);
+
+// load the chunks listed in the manifest like the rsc client and render the components
+const test = async () => {
+  try {
+    await testWithBrowser({
+      cwd: __dirname,
+      fn: async ({ page }) => {
+        const rendered = {};
+        for (const [key, reference] of Object.entries(manifest)) {
+          for (let i = 1; i < reference.chunks.length; i += 2) {
+            await page.addScriptTag({ url: reference.chunks[i] });
+          }
+          rendered[key] = await page.evaluate((id) => {
+            const exports = window.rscClient.require(id);
+            return (exports.default || exports.Counter)();
+          }, reference.id);
+        }
+        assert.deepStrictEqual(rendered, {
+          "src/components/Button.tsx": "client:button",
+          "src/components/Counter.tsx": "client:counter",
+        });
+      },
+      entry: "index.js",
+    });
+  } catch (e) {
+    throw new Error(e);
+  }
+};
+module.exports = test;
@@ -0,0 +1,6 @@
+'use client';
+import { prefix } from './utils';
+
+export default function Button() {
+  return `${prefix}button`;
+}
@@ -0,0 +1,6 @@
+'use client';
+import { prefix } from './utils';
+
+export function Counter() {
+  return `${prefix}counter`;
+}
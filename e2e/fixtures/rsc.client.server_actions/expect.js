@@ -0,0 +1,23 @@
+const assert = require("assert");
+const fs = require("fs");
+const path = require("path");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+assert(!content.includes("server:"), "should strip the bodies of the server actions");
+assert(content.includes("createServerReference"), "should replace the actions with the stubs");
+
+const manifest = JSON.parse(
+  fs.readFileSync(path.join(__dirname, "dist/server-actions-manifest.json"), "utf-8")
+);
+const actions = Object.values(manifest);
+assert.deepStrictEqual(
+  actions.map((action) => `${action.path}:${action.name}`).sort(),
+  ["src/Form.tsx:submit", "src/actions.ts:createTodo", "src/actions.ts:deleteTodo"],
+  "should register the server actions"
+);
+for (const [id, action] of Object.entries(manifest)) {
+  assert.strictEqual(id, `${action.moduleId}#${action.name}`);
+  assert(content.includes(JSON.stringify(id)), `should reference the action ${id}`);
+}
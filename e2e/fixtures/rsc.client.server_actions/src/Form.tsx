@@ -0,0 +1,9 @@
+"use client";
+
+export function Form() {
+  async function submit(data: FormData) {
+    "use server";
+    console.log("server:submit", data);
+  }
+  return submit;
+}
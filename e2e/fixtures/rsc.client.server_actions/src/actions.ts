@@ -0,0 +1,11 @@
+"use server";
+
+import { db } from "./db";
+
+export async function createTodo(title: string) {
+  return db.insert("server:create", title);
+}
+
+export const deleteTodo = async (id: string) => {
+  return db.delete("server:delete", id);
+};
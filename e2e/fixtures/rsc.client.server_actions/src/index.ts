@@ -0,0 +1,4 @@
+import { createTodo, deleteTodo } from "./actions";
+import { Form } from "./Form";
+
+console.log(createTodo, deleteTodo, Form);
@@ -0,0 +1,3 @@
+export function createServerReference(id: string) {
+  return async (...args: unknown[]) => ({ id, args });
+}
@@ -0,0 +1,40 @@
+const assert = require("assert");
+const { parseBuildResult, testWithBrowser } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const js = files["index.js"];
+
+assert(
+  js.includes("requireModule.resolveCssUrls = function"),
+  "the relative urls of the injected css should be resolved"
+);
+assert(
+  js.includes("window.__INJECTED_PUBLIC_PATH_BY_QIANKUN__"),
+  "the public path injected by the container should be used"
+);
+
+const test = async () => {
+  try {
+    await testWithBrowser({
+      cwd: __dirname,
+      fn: async ({ page }) => {
+        const result = await page.evaluate(() => {
+          const shadowRoot = document.getElementById("host").shadowRoot;
+          return {
+            inside: getComputedStyle(shadowRoot.getElementById("inside")).color,
+            outside: getComputedStyle(document.getElementById("outside")).color,
+            shadowStyles: shadowRoot.querySelectorAll("style").length,
+            documentStyles: document.querySelectorAll("style").length,
+          };
+        });
+        assert.equal(result.inside, "rgb(255, 0, 0)", "the styles should apply in the shadow root");
+        assert.notEqual(result.outside, "rgb(255, 0, 0)", "the styles should not leak into the document");
+        assert.equal(result.shadowStyles, 1, "the styles should be injected into the shadow root");
+        assert.equal(result.documentStyles, 0, "the styles should not be injected into the document");
+      },
+    });
+  } catch (e) {
+    throw new Error(e);
+  }
+};
+module.exports = test;
@@ -0,0 +1,58 @@
+const assert = require("assert");
+const { testWithBrowser } = require("../../../scripts/test-utils");
+
+const RED = "rgb(255, 0, 0)";
+const BLUE = "rgb(0, 0, 255)";
+
+const colorOf = (page, selector) =>
+  page.evaluate(
+    (selector) =>
+      getComputedStyle(
+        document.querySelector("#host").shadowRoot.querySelector(selector),
+      ).color,
+    selector,
+  );
+
+const test = async () => {
+  try {
+    await testWithBrowser({
+      cwd: __dirname,
+      fn: async ({ page }) => {
+        await page.waitForSelector("#app[data-loaded=true]");
+        await page.waitForFunction(
+          (color) =>
+            getComputedStyle(
+              document.querySelector("#host").shadowRoot.querySelector(".entry"),
+            ).color === color,
+          BLUE,
+        );
+        assert.equal(await colorOf(page, ".lazy"), RED, "the css chunk should be adopted by the shadow root");
+        const links = await page.locator("link[rel=stylesheet]").count();
+        assert.equal(links, 0, "the css chunks should not be inserted as tags");
+        const documentSheets = await page.evaluate(() => document.adoptedStyleSheets.length);
+        assert.equal(documentSheets, 0, "the styles should only be adopted by the shadow root");
+
+        // the stylesheets are replaced in place when they're reloaded
+        const reloaded = await page.evaluate(async () => {
+          const requireModule = window.__mako_require_module__;
+          const count = requireModule.getStyleSheets().length;
+          await Promise.all(
+            Object.keys(requireModule.cssInstalled).map((chunkId) =>
+              requireModule.reloadStyleSheet(chunkId),
+            ),
+          );
+          return [count, requireModule.getStyleSheets().length];
+        });
+        assert.equal(reloaded[0], 2, "the entry and the lazy css should be stylesheets");
+        assert.equal(reloaded[1], reloaded[0], "the reloaded stylesheets should be reused");
+        assert.equal(await colorOf(page, ".lazy"), RED, "the reloaded styles should be kept");
+        assert.equal(await colorOf(page, ".entry"), BLUE, "the reloaded styles should be kept");
+      },
+      rootElement: "app",
+      entry: "index.js",
+    });
+  } catch (e) {
+    throw new Error(e);
+  }
+};
+module.exports = test;
@@ -0,0 +1,16 @@
+import './index.css';
+
+declare const __mako_require__: any;
+
+const app = document.getElementById('app')!;
+const host = document.createElement('div');
+host.id = 'host';
+host.attachShadow({ mode: 'open' }).innerHTML =
+  '<span class="entry">entry</span><span class="lazy">lazy</span>';
+app.appendChild(host);
+// the linked css of the entry is loaded as a stylesheet once the styles are adopted
+__mako_require__.adoptStyles(host.shadowRoot);
+
+import('./lazy').then(() => {
+  app.dataset.loaded = 'true';
+});
@@ -0,0 +1,22 @@
+const assert = require("assert");
+const { testWithBrowser } = require("../../../scripts/test-utils");
+
+const test = async () => {
+  try {
+    await testWithBrowser({
+      cwd: __dirname,
+      fn: async ({ page }) => {
+        await page.waitForSelector("#app[data-loaded=true]");
+        const appLinks = await page.locator("#app link[rel=stylesheet]").count();
+        assert.equal(appLinks, 1, "the css chunk should be inserted into the configured element");
+        const headLinks = await page.locator("head link[rel=stylesheet]").count();
+        assert.equal(headLinks, 0, "the css chunk should not be inserted into the head");
+      },
+      rootElement: "app",
+      entry: "index.js",
+    });
+  } catch (e) {
+    throw new Error(e);
+  }
+};
+module.exports = test;
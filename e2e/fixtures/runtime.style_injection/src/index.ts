@@ -0,0 +1,3 @@
+import('./lazy').then(() => {
+  document.getElementById('app')!.dataset.loaded = 'true';
+});
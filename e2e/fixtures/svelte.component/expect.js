@@ -0,0 +1,51 @@
+const assert = require("assert");
+const {
+  parseBuildResult,
+  testWithBrowser,
+} = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const css = Object.keys(files)
+  .filter((file) => file.endsWith(".css"))
+  .map((file) => files[file])
+  .join("\n");
+assert.match(
+  css,
+  /\.title\.svelte-[a-z0-9]+/,
+  "the style should be scoped with the svelte class"
+);
+
+const test = async () => {
+  await testWithBrowser({
+    cwd: __dirname,
+    fn: async ({ page }) => {
+      const title = page.locator("#root h1");
+      await title.waitFor();
+      assert.equal(
+        await title.innerText(),
+        "hello 1",
+        "the component should be rendered with the props"
+      );
+      await title.click();
+      assert.equal(
+        await title.innerText(),
+        "hello 2",
+        "the state of the component should be reactive"
+      );
+      const color = (selector) =>
+        page.locator(selector).evaluate((el) => getComputedStyle(el).color);
+      assert.equal(
+        await color("#root h1"),
+        "rgb(255, 0, 0)",
+        "the style should be applied to the component"
+      );
+      assert.notEqual(
+        await color("#outside"),
+        "rgb(255, 0, 0)",
+        "the style should not be applied outside the component"
+      );
+    },
+    entry: "index.js",
+  });
+};
+module.exports = test;
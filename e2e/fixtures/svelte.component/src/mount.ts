@@ -0,0 +1,10 @@
+import App from './App.svelte';
+
+document.body.insertAdjacentHTML(
+  'beforeend',
+  '<p class="title" id="outside">outside</p>',
+);
+new App({
+  target: document.getElementById('root'),
+  props: { name: 'hello' },
+});
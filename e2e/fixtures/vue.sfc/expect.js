@@ -0,0 +1,51 @@
+const assert = require("assert");
+const {
+  parseBuildResult,
+  testWithBrowser,
+} = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const css = Object.keys(files)
+  .filter((file) => file.endsWith(".css"))
+  .map((file) => files[file])
+  .join("\n");
+assert.match(
+  css,
+  /\.title\[data-v-[0-9a-f]{8}\]/,
+  "scoped style should be scoped with the data-v attribute"
+);
+
+const test = async () => {
+  await testWithBrowser({
+    cwd: __dirname,
+    fn: async ({ page }) => {
+      const title = page.locator("#root h1");
+      await title.waitFor();
+      assert.equal(
+        await title.innerText(),
+        "hello 1",
+        "template and script setup should be rendered"
+      );
+      await title.click();
+      assert.equal(
+        await title.innerText(),
+        "hello 2",
+        "the refs of script setup should be reactive"
+      );
+      const color = (selector) =>
+        page.locator(selector).evaluate((el) => getComputedStyle(el).color);
+      assert.equal(
+        await color("#root h1"),
+        "rgb(255, 0, 0)",
+        "scoped style should be applied to the component"
+      );
+      assert.notEqual(
+        await color("#outside"),
+        "rgb(255, 0, 0)",
+        "scoped style should not be applied outside the component"
+      );
+    },
+    entry: "index.js",
+  });
+};
+module.exports = test;
@@ -0,0 +1,8 @@
+import { createApp } from 'vue';
+import App from './App.vue';
+
+document.body.insertAdjacentHTML(
+  'beforeend',
+  '<p class="title" id="outside">outside</p>',
+);
+createApp(App, { msg: 'hello' }).mount('#root');
@@ -0,0 +1,12 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const main = files["index.js"];
+const worker = Object.entries(files).find(([fileName]) => fileName.startsWith("src_worker_ts"))[1];
+
+assert(main.includes("requireModule.chunkEnsures.css ="), "the main runtime should load the css chunks");
+assert(main.includes("requireModule.loadScript(url, onLoadEnd"), "the main runtime should load the chunks by script tags");
+assert(worker.includes("importScripts(url);"), "the worker runtime should load the chunks by importScripts");
+assert(!worker.includes("requireModule.chunkEnsures.css ="), "the worker runtime should not load the css chunks");
+assert(!worker.includes("chunk hints"), "the worker runtime should not hint the chunks");
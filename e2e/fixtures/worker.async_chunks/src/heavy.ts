@@ -0,0 +1,3 @@
+export function compute() {
+  return 42;
+}
@@ -0,0 +1,4 @@
+import("./page").then(({ render }) => render());
+
+const worker = new Worker(new URL("./worker.ts", import.meta.url));
+worker.postMessage({ command: "start" });
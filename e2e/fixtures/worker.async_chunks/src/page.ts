@@ -0,0 +1,5 @@
+import "./page.css";
+
+export function render() {
+  document.body.innerHTML = "page";
+}
@@ -0,0 +1,3 @@
+self.addEventListener("message", () => {
+  import("./heavy").then(({ compute }) => self.postMessage(compute()));
+});
@@ -0,0 +1,17 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const index = Object.keys(files).find((fileName) => /^index\.[\w-]+\.js$/.test(fileName));
+assert(index, "should hash the entry");
+assert(files["sw.js"], "should emit the service worker as sw.js without the hash");
+assert(
+  files[index].includes(`navigator.serviceWorker.register(new URL("sw.js"`),
+  "should register the service worker by its stable url",
+);
+assert(files["sw.js"].includes("sw-cache-v1"), "should bundle the dynamic imports into the service worker");
+assert.deepEqual(
+  Object.keys(files).filter((fileName) => fileName.endsWith(".js")).sort(),
+  [index, "sw.js"].sort(),
+  "should not split the service worker",
+);
@@ -0,0 +1 @@
+export const CACHE_NAME = "sw-cache-v1";
@@ -0,0 +1 @@
+navigator.serviceWorker.register(new URL("./sw.ts", import.meta.url));
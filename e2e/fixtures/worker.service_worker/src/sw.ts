@@ -0,0 +1,3 @@
+self.addEventListener("activate", () => {
+  import("./cache").then(({ CACHE_NAME }) => console.log(CACHE_NAME));
+});
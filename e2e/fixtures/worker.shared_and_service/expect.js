@@ -0,0 +1,14 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+const content = files["index.js"];
+
+const shared = Object.keys(files).find((fileName) => fileName.startsWith("src_shared_ts"));
+const sw = "sw.js";
+assert(shared, "should generate worker file for the shared worker");
+assert(files[sw], "should emit the service worker as sw.js");
+assert(content.includes(`new SharedWorker(new URL("${shared}"`), "should replace the url of the shared worker");
+assert(
+  content.includes(`navigator.serviceWorker.register(new URL("${sw}"`),
+  "should replace the url of the service worker",
+);
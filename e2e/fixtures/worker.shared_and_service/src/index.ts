@@ -0,0 +1,4 @@
+const shared = new SharedWorker(new URL("./shared.ts", import.meta.url));
+shared.port.postMessage("start");
+
+navigator.serviceWorker.register(new URL("./sw.ts", import.meta.url));
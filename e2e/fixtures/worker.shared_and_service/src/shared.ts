@@ -0,0 +1,3 @@
+self.addEventListener("connect", (event: any) => {
+  event.ports[0].postMessage("connected");
+});
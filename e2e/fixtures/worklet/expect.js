@@ -0,0 +1,46 @@
+const assert = require("assert");
+const { parseBuildResult, testWithBrowser } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+const content = files["index.js"];
+
+const processor = Object.keys(files).find((fileName) => fileName.startsWith("src_processor_ts"));
+const painter = Object.keys(files).find((fileName) => fileName.startsWith("src_painter_ts"));
+const gain = Object.keys(files).find((fileName) => fileName.endsWith("-worklet.js"));
+assert(processor, "should generate worklet file for the audio worklet");
+assert(painter, "should generate worklet file for the paint worklet");
+assert(gain, "should emit the dependencies of the worklet next to it");
+assert(
+  content.includes(`audioWorklet.addModule(new URL("${processor}"`),
+  "should replace the url of the audio worklet",
+);
+assert(
+  content.includes(`paintWorklet.addModule(new URL("${painter}"`),
+  "should replace the url of the paint worklet",
+);
+assert(
+  files[processor].includes(`from "./${gain}"`),
+  "should import the dependencies of the worklet as the es modules",
+);
+assert(files[gain].includes("export function gain"), "should emit the worklet modules as the es modules");
+assert(
+  !files[processor].includes("__mako_require__") && !files[processor].includes("requireModule"),
+  "should not include the module runtime in the worklet",
+);
+assert(content.includes("requireModule.ensure"), "should include the chunk loading runtime in the entry");
+
+const test = async () => {
+  try {
+    await testWithBrowser({
+      cwd: __dirname,
+      fn: async ({ page }) => {
+        await page.waitForSelector("#root[data-sample]");
+        const sample = await page.locator("#root").getAttribute("data-sample");
+        assert.equal(sample, "0.5", "the audio worklet should process the samples with its dependencies");
+      },
+      entry: "index.js",
+    });
+  } catch (e) {
+    throw new Error(e);
+  }
+};
+module.exports = test;
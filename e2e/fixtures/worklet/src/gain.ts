@@ -0,0 +1,3 @@
+export function gain(value: number) {
+  return value * 0.5;
+}
@@ -0,0 +1,17 @@
+const context = new OfflineAudioContext(1, 128, 44100);
+context.audioWorklet
+  .addModule(new URL("./processor.ts", import.meta.url))
+  .then(() => {
+    const source = context.createConstantSource();
+    const node = new AudioWorkletNode(context, "gain-processor");
+    source.connect(node).connect(context.destination);
+    source.start();
+    return context.startRendering();
+  })
+  .then((buffer) => {
+    document.getElementById("root")!.dataset.sample = String(buffer.getChannelData(0)[0]);
+  });
+
+(CSS as any).paintWorklet.addModule(new URL("./painter.ts", import.meta.url));
+
+import("./lazy").then(({ lazy }) => lazy());
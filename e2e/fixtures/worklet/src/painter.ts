@@ -0,0 +1,8 @@
+registerPaint(
+  "checkerboard",
+  class {
+    paint(ctx: any) {
+      ctx.fillRect(0, 0, 10, 10);
+    }
+  },
+);
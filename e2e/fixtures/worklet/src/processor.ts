@@ -0,0 +1,13 @@
+import { gain } from "./gain";
+
+class GainProcessor extends AudioWorkletProcessor {
+  process(inputs: Float32Array[][], outputs: Float32Array[][]) {
+    const input = inputs[0][0];
+    if (input) {
+      outputs[0][0].set(input.map(gain));
+    }
+    return true;
+  }
+}
+
+registerProcessor("gain-processor", GainProcessor);
@@ -3,12 +3,34 @@
 
 /* auto-generated by NAPI-RS */
 
+/**
+ * A pattern of the include and exclude filters of plugins, either a glob of the strings or the
+ * source of a RegExp
+ */
+export interface JsFilterPattern {
+  glob?: string;
+  regex?: string;
+}
 export interface JsHooks {
   name?: string;
-  enforce?: string;
+  enforce?: 'pre' | 'post';
+  include?: Array<JsFilterPattern>;
+  exclude?: Array<JsFilterPattern>;
+  /**
+   * The files read by the whole build, e.g. the tailwind config, all the modules are rebuilt
+   * when they change in watch mode
+   */
+  buildDependencies?: string[];
   load?: (
     filePath: string,
-  ) => Promise<{ content: string; type: 'css' | 'js' } | void> | void;
+  ) => Promise<
+    {
+      content: string;
+      type: 'css' | 'js';
+      dependencies?: string[];
+      contextDependencies?: string[];
+    } | void
+  > | void;
   loadInclude?: (filePath: string) => Promise<bool> | bool;
   generateEnd?: (data: {
     isFirstCompile: boolean;
@@ -52,6 +74,30 @@ export interface JsHooks {
     };
   }) => void;
   writeBundle?: () => Promise<void>;
+  sourceMapsEmitted?: (
+    sourceMaps: { chunk: string; sourceMap: string; debugId?: string }[],
+  ) => Promise<void>;
+  rebuildEnd?: (delta: {
+    changedFiles: string[];
+    addedModules: string[];
+    removedModules: string[];
+    modifiedModules: string[];
+    updatedChunks: string[];
+    time: number;
+    errors: string[];
+  }) => Promise<void> | void;
+  moduleBuilt?: (module: {
+    path: string;
+    sourceSize: number;
+    size: number;
+    durations: {
+      load: number;
+      parse: number;
+      transform: number;
+      analyzeDeps: number;
+    };
+    dependencies: number;
+  }) => Promise<void> | void;
   watchChanges?: (
     id: string,
     change: { event: 'create' | 'delete' | 'update' },
@@ -67,7 +113,14 @@ export interface JsHooks {
   transform?: (
     content: { content: string; type: 'css' | 'js' },
     path: string,
-  ) => Promise<{ content: string; type: 'css' | 'js' } | void> | void;
+  ) => Promise<
+    {
+      content: string;
+      type: 'css' | 'js';
+      dependencies?: string[];
+      contextDependencies?: string[];
+    } | void
+  > | void;
   transformInclude?: (filePath: string) => Promise<bool> | bool;
 }
 export interface WriteFile {
@@ -77,6 +130,10 @@ export interface WriteFile {
 export interface LoadResult {
   content: string;
   type: string;
+  /** the files read by the loader, e.g. the sass partials */
+  dependencies?: string[];
+  /** the dirs scanned by the loader, e.g. the dirs of the glob imports */
+  contextDependencies?: string[];
 }
 export interface WatchChangesParams {
   event: string;
@@ -91,6 +148,8 @@ export interface ResolveIdParams {
 export interface TransformResult {
   content: string;
   type: string;
+  dependencies?: string[];
+  contextDependencies?: string[];
 }
 export interface BuildParams {
   root: string;
@@ -265,3 +324,22 @@ export interface BuildParams {
   watch: boolean;
 }
 export declare function build(buildParams: BuildParams): Promise<void>;
+export interface DevMiddlewareResponse {
+  status: number;
+  headers: Record<string, string>;
+  body: Buffer;
+}
+export declare function createDevMiddleware(
+  buildParams: BuildParams,
+): Promise<DevMiddleware>;
+/** The dev server without listening on a port, for the frameworks with their own servers */
+export declare class DevMiddleware {
+  /** The local port which the hmr websocket connections should be proxied to */
+  get hmrPort(): number;
+  /** Resolves null if the request is not served by mako */
+  handle(
+    method: string,
+    url: string,
+    headers: Record<string, string>,
+  ): Promise<DevMiddlewareResponse | null>;
+}
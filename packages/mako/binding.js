@@ -304,6 +304,8 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`);
 }
 
-const { build } = nativeBinding;
+const { build, DevMiddleware, createDevMiddleware } = nativeBinding;
 
 module.exports.build = build;
+module.exports.DevMiddleware = DevMiddleware;
+module.exports.createDevMiddleware = createDevMiddleware;
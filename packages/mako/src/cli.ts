@@ -25,6 +25,8 @@ import { check } from './checker';
         root,
         config: {
           mode: argv.mode || 'development',
+          // --open or --open /path
+          ...(watch && argv.open ? { devServer: { open: argv.open } } : {}),
         },
         plugins: [],
         watch,
@@ -69,9 +71,11 @@ function showBuildHelp() {
   console.log(`  --help,-h`);
   console.log(`  --root`);
   console.log(`  --watch,-w`);
+  console.log(`  --open [path]`);
   console.log(``);
   console.log(`Examples:`);
   console.log(`  mako build`);
   console.log(`  mako build --watch`);
   console.log(`  mako build --root ./src`);
+  console.log(`  mako build --watch --open`);
 }
@@ -4,15 +4,25 @@ import { omit } from 'lodash';
 import resolve from 'resolve';
 import { type Options } from 'sass';
 import * as binding from '../binding';
-import { ForkTSChecker as ForkTSChecker } from './forkTSChecker';
 import { LessLoaderOpts, lessLoader } from './lessLoader';
 import { sassLoader } from './sassLoader';
+import { svelteLoader } from './svelteLoader';
+import { vueLoader } from './vueLoader';
+
+type PluginFilter = string | RegExp | (string | RegExp)[];
+
+type Plugin = Omit<binding.JsHooks, 'include' | 'exclude'> & {
+  include?: PluginFilter;
+  exclude?: PluginFilter;
+};
 
 type Config = binding.BuildParams['config'] & {
-  plugins?: binding.BuildParams['plugins'];
+  plugins?: (Plugin | string)[];
   less?: LessLoaderOpts;
   sass?: Options<'async'> & { resources: string[] };
   forkTSChecker?: boolean;
+  vue?: boolean;
+  svelte?: boolean | { compilerOptions?: Record<string, any> };
 };
 
 type BuildParams = {
@@ -23,6 +33,25 @@ type BuildParams = {
 
 export { BuildParams };
 
+// the strings are globs and the RegExps are regexes, the others are rejected
+function normalizePluginFilter(filter?: PluginFilter) {
+  if (!filter) {
+    return undefined;
+  }
+  return (Array.isArray(filter) ? filter : [filter]).map((pattern) => {
+    if (pattern instanceof RegExp) {
+      let flags = pattern.flags.includes('i') ? '(?i)' : '';
+      return { regex: `${flags}${pattern.source}` };
+    }
+    if (typeof pattern === 'string') {
+      return { glob: pattern };
+    }
+    throw new Error(
+      `Invalid plugin filter: ${pattern}, only strings and RegExps are supported.`,
+    );
+  });
+}
+
 // ref:
 // https://github.com/vercel/next.js/pull/51883
 function blockStdout() {
@@ -37,9 +66,9 @@ function blockStdout() {
   }
 }
 
-export async function build(params: BuildParams) {
-  blockStdout();
-
+async function getBindingParams(
+  params: BuildParams,
+): Promise<binding.BuildParams> {
   params.config.plugins = params.config.plugins || [];
   params.config.resolve = params.config.resolve || {};
 
@@ -134,6 +163,52 @@ export async function build(params: BuildParams) {
       },
     });
   }
+  if (makoConfig?.vue || params.config?.vue) {
+    let vue = vueLoader({
+      root: params.root,
+      isProduction:
+        (params.config.mode || makoConfig.mode) === 'production',
+    });
+    params.config.plugins.push({
+      name: 'vue',
+      async load(filePath: string) {
+        let vueResult = await vue.render(filePath);
+        if (vueResult) {
+          return vueResult;
+        }
+      },
+    });
+    // feature flags of the esm-bundler build of vue
+    params.config.define = {
+      __VUE_OPTIONS_API__: 'true',
+      __VUE_PROD_DEVTOOLS__: 'false',
+      __VUE_PROD_HYDRATION_MISMATCH_DETAILS__: 'false',
+      ...(makoConfig.define || {}),
+      ...(params.config.define || {}),
+    };
+  }
+  if (makoConfig?.svelte || params.config?.svelte) {
+    const svelteOpts = {
+      ...(typeof makoConfig.svelte === 'object' ? makoConfig.svelte : {}),
+      ...(typeof params.config.svelte === 'object' ? params.config.svelte : {}),
+    };
+    let svelte = svelteLoader({
+      root: params.root,
+      isProduction:
+        (params.config.mode || makoConfig.mode) === 'production',
+      hot: params.watch && (params.config.hmr ?? makoConfig.hmr) !== false,
+      compilerOptions: svelteOpts.compilerOptions,
+    });
+    params.config.plugins.push({
+      name: 'svelte',
+      async load(filePath: string) {
+        let svelteResult = await svelte.render(filePath);
+        if (svelteResult) {
+          return svelteResult;
+        }
+      },
+    });
+  }
   // support dump mako config
   if (process.env.DUMP_MAKO_CONFIG) {
     const configFile = path.join(params.root, 'mako.config.json');
@@ -157,7 +232,7 @@ export async function build(params: BuildParams) {
     });
   }
 
-  let plugins = params.config.plugins;
+  let plugins: any[] = params.config.plugins;
   plugins = plugins.map((plugin: any) => {
     if (typeof plugin === 'string') {
       let fn = require(
@@ -184,22 +259,96 @@ export async function build(params: BuildParams) {
       );
     }
   });
+  // the plugins are inherited instead of spread to keep the hooks of their prototypes,
+  // e.g. the plugins written as classes
+  plugins = plugins.map((plugin: any) =>
+    Object.assign(Object.create(plugin), {
+      include: normalizePluginFilter(plugin.include),
+      exclude: normalizePluginFilter(plugin.exclude),
+    }),
+  );
+  // forkTSChecker is kept as an alias of the type checker run by mako
+  if (params.config.forkTSChecker && !params.config.typeCheck) {
+    params.config.typeCheck = {};
+  }
   params.config = omit(params.config, [
     'less',
     'sass',
     'forkTSChecker',
     'plugins',
+    'vue',
+    'svelte',
   ]) as BuildParams['config'];
-  await binding.build({
+  return {
     ...params,
     plugins,
-  });
+  };
+}
 
-  if (params.config.forkTSChecker) {
-    let forkTypeChecker = new ForkTSChecker({
-      root: params.root,
-      watch: params.watch,
+export async function build(params: BuildParams) {
+  blockStdout();
+
+  await binding.build(await getBindingParams(params));
+}
+
+type DevMiddleware = {
+  (req: any, res: any, next: (err?: any) => void): void;
+  upgrade: (req: any, socket: any, head: Buffer) => void;
+};
+
+// serve the outputs and the hmr websocket of mako in the servers of frameworks,
+// the upgrade handler should be bound to the `upgrade` event of the http server
+export async function createDevMiddleware(
+  params: Omit<BuildParams, 'watch'>,
+): Promise<DevMiddleware> {
+  blockStdout();
+
+  const devMiddleware = await binding.createDevMiddleware(
+    await getBindingParams({ ...params, watch: true }),
+  );
+  const middleware = ((req: any, res: any, next: (err?: any) => void) => {
+    if (req.method !== 'GET' && req.method !== 'HEAD') {
+      return next();
+    }
+    // the repeated headers are joined like the ones of fetch
+    const headers: Record<string, string> = {};
+    Object.entries(req.headers).forEach(([name, value]) => {
+      if (value !== undefined) {
+        headers[name] = Array.isArray(value) ? value.join(', ') : String(value);
+      }
     });
-    forkTypeChecker.runTypeCheckInChildProcess();
-  }
+    devMiddleware
+      .handle(req.method, req.url, headers)
+      .then((result) => {
+        if (!result) {
+          return next();
+        }
+        res.statusCode = result.status;
+        Object.entries(result.headers).forEach(([name, value]) => {
+          res.setHeader(name, value);
+        });
+        res.end(req.method === 'HEAD' ? undefined : result.body);
+      })
+      .catch(next);
+  }) as DevMiddleware;
+  middleware.upgrade = (req: any, socket: any, head: Buffer) => {
+    if (!req.url?.endsWith('/__/hmr-ws')) {
+      return;
+    }
+    const net = require('net');
+    const proxySocket = net.connect(devMiddleware.hmrPort, '127.0.0.1', () => {
+      const headers = Object.entries(req.headers)
+        .map(([name, value]) => `${name}: ${value}`)
+        .join('\r\n');
+      proxySocket.write(
+        `${req.method} /__/hmr-ws HTTP/${req.httpVersion}\r\n${headers}\r\n\r\n`,
+      );
+      proxySocket.write(head);
+      proxySocket.pipe(socket);
+      socket.pipe(proxySocket);
+    });
+    proxySocket.on('error', () => socket.destroy());
+    socket.on('error', () => proxySocket.destroy());
+  };
+  return middleware;
 }
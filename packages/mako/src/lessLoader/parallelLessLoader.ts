@@ -5,7 +5,7 @@ import { LessLoaderOpts } from '.';
 export const createParallelLoader = () =>
   new Piscina<
     { filename: string; opts: LessLoaderOpts },
-    { content: string; type: 'css' }
+    { content: string; type: 'css'; dependencies: string[] }
   >({
     filename: path.resolve(__dirname + '/render.js'),
     idleTimeout: 30000,
@@ -5,7 +5,7 @@ import { LessLoaderOpts } from '.';
 module.exports = async function render(param: {
   filename: string;
   opts: LessLoaderOpts;
-}): Promise<{ content: string; type: 'css' }> {
+}): Promise<{ content: string; type: 'css'; dependencies: string[] }> {
   const { modifyVars, globalVars, math, sourceMap, plugins } = param.opts;
   const input = fs.readFileSync(param.filename, 'utf-8');
 
@@ -34,5 +34,6 @@ module.exports = async function render(param: {
       throw new Error(err.toString());
     });
 
-  return { content: result.css, type: 'css' };
+  // the imported files are rebuilt with the file in watch mode
+  return { content: result.css, type: 'css', dependencies: result.imports };
 };
@@ -1,9 +1,10 @@
+import { fileURLToPath } from 'url';
 import { type Options } from 'sass';
 
 async function render(param: {
   filename: string;
   opts: Options<'async'> & { resources: string[] };
-}): Promise<{ content: string; type: 'css' }> {
+}): Promise<{ content: string; type: 'css'; dependencies: string[] }> {
   let sass;
   try {
     sass = require('sass');
@@ -17,7 +18,12 @@ async function render(param: {
     .catch((err: any) => {
       throw new Error(err.toString());
     });
-  return { content: result.css, type: 'css' };
+  // the partials are rebuilt with the file in watch mode
+  const dependencies = result.loadedUrls
+    .filter((url: URL) => url.protocol === 'file:')
+    .map((url: URL) => fileURLToPath(url))
+    .filter((file: string) => file !== param.filename);
+  return { content: result.css, type: 'css', dependencies };
 }
 
 export { render };
@@ -0,0 +1,30 @@
+import url from 'url';
+
+export interface SvelteLoaderOpts {
+  root: string;
+  isProduction: boolean;
+  hot: boolean;
+  compilerOptions?: Record<string, any>;
+}
+
+function svelteLoader(opts: SvelteLoaderOpts) {
+  return {
+    render: async (filePath: string) => {
+      let filename = '';
+      let query: url.UrlWithParsedQuery['query'] = {};
+      try {
+        const parsed = url.parse(filePath, true);
+        filename = decodeURIComponent(parsed.pathname || '');
+        query = parsed.query;
+      } catch (e) {
+        return;
+      }
+      if (filename?.endsWith('.svelte')) {
+        const { render } = require('./render');
+        return render({ filename, query, opts });
+      }
+    },
+  };
+}
+
+export { svelteLoader };
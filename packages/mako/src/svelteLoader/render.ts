@@ -0,0 +1,99 @@
+import fs from 'fs';
+import path from 'path';
+import { type SvelteLoaderOpts } from './index';
+
+interface Compiled {
+  source: string;
+  js: string;
+  css: string | null;
+}
+
+// the js module and the css sub-module of the same .svelte file share the compile result
+const cache = new Map<string, Compiled>();
+let makeHot: Function | null | undefined;
+
+function getCompiler() {
+  try {
+    return require('svelte/compiler');
+  } catch (err) {
+    throw new Error(
+      'The "svelte" package is not installed. Please run "npm install svelte" to install it.',
+    );
+  }
+}
+
+function getMakeHot(): Function | null {
+  if (makeHot === undefined) {
+    try {
+      const { createMakeHot } = require('svelte-hmr');
+      // use `module.hot` since the modules are wrapped in commonjs style by mako
+      makeHot = createMakeHot({ walk: getCompiler().walk, meta: 'module' });
+    } catch (err) {
+      console.warn(
+        'The "svelte-hmr" package is not installed, hot update of svelte components is disabled. Please run "npm install svelte-hmr" to enable it.',
+      );
+      makeHot = null;
+    }
+  }
+  return makeHot ?? null;
+}
+
+function compile(filename: string, opts: SvelteLoaderOpts): Compiled {
+  const source = fs.readFileSync(filename, 'utf-8');
+  const cached = cache.get(filename);
+  if (cached && cached.source === source) {
+    return cached;
+  }
+  const compileOptions = {
+    ...opts.compilerOptions,
+    filename,
+    dev: !opts.isProduction,
+    // the css is emitted as a sub-module and goes through the css pipeline of mako
+    css: 'external',
+  };
+  let result;
+  try {
+    result = getCompiler().compile(source, compileOptions);
+  } catch (err: any) {
+    throw new Error(`Failed to compile ${filename}:\n${err.message}`);
+  }
+  result.warnings?.forEach((warning: any) => {
+    console.warn(`Warning ${filename}: ${warning.message}`);
+  });
+  let js = result.js.code;
+  const hot = opts.hot ? getMakeHot() : null;
+  if (hot) {
+    js = hot({
+      id: filename,
+      compiledCode: js,
+      hotOptions: { injectCss: false },
+      compiled: result,
+      originalCode: source,
+      compileOptions,
+    });
+  }
+  const compiled = { source, js, css: result.css?.code || null };
+  cache.set(filename, compiled);
+  return compiled;
+}
+
+async function render(param: {
+  filename: string;
+  query: Record<string, string | string[] | undefined>;
+  opts: SvelteLoaderOpts;
+}): Promise<{ content: string; type: 'js' | 'css' }> {
+  const { filename, query, opts } = param;
+  const compiled = compile(filename, opts);
+  if (query.svelte !== undefined && query.type === 'style') {
+    return { content: compiled.css || '', type: 'css' };
+  }
+  let content = compiled.js;
+  if (compiled.css) {
+    content += `\nimport ${JSON.stringify(
+      `${filename}?svelte&type=style&lang.css`,
+    )};`;
+  }
+  return { content, type: 'js' };
+}
+
+export { render };
@@ -0,0 +1,28 @@
+import url from 'url';
+
+export interface VueLoaderOpts {
+  root: string;
+  isProduction: boolean;
+}
+
+function vueLoader(opts: VueLoaderOpts) {
+  return {
+    render: async (filePath: string) => {
+      let filename = '';
+      let query: url.UrlWithParsedQuery['query'] = {};
+      try {
+        const parsed = url.parse(filePath, true);
+        filename = decodeURIComponent(parsed.pathname || '');
+        query = parsed.query;
+      } catch (e) {
+        return;
+      }
+      if (filename?.endsWith('.vue')) {
+        const { render } = require('./render');
+        return render({ filename, query, opts });
+      }
+    },
+  };
+}
+
+export { vueLoader };
@@ -0,0 +1,181 @@
+import crypto from 'crypto';
+import fs from 'fs';
+import path from 'path';
+import { type VueLoaderOpts } from './index';
+
+type LoadResult = {
+  content: string;
+  type: 'js' | 'jsx' | 'ts' | 'tsx' | 'css';
+};
+
+interface Compiled {
+  source: string;
+  descriptor: any;
+  script: any;
+}
+
+// the sub-modules of the same .vue file share the parsed descriptor
+const cache = new Map<string, Compiled>();
+
+function getCompiler() {
+  try {
+    return require('@vue/compiler-sfc');
+  } catch (err) {
+    throw new Error(
+      'The "@vue/compiler-sfc" package is not installed. Please run "npm install @vue/compiler-sfc" to install it.',
+    );
+  }
+}
+
+function scopeId(filename: string, opts: VueLoaderOpts) {
+  // use the relative path so that the ids are stable across machines
+  const relativePath = path.relative(opts.root, filename).replace(/\\/g, '/');
+  return crypto
+    .createHash('md5')
+    .update(relativePath)
+    .digest('hex')
+    .slice(0, 8);
+}
+
+function compile(filename: string, opts: VueLoaderOpts): Compiled {
+  const source = fs.readFileSync(filename, 'utf-8');
+  const cached = cache.get(filename);
+  if (cached && cached.source === source) {
+    return cached;
+  }
+  const compiler = getCompiler();
+  const { descriptor, errors } = compiler.parse(source, {
+    filename,
+    sourceMap: false,
+  });
+  if (errors.length) {
+    throw new Error(formatErrors(filename, errors));
+  }
+  const id = scopeId(filename, opts);
+  const script =
+    descriptor.script || descriptor.scriptSetup
+      ? compiler.compileScript(descriptor, {
+          id,
+          isProd: opts.isProduction,
+          inlineTemplate: false,
+        })
+      : null;
+  const compiled = { source, descriptor, script };
+  cache.set(filename, compiled);
+  return compiled;
+}
+
+function formatErrors(filename: string, errors: any[]) {
+  return [
+    `Failed to compile ${filename}:`,
+    ...errors.map((e) => (typeof e === 'string' ? e : e.message)),
+  ].join('\n');
+}
+
+function scriptLang(descriptor: any): 'js' | 'jsx' | 'ts' | 'tsx' {
+  const lang = (descriptor.scriptSetup || descriptor.script)?.lang;
+  return ['jsx', 'ts', 'tsx'].includes(lang) ? lang : 'js';
+}
+
+function renderMain(filename: string, compiled: Compiled, opts: VueLoaderOpts) {
+  const { descriptor } = compiled;
+  const id = scopeId(filename, opts);
+  const request = JSON.stringify;
+  const code: string[] = [];
+  if (compiled.script) {
+    code.push(
+      `import script from ${request(
+        `${filename}?vue&type=script&lang.${scriptLang(descriptor)}`,
+      )};`,
+    );
+  } else {
+    code.push('const script = {};');
+  }
+  if (descriptor.template) {
+    code.push(
+      `import { render } from ${request(
+        `${filename}?vue&type=template&lang.js`,
+      )};`,
+      'script.render = render;',
+    );
+  }
+  descriptor.styles.forEach((style: any, index: number) => {
+    if (style.module) {
+      throw new Error(
+        `<style module> is not supported yet, use <style scoped> instead in ${filename}`,
+      );
+    }
+    code.push(
+      `import ${request(`${filename}?vue&type=style&index=${index}&lang.css`)};`,
+    );
+  });
+  if (descriptor.styles.some((style: any) => style.scoped)) {
+    code.push(`script.__scopeId = ${request(`data-v-${id}`)};`);
+  }
+  if (!opts.isProduction) {
+    code.push(
+      `script.__file = ${request(path.relative(opts.root, filename))};`,
+    );
+  }
+  code.push('export default script;');
+  return code.join('\n');
+}
+
+async function render(param: {
+  filename: string;
+  query: Record<string, string | string[] | undefined>;
+  opts: VueLoaderOpts;
+}): Promise<LoadResult> {
+  const { filename, query, opts } = param;
+  const compiled = compile(filename, opts);
+  const { descriptor } = compiled;
+  const id = scopeId(filename, opts);
+
+  if (query.vue === undefined) {
+    return { content: renderMain(filename, compiled, opts), type: 'js' };
+  }
+
+  switch (query.type) {
+    case 'script':
+      return { content: compiled.script.content, type: scriptLang(descriptor) };
+    case 'template': {
+      const hasScoped = descriptor.styles.some((style: any) => style.scoped);
+      const result = getCompiler().compileTemplate({
+        source: descriptor.template.content,
+        filename,
+        id,
+        scoped: hasScoped,
+        slotted: descriptor.slotted,
+        isProd: opts.isProduction,
+        compilerOptions: {
+          scopeId: hasScoped ? `data-v-${id}` : undefined,
+          bindingMetadata: compiled.script?.bindings,
+        },
+      });
+      if (result.errors.length) {
+        throw new Error(formatErrors(filename, result.errors));
+      }
+      return { content: result.code, type: 'js' };
+    }
+    case 'style': {
+      const style = descriptor.styles[Number(query.index)];
+      const result = await getCompiler().compileStyleAsync({
+        source: style.content,
+        filename,
+        id: `data-v-${id}`,
+        scoped: style.scoped,
+        isProd: opts.isProduction,
+        // less, sass, scss and stylus are supported if the preprocessor is installed
+        preprocessLang: style.lang,
+      });
+      if (result.errors.length) {
+        throw new Error(formatErrors(filename, result.errors));
+      }
+      return { content: result.code, type: 'css' };
+    }
+    default:
+      throw new Error(`Unknown vue block type ${query.type} in ${filename}`);
+  }
+}
+
+export { render };
@@ -690,6 +690,50 @@ ReactDOM.createRoot(document.getElementById("root")!).render(<><App /><section>{
   );
 });
 
+runTest('js: entry > js, log the full reload reason', async () => {
+  write(
+    normalizeFiles({
+      '/src/util.ts': `
+export function foo() {
+  return 'foo';
+}
+`,
+      '/src/index.tsx': `
+import React from 'react';
+import ReactDOM from "react-dom/client";
+import { foo } from './util';
+function App() {
+return <div>App {foo()}</div>;
+}
+ReactDOM.createRoot(document.getElementById("root")!).render(<><App /><section>{Math.random()}</section></>);
+  `,
+    }),
+  );
+  await startMakoDevServer();
+  await delay(DELAY_TIME);
+  const { browser, page } = await startBrowser();
+  const warnings = [];
+  page.on('console', (msg) => {
+    if (msg.type() === 'warning') warnings.push(msg.text());
+  });
+  write({
+    '/src/util.ts': `
+export function foo() {
+  return 'bar';
+}
+    `,
+  });
+  await delay(DELAY_TIME);
+  const thisResult = normalizeHtml(await getRootHtml(page));
+  assert.equal(thisResult.html, '<div>App bar</div>', 'Second render');
+  assert.match(
+    warnings.join('\n'),
+    /\[HMR\] Full reload, the update of .*util\.ts -> .*index\.tsx reaches the entry/,
+    'should log the full reload reason',
+  );
+  await cleanup({ process, browser });
+});
+
 runTest('js: entry > react component', async () => {
   await commonTest(
     {